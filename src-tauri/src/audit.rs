@@ -0,0 +1,159 @@
+use rusqlite::{params_from_iter, types::Value as SqlValue};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::path::Path;
+use std::time::Instant;
+use tauri::{command, AppHandle};
+use tracing::{instrument, warn};
+
+use crate::db::{self, PooledConnection};
+use crate::{events, settings};
+
+#[derive(Debug, Serialize)]
+pub struct ActivityEntry {
+    pub id: i64,
+    pub project: String,
+    /// Command that ran, e.g. "git_commit_and_push" or "dvc_push"
+    pub operation: String,
+    pub params: Value,
+    /// "success" or "failure"
+    pub outcome: String,
+    pub error: Option<String>,
+    /// RFC 3339 start time
+    pub started_at: String,
+    pub duration_ms: i64,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct ActivityFilter {
+    pub operation: Option<String>,
+    pub outcome: Option<String>,
+    /// Only entries started at or after this RFC 3339 time
+    pub since: Option<String>,
+    pub until: Option<String>,
+    /// Defaults to 200 entries, newest first
+    pub limit: Option<u32>,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::ACTIVITY_LOG_SCHEMA)
+}
+
+/// Project key the log is kept under: the repository root when `path` is
+/// inside one, so entries from different paths of a repo end up together
+fn project_key(path: &str) -> String {
+    let root = git2::Repository::discover(path)
+        .ok()
+        .and_then(|repo| repo.workdir().map(Path::to_path_buf));
+    settings::project_key(root.as_deref().unwrap_or(Path::new(path)))
+}
+
+fn insert(
+    app_handle: &AppHandle,
+    project: &str,
+    operation: &str,
+    params: &Value,
+    error: Option<&str>,
+    started_at: &str,
+    duration_ms: i64,
+) -> Result<(), String> {
+    let conn = open_connection(app_handle)?;
+    conn.execute(
+        "INSERT INTO activity_log (project, operation, params, outcome, error, started_at, duration_ms)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        rusqlite::params![
+            project_key(project),
+            operation,
+            params.to_string(),
+            if error.is_some() { "failure" } else { "success" },
+            error,
+            started_at,
+            duration_ms
+        ],
+    )
+    .map_err(|e| format!("Failed to record activity: {}", e))?;
+    Ok(())
+}
+
+/// Runs a mutating operation and records it in the activity log with its
+/// parameters and outcome, and emits the repository change events for it.
+/// Failing to write the log never fails the operation.
+pub fn track<T>(
+    app_handle: &AppHandle,
+    project: &str,
+    operation: &str,
+    params: Value,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let started = Instant::now();
+    let result = events::track(app_handle, project, operation, run);
+
+    let error = result.as_ref().err().map(String::as_str);
+    let duration_ms = started.elapsed().as_millis() as i64;
+    if let Err(e) = insert(
+        app_handle,
+        project,
+        operation,
+        &params,
+        error,
+        &started_at,
+        duration_ms,
+    ) {
+        warn!("{}", e);
+    }
+    result
+}
+
+/// Operations the app ran for a project, newest first
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_activity_log(
+    app_handle: AppHandle,
+    project: String,
+    filters: Option<ActivityFilter>,
+) -> Result<Vec<ActivityEntry>, String> {
+    let filters = filters.unwrap_or_default();
+    let mut sql = String::from(
+        "SELECT id, project, operation, params, outcome, error, started_at, duration_ms
+         FROM activity_log WHERE project = ?",
+    );
+    let mut values = vec![SqlValue::Text(project_key(&project))];
+    for (column, op, value) in [
+        ("operation", "=", filters.operation),
+        ("outcome", "=", filters.outcome),
+        ("started_at", ">=", filters.since),
+        ("started_at", "<=", filters.until),
+    ] {
+        if let Some(value) = value {
+            sql.push_str(&format!(" AND {} {} ?", column, op));
+            values.push(SqlValue::Text(value));
+        }
+    }
+    sql.push_str(" ORDER BY started_at DESC, id DESC LIMIT ?");
+    values.push(SqlValue::Integer(filters.limit.unwrap_or(200) as i64));
+
+    let conn = open_connection(&app_handle)?;
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to query activity log: {}", e))?;
+    let entries = stmt
+        .query_map(params_from_iter(values), |row| {
+            let params: String = row.get(3)?;
+            Ok(ActivityEntry {
+                id: row.get(0)?,
+                project: row.get(1)?,
+                operation: row.get(2)?,
+                params: serde_json::from_str(&params).unwrap_or(Value::Null),
+                outcome: row.get(4)?,
+                error: row.get(5)?,
+                started_at: row.get(6)?,
+                duration_ms: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query activity log: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read activity log: {}", e))?;
+    Ok(entries)
+}
@@ -0,0 +1,286 @@
+use git2::{BranchType, FetchOptions, RemoteCallbacks, Repository};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tracing::{instrument, warn};
+
+use crate::branch_compare::{self, DataChange};
+use crate::storage::{self, TransferConfig};
+use crate::{dvc_config, settings, ssh, transfer};
+
+pub const REMOTE_CHANGES_EVENT: &str = "git://remote-changes";
+
+/// How often a scheduler thread checks whether it was stopped
+const STOP_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Background fetch settings of a repository
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoFetchSettings {
+    pub enabled: bool,
+    pub interval_minutes: u64,
+}
+
+impl Default for AutoFetchSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_minutes: 15,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RemoteChanges {
+    pub repo_path: String,
+    pub branch: String,
+    /// "<remote>/<branch>" the branch tracks, `None` without an upstream
+    pub upstream: Option<String>,
+    /// Local commits not on the upstream
+    pub ahead: usize,
+    /// Upstream commits not merged locally
+    pub behind: usize,
+    /// DVC outputs whose version on the upstream differs from HEAD
+    pub new_data: Vec<DataChange>,
+    /// Paths of `new_data` whose objects are already on the DVC remote
+    pub data_on_remote: Vec<String>,
+    /// Why the DVC remote could not be checked, e.g. no remote configured
+    pub dvc_remote_error: Option<String>,
+    pub checked_at: String,
+}
+
+impl RemoteChanges {
+    pub fn has_changes(&self) -> bool {
+        self.behind > 0 || !self.new_data.is_empty()
+    }
+}
+
+/// Running scheduler threads per repository, each with its stop flag
+#[derive(Default)]
+pub struct AutoFetchScheduler {
+    running: HashMap<String, Arc<AtomicBool>>,
+}
+
+impl AutoFetchScheduler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn stop(&mut self, repo_key: &str) {
+        if let Some(stop) = self.running.remove(repo_key) {
+            stop.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// Replaces the repository's scheduler thread according to `settings`
+    fn apply(&mut self, app_handle: &AppHandle, repo_root: &Path, settings: &AutoFetchSettings) {
+        let repo_key = settings::project_key(repo_root);
+        self.stop(&repo_key);
+        if !settings.enabled {
+            return;
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        self.running.insert(repo_key, stop.clone());
+        let app_handle = app_handle.clone();
+        let repo_root = repo_root.to_path_buf();
+        let interval = Duration::from_secs(settings.interval_minutes.max(1) * 60);
+        thread::spawn(move || run_schedule(app_handle, repo_root, interval, stop));
+    }
+}
+
+pub type AutoFetchState = Mutex<AutoFetchScheduler>;
+
+fn run_schedule(
+    app_handle: AppHandle,
+    repo_root: PathBuf,
+    interval: Duration,
+    stop: Arc<AtomicBool>,
+) {
+    loop {
+        let started = Instant::now();
+        while started.elapsed() < interval {
+            if stop.load(Ordering::Relaxed) {
+                return;
+            }
+            thread::sleep(STOP_POLL_INTERVAL);
+        }
+
+        match check(&app_handle, &repo_root) {
+            Ok(changes) if changes.has_changes() => emit_changes(&app_handle, &changes),
+            Ok(_) => {}
+            Err(e) => warn!("Background fetch of {} failed: {}", repo_root.display(), e),
+        }
+    }
+}
+
+fn emit_changes(app_handle: &AppHandle, changes: &RemoteChanges) {
+    if let Err(e) = app_handle.emit(REMOTE_CHANGES_EVENT, changes) {
+        warn!("Failed to emit remote changes: {}", e);
+    }
+}
+
+/// Starts the schedulers of all repositories with auto-fetch enabled
+pub fn start_all(app_handle: &AppHandle) -> Result<(), String> {
+    let state = app_handle.state::<AutoFetchState>();
+    let mut scheduler = state
+        .lock()
+        .map_err(|_| "Failed to lock auto-fetch scheduler".to_string())?;
+    for (repo_key, project) in settings::load_all(app_handle)? {
+        if project.auto_fetch.enabled {
+            scheduler.apply(app_handle, Path::new(&repo_key), &project.auto_fetch);
+        }
+    }
+    Ok(())
+}
+
+/// Checks which objects of the changed outputs the DVC remote already has
+fn objects_on_remote(repo_root: &Path, changes: &[DataChange]) -> Result<Vec<String>, String> {
+    let remote = dvc_config::remote(repo_root, None)?;
+    let storage = storage::open_remote(&remote, &TransferConfig::default())?;
+
+    let mut available = Vec::new();
+    for change in changes {
+        let Some(md5) = change.md5_b.as_deref() else {
+            continue;
+        };
+        if storage.exists(&storage::object_key(md5))?
+            || storage.exists(&storage::legacy_object_key(md5))?
+        {
+            available.push(change.path.clone());
+        }
+    }
+    Ok(available)
+}
+
+/// Fetches the upstream of the current branch and compares it with HEAD
+fn check(app_handle: &AppHandle, repo_root: &Path) -> Result<RemoteChanges, String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+    let head_commit = head
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    let mut changes = RemoteChanges {
+        repo_path: repo_root.to_string_lossy().to_string(),
+        branch: branch_name.clone(),
+        upstream: None,
+        ahead: 0,
+        behind: 0,
+        new_data: Vec::new(),
+        data_on_remote: Vec::new(),
+        dvc_remote_error: None,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let Ok(upstream) = repo
+        .find_branch(&branch_name, BranchType::Local)
+        .and_then(|branch| branch.upstream())
+    else {
+        return Ok(changes);
+    };
+    let upstream_name = upstream
+        .name()
+        .ok()
+        .flatten()
+        .ok_or("Invalid upstream name")?
+        .to_string();
+    let remote_name = repo
+        .branch_upstream_remote(&format!("refs/heads/{}", branch_name))
+        .map_err(|e| format!("Failed to get upstream remote: {}", e))?;
+    let remote_name = remote_name.as_str().ok_or("Invalid upstream remote name")?;
+
+    // Fetch quietly: background fetches don't report transfer progress
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(ssh::credential_callback(app_handle));
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(callbacks);
+    repo.find_remote(remote_name)
+        .map_err(|e| format!("Failed to find remote: {}", e))?
+        .fetch(&[] as &[&str], Some(&mut fetch_options), None)
+        .map_err(|e| format!("Failed to fetch: {}", e))?;
+
+    // Re-read the upstream now that the fetch may have moved it
+    let upstream_commit = repo
+        .revparse_single(&format!("refs/remotes/{}", upstream_name))
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find upstream commit: {}", e))?;
+    let (ahead, behind) = repo
+        .graph_ahead_behind(head_commit.id(), upstream_commit.id())
+        .map_err(|e| format!("Failed to compare with upstream: {}", e))?;
+
+    let head_tree = head_commit
+        .tree()
+        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+    let upstream_tree = upstream_commit
+        .tree()
+        .map_err(|e| format!("Failed to get upstream tree: {}", e))?;
+    let new_data = if behind > 0 {
+        branch_compare::compare_trees(&repo, &head_tree, &upstream_tree)?
+            .into_iter()
+            .filter(|change| change.status != "removed")
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    if !new_data.is_empty() {
+        match objects_on_remote(repo_root, &new_data) {
+            Ok(paths) => changes.data_on_remote = paths,
+            Err(e) => changes.dvc_remote_error = Some(e),
+        }
+    }
+
+    changes.upstream = Some(upstream_name);
+    changes.ahead = ahead;
+    changes.behind = behind;
+    changes.new_data = new_data;
+    Ok(changes)
+}
+
+/// Saves the background fetch settings of a repository and restarts its
+/// scheduler with them
+#[command]
+#[instrument(skip(app_handle, scheduler), err(Debug))]
+pub fn set_auto_fetch(
+    app_handle: AppHandle,
+    scheduler: State<'_, AutoFetchState>,
+    repo_path: String,
+    auto_fetch: AutoFetchSettings,
+) -> Result<AutoFetchSettings, String> {
+    if auto_fetch.interval_minutes == 0 {
+        return Err("The fetch interval must be at least one minute".to_string());
+    }
+
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let saved = settings::update(&app_handle, &repo_root, |settings| {
+        settings.auto_fetch = auto_fetch;
+    })?;
+    scheduler
+        .lock()
+        .map_err(|_| "Failed to lock auto-fetch scheduler".to_string())?
+        .apply(&app_handle, &repo_root, &saved.auto_fetch);
+    Ok(saved.auto_fetch)
+}
+
+/// Fetches now and reports how the upstream differs from the local branch,
+/// also emitting the result as a remote changes event
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn check_for_remote_changes(
+    app_handle: AppHandle,
+    repo_path: String,
+) -> Result<RemoteChanges, String> {
+    let changes = check(&app_handle, &transfer::repo_root(&repo_path)?)?;
+    emit_changes(&app_handle, &changes);
+    Ok(changes)
+}
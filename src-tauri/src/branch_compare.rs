@@ -0,0 +1,150 @@
+use git2::{ObjectType, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::command;
+use tracing::instrument;
+
+use crate::dvcfile::{self, DvcOut};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DataChange {
+    /// Data path relative to the repository root
+    pub path: String,
+    /// "added", "removed" or "changed", going from `branch_a` to `branch_b`
+    pub status: String,
+    pub is_dir: bool,
+    pub md5_a: Option<String>,
+    pub md5_b: Option<String>,
+    pub size_a: Option<u64>,
+    pub size_b: Option<u64>,
+    /// `size_b - size_a` with a missing side counting as zero; `None` when a
+    /// pointer doesn't record its size
+    pub size_delta: Option<i64>,
+    pub nfiles_a: Option<u64>,
+    pub nfiles_b: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchDataComparison {
+    pub branch_a: String,
+    pub branch_b: String,
+    pub changes: Vec<DataChange>,
+    /// Sum of the known size deltas
+    pub total_size_delta: i64,
+}
+
+pub(crate) fn branch_tree<'a>(repo: &'a Repository, branch: &str) -> Result<Tree<'a>, String> {
+    repo.revparse_single(branch)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| format!("Failed to find branch {}: {}", branch, e))
+}
+
+/// Outputs of every `.dvc` pointer in a tree, keyed by repository-relative
+/// data path. Only pointer blobs are read, so no data is needed.
+pub(crate) fn tracked_outputs(
+    repo: &Repository,
+    tree: &Tree,
+) -> Result<BTreeMap<String, DvcOut>, String> {
+    let mut pointers = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(ObjectType::Blob)
+            && entry.name().is_some_and(|name| name.ends_with(".dvc"))
+        {
+            pointers.push((root.to_string(), entry.id()));
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(|e| format!("Failed to walk tree: {}", e))?;
+
+    let mut outputs = BTreeMap::new();
+    for (root, id) in pointers {
+        let blob = repo
+            .find_blob(id)
+            .map_err(|e| format!("Failed to read pointer {}: {}", id, e))?;
+        // Skip pointers that don't parse, e.g. ones with conflict markers
+        let Ok(dvc_file) = dvcfile::parse_dvc_file(&String::from_utf8_lossy(blob.content())) else {
+            continue;
+        };
+        for out in dvc_file.outs {
+            let path = dvcfile::to_git_path(&Path::new(&root).join(&out.path));
+            outputs.insert(path, out);
+        }
+    }
+    Ok(outputs)
+}
+
+fn change(path: &str, status: &str, a: Option<&DvcOut>, b: Option<&DvcOut>) -> DataChange {
+    let size_a = a.and_then(|out| out.size);
+    let size_b = b.and_then(|out| out.size);
+    let size_delta = match (a, b) {
+        (Some(_), Some(_)) => size_a.zip(size_b).map(|(a, b)| b as i64 - a as i64),
+        (None, Some(_)) => size_b.map(|b| b as i64),
+        (Some(_), None) => size_a.map(|a| -(a as i64)),
+        (None, None) => None,
+    };
+
+    DataChange {
+        path: path.to_string(),
+        status: status.to_string(),
+        is_dir: a.or(b).is_some_and(DvcOut::is_dir),
+        md5_a: a.and_then(|out| out.md5.clone()),
+        md5_b: b.and_then(|out| out.md5.clone()),
+        size_a,
+        size_b,
+        size_delta,
+        nfiles_a: a.and_then(|out| out.nfiles),
+        nfiles_b: b.and_then(|out| out.nfiles),
+    }
+}
+
+/// DVC outputs that differ between two trees, sorted by path
+pub(crate) fn compare_trees(
+    repo: &Repository,
+    tree_a: &Tree,
+    tree_b: &Tree,
+) -> Result<Vec<DataChange>, String> {
+    let outputs_a = tracked_outputs(repo, tree_a)?;
+    let outputs_b = tracked_outputs(repo, tree_b)?;
+
+    let mut changes = Vec::new();
+    for (path, a) in &outputs_a {
+        match outputs_b.get(path) {
+            None => changes.push(change(path, "removed", Some(a), None)),
+            Some(b) if a.md5 != b.md5 => changes.push(change(path, "changed", Some(a), Some(b))),
+            Some(_) => {}
+        }
+    }
+    for (path, b) in &outputs_b {
+        if !outputs_a.contains_key(path) {
+            changes.push(change(path, "added", None, Some(b)));
+        }
+    }
+    changes.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(changes)
+}
+
+/// DVC-tracked data that differs between two branches, read from the pointer
+/// files alone so nothing has to be downloaded
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn compare_branches_data(
+    repo_path: String,
+    branch_a: String,
+    branch_b: String,
+) -> Result<BranchDataComparison, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let changes = compare_trees(
+        &repo,
+        &branch_tree(&repo, &branch_a)?,
+        &branch_tree(&repo, &branch_b)?,
+    )?;
+
+    Ok(BranchDataComparison {
+        total_size_delta: changes.iter().filter_map(|c| c.size_delta).sum(),
+        branch_a,
+        branch_b,
+        changes,
+    })
+}
@@ -0,0 +1,216 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::command;
+use tracing::{debug, instrument};
+
+use crate::dvc_config::{self, DvcConfig};
+use crate::dvcfile;
+use crate::transfer;
+
+/// How a cache object is placed in the workspace, matching DVC's `cache.type`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkType {
+    Reflink,
+    Hardlink,
+    Symlink,
+    Copy,
+}
+
+/// Preference order used when picking link types automatically. Reflinks are
+/// as safe as copies; hard and symbolic links share data with the cache.
+const PREFERENCE: [LinkType; 4] = [
+    LinkType::Reflink,
+    LinkType::Hardlink,
+    LinkType::Symlink,
+    LinkType::Copy,
+];
+
+impl LinkType {
+    fn as_str(self) -> &'static str {
+        match self {
+            LinkType::Reflink => "reflink",
+            LinkType::Hardlink => "hardlink",
+            LinkType::Symlink => "symlink",
+            LinkType::Copy => "copy",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        PREFERENCE.into_iter().find(|t| t.as_str() == value)
+    }
+
+    /// Whether the workspace file shares its data with the cache object, so
+    /// editing it in place would corrupt the cache
+    fn shares_data(self) -> bool {
+        matches!(self, LinkType::Hardlink | LinkType::Symlink)
+    }
+
+    fn create(self, object: &Path, dest: &Path) -> std::io::Result<()> {
+        match self {
+            LinkType::Reflink => reflink_copy::reflink(object, dest),
+            LinkType::Hardlink => fs::hard_link(object, dest),
+            LinkType::Symlink => symlink(object, dest),
+            LinkType::Copy => fs::copy(object, dest).map(|_| ()),
+        }
+    }
+}
+
+#[cfg(unix)]
+fn symlink(object: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(object, dest)
+}
+
+#[cfg(windows)]
+fn symlink(object: &Path, dest: &Path) -> std::io::Result<()> {
+    std::os::windows::fs::symlink_file(object, dest)
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheLinkSettings {
+    /// Link types tried in order during checkout, as saved in `.dvc/config`
+    pub link_types: Vec<LinkType>,
+    /// Link types that work between the cache and the workspace
+    pub supported: Vec<LinkType>,
+}
+
+/// Link types configured for a repository, copy when nothing is set
+pub fn configured(repo_root: &Path) -> Vec<LinkType> {
+    let link_types = dvc_config::load(repo_root)
+        .ok()
+        .and_then(|config| config.get("cache", "type").map(str::to_string))
+        .map(|value| {
+            value
+                .split(',')
+                .filter_map(|t| LinkType::parse(t.trim()))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    if link_types.is_empty() {
+        vec![LinkType::Copy]
+    } else {
+        link_types
+    }
+}
+
+/// Places `object` at `dest` with the first link type that works, falling
+/// back to a copy. Objects shared through links are made read-only so edits
+/// in the workspace cannot change the cache.
+pub fn link(object: &Path, dest: &Path, link_types: &[LinkType]) -> Result<(), String> {
+    for &link_type in link_types {
+        if link_type.shares_data() {
+            protect(object)?;
+        }
+        match link_type.create(object, dest) {
+            Ok(()) => return Ok(()),
+            Err(e) => debug!(?link_type, error = %e, "Link type failed, trying the next one"),
+        }
+    }
+    fs::copy(object, dest)
+        .map(|_| ())
+        .map_err(|e| format!("Failed to copy {}: {}", dest.display(), e))
+}
+
+fn protect(object: &Path) -> Result<(), String> {
+    let mut permissions = fs::metadata(object)
+        .map_err(|e| format!("Failed to stat {}: {}", object.display(), e))?
+        .permissions();
+    if !permissions.readonly() {
+        permissions.set_readonly(true);
+        fs::set_permissions(object, permissions)
+            .map_err(|e| format!("Failed to protect {}: {}", object.display(), e))?;
+    }
+    Ok(())
+}
+
+/// Tries every link type between the cache and the workspace with a probe
+/// file, since support depends on both filesystems
+pub fn detect_supported(repo_root: &Path) -> Result<Vec<LinkType>, String> {
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create {}: {}", cache_dir.display(), e))?;
+    let probe = cache_dir.join(format!(
+        ".fenn-link-probe-{}",
+        uuid::Uuid::new_v4().simple()
+    ));
+    fs::write(&probe, b"probe").map_err(|e| format!("Failed to write link probe: {}", e))?;
+
+    let supported = PREFERENCE
+        .into_iter()
+        .filter(|link_type| {
+            let dest = repo_root.join(format!(
+                ".fenn-link-probe-{}",
+                uuid::Uuid::new_v4().simple()
+            ));
+            let works = link_type.create(&probe, &dest).is_ok();
+            let _ = fs::remove_file(&dest);
+            works
+        })
+        .collect();
+
+    let _ = fs::remove_file(&probe);
+    Ok(supported)
+}
+
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn get_cache_link_settings(repo_path: String) -> Result<CacheLinkSettings, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    Ok(CacheLinkSettings {
+        link_types: configured(&repo_root),
+        supported: detect_supported(&repo_root)?,
+    })
+}
+
+/// Saves the checkout link strategy in `.dvc/config`. Without a link type the
+/// best ones supported by the filesystem are chosen, with copy as a fallback.
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn set_cache_link_type(
+    repo_path: String,
+    link_type: Option<LinkType>,
+) -> Result<CacheLinkSettings, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let supported = detect_supported(&repo_root)?;
+
+    let link_types = match link_type {
+        Some(link_type) if !supported.contains(&link_type) => {
+            return Err(format!(
+                "{} links are not supported between the cache and the workspace",
+                link_type.as_str()
+            ));
+        }
+        Some(LinkType::Copy) => vec![LinkType::Copy],
+        Some(link_type) => vec![link_type, LinkType::Copy],
+        None => {
+            // The preferred supported link, with copy for files it fails on
+            let mut link_types = supported
+                .iter()
+                .copied()
+                .filter(|t| *t != LinkType::Copy)
+                .take(1)
+                .collect::<Vec<_>>();
+            link_types.push(LinkType::Copy);
+            link_types
+        }
+    };
+
+    let path = dvc_config::config_path(&repo_root);
+    let mut config = DvcConfig::read(&path)?;
+    config.set(
+        "cache",
+        "type",
+        &link_types
+            .iter()
+            .map(|t| t.as_str())
+            .collect::<Vec<_>>()
+            .join(","),
+    );
+    config.write(&path)?;
+
+    Ok(CacheLinkSettings {
+        link_types,
+        supported,
+    })
+}
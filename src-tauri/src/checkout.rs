@@ -0,0 +1,307 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+use crate::cache_link::{self, LinkType};
+use crate::dvc_compat::{self, DvcFormat};
+use crate::dvcfile::{self, DirManifestEntry, DvcOut};
+
+#[derive(Debug, Default, Serialize)]
+pub struct CheckoutSummary {
+    /// Files materialized that were missing from the workspace
+    pub added: Vec<String>,
+    /// Files replaced because their content differed from the pointer
+    pub modified: Vec<String>,
+    /// Files whose object is not in the local cache
+    pub missing_from_cache: Vec<String>,
+    /// Locally modified files left untouched because `force` was not set
+    pub skipped: Vec<String>,
+    pub unchanged: usize,
+}
+
+/// Workspace data brought in line with the pointers after HEAD moved
+#[derive(Debug, Default, Serialize)]
+pub struct DataSyncSummary {
+    pub checkout: CheckoutSummary,
+    /// Files of outputs no longer tracked, removed as their content is cached
+    pub removed: Vec<String>,
+    /// Files of outputs no longer tracked, kept as they were modified or
+    /// aren't cached
+    pub kept: Vec<String>,
+}
+
+/// The local cache of a repository, with the format deciding how workspace
+/// files are hashed to compare them with its objects
+struct Cache {
+    dir: PathBuf,
+    format: Option<DvcFormat>,
+}
+
+impl Cache {
+    fn of(repo_root: &Path) -> Self {
+        Self {
+            dir: dvcfile::cache_dir(repo_root),
+            format: dvc_compat::repo_format(repo_root),
+        }
+    }
+
+    /// Whether `path` holds the content of the object `md5`
+    fn matches(&self, path: &Path, md5: &str) -> Result<bool, String> {
+        Ok(dvcfile::hash_file_for(path, self.format)? == md5)
+    }
+}
+
+/// Links or copies a cache object into the workspace through a temporary file
+/// so a failed copy never leaves a truncated data file behind
+pub(crate) fn materialize(
+    object: &Path,
+    dest: &Path,
+    link_types: &[LinkType],
+) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let temp = dest.with_file_name(format!(
+        ".{}.fenn-tmp",
+        dest.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    if temp.exists() {
+        fs::remove_file(&temp)
+            .map_err(|e| format!("Failed to remove {}: {}", temp.display(), e))?;
+    }
+    cache_link::link(object, &temp, link_types)?;
+    if dest.exists() {
+        fs::remove_file(dest)
+            .map_err(|e| format!("Failed to replace {}: {}", dest.display(), e))?;
+    }
+    fs::rename(&temp, dest).map_err(|e| format!("Failed to move {}: {}", dest.display(), e))
+}
+
+fn checkout_file(
+    repo_root: &Path,
+    cache: &Cache,
+    md5: &str,
+    dest: &Path,
+    force: bool,
+    link_types: &[LinkType],
+    summary: &mut CheckoutSummary,
+) -> Result<(), String> {
+    let relative = dvcfile::to_git_path(dest.strip_prefix(repo_root).unwrap_or(dest));
+    let object = dvcfile::cache_object_path(&cache.dir, md5);
+
+    let existing = if dest.exists() {
+        Some(cache.matches(dest, md5)?)
+    } else {
+        None
+    };
+
+    match existing {
+        Some(true) => {
+            summary.unchanged += 1;
+            return Ok(());
+        }
+        Some(false) if !force => {
+            summary.skipped.push(relative);
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    if !object.exists() {
+        summary.missing_from_cache.push(relative);
+        return Ok(());
+    }
+
+    materialize(&object, dest, link_types)?;
+    if existing.is_some() {
+        summary.modified.push(relative);
+    } else {
+        summary.added.push(relative);
+    }
+    Ok(())
+}
+
+fn checkout_out(
+    repo_root: &Path,
+    base_dir: &Path,
+    out: &DvcOut,
+    cache: &Cache,
+    force: bool,
+    link_types: &[LinkType],
+    summary: &mut CheckoutSummary,
+) -> Result<(), String> {
+    let Some(md5) = out.md5.as_deref() else {
+        return Ok(());
+    };
+    let data_path = base_dir.join(&out.path);
+
+    if out.is_dir() {
+        let manifest = match dvcfile::read_dir_manifest(&cache.dir, md5) {
+            Ok(manifest) => manifest,
+            Err(_) => {
+                summary.missing_from_cache.push(dvcfile::to_git_path(
+                    data_path.strip_prefix(repo_root).unwrap_or(&data_path),
+                ));
+                return Ok(());
+            }
+        };
+        for entry in manifest {
+            checkout_file(
+                repo_root,
+                cache,
+                &entry.md5,
+                &data_path.join(&entry.relpath),
+                force,
+                link_types,
+                summary,
+            )?;
+        }
+        Ok(())
+    } else {
+        checkout_file(
+            repo_root, cache, md5, &data_path, force, link_types, summary,
+        )
+    }
+}
+
+/// Materializes the outputs of the given pointers from the local cache using
+/// the repository's configured link types. Locally modified data is only
+/// overwritten when `force` is set.
+pub fn checkout_pointers(
+    repo_root: &Path,
+    pointers: &[PathBuf],
+    force: bool,
+) -> Result<CheckoutSummary, String> {
+    let mut summary = CheckoutSummary::default();
+    let link_types = cache_link::configured(repo_root);
+    let cache = Cache::of(repo_root);
+
+    for pointer in pointers {
+        let dvc_file = dvcfile::read_dvc_file(pointer)?;
+        let base_dir = pointer.parent().unwrap_or(repo_root);
+        for out in &dvc_file.outs {
+            checkout_out(
+                repo_root,
+                base_dir,
+                out,
+                &cache,
+                force,
+                &link_types,
+                &mut summary,
+            )?;
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Materializes some files of a directory output, given by their entries in
+/// its manifest, leaving the rest of the directory as it is
+pub(crate) fn checkout_dir_entries(
+    repo_root: &Path,
+    data_path: &Path,
+    entries: &[DirManifestEntry],
+    force: bool,
+) -> Result<CheckoutSummary, String> {
+    let mut summary = CheckoutSummary::default();
+    let link_types = cache_link::configured(repo_root);
+    let cache = Cache::of(repo_root);
+    for entry in entries {
+        checkout_file(
+            repo_root,
+            &cache,
+            &entry.md5,
+            &data_path.join(&entry.relpath),
+            force,
+            &link_types,
+            &mut summary,
+        )?;
+    }
+    Ok(summary)
+}
+
+/// Removes a data file when the cache holds the same content
+fn remove_cached_file(
+    cache: &Cache,
+    md5: &str,
+    dest: &Path,
+    relative: String,
+    summary: &mut DataSyncSummary,
+) -> Result<(), String> {
+    if !dest.exists() {
+        return Ok(());
+    }
+    let cached = dvcfile::cache_object_path(&cache.dir, md5).exists();
+    if cached && cache.matches(dest, md5)? {
+        fs::remove_file(dest).map_err(|e| format!("Failed to remove {}: {}", relative, e))?;
+        summary.removed.push(relative);
+    } else {
+        summary.kept.push(relative);
+    }
+    Ok(())
+}
+
+fn remove_output(
+    repo_root: &Path,
+    path: &str,
+    out: &DvcOut,
+    summary: &mut DataSyncSummary,
+) -> Result<(), String> {
+    let Some(md5) = out.md5.as_deref() else {
+        return Ok(());
+    };
+    let cache = Cache::of(repo_root);
+    let data_path = repo_root.join(path);
+
+    if !out.is_dir() {
+        return remove_cached_file(&cache, md5, &data_path, path.to_string(), summary);
+    }
+    let Ok(manifest) = dvcfile::read_dir_manifest(&cache.dir, md5) else {
+        if data_path.exists() {
+            summary.kept.push(path.to_string());
+        }
+        return Ok(());
+    };
+    for entry in manifest {
+        remove_cached_file(
+            &cache,
+            &entry.md5,
+            &data_path.join(&entry.relpath),
+            format!("{}/{}", path, entry.relpath),
+            summary,
+        )?;
+    }
+    // Directories emptied by the removal go too; ones with other files stay
+    for dir in WalkDir::new(&data_path)
+        .contents_first(true)
+        .into_iter()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_dir())
+    {
+        let _ = fs::remove_dir(dir.path());
+    }
+    Ok(())
+}
+
+/// Makes the workspace data match the pointers after HEAD moved from
+/// `previous` to `current` outputs (keyed by data path): outputs that are no
+/// longer tracked are removed where their data is safely cached, and the
+/// current pointers are checked out without overwriting local changes.
+pub fn sync_outputs(
+    repo_root: &Path,
+    previous: &BTreeMap<String, DvcOut>,
+    current: &BTreeMap<String, DvcOut>,
+) -> Result<DataSyncSummary, String> {
+    let mut summary = DataSyncSummary::default();
+    for (path, out) in previous {
+        if !current.contains_key(path) {
+            remove_output(repo_root, path, out, &mut summary)?;
+        }
+    }
+    summary.checkout =
+        checkout_pointers(repo_root, &dvcfile::find_pointer_files(repo_root), false)?;
+    Ok(summary)
+}
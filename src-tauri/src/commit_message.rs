@@ -0,0 +1,390 @@
+use git2::{Delta, Repository};
+use rusqlite::{params, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::db::{self, PooledConnection};
+use crate::dvcfile;
+
+const TEMPLATE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS commit_templates (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    repo_path TEXT,
+    name TEXT NOT NULL,
+    summary TEXT NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+";
+
+/// A staged change to a DVC-tracked dataset, read from its pointer
+#[derive(Debug, Serialize)]
+pub struct DatasetChange {
+    /// Data path the pointer tracks
+    pub path: String,
+    /// "added", "modified" or "removed"
+    pub change: String,
+    pub old_size: Option<u64>,
+    pub new_size: Option<u64>,
+    pub old_nfiles: Option<u64>,
+    pub new_nfiles: Option<u64>,
+}
+
+impl DatasetChange {
+    fn size_delta(&self) -> i64 {
+        self.new_size.unwrap_or(0) as i64 - self.old_size.unwrap_or(0) as i64
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileChange {
+    pub path: String,
+    /// "added", "modified", "removed" or "renamed"
+    pub change: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StagedSummary {
+    pub datasets: Vec<DatasetChange>,
+    /// Staged files other than DVC pointers and ignore files
+    pub files: Vec<FileChange>,
+    /// Net change in tracked data size, in bytes
+    pub size_delta: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitSuggestion {
+    pub summary: String,
+    pub description: String,
+    pub changes: StagedSummary,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CommitTemplate {
+    pub id: Option<i64>,
+    /// Repository the template belongs to; `None` for templates shared by all
+    pub repo_path: Option<String>,
+    pub name: String,
+    /// May use `{summary}`, `{datasets}`, `{size_delta}`, `{file_count}` and
+    /// `{changes}` placeholders
+    pub summary: String,
+    pub description: String,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, TEMPLATE_SCHEMA)
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+fn format_delta(delta: i64) -> String {
+    let sign = if delta < 0 { "-" } else { "+" };
+    format!("{}{}", sign, format_size(delta.unsigned_abs()))
+}
+
+fn change_name(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added | Delta::Copied => "added",
+        Delta::Deleted => "removed",
+        Delta::Renamed => "renamed",
+        _ => "modified",
+    }
+}
+
+/// First output of a pointer blob as (data path, size, nfiles)
+fn pointer_out(
+    repo: &Repository,
+    id: git2::Oid,
+    pointer: &Path,
+) -> Option<(String, Option<u64>, Option<u64>)> {
+    if id.is_zero() {
+        return None;
+    }
+    let blob = repo.find_blob(id).ok()?;
+    let dvc_file = dvcfile::parse_dvc_file(&String::from_utf8_lossy(blob.content())).ok()?;
+    let out = dvc_file.outs.into_iter().next()?;
+    let data_path = pointer.parent().unwrap_or(Path::new("")).join(&out.path);
+    Some((dvcfile::to_git_path(&data_path), out.size, out.nfiles))
+}
+
+/// Classifies the staged changes into dataset and file changes
+pub fn staged_summary(repo: &Repository) -> Result<StagedSummary, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+        .map_err(|e| format!("Failed to diff staged changes: {}", e))?;
+
+    let mut datasets = Vec::new();
+    let mut files = Vec::new();
+    for delta in diff.deltas() {
+        let Some(path) = delta.new_file().path().or(delta.old_file().path()) else {
+            continue;
+        };
+        if path.file_name().and_then(|n| n.to_str()) == Some(".gitignore") {
+            continue;
+        }
+
+        if path.extension().and_then(|e| e.to_str()) == Some("dvc") {
+            let old = pointer_out(repo, delta.old_file().id(), path);
+            let new = pointer_out(repo, delta.new_file().id(), path);
+            let data_path = new
+                .as_ref()
+                .or(old.as_ref())
+                .map(|(data, _, _)| data.clone())
+                .unwrap_or_else(|| dvcfile::to_git_path(&path.with_extension("")));
+            let change = match (&old, &new) {
+                (None, Some(_)) => "added",
+                (Some(_), None) => "removed",
+                _ => "modified",
+            };
+            datasets.push(DatasetChange {
+                path: data_path,
+                change: change.to_string(),
+                old_size: old.as_ref().and_then(|(_, size, _)| *size),
+                new_size: new.as_ref().and_then(|(_, size, _)| *size),
+                old_nfiles: old.and_then(|(_, _, nfiles)| nfiles),
+                new_nfiles: new.and_then(|(_, _, nfiles)| nfiles),
+            });
+        } else {
+            files.push(FileChange {
+                path: dvcfile::to_git_path(path),
+                change: change_name(delta.status()).to_string(),
+            });
+        }
+    }
+
+    let size_delta = datasets.iter().map(DatasetChange::size_delta).sum();
+    Ok(StagedSummary {
+        datasets,
+        files,
+        size_delta,
+    })
+}
+
+fn file_name(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
+
+fn verb(change: &str) -> &'static str {
+    match change {
+        "added" => "Add",
+        "removed" => "Remove",
+        "renamed" => "Rename",
+        _ => "Update",
+    }
+}
+
+fn generated_summary(changes: &StagedSummary) -> String {
+    let data = match changes.datasets.as_slice() {
+        [] => None,
+        [dataset] => {
+            let mut part = format!("{} {}", verb(&dataset.change), file_name(&dataset.path));
+            if dataset.change == "modified" {
+                part.push_str(&format!(" ({})", format_delta(dataset.size_delta())));
+            }
+            Some(part)
+        }
+        datasets => Some(format!("Update {} datasets", datasets.len())),
+    };
+    let code = match changes.files.as_slice() {
+        [] => None,
+        [file] => Some(format!("{} {}", verb(&file.change), file_name(&file.path))),
+        files => Some(format!("Update {} files", files.len())),
+    };
+
+    match (data, code) {
+        (Some(data), Some(code)) => {
+            // Lower-case the verb of the second part so it reads as one sentence
+            let mut chars = code.chars();
+            let first = chars.next().map(|c| c.to_ascii_lowercase());
+            format!(
+                "{} and {}{}",
+                data,
+                first.into_iter().collect::<String>(),
+                chars.as_str()
+            )
+        }
+        (Some(part), None) | (None, Some(part)) => part,
+        (None, None) => String::new(),
+    }
+}
+
+fn generated_description(changes: &StagedSummary) -> String {
+    let mut lines = Vec::new();
+    for dataset in &changes.datasets {
+        let mut line = format!("- {} {}", verb(&dataset.change), dataset.path);
+        match (dataset.old_size, dataset.new_size) {
+            (Some(old), Some(new)) if dataset.change == "modified" => line.push_str(&format!(
+                ": {} -> {} ({})",
+                format_size(old),
+                format_size(new),
+                format_delta(dataset.size_delta())
+            )),
+            (_, Some(new)) => line.push_str(&format!(" ({})", format_size(new))),
+            _ => {}
+        }
+        if let Some(nfiles) = dataset.new_nfiles {
+            line.push_str(&format!(", {} files", nfiles));
+        }
+        lines.push(line);
+    }
+    for file in &changes.files {
+        lines.push(format!("- {} {}", verb(&file.change), file.path));
+    }
+    lines.join("\n")
+}
+
+fn render(template: &str, summary: &str, changes: &StagedSummary, list: &str) -> String {
+    let datasets: Vec<&str> = changes.datasets.iter().map(|d| d.path.as_str()).collect();
+    template
+        .replace("{summary}", summary)
+        .replace("{datasets}", &datasets.join(", "))
+        .replace("{size_delta}", &format_delta(changes.size_delta))
+        .replace(
+            "{file_count}",
+            &(changes.files.len() + changes.datasets.len()).to_string(),
+        )
+        .replace("{changes}", list)
+}
+
+/// Pre-fills the commit dialog from the staged changes, optionally through a
+/// saved template
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn suggest_commit_message(
+    app_handle: AppHandle,
+    repo_path: String,
+    template_id: Option<i64>,
+) -> Result<CommitSuggestion, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let changes = staged_summary(&repo)?;
+    let summary = generated_summary(&changes);
+    let description = generated_description(&changes);
+
+    let Some(template_id) = template_id else {
+        return Ok(CommitSuggestion {
+            summary,
+            description,
+            changes,
+        });
+    };
+
+    let conn = open_connection(&app_handle)?;
+    let (template_summary, template_description): (String, String) = conn
+        .query_row(
+            "SELECT summary, description FROM commit_templates WHERE id = ?1",
+            params![template_id],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| format!("Failed to read commit template: {}", e))?
+        .ok_or_else(|| format!("Commit template {} not found", template_id))?;
+
+    Ok(CommitSuggestion {
+        summary: render(&template_summary, &summary, &changes, &description),
+        description: render(&template_description, &summary, &changes, &description),
+        changes,
+    })
+}
+
+/// Templates shared by all repositories plus those saved for `repo_path`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_commit_templates(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Vec<CommitTemplate>, String> {
+    let conn = open_connection(&app_handle)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, repo_path, name, summary, description FROM commit_templates
+             WHERE repo_path IS NULL OR repo_path = ?1 ORDER BY name",
+        )
+        .map_err(|e| format!("Failed to query commit templates: {}", e))?;
+    let templates = stmt
+        .query_map(params![repo_path], |row| {
+            Ok(CommitTemplate {
+                id: row.get(0)?,
+                repo_path: row.get(1)?,
+                name: row.get(2)?,
+                summary: row.get(3)?,
+                description: row.get(4)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query commit templates: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read commit templates: {}", e))?;
+    Ok(templates)
+}
+
+/// Creates a template, or updates it when `id` is set. Returns its id.
+#[command]
+#[instrument(skip(app_handle, template), err(Debug))]
+pub fn save_commit_template(
+    app_handle: AppHandle,
+    template: CommitTemplate,
+) -> Result<i64, String> {
+    if template.name.trim().is_empty() {
+        return Err("Template name cannot be empty".to_string());
+    }
+    let conn = open_connection(&app_handle)?;
+    match template.id {
+        Some(id) => {
+            conn.execute(
+                "UPDATE commit_templates SET repo_path = ?1, name = ?2, summary = ?3, description = ?4
+                 WHERE id = ?5",
+                params![
+                    template.repo_path,
+                    template.name,
+                    template.summary,
+                    template.description,
+                    id
+                ],
+            )
+            .map_err(|e| format!("Failed to update commit template: {}", e))?;
+            Ok(id)
+        }
+        None => {
+            conn.execute(
+                "INSERT INTO commit_templates (repo_path, name, summary, description)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    template.repo_path,
+                    template.name,
+                    template.summary,
+                    template.description
+                ],
+            )
+            .map_err(|e| format!("Failed to save commit template: {}", e))?;
+            Ok(conn.last_insert_rowid())
+        }
+    }
+}
+
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn delete_commit_template(app_handle: AppHandle, id: i64) -> Result<(), String> {
+    let conn = open_connection(&app_handle)?;
+    conn.execute("DELETE FROM commit_templates WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete commit template: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use git2::{BranchType, Commit, Delta, Diff, FetchOptions, Index, IndexEntry, Oid, Repository};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::remote;
+
+#[derive(Debug, Serialize)]
+pub struct ConflictFile {
+    pub path: String,
+    /// Blob ids of the common ancestor, our side and their side; `None` when
+    /// the file doesn't exist on that side (e.g. deleted on one branch)
+    pub ancestor_id: Option<String>,
+    pub our_id: Option<String>,
+    pub their_id: Option<String>,
+    /// DVC pointer conflicts have to be resolved by picking a data version
+    pub is_dvc_pointer: bool,
+}
+
+fn entry_id(entry: &Option<IndexEntry>) -> Option<String> {
+    entry.as_ref().map(|e| e.id.to_string())
+}
+
+/// Conflicts recorded in an index, e.g. after a cherry-pick, revert or rebase step
+pub fn index_conflicts(index: &Index) -> Result<Vec<ConflictFile>, String> {
+    let conflicts = index
+        .conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?;
+
+    let mut files = Vec::new();
+    for conflict in conflicts {
+        let conflict = conflict.map_err(|e| format!("Failed to read conflict: {}", e))?;
+        let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .next()
+        else {
+            continue;
+        };
+
+        files.push(ConflictFile {
+            is_dvc_pointer: path.ends_with(".dvc"),
+            ancestor_id: entry_id(&conflict.ancestor),
+            our_id: entry_id(&conflict.our),
+            their_id: entry_id(&conflict.their),
+            path,
+        });
+    }
+    Ok(files)
+}
+
+/// Lists the conflicted files of an in-progress merge, cherry-pick, revert or rebase
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn list_conflicts(repo_path: String) -> Result<Vec<ConflictFile>, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    index_conflicts(&index)
+}
+
+/// A staged file that also changed on the upstream since the branches diverged
+#[derive(Debug, Serialize)]
+pub struct UpstreamConflict {
+    pub path: String,
+    /// How the upstream changed it: "added", "modified", "deleted" or "renamed"
+    pub upstream_status: String,
+    /// Newest upstream commit touching the file
+    pub commit_id: String,
+    pub author: String,
+    pub summary: String,
+    pub is_dvc_pointer: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UpstreamConflicts {
+    pub branch: String,
+    /// "<remote>/<branch>" the branch tracks, `None` without an upstream
+    pub upstream: Option<String>,
+    /// Upstream commits not merged locally
+    pub behind: usize,
+    pub conflicts: Vec<UpstreamConflict>,
+}
+
+fn delta_status(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added => "added",
+        Delta::Deleted => "deleted",
+        Delta::Renamed => "renamed",
+        _ => "modified",
+    }
+}
+
+/// Paths a diff touches, both sides of renames included
+fn diff_paths(diff: &Diff) -> Vec<(String, Delta)> {
+    diff.deltas()
+        .flat_map(|delta| {
+            let status = delta.status();
+            [delta.old_file().path(), delta.new_file().path()]
+                .into_iter()
+                .flatten()
+                .map(move |path| (path.to_string_lossy().to_string(), status))
+        })
+        .collect()
+}
+
+/// Upstream changes since `base`, each path with the newest commit touching it
+fn upstream_changes(
+    repo: &Repository,
+    base: Oid,
+    upstream: Oid,
+) -> Result<HashMap<String, (Delta, Commit<'_>)>, String> {
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    walk.push(upstream)
+        .and_then(|_| walk.hide(base))
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let mut changes = HashMap::new();
+    for oid in walk {
+        let commit = oid
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|e| format!("Failed to read commit: {}", e))?;
+        let tree = commit
+            .tree()
+            .map_err(|e| format!("Failed to read tree: {}", e))?;
+        let parent_tree = commit.parent(0).and_then(|parent| parent.tree()).ok();
+        let diff = repo
+            .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+            .map_err(|e| format!("Failed to diff commit {}: {}", commit.id(), e))?;
+        // The walk starts at the newest commit, which wins
+        for (path, status) in diff_paths(&diff) {
+            changes
+                .entry(path)
+                .or_insert_with(|| (status, commit.clone()));
+        }
+    }
+    Ok(changes)
+}
+
+/// Fetches the upstream of the current branch and lists the staged files it
+/// changed since the branches diverged, so they can be pulled before
+/// committing instead of conflicting on the next merge
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn detect_upstream_conflicts(
+    app_handle: AppHandle,
+    repo_path: String,
+    operation_id: Option<String>,
+) -> Result<UpstreamConflicts, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+    let head_commit = head
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    let mut result = UpstreamConflicts {
+        branch: branch_name.clone(),
+        upstream: None,
+        behind: 0,
+        conflicts: Vec::new(),
+    };
+    let Ok(upstream) = repo
+        .find_branch(&branch_name, BranchType::Local)
+        .and_then(|branch| branch.upstream())
+    else {
+        return Ok(result);
+    };
+    let upstream_name = upstream
+        .name()
+        .ok()
+        .flatten()
+        .ok_or("Invalid upstream name")?
+        .to_string();
+    let remote_name = repo
+        .branch_upstream_remote(&format!("refs/heads/{}", branch_name))
+        .map_err(|e| format!("Failed to get upstream remote: {}", e))?;
+    let remote_name = remote_name.as_str().ok_or("Invalid upstream remote name")?;
+
+    let operation_id = remote::operation_id(operation_id);
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(remote::progress_callbacks(
+        &app_handle,
+        &operation_id,
+        "fetch",
+    ));
+    repo.find_remote(remote_name)
+        .map_err(|e| format!("Failed to find remote: {}", e))?
+        .fetch(&[] as &[&str], Some(&mut fetch_options), None)
+        .map_err(|e| format!("Failed to fetch: {}", e))?;
+
+    // Re-read the upstream now that the fetch may have moved it
+    let upstream_commit = repo
+        .revparse_single(&format!("refs/remotes/{}", upstream_name))
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find upstream commit: {}", e))?;
+    let (_, behind) = repo
+        .graph_ahead_behind(head_commit.id(), upstream_commit.id())
+        .map_err(|e| format!("Failed to compare with upstream: {}", e))?;
+    result.upstream = Some(upstream_name);
+    result.behind = behind;
+    if behind == 0 {
+        return Ok(result);
+    }
+
+    let base = repo
+        .merge_base(head_commit.id(), upstream_commit.id())
+        .map_err(|e| format!("Failed to find merge base: {}", e))?;
+    let upstream_changes = upstream_changes(&repo, base, upstream_commit.id())?;
+
+    let head_tree = head_commit
+        .tree()
+        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+    let staged = repo
+        .diff_tree_to_index(Some(&head_tree), None, None)
+        .map_err(|e| format!("Failed to diff staged changes: {}", e))?;
+    let mut staged_paths: Vec<String> = diff_paths(&staged)
+        .into_iter()
+        .map(|(path, _)| path)
+        .collect();
+    staged_paths.sort();
+    staged_paths.dedup();
+
+    result.conflicts = staged_paths
+        .into_iter()
+        .filter_map(|path| {
+            let (status, commit) = upstream_changes.get(&path)?;
+            Some(UpstreamConflict {
+                upstream_status: delta_status(*status).to_string(),
+                commit_id: commit.id().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                is_dvc_pointer: path.ends_with(".dvc"),
+                path,
+            })
+        })
+        .collect();
+    Ok(result)
+}
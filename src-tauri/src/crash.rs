@@ -0,0 +1,279 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::fs;
+use std::panic::PanicHookInfo;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use tauri::command;
+use tauri::AppHandle;
+use tauri::Manager;
+use tracing::field::{Field, Visit};
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::LookupSpan;
+
+/// Number of tracing events kept in memory for inclusion in crash reports
+const RECENT_EVENTS_CAPACITY: usize = 200;
+
+static RECENT_EVENTS: OnceLock<Mutex<VecDeque<String>>> = OnceLock::new();
+static CRASH_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrashReport {
+    pub id: String,
+    pub timestamp: String,
+    pub message: String,
+    pub location: Option<String>,
+    pub thread: Option<String>,
+    pub operation: Option<String>,
+    pub backtrace: String,
+    pub recent_events: Vec<String>,
+    pub app_version: String,
+    pub os: String,
+    pub submitted: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CrashReportSummary {
+    pub id: String,
+    pub timestamp: String,
+    pub message: String,
+    pub operation: Option<String>,
+    pub submitted: bool,
+}
+
+/// Tracing layer that keeps the most recent events in a ring buffer
+pub struct RecentEventsLayer;
+
+#[derive(Default)]
+struct EventVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for EventVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+}
+
+impl<S> Layer<S> for RecentEventsLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut visitor = EventVisitor::default();
+        event.record(&mut visitor);
+
+        let scope = ctx
+            .event_scope(event)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| span.name())
+                    .collect::<Vec<_>>()
+                    .join(":")
+            })
+            .unwrap_or_default();
+
+        let metadata = event.metadata();
+        let mut line = format!(
+            "{} {} {} [{}] {}",
+            chrono::Utc::now().to_rfc3339(),
+            metadata.level(),
+            metadata.target(),
+            scope,
+            visitor.message
+        );
+        if !visitor.fields.is_empty() {
+            line.push(' ');
+            line.push_str(&visitor.fields.join(" "));
+        }
+
+        let buffer = RECENT_EVENTS.get_or_init(|| Mutex::new(VecDeque::new()));
+        if let Ok(mut buffer) = buffer.lock() {
+            if buffer.len() >= RECENT_EVENTS_CAPACITY {
+                buffer.pop_front();
+            }
+            buffer.push_back(line);
+        }
+    }
+}
+
+fn recent_events() -> Vec<String> {
+    RECENT_EVENTS
+        .get()
+        .and_then(|buffer| buffer.lock().ok().map(|b| b.iter().cloned().collect()))
+        .unwrap_or_default()
+}
+
+fn crash_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("crash-reports");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create crash directory: {}", e))?;
+    Ok(dir)
+}
+
+fn new_report(message: String, location: Option<String>) -> CrashReport {
+    let thread = std::thread::current();
+    let operation = tracing::Span::current()
+        .metadata()
+        .map(|metadata| metadata.name().to_string());
+
+    CrashReport {
+        id: format!(
+            "crash-{}-{}",
+            chrono::Utc::now().format("%Y%m%d%H%M%S"),
+            uuid::Uuid::new_v4().simple()
+        ),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        message,
+        location,
+        thread: thread.name().map(|n| n.to_string()),
+        operation,
+        backtrace: std::backtrace::Backtrace::force_capture().to_string(),
+        recent_events: recent_events(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        os: format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+        submitted: false,
+    }
+}
+
+fn write_report(dir: &Path, report: &CrashReport) -> Result<PathBuf, String> {
+    let path = dir.join(format!("{}.json", report.id));
+    let json = serde_json::to_string_pretty(report)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    fs::write(&path, json).map_err(|e| format!("Failed to write crash report: {}", e))?;
+    Ok(path)
+}
+
+fn panic_message(info: &PanicHookInfo<'_>) -> String {
+    if let Some(s) = info.payload().downcast_ref::<&str>() {
+        s.to_string()
+    } else if let Some(s) = info.payload().downcast_ref::<String>() {
+        s.clone()
+    } else {
+        "Unknown panic payload".to_string()
+    }
+}
+
+/// Installs a panic hook that writes a crash report under app data before
+/// delegating to the default hook
+pub fn install_panic_hook(app_handle: &AppHandle) -> Result<(), String> {
+    let dir = crash_dir(app_handle)?;
+    let _ = CRASH_DIR.set(dir);
+
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if let Some(dir) = CRASH_DIR.get() {
+            let location = info
+                .location()
+                .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()));
+            let report = new_report(panic_message(info), location);
+            if let Err(e) = write_report(dir, &report) {
+                eprintln!("Failed to record crash report: {}", e);
+            }
+        }
+        default_hook(info);
+    }));
+
+    Ok(())
+}
+
+/// Records a crash report for a fatal error that did not unwind through the panic hook
+pub fn record_fatal_error(message: String) {
+    if let Some(dir) = CRASH_DIR.get() {
+        if let Err(e) = write_report(dir, &new_report(message, None)) {
+            eprintln!("Failed to record crash report: {}", e);
+        }
+    }
+}
+
+fn read_report(dir: &Path, id: &str) -> Result<CrashReport, String> {
+    if id.contains('/') || id.contains('\\') || id.contains("..") {
+        return Err(format!("Invalid crash report id: {}", id));
+    }
+    let content = fs::read_to_string(dir.join(format!("{}.json", id)))
+        .map_err(|e| format!("Failed to read crash report {}: {}", id, e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse crash report: {}", e))
+}
+
+#[command]
+pub fn list_crash_reports(app_handle: AppHandle) -> Result<Vec<CrashReportSummary>, String> {
+    let dir = crash_dir(&app_handle)?;
+    let mut reports = Vec::new();
+
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read crash directory: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let Some(id) = path.file_stem().and_then(|s| s.to_str()) else {
+            continue;
+        };
+        // Skip unreadable reports rather than failing the whole listing
+        if let Ok(report) = read_report(&dir, id) {
+            reports.push(CrashReportSummary {
+                id: report.id,
+                timestamp: report.timestamp,
+                message: report.message,
+                operation: report.operation,
+                submitted: report.submitted,
+            });
+        }
+    }
+
+    reports.sort_by(|a, b| b.timestamp.cmp(&a.timestamp));
+    Ok(reports)
+}
+
+#[command]
+pub fn get_crash_report(app_handle: AppHandle, id: String) -> Result<CrashReport, String> {
+    let dir = crash_dir(&app_handle)?;
+    read_report(&dir, &id)
+}
+
+/// Sends a crash report to the given endpoint. Nothing leaves the machine
+/// unless the user has explicitly consented for this report.
+#[command]
+pub fn submit_crash_report(
+    app_handle: AppHandle,
+    id: String,
+    endpoint: String,
+    consent: bool,
+) -> Result<String, String> {
+    if !consent {
+        return Err("Crash reports are only submitted with user consent".to_string());
+    }
+
+    let dir = crash_dir(&app_handle)?;
+    let mut report = read_report(&dir, &id)?;
+
+    let body = serde_json::to_value(&report)
+        .map_err(|e| format!("Failed to serialize crash report: {}", e))?;
+    ureq::post(&endpoint)
+        .send_json(body)
+        .map_err(|e| format!("Failed to submit crash report: {}", e))?;
+
+    report.submitted = true;
+    write_report(&dir, &report)?;
+
+    Ok(format!("Submitted crash report {}", id))
+}
+
+#[command]
+pub fn delete_crash_report(app_handle: AppHandle, id: String) -> Result<(), String> {
+    let dir = crash_dir(&app_handle)?;
+    // Validate the id before touching the filesystem
+    read_report(&dir, &id)?;
+    fs::remove_file(dir.join(format!("{}.json", id)))
+        .map_err(|e| format!("Failed to delete crash report: {}", e))
+}
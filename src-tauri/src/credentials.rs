@@ -0,0 +1,343 @@
+//! Credentials for DVC remote backends. Like DVC itself, remote options
+//! (usually kept in `.dvc/config.local`) take precedence over environment
+//! variables, which take precedence over the provider's standard files.
+
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use tauri::command;
+use tracing::instrument;
+
+use crate::dvc_config::{self, DvcConfig, RemoteConfig};
+use crate::transfer;
+
+#[derive(Debug, Clone)]
+pub struct S3Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub session_token: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum GcsCredentials {
+    /// Service account key file, exchanged for tokens with a signed JWT
+    ServiceAccount {
+        client_email: String,
+        private_key: String,
+        token_uri: String,
+    },
+    /// `gcloud auth application-default login` credentials
+    AuthorizedUser {
+        client_id: String,
+        client_secret: String,
+        refresh_token: String,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub enum AzureAuth {
+    /// Base64-encoded storage account key used for Shared Key signing
+    SharedKey(String),
+    /// SAS token query string, without the leading `?`
+    Sas(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct AzureCredentials {
+    pub account_name: String,
+    /// Blob endpoint override, e.g. for Azurite or sovereign clouds
+    pub endpoint: Option<String>,
+    pub auth: AzureAuth,
+}
+
+/// Credentials entered in the app, stored in `.dvc/config.local` under the
+/// option names DVC uses so the CLI picks them up as well
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum RemoteCredentials {
+    S3 {
+        access_key_id: String,
+        secret_access_key: String,
+        session_token: Option<String>,
+    },
+    Gcs {
+        service_account_path: String,
+    },
+    Azure {
+        account_name: Option<String>,
+        account_key: Option<String>,
+        sas_token: Option<String>,
+        connection_string: Option<String>,
+    },
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+fn env(name: &str) -> Option<String> {
+    std::env::var(name).ok().filter(|value| !value.is_empty())
+}
+
+/// Credentials from the remote options, the environment, or `~/.aws/credentials`
+pub fn s3_credentials(remote: &RemoteConfig) -> Result<S3Credentials, String> {
+    if let (Some(id), Some(secret)) = (
+        remote.option("access_key_id"),
+        remote.option("secret_access_key"),
+    ) {
+        return Ok(S3Credentials {
+            access_key_id: id.to_string(),
+            secret_access_key: secret.to_string(),
+            session_token: remote.option("session_token").map(|s| s.to_string()),
+        });
+    }
+
+    if let (Some(id), Some(secret)) = (env("AWS_ACCESS_KEY_ID"), env("AWS_SECRET_ACCESS_KEY")) {
+        return Ok(S3Credentials {
+            access_key_id: id,
+            secret_access_key: secret,
+            session_token: env("AWS_SESSION_TOKEN"),
+        });
+    }
+
+    let profile = remote
+        .option("profile")
+        .map(|s| s.to_string())
+        .or_else(|| env("AWS_PROFILE"))
+        .unwrap_or_else(|| "default".to_string());
+    let credentials_file = remote
+        .option("credentialpath")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".aws").join("credentials")));
+
+    if let Some(path) = credentials_file {
+        let file = DvcConfig::read(&path)?;
+        if let (Some(id), Some(secret)) = (
+            file.get(&profile, "aws_access_key_id"),
+            file.get(&profile, "aws_secret_access_key"),
+        ) {
+            return Ok(S3Credentials {
+                access_key_id: id.to_string(),
+                secret_access_key: secret.to_string(),
+                session_token: file
+                    .get(&profile, "aws_session_token")
+                    .map(|s| s.to_string()),
+            });
+        }
+    }
+
+    Err(format!(
+        "No S3 credentials found for DVC remote '{}'",
+        remote.name
+    ))
+}
+
+fn gcloud_default_credentials() -> Option<PathBuf> {
+    let config_dir = match std::env::var_os("APPDATA") {
+        Some(appdata) => PathBuf::from(appdata).join("gcloud"),
+        None => home_dir()?.join(".config").join("gcloud"),
+    };
+    Some(config_dir.join("application_default_credentials.json"))
+}
+
+fn json_field(value: &Value, key: &str) -> Result<String, String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+        .ok_or_else(|| format!("Credentials file is missing '{}'", key))
+}
+
+/// Parses a Google credentials JSON file (service account or authorized user)
+pub fn read_gcs_credentials(path: &std::path::Path) -> Result<GcsCredentials, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let value: Value = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+
+    match value.get("type").and_then(|v| v.as_str()) {
+        Some("service_account") => Ok(GcsCredentials::ServiceAccount {
+            client_email: json_field(&value, "client_email")?,
+            private_key: json_field(&value, "private_key")?,
+            token_uri: json_field(&value, "token_uri")
+                .unwrap_or_else(|_| "https://oauth2.googleapis.com/token".to_string()),
+        }),
+        Some("authorized_user") => Ok(GcsCredentials::AuthorizedUser {
+            client_id: json_field(&value, "client_id")?,
+            client_secret: json_field(&value, "client_secret")?,
+            refresh_token: json_field(&value, "refresh_token")?,
+        }),
+        other => Err(format!(
+            "Unsupported Google credentials type: {}",
+            other.unwrap_or("none")
+        )),
+    }
+}
+
+/// Credentials from the remote's `credentialpath`, `GOOGLE_APPLICATION_CREDENTIALS`
+/// or the gcloud application default credentials
+pub fn gcs_credentials(remote: &RemoteConfig) -> Result<GcsCredentials, String> {
+    let path = remote
+        .option("credentialpath")
+        .map(PathBuf::from)
+        .or_else(|| env("GOOGLE_APPLICATION_CREDENTIALS").map(PathBuf::from))
+        .or_else(|| gcloud_default_credentials().filter(|path| path.exists()))
+        .ok_or_else(|| {
+            format!(
+                "No Google Cloud credentials found for DVC remote '{}'",
+                remote.name
+            )
+        })?;
+    read_gcs_credentials(&path)
+}
+
+/// Splits an Azure connection string (`Key=Value;Key=Value`) into its parts
+fn parse_connection_string(connection_string: &str) -> Vec<(String, String)> {
+    connection_string
+        .split(';')
+        .filter_map(|part| part.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+fn azure_from_connection_string(connection_string: &str) -> Result<AzureCredentials, String> {
+    let parts = parse_connection_string(connection_string);
+    let get = |key: &str| {
+        parts
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(key))
+            .map(|(_, v)| v.clone())
+    };
+
+    let endpoint = get("BlobEndpoint");
+    let account_name = get("AccountName")
+        .or_else(|| {
+            // SAS connection strings often only carry the endpoint
+            endpoint.as_deref().and_then(|url| {
+                url.split("://")
+                    .nth(1)
+                    .and_then(|host| host.split('.').next())
+                    .map(|s| s.to_string())
+            })
+        })
+        .ok_or("Azure connection string has no AccountName")?;
+    let auth = match (get("AccountKey"), get("SharedAccessSignature")) {
+        (Some(key), _) => AzureAuth::SharedKey(key),
+        (None, Some(sas)) => AzureAuth::Sas(sas.trim_start_matches('?').to_string()),
+        (None, None) => {
+            return Err("Azure connection string has no AccountKey or SharedAccessSignature".into())
+        }
+    };
+
+    Ok(AzureCredentials {
+        account_name,
+        endpoint,
+        auth,
+    })
+}
+
+/// Credentials from the remote options (`connection_string`, `account_name`
+/// with `account_key` or `sas_token`) or the `AZURE_STORAGE_*` variables
+pub fn azure_credentials(remote: &RemoteConfig) -> Result<AzureCredentials, String> {
+    if let Some(connection_string) = remote
+        .option("connection_string")
+        .map(|s| s.to_string())
+        .or_else(|| env("AZURE_STORAGE_CONNECTION_STRING"))
+    {
+        return azure_from_connection_string(&connection_string);
+    }
+
+    let option_or_env = |option: &str, var: &str| {
+        remote
+            .option(option)
+            .map(|s| s.to_string())
+            .or_else(|| env(var))
+    };
+
+    let account_name = option_or_env("account_name", "AZURE_STORAGE_ACCOUNT").ok_or_else(|| {
+        format!(
+            "DVC remote '{}' has no Azure storage account configured",
+            remote.name
+        )
+    })?;
+    let auth = if let Some(sas) = option_or_env("sas_token", "AZURE_STORAGE_SAS_TOKEN") {
+        AzureAuth::Sas(sas.trim_start_matches('?').to_string())
+    } else if let Some(key) = option_or_env("account_key", "AZURE_STORAGE_KEY") {
+        AzureAuth::SharedKey(key)
+    } else {
+        return Err(format!(
+            "No Azure credentials found for DVC remote '{}'",
+            remote.name
+        ));
+    };
+
+    Ok(AzureCredentials {
+        account_name,
+        endpoint: None,
+        auth,
+    })
+}
+
+/// Stores credentials for a DVC remote in `.dvc/config.local`, which DVC keeps
+/// out of git
+#[command]
+#[instrument(skip(repo_path, credentials), err(Debug))]
+pub fn set_remote_credentials(
+    repo_path: String,
+    remote: String,
+    credentials: RemoteCredentials,
+) -> Result<(), String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    // Fails early when the remote doesn't exist
+    dvc_config::remote(&repo_root, Some(&remote))?;
+
+    let path = dvc_config::local_config_path(&repo_root);
+    let mut config = DvcConfig::read(&path)?;
+    let section = format!("remote \"{}\"", remote);
+
+    let options: Vec<(&str, Option<String>)> = match credentials {
+        RemoteCredentials::S3 {
+            access_key_id,
+            secret_access_key,
+            session_token,
+        } => vec![
+            ("access_key_id", Some(access_key_id)),
+            ("secret_access_key", Some(secret_access_key)),
+            ("session_token", session_token),
+        ],
+        RemoteCredentials::Gcs {
+            service_account_path,
+        } => {
+            read_gcs_credentials(std::path::Path::new(&service_account_path))?;
+            vec![("credentialpath", Some(service_account_path))]
+        }
+        RemoteCredentials::Azure {
+            account_name,
+            account_key,
+            sas_token,
+            connection_string,
+        } => {
+            if account_key.is_none() && sas_token.is_none() && connection_string.is_none() {
+                return Err("Provide an account key, SAS token or connection string".to_string());
+            }
+            vec![
+                ("account_name", account_name),
+                ("account_key", account_key),
+                ("sas_token", sas_token),
+                ("connection_string", connection_string),
+            ]
+        }
+    };
+
+    for (key, value) in options {
+        match value.filter(|v| !v.is_empty()) {
+            Some(value) => config.set(&section, key, &value),
+            None => config.unset(&section, key),
+        }
+    }
+
+    config.write(&path)
+}
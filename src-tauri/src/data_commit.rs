@@ -0,0 +1,403 @@
+use git2::{IndexAddOption, Repository};
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+use tauri::{command, AppHandle, Manager};
+use tracing::{instrument, warn};
+
+use crate::dvc_undo::AddJournal;
+use crate::git::{self, CommitResult};
+use crate::state::{SelectedFiles, SelectedFilesState};
+use crate::{audit, dvc, dvcfile, hooks, remote, repo_manager, settings, transfer};
+
+/// One step of `commit_data_changes`
+#[derive(Debug, Serialize)]
+pub struct WorkflowStep {
+    /// "dvc_add", "git_add", "commit", "dvc_push" or "git_push"
+    pub step: String,
+    /// "done", "skipped", "failed" or "rolled_back"
+    pub status: String,
+    pub message: String,
+    /// Paths the step worked on, relative to the repository root
+    pub paths: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitDataResult {
+    /// Whether every step that ran succeeded
+    pub success: bool,
+    pub commit: Option<CommitResult>,
+    pub steps: Vec<WorkflowStep>,
+}
+
+/// The selection split by how it is committed
+struct Plan {
+    /// Paths tracked with DVC
+    data: Vec<String>,
+    /// Paths staged in git as they are
+    code: Vec<String>,
+}
+
+fn step(step: &str, status: &str, message: String, paths: &[String]) -> WorkflowStep {
+    WorkflowStep {
+        step: step.to_string(),
+        status: status.to_string(),
+        message,
+        paths: paths.to_vec(),
+    }
+}
+
+/// The selection with directories walked around the paths deselected inside
+/// them in the file tree, so those paths are neither tracked nor staged
+fn expand_selection(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    selection: &[String],
+) -> Result<Vec<String>, String> {
+    let excluded = app_handle
+        .state::<SelectedFilesState>()
+        .lock()
+        .map_err(|e| e.to_string())?
+        .excluded
+        .clone();
+    let mut selected = SelectedFiles::new();
+    for path in selection {
+        selected.add_path(path.clone());
+    }
+    selected.excluded = excluded;
+    Ok(selected.expand(repo_root, false))
+}
+
+/// Splits the selection into data for DVC (already tracked, or what the
+/// staging guard flags as large or data files) and everything else for git
+fn classify(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    selection: &[String],
+) -> Result<Plan, String> {
+    let relative: Vec<String> = selection
+        .iter()
+        .map(|path| {
+            dvcfile::repo_relative_path(repo_root, &repo_root.join(path))
+                .map(|path| dvcfile::to_git_path(&path))
+        })
+        .collect::<Result<_, _>>()?;
+    let hook_settings = settings::load(app_handle, repo_root)?.hooks;
+    let suggested = hooks::staging_guard(repo_root, &hook_settings, &relative);
+
+    let (data, code) = relative.into_iter().partition(|path| {
+        !path.ends_with(".dvc")
+            && (dvcfile::pointer_path(&repo_root.join(path)).exists()
+                || suggested.iter().any(|suggestion| &suggestion.path == path))
+    });
+    Ok(Plan { data, code })
+}
+
+/// Stages code paths like `git add -A <paths>`, directories and deletions
+/// included
+fn stage(repo: &Repository, paths: &[String]) -> Result<(), String> {
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    index
+        .add_all(paths, IndexAddOption::DEFAULT, None)
+        .and_then(|_| index.update_all(paths, None))
+        .map_err(|e| format!("Failed to stage files: {}", e))?;
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))
+}
+
+/// Puts the index back to the tree it held before the flow started
+fn restore_index(repo: &Repository, tree_id: git2::Oid) -> Result<(), String> {
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find index snapshot: {}", e))?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    index
+        .read_tree(&tree)
+        .and_then(|_| index.write())
+        .map_err(|e| format!("Failed to restore index: {}", e))
+}
+
+/// Deletes the branch an unborn HEAD pointed to, so HEAD is unborn again
+/// after a commit was made on it
+fn reset_unborn(repo: &Repository, branch: &str) -> Result<(), String> {
+    match repo.find_reference(branch) {
+        Ok(mut reference) => reference
+            .delete()
+            .map_err(|e| format!("Failed to remove the initial commit: {}", e)),
+        Err(_) => Ok(()),
+    }
+}
+
+/// DVC add, git add and commit. A failing step undoes the ones before it so
+/// the workspace and index are left as they were.
+fn add_and_commit(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    repo_root: &Path,
+    plan: &Plan,
+    summary: String,
+    description: String,
+    steps: &mut Vec<WorkflowStep>,
+) -> Option<CommitResult> {
+    let (data, code) = (plan.data.as_slice(), plan.code.as_slice());
+    let repo_root_str = repo_root.to_string_lossy().to_string();
+    let repo = match Repository::open(repo_root) {
+        Ok(repo) => repo,
+        Err(e) => {
+            steps.push(step(
+                "dvc_add",
+                "failed",
+                format!("Failed to open repository: {}", e),
+                data,
+            ));
+            return None;
+        }
+    };
+    // `dvc add` creates a root commit when HEAD is unborn; a rollback removes it
+    let unborn_branch = match repo.head() {
+        Ok(_) => None,
+        Err(_) => repo
+            .find_reference("HEAD")
+            .ok()
+            .and_then(|head| head.symbolic_target().map(String::from)),
+    };
+    let snapshot = repo.index().and_then(|mut index| index.write_tree());
+    let snapshot = match snapshot {
+        Ok(tree_id) => tree_id,
+        Err(e) => {
+            steps.push(step(
+                "dvc_add",
+                "failed",
+                format!("Resolve conflicts before committing: {}", e),
+                data,
+            ));
+            return None;
+        }
+    };
+
+    let journal = match AddJournal::record(&repo_root_str, data) {
+        Ok(journal) => journal,
+        Err(e) => {
+            steps.push(step("dvc_add", "failed", e, data));
+            return None;
+        }
+    };
+    let rollback = |steps: &mut Vec<WorkflowStep>| {
+        let undone = journal
+            .rollback(&repo_root_str)
+            .and_then(|_| restore_index(&repo, snapshot))
+            .and_then(|_| match &unborn_branch {
+                Some(branch) => reset_unborn(&repo, branch),
+                None => Ok(()),
+            });
+        for done in steps.iter_mut().filter(|s| s.status == "done") {
+            done.status = "rolled_back".to_string();
+        }
+        if let Err(e) = undone {
+            warn!("Failed to roll back commit_data_changes: {}", e);
+            steps.push(step(
+                "rollback",
+                "failed",
+                format!("Rolling back failed, check the workspace: {}", e),
+                &[],
+            ));
+        }
+    };
+
+    if data.is_empty() {
+        steps.push(step(
+            "dvc_add",
+            "skipped",
+            "No data files".to_string(),
+            data,
+        ));
+    } else {
+        let added: Result<Vec<String>, String> = data
+            .iter()
+            .map(|file| dvc::add_single(app_handle, &repo_root_str, file).map_err(String::from))
+            .collect();
+        match added {
+            Ok(_) => steps.push(step(
+                "dvc_add",
+                "done",
+                format!("Tracked {} path(s) with DVC", data.len()),
+                data,
+            )),
+            Err(e) => {
+                steps.push(step("dvc_add", "failed", e, data));
+                rollback(steps);
+                return None;
+            }
+        }
+    }
+
+    if code.is_empty() {
+        steps.push(step(
+            "git_add",
+            "skipped",
+            "No code files".to_string(),
+            code,
+        ));
+    } else {
+        match stage(&repo, code) {
+            Ok(()) => steps.push(step(
+                "git_add",
+                "done",
+                format!("Staged {} path(s)", code.len()),
+                code,
+            )),
+            Err(e) => {
+                steps.push(step("git_add", "failed", e, code));
+                rollback(steps);
+                return None;
+            }
+        }
+    }
+
+    match git::commit(
+        app_handle.clone(),
+        repo_path.to_string(),
+        summary,
+        description,
+    ) {
+        Ok(result) if result.success => {
+            steps.push(step("commit", "done", result.message.clone(), &[]));
+            Some(result)
+        }
+        Ok(result) => {
+            steps.push(step("commit", "failed", result.message.clone(), &[]));
+            rollback(steps);
+            Some(result)
+        }
+        Err(e) => {
+            steps.push(step("commit", "failed", e, &[]));
+            rollback(steps);
+            None
+        }
+    }
+}
+
+/// The app's core workflow in one call: tracks the selected data with DVC,
+/// stages the selected code and pointers, commits, and optionally pushes the
+/// data and the branch. Everything before the commit is undone if a step
+/// fails; a failed push leaves the commit in place to push again later.
+#[command(async)]
+#[instrument(skip(app_handle, selection, description), err(Debug))]
+pub fn commit_data_changes(
+    app_handle: AppHandle,
+    repo_path: String,
+    selection: Vec<String>,
+    summary: String,
+    description: String,
+    push: bool,
+    operation_id: Option<String>,
+) -> Result<CommitDataResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "commit_data_changes",
+        json!({ "selection": selection, "summary": summary, "push": push }),
+        || {
+            if selection.is_empty() {
+                return Err("Select the files to commit".to_string());
+            }
+            let repo_root = transfer::repo_root(&repo_path)?;
+
+            let mut steps = Vec::new();
+            // Classified under the lock so the plan matches the workspace
+            // that is committed
+            let (plan, commit) = repo_manager::write(&app_handle, &repo_path, || {
+                let selection = expand_selection(&app_handle, &repo_root, &selection)?;
+                let plan = classify(&app_handle, &repo_root, &selection)?;
+                let commit = add_and_commit(
+                    &app_handle,
+                    &repo_path,
+                    &repo_root,
+                    &plan,
+                    summary,
+                    description,
+                    &mut steps,
+                );
+                Ok((plan, commit))
+            })?;
+            let committed = commit.as_ref().is_some_and(|commit| commit.success);
+
+            if !committed || !push {
+                let reason = if committed {
+                    "Push not requested"
+                } else {
+                    "Nothing was committed"
+                };
+                steps.push(step("dvc_push", "skipped", reason.to_string(), &[]));
+                steps.push(step("git_push", "skipped", reason.to_string(), &[]));
+            } else {
+                let operation_id = remote::operation_id(operation_id);
+                if plan.data.is_empty() {
+                    steps.push(step(
+                        "dvc_push",
+                        "skipped",
+                        "No data files".to_string(),
+                        &[],
+                    ));
+                } else {
+                    match transfer::run_push(
+                        &app_handle,
+                        &repo_root,
+                        &plan.data,
+                        None,
+                        operation_id.clone(),
+                    ) {
+                        Ok(report) if report.failed.is_empty() => steps.push(step(
+                            "dvc_push",
+                            "done",
+                            format!(
+                                "Uploaded {} object(s) to {}",
+                                report.transferred, report.remote
+                            ),
+                            &plan.data,
+                        )),
+                        Ok(report) => steps.push(step(
+                            "dvc_push",
+                            "failed",
+                            format!("{} object(s) failed to upload", report.failed.len()),
+                            &report.failed,
+                        )),
+                        Err(e) => steps.push(step("dvc_push", "failed", e, &plan.data)),
+                    }
+                }
+                // Pushing pointers whose data didn't reach the remote would
+                // leave teammates unable to pull it
+                if steps.last().is_some_and(|last| last.status == "failed") {
+                    steps.push(step(
+                        "git_push",
+                        "skipped",
+                        "Data push failed".to_string(),
+                        &[],
+                    ));
+                } else {
+                    match remote::push(
+                        app_handle.clone(),
+                        repo_path.clone(),
+                        None,
+                        None,
+                        Some(operation_id),
+                    ) {
+                        Ok(summary) => steps.push(step("git_push", "done", summary.message, &[])),
+                        Err(e) => steps.push(step("git_push", "failed", e, &[])),
+                    }
+                }
+            }
+
+            Ok(CommitDataResult {
+                success: committed && !steps.iter().any(|s| s.status == "failed"),
+                commit,
+                steps,
+            })
+        },
+    )
+}
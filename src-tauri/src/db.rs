@@ -0,0 +1,204 @@
+use rusqlite::Connection;
+use std::collections::HashSet;
+use std::ops::{Deref, DerefMut};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_sql::{Migration, MigrationKind};
+
+/// Database shared with the frontend's SQL plugin
+const DATABASE_FILE: &str = "fenn.db";
+
+/// Tables of the backend subsystems. The SQL plugin creates them at startup
+/// with the migrations below; they are created again on first use in case a
+/// command runs before it.
+pub(crate) const FILE_INDEX_SCHEMA: &str = include_str!("migrations/003_file_index.sql");
+pub(crate) const TRANSFER_JOBS_SCHEMA: &str = include_str!("migrations/004_transfer_jobs.sql");
+pub(crate) const ACTIVITY_LOG_SCHEMA: &str = include_str!("migrations/005_activity_log.sql");
+pub(crate) const DATASET_METADATA_SCHEMA: &str =
+    include_str!("migrations/006_dataset_metadata.sql");
+pub(crate) const REMOTE_OBJECTS_SCHEMA: &str = include_str!("migrations/007_remote_objects.sql");
+pub(crate) const DATA_PINS_SCHEMA: &str = include_str!("migrations/008_data_pins.sql");
+pub(crate) const SYNC_OPERATIONS_SCHEMA: &str = include_str!("migrations/009_sync_operations.sql");
+
+/// Connections kept open between commands; more are opened under load and
+/// closed once returned
+const MAX_IDLE_CONNECTIONS: usize = 4;
+
+/// How long a write waits for another connection's write, including the
+/// frontend's, before failing as busy
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Pool of connections to `fenn.db` used by every backend subsystem. The
+/// database is in WAL mode, so readers never wait for a writer and writers
+/// wait for each other instead of failing with "database is locked".
+#[derive(Debug, Default)]
+pub struct Database {
+    idle: Mutex<Vec<Connection>>,
+    /// Schemas already created in this session
+    schemas: Mutex<HashSet<&'static str>>,
+}
+
+pub type DatabaseState = Database;
+
+impl Database {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get(&self, app_handle: &AppHandle) -> Result<Connection, String> {
+        let idle = self
+            .idle
+            .lock()
+            .map_err(|e| format!("Failed to lock connection pool: {}", e))?
+            .pop();
+        match idle {
+            Some(conn) => Ok(conn),
+            None => open(app_handle),
+        }
+    }
+
+    fn ensure_schema(&self, conn: &Connection, schema: &'static str) -> Result<(), String> {
+        let mut schemas = self
+            .schemas
+            .lock()
+            .map_err(|e| format!("Failed to lock connection pool: {}", e))?;
+        if !schemas.contains(schema) {
+            conn.execute_batch(schema)
+                .map_err(|e| format!("Failed to create tables: {}", e))?;
+            schemas.insert(schema);
+        }
+        Ok(())
+    }
+
+    fn put(&self, conn: Connection) {
+        if let Ok(mut idle) = self.idle.lock() {
+            if idle.len() < MAX_IDLE_CONNECTIONS {
+                idle.push(conn);
+            }
+        }
+    }
+}
+
+/// Connection borrowed from the pool, returned to it on drop
+pub struct PooledConnection<'a> {
+    conn: Option<Connection>,
+    database: &'a Database,
+}
+
+impl Deref for PooledConnection<'_> {
+    type Target = Connection;
+
+    fn deref(&self) -> &Connection {
+        self.conn
+            .as_ref()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl DerefMut for PooledConnection<'_> {
+    fn deref_mut(&mut self) -> &mut Connection {
+        self.conn
+            .as_mut()
+            .expect("connection is only taken on drop")
+    }
+}
+
+impl Drop for PooledConnection<'_> {
+    fn drop(&mut self) {
+        // A connection dropped mid-transaction rolls it back, so it's clean
+        if let Some(conn) = self.conn.take() {
+            if conn.is_autocommit() {
+                self.database.put(conn);
+            }
+        }
+    }
+}
+
+fn open(app_handle: &AppHandle) -> Result<Connection, String> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+
+    let conn = Connection::open(dir.join(DATABASE_FILE))
+        .map_err(|e| format!("Failed to open database: {}", e))?;
+    conn.busy_timeout(BUSY_TIMEOUT)
+        .map_err(|e| format!("Failed to set database busy timeout: {}", e))?;
+    // WAL mode is stored in the database file, so the frontend's connections
+    // use it too
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))
+        .map_err(|e| format!("Failed to enable WAL mode: {}", e))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")
+        .map_err(|e| format!("Failed to set database synchronous mode: {}", e))?;
+    Ok(conn)
+}
+
+/// Connection from the pool, with the tables of `schema` created
+pub(crate) fn connection<'a>(
+    app_handle: &'a AppHandle,
+    schema: &'static str,
+) -> Result<PooledConnection<'a>, String> {
+    let database = app_handle.state::<DatabaseState>().inner();
+    let conn = PooledConnection {
+        conn: Some(database.get(app_handle)?),
+        database,
+    };
+    database.ensure_schema(&conn, schema)?;
+    Ok(conn)
+}
+
+/// Connection of its own with the tables of `schema` created, for work that
+/// holds one for its whole duration, like a transfer; closed on drop
+pub(crate) fn dedicated_connection(
+    app_handle: &AppHandle,
+    schema: &'static str,
+) -> Result<Connection, String> {
+    let database = app_handle.state::<DatabaseState>().inner();
+    let conn = open(app_handle)?;
+    database.ensure_schema(&conn, schema)?;
+    Ok(conn)
+}
+
+/// Migrations of `fenn.db`, run by the SQL plugin when the app starts
+pub fn migrations() -> Vec<Migration> {
+    [
+        (
+            "create_initial_tables",
+            include_str!("migrations/001_initial_schema.sql"),
+        ),
+        (
+            "create_current_project_table",
+            include_str!("migrations/002_current_project_state.sql"),
+        ),
+        ("create_file_index_tables", FILE_INDEX_SCHEMA),
+        ("create_transfer_job_tables", TRANSFER_JOBS_SCHEMA),
+        ("create_activity_log_table", ACTIVITY_LOG_SCHEMA),
+        ("create_dataset_metadata_table", DATASET_METADATA_SCHEMA),
+        ("create_remote_objects_table", REMOTE_OBJECTS_SCHEMA),
+        ("create_data_pins_table", DATA_PINS_SCHEMA),
+        ("create_sync_operations_table", SYNC_OPERATIONS_SCHEMA),
+    ]
+    .into_iter()
+    .zip(1..)
+    .map(|((description, sql), version)| Migration {
+        version,
+        description,
+        sql,
+        kind: MigrationKind::Up,
+    })
+    .collect()
+}
+
+/// Whether `table` has `column`, for adding columns to tables created by
+/// older versions
+pub(crate) fn column_exists(conn: &Connection, table: &str, column: &str) -> Result<bool, String> {
+    conn.query_row(
+        "SELECT COUNT(*) > 0 FROM pragma_table_info(?1) WHERE name = ?2",
+        [table, column],
+        |row| row.get(0),
+    )
+    .map_err(|e| format!("Failed to read {} columns: {}", table, e))
+}
@@ -0,0 +1,191 @@
+use git2::Repository;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use walkdir::WalkDir;
+
+use crate::{dvc_compat, dvcfile};
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateGroup {
+    pub md5: String,
+    pub size: u64,
+    pub paths: Vec<String>,
+    /// Bytes that would be freed by keeping a single copy
+    pub potential_savings: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DuplicateReport {
+    pub groups: Vec<DuplicateGroup>,
+    pub files_scanned: usize,
+    pub total_potential_savings: u64,
+}
+
+struct Candidate {
+    path: String,
+    absolute: PathBuf,
+    size: u64,
+    md5: Option<String>,
+}
+
+/// Collects DVC-tracked files with the hashes already recorded in their pointers
+/// (and directory manifests), returning the tracked data roots so the workspace
+/// walk can skip them.
+fn collect_tracked(repo_root: &Path, candidates: &mut Vec<Candidate>) -> HashSet<PathBuf> {
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let mut tracked_roots = HashSet::new();
+
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        let Ok(dvc_file) = dvcfile::read_dvc_file(&pointer) else {
+            continue;
+        };
+        let base_dir = pointer.parent().unwrap_or(repo_root).to_path_buf();
+
+        for out in &dvc_file.outs {
+            let data_path = base_dir.join(&out.path);
+            let Some(md5) = out.md5.clone() else {
+                continue;
+            };
+            tracked_roots.insert(data_path.clone());
+
+            if out.is_dir() {
+                let Ok(manifest) = dvcfile::read_dir_manifest(&cache_dir, &md5) else {
+                    continue;
+                };
+                for entry in manifest {
+                    let absolute = data_path.join(&entry.relpath);
+                    let object = dvcfile::cache_object_path(&cache_dir, &entry.md5);
+                    let size = fs::metadata(&absolute)
+                        .or_else(|_| fs::metadata(&object))
+                        .map(|m| m.len())
+                        .unwrap_or(0);
+                    candidates.push(Candidate {
+                        path: dvcfile::to_git_path(
+                            absolute.strip_prefix(repo_root).unwrap_or(&absolute),
+                        ),
+                        absolute,
+                        size,
+                        md5: Some(entry.md5),
+                    });
+                }
+            } else {
+                candidates.push(Candidate {
+                    path: dvcfile::to_git_path(
+                        data_path.strip_prefix(repo_root).unwrap_or(&data_path),
+                    ),
+                    size: out.size.unwrap_or(0),
+                    absolute: data_path,
+                    md5: Some(md5),
+                });
+            }
+        }
+    }
+
+    tracked_roots
+}
+
+/// Reports files with identical content across the repository. DVC-tracked data
+/// reuses the md5s from pointers and the cache; other files are only hashed when
+/// another file has the same size.
+#[command]
+pub fn find_duplicate_files(
+    repo_path: String,
+    min_size: Option<u64>,
+) -> Result<DuplicateReport, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let min_size = min_size.unwrap_or(1);
+
+    let mut candidates = Vec::new();
+    let tracked_roots = collect_tracked(&repo_root, &mut candidates);
+
+    let walker = WalkDir::new(&repo_root).into_iter().filter_entry(|entry| {
+        let name = entry.file_name().to_string_lossy();
+        if entry.file_type().is_dir() && (name == ".git" || name == ".dvc") {
+            return false;
+        }
+        !tracked_roots.contains(entry.path())
+    });
+
+    for entry in walker.filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        if entry.path().extension().and_then(|e| e.to_str()) == Some("dvc") {
+            continue;
+        }
+        let size = entry.metadata().map(|m| m.len()).unwrap_or(0);
+        candidates.push(Candidate {
+            path: dvcfile::to_git_path(
+                entry
+                    .path()
+                    .strip_prefix(&repo_root)
+                    .unwrap_or(entry.path()),
+            ),
+            absolute: entry.path().to_path_buf(),
+            size,
+            md5: None,
+        });
+    }
+
+    let files_scanned = candidates.len();
+
+    // Only files sharing a size can share content
+    let mut by_size: HashMap<u64, Vec<Candidate>> = HashMap::new();
+    for candidate in candidates.into_iter().filter(|c| c.size >= min_size) {
+        by_size.entry(candidate.size).or_default().push(candidate);
+    }
+
+    // Hashed like DVC would, so untracked files compare with pointer md5s
+    let format = dvc_compat::repo_format(&repo_root);
+    let mut by_hash: HashMap<String, (u64, Vec<String>)> = HashMap::new();
+    for (size, group) in by_size {
+        if group.len() < 2 {
+            continue;
+        }
+        for candidate in group {
+            let md5 = match candidate.md5 {
+                Some(md5) => md5,
+                None => match dvcfile::hash_file_for(&candidate.absolute, format) {
+                    Ok(md5) => md5,
+                    Err(_) => continue,
+                },
+            };
+            by_hash
+                .entry(md5)
+                .or_insert_with(|| (size, Vec::new()))
+                .1
+                .push(candidate.path);
+        }
+    }
+
+    let mut groups: Vec<DuplicateGroup> = by_hash
+        .into_iter()
+        .filter(|(_, (_, paths))| paths.len() > 1)
+        .map(|(md5, (size, mut paths))| {
+            paths.sort();
+            DuplicateGroup {
+                potential_savings: size * (paths.len() as u64 - 1),
+                md5,
+                size,
+                paths,
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| b.potential_savings.cmp(&a.potential_savings));
+
+    let total_potential_savings = groups.iter().map(|g| g.potential_savings).sum();
+
+    Ok(DuplicateReport {
+        groups,
+        files_scanned,
+        total_potential_savings,
+    })
+}
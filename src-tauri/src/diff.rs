@@ -0,0 +1,203 @@
+use git2::{Delta, Diff, DiffFindOptions, Patch, Repository, Tree};
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{dvcfile, file_type, repo_manager};
+
+/// Line counts of one changed file
+#[derive(Debug, Serialize)]
+pub struct FileDiffStat {
+    pub path: String,
+    /// Path before a rename
+    pub old_path: Option<String>,
+    /// "added", "modified", "removed" or "renamed"
+    pub change: String,
+    pub insertions: usize,
+    pub deletions: usize,
+    /// Binary files have no line counts
+    pub binary: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiffStats {
+    pub files: Vec<FileDiffStat>,
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub binary_files: usize,
+    /// Changed binary files that would be better tracked with DVC
+    pub dvc_hints: Vec<BinaryChangeHint>,
+}
+
+/// Binary file changed in git rather than tracked with DVC, for a "track
+/// with DVC instead?" prompt
+#[derive(Debug, Serialize)]
+pub struct BinaryChangeHint {
+    pub path: String,
+    pub size: u64,
+    /// Category from `file_type::detect_content`, e.g. "image" or "model"
+    pub file_type: String,
+}
+
+fn change_name(delta: Delta) -> &'static str {
+    match delta {
+        Delta::Added | Delta::Copied => "added",
+        Delta::Deleted => "removed",
+        Delta::Renamed => "renamed",
+        _ => "modified",
+    }
+}
+
+/// Whether a `.dvc` pointer tracks the path or one of its parent directories
+fn dvc_tracked(repo_root: &Path, path: &str) -> bool {
+    repo_root
+        .join(path)
+        .ancestors()
+        .take_while(|ancestor| *ancestor != repo_root)
+        .any(|ancestor| dvcfile::pointer_path(ancestor).exists())
+}
+
+fn hint(repo_root: &Path, path: &str, head: &[u8], size: u64) -> Option<BinaryChangeHint> {
+    if dvc_tracked(repo_root, path) {
+        return None;
+    }
+    let file_type = file_type::detect_content(head, Path::new(path));
+    Some(BinaryChangeHint {
+        path: path.to_string(),
+        size,
+        file_type: if file_type.binary {
+            file_type.category
+        } else {
+            "binary".to_string()
+        },
+    })
+}
+
+/// Hint for a working tree file git would store as binary, unless DVC tracks
+/// it already
+pub(crate) fn binary_hint(repo_root: &Path, path: &str) -> Option<BinaryChangeHint> {
+    let full_path = repo_root.join(path);
+    let metadata = fs::metadata(&full_path).ok().filter(|m| m.is_file())?;
+    let head = file_type::read_head(&full_path)?;
+    if !file_type::looks_binary(&head) {
+        return None;
+    }
+    hint(repo_root, path, &head, metadata.len())
+}
+
+/// Hint for a file the diff flagged as binary, read from the new blob or, for
+/// working tree changes, from disk
+fn diff_hint(repo: &Repository, delta: &git2::DiffDelta, path: &str) -> Option<BinaryChangeHint> {
+    let repo_root = repo.workdir()?;
+    let blob = Some(delta.new_file().id())
+        .filter(|id| !id.is_zero())
+        .and_then(|id| repo.find_blob(id).ok());
+    match blob {
+        Some(blob) => hint(repo_root, path, blob.content(), blob.size() as u64),
+        None => binary_hint(repo_root, path)
+            .or_else(|| hint(repo_root, path, &[], delta.new_file().size())),
+    }
+}
+
+fn tree<'a>(repo: &'a Repository, spec: &str) -> Result<Tree<'a>, String> {
+    repo.revparse_single(spec)
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|e| format!("Failed to find {}: {}", spec, e))
+}
+
+/// Diff between two revisions. Without `to_ref` the working tree is compared,
+/// staged or not; without `from_ref` the comparison starts at the parent of
+/// `to_ref` (nothing for a root commit) or at HEAD for the working tree.
+fn diff<'a>(
+    repo: &'a Repository,
+    from_ref: Option<&str>,
+    to_ref: Option<&str>,
+) -> Result<Diff<'a>, String> {
+    let old_tree = match (from_ref, to_ref) {
+        (Some(from_ref), _) => Some(tree(repo, from_ref)?),
+        (None, Some(to_ref)) => repo
+            .revparse_single(to_ref)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("Failed to find {}: {}", to_ref, e))?
+            .parents()
+            .next()
+            .map(|parent| parent.tree())
+            .transpose()
+            .map_err(|e| format!("Failed to read parent tree: {}", e))?,
+        (None, None) => repo.head().and_then(|head| head.peel_to_tree()).ok(),
+    };
+    let mut diff = match to_ref {
+        Some(to_ref) => repo.diff_tree_to_tree(old_tree.as_ref(), Some(&tree(repo, to_ref)?), None),
+        None => repo.diff_tree_to_workdir_with_index(old_tree.as_ref(), None),
+    }
+    .map_err(|e| format!("Failed to create diff: {}", e))?;
+    diff.find_similar(Some(DiffFindOptions::new().renames(true)))
+        .map_err(|e| format!("Failed to detect renames: {}", e))?;
+    Ok(diff)
+}
+
+fn stats(repo: &Repository, diff: &Diff) -> Result<DiffStats, String> {
+    let mut files = Vec::new();
+    let mut dvc_hints = Vec::new();
+    for index in 0..diff.deltas().len() {
+        let delta = diff.get_delta(index).ok_or("Missing diff entry")?;
+        let patch =
+            Patch::from_diff(diff, index).map_err(|e| format!("Failed to read diff: {}", e))?;
+        let path = |file: git2::DiffFile| file.path().map(|p| p.to_string_lossy().to_string());
+        let new_path = path(delta.new_file()).or_else(|| path(delta.old_file()));
+        let old_path = (delta.status() == Delta::Renamed)
+            .then(|| path(delta.old_file()))
+            .flatten();
+        // Binary flags are only known once the patch loaded the content
+        let binary = patch
+            .as_ref()
+            .is_none_or(|patch| patch.delta().flags().is_binary());
+        let (insertions, deletions) = match &patch {
+            Some(patch) if !binary => patch
+                .line_stats()
+                .map(|(_, insertions, deletions)| (insertions, deletions))
+                .map_err(|e| format!("Failed to count lines: {}", e))?,
+            _ => (0, 0),
+        };
+        let path = new_path.unwrap_or_default();
+        if binary && delta.status() != Delta::Deleted {
+            dvc_hints.extend(diff_hint(repo, &delta, &path));
+        }
+        files.push(FileDiffStat {
+            path,
+            old_path,
+            change: change_name(delta.status()).to_string(),
+            insertions,
+            deletions,
+            binary,
+        });
+    }
+
+    Ok(DiffStats {
+        files_changed: files.len(),
+        insertions: files.iter().map(|file| file.insertions).sum(),
+        deletions: files.iter().map(|file| file.deletions).sum(),
+        binary_files: files.iter().filter(|file| file.binary).count(),
+        files,
+        dvc_hints,
+    })
+}
+
+/// Per-file insertions and deletions between two revisions, with totals, for
+/// change summaries that don't need the full patch. Binary files not tracked
+/// with DVC come with a hint to track them with DVC instead.
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_diff_stats(
+    app_handle: AppHandle,
+    repo_path: String,
+    from_ref: Option<String>,
+    to_ref: Option<String>,
+) -> Result<DiffStats, String> {
+    repo_manager::read(&app_handle, &repo_path, |repo| {
+        stats(repo, &diff(repo, from_ref.as_deref(), to_ref.as_deref())?)
+    })
+}
@@ -1,186 +1,244 @@
+use git2::IndexAddOption;
 use git2::Repository;
 use git2::Signature;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::path::Path;
 use std::process::Command;
+use std::time::Instant;
 use tauri::command;
 use tauri::AppHandle;
-use tauri::Manager;
+use tracing::{debug, info, instrument, warn};
+
+use crate::audit;
+use crate::dvc_compat;
+use crate::dvc_undo::{self, AddJournal};
+use crate::dvcfile;
+use crate::env;
+use crate::identity;
+use crate::metrics;
+use crate::repo_manager;
+use crate::script_locator::ScriptLocator;
+use crate::templates;
+use crate::watchdog::{self, CommandError, FailureKind};
+
+/// Command running a DVC script: its Python source with the interpreter chosen
+/// in the environment settings when there is one, otherwise the pre-built
+/// executable
+fn script_command(app_handle: &AppHandle, script: &str) -> Result<Command, String> {
+    let locator = ScriptLocator::new(app_handle);
+    if let Some(interpreter) = env::configured_interpreter(app_handle) {
+        match locator.locate_source(script) {
+            Some(source) => {
+                debug!(interpreter = %interpreter.display(), source = %source.display(), "Running DVC script with Python");
+                let mut command = Command::new(interpreter);
+                command.arg(source);
+                return Ok(command);
+            }
+            None => warn!(
+                script,
+                "Python source not found, using the pre-built script"
+            ),
+        }
+    }
+    let path = locator.locate(script).map_err(|diagnostic| {
+        warn!(?diagnostic, "DVC script not found");
+        diagnostic.to_string()
+    })?;
+    Ok(Command::new(path))
+}
 
-/// Helper function to find script and venv paths using Tauri's resource system
-fn find_script_path(app_handle: &AppHandle, exe_name: &str) -> Result<std::path::PathBuf, String> {
-    println!("Finding script path for: {}", exe_name);
+/// Identity of the initial commit: the user's, or the app's while the user has
+/// not set one up yet
+fn initial_commit_signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    identity::signature(repo).or_else(|_| {
+        Signature::now("fenn-app", "fenn@app.local")
+            .map_err(|e| format!("Failed to create signature: {}", e))
+    })
+}
 
-    // Determine the appropriate extension based on platform
-    let extension = if cfg!(target_os = "windows") {
-        ".exe"
-    } else {
-        ".bin"
-    };
-    let script_name = if exe_name.ends_with(".exe") {
-        exe_name.replace(".exe", extension)
-    } else if exe_name.ends_with(".bin") {
-        exe_name.replace(".bin", extension)
-    } else {
-        format!("{}{}", exe_name, extension)
-    };
+/// Creates an initial commit holding only `.gitignore` when the repository has
+/// no commits yet. Returns whether a commit was created.
+pub(crate) fn ensure_initial_commit(repo: &Repository, path: &Path) -> Result<bool, String> {
+    if repo.head().is_ok() {
+        return Ok(false);
+    }
 
-    // First, check if we're in development mode (check dvc-scripts in project root)
-    let project_root =
-        std::env::current_dir().map_err(|e| format!("Failed to get current directory: {}", e))?;
-    println!("Project root: {}", project_root.display());
-
-    let scripts_path = project_root.join("dvc-scripts").join(&script_name);
-    println!("Development scripts path: {}", scripts_path.display());
-    if scripts_path.exists() {
-        println!("Development scripts path exists");
-        println!(
-            "Found script in development dvc-scripts: {}",
-            scripts_path.display()
-        );
-        return Ok(scripts_path);
+    // Create empty .gitignore if it doesn't exist
+    let gitignore_path = path.join(".gitignore");
+    if !gitignore_path.exists() {
+        std::fs::write(&gitignore_path, "")
+            .map_err(|e| format!("Failed to create .gitignore: {}", e))?;
     }
-    println!("Development scripts path does not exist");
-
-    // If not found in development, try to get from bundled resources
-    let resource_path = app_handle
-        .path()
-        .resource_dir()
-        .map_err(|e| format!("Failed to get resource directory: {}", e))?;
-
-    let bundled_script_path = resource_path.join("dvc-scripts").join(&script_name);
-    println!("Bundled script path: {}", bundled_script_path.display());
-    if bundled_script_path.exists() {
-        println!(
-            "Found script in bundled resources: {}",
-            bundled_script_path.display()
-        );
-        return Ok(bundled_script_path);
+
+    let sig = initial_commit_signature(repo)?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+    // Only add .gitignore to the initial commit
+    index
+        .add_path(Path::new(".gitignore"))
+        .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+
+    let tree_id = index
+        .write_tree()
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+    // No parents for the first commit
+    repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
+        .map_err(|e| format!("Failed to create initial commit: {}", e))?;
+    Ok(true)
+}
+
+/// Runs `dvc init` in an existing git repository
+pub(crate) fn run_dvc_init(app_handle: &AppHandle, path: &str) -> Result<(), CommandError> {
+    // Initialize DVC using the script
+    let limits = watchdog::limits(app_handle, Path::new(path), "dvc_init_script");
+    let dvc_init = watchdog::output(
+        script_command(app_handle, "dvc_init_script")?
+            .arg("--repo-path")
+            .arg(path)
+            .current_dir(path),
+        "dvc_init_script",
+        &limits,
+    )?;
+
+    if !dvc_init.status.success() {
+        return Err(format!(
+            "DVC init failed: {}",
+            String::from_utf8_lossy(&dvc_init.stderr)
+        )
+        .into());
     }
-    println!("Bundled script path does not exist");
 
-    Err(format!(
-        "Executable '{}' not found in development dvc-scripts or bundled resources",
-        script_name
-    ))
+    Ok(())
 }
 
+/// Initializes git and DVC in a directory, first laying out the files of
+/// `template` (see `list_project_templates`) when one is given
 #[command]
-pub fn init_dvc_project(app_handle: AppHandle, path: &str) -> Result<String, String> {
+pub fn init_dvc_project(
+    app_handle: AppHandle,
+    path: &str,
+    template: Option<String>,
+) -> Result<String, CommandError> {
+    if let Some(template) = &template {
+        templates::validate(&app_handle, template)?;
+    }
+
     // First initialize git repository using git2
     let repo = Repository::init(path)
         .map_err(|e| format!("Failed to initialize git repository: {}", e))?;
 
-    // Create an initial commit if there are no commits yet
-    if repo.head().is_err() {
-        // Create empty .gitignore if it doesn't exist
-        let gitignore_path = Path::new(path).join(".gitignore");
-        if !gitignore_path.exists() {
-            std::fs::write(&gitignore_path, "")
-                .map_err(|e| format!("Failed to create .gitignore: {}", e))?;
+    let failure = FailureKind::default();
+    repo_manager::write(&app_handle, path, || {
+        // The template's .gitignore goes into the initial commit; its other
+        // files are left for the user to commit
+        if let Some(template) = &template {
+            let written = templates::apply(&app_handle, Path::new(path), template)?;
+            info!(template, files = written.len(), "Applied project template");
         }
 
-        let sig = Signature::now("fenn-app", "fenn@app.local")
-            .map_err(|e| format!("Failed to create signature: {}", e))?;
-        let mut index = repo
-            .index()
-            .map_err(|e| format!("Failed to get repository index: {}", e))?;
-
-        // Only add .gitignore to the initial commit
-        index
-            .add_path(Path::new(".gitignore"))
-            .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
-
-        let tree_id = index
-            .write_tree()
-            .map_err(|e| format!("Failed to write tree: {}", e))?;
-        let tree = repo
-            .find_tree(tree_id)
-            .map_err(|e| format!("Failed to find tree: {}", e))?;
-        // No parents for the first commit
-        repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
-            .map_err(|e| format!("Failed to create initial commit: {}", e))?;
-    }
-
-    // Find the exe path using the helper function
-    let exe_path = find_script_path(&app_handle, "dvc_init_script.exe")?;
+        // Create an initial commit if there are no commits yet
+        ensure_initial_commit(&repo, Path::new(path))?;
 
-    // Then initialize DVC using the exe
-    let dvc_init = Command::new(exe_path)
-        .arg("--repo-path")
-        .arg(path)
-        .current_dir(path)
-        .output()
-        .map_err(|e| format!("Failed to run dvc_init_script.exe: {}", e))?;
-
-    if !dvc_init.status.success() {
-        return Err(format!(
-            "DVC init failed: {}",
-            String::from_utf8_lossy(&dvc_init.stderr)
-        ));
-    }
+        // Then initialize DVC
+        run_dvc_init(&app_handle, path).map_err(|e| failure.record(e))
+    })
+    .map_err(|message| failure.error(message))?;
 
     Ok("Successfully initialized Git and DVC repository".to_string())
 }
 
+/// Tracks a file or directory with DVC, recording what it changes so it can be
+/// reverted with `undo_last_dvc_add`
 #[command]
-pub fn add_dvc_file(app_handle: AppHandle, path: &str, file: &str) -> Result<String, String> {
-    println!("Adding DVC file: {}", file);
-    println!("Path: {}", path);
+pub fn add_dvc_file(app_handle: AppHandle, path: &str, file: &str) -> Result<String, CommandError> {
+    let failure = FailureKind::default();
+    audit::track(
+        &app_handle,
+        path,
+        "add_dvc_file",
+        json!({ "file": file }),
+        || {
+            repo_manager::write(&app_handle, path, || {
+                let journal = AddJournal::record(path, &[file.to_string()])?;
+                let result = add_single(&app_handle, path, file).map_err(|e| failure.record(e));
+                dvc_undo::finish(&journal, path, result)
+            })
+        },
+    )
+    .map_err(|message| failure.error(message))
+}
 
-    // Find the exe path using the helper function
-    let exe_path = find_script_path(&app_handle, "dvc_add_script.exe")?;
+/// Tracks several files or directories as one operation: if any of them fails
+/// the others are rolled back, and `undo_last_dvc_add` reverts all of them
+#[command]
+pub fn add_dvc_files(
+    app_handle: AppHandle,
+    path: &str,
+    files: Vec<String>,
+) -> Result<Vec<String>, CommandError> {
+    let failure = FailureKind::default();
+    audit::track(
+        &app_handle,
+        path,
+        "add_dvc_files",
+        json!({ "files": files }),
+        || {
+            repo_manager::write(&app_handle, path, || {
+                let journal = AddJournal::record(path, &files)?;
+                let result = files
+                    .iter()
+                    .map(|file| add_single(&app_handle, path, file).map_err(|e| failure.record(e)))
+                    .collect::<Result<Vec<_>, _>>();
+                dvc_undo::finish(&journal, path, result)
+            })
+        },
+    )
+    .map_err(|message| failure.error(message))
+}
 
-    // Step 1: dvc add <file> using the exe
-    let dvc_add = Command::new(exe_path)
-        .arg(file)
-        .current_dir(path)
-        .output()
-        .map_err(|e| format!("Failed to run dvc_add_script.exe: {}", e))?;
+#[instrument(skip(app_handle), err(Debug))]
+pub(crate) fn add_single(
+    app_handle: &AppHandle,
+    path: &str,
+    file: &str,
+) -> Result<String, CommandError> {
+    // Step 1: dvc add <file> using the script
+    let limits = watchdog::limits(app_handle, Path::new(path), "dvc_add_script");
+    let dvc_add = watchdog::output(
+        script_command(app_handle, "dvc_add_script")?
+            .arg(file)
+            .current_dir(path),
+        "dvc_add_script",
+        &limits,
+    )?;
 
     if !dvc_add.status.success() {
         return Err(format!(
             "DVC add failed: {}",
             String::from_utf8_lossy(&dvc_add.stderr)
-        ));
+        )
+        .into());
     }
 
     // Step 2: git add .gitignore <file>.dvc using git2
+    Ok(stage_pointer(path, file)?)
+}
+
+/// Stages the `.dvc` pointer and `.gitignore` entry `dvc add` wrote for
+/// `file`, first creating an initial commit when the repository has none
+pub(crate) fn stage_pointer(path: &str, file: &str) -> Result<String, String> {
     let repo =
         Repository::open(path).map_err(|e| format!("Failed to open git repository: {}", e))?;
 
     // Ensure there's an initial commit if needed
-    if repo.head().is_err() {
-        println!("No HEAD found, creating initial commit...");
-        // Create empty .gitignore if it doesn't exist
-        let gitignore_path = Path::new(path).join(".gitignore");
-        if !gitignore_path.exists() {
-            std::fs::write(&gitignore_path, "")
-                .map_err(|e| format!("Failed to create .gitignore: {}", e))?;
-        }
-
-        let sig = Signature::now("fenn-app", "fenn@app.local")
-            .map_err(|e| format!("Failed to create signature: {}", e))?;
-        let mut index = repo
-            .index()
-            .map_err(|e| format!("Failed to get repository index: {}", e))?;
-
-        // Only add .gitignore to the initial commit
-        index
-            .add_path(Path::new(".gitignore"))
-            .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
-
-        let tree_id = index
-            .write_tree()
-            .map_err(|e| format!("Failed to write tree: {}", e))?;
-        let tree = repo
-            .find_tree(tree_id)
-            .map_err(|e| format!("Failed to find tree: {}", e))?;
-        // No parents for the first commit
-        repo.commit(Some("HEAD"), &sig, &sig, "Initial commit", &tree, &[])
-            .map_err(|e| format!("Failed to create initial commit: {}", e))?;
-        println!("Initial commit created successfully");
+    if ensure_initial_commit(&repo, Path::new(path))? {
+        info!("No HEAD found, created initial commit");
     }
 
     // Get the repository root path
@@ -188,8 +246,7 @@ pub fn add_dvc_file(app_handle: AppHandle, path: &str, file: &str) -> Result<Str
         .workdir()
         .ok_or_else(|| "Repository has no working directory".to_string())?;
 
-    println!("Repository root: {}", repo_root.display());
-    println!("Original file path: {}", file);
+    debug!(repo_root = %repo_root.display(), "Resolved repository root");
 
     // Convert file path to relative path from repository root
     let file_path = Path::new(file);
@@ -197,41 +254,35 @@ pub fn add_dvc_file(app_handle: AppHandle, path: &str, file: &str) -> Result<Str
         let relative = file_path
             .strip_prefix(repo_root)
             .map_err(|e| format!("Failed to make file path relative: {}", e))?;
-        println!(
-            "Converted absolute path to relative: {}",
-            relative.display()
-        );
         relative
     } else {
-        println!("File path is already relative: {}", file_path.display());
         file_path
     };
 
     // Check if the file already has a .dvc extension
     let dvc_file = if relative_file_path.extension().and_then(|e| e.to_str()) == Some("dvc") {
         // File already has .dvc extension, use it as is
-        println!(
-            "File already has .dvc extension: {}",
-            relative_file_path.display()
-        );
         relative_file_path.to_string_lossy().to_string()
     } else {
         // Add .dvc extension
-        let dvc_path = format!("{}.dvc", relative_file_path.to_string_lossy());
-        println!("Added .dvc extension: {}", dvc_path);
-        dvc_path
+        format!("{}.dvc", relative_file_path.to_string_lossy())
     };
+    debug!(dvc_file = %dvc_file, "Staging DVC pointer file");
 
-    println!("Final DVC file path to add: {}", dvc_file);
-
-    // Add .gitignore to index
+    // Add the .gitignore DVC wrote next to the data to the index
     let mut index = repo
         .index()
         .map_err(|e| format!("Failed to get repository index: {}", e))?;
 
-    index
-        .add_path(Path::new(".gitignore"))
-        .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+    let gitignore = relative_file_path
+        .parent()
+        .unwrap_or_else(|| Path::new(""))
+        .join(".gitignore");
+    if repo_root.join(&gitignore).exists() {
+        index
+            .add_path(&gitignore)
+            .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+    }
 
     // Add .dvc file to index using relative path
     index
@@ -249,55 +300,247 @@ pub fn add_dvc_file(app_handle: AppHandle, path: &str, file: &str) -> Result<Str
     ))
 }
 
-pub fn dvc_diff(app_handle: &AppHandle, path: &Path) -> Result<HashMap<String, String>, String> {
-    println!("dvc_diff: {}", path.display());
-
-    // Find the exe path using the helper function
-    let exe_path = find_script_path(app_handle, "dvc_diff_script.exe")?;
-
-    println!("Using exe path: {}", exe_path.display());
-
-    // Run the exe
-    let output = Command::new(exe_path)
-        .current_dir(path)
-        .output()
-        .map_err(|e| format!("Failed to run dvc_diff_script.exe: {}", e))?;
+#[instrument(skip(app_handle), err(Debug))]
+pub fn dvc_diff(
+    app_handle: &AppHandle,
+    path: &Path,
+) -> Result<HashMap<String, String>, CommandError> {
+    // Run the script
+    let started = Instant::now();
+    // A hung script must not hold up the status of the whole tree
+    let limits = watchdog::limits(app_handle, path, "dvc_diff_script");
+    let output = watchdog::output(
+        script_command(app_handle, "dvc_diff_script")?.current_dir(path),
+        "dvc_diff_script",
+        &limits,
+    )?;
+    metrics::record(app_handle, path, "dvc_diff", started.elapsed(), None, None);
 
     if !output.status.success() {
-        return Err(String::from_utf8_lossy(&output.stderr).to_string());
+        return Err(String::from_utf8_lossy(&output.stderr).to_string().into());
     }
 
     let stdout = String::from_utf8_lossy(&output.stdout);
     let json: Value = serde_json::from_str(&stdout)
         .map_err(|e| format!("Failed to parse dvc diff JSON: {}", e))?;
 
-    let mut status_map = HashMap::new();
-    let categories = [
-        ("added", "added"),
-        ("deleted", "deleted"),
-        ("modified", "modified"),
-        ("renamed", "renamed"),
-        ("not in cache", "not in cache"),
-    ];
-
-    // Helper function to normalize paths
-    let normalize_path = |p: &str| -> String { Path::new(p).to_string_lossy().replace('\\', "/") };
-
-    for (cat_key, status) in &categories {
-        if let Some(arr) = json.get(*cat_key).and_then(|v| v.as_array()) {
-            for entry in arr {
-                // For 'renamed', DVC gives objects with 'path' and 'path_old'. For others, just 'path'.
-                if *cat_key == "renamed" {
-                    if let Some(path) = entry.get("path").and_then(|v| v.as_str()) {
-                        status_map.insert(normalize_path(path), status.to_string());
-                    }
-                } else {
-                    if let Some(path) = entry.get("path").and_then(|v| v.as_str()) {
-                        status_map.insert(normalize_path(path), status.to_string());
-                    }
-                }
-            }
+    Ok(dvc_compat::parse_diff(&json)?)
+}
+
+/// Reverse of `add_dvc_file`: stops DVC tracking a file or directory and
+/// stages the resulting git changes
+#[command]
+pub fn remove_dvc_file(
+    app_handle: AppHandle,
+    repo_path: &str,
+    file: &str,
+    keep_data: bool,
+) -> Result<String, String> {
+    audit::track(
+        &app_handle,
+        repo_path,
+        "remove_dvc_file",
+        json!({ "file": file, "keep_data": keep_data }),
+        || {
+            repo_manager::write(&app_handle, repo_path, || {
+                remove_tracking(repo_path, file, keep_data)
+            })
+        },
+    )
+}
+
+/// Untracks `file`, either repository relative or absolute, in the repository
+/// at `repo_path`
+pub(crate) fn remove_tracking(
+    repo_path: &str,
+    file: &str,
+    keep_data: bool,
+) -> Result<String, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open git repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or_else(|| "Repository has no working directory".to_string())?
+        .to_path_buf();
+
+    let relative_data_path = dvcfile::repo_relative_path(&repo_root, Path::new(file))?;
+    let absolute_data_path = repo_root.join(&relative_data_path);
+    let absolute_pointer = dvcfile::pointer_path(&absolute_data_path);
+
+    if !absolute_pointer.exists() {
+        return Err(format!(
+            "{} is not tracked by DVC",
+            relative_data_path.display()
+        ));
+    }
+
+    std::fs::remove_file(&absolute_pointer)
+        .map_err(|e| format!("Failed to remove {}: {}", absolute_pointer.display(), e))?;
+    let gitignore = dvcfile::remove_gitignore_entry(&absolute_data_path)?;
+
+    if !keep_data && absolute_data_path.exists() {
+        let removed = if absolute_data_path.is_dir() {
+            std::fs::remove_dir_all(&absolute_data_path)
+        } else {
+            std::fs::remove_file(&absolute_data_path)
+        };
+        removed.map_err(|e| format!("Failed to remove data: {}", e))?;
+    }
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+    let relative_pointer = dvcfile::pointer_path(&relative_data_path);
+    if index.get_path(&relative_pointer, 0).is_some() {
+        index
+            .remove_path(&relative_pointer)
+            .map_err(|e| format!("Failed to unstage {}: {}", relative_pointer.display(), e))?;
+    }
+
+    if let Some(gitignore) = gitignore {
+        let relative_gitignore = dvcfile::repo_relative_path(&repo_root, &gitignore)?;
+        if gitignore.exists() {
+            index
+                .add_path(&relative_gitignore)
+                .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+        } else if index.get_path(&relative_gitignore, 0).is_some() {
+            index
+                .remove_path(&relative_gitignore)
+                .map_err(|e| format!("Failed to remove .gitignore from index: {}", e))?;
+        }
+    }
+
+    // Hand the data back to git when the user wants to keep it
+    if keep_data && absolute_data_path.exists() {
+        let pathspec = dvcfile::to_git_path(&relative_data_path);
+        index
+            .add_all([pathspec.as_str()].iter(), IndexAddOption::DEFAULT, None)
+            .map_err(|e| format!("Failed to add {} to index: {}", pathspec, e))?;
+    }
+
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    Ok(format!(
+        "Stopped DVC tracking for {}",
+        relative_data_path.display()
+    ))
+}
+
+/// Moves a DVC-tracked file or directory, rewriting its pointer and ignore
+/// entries and staging the git side of the rename
+#[command]
+pub fn move_tracked_file(
+    app_handle: AppHandle,
+    repo_path: &str,
+    old_path: &str,
+    new_path: &str,
+) -> Result<String, String> {
+    audit::track(
+        &app_handle,
+        repo_path,
+        "move_tracked_file",
+        json!({ "old_path": old_path, "new_path": new_path }),
+        || {
+            repo_manager::write(&app_handle, repo_path, || {
+                move_tracked(repo_path, old_path, new_path)
+            })
+        },
+    )
+}
+
+/// Moves `old_path` to `new_path`, both repository relative or absolute, in
+/// the repository at `repo_path`
+fn move_tracked(repo_path: &str, old_path: &str, new_path: &str) -> Result<String, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open git repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or_else(|| "Repository has no working directory".to_string())?
+        .to_path_buf();
+
+    let relative_old = dvcfile::repo_relative_path(&repo_root, Path::new(old_path))?;
+    let relative_new = dvcfile::repo_relative_path(&repo_root, Path::new(new_path))?;
+    let absolute_old = repo_root.join(&relative_old);
+    let absolute_new = repo_root.join(&relative_new);
+
+    let old_pointer = dvcfile::pointer_path(&absolute_old);
+    let new_pointer = dvcfile::pointer_path(&absolute_new);
+
+    if !old_pointer.exists() {
+        return Err(format!("{} is not tracked by DVC", relative_old.display()));
+    }
+    if absolute_new.exists() || new_pointer.exists() {
+        return Err(format!("{} already exists", relative_new.display()));
+    }
+
+    let new_name = absolute_new
+        .file_name()
+        .ok_or_else(|| format!("Invalid destination path: {}", new_path))?
+        .to_string_lossy()
+        .to_string();
+
+    // Rewrite the pointer before touching the data so a parse failure leaves everything as is
+    let mut dvc_file = dvcfile::read_dvc_file(&old_pointer)?;
+    for out in dvc_file.outs.iter_mut() {
+        out.path = new_name.clone();
+    }
+
+    if let Some(parent) = absolute_new.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create destination directory: {}", e))?;
+    }
+    if absolute_old.exists() {
+        std::fs::rename(&absolute_old, &absolute_new)
+            .map_err(|e| format!("Failed to move data: {}", e))?;
+    }
+
+    dvcfile::write_dvc_file(&new_pointer, &dvc_file)?;
+    std::fs::remove_file(&old_pointer)
+        .map_err(|e| format!("Failed to remove {}: {}", old_pointer.display(), e))?;
+
+    let old_gitignore = dvcfile::remove_gitignore_entry(&absolute_old)?;
+    let new_gitignore = dvcfile::add_gitignore_entry(&absolute_new)?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+    let relative_old_pointer = dvcfile::pointer_path(&relative_old);
+    if index.get_path(&relative_old_pointer, 0).is_some() {
+        index
+            .remove_path(&relative_old_pointer)
+            .map_err(|e| format!("Failed to unstage old pointer: {}", e))?;
+    }
+    index
+        .add_path(&dvcfile::pointer_path(&relative_new))
+        .map_err(|e| format!("Failed to add new pointer to index: {}", e))?;
+
+    for gitignore in old_gitignore
+        .into_iter()
+        .chain(std::iter::once(new_gitignore))
+    {
+        let relative_gitignore = dvcfile::repo_relative_path(&repo_root, &gitignore)?;
+        if gitignore.exists() {
+            index
+                .add_path(&relative_gitignore)
+                .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+        } else if index.get_path(&relative_gitignore, 0).is_some() {
+            index
+                .remove_path(&relative_gitignore)
+                .map_err(|e| format!("Failed to remove .gitignore from index: {}", e))?;
         }
     }
-    Ok(status_map)
+
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    Ok(format!(
+        "Moved {} to {}",
+        relative_old.display(),
+        relative_new.display()
+    ))
 }
@@ -0,0 +1,150 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::{debug, instrument};
+
+use crate::{dvcfile, env, script_locator, transfer};
+
+/// Categories of `dvc diff --json`; later ones win when a path is listed twice
+const DIFF_CATEGORIES: &[&str] = &["added", "deleted", "modified", "renamed", "not in cache"];
+
+/// On-disk format of a DVC repository. DVC 3 keeps cache objects under
+/// `files/md5` and records `hash: md5` in pointers; DVC 2 does neither.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DvcFormat {
+    V2,
+    V3,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DvcVersionInfo {
+    /// Version of the DVC the app runs, `None` when it cannot be determined
+    pub version: Option<String>,
+    pub major: Option<u32>,
+    /// "interpreter" for the configured Python environment, "bundled" for the
+    /// pre-built scripts
+    pub source: String,
+    /// Format of the repository's cache and pointers, `None` while it holds
+    /// no data
+    pub repo_format: Option<DvcFormat>,
+    /// False when this DVC cannot read the repository, e.g. DVC 2 with a
+    /// DVC 3 cache
+    pub compatible: bool,
+}
+
+pub(crate) fn major_version(version: &str) -> Option<u32> {
+    version.split('.').next()?.trim().parse().ok()
+}
+
+/// Layout of a cache directory, `None` while it is empty
+pub(crate) fn cache_format(cache_dir: &Path) -> Option<DvcFormat> {
+    if cache_dir.join("files").join("md5").is_dir() {
+        return Some(DvcFormat::V3);
+    }
+    let entries = fs::read_dir(cache_dir).ok()?;
+    let legacy = entries.flatten().any(|entry| {
+        let name = entry.file_name();
+        let name = name.to_string_lossy();
+        name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()) && entry.path().is_dir()
+    });
+    legacy.then_some(DvcFormat::V2)
+}
+
+/// Format of a repository, from its cache or else its pointer files
+pub(crate) fn repo_format(repo_root: &Path) -> Option<DvcFormat> {
+    if let Some(format) = cache_format(&dvcfile::cache_dir(repo_root)) {
+        return Some(format);
+    }
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        let Ok(dvc_file) = dvcfile::read_dvc_file(&pointer) else {
+            continue;
+        };
+        if let Some(out) = dvc_file.outs.iter().find(|out| out.md5.is_some()) {
+            return Some(match out.hash {
+                Some(_) => DvcFormat::V3,
+                None => DvcFormat::V2,
+            });
+        }
+    }
+    None
+}
+
+/// Path an entry of `dvc diff --json` refers to. Renames carry
+/// `{"old": ..., "new": ...}` and report the new path; everything else has
+/// the path as a string.
+fn diff_entry_path(entry: &Value) -> Option<&str> {
+    match entry.get("path")? {
+        Value::String(path) => Some(path.as_str()),
+        Value::Object(paths) => paths.get("new").and_then(Value::as_str),
+        _ => None,
+    }
+}
+
+/// Maps the paths in `dvc diff --json` output to their category. DVC 2
+/// omits empty categories and older releases only know some of them, so
+/// missing ones are skipped; entries of an unknown shape are an error rather
+/// than being dropped.
+pub(crate) fn parse_diff(json: &Value) -> Result<HashMap<String, String>, String> {
+    let categories = json
+        .as_object()
+        .ok_or("Unexpected dvc diff output: not a JSON object")?;
+    for category in categories.keys() {
+        if !DIFF_CATEGORIES.contains(&category.as_str()) {
+            debug!(category = %category, "Ignoring unknown dvc diff category");
+        }
+    }
+
+    let mut status_map = HashMap::new();
+    for category in DIFF_CATEGORIES {
+        let Some(entries) = categories.get(*category) else {
+            continue;
+        };
+        let entries = entries
+            .as_array()
+            .ok_or_else(|| format!("Unexpected dvc diff output: {} is not a list", category))?;
+        for entry in entries {
+            let path = diff_entry_path(entry)
+                .ok_or_else(|| format!("Unexpected dvc diff entry in {}: {}", category, entry))?;
+            status_map.insert(path.replace('\\', "/"), category.to_string());
+        }
+    }
+    Ok(status_map)
+}
+
+/// DVC version the app runs for a repository and whether it can work with
+/// the repository's format
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_dvc_version(app_handle: AppHandle, repo_path: String) -> Result<DvcVersionInfo, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+
+    let (version, source) = match env::configured_interpreter(&app_handle) {
+        Some(interpreter) => (
+            env::probe("configured", &interpreter).and_then(|env| env.dvc_version),
+            "interpreter",
+        ),
+        None => (
+            script_locator::bundled_dvc_version().map(str::to_string),
+            "bundled",
+        ),
+    };
+    let major = version.as_deref().and_then(major_version);
+    let repo_format = repo_format(&repo_root);
+    let compatible = match (major, repo_format) {
+        (Some(major), _) if major < 2 => false,
+        (Some(2), Some(DvcFormat::V3)) => false,
+        _ => true,
+    };
+
+    Ok(DvcVersionInfo {
+        version,
+        major,
+        source: source.to_string(),
+        repo_format,
+        compatible,
+    })
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// An INI-style DVC config file (`.dvc/config` or `.dvc/config.local`).
+/// Section names are stored unquoted, e.g. `core` or `remote "storage"`.
+#[derive(Debug, Clone, Default)]
+pub struct DvcConfig {
+    pub sections: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RemoteConfig {
+    pub name: String,
+    pub url: String,
+    pub options: BTreeMap<String, String>,
+}
+
+impl RemoteConfig {
+    pub fn option(&self, key: &str) -> Option<&str> {
+        self.options.get(key).map(|s| s.as_str())
+    }
+}
+
+pub fn config_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".dvc").join("config")
+}
+
+pub fn local_config_path(repo_root: &Path) -> PathBuf {
+    repo_root.join(".dvc").join("config.local")
+}
+
+impl DvcConfig {
+    pub fn parse(content: &str) -> Self {
+        let mut config = DvcConfig::default();
+        let mut current: Option<String> = None;
+
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') && line.ends_with(']') {
+                let name = line[1..line.len() - 1]
+                    .trim()
+                    .trim_matches('\'')
+                    .to_string();
+                config.sections.entry(name.clone()).or_default();
+                current = Some(name);
+            } else if let (Some(section), Some((key, value))) = (&current, line.split_once('=')) {
+                config
+                    .sections
+                    .entry(section.clone())
+                    .or_default()
+                    .insert(key.trim().to_string(), value.trim().to_string());
+            }
+        }
+
+        config
+    }
+
+    pub fn read(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(DvcConfig::default());
+        }
+        let content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(Self::parse(&content))
+    }
+
+    pub fn write(&self, path: &Path) -> Result<(), String> {
+        let mut content = String::new();
+        for (section, values) in &self.sections {
+            if section.contains('"') {
+                content.push_str(&format!("['{}']\n", section));
+            } else {
+                content.push_str(&format!("[{}]\n", section));
+            }
+            for (key, value) in values {
+                content.push_str(&format!("    {} = {}\n", key, value));
+            }
+        }
+        fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.sections
+            .get(section)
+            .and_then(|values| values.get(key))
+            .map(|s| s.as_str())
+    }
+
+    pub fn set(&mut self, section: &str, key: &str, value: &str) {
+        self.sections
+            .entry(section.to_string())
+            .or_default()
+            .insert(key.to_string(), value.to_string());
+    }
+
+    pub fn unset(&mut self, section: &str, key: &str) {
+        if let Some(values) = self.sections.get_mut(section) {
+            values.remove(key);
+            if values.is_empty() {
+                self.sections.remove(section);
+            }
+        }
+    }
+
+    /// Overlays another config on top of this one, as `config.local` does
+    fn merge(&mut self, other: DvcConfig) {
+        for (section, values) in other.sections {
+            self.sections.entry(section).or_default().extend(values);
+        }
+    }
+
+    /// Names of all configured remotes
+    pub fn remote_names(&self) -> Vec<String> {
+        self.sections
+            .keys()
+            .filter_map(|section| {
+                section
+                    .strip_prefix("remote \"")
+                    .and_then(|rest| rest.strip_suffix('"'))
+                    .map(|name| name.to_string())
+            })
+            .collect()
+    }
+}
+
+/// Reads `.dvc/config` with `.dvc/config.local` (where DVC keeps credentials) layered on top
+pub fn load(repo_root: &Path) -> Result<DvcConfig, String> {
+    let mut config = DvcConfig::read(&config_path(repo_root))?;
+    config.merge(DvcConfig::read(&local_config_path(repo_root))?);
+    Ok(config)
+}
+
+/// Resolves a named remote, or the `core.remote` default when no name is given
+pub fn remote(repo_root: &Path, name: Option<&str>) -> Result<RemoteConfig, String> {
+    let config = load(repo_root)?;
+    let name = match name {
+        Some(name) => name.to_string(),
+        None => config
+            .get("core", "remote")
+            .ok_or("No default DVC remote configured")?
+            .to_string(),
+    };
+
+    let options = config
+        .sections
+        .get(&format!("remote \"{}\"", name))
+        .cloned()
+        .ok_or_else(|| format!("DVC remote '{}' is not configured", name))?;
+    let mut url = options
+        .get("url")
+        .cloned()
+        .ok_or_else(|| format!("DVC remote '{}' has no url", name))?;
+
+    // DVC resolves relative local remotes against the `.dvc` directory
+    if !url.contains("://") && Path::new(&url).is_relative() {
+        url = repo_root
+            .join(".dvc")
+            .join(&url)
+            .to_string_lossy()
+            .to_string();
+    }
+
+    Ok(RemoteConfig { name, url, options })
+}
@@ -0,0 +1,381 @@
+use git2::{IndexEntry, Oid, Repository};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::checkout::{self, CheckoutSummary};
+use crate::{audit, conflicts, dvcfile, repo_manager};
+
+/// One side of a conflicted `.dvc` pointer
+#[derive(Debug, Serialize)]
+pub struct DataVersion {
+    pub blob_id: String,
+    pub md5: Option<String>,
+    pub size: Option<u64>,
+    /// Number of files for directory outputs
+    pub nfiles: Option<u64>,
+    /// Commit the version comes from; `None` for the common ancestor
+    pub commit_id: Option<String>,
+    /// RFC 3339 time of that commit
+    pub commit_date: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DvcPointerConflict {
+    pub path: String,
+    /// `None` when the pointer doesn't exist on that side
+    pub ancestor: Option<DataVersion>,
+    pub ours: Option<DataVersion>,
+    pub theirs: Option<DataVersion>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DvcResolution {
+    Ours,
+    Theirs,
+    /// Keeps our version in place and their version under a renamed output
+    KeepBoth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DvcResolutionResult {
+    /// Pointer files staged by the resolution
+    pub pointers: Vec<String>,
+    pub checkout: CheckoutSummary,
+    /// Conflicts left before the merge can be committed
+    pub remaining_conflicts: usize,
+}
+
+fn open_repo(repo_path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo =
+        Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    Ok((repo, repo_root))
+}
+
+/// The commit being brought in by the merge, cherry-pick or revert in progress
+fn their_commit(repo: &Repository) -> Option<git2::Commit<'_>> {
+    ["MERGE_HEAD", "CHERRY_PICK_HEAD", "REVERT_HEAD"]
+        .into_iter()
+        .find_map(|name| repo.revparse_single(name).ok())
+        .and_then(|object| object.peel_to_commit().ok())
+}
+
+fn commit_date(commit: &git2::Commit) -> Option<String> {
+    chrono::DateTime::from_timestamp(commit.time().seconds(), 0).map(|time| time.to_rfc3339())
+}
+
+fn blob_content(repo: &Repository, id: Oid) -> Result<String, String> {
+    let blob = repo
+        .find_blob(id)
+        .map_err(|e| format!("Failed to read blob {}: {}", id, e))?;
+    Ok(String::from_utf8_lossy(blob.content()).to_string())
+}
+
+fn data_version(
+    repo: &Repository,
+    entry: &Option<IndexEntry>,
+    commit: Option<&git2::Commit>,
+) -> Result<Option<DataVersion>, String> {
+    let Some(entry) = entry else {
+        return Ok(None);
+    };
+    let dvc_file = dvcfile::parse_dvc_file(&blob_content(repo, entry.id)?)?;
+    let out = dvc_file.outs.first();
+
+    Ok(Some(DataVersion {
+        blob_id: entry.id.to_string(),
+        md5: out.and_then(|o| o.md5.clone()),
+        size: out.and_then(|o| o.size),
+        nfiles: out.and_then(|o| o.nfiles),
+        commit_id: commit.map(|c| c.id().to_string()),
+        commit_date: commit.and_then(commit_date),
+    }))
+}
+
+/// Name of the output kept for their side: `data.csv` becomes
+/// `data-theirs.csv` and a directory `images` becomes `images-theirs`
+fn renamed_output(path: &str) -> String {
+    let path = Path::new(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let name = match path.extension() {
+        Some(ext) => format!("{}-theirs.{}", stem, ext.to_string_lossy()),
+        None => format!("{}-theirs", stem),
+    };
+    dvcfile::to_git_path(&path.with_file_name(name))
+}
+
+/// `.dvc` pointer conflicts of the operation in progress, with the data
+/// version each side points to
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn list_dvc_conflicts(repo_path: String) -> Result<Vec<DvcPointerConflict>, String> {
+    let (repo, _) = open_repo(&repo_path)?;
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let ours = repo.head().ok().and_then(|head| head.peel_to_commit().ok());
+    let theirs = their_commit(&repo);
+
+    let mut result = Vec::new();
+    for conflict in index
+        .conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?
+    {
+        let conflict = conflict.map_err(|e| format!("Failed to read conflict: {}", e))?;
+        let Some(path) = [&conflict.our, &conflict.their, &conflict.ancestor]
+            .into_iter()
+            .flatten()
+            .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+            .next()
+        else {
+            continue;
+        };
+        if !path.ends_with(".dvc") {
+            continue;
+        }
+
+        result.push(DvcPointerConflict {
+            ancestor: data_version(&repo, &conflict.ancestor, None)?,
+            ours: data_version(&repo, &conflict.our, ours.as_ref())?,
+            theirs: data_version(&repo, &conflict.their, theirs.as_ref())?,
+            path,
+        });
+    }
+    Ok(result)
+}
+
+/// Writes one side of a conflicted pointer to the workspace, or removes the
+/// pointer when that side deleted it
+fn take_side(
+    repo: &Repository,
+    repo_root: &Path,
+    path: &str,
+    id: Option<Oid>,
+) -> Result<(), String> {
+    let file = repo_root.join(path);
+    match id {
+        Some(id) => fs::write(&file, blob_content(repo, id)?)
+            .map_err(|e| format!("Failed to write {}: {}", path, e)),
+        None if file.exists() => {
+            fs::remove_file(&file).map_err(|e| format!("Failed to remove {}: {}", path, e))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Writes their pointer next to ours with every output renamed, returning the
+/// new pointer's path relative to the repository root
+fn keep_theirs_renamed(
+    repo: &Repository,
+    repo_root: &Path,
+    path: &str,
+    id: Oid,
+) -> Result<String, String> {
+    let mut dvc_file = dvcfile::parse_dvc_file(&blob_content(repo, id)?)?;
+
+    for out in &mut dvc_file.outs {
+        out.path = renamed_output(&out.path);
+    }
+
+    let data_path = path.strip_suffix(".dvc").unwrap_or(path);
+    let new_pointer = format!("{}.dvc", renamed_output(data_path));
+    let new_pointer_path = repo_root.join(&new_pointer);
+    if new_pointer_path.exists() {
+        return Err(format!("{} already exists", new_pointer));
+    }
+    dvcfile::write_dvc_file(&new_pointer_path, &dvc_file)?;
+    Ok(new_pointer)
+}
+
+fn resolve(
+    repo_path: &str,
+    path: &str,
+    resolution: DvcResolution,
+) -> Result<DvcResolutionResult, String> {
+    let (repo, repo_root) = open_repo(repo_path)?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflict = index
+        .conflicts()
+        .map_err(|e| format!("Failed to read conflicts: {}", e))?
+        .filter_map(|c| c.ok())
+        .find(|c| {
+            [&c.our, &c.their, &c.ancestor]
+                .into_iter()
+                .flatten()
+                .any(|entry| entry.path == path.as_bytes())
+        })
+        .ok_or_else(|| format!("{} has no conflict to resolve", path))?;
+    let our_id = conflict.our.as_ref().map(|e| e.id);
+    let their_id = conflict.their.as_ref().map(|e| e.id);
+
+    let mut pointers = vec![path.to_string()];
+    match resolution {
+        DvcResolution::Ours => take_side(&repo, &repo_root, path, our_id)?,
+        DvcResolution::Theirs => take_side(&repo, &repo_root, path, their_id)?,
+        DvcResolution::KeepBoth => {
+            let their_id = their_id.ok_or("Their side deleted the pointer, keep ours instead")?;
+            take_side(&repo, &repo_root, path, our_id)?;
+            pointers.push(keep_theirs_renamed(&repo, &repo_root, path, their_id)?);
+        }
+    }
+
+    // Staging a path clears its conflict entries
+    let mut staged = Vec::new();
+    for pointer in &pointers {
+        let relative = Path::new(pointer);
+        if repo_root.join(relative).exists() {
+            index
+                .add_path(relative)
+                .map_err(|e| format!("Failed to stage {}: {}", pointer, e))?;
+            staged.push(repo_root.join(relative));
+        } else {
+            index
+                .remove_path(relative)
+                .map_err(|e| format!("Failed to remove {}: {}", pointer, e))?;
+        }
+    }
+    for pointer in &staged {
+        let dvc_file = dvcfile::read_dvc_file(pointer)?;
+        let base_dir = pointer.parent().unwrap_or(&repo_root);
+        for out in &dvc_file.outs {
+            let gitignore = dvcfile::add_gitignore_entry(&base_dir.join(&out.path))?;
+            index
+                .add_path(&dvcfile::repo_relative_path(&repo_root, &gitignore)?)
+                .map_err(|e| format!("Failed to add .gitignore to index: {}", e))?;
+        }
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    // The chosen data replaces whatever the workspace had for these outputs
+    let checkout = checkout::checkout_pointers(&repo_root, &staged, true)?;
+    Ok(DvcResolutionResult {
+        pointers,
+        checkout,
+        remaining_conflicts: conflicts::index_conflicts(&index)?.len(),
+    })
+}
+
+/// Resolves a `.dvc` pointer conflict by picking a data version, or by keeping
+/// both with their version under a renamed output. The chosen data is checked
+/// out from the cache.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn resolve_dvc_conflict(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    resolution: DvcResolution,
+) -> Result<DvcResolutionResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "resolve_dvc_conflict",
+        json!({ "path": path, "resolution": format!("{:?}", resolution) }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                resolve(&repo_path, &path, resolution)
+            })
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+    use git2::build::CheckoutBuilder;
+    use md5::{Digest, Md5};
+
+    fn switch(fixture: &FixtureRepo, branch: &str) {
+        let repo = fixture.repo();
+        repo.set_head(&format!("refs/heads/{}", branch)).unwrap();
+        repo.checkout_head(Some(CheckoutBuilder::new().force()))
+            .unwrap();
+    }
+
+    /// A merge of `other` into `main` stopped at a conflict in `data.csv.dvc`
+    fn conflicted_fixture() -> FixtureRepo {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        fixture.track_dataset("data.csv", "base\n");
+        fixture.commit_all("Track data");
+        fixture.create_branch("other");
+        fixture.track_dataset("data.csv", "ours\n");
+        fixture.commit_all("Our data");
+        switch(&fixture, "other");
+        fixture.track_dataset("data.csv", "theirs\n");
+        let theirs = fixture.commit_all("Their data");
+        switch(&fixture, "main");
+
+        let repo = fixture.repo();
+        let theirs = repo.find_annotated_commit(theirs).unwrap();
+        repo.merge(&[&theirs], None, None).unwrap();
+        fixture
+    }
+
+    #[test]
+    fn renames_their_outputs() {
+        let cases = [
+            ("data.csv", "data-theirs.csv"),
+            ("data/train.csv", "data/train-theirs.csv"),
+            ("images", "images-theirs"),
+            ("data/archive.tar.gz", "data/archive.tar-theirs.gz"),
+        ];
+        for (path, expected) in cases {
+            assert_eq!(renamed_output(path), expected, "renaming {}", path);
+        }
+    }
+
+    #[test]
+    fn lists_the_data_versions_of_pointer_conflicts() {
+        let fixture = conflicted_fixture();
+        let conflicts = list_dvc_conflicts(fixture.path_string()).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        let conflict = &conflicts[0];
+        assert_eq!(conflict.path, "data.csv.dvc");
+        let md5 = |version: &Option<DataVersion>| version.as_ref().unwrap().md5.clone();
+        assert_eq!(
+            md5(&conflict.ours),
+            Some(format!("{:x}", Md5::digest("ours\n")))
+        );
+        assert_eq!(
+            md5(&conflict.theirs),
+            Some(format!("{:x}", Md5::digest("theirs\n")))
+        );
+        assert!(conflict.theirs.as_ref().unwrap().commit_id.is_some());
+    }
+
+    #[test]
+    fn resolves_pointer_conflicts_with_the_chosen_data() {
+        let cases = [
+            (DvcResolution::Ours, "ours\n", None),
+            (DvcResolution::Theirs, "theirs\n", None),
+            (DvcResolution::KeepBoth, "ours\n", Some("theirs\n")),
+        ];
+        for (resolution, data, renamed) in cases {
+            let fixture = conflicted_fixture();
+            let result = resolve(&fixture.path_string(), "data.csv.dvc", resolution).unwrap();
+            assert_eq!(result.remaining_conflicts, 0, "{:?}", resolution);
+            assert_eq!(fixture.read_file("data.csv"), data, "{:?}", resolution);
+            match renamed {
+                Some(renamed) => {
+                    assert_eq!(fixture.read_file("data-theirs.csv"), renamed);
+                    assert!(result.pointers.contains(&"data-theirs.csv.dvc".to_string()));
+                }
+                None => assert_eq!(result.pointers, vec!["data.csv.dvc".to_string()]),
+            }
+        }
+    }
+}
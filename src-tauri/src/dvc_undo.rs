@@ -0,0 +1,196 @@
+use git2::{Oid, Repository};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{audit, dvcfile, repo_manager};
+
+/// Journal of the last DVC add, kept in the git directory so it can be undone
+/// after a restart
+const JOURNAL_FILE: &str = "fenn-dvc-add.json";
+
+/// A file the add touches, as it was before
+#[derive(Debug, Serialize, Deserialize)]
+struct FileSnapshot {
+    /// Repository relative path
+    path: String,
+    /// Previous content; `None` when the file didn't exist
+    content: Option<String>,
+    /// Previous index entry as (blob id, mode); `None` when it wasn't staged
+    staged: Option<(String, u32)>,
+}
+
+/// Pre-state of a DVC add: the pointers it creates and the `.gitignore` files
+/// it edits, with their content and index entries
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddJournal {
+    /// Data paths the add tracked, relative to the repository root
+    pub targets: Vec<String>,
+    snapshots: Vec<FileSnapshot>,
+}
+
+fn journal_path(repo: &Repository) -> PathBuf {
+    repo.path().join(JOURNAL_FILE)
+}
+
+fn open_repo(path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo =
+        Repository::discover(path).map_err(|e| format!("Failed to open git repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or_else(|| "Repository has no working directory".to_string())?
+        .to_path_buf();
+    Ok((repo, repo_root))
+}
+
+impl AddJournal {
+    /// Snapshots everything adding `files` (absolute, or relative to `path`) can change
+    pub fn record(path: &str, files: &[String]) -> Result<Self, String> {
+        let (repo, repo_root) = open_repo(path)?;
+        let index = repo
+            .index()
+            .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+        let mut targets = Vec::new();
+        let mut touched = vec![PathBuf::from(".gitignore")];
+        for file in files {
+            let data_path = Path::new(path).join(file);
+            let relative = dvcfile::repo_relative_path(&repo_root, &data_path)?;
+            touched.push(dvcfile::pointer_path(&relative));
+            touched.push(
+                relative
+                    .parent()
+                    .unwrap_or_else(|| Path::new(""))
+                    .join(".gitignore"),
+            );
+            targets.push(dvcfile::to_git_path(&relative));
+        }
+        touched.sort();
+        touched.dedup();
+
+        let snapshots = touched
+            .into_iter()
+            .map(|relative| FileSnapshot {
+                content: fs::read_to_string(repo_root.join(&relative)).ok(),
+                staged: index
+                    .get_path(&relative, 0)
+                    .map(|entry| (entry.id.to_string(), entry.mode)),
+                path: dvcfile::to_git_path(&relative),
+            })
+            .collect();
+
+        Ok(Self { targets, snapshots })
+    }
+
+    /// Keeps the journal as the one `undo_last_dvc_add` reverts
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let (repo, _) = open_repo(path)?;
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize DVC add journal: {}", e))?;
+        fs::write(journal_path(&repo), content)
+            .map_err(|e| format!("Failed to save DVC add journal: {}", e))
+    }
+
+    /// Puts the snapshotted files and index entries back as they were
+    pub fn rollback(&self, path: &str) -> Result<(), String> {
+        let (repo, repo_root) = open_repo(path)?;
+        let mut index = repo
+            .index()
+            .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+        for snapshot in &self.snapshots {
+            let absolute = repo_root.join(&snapshot.path);
+            match &snapshot.content {
+                Some(content) => fs::write(&absolute, content),
+                None if absolute.exists() => fs::remove_file(&absolute),
+                None => Ok(()),
+            }
+            .map_err(|e| format!("Failed to restore {}: {}", snapshot.path, e))?;
+
+            let relative = Path::new(&snapshot.path);
+            match &snapshot.staged {
+                Some((id, mode)) => {
+                    let id = Oid::from_str(id)
+                        .map_err(|e| format!("Invalid blob id for {}: {}", snapshot.path, e))?;
+                    let blob = repo
+                        .find_blob(id)
+                        .map_err(|e| format!("Failed to read {}: {}", snapshot.path, e))?;
+                    let mut entry = match index.get_path(relative, 0) {
+                        Some(entry) => entry,
+                        // Re-add from disk to get a complete entry, then point it back
+                        None => {
+                            index.add_path(relative).map_err(|e| {
+                                format!("Failed to restage {}: {}", snapshot.path, e)
+                            })?;
+                            index
+                                .get_path(relative, 0)
+                                .ok_or_else(|| format!("Failed to restage {}", snapshot.path))?
+                        }
+                    };
+                    entry.id = blob.id();
+                    entry.mode = *mode;
+                    entry.file_size = blob.size() as u32;
+                    index
+                        .add(&entry)
+                        .map_err(|e| format!("Failed to restage {}: {}", snapshot.path, e))?;
+                }
+                None if index.get_path(relative, 0).is_some() => {
+                    index
+                        .remove_path(relative)
+                        .map_err(|e| format!("Failed to unstage {}: {}", snapshot.path, e))?;
+                }
+                None => {}
+            }
+        }
+
+        index
+            .write()
+            .map_err(|e| format!("Failed to write index: {}", e))
+    }
+}
+
+/// Saves the journal when the add succeeded, or rolls the partial add back
+/// when it failed so the repository is never left half-tracked
+pub fn finish<T>(journal: &AddJournal, path: &str, result: Result<T, String>) -> Result<T, String> {
+    match result {
+        Ok(value) => {
+            journal.save(path)?;
+            Ok(value)
+        }
+        Err(e) => match journal.rollback(path) {
+            Ok(()) => Err(e),
+            Err(rollback) => Err(format!("{} (rollback also failed: {})", e, rollback)),
+        },
+    }
+}
+
+/// Reverts the most recent `add_dvc_file`/`add_dvc_files`: removes the pointers
+/// it created and restores `.gitignore` files and staged entries. The data is
+/// left in the workspace and its copy in the cache is kept.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn undo_last_dvc_add(app_handle: AppHandle, repo_path: String) -> Result<Vec<String>, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "undo_last_dvc_add",
+        json!({}),
+        || repo_manager::write(&app_handle, &repo_path, || undo(&repo_path)),
+    )
+}
+
+fn undo(repo_path: &str) -> Result<Vec<String>, String> {
+    let (repo, _) = open_repo(repo_path)?;
+    let path = journal_path(&repo);
+    let content =
+        fs::read_to_string(&path).map_err(|_| "There is no DVC add to undo".to_string())?;
+    let journal: AddJournal = serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to read DVC add journal: {}", e))?;
+
+    journal.rollback(repo_path)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove DVC add journal: {}", e))?;
+    Ok(journal.targets)
+}
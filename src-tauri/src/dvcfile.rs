@@ -0,0 +1,288 @@
+use md5::{Digest, Md5};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::dvc_compat::{self, DvcFormat};
+use crate::dvc_config;
+
+/// Contents of a `.dvc` pointer file. Unknown keys are preserved on write.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DvcFile {
+    #[serde(default)]
+    pub outs: Vec<DvcOut>,
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DvcOut {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub md5: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nfiles: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+    pub path: String,
+    #[serde(flatten)]
+    pub extra: serde_yaml::Mapping,
+}
+
+impl DvcOut {
+    /// Directory outputs reference a `.dir` manifest instead of a single object
+    pub fn is_dir(&self) -> bool {
+        self.md5.as_deref().is_some_and(|md5| md5.ends_with(".dir"))
+    }
+}
+
+pub fn read_dvc_file(path: &Path) -> Result<DvcFile, String> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_yaml::from_str(&content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))
+}
+
+/// Parses pointer file contents, e.g. a blob read from an older commit
+pub fn parse_dvc_file(content: &str) -> Result<DvcFile, String> {
+    serde_yaml::from_str(content).map_err(|e| format!("Failed to parse DVC file: {}", e))
+}
+
+pub fn write_dvc_file(path: &Path, dvc_file: &DvcFile) -> Result<(), String> {
+    let content = serde_yaml::to_string(dvc_file)
+        .map_err(|e| format!("Failed to serialize {}: {}", path.display(), e))?;
+    fs::write(path, content).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+/// Entry of a directory output's `.dir` manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DirManifestEntry {
+    pub md5: String,
+    pub relpath: String,
+}
+
+/// Location of the repository's DVC cache: `cache.dir` when configured, which
+/// DVC resolves against the `.dvc` directory, otherwise `.dvc/cache`
+pub fn cache_dir(repo_root: &Path) -> PathBuf {
+    let dvc_dir = repo_root.join(".dvc");
+    dvc_config::load(repo_root)
+        .ok()
+        .and_then(|config| config.get("cache", "dir").map(|dir| dvc_dir.join(dir)))
+        .unwrap_or_else(|| dvc_dir.join("cache"))
+}
+
+/// Path of an object in the cache. DVC 3 stores objects under `files/md5`,
+/// DVC 2 directly under the cache root. An existing object is found in
+/// either; a new one goes where the cache's layout puts it.
+pub fn cache_object_path(cache_dir: &Path, md5: &str) -> PathBuf {
+    cached_object(cache_dir, md5)
+        .unwrap_or_else(|| new_object_path(cache_dir, md5, dvc_compat::cache_format(cache_dir)))
+}
+
+/// Like `cache_object_path`, but a new object goes where a repository in
+/// `format` keeps it, which also holds while its cache is still empty
+pub fn cache_object_path_for(cache_dir: &Path, md5: &str, format: Option<DvcFormat>) -> PathBuf {
+    cached_object(cache_dir, md5).unwrap_or_else(|| new_object_path(cache_dir, md5, format))
+}
+
+fn cached_object(cache_dir: &Path, md5: &str) -> Option<PathBuf> {
+    [
+        new_object_path(cache_dir, md5, Some(DvcFormat::V2)),
+        new_object_path(cache_dir, md5, Some(DvcFormat::V3)),
+    ]
+    .into_iter()
+    .find(|path| path.exists())
+}
+
+fn new_object_path(cache_dir: &Path, md5: &str, format: Option<DvcFormat>) -> PathBuf {
+    let (prefix, rest) = md5.split_at(2.min(md5.len()));
+    match format {
+        Some(DvcFormat::V2) => cache_dir.join(prefix).join(rest),
+        _ => cache_dir.join("files").join("md5").join(prefix).join(rest),
+    }
+}
+
+/// Reads the `.dir` manifest of a directory output from the cache
+pub fn read_dir_manifest(cache_dir: &Path, md5: &str) -> Result<Vec<DirManifestEntry>, String> {
+    let path = cache_object_path(cache_dir, md5);
+    let content = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read directory manifest {}: {}", md5, e))?;
+    serde_json::from_str(&content)
+        .map_err(|e| format!("Failed to parse directory manifest {}: {}", md5, e))
+}
+
+/// Streams a file through md5, the hash DVC 3 records in pointers and cache
+/// paths. Comparisons with a pointer md5 go through `hash_file_for`.
+pub fn hash_file(path: &Path) -> Result<String, String> {
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Md5::new();
+    let mut buffer = vec![0u8; 1024 * 1024];
+    loop {
+        let read = file
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Size of the chunks DVC 2 converts and hashes text files in
+const DVC2_CHUNK_SIZE: u64 = 1024 * 1024;
+
+/// Like `hash_file`, but with the rule of a repository in `format`: DVC 2
+/// hashes text files with CRLF line endings converted to LF, chunk by chunk
+pub fn hash_file_for(path: &Path, format: Option<DvcFormat>) -> Result<String, String> {
+    if format != Some(DvcFormat::V2) || !is_text_file(path)? {
+        return hash_file(path);
+    }
+
+    let mut file =
+        fs::File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+    let mut hasher = Md5::new();
+    let mut chunk = Vec::new();
+    loop {
+        chunk.clear();
+        let read = file
+            .by_ref()
+            .take(DVC2_CHUNK_SIZE)
+            .read_to_end(&mut chunk)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(dos2unix(&chunk));
+    }
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// DVC 2's text detection: no NUL byte and at most 30% non-text characters
+/// in the first 512 bytes
+fn is_text_file(path: &Path) -> Result<bool, String> {
+    let mut block = Vec::new();
+    fs::File::open(path)
+        .and_then(|file| file.take(512).read_to_end(&mut block))
+        .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if block.is_empty() {
+        return Ok(true);
+    }
+    if block.contains(&0) {
+        return Ok(false);
+    }
+    let non_text = block
+        .iter()
+        .filter(|&&b| !matches!(b, 32..=126 | b'\n' | b'\r' | b'\t' | 0x0c | 0x08))
+        .count();
+    Ok(non_text as f64 / block.len() as f64 <= 0.30)
+}
+
+fn dos2unix(data: &[u8]) -> Vec<u8> {
+    let mut converted = Vec::with_capacity(data.len());
+    let mut bytes = data.iter().peekable();
+    while let Some(&byte) = bytes.next() {
+        if byte == b'\r' && bytes.peek() == Some(&&b'\n') {
+            continue;
+        }
+        converted.push(byte);
+    }
+    converted
+}
+
+/// Finds every `.dvc` pointer file in the working tree, skipping `.git` and `.dvc`
+pub fn find_pointer_files(repo_root: &Path) -> Vec<PathBuf> {
+    walkdir::WalkDir::new(repo_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            !(entry.file_type().is_dir() && (name == ".git" || name == ".dvc"))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| {
+            entry.file_type().is_file()
+                && entry.path().extension().and_then(|e| e.to_str()) == Some("dvc")
+        })
+        .map(|entry| entry.into_path())
+        .collect()
+}
+
+/// Returns the `.dvc` pointer path for a data file or directory
+pub fn pointer_path(data_path: &Path) -> PathBuf {
+    let mut pointer = data_path.as_os_str().to_owned();
+    pointer.push(".dvc");
+    PathBuf::from(pointer)
+}
+
+/// Converts a path to be relative to the repository root, leaving relative paths untouched
+pub fn repo_relative_path(repo_root: &Path, path: &Path) -> Result<PathBuf, String> {
+    if path.is_absolute() {
+        path.strip_prefix(repo_root)
+            .map(|p| p.to_path_buf())
+            .map_err(|e| format!("Failed to make file path relative: {}", e))
+    } else {
+        Ok(path.to_path_buf())
+    }
+}
+
+/// Forward-slash form of a repository relative path, as used by git and DVC
+pub fn to_git_path(path: &Path) -> String {
+    path.to_string_lossy().replace('\\', "/")
+}
+
+/// Returns the `.gitignore` DVC writes the data path into and the entry it uses
+fn gitignore_location(data_path: &Path) -> Result<(PathBuf, String), String> {
+    let name = data_path
+        .file_name()
+        .ok_or_else(|| format!("Invalid data path: {}", data_path.display()))?
+        .to_string_lossy();
+    let dir = data_path.parent().unwrap_or_else(|| Path::new(""));
+    Ok((dir.join(".gitignore"), format!("/{}", name)))
+}
+
+/// Adds the DVC-style ignore entry for a data path, returning the `.gitignore` path
+pub fn add_gitignore_entry(data_path: &Path) -> Result<PathBuf, String> {
+    let (gitignore_path, entry) = gitignore_location(data_path)?;
+    let mut content = fs::read_to_string(&gitignore_path).unwrap_or_default();
+
+    if content.lines().any(|line| line.trim() == entry) {
+        return Ok(gitignore_path);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&entry);
+    content.push('\n');
+
+    fs::write(&gitignore_path, content)
+        .map_err(|e| format!("Failed to update {}: {}", gitignore_path.display(), e))?;
+    Ok(gitignore_path)
+}
+
+/// Removes the DVC-style ignore entry for a data path, returning the `.gitignore` path.
+/// A `.gitignore` left empty is deleted, as DVC does.
+pub fn remove_gitignore_entry(data_path: &Path) -> Result<Option<PathBuf>, String> {
+    let (gitignore_path, entry) = gitignore_location(data_path)?;
+    let Ok(content) = fs::read_to_string(&gitignore_path) else {
+        return Ok(None);
+    };
+
+    let remaining: Vec<&str> = content
+        .lines()
+        .filter(|line| line.trim() != entry)
+        .collect();
+
+    if remaining.iter().all(|line| line.trim().is_empty()) {
+        fs::remove_file(&gitignore_path)
+            .map_err(|e| format!("Failed to remove {}: {}", gitignore_path.display(), e))?;
+    } else {
+        let mut updated = remaining.join("\n");
+        updated.push('\n');
+        fs::write(&gitignore_path, updated)
+            .map_err(|e| format!("Failed to update {}: {}", gitignore_path.display(), e))?;
+    }
+
+    Ok(Some(gitignore_path))
+}
@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{command, AppHandle, Manager};
+use tracing::{info, instrument};
+
+use crate::watchdog::{self, WatchdogSettings};
+
+const ENV_SETTINGS_FILE: &str = "python-env.json";
+
+/// Directory in the app data dir holding the virtualenv the app creates
+const MANAGED_VENV_DIR: &str = "python-env";
+
+/// Package installed into the managed virtualenv
+const DVC_REQUIREMENT: &str = "dvc[s3]";
+
+/// Prints the interpreter version and, when importable, the DVC version
+const PROBE_SCRIPT: &str = "import sys
+try:
+    import dvc
+    dvc_version = dvc.__version__
+except Exception:
+    dvc_version = ''
+print(sys.version.split()[0])
+print(dvc_version)";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PythonEnvironment {
+    /// "system", "conda", "venv" or "managed" for the one the app created
+    pub kind: String,
+    pub interpreter: String,
+    pub python_version: String,
+    /// `None` when DVC is not installed in the environment
+    pub dvc_version: Option<String>,
+}
+
+/// How the DVC layer runs its scripts
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct EnvSettings {
+    /// Interpreter running the Python sources of the scripts
+    interpreter: Option<String>,
+    /// Directory searched first for the pre-built scripts
+    scripts_dir: Option<String>,
+}
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    Ok(dir.join(ENV_SETTINGS_FILE))
+}
+
+fn read_settings(app_handle: &AppHandle) -> Result<EnvSettings, String> {
+    let path = settings_path(app_handle)?;
+    if !path.exists() {
+        return Ok(EnvSettings::default());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read Python settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse Python settings: {}", e))
+}
+
+fn update_settings(
+    app_handle: &AppHandle,
+    update: impl FnOnce(&mut EnvSettings),
+) -> Result<(), String> {
+    let mut settings = read_settings(app_handle)?;
+    update(&mut settings);
+    let content = serde_json::to_string_pretty(&settings)
+        .map_err(|e| format!("Failed to serialize Python settings: {}", e))?;
+    fs::write(settings_path(app_handle)?, content)
+        .map_err(|e| format!("Failed to write Python settings: {}", e))
+}
+
+/// Interpreter chosen for the DVC layer, if any; the pre-built scripts are
+/// used otherwise
+pub(crate) fn configured_interpreter(app_handle: &AppHandle) -> Option<PathBuf> {
+    read_settings(app_handle)
+        .ok()
+        .and_then(|settings| settings.interpreter)
+        .map(PathBuf::from)
+        .filter(|path| path.exists())
+}
+
+/// User-configured directory of pre-built scripts, if any
+pub(crate) fn scripts_override(app_handle: &AppHandle) -> Option<PathBuf> {
+    read_settings(app_handle)
+        .ok()
+        .and_then(|settings| settings.scripts_dir)
+        .map(PathBuf::from)
+}
+
+/// Saves the directory searched first for the pre-built scripts
+pub(crate) fn set_scripts_override(
+    app_handle: &AppHandle,
+    scripts_dir: Option<String>,
+) -> Result<(), String> {
+    update_settings(app_handle, |settings| settings.scripts_dir = scripts_dir)
+}
+
+/// Interpreter inside a virtualenv or conda environment directory
+fn env_interpreter(env_dir: &Path) -> PathBuf {
+    if cfg!(target_os = "windows") {
+        let scripts = env_dir.join("Scripts").join("python.exe");
+        if scripts.exists() {
+            scripts
+        } else {
+            env_dir.join("python.exe")
+        }
+    } else {
+        env_dir.join("bin").join("python")
+    }
+}
+
+fn managed_venv_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(MANAGED_VENV_DIR))
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Executables called `name` on the `PATH`, in order
+fn find_on_path(name: &str) -> Vec<PathBuf> {
+    let name = if cfg!(target_os = "windows") {
+        format!("{}.exe", name)
+    } else {
+        name.to_string()
+    };
+    std::env::var_os("PATH")
+        .map(|paths| {
+            std::env::split_paths(&paths)
+                .map(|dir| dir.join(&name))
+                .filter(|path| path.is_file())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Runs the interpreter to learn its version and whether DVC is installed
+pub(crate) fn probe(kind: &str, interpreter: &Path) -> Option<PythonEnvironment> {
+    let limits = WatchdogSettings::default().limits("python_probe");
+    let output = watchdog::output(
+        Command::new(interpreter).arg("-c").arg(PROBE_SCRIPT),
+        "python_probe",
+        &limits,
+    )
+    .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let mut lines = stdout.lines();
+    let python_version = lines.next()?.trim().to_string();
+    let dvc_version = lines
+        .next()
+        .map(str::trim)
+        .filter(|version| !version.is_empty())
+        .map(str::to_string);
+
+    Some(PythonEnvironment {
+        kind: kind.to_string(),
+        interpreter: interpreter.to_string_lossy().to_string(),
+        python_version,
+        dvc_version,
+    })
+}
+
+/// Candidate interpreters with their kind, most specific first
+fn candidates(app_handle: &AppHandle, repo_path: Option<&str>) -> Vec<(&'static str, PathBuf)> {
+    let mut candidates = Vec::new();
+
+    if let Ok(dir) = managed_venv_dir(app_handle) {
+        candidates.push(("managed", env_interpreter(&dir)));
+    }
+    if let Some(repo_path) = repo_path {
+        for name in [".venv", "venv", "env"] {
+            candidates.push(("venv", env_interpreter(&Path::new(repo_path).join(name))));
+        }
+    }
+    if let Some(dir) = std::env::var_os("VIRTUAL_ENV") {
+        candidates.push(("venv", env_interpreter(Path::new(&dir))));
+    }
+
+    let mut conda_roots = Vec::new();
+    if let Some(prefix) = std::env::var_os("CONDA_PREFIX") {
+        candidates.push(("conda", env_interpreter(Path::new(&prefix))));
+    }
+    if let Ok(home) = app_handle.path().home_dir() {
+        for name in ["miniconda3", "anaconda3", "miniforge3", "mambaforge"] {
+            conda_roots.push(home.join(name));
+        }
+    }
+    for root in conda_roots.into_iter().filter(|root| root.is_dir()) {
+        candidates.push(("conda", env_interpreter(&root)));
+        if let Ok(entries) = fs::read_dir(root.join("envs")) {
+            for entry in entries.flatten() {
+                candidates.push(("conda", env_interpreter(&entry.path())));
+            }
+        }
+    }
+
+    for name in ["python3", "python"] {
+        for path in find_on_path(name) {
+            candidates.push(("system", path));
+        }
+    }
+    candidates
+}
+
+fn detect(app_handle: &AppHandle, repo_path: Option<&str>) -> Vec<PythonEnvironment> {
+    let mut seen = HashSet::new();
+    candidates(app_handle, repo_path)
+        .into_iter()
+        .filter(|(_, path)| path.is_file())
+        // A system interpreter is often reachable through several symlinks,
+        // while a virtualenv's interpreter is itself a link to one
+        .filter(|(kind, path)| {
+            let key = match *kind {
+                "system" => path.canonicalize().unwrap_or_else(|_| path.clone()),
+                _ => path.clone(),
+            };
+            seen.insert(key)
+        })
+        .filter_map(|(kind, path)| probe(kind, &path))
+        .collect()
+}
+
+fn run_checked(command: &mut Command, name: &str, action: &str) -> Result<(), String> {
+    let limits = WatchdogSettings::default().limits(name);
+    let output = watchdog::output(command, name, &limits)
+        .map_err(|e| format!("Failed to {}: {}", action, e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let tail: Vec<&str> = stderr.lines().rev().take(10).collect();
+        return Err(format!(
+            "Failed to {}: {}",
+            action,
+            tail.into_iter().rev().collect::<Vec<_>>().join("\n")
+        ));
+    }
+    Ok(())
+}
+
+/// Python installations found on this machine: the app's own virtualenv,
+/// virtualenvs of the project, conda environments and interpreters on the
+/// `PATH`, each with the DVC version it has installed
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn detect_python_environments(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Vec<PythonEnvironment>, String> {
+    Ok(detect(&app_handle, repo_path.as_deref()))
+}
+
+/// Creates the app's own virtualenv from `base_interpreter` (or the first
+/// Python on the `PATH`), installs DVC with S3 support into it and makes it
+/// the interpreter of the DVC layer
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn create_dvc_environment(
+    app_handle: AppHandle,
+    base_interpreter: Option<String>,
+) -> Result<PythonEnvironment, String> {
+    let base = match base_interpreter {
+        Some(path) => PathBuf::from(path),
+        None => ["python3", "python"]
+            .into_iter()
+            .flat_map(find_on_path)
+            .next()
+            .ok_or("No Python interpreter found on the PATH")?,
+    };
+    let venv_dir = managed_venv_dir(&app_handle)?;
+
+    info!(base = %base.display(), venv = %venv_dir.display(), "Creating DVC environment");
+    run_checked(
+        Command::new(&base).arg("-m").arg("venv").arg(&venv_dir),
+        "python_venv",
+        "create the virtualenv",
+    )?;
+    let interpreter = env_interpreter(&venv_dir);
+    run_checked(
+        Command::new(&interpreter)
+            .args(["-m", "pip", "install", "--upgrade", "pip"])
+            .arg(DVC_REQUIREMENT),
+        "pip_install",
+        "install DVC",
+    )?;
+
+    let environment = probe("managed", &interpreter)
+        .ok_or("The new environment's interpreter could not be run")?;
+    if environment.dvc_version.is_none() {
+        return Err("DVC was installed but cannot be imported".to_string());
+    }
+    update_settings(&app_handle, |settings| {
+        settings.interpreter = Some(environment.interpreter.clone());
+    })?;
+    Ok(environment)
+}
+
+/// The interpreter the DVC layer uses, `None` while the pre-built scripts are
+/// used
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_dvc_interpreter(app_handle: AppHandle) -> Result<Option<PythonEnvironment>, String> {
+    let Some(interpreter) = configured_interpreter(&app_handle) else {
+        return Ok(None);
+    };
+    let kind = if interpreter.starts_with(managed_venv_dir(&app_handle)?) {
+        "managed"
+    } else {
+        "configured"
+    };
+    Ok(probe(kind, &interpreter))
+}
+
+/// Records the interpreter the DVC layer runs its scripts with; `None` goes
+/// back to the pre-built scripts. The interpreter must have DVC installed.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_dvc_interpreter(
+    app_handle: AppHandle,
+    interpreter: Option<String>,
+) -> Result<Option<PythonEnvironment>, String> {
+    let environment = match &interpreter {
+        Some(path) => {
+            let environment = probe("configured", Path::new(path))
+                .ok_or_else(|| format!("{} is not a working Python interpreter", path))?;
+            if environment.dvc_version.is_none() {
+                return Err(format!("DVC is not installed for {}", path));
+            }
+            Some(environment)
+        }
+        None => None,
+    };
+    update_settings(&app_handle, |settings| settings.interpreter = interpreter)?;
+    Ok(environment)
+}
@@ -0,0 +1,149 @@
+use git2::Repository;
+use serde::Serialize;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+/// Emitted after every mutating operation, so views refresh their status
+/// instead of polling
+pub const STATUS_CHANGED_EVENT: &str = "repo://status-changed";
+/// Emitted when an operation moved HEAD to another branch or commit
+pub const BRANCH_CHANGED_EVENT: &str = "repo://branch-changed";
+/// Emitted when an operation may have added or removed DVC cache objects
+pub const CACHE_UPDATED_EVENT: &str = "dvc://cache-updated";
+
+/// Operations that write to or remove from the DVC cache
+const CACHE_OPERATIONS: &[&str] = &[
+    "add_dvc_file",
+    "add_dvc_files",
+    "commit_data_changes",
+    "download_and_track",
+    "dvc_pull",
+    "dvc_pull_subpath",
+    "gc_cache",
+    "import_external_data",
+    "pin_data_version",
+    "prefetch_branch_data",
+    "register_model",
+    "remove_dvc_file",
+    "resolve_dvc_conflict",
+    "restore_file_version",
+    "restore_snapshot",
+    "set_shared_cache",
+    "undo_last_dvc_add",
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusChanged {
+    /// Repository root the operation ran in
+    pub repo_path: String,
+    pub operation: String,
+    pub success: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BranchChanged {
+    pub repo_path: String,
+    pub operation: String,
+    /// Branch before the operation; `None` when HEAD was detached
+    pub previous: Option<String>,
+    /// Branch after the operation; `None` when HEAD is detached
+    pub current: Option<String>,
+    /// Commit HEAD points to after the operation
+    pub head: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheUpdated {
+    pub repo_path: String,
+    pub operation: String,
+}
+
+/// Branch and commit HEAD points to
+#[derive(Debug, PartialEq)]
+struct HeadState {
+    branch: Option<String>,
+    commit: Option<String>,
+}
+
+fn head_state(repo: &Repository) -> Option<HeadState> {
+    let head = repo.find_reference("HEAD").ok()?;
+    Some(HeadState {
+        branch: head.symbolic_target().map(|target| {
+            target
+                .strip_prefix("refs/heads/")
+                .unwrap_or(target)
+                .to_string()
+        }),
+        commit: repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string()),
+    })
+}
+
+fn emit<S: Serialize + Clone>(app_handle: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app_handle.emit(event, payload) {
+        warn!("Failed to emit {}: {}", event, e);
+    }
+}
+
+/// Runs a mutating operation and tells the frontend what it changed. Events
+/// are sent whatever the outcome, as a failed operation may have changed
+/// the repository part way.
+pub(crate) fn track<T>(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    operation: &str,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let repo = Repository::discover(repo_path).ok();
+    let before = repo.as_ref().and_then(head_state);
+    let result = run();
+
+    let repo_path = repo
+        .as_ref()
+        .and_then(|repo| repo.workdir())
+        .unwrap_or(Path::new(repo_path))
+        .to_string_lossy()
+        .to_string();
+    emit(
+        app_handle,
+        STATUS_CHANGED_EVENT,
+        StatusChanged {
+            repo_path: repo_path.clone(),
+            operation: operation.to_string(),
+            success: result.is_ok(),
+        },
+    );
+
+    let after = repo.as_ref().and_then(head_state);
+    if let (Some(before), Some(after)) = (before, after) {
+        if before != after && (before.branch != after.branch || after.branch.is_none()) {
+            emit(
+                app_handle,
+                BRANCH_CHANGED_EVENT,
+                BranchChanged {
+                    repo_path: repo_path.clone(),
+                    operation: operation.to_string(),
+                    previous: before.branch,
+                    current: after.branch,
+                    head: after.commit,
+                },
+            );
+        }
+    }
+
+    if CACHE_OPERATIONS.contains(&operation) {
+        emit(
+            app_handle,
+            CACHE_UPDATED_EVENT,
+            CacheUpdated {
+                repo_path,
+                operation: operation.to_string(),
+            },
+        );
+    }
+    result
+}
@@ -0,0 +1,402 @@
+use git2::Repository;
+use serde::Serialize;
+use serde_json::Value;
+use serde_yaml::Value as YamlValue;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::history::{self, VersionContent};
+use crate::{branch_compare, repo_manager};
+
+/// Where DVC keeps experiment commits: `refs/exps/<hash>/<name>`
+const EXPERIMENT_REFS: &str = "refs/exps/*";
+const DEFAULT_PARAMS_FILE: &str = "params.yaml";
+
+#[derive(Debug, Serialize)]
+pub struct ExperimentReport {
+    /// "markdown" or "html"
+    pub format: String,
+    pub content: String,
+    /// File the report was written to, when asked for
+    pub output_path: Option<String>,
+}
+
+/// What the report shows of one experiment, every value rendered as text
+struct ExperimentSummary {
+    label: String,
+    commit_id: String,
+    /// By `<params file>:<key>`
+    params: BTreeMap<String, String>,
+    /// By `<metrics file>:<key>`
+    metrics: BTreeMap<String, String>,
+    /// Number of data points by plot file
+    plots: BTreeMap<String, String>,
+    /// DVC hash by tracked path
+    data: BTreeMap<String, String>,
+}
+
+/// Finds an experiment by its DVC name, falling back to any git revision
+fn resolve(repo: &Repository, id: &str) -> Result<(String, git2::Oid), String> {
+    let refs = repo
+        .references_glob(EXPERIMENT_REFS)
+        .map_err(|e| format!("Failed to list experiments: {}", e))?;
+    for reference in refs.flatten() {
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        // Experiments being run live under `refs/exps/exec/`
+        if name.starts_with("refs/exps/exec/") || name.rsplit('/').next() != Some(id) {
+            continue;
+        }
+        if let Ok(commit) = reference.peel_to_commit() {
+            return Ok((id.to_string(), commit.id()));
+        }
+    }
+    let commit = repo
+        .revparse_single(id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find experiment {}: {}", id, e))?;
+    Ok((id.to_string(), commit.id()))
+}
+
+/// Content of a file at a commit, from git or the DVC cache
+fn read_at(repo: &Repository, commit_id: &str, path: &str) -> Option<Vec<u8>> {
+    match history::content_at(repo, Path::new(path), commit_id).ok()? {
+        VersionContent::Blob(content) => Some(content),
+        VersionContent::Cache(object) => fs::read(object).ok(),
+    }
+}
+
+/// YAML or JSON (which YAML parses too) at a commit
+fn parse_at(repo: &Repository, commit_id: &str, path: &str) -> Option<Value> {
+    serde_yaml::from_slice(&read_at(repo, commit_id, path)?).ok()
+}
+
+/// Flattens nested values into `prefix.key.subkey` entries
+fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+    match value {
+        Value::Object(map) => {
+            for (key, value) in map {
+                let key = if prefix.ends_with(':') {
+                    format!("{}{}", prefix, key)
+                } else {
+                    format!("{}.{}", prefix, key)
+                };
+                flatten(&key, value, out);
+            }
+        }
+        Value::String(text) => {
+            out.insert(prefix.to_string(), text.clone());
+        }
+        value => {
+            out.insert(prefix.to_string(), value.to_string());
+        }
+    }
+}
+
+/// Paths listed under a `dvc.yaml` key, either as strings or as the keys of
+/// mappings with per-file options
+fn listed_paths(value: Option<&YamlValue>) -> Vec<String> {
+    let items: Vec<&YamlValue> = match value {
+        Some(YamlValue::Sequence(items)) => items.iter().collect(),
+        Some(mapping @ YamlValue::Mapping(_)) => vec![mapping],
+        _ => Vec::new(),
+    };
+    items
+        .into_iter()
+        .flat_map(|item| match item {
+            YamlValue::String(path) => vec![path.clone()],
+            YamlValue::Mapping(mapping) => mapping
+                .keys()
+                .filter_map(|key| key.as_str().map(str::to_string))
+                .collect(),
+            _ => Vec::new(),
+        })
+        .collect()
+}
+
+/// Params, metrics and plots files a `dvc.yaml` declares, top level and in
+/// its stages
+fn declared_files(pipeline: &YamlValue) -> (Vec<String>, Vec<String>, Vec<String>) {
+    let mut params = vec![DEFAULT_PARAMS_FILE.to_string()];
+    let mut metrics = listed_paths(pipeline.get("metrics"));
+    let mut plots = listed_paths(pipeline.get("plots"));
+    for stage in pipeline
+        .get("stages")
+        .and_then(YamlValue::as_mapping)
+        .into_iter()
+        .flat_map(|stages| stages.values())
+    {
+        // Params of other files are `{file: [keys]}` entries
+        for entry in stage
+            .get("params")
+            .and_then(YamlValue::as_sequence)
+            .into_iter()
+            .flatten()
+        {
+            if let Some(mapping) = entry.as_mapping() {
+                params.extend(
+                    mapping
+                        .keys()
+                        .filter_map(|key| key.as_str().map(str::to_string)),
+                );
+            }
+        }
+        metrics.extend(listed_paths(stage.get("metrics")));
+        plots.extend(listed_paths(stage.get("plots")));
+    }
+    for files in [&mut params, &mut metrics, &mut plots] {
+        files.sort();
+        files.dedup();
+    }
+    (params, metrics, plots)
+}
+
+/// Number of data points in a plot file: CSV/TSV rows or the entries of a
+/// JSON/YAML list
+fn plot_points(content: &[u8], path: &str) -> Option<usize> {
+    if path.ends_with(".csv") || path.ends_with(".tsv") {
+        let rows = String::from_utf8_lossy(content)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .count();
+        return Some(rows.saturating_sub(1));
+    }
+    match serde_yaml::from_slice::<Value>(content).ok()? {
+        Value::Array(points) => Some(points.len()),
+        // DVC's JSON plots are `{"<name>": [points]}`
+        Value::Object(map) => map.values().find_map(Value::as_array).map(Vec::len),
+        _ => None,
+    }
+}
+
+fn summarize(
+    repo: &Repository,
+    label: String,
+    oid: git2::Oid,
+) -> Result<ExperimentSummary, String> {
+    let commit_id = oid.to_string();
+    let pipeline = read_at(repo, &commit_id, "dvc.yaml")
+        .and_then(|content| serde_yaml::from_slice::<YamlValue>(&content).ok())
+        .unwrap_or(YamlValue::Null);
+    let (params_files, metrics_files, plots_files) = declared_files(&pipeline);
+
+    let mut params = BTreeMap::new();
+    for file in &params_files {
+        if let Some(value) = parse_at(repo, &commit_id, file) {
+            flatten(&format!("{}:", file), &value, &mut params);
+        }
+    }
+    let mut metrics = BTreeMap::new();
+    for file in &metrics_files {
+        if let Some(value) = parse_at(repo, &commit_id, file) {
+            flatten(&format!("{}:", file), &value, &mut metrics);
+        }
+    }
+    let plots = plots_files
+        .iter()
+        .filter_map(|file| {
+            let content = read_at(repo, &commit_id, file)?;
+            let points = plot_points(&content, file)
+                .map(|points| format!("{} points", points))
+                .unwrap_or_else(|| format!("{} bytes", content.len()));
+            Some((file.clone(), points))
+        })
+        .collect();
+
+    let tree = repo
+        .find_commit(oid)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| format!("Failed to read tree of {}: {}", commit_id, e))?;
+    let data = branch_compare::tracked_outputs(repo, &tree)?
+        .into_iter()
+        .map(|(path, out)| (path, out.md5.unwrap_or_default()))
+        .collect();
+
+    Ok(ExperimentSummary {
+        label,
+        commit_id,
+        params,
+        metrics,
+        plots,
+        data,
+    })
+}
+
+/// Rows of a report section: every key any experiment has, its value in each
+/// experiment, and whether the values differ
+fn section_rows<'a>(
+    experiments: &'a [ExperimentSummary],
+    values: impl Fn(&'a ExperimentSummary) -> &'a BTreeMap<String, String>,
+) -> Vec<(String, Vec<String>, bool)> {
+    let mut keys: Vec<&String> = experiments
+        .iter()
+        .flat_map(|experiment| values(experiment).keys())
+        .collect();
+    keys.sort();
+    keys.dedup();
+    keys.into_iter()
+        .map(|key| {
+            let cells: Vec<String> = experiments
+                .iter()
+                .map(|experiment| values(experiment).get(key).cloned().unwrap_or_default())
+                .collect();
+            let differs = cells.windows(2).any(|pair| pair[0] != pair[1]);
+            (key.clone(), cells, differs)
+        })
+        .collect()
+}
+
+type Section<'a> = (&'a str, Vec<(String, Vec<String>, bool)>);
+
+fn sections(experiments: &[ExperimentSummary]) -> Vec<Section<'static>> {
+    let commits = vec![(
+        "Commit".to_string(),
+        experiments
+            .iter()
+            .map(|experiment| experiment.commit_id[..7].to_string())
+            .collect(),
+        false,
+    )];
+    vec![
+        ("Experiments", commits),
+        ("Params", section_rows(experiments, |e| &e.params)),
+        ("Metrics", section_rows(experiments, |e| &e.metrics)),
+        ("Plots", section_rows(experiments, |e| &e.plots)),
+        ("Data versions", section_rows(experiments, |e| &e.data)),
+    ]
+}
+
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+fn render_markdown(experiments: &[ExperimentSummary], generated_at: &str) -> String {
+    let mut out = format!(
+        "# Experiment report\n\nGenerated {} for {} experiments. Rows marked with * differ between experiments.\n",
+        generated_at,
+        experiments.len()
+    );
+    for (title, rows) in sections(experiments) {
+        out.push_str(&format!("\n## {}\n\n", title));
+        if rows.is_empty() {
+            out.push_str("_None_\n");
+            continue;
+        }
+        out.push('|');
+        out.push_str(" |");
+        for experiment in experiments {
+            out.push_str(&format!(" {} |", markdown_cell(&experiment.label)));
+        }
+        out.push_str("\n|---|");
+        out.push_str(&"---|".repeat(experiments.len()));
+        out.push('\n');
+        for (key, cells, differs) in rows {
+            let marker = if differs { " *" } else { "" };
+            out.push_str(&format!("| {}{} |", markdown_cell(&key), marker));
+            for cell in cells {
+                out.push_str(&format!(" {} |", markdown_cell(&cell)));
+            }
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn render_html(experiments: &[ExperimentSummary], generated_at: &str) -> String {
+    let mut out = String::from(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>Experiment report</title>\n<style>\n\
+         body { font-family: sans-serif; margin: 2rem; }\n\
+         table { border-collapse: collapse; margin-bottom: 1.5rem; }\n\
+         th, td { border: 1px solid #ddd; padding: 4px 8px; text-align: left; font-size: 14px; }\n\
+         tr.differs td { background: #fff6d5; }\n\
+         </style>\n</head>\n<body>\n<h1>Experiment report</h1>\n",
+    );
+    out.push_str(&format!(
+        "<p>Generated {} for {} experiments. Highlighted rows differ between experiments.</p>\n",
+        html_escape(generated_at),
+        experiments.len()
+    ));
+    for (title, rows) in sections(experiments) {
+        out.push_str(&format!("<h2>{}</h2>\n", title));
+        if rows.is_empty() {
+            out.push_str("<p>None</p>\n");
+            continue;
+        }
+        out.push_str("<table>\n<tr><th></th>");
+        for experiment in experiments {
+            out.push_str(&format!("<th>{}</th>", html_escape(&experiment.label)));
+        }
+        out.push_str("</tr>\n");
+        for (key, cells, differs) in rows {
+            let class = if differs { " class=\"differs\"" } else { "" };
+            out.push_str(&format!("<tr{}><td>{}</td>", class, html_escape(&key)));
+            for cell in cells {
+                out.push_str(&format!("<td>{}</td>", html_escape(&cell)));
+            }
+            out.push_str("</tr>\n");
+        }
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
+
+/// Report comparing experiments (DVC experiment names or any git revisions)
+/// side by side: their params, metrics, plot files and the versions of the
+/// DVC-tracked data, as Markdown or a standalone HTML page. It is written to
+/// `output_path` when given.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn export_experiment_report(
+    app_handle: AppHandle,
+    repo_path: String,
+    exp_ids: Vec<String>,
+    format: String,
+    output_path: Option<String>,
+) -> Result<ExperimentReport, String> {
+    if exp_ids.is_empty() {
+        return Err("Select the experiments to compare".to_string());
+    }
+    let format = format.to_lowercase();
+    let render = match format.as_str() {
+        "markdown" | "md" => render_markdown,
+        "html" => render_html,
+        _ => return Err(format!("Unknown report format: {}", format)),
+    };
+
+    let experiments = repo_manager::read(&app_handle, &repo_path, |repo| {
+        exp_ids
+            .iter()
+            .map(|id| {
+                let (label, oid) = resolve(repo, id)?;
+                summarize(repo, label, oid)
+            })
+            .collect::<Result<Vec<_>, _>>()
+    })?;
+    let generated_at = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let content = render(&experiments, &generated_at);
+
+    if let Some(path) = &output_path {
+        fs::write(path, &content).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    }
+    Ok(ExperimentReport {
+        format: if format == "md" {
+            "markdown".to_string()
+        } else {
+            format
+        },
+        content,
+        output_path,
+    })
+}
@@ -1,14 +1,23 @@
 use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use git2::{Repository, StatusOptions};
+use git2::{Repository, Status, StatusOptions};
+use glob::{MatchOptions, Pattern};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use tauri::State;
+use tauri::{AppHandle, State};
+use tracing::{instrument, warn};
 use walkdir::WalkDir;
 
 use crate::dvc;
-use crate::state::SelectedFilesState;
+use crate::dvcfile;
+use crate::file_type;
+use crate::index::{self, FileIndexState, FileTreeSnapshot};
+use crate::lfs;
+use crate::settings;
+use crate::state::{SelectedFilesState, SelectionState, TreeCacheState};
+use crate::submodule;
+use crate::transfer;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileNode {
@@ -20,6 +29,104 @@ pub struct FileNode {
     git_status: String,
 }
 
+/// Entries skipped by the tree walker unless a project configures its own
+/// (similar to gitbutler-fs patterns)
+pub const DEFAULT_IGNORE_PATTERNS: &[&str] = &["target", "node_modules", ".git", "dist", "build"];
+
+/// Paths listed by `preview_ignore_effect`; the count covers the rest
+const MAX_PREVIEW_PATHS: usize = 500;
+
+/// What the tree walker lists, stored with the project settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TreeSettings {
+    /// Globs of entries to hide, matched against both the entry name and its
+    /// path from the repository root. Everything below a hidden directory is
+    /// hidden too.
+    pub ignore_patterns: Vec<String>,
+    /// Deepest level listed, 1 being the repository root's entries; `None`
+    /// is unlimited
+    pub max_depth: Option<usize>,
+    /// The walk stops after this many entries so huge trees stay usable;
+    /// `None` is unlimited
+    pub max_entries: Option<usize>,
+}
+
+impl Default for TreeSettings {
+    fn default() -> Self {
+        Self {
+            ignore_patterns: DEFAULT_IGNORE_PATTERNS
+                .iter()
+                .map(|pattern| pattern.to_string())
+                .collect(),
+            max_depth: None,
+            max_entries: Some(200_000),
+        }
+    }
+}
+
+/// `TreeSettings` with the patterns compiled
+#[derive(Debug, Default)]
+pub(crate) struct TreeFilter {
+    patterns: Vec<Pattern>,
+    max_depth: Option<usize>,
+    max_entries: Option<usize>,
+}
+
+impl TreeFilter {
+    pub(crate) fn new(settings: &TreeSettings) -> Result<Self, String> {
+        Ok(Self {
+            patterns: compile_patterns(&settings.ignore_patterns)?,
+            max_depth: settings.max_depth,
+            max_entries: settings.max_entries,
+        })
+    }
+
+    /// Filter configured for a repository
+    pub(crate) fn load(app_handle: &AppHandle, repo_root: &Path) -> Result<Self, String> {
+        Self::new(&settings::load(app_handle, repo_root)?.tree)
+    }
+
+    /// Whether an entry is hidden, by its path from the repository root
+    fn is_ignored(&self, relative: &str) -> bool {
+        let name = relative.rsplit('/').next().unwrap_or(relative);
+        let path_options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        self.patterns
+            .iter()
+            .any(|pattern| pattern.matches(name) || pattern.matches_with(relative, path_options))
+    }
+
+    fn is_too_deep(&self, relative: &str) -> bool {
+        self.max_depth
+            .is_some_and(|max_depth| relative.split('/').count() > max_depth)
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern.trim_end_matches('/'))
+                .map_err(|e| format!("Invalid ignore pattern {}: {}", pattern, e))
+        })
+        .collect()
+}
+
+/// Entries a set of ignore patterns would hide from the tree
+#[derive(Debug, Serialize)]
+pub struct IgnorePreview {
+    /// Topmost hidden paths; what is below a hidden directory isn't repeated
+    pub hidden: Vec<String>,
+    /// Hidden files and directories, including everything below hidden
+    /// directories
+    pub hidden_count: usize,
+    /// Whether `hidden` was cut short
+    pub truncated: bool,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileStatus {
     pub path: String,
@@ -27,13 +134,154 @@ pub struct FileStatus {
     pub has_dvc_file: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A selected file or directory with its git and DVC state
+#[derive(Debug, Serialize)]
+pub struct SelectedFileStatus {
+    /// Path as it was selected
+    pub path: String,
+    /// Path relative to the repository root
+    pub relative_path: String,
+    pub exists: bool,
+    pub is_directory: bool,
+    /// Size on disk, or the tracked size of DVC data missing from the workspace
+    pub size: Option<u64>,
+    pub git_status: String,
+    pub dvc_tracked: bool,
+    /// DVC status of tracked data, e.g. "modified"
+    pub dvc_status: Option<String>,
+    /// Whether the file, or the pointer of tracked data, has staged changes
+    pub staged: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FileEntry {
     pub path: String,
     pub size: u64,
     pub is_directory: bool,
     pub has_dvc_file: bool,
     pub git_status: String,
+    /// Category detected from the content, e.g. "image" or "table"; `None`
+    /// for directories
+    pub file_type: Option<String>,
+    /// Statuses of everything below a directory; `None` for files
+    pub rollup: Option<DirectoryRollup>,
+    /// Whether the data of a DVC-tracked entry is on the default remote:
+    /// "pushed", "not-pushed" or "unknown"; `None` for other entries
+    pub remote_status: Option<String>,
+}
+
+/// Statuses of the files below a directory, so folders can be badged without
+/// loading their children
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct DirectoryRollup {
+    /// Files by status, e.g. {"modified": 2, "pushed": 40}. DVC-tracked
+    /// directories count as one file.
+    pub counts: BTreeMap<String, usize>,
+    /// "contains_conflict", "contains_modified", "contains_deleted",
+    /// "contains_staged", "contains_untracked" or "contains_changes" for the
+    /// most pressing status inside; "all_pushed" or "empty" otherwise
+    pub summary: String,
+}
+
+/// Statuses of files that are committed and need no attention
+const SETTLED_STATUSES: &[&str] = &["pushed", "lfs", "submodule"];
+
+/// Statuses a roll-up summary reports, most pressing first
+const ROLLUP_PRIORITY: &[&str] = &["conflict", "modified", "deleted", "staged", "untracked"];
+
+impl DirectoryRollup {
+    fn summarize(&mut self) {
+        self.summary = if self.counts.is_empty() {
+            "empty".to_string()
+        } else if let Some(status) = ROLLUP_PRIORITY
+            .iter()
+            .find(|status| self.counts.contains_key(**status))
+        {
+            format!("contains_{}", status)
+        } else if self
+            .counts
+            .keys()
+            .all(|status| SETTLED_STATUSES.contains(&status.as_str()))
+        {
+            "all_pushed".to_string()
+        } else {
+            "contains_changes".to_string()
+        };
+    }
+}
+
+/// Fills in the roll-up of every directory from the entries below it; the
+/// entries must include the whole subtree of each directory
+pub(crate) fn compute_rollups(entries: &mut [FileEntry]) {
+    let dvc_directories: HashSet<String> = entries
+        .iter()
+        .filter(|entry| entry.is_directory && entry.has_dvc_file)
+        .map(|entry| entry.path.clone())
+        .collect();
+    let ancestors = |path: &str| -> Vec<String> {
+        let mut ancestors = Vec::new();
+        let mut current = path;
+        while let Some((parent, _)) = current.rsplit_once('/') {
+            ancestors.push(parent.to_string());
+            current = parent;
+        }
+        if !path.is_empty() {
+            ancestors.push(String::new());
+        }
+        ancestors
+    };
+
+    let mut rollups: HashMap<String, DirectoryRollup> = HashMap::new();
+    for entry in entries.iter() {
+        let is_leaf = !entry.is_directory || entry.has_dvc_file;
+        let parents = ancestors(&entry.path);
+        // Files inside a DVC-tracked directory are covered by its status
+        if !is_leaf || parents.iter().any(|dir| dvc_directories.contains(dir)) {
+            continue;
+        }
+        for parent in parents {
+            *rollups
+                .entry(parent)
+                .or_default()
+                .counts
+                .entry(entry.git_status.clone())
+                .or_default() += 1;
+        }
+    }
+
+    for entry in entries.iter_mut() {
+        if !entry.is_directory || entry.has_dvc_file {
+            continue;
+        }
+        let mut rollup = rollups.remove(&entry.path).unwrap_or_default();
+        rollup.summarize();
+        entry.rollup = Some(rollup);
+    }
+}
+
+/// Status shown for a changed path; workspace changes win over staged ones
+fn status_label(status: Status) -> &'static str {
+    if status.is_wt_new() {
+        "untracked"
+    } else if status.is_index_new() {
+        "staged"
+    } else if status.is_wt_modified() {
+        "modified"
+    } else if status.is_index_modified() {
+        "staged"
+    } else if status.is_wt_deleted() {
+        "deleted"
+    } else if status.is_index_deleted() {
+        "staged"
+    } else if status.is_conflicted() {
+        "conflict"
+    } else {
+        "other"
+    }
+}
+
+fn is_staged(status: Status) -> bool {
+    status.is_index_new() || status.is_index_modified() || status.is_index_deleted()
 }
 
 fn update_git_status_map(repo_root: &Path) -> Result<HashMap<String, String>, String> {
@@ -75,9 +323,15 @@ fn update_git_status_map(repo_root: &Path) -> Result<HashMap<String, String>, St
         })
         .map_err(|e| format!("Failed to walk tree: {}", e))?;
 
-        // First, mark all tracked files in HEAD as pushed
+        // First, mark all tracked files in HEAD as pushed, or as LFS files
+        let has_lfs = !lfs::lfs_patterns(repo_root).is_empty();
         for file in tracked_files {
-            status_map.insert(file, "pushed".to_string());
+            let status = if has_lfs && lfs::is_lfs_tracked(&repo, Path::new(&file)) {
+                "lfs"
+            } else {
+                "pushed"
+            };
+            status_map.insert(file, status.to_string());
         }
     }
 
@@ -97,31 +351,20 @@ fn update_git_status_map(repo_root: &Path) -> Result<HashMap<String, String>, St
 
         let normalized_path = Path::new(path).to_string_lossy().replace('\\', "/");
 
-        let git_status = if status.is_wt_new() {
-            "untracked"
-        } else if status.is_index_new() {
-            "staged"
-        } else if status.is_wt_modified() {
-            "modified"
-        } else if status.is_index_modified() {
-            "staged"
-        } else if status.is_wt_deleted() {
-            "deleted"
-        } else if status.is_index_deleted() {
-            "staged"
-        } else if status.is_conflicted() {
-            "conflict"
-        } else {
-            "other"
-        };
+        status_map.insert(normalized_path, status_label(status).to_string());
+    }
 
-        status_map.insert(normalized_path, git_status.to_string());
+    // Submodule roots are shown as such rather than as modified directories
+    for path in submodule::paths(&repo) {
+        status_map.insert(path, "submodule".to_string());
     }
 
     Ok(status_map)
 }
 
-fn get_repo_git_status(path: &Path) -> Result<(PathBuf, HashMap<String, String>), String> {
+pub(crate) fn get_repo_git_status(
+    path: &Path,
+) -> Result<(PathBuf, HashMap<String, String>), String> {
     // Find the git repository root using git2
     let repo =
         Repository::discover(path).map_err(|e| format!("Failed to find git repository: {}", e))?;
@@ -164,7 +407,7 @@ fn get_relative_path(path: &Path, repo_root: &Path) -> String {
         .unwrap_or_else(|_| path.to_string_lossy().replace('\\', "/"))
 }
 
-fn get_git_status_for_path(
+pub(crate) fn get_git_status_for_path(
     path: &Path,
     repo_root: &Path,
     git_status_map: &HashMap<String, String>,
@@ -201,12 +444,12 @@ fn get_git_status_for_path(
 }
 
 // Returns an ordered list of file entries inside a directory recursively, similar to list_files in gitbutler-fs
-fn list_file_entries<P: AsRef<Path>>(
+pub(crate) fn list_file_entries<P: AsRef<Path>>(
     dir_path: P,
     repo_root: &Path,
     git_status_map: &HashMap<String, String>,
     dvc_status_map: &HashMap<String, String>,
-    ignore_prefixes: &[&str],
+    filter: &TreeFilter,
     recursive: bool,
 ) -> Result<Vec<FileEntry>, String> {
     let mut files = Vec::new();
@@ -216,7 +459,10 @@ fn list_file_entries<P: AsRef<Path>>(
         return Ok(files);
     }
 
-    for entry in WalkDir::new(dir_path).max_depth(if recursive { usize::MAX } else { 1 }) {
+    let mut walker = WalkDir::new(dir_path)
+        .max_depth(if recursive { usize::MAX } else { 1 })
+        .into_iter();
+    while let Some(entry) = walker.next() {
         let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
         let path = entry.path();
 
@@ -236,11 +482,25 @@ fn list_file_entries<P: AsRef<Path>>(
             continue;
         }
 
-        // Skip ignored directories
-        if entry.file_type().is_dir() {
-            let dir_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("");
-            if ignore_prefixes.contains(&dir_name) {
-                continue;
+        // Skip ignored and too deep entries, and everything below them
+        let relative_path = get_relative_path(path, repo_root);
+        if !relative_path.is_empty()
+            && (filter.is_ignored(&relative_path) || filter.is_too_deep(&relative_path))
+        {
+            if entry.file_type().is_dir() {
+                walker.skip_current_dir();
+            }
+            continue;
+        }
+
+        if let Some(max_entries) = filter.max_entries {
+            if files.len() >= max_entries {
+                warn!(
+                    "Listing of {} stopped at {} entries",
+                    dir_path.display(),
+                    max_entries
+                );
+                break;
             }
         }
 
@@ -264,13 +524,19 @@ fn list_file_entries<P: AsRef<Path>>(
 
         // Override with DVC status if file has DVC tracking
         if has_dvc_file {
-            let relative_path = get_relative_path(path, repo_root);
             if let Some(dvc_status) = dvc_status_map.get(&relative_path) {
                 git_status = dvc_status.clone();
             }
         }
 
-        let relative_path = get_relative_path(path, repo_root);
+        // Files of a submodule belong to its own repository, so only its root
+        // is listed
+        let is_submodule_root =
+            entry.file_type().is_dir() && path != repo_root && submodule::is_repo_root(path);
+        if is_submodule_root {
+            git_status = "submodule".to_string();
+            walker.skip_current_dir();
+        }
 
         files.push(FileEntry {
             path: relative_path,
@@ -278,36 +544,118 @@ fn list_file_entries<P: AsRef<Path>>(
             is_directory: entry.file_type().is_dir(),
             has_dvc_file,
             git_status,
+            file_type: file_type::detect(path).map(|file_type| file_type.category),
+            rollup: None,
+            remote_status: None,
         });
     }
 
     files.sort_by(|a, b| a.path.cmp(&b.path));
+    if recursive {
+        compute_rollups(&mut files);
+    }
 
     Ok(files)
 }
 
-#[tauri::command]
-pub fn get_file_tree_structure(
-    app_handle: tauri::AppHandle,
-    path: &str,
-) -> Result<Vec<FileEntry>, String> {
+/// Walks the repository and returns every entry with its git/DVC status
+pub fn walk_file_tree(app_handle: &tauri::AppHandle, path: &str) -> Result<Vec<FileEntry>, String> {
     let path = Path::new(path);
     let (repo_root, git_status_map) = get_repo_git_status(path)?;
-    let dvc_status_map = dvc::dvc_diff(&app_handle, path)?;
-
-    // Define directories to ignore (similar to gitbutler-fs patterns)
-    let ignore_prefixes = &["target", "node_modules", ".git", "dist", "build"];
+    let dvc_status_map = dvc::dvc_diff(app_handle, path)?;
+    let filter = TreeFilter::load(app_handle, &repo_root)?;
 
     list_file_entries(
         path,
         &repo_root,
         &git_status_map,
         &dvc_status_map,
-        ignore_prefixes,
+        &filter,
         true, // recursive
     )
 }
 
+/// Saves the tree walker settings of a repository and rebuilds its index
+/// with them on the next load
+#[tauri::command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_tree_settings(
+    app_handle: AppHandle,
+    repo_path: String,
+    tree: TreeSettings,
+) -> Result<TreeSettings, String> {
+    TreeFilter::new(&tree)?;
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let saved = settings::update(&app_handle, &repo_root, |settings| {
+        settings.tree = tree;
+    })?
+    .tree;
+    index::invalidate(&app_handle, &repo_path)?;
+    Ok(saved)
+}
+
+/// What `patterns` would hide from the tree of a repository, to try patterns
+/// out before saving them
+#[tauri::command]
+#[instrument(err(Debug))]
+pub fn preview_ignore_effect(
+    repo_path: String,
+    patterns: Vec<String>,
+) -> Result<IgnorePreview, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let filter = TreeFilter {
+        patterns: compile_patterns(&patterns)?,
+        ..TreeFilter::default()
+    };
+
+    let mut preview = IgnorePreview {
+        hidden: Vec::new(),
+        hidden_count: 0,
+        truncated: false,
+    };
+    // Hidden files are never listed, so they aren't reported either
+    let mut walker = WalkDir::new(&repo_root)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| !entry.file_name().to_string_lossy().starts_with('.'));
+    while let Some(entry) = walker.next() {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let relative_path = get_relative_path(entry.path(), &repo_root);
+        if !filter.is_ignored(&relative_path) {
+            continue;
+        }
+        if entry.file_type().is_dir() {
+            preview.hidden_count += WalkDir::new(entry.path()).into_iter().count();
+            walker.skip_current_dir();
+        } else {
+            preview.hidden_count += 1;
+        }
+        if preview.hidden.len() < MAX_PREVIEW_PATHS {
+            preview.hidden.push(relative_path);
+        } else {
+            preview.truncated = true;
+        }
+    }
+    Ok(preview)
+}
+
+/// Serves the tree from the persistent index, building it on first use and
+/// applying watcher changes incrementally
+#[tauri::command]
+pub fn get_file_tree_structure(
+    app_handle: tauri::AppHandle,
+    index_state: State<'_, FileIndexState>,
+    tree_cache: State<'_, TreeCacheState>,
+    path: &str,
+) -> Result<FileTreeSnapshot, String> {
+    let snapshot = index::load_tree(&app_handle, &index_state, path)?;
+
+    let mut cache = tree_cache.lock().map_err(|e| e.to_string())?;
+    cache.insert(path.to_string(), snapshot.entries.clone());
+
+    Ok(snapshot)
+}
+
 #[tauri::command]
 pub fn get_file_binary(path: &str) -> Result<String, String> {
     // Normalize path separators for Windows
@@ -351,6 +699,35 @@ pub fn get_selected_files(state: State<'_, SelectedFilesState>) -> Result<Vec<St
     Ok(selected_files.paths.iter().cloned().collect())
 }
 
+/// Whether each path is selected, partially selected (a directory with some
+/// of its contents selected) or not selected
+#[tauri::command]
+pub fn get_selection_states(
+    state: State<'_, SelectedFilesState>,
+    paths: Vec<String>,
+) -> Result<HashMap<String, SelectionState>, String> {
+    let selected_files = state.lock().map_err(|e| e.to_string())?;
+    Ok(paths
+        .into_iter()
+        .map(|path| {
+            let selection = selected_files.selection_state(&path);
+            (path, selection)
+        })
+        .collect())
+}
+
+/// The selection expanded for `add_dvc_files` (whole directories where
+/// possible) or, with `files_only`, for `git_add_files`
+#[tauri::command]
+pub fn get_expanded_selection(
+    state: State<'_, SelectedFilesState>,
+    repo_path: &str,
+    files_only: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let selected_files = state.lock().map_err(|e| e.to_string())?;
+    Ok(selected_files.expand(Path::new(repo_path), files_only.unwrap_or(false)))
+}
+
 #[tauri::command]
 pub fn clear_selected_files(state: State<'_, SelectedFilesState>) -> Result<(), String> {
     let mut selected_files = state.lock().map_err(|e| e.to_string())?;
@@ -421,6 +798,115 @@ pub fn get_files_status(
     Ok(statuses)
 }
 
+/// Status of every selected path from one git status pass and one DVC diff,
+/// for the selected-files panel
+#[tauri::command]
+pub fn get_selected_files_status(
+    app_handle: tauri::AppHandle,
+    state: State<'_, SelectedFilesState>,
+    repo_path: &str,
+) -> Result<Vec<SelectedFileStatus>, String> {
+    let mut selected: Vec<String> = {
+        let selected_files = state.lock().map_err(|e| e.to_string())?;
+        selected_files.paths.iter().cloned().collect()
+    };
+    if selected.is_empty() {
+        return Ok(Vec::new());
+    }
+    selected.sort();
+
+    let repo = Repository::discover(repo_path)
+        .map_err(|e| format!("Failed to find git repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+
+    let mut status_options = StatusOptions::new();
+    status_options
+        .include_untracked(true)
+        .recurse_untracked_dirs(true)
+        .include_ignored(false);
+    let statuses: Vec<(String, Status)> = repo
+        .statuses(Some(&mut status_options))
+        .map_err(|e| format!("Failed to get git status: {}", e))?
+        .iter()
+        .filter_map(|entry| Some((entry.path()?.to_string(), entry.status())))
+        .collect();
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+
+    let entries: Vec<(String, PathBuf, PathBuf)> = selected
+        .into_iter()
+        .map(|path| {
+            let absolute = if Path::new(&path).is_absolute() {
+                PathBuf::from(&path)
+            } else {
+                repo_root.join(&path)
+            };
+            let pointer = dvcfile::pointer_path(&absolute);
+            (path, absolute, pointer)
+        })
+        .collect();
+    let dvc_status_map = if entries.iter().any(|(_, _, pointer)| pointer.exists()) {
+        dvc::dvc_diff(&app_handle, &repo_root)?
+    } else {
+        HashMap::new()
+    };
+
+    let mut result = Vec::new();
+    for (path, absolute, pointer) in entries {
+        let relative_path = get_relative_path(&absolute, &repo_root);
+        let dvc_tracked = pointer.exists();
+        let git_path = if dvc_tracked {
+            get_relative_path(&pointer, &repo_root)
+        } else {
+            relative_path.clone()
+        };
+        let metadata = fs::metadata(&absolute).ok();
+        let is_directory = metadata.as_ref().is_some_and(|m| m.is_dir());
+
+        // A directory takes the status of the changes inside it
+        let prefix = format!("{}/", git_path);
+        let changes: Vec<Status> = statuses
+            .iter()
+            .filter(|(path, _)| {
+                *path == git_path || (is_directory && !dvc_tracked && path.starts_with(&prefix))
+            })
+            .map(|(_, status)| *status)
+            .collect();
+        let git_status = match changes.first() {
+            Some(status) => status_label(*status),
+            None if index.get_path(Path::new(&git_path), 0).is_some() => "pushed",
+            None if is_directory && !dvc_tracked => "pushed",
+            None => "untracked",
+        };
+
+        let size = match &metadata {
+            Some(metadata) if metadata.is_file() => Some(metadata.len()),
+            _ if dvc_tracked => dvcfile::read_dvc_file(&pointer)
+                .ok()
+                .and_then(|dvc_file| dvc_file.outs.into_iter().next())
+                .and_then(|out| out.size),
+            _ => None,
+        };
+
+        result.push(SelectedFileStatus {
+            path,
+            exists: metadata.is_some(),
+            is_directory,
+            size,
+            git_status: git_status.to_string(),
+            dvc_tracked,
+            dvc_status: dvc_status_map.get(&relative_path).cloned(),
+            staged: changes.into_iter().any(is_staged),
+            relative_path,
+        });
+    }
+    Ok(result)
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GitStatusEntry {
     pub path: String,
@@ -0,0 +1,159 @@
+use serde::Serialize;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+use tauri::command;
+use tracing::instrument;
+
+/// Bytes read to detect a type; git also looks this far to tell binary from
+/// text
+const SNIFF_LEN: usize = 8000;
+
+/// Magic bytes at the start of common formats, with their MIME type and
+/// category
+const MAGIC: &[(&[u8], &str, &str)] = &[
+    (b"\x89PNG", "image/png", "image"),
+    (b"\xFF\xD8\xFF", "image/jpeg", "image"),
+    (b"GIF8", "image/gif", "image"),
+    (b"II*\0", "image/tiff", "image"),
+    (b"MM\0*", "image/tiff", "image"),
+    (b"PK\x03\x04", "application/zip", "archive"),
+    (b"\x1F\x8B", "application/gzip", "archive"),
+    (b"7z\xBC\xAF", "application/x-7z-compressed", "archive"),
+    (b"\xFD7zXZ\0", "application/x-xz", "archive"),
+    (b"BZh", "application/x-bzip2", "archive"),
+    (b"%PDF", "application/pdf", "document"),
+    (b"PAR1", "application/vnd.apache.parquet", "table"),
+    (b"ARROW1", "application/vnd.apache.arrow.file", "table"),
+    (b"ORC", "application/x-orc", "table"),
+    (b"\x89HDF", "application/x-hdf5", "array"),
+    (b"\x93NUMPY", "application/x-npy", "array"),
+    (b"SQLite format 3\0", "application/vnd.sqlite3", "database"),
+    (b"ID3", "audio/mpeg", "audio"),
+    (b"fLaC", "audio/flac", "audio"),
+    (b"OggS", "audio/ogg", "audio"),
+    (b"\x7FELF", "application/x-executable", "executable"),
+];
+
+/// Binary formats without a signature of their own
+const BINARY_EXTENSIONS: &[(&str, &str, &str)] = &[
+    ("pt", "application/octet-stream", "model"),
+    ("pth", "application/octet-stream", "model"),
+    ("ckpt", "application/octet-stream", "model"),
+    ("onnx", "application/octet-stream", "model"),
+    ("pkl", "application/octet-stream", "model"),
+    ("joblib", "application/octet-stream", "model"),
+    ("safetensors", "application/octet-stream", "model"),
+    ("tflite", "application/octet-stream", "model"),
+    ("pb", "application/octet-stream", "model"),
+    ("feather", "application/vnd.apache.arrow.file", "table"),
+];
+
+/// Text formats, which only their extension tells apart
+const TEXT_EXTENSIONS: &[(&str, &str, &str)] = &[
+    ("csv", "text/csv", "table"),
+    ("tsv", "text/tab-separated-values", "table"),
+    ("json", "application/json", "text"),
+    ("jsonl", "application/jsonl", "text"),
+    ("yaml", "application/yaml", "text"),
+    ("yml", "application/yaml", "text"),
+    ("xml", "application/xml", "text"),
+    ("svg", "image/svg+xml", "image"),
+    ("html", "text/html", "text"),
+    ("md", "text/markdown", "text"),
+    ("py", "text/x-python", "text"),
+    ("ipynb", "application/x-ipynb+json", "text"),
+];
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FileType {
+    pub mime: String,
+    /// "image", "audio", "video", "archive", "document", "table", "array",
+    /// "model", "database", "executable", "text" or "binary"
+    pub category: String,
+    pub binary: bool,
+}
+
+impl FileType {
+    fn new(mime: &str, category: &str, binary: bool) -> Self {
+        Self {
+            mime: mime.to_string(),
+            category: category.to_string(),
+            binary,
+        }
+    }
+}
+
+/// Same heuristic as git: binary content has a NUL byte near the start
+pub(crate) fn looks_binary(head: &[u8]) -> bool {
+    head[..head.len().min(SNIFF_LEN)].contains(&0)
+}
+
+/// First bytes of a file, enough to detect its type
+pub(crate) fn read_head(path: &Path) -> Option<Vec<u8>> {
+    let mut head = Vec::with_capacity(SNIFF_LEN);
+    File::open(path)
+        .ok()?
+        .take(SNIFF_LEN as u64)
+        .read_to_end(&mut head)
+        .ok()?;
+    Some(head)
+}
+
+/// Type of content starting with `head`, from its magic bytes and then, for
+/// formats without any, the extension of `path`
+pub(crate) fn detect_content(head: &[u8], path: &Path) -> FileType {
+    if let Some(&(_, mime, category)) = MAGIC.iter().find(|(magic, _, _)| head.starts_with(magic)) {
+        return FileType::new(mime, category, true);
+    }
+    if head.starts_with(b"RIFF") && head.len() >= 12 {
+        match &head[8..12] {
+            b"WAVE" => return FileType::new("audio/wav", "audio", true),
+            b"AVI " => return FileType::new("video/x-msvideo", "video", true),
+            b"WEBP" => return FileType::new("image/webp", "image", true),
+            _ => {}
+        }
+    }
+    if head.get(4..8) == Some(&b"ftyp"[..]) {
+        return FileType::new("video/mp4", "video", true);
+    }
+
+    let binary = looks_binary(head);
+    let extensions = if binary {
+        BINARY_EXTENSIONS
+    } else {
+        TEXT_EXTENSIONS
+    };
+    let by_extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| {
+            extensions
+                .iter()
+                .find(|(known, _, _)| known.eq_ignore_ascii_case(ext))
+        });
+    match by_extension {
+        Some(&(_, mime, category)) => FileType::new(mime, category, binary),
+        None if binary => FileType::new("application/octet-stream", "binary", true),
+        None => FileType::new("text/plain", "text", false),
+    }
+}
+
+/// Type of a file on disk; `None` for directories and unreadable files
+pub(crate) fn detect(path: &Path) -> Option<FileType> {
+    if !path.is_file() {
+        return None;
+    }
+    Some(detect_content(&read_head(path)?, path))
+}
+
+/// Detects a file's type from its content, for icons and picking a preview
+#[command]
+#[instrument(err(Debug))]
+pub fn detect_file_type(path: String) -> Result<FileType, String> {
+    let path = Path::new(&path);
+    if !path.exists() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    detect(path).ok_or_else(|| format!("Failed to read {}", path.display()))
+}
@@ -0,0 +1,434 @@
+use git2::{Cred, PushOptions, Repository};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tauri::command;
+use tauri::AppHandle;
+use tracing::instrument;
+
+use crate::remote;
+
+const GITHUB_API: &str = "https://api.github.com";
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Provider {
+    Github,
+    Gitlab,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RemoteRepository {
+    pub provider: Provider,
+    pub name: String,
+    pub web_url: String,
+    pub clone_url: String,
+    pub ssh_url: String,
+    pub pushed_branch: Option<String>,
+}
+
+/// API base URL, allowing self-hosted GitLab/GitHub Enterprise instances
+pub(crate) fn api_base(provider: Provider, base_url: Option<&str>) -> String {
+    match (provider, base_url) {
+        (_, Some(url)) => url.trim_end_matches('/').to_string(),
+        (Provider::Github, None) => GITHUB_API.to_string(),
+        (Provider::Gitlab, None) => GITLAB_API.to_string(),
+    }
+}
+
+/// Builds an authenticated API request for the provider
+pub(crate) fn api_request(
+    provider: Provider,
+    method: &str,
+    url: &str,
+    token: &str,
+) -> ureq::Request {
+    let request = ureq::request(method, url).set("User-Agent", "fenn-app");
+    match provider {
+        Provider::Github => request
+            .set("Authorization", &format!("Bearer {}", token))
+            .set("Accept", "application/vnd.github+json"),
+        Provider::Gitlab => request.set("PRIVATE-TOKEN", token),
+    }
+}
+
+/// Turns an API failure into a readable message, including the provider's error body
+pub(crate) fn api_error(action: &str, error: ureq::Error) -> String {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            let message = serde_json::from_str::<Value>(&body)
+                .ok()
+                .and_then(|v| v.get("message").map(|m| m.to_string()))
+                .unwrap_or(body);
+            format!("Failed to {}: HTTP {} {}", action, code, message)
+        }
+        other => format!("Failed to {}: {}", action, other),
+    }
+}
+
+/// Username paired with a token for HTTPS git operations
+pub(crate) fn token_username(provider: Provider) -> &'static str {
+    match provider {
+        Provider::Github => "x-access-token",
+        Provider::Gitlab => "oauth2",
+    }
+}
+
+fn string_field(value: &Value, key: &str) -> String {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn push_initial_branch(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    provider: Provider,
+    token: &str,
+) -> Result<String, String> {
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let branch = head
+        .shorthand()
+        .ok_or("Failed to get branch name")?
+        .to_string();
+
+    let mut remote = repo
+        .find_remote("origin")
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+
+    let operation_id = remote::operation_id(None);
+    let mut callbacks = remote::progress_callbacks(app_handle, &operation_id, "push");
+    let username = token_username(provider);
+    let token = token.to_string();
+    callbacks
+        .credentials(move |_url, _username, _allowed| Cred::userpass_plaintext(username, &token));
+
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote
+        .push(&[refspec.as_str()], Some(&mut push_options))
+        .map_err(|e| format!("Failed to push: {}", e))?;
+
+    // Record the upstream so status can report ahead/behind
+    let mut config = repo
+        .config()
+        .map_err(|e| format!("Failed to open git config: {}", e))?;
+    config
+        .set_str(&format!("branch.{}.remote", branch), "origin")
+        .map_err(|e| format!("Failed to set upstream remote: {}", e))?;
+    config
+        .set_str(
+            &format!("branch.{}.merge", branch),
+            &format!("refs/heads/{}", branch),
+        )
+        .map_err(|e| format!("Failed to set upstream branch: {}", e))?;
+
+    Ok(branch)
+}
+
+/// Creates a repository on GitHub or GitLab, adds it as `origin` and pushes
+/// the current branch to it
+#[command(async)]
+#[instrument(skip(app_handle, repo_path, token), err(Debug))]
+pub fn create_remote_repository(
+    app_handle: AppHandle,
+    repo_path: String,
+    provider: Provider,
+    name: String,
+    private: bool,
+    token: String,
+    base_url: Option<String>,
+) -> Result<RemoteRepository, String> {
+    if name.trim().is_empty() {
+        return Err("Repository name cannot be empty".to_string());
+    }
+
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    if repo.find_remote("origin").is_ok() {
+        return Err("Repository already has an 'origin' remote".to_string());
+    }
+
+    let api = api_base(provider, base_url.as_deref());
+    let (url, body) = match provider {
+        Provider::Github => (
+            format!("{}/user/repos", api),
+            json!({ "name": name, "private": private }),
+        ),
+        Provider::Gitlab => (
+            format!("{}/projects", api),
+            json!({
+                "name": name,
+                "visibility": if private { "private" } else { "public" },
+            }),
+        ),
+    };
+
+    let created: Value = api_request(provider, "POST", &url, &token)
+        .send_json(body)
+        .map_err(|e| api_error("create repository", e))?
+        .into_json()
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+    let (web_url, clone_url, ssh_url) = match provider {
+        Provider::Github => (
+            string_field(&created, "html_url"),
+            string_field(&created, "clone_url"),
+            string_field(&created, "ssh_url"),
+        ),
+        Provider::Gitlab => (
+            string_field(&created, "web_url"),
+            string_field(&created, "http_url_to_repo"),
+            string_field(&created, "ssh_url_to_repo"),
+        ),
+    };
+
+    repo.remote("origin", &clone_url)
+        .map_err(|e| format!("Failed to add origin remote: {}", e))?;
+
+    // An unborn HEAD has nothing to push yet; the repository is still usable
+    let pushed_branch = if repo.head().is_ok() {
+        Some(push_initial_branch(&app_handle, &repo, provider, &token)?)
+    } else {
+        None
+    };
+
+    Ok(RemoteRepository {
+        provider,
+        name,
+        web_url,
+        clone_url,
+        ssh_url,
+        pushed_branch,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct PullRequest {
+    pub number: u64,
+    pub title: String,
+    pub state: String,
+    pub web_url: String,
+    pub head: String,
+    pub base: String,
+    pub author: String,
+}
+
+/// Provider of a self-hosted API from its standard path: `/api/v3` on
+/// GitHub Enterprise, `/api/v4` on GitLab
+fn api_provider(base_url: &str) -> Option<Provider> {
+    if base_url.contains("/api/v4") {
+        Some(Provider::Gitlab)
+    } else if base_url.contains("/api/v3") {
+        Some(Provider::Github)
+    } else {
+        None
+    }
+}
+
+/// Provider and "owner/repo" path derived from a remote URL such as
+/// `git@github.com:owner/repo.git` or `https://gitlab.com/group/repo.git`.
+/// The provider is taken from the host unless `provider` is given, as for a
+/// self-hosted instance whose host can be anything.
+fn parse_remote_url(url: &str, provider: Option<Provider>) -> Option<(Provider, String)> {
+    let without_scheme = url.split("://").nth(1).unwrap_or(url);
+    let without_user = without_scheme
+        .rsplit_once('@')
+        .map(|(_, rest)| rest)
+        .unwrap_or(without_scheme);
+    let (host, path) = without_user.split_once([':', '/'])?;
+
+    let provider = match provider {
+        Some(provider) => provider,
+        None if host.contains("github") => Provider::Github,
+        None if host.contains("gitlab") => Provider::Gitlab,
+        None => return None,
+    };
+
+    // Drop a port left over from "host:port/path" URLs
+    let path = match path.split_once('/') {
+        Some((port, rest)) if port.chars().all(|c| c.is_ascii_digit()) => rest,
+        _ => path,
+    };
+    let path = path.trim_matches('/').trim_end_matches(".git").to_string();
+    if path.is_empty() {
+        return None;
+    }
+
+    Some((provider, path))
+}
+
+/// Provider and project of the `origin` remote. With a `base_url` the remote
+/// is trusted to live on that instance whatever its host, and the provider
+/// comes from `provider` or the API path.
+fn origin_project(
+    repo_path: &str,
+    base_url: Option<&str>,
+    provider: Option<Provider>,
+) -> Result<(Provider, String), String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let origin = repo
+        .find_remote("origin")
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+    let url = origin.url().ok_or("Origin remote has no URL")?;
+    let provider = provider.or_else(|| base_url.and_then(api_provider));
+    if let (Some(base_url), None) = (base_url, provider) {
+        if parse_remote_url(url, None).is_none() {
+            return Err(format!(
+                "Can't tell whether {} is GitHub or GitLab; pass the provider",
+                base_url
+            ));
+        }
+    }
+    parse_remote_url(url, provider)
+        .ok_or_else(|| format!("Origin {} is not a GitHub or GitLab repository", url))
+}
+
+fn gitlab_project_id(path: &str) -> String {
+    path.replace('/', "%2F")
+}
+
+fn pull_request_from_json(provider: Provider, value: &Value) -> PullRequest {
+    match provider {
+        Provider::Github => PullRequest {
+            number: value.get("number").and_then(|v| v.as_u64()).unwrap_or(0),
+            title: string_field(value, "title"),
+            state: string_field(value, "state"),
+            web_url: string_field(value, "html_url"),
+            head: value
+                .get("head")
+                .map(|h| string_field(h, "ref"))
+                .unwrap_or_default(),
+            base: value
+                .get("base")
+                .map(|b| string_field(b, "ref"))
+                .unwrap_or_default(),
+            author: value
+                .get("user")
+                .map(|u| string_field(u, "login"))
+                .unwrap_or_default(),
+        },
+        Provider::Gitlab => PullRequest {
+            number: value.get("iid").and_then(|v| v.as_u64()).unwrap_or(0),
+            title: string_field(value, "title"),
+            state: string_field(value, "state"),
+            web_url: string_field(value, "web_url"),
+            head: string_field(value, "source_branch"),
+            base: string_field(value, "target_branch"),
+            author: value
+                .get("author")
+                .map(|a| string_field(a, "username"))
+                .unwrap_or_default(),
+        },
+    }
+}
+
+/// Opens a pull request (GitHub) or merge request (GitLab) against the
+/// project behind the `origin` remote
+#[command(async)]
+#[instrument(skip(repo_path, token, body), err(Debug))]
+pub fn create_pull_request(
+    repo_path: String,
+    base: String,
+    head: String,
+    title: String,
+    body: String,
+    token: String,
+    base_url: Option<String>,
+    provider: Option<Provider>,
+) -> Result<PullRequest, String> {
+    if title.trim().is_empty() {
+        return Err("Pull request title cannot be empty".to_string());
+    }
+
+    let (provider, project) = origin_project(&repo_path, base_url.as_deref(), provider)?;
+    let api = api_base(provider, base_url.as_deref());
+
+    let (url, payload) = match provider {
+        Provider::Github => (
+            format!("{}/repos/{}/pulls", api, project),
+            json!({ "title": title, "head": head, "base": base, "body": body }),
+        ),
+        Provider::Gitlab => (
+            format!(
+                "{}/projects/{}/merge_requests",
+                api,
+                gitlab_project_id(&project)
+            ),
+            json!({
+                "title": title,
+                "source_branch": head,
+                "target_branch": base,
+                "description": body,
+            }),
+        ),
+    };
+
+    let created: Value = api_request(provider, "POST", &url, &token)
+        .send_json(payload)
+        .map_err(|e| api_error("create pull request", e))?
+        .into_json()
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+    Ok(pull_request_from_json(provider, &created))
+}
+
+/// Lists open pull/merge requests of the project behind the `origin` remote
+#[command(async)]
+#[instrument(skip(repo_path, token), err(Debug))]
+pub fn list_pull_requests(
+    repo_path: String,
+    token: String,
+    base_url: Option<String>,
+    provider: Option<Provider>,
+) -> Result<Vec<PullRequest>, String> {
+    let (provider, project) = origin_project(&repo_path, base_url.as_deref(), provider)?;
+    let api = api_base(provider, base_url.as_deref());
+
+    let url = match provider {
+        Provider::Github => format!("{}/repos/{}/pulls?state=open", api, project),
+        Provider::Gitlab => format!(
+            "{}/projects/{}/merge_requests?state=opened",
+            api,
+            gitlab_project_id(&project)
+        ),
+    };
+
+    let listed: Vec<Value> = api_request(provider, "GET", &url, &token)
+        .call()
+        .map_err(|e| api_error("list pull requests", e))?
+        .into_json()
+        .map_err(|e| format!("Failed to parse API response: {}", e))?;
+
+    Ok(listed
+        .iter()
+        .map(|value| pull_request_from_json(provider, value))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn self_hosted_remotes_parse_when_the_provider_is_known() {
+        let url = "git@code.example.com:2222/team/data.git";
+        assert_eq!(parse_remote_url(url, None), None);
+        assert_eq!(
+            parse_remote_url(url, Some(Provider::Gitlab)),
+            Some((Provider::Gitlab, "team/data".to_string()))
+        );
+        assert_eq!(
+            parse_remote_url("https://github.com/owner/repo.git", None),
+            Some((Provider::Github, "owner/repo".to_string()))
+        );
+    }
+}
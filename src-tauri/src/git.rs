@@ -1,10 +1,29 @@
 use anyhow::Result;
-use git2::{BranchType, Repository, StatusOptions};
-use serde::Serialize;
-use std::path::Path;
+use git2::build::CheckoutBuilder;
+use git2::{
+    BranchType, Commit, ErrorCode, FetchOptions, Oid, Reference, Repository, RepositoryState,
+    Signature, StashApplyOptions, StashFlags, Status, StatusOptions, Tree,
+};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use tauri::command;
+use tauri::AppHandle;
 use tracing::instrument;
 
+use crate::audit;
+use crate::branch_compare;
+use crate::checkout::{self, CheckoutSummary, DataSyncSummary};
+use crate::conflicts::{self, ConflictFile};
+use crate::diff::{self, BinaryChangeHint};
+use crate::dvcfile::DvcOut;
+use crate::hooks::{self, DvcTrackSuggestion, HookViolation};
+use crate::offline::{self, SyncOperation};
+use crate::size_history::{self, SizeAlert};
+use crate::submodule::{self, SubmoduleInfo};
+use crate::{events, identity, rebase, remote, repo_manager, settings, signing, sparse, transfer};
+
 #[derive(Debug, Serialize)]
 pub struct GitFile {
     pub path: String,
@@ -33,6 +52,28 @@ pub struct GitStatus {
     pub has_untracked: bool,
     pub has_staged: bool,
     pub has_unstaged: bool,
+    pub submodules: Vec<SubmoduleInfo>,
+    /// Set when HEAD is detached, e.g. after `git_checkout_tag`
+    pub detached: Option<DetachedHead>,
+}
+
+/// Commit a detached HEAD points at
+#[derive(Debug, Serialize)]
+pub struct DetachedHead {
+    pub commit_id: String,
+    pub short_id: String,
+    pub summary: String,
+    /// Tag pointing at the commit, when there is one
+    pub tag: Option<String>,
+    /// Name to offer when the user starts a branch here with `git_checkout`
+    pub suggested_branch: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DetachedCheckoutResult {
+    pub head: DetachedHead,
+    /// DVC data brought in line with the checked out pointers
+    pub data: CheckoutSummary,
 }
 
 #[derive(Debug, Serialize)]
@@ -40,20 +81,53 @@ pub struct CommitResult {
     pub success: bool,
     pub message: String,
     pub commit_id: Option<String>,
+    /// Pre-commit checks that blocked the commit
+    pub violations: Vec<HookViolation>,
+    /// Datasets the commit grew beyond the project's size alert limits
+    pub size_alerts: Vec<SizeAlert>,
+    /// Notebooks committed without their outputs
+    pub stripped_notebooks: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BranchSwitchResult {
+    pub message: String,
+    /// Data files updated and removed by the DVC checkout, when one was asked for
+    pub data: Option<DataSyncSummary>,
+}
+
+/// How `git_pull` integrates the fetched upstream into the current branch
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PullStrategy {
+    /// Creates a merge commit, like `git pull --no-rebase`
+    #[default]
+    Merge,
+    /// Only moves the branch forward and refuses to pull diverged history
+    FfOnly,
+    /// Replays local commits on top of the upstream, like `git pull --rebase`
+    Rebase,
 }
 
 /// Enhanced git status using git2 library for better performance and reliability
 #[command]
-#[instrument(skip(repo_path), err(Debug))]
-pub fn git_status(repo_path: String) -> Result<GitStatus, String> {
-    let repo =
-        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_status(app_handle: AppHandle, repo_path: String) -> Result<GitStatus, String> {
+    repo_manager::read(&app_handle, &repo_path, status)
+}
 
-    // Get current branch
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let current_branch = head.shorthand().unwrap_or("HEAD").to_string();
+pub(crate) fn status(repo: &Repository) -> Result<GitStatus, String> {
+    let (current_branch, detached) = match head_branch(repo)? {
+        Some(branch) => (branch, None),
+        None => {
+            let head = detached_head(repo)?;
+            let label = format!(
+                "(detached at {})",
+                head.tag.as_deref().unwrap_or(&head.short_id)
+            );
+            (label, Some(head))
+        }
+    };
 
     // Configure status options for comprehensive status
     let mut status_opts = StatusOptions::new();
@@ -68,6 +142,9 @@ pub fn git_status(repo_path: String) -> Result<GitStatus, String> {
         .statuses(Some(&mut status_opts))
         .map_err(|e| format!("Failed to get status: {}", e))?;
 
+    // Files left out by sparse checkout are missing on purpose
+    let sparse_skipped = sparse::skipped_paths(repo)?;
+
     let mut files = Vec::new();
     let mut has_untracked = false;
     let mut has_staged = false;
@@ -76,6 +153,9 @@ pub fn git_status(repo_path: String) -> Result<GitStatus, String> {
     for entry in statuses.iter() {
         let path = entry.path().unwrap_or("unknown").to_string();
         let status = entry.status();
+        if status == Status::WT_DELETED && sparse_skipped.contains(&path) {
+            continue;
+        }
 
         let is_staged =
             status.is_index_new() || status.is_index_modified() || status.is_index_deleted();
@@ -120,7 +200,7 @@ pub fn git_status(repo_path: String) -> Result<GitStatus, String> {
     }
 
     // Get ahead/behind information
-    let (ahead, behind) = get_ahead_behind(&repo, &current_branch).unwrap_or((0, 0));
+    let (ahead, behind) = get_ahead_behind(repo, &current_branch).unwrap_or((0, 0));
 
     Ok(GitStatus {
         files,
@@ -130,23 +210,99 @@ pub fn git_status(repo_path: String) -> Result<GitStatus, String> {
         has_untracked,
         has_staged,
         has_unstaged,
+        submodules: submodule::list(repo)?,
+        detached,
     })
 }
 
 /// Enhanced commit function with better error handling and validation
 #[command]
-#[instrument(skip(repo_path, summary, description), err(Debug))]
+#[instrument(skip(app_handle, repo_path, summary, description), err(Debug))]
 pub fn git_commit_and_push(
+    app_handle: AppHandle,
     repo_path: String,
     summary: String,
     description: String,
+) -> Result<CommitResult, String> {
+    // A commit blocked by the pre-commit checks is logged as a failure but
+    // still returned, so the violations reach the caller
+    let mut blocked = None;
+    let result = audit::track(
+        &app_handle,
+        &repo_path,
+        "git_commit_and_push",
+        json!({ "summary": summary, "description": description }),
+        || {
+            let result = repo_manager::write(&app_handle, &repo_path, || {
+                commit(app_handle.clone(), repo_path.clone(), summary, description)
+            })?;
+            if !result.success {
+                let message = result.message.clone();
+                blocked = Some(result);
+                return Err(message);
+            }
+            Ok(result)
+        },
+    );
+    match blocked {
+        Some(result) => Ok(result),
+        None => result,
+    }
+}
+
+pub(crate) fn commit(
+    app_handle: AppHandle,
+    repo_path: String,
+    summary: String,
+    description: String,
+) -> Result<CommitResult, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let hook_settings = settings::load(&app_handle, &repo_root)?.hooks;
+
+    // Signed with the project's key when commit signing is on
+    let mut result = commit_staged(
+        &repo_path,
+        &summary,
+        &description,
+        &hook_settings,
+        |repo, signature, message, tree, parents| {
+            signing::commit(
+                &app_handle,
+                repo,
+                signature,
+                signature,
+                message,
+                tree,
+                parents,
+            )
+        },
+    )?;
+
+    if let Some(commit_id) = result.commit_id.as_deref() {
+        let repo = Repository::open(&repo_path)
+            .map_err(|e| format!("Failed to open repository: {}", e))?;
+        let commit_id =
+            Oid::from_str(commit_id).map_err(|e| format!("Failed to read commit id: {}", e))?;
+        result.size_alerts = size_history::record_commit(&app_handle, &repo, commit_id);
+    }
+    Ok(result)
+}
+
+/// Commits the staged changes after running the pre-commit checks in
+/// `hook_settings`. `create` writes the commit object and moves HEAD to it.
+pub(crate) fn commit_staged(
+    repo_path: &str,
+    summary: &str,
+    description: &str,
+    hook_settings: &hooks::HookSettings,
+    create: impl FnOnce(&Repository, &Signature, &str, &Tree, &[&Commit]) -> Result<Oid, String>,
 ) -> Result<CommitResult, String> {
     if summary.trim().is_empty() {
         return Err("Commit summary cannot be empty".to_string());
     }
 
     let repo =
-        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
     // Check if there are staged changes
     let mut status_opts = StatusOptions::new();
@@ -160,14 +316,49 @@ pub fn git_commit_and_push(
         status.is_index_new() || status.is_index_modified() || status.is_index_deleted()
     });
 
-    if !has_staged {
+    // A merge resolved entirely to our side still needs its merge commit
+    if !has_staged && repo.state() != RepositoryState::Merge {
         return Err("No staged changes to commit".to_string());
     }
 
+    // Create commit message
+    let mut commit_msg = summary.trim().to_string();
+    if !description.trim().is_empty() {
+        commit_msg.push_str("\n\n");
+        commit_msg.push_str(description.trim());
+    }
+
     // Get the index and create a tree
     let mut index = repo
         .index()
         .map_err(|e| format!("Failed to get index: {}", e))?;
+    if index.has_conflicts() {
+        return Err("Resolve all conflicts before committing".to_string());
+    }
+
+    // Run the pre-commit checks configured for the project, on the staged
+    // content as it will be committed. Stripped notebooks only reach the
+    // index on disk once the checks pass.
+    let stripped_notebooks = hooks::strip_staged_notebooks(&repo, &mut index, hook_settings)?;
+    let violations = hooks::run_pre_commit(&repo, &index, hook_settings, &commit_msg)?;
+    if !violations.is_empty() {
+        return Ok(CommitResult {
+            success: false,
+            message: format!(
+                "Commit blocked by {} pre-commit violation(s)",
+                violations.len()
+            ),
+            commit_id: None,
+            violations,
+            size_alerts: Vec::new(),
+            stripped_notebooks: Vec::new(),
+        });
+    }
+    if !stripped_notebooks.is_empty() {
+        index
+            .write()
+            .map_err(|e| format!("Failed to write index: {}", e))?;
+    }
 
     let tree_id = index
         .write_tree()
@@ -177,37 +368,38 @@ pub fn git_commit_and_push(
         .find_tree(tree_id)
         .map_err(|e| format!("Failed to find tree: {}", e))?;
 
-    // Get the current HEAD
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let parent = repo
-        .find_commit(head.target().unwrap())
-        .map_err(|e| format!("Failed to find parent commit: {}", e))?;
+    // The first commit of a repository has no parent
+    let parent = match repo.head() {
+        Ok(head) => Some(
+            head.peel_to_commit()
+                .map_err(|e| format!("Failed to find parent commit: {}", e))?,
+        ),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
 
-    // Create commit message
-    let mut commit_msg = summary.trim().to_string();
-    if !description.trim().is_empty() {
-        commit_msg.push_str("\n\n");
-        commit_msg.push_str(description.trim());
-    }
+    // Committing during a merge concludes it, with the merged commit as second parent
+    let merge_parent = if repo.state() == RepositoryState::Merge {
+        Some(
+            repo.revparse_single("MERGE_HEAD")
+                .and_then(|object| object.peel_to_commit())
+                .map_err(|e| format!("Failed to find merge commit: {}", e))?,
+        )
+    } else {
+        None
+    };
+    let mut parents: Vec<&Commit> = parent.iter().collect();
+    parents.extend(merge_parent.as_ref());
 
     // Get author and committer signatures
-    let signature = repo
-        .signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let signature = identity::signature(&repo)?;
 
     // Create the commit
-    let commit_id = repo
-        .commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            &commit_msg,
-            &tree,
-            &[&parent],
-        )
-        .map_err(|e| format!("Failed to create commit: {}", e))?;
+    let commit_id = create(&repo, &signature, &commit_msg, &tree, &parents)?;
+    if merge_parent.is_some() {
+        repo.cleanup_state()
+            .map_err(|e| format!("Failed to finish merge: {}", e))?;
+    }
 
     // Try to push (commented out as in original)
     // let push_result = push_to_remote(&repo).map_err(|e| format!("Push failed: {}", e))?;
@@ -216,20 +408,192 @@ pub fn git_commit_and_push(
         success: true,
         message: "Commit successful".to_string(),
         commit_id: Some(commit_id.to_string()),
+        violations: Vec::new(),
+        size_alerts: Vec::new(),
+        stripped_notebooks,
     })
 }
 
-/// Enhanced pull function with better error handling
+/// Enhanced pull function with better error handling. With `autostash` local
+/// changes are stashed before the pull and re-applied afterwards, like
+/// `git pull --autostash`. `pull_strategy` defaults to the project's setting.
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_pull(
+    app_handle: AppHandle,
+    repo_path: String,
+    operation_id: Option<String>,
+    autostash: Option<bool>,
+    pull_strategy: Option<PullStrategy>,
+) -> Result<String, String> {
+    let autostash = autostash.unwrap_or(false);
+    let strategy = match pull_strategy {
+        Some(strategy) => strategy,
+        None => settings::load(&app_handle, &transfer::repo_root(&repo_path)?)?.pull_strategy,
+    };
+    let operation = SyncOperation::GitPull {
+        autostash: Some(autostash),
+        pull_strategy: Some(strategy),
+    };
+    offline::queue_when_offline(&app_handle, &repo_path, operation, || {
+        audit::track(
+            &app_handle,
+            &repo_path,
+            "git_pull",
+            json!({
+                "operation_id": operation_id,
+                "autostash": autostash,
+                "pull_strategy": strategy,
+            }),
+            || {
+                repo_manager::write(&app_handle, &repo_path, || {
+                    pull(
+                        app_handle.clone(),
+                        repo_path.clone(),
+                        operation_id,
+                        autostash,
+                        strategy,
+                    )
+                })
+            },
+        )
+    })
+}
+
+/// Saves the strategy `git_pull` uses when none is given
 #[command]
-#[instrument(skip(repo_path), err(Debug))]
-pub fn git_pull(repo_path: String) -> Result<String, String> {
-    let repo =
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_pull_strategy(
+    app_handle: AppHandle,
+    repo_path: String,
+    pull_strategy: PullStrategy,
+) -> Result<PullStrategy, String> {
+    let saved = settings::update(&app_handle, &transfer::repo_root(&repo_path)?, |settings| {
+        settings.pull_strategy = pull_strategy;
+    })?;
+    Ok(saved.pull_strategy)
+}
+
+fn pull(
+    app_handle: AppHandle,
+    repo_path: String,
+    operation_id: Option<String>,
+    autostash: bool,
+    strategy: PullStrategy,
+) -> Result<String, String> {
+    let mut repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    if !autostash {
+        return fetch_and_integrate(&app_handle, &repo, operation_id, strategy);
+    }
+
+    let mut status_opts = StatusOptions::new();
+    status_opts.include_untracked(true);
+    let sparse_skipped = sparse::skipped_paths(&repo)?;
+    let has_changes = repo
+        .statuses(Some(&mut status_opts))
+        .map_err(|e| format!("Failed to get status: {}", e))?
+        .iter()
+        .any(|entry| {
+            entry.status() != Status::WT_DELETED
+                || !entry
+                    .path()
+                    .is_some_and(|path| sparse_skipped.contains(path))
+        });
+    if !has_changes {
+        return fetch_and_integrate(&app_handle, &repo, operation_id, strategy);
+    }
+
+    let signature = identity::signature(&repo)?;
+    repo.stash_save(
+        &signature,
+        "Autostash before pull",
+        Some(StashFlags::INCLUDE_UNTRACKED),
+    )
+    .map_err(|e| format!("Failed to stash local changes: {}", e))?;
+
+    let result = fetch_and_integrate(&app_handle, &repo, operation_id, strategy);
+    if result.is_ok() && repo.state() != RepositoryState::Clean {
+        // Re-applying on top of a stopped merge or rebase would mix both
+        // sets of conflicts
+        return result.map(|message| {
+            format!(
+                "{}. Local changes stay in the stash until the {} is finished",
+                message,
+                if strategy == PullStrategy::Rebase {
+                    "rebase"
+                } else {
+                    "merge"
+                }
+            )
+        });
+    }
+
+    let reapplied = reapply_autostash(&mut repo);
+    match (result, reapplied) {
+        (Ok(message), Ok(())) => Ok(format!("{}. Local changes re-applied", message)),
+        (Ok(message), Err(e)) => Ok(format!("{}. {}", message, e)),
+        (Err(e), Ok(())) => Err(e),
+        (Err(e), Err(stash_error)) => Err(format!("{}. {}", e, stash_error)),
+    }
+}
 
+/// Applies the autostash and drops it, unless applying it conflicts with the
+/// pulled changes; the stash is then kept so nothing is lost
+fn reapply_autostash(repo: &mut Repository) -> Result<(), String> {
+    let mut options = StashApplyOptions::new();
+    options.reinstate_index();
+    if let Err(e) = repo.stash_apply(0, Some(&mut options)) {
+        return Err(format!(
+            "Local changes could not be re-applied and are kept in the stash: {}",
+            e.message()
+        ));
+    }
+
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let conflicted = conflicts::index_conflicts(&index)?;
+    if !conflicted.is_empty() {
+        let paths: Vec<String> = conflicted.into_iter().map(|c| c.path).collect();
+        return Err(format!(
+            "Re-applying local changes conflicted in {}; they are also kept in the stash",
+            paths.join(", ")
+        ));
+    }
+
+    repo.stash_drop(0)
+        .map_err(|e| format!("Failed to drop autostash: {}", e))
+}
+
+/// Moves the current branch forward to `target`. The workspace is updated
+/// first so local changes that would be overwritten abort the pull.
+fn fast_forward(repo: &Repository, head: &Reference, target: &Commit) -> Result<(), String> {
+    let branch_ref = head.name().ok_or("Invalid branch name")?;
+    repo.checkout_tree(target.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| format!("Failed to check out {}: {}", target.id(), e))?;
+    repo.reference(branch_ref, target.id(), true, "pull: fast-forward")
+        .map_err(|e| format!("Failed to update branch: {}", e))?;
+    Ok(())
+}
+
+fn fetch_and_integrate(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    operation_id: Option<String>,
+    strategy: PullStrategy,
+) -> Result<String, String> {
     // Get the current branch
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let head = match repo.head() {
+        Ok(head) => head,
+        Err(e) if e.code() == ErrorCode::UnbornBranch => {
+            return Err("The repository has no commits yet; commit before pulling".to_string())
+        }
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
+    if !head.is_branch() {
+        return Err("HEAD is detached; check out a branch to pull into".to_string());
+    }
     let branch_name = head.shorthand().ok_or("Failed to get branch name")?;
 
     // Find the branch
@@ -247,13 +611,30 @@ pub fn git_pull(repo_path: String) -> Result<String, String> {
         .map_err(|e| format!("Failed to get upstream name: {}", e))?
         .ok_or("No upstream name")?;
 
+    // The upstream is "<remote>/<branch>"; resolve the remote it belongs to
+    let remote_name = repo
+        .branch_upstream_remote(&format!("refs/heads/{}", branch_name))
+        .map_err(|e| format!("Failed to get upstream remote: {}", e))?;
+    let remote_name = remote_name.as_str().ok_or("Invalid upstream remote name")?;
+    let remote_branch = upstream_name
+        .strip_prefix(&format!("{}/", remote_name))
+        .unwrap_or(upstream_name);
+
     // Fetch from remote
     let mut remote = repo
-        .find_remote(upstream_name)
+        .find_remote(remote_name)
         .map_err(|e| format!("Failed to find remote: {}", e))?;
 
+    let operation_id = remote::operation_id(operation_id);
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(remote::progress_callbacks(
+        app_handle,
+        &operation_id,
+        "pull",
+    ));
+
     remote
-        .fetch(&[upstream_name], None, None)
+        .fetch(&[remote_branch], Some(&mut fetch_options), None)
         .map_err(|e| format!("Failed to fetch: {}", e))?;
 
     // Merge the fetched changes
@@ -274,26 +655,72 @@ pub fn git_pull(repo_path: String) -> Result<String, String> {
         return Ok("Already up to date".to_string());
     }
 
+    if strategy != PullStrategy::Merge {
+        let fetch_annotated = repo
+            .reference_to_annotated_commit(&fetch_head)
+            .map_err(|e| format!("Failed to read fetch commit: {}", e))?;
+        let (analysis, _) = repo
+            .merge_analysis(&[&fetch_annotated])
+            .map_err(|e| format!("Failed to analyze merge: {}", e))?;
+        if analysis.is_up_to_date() {
+            return Ok("Already up to date".to_string());
+        }
+        if analysis.is_fast_forward() {
+            fast_forward(repo, &head, &fetch_commit)?;
+            return Ok("Pull successful (fast-forward)".to_string());
+        }
+        if strategy == PullStrategy::FfOnly {
+            return Err(format!(
+                "{} has diverged from {}; it cannot be fast-forwarded",
+                branch_name, upstream_name
+            ));
+        }
+
+        let result = rebase::rebase_onto(repo, &fetch_commit.id().to_string())?;
+        if result.status == "conflicts" {
+            return Ok(format!(
+                "Rebase stopped on {} conflicting file(s); resolve them and continue the rebase to finish the pull",
+                result.conflicts.len()
+            ));
+        }
+        return Ok(format!(
+            "Pull successful, local commits rebased onto {}",
+            upstream_name
+        ));
+    }
+
     // Perform the merge
     let mut index = repo
         .merge_commits(&head_commit, &fetch_commit, None)
         .map_err(|e| format!("Failed to merge: {}", e))?;
 
     if index.has_conflicts() {
-        return Err("Merge conflicts detected".to_string());
+        // Pointer conflicts can be resolved by picking data versions, so the
+        // merge is left in progress for `resolve_dvc_conflict` to finish
+        let conflicts = conflicts::index_conflicts(&index)?;
+        if !conflicts.iter().all(|c| c.is_dvc_pointer) {
+            return Err("Merge conflicts detected".to_string());
+        }
+        let annotated = repo
+            .reference_to_annotated_commit(&fetch_head)
+            .map_err(|e| format!("Failed to read fetch commit: {}", e))?;
+        repo.merge(&[&annotated], None, None)
+            .map_err(|e| format!("Failed to start merge: {}", e))?;
+        return Ok(format!(
+            "Merge stopped on {} conflicting DVC pointer(s); resolve them and commit to finish the pull",
+            conflicts.len()
+        ));
     }
 
     let tree_id = index
-        .write_tree_to(&repo)
+        .write_tree_to(repo)
         .map_err(|e| format!("Failed to write tree: {}", e))?;
 
     let tree = repo
         .find_tree(tree_id)
         .map_err(|e| format!("Failed to find tree: {}", e))?;
 
-    let signature = repo
-        .signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let signature = identity::signature(repo)?;
 
     repo.commit(
         Some("HEAD"),
@@ -315,10 +742,7 @@ pub fn git_list_branches(repo_path: String) -> Result<Vec<GitBranch>, String> {
     let repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let current_branch_name = head.shorthand().unwrap_or("HEAD").to_string();
+    let current_branch_name = head_branch(&repo)?;
 
     let mut branches = Vec::new();
 
@@ -336,7 +760,7 @@ pub fn git_list_branches(repo_path: String) -> Result<Vec<GitBranch>, String> {
             .unwrap_or("unknown")
             .to_string();
 
-        let is_current = name == current_branch_name;
+        let is_current = current_branch_name.as_deref() == Some(name.as_str());
         let upstream = branch
             .upstream()
             .ok()
@@ -376,10 +800,65 @@ pub fn git_list_branches(repo_path: String) -> Result<Vec<GitBranch>, String> {
     Ok(branches)
 }
 
+/// DVC outputs of the pointers at HEAD, keyed by data path
+fn head_outputs(repo_path: &str) -> Result<BTreeMap<String, DvcOut>, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => branch_compare::tracked_outputs(&repo, &tree),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => Ok(BTreeMap::new()),
+        Err(e) => Err(format!("Failed to get HEAD: {}", e)),
+    }
+}
+
+/// Runs a branch change and, with `dvc_checkout`, brings the workspace data
+/// in line with the new pointers so it doesn't silently desync from them
+fn with_data_sync(
+    repo_path: &str,
+    dvc_checkout: bool,
+    switch: impl FnOnce() -> Result<String, String>,
+) -> Result<BranchSwitchResult, String> {
+    if !dvc_checkout {
+        return Ok(BranchSwitchResult {
+            message: switch()?,
+            data: None,
+        });
+    }
+    let previous = head_outputs(repo_path)?;
+    let message = switch()?;
+    let current = head_outputs(repo_path)?;
+    let data = checkout::sync_outputs(&transfer::repo_root(repo_path)?, &previous, &current)?;
+    Ok(BranchSwitchResult {
+        message,
+        data: Some(data),
+    })
+}
+
 /// Enhanced checkout with better error handling
 #[command]
-#[instrument(skip(repo_path, branch), err(Debug))]
-pub fn git_checkout(repo_path: String, branch: String) -> Result<String, String> {
+#[instrument(skip(app_handle, repo_path, branch), err(Debug))]
+pub fn git_checkout(
+    app_handle: AppHandle,
+    repo_path: String,
+    branch: String,
+    dvc_checkout: Option<bool>,
+) -> Result<BranchSwitchResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_checkout",
+        json!({ "branch": branch, "dvc_checkout": dvc_checkout }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                with_data_sync(&repo_path, dvc_checkout.unwrap_or(false), || {
+                    checkout(repo_path.clone(), branch)
+                })
+            })
+        },
+    )
+}
+
+pub(crate) fn checkout(repo_path: String, branch: String) -> Result<String, String> {
     let repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
     let branch_ref_name = format!("refs/heads/{}", branch);
@@ -405,12 +884,18 @@ pub fn git_checkout(repo_path: String, branch: String) -> Result<String, String>
         Ok(format!("Checked out to branch {}", branch))
     } else {
         // Branch doesn't exist, create it
-        let head = repo
-            .head()
-            .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-        let head_commit = repo
-            .find_commit(head.target().unwrap())
-            .map_err(|e| format!("Failed to find HEAD commit: {}", e))?;
+        let head_commit = match repo.head() {
+            Ok(head) => head
+                .peel_to_commit()
+                .map_err(|e| format!("Failed to find HEAD commit: {}", e))?,
+            Err(e) if e.code() == ErrorCode::UnbornBranch => {
+                // Before the first commit a branch is only a name HEAD points at
+                repo.set_head(&branch_ref_name)
+                    .map_err(|e| format!("Failed to set HEAD: {}", e))?;
+                return Ok(format!("Switched to new branch {}", branch));
+            }
+            Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+        };
         let new_branch = repo
             .branch(&branch, &head_commit, false)
             .map_err(|e| format!("Failed to create branch: {}", e))?;
@@ -440,14 +925,18 @@ pub fn git_checkout(repo_path: String, branch: String) -> Result<String, String>
 
 /// Enhanced stash function
 #[command]
-#[instrument(skip(repo_path), err(Debug))]
-pub fn git_stash(repo_path: String) -> Result<String, String> {
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_stash(app_handle: AppHandle, repo_path: String) -> Result<String, String> {
+    audit::track(&app_handle, &repo_path, "git_stash", json!({}), || {
+        repo_manager::write(&app_handle, &repo_path, || stash(repo_path.clone()))
+    })
+}
+
+fn stash(repo_path: String) -> Result<String, String> {
     let mut repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
-    let signature = repo
-        .signature()
-        .map_err(|e| format!("Failed to get signature: {}", e))?;
+    let signature = identity::signature(&repo)?;
 
     let stash_message = "Stash created by fenn-app";
 
@@ -465,18 +954,34 @@ pub fn git_current_branch(repo_path: String) -> Result<String, String> {
     let repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
-    let head = repo
-        .head()
-        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
-    let branch_name = head.shorthand().unwrap_or("HEAD").to_string();
-
-    Ok(branch_name)
+    Ok(head_branch(&repo)?.unwrap_or_else(|| "HEAD".to_string()))
 }
 
 /// Enhanced branch switching
 #[command]
-#[instrument(skip(repo_path, branch), err(Debug))]
-pub fn git_switch_branch(repo_path: String, branch: String) -> Result<String, String> {
+#[instrument(skip(app_handle, repo_path, branch), err(Debug))]
+pub fn git_switch_branch(
+    app_handle: AppHandle,
+    repo_path: String,
+    branch: String,
+    dvc_checkout: Option<bool>,
+) -> Result<BranchSwitchResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_switch_branch",
+        json!({ "branch": branch, "dvc_checkout": dvc_checkout }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                with_data_sync(&repo_path, dvc_checkout.unwrap_or(false), || {
+                    switch_branch(repo_path.clone(), branch)
+                })
+            })
+        },
+    )
+}
+
+pub(crate) fn switch_branch(repo_path: String, branch: String) -> Result<String, String> {
     let repo =
         Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
@@ -502,6 +1007,114 @@ pub fn git_switch_branch(repo_path: String, branch: String) -> Result<String, St
     Ok(format!("Switched to branch {}", branch))
 }
 
+/// Describes the commit HEAD is detached at
+fn detached_head(repo: &Repository) -> Result<DetachedHead, String> {
+    let commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+    let tag = repo
+        .references_glob("refs/tags/*")
+        .map_err(|e| format!("Failed to list tags: {}", e))?
+        .flatten()
+        .find(|reference| {
+            reference
+                .peel_to_commit()
+                .is_ok_and(|tagged| tagged.id() == commit.id())
+        })
+        .and_then(|reference| reference.shorthand().map(str::to_string));
+    let short_id: String = commit.id().to_string().chars().take(7).collect();
+    Ok(DetachedHead {
+        commit_id: commit.id().to_string(),
+        suggested_branch: format!("from-{}", tag.as_deref().unwrap_or(&short_id)),
+        short_id,
+        summary: commit.summary().unwrap_or_default().to_string(),
+        tag,
+    })
+}
+
+/// Checks out a commit without a branch and updates the DVC data of the
+/// pointers that changed, so the workspace holds the data of that version
+fn checkout_detached(repo_path: &str, spec: &str) -> Result<DetachedCheckoutResult, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let commit = repo
+        .revparse_single(spec)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find {}: {}", spec, e))?;
+    let old_tree = repo.head().and_then(|head| head.peel_to_tree()).ok();
+    let new_tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to read commit tree: {}", e))?;
+
+    // Safe mode refuses to overwrite uncommitted changes
+    repo.checkout_tree(commit.as_object(), Some(CheckoutBuilder::new().safe()))
+        .map_err(|e| format!("Failed to checkout {}: {}", spec, e))?;
+    repo.set_head_detached(commit.id())
+        .map_err(|e| format!("Failed to detach HEAD: {}", e))?;
+
+    let pointers: Vec<PathBuf> = repo
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)
+        .map_err(|e| format!("Failed to diff trees: {}", e))?
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().map(|path| repo_root.join(path)))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "dvc") && path.exists())
+        .collect();
+    let data = checkout::checkout_pointers(&repo_root, &pointers, false)?;
+
+    Ok(DetachedCheckoutResult {
+        head: detached_head(&repo)?,
+        data,
+    })
+}
+
+/// Checks out a commit in detached HEAD mode, e.g. to look at an old version
+/// of the data. `git_checkout` with a new name starts a branch from there.
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_checkout_commit(
+    app_handle: AppHandle,
+    repo_path: String,
+    commit_id: String,
+) -> Result<DetachedCheckoutResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_checkout_commit",
+        json!({ "commit_id": commit_id }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                checkout_detached(&repo_path, &commit_id)
+            })
+        },
+    )
+}
+
+/// Checks out the commit a tag points at in detached HEAD mode
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_checkout_tag(
+    app_handle: AppHandle,
+    repo_path: String,
+    tag: String,
+) -> Result<DetachedCheckoutResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_checkout_tag",
+        json!({ "tag": tag }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                checkout_detached(&repo_path, &format!("refs/tags/{}", tag))
+            })
+        },
+    )
+}
+
 /// New function: Get detailed diff information
 #[command]
 #[instrument(skip(repo_path, file_path), err(Debug))]
@@ -552,38 +1165,87 @@ pub fn git_file_diff(repo_path: String, file_path: String) -> Result<String, Str
 /// Helper function to get ahead/behind information
 fn get_ahead_behind(repo: &Repository, branch_name: &str) -> Result<(i32, i32), git2::Error> {
     let branch = repo.find_branch(branch_name, BranchType::Local)?;
+    let upstream = branch.upstream().ok();
+    let (Some(local_oid), Some(remote_oid)) = (
+        branch.get().target(),
+        upstream.and_then(|upstream| upstream.get().target()),
+    ) else {
+        return Ok((0, 0));
+    };
+    let (ahead, behind) = repo.graph_ahead_behind(local_oid, remote_oid)?;
+    Ok((ahead as i32, behind as i32))
+}
 
-    if let Ok(upstream) = branch.upstream() {
-        let upstream_name = upstream.name()?.unwrap_or("origin/main");
-        let remote = repo.find_remote(upstream_name)?;
-
-        // Get the remote branch reference
-        let remote_ref = format!("refs/remotes/{}/{}", remote.name().unwrap(), branch_name);
-        if let Ok(remote_ref) = repo.find_reference(&remote_ref) {
-            let local_oid = branch.get().target().unwrap();
-            let remote_oid = remote_ref.target().unwrap();
-
-            let (ahead, behind) = repo.graph_ahead_behind(local_oid, remote_oid)?;
-            return Ok((ahead as i32, behind as i32));
-        }
-    }
+/// Branch HEAD is on, also before its first commit; `None` when HEAD is
+/// detached
+fn head_branch(repo: &Repository) -> Result<Option<String>, String> {
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    Ok(head.symbolic_target().map(|target| {
+        target
+            .strip_prefix("refs/heads/")
+            .unwrap_or(target)
+            .to_string()
+    }))
+}
 
-    Ok((0, 0))
+#[derive(Debug, Serialize)]
+pub struct StageResult {
+    pub staged: Vec<String>,
+    /// Files refused because they should be tracked with DVC instead
+    pub suggestions: Vec<DvcTrackSuggestion>,
+    /// Staged binary files that would be better tracked with DVC
+    pub dvc_hints: Vec<BinaryChangeHint>,
+    pub message: String,
 }
 
-/// New function: Stage specific files
+/// Stage specific files. Large or data files are refused with a suggestion to
+/// track them with DVC unless `force` is set.
 #[command]
-#[instrument(skip(repo_path, files), err(Debug))]
-pub fn git_add_files(repo_path: String, files: Vec<String>) -> Result<String, String> {
+#[instrument(skip(app_handle, repo_path, files), err(Debug))]
+pub fn git_add_files(
+    app_handle: AppHandle,
+    repo_path: String,
+    files: Vec<String>,
+    force: Option<bool>,
+) -> Result<StageResult, String> {
+    events::track(&app_handle, &repo_path, "git_add_files", || {
+        repo_manager::write(&app_handle, &repo_path, || {
+            let repo_root = transfer::repo_root(&repo_path)?;
+            let hook_settings = settings::load(&app_handle, &repo_root)?.hooks;
+            stage_files(&repo_path, files, force, &hook_settings)
+        })
+    })
+}
+
+/// Stages files, leaving out the ones the staging guard in `hook_settings`
+/// says belong in DVC unless `force` is set
+pub(crate) fn stage_files(
+    repo_path: &str,
+    files: Vec<String>,
+    force: Option<bool>,
+    hook_settings: &hooks::HookSettings,
+) -> Result<StageResult, String> {
     let repo =
-        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let suggestions = match repo.workdir() {
+        Some(workdir) if !force.unwrap_or(false) => {
+            hooks::staging_guard(workdir, hook_settings, &files)
+        }
+        _ => Vec::new(),
+    };
+    let staged: Vec<String> = files
+        .into_iter()
+        .filter(|file| !suggestions.iter().any(|s| &s.path == file))
+        .collect();
 
     let mut index = repo
         .index()
         .map_err(|e| format!("Failed to get index: {}", e))?;
 
-    let files_count = files.len();
-    for file in &files {
+    for file in &staged {
         index
             .add_path(Path::new(file))
             .map_err(|e| format!("Failed to add file {}: {}", file, e))?;
@@ -593,15 +1255,45 @@ pub fn git_add_files(repo_path: String, files: Vec<String>) -> Result<String, St
         .write()
         .map_err(|e| format!("Failed to write index: {}", e))?;
 
-    Ok(format!("Added {} files to staging area", files_count))
+    let dvc_hints = match repo.workdir() {
+        Some(workdir) => staged
+            .iter()
+            .filter_map(|file| diff::binary_hint(workdir, file))
+            .collect(),
+        None => Vec::new(),
+    };
+
+    let mut message = format!("Added {} files to staging area", staged.len());
+    if !suggestions.is_empty() {
+        message.push_str(&format!(
+            ", {} should be tracked with DVC instead",
+            suggestions.len()
+        ));
+    }
+    Ok(StageResult {
+        staged,
+        suggestions,
+        dvc_hints,
+        message,
+    })
 }
 
 /// New function: Unstage specific files
 #[command]
-#[instrument(skip(repo_path, files), err(Debug))]
-pub fn git_reset_files(repo_path: String, files: Vec<String>) -> Result<String, String> {
+#[instrument(skip(app_handle, repo_path, files), err(Debug))]
+pub fn git_reset_files(
+    app_handle: AppHandle,
+    repo_path: String,
+    files: Vec<String>,
+) -> Result<String, String> {
+    events::track(&app_handle, &repo_path, "git_reset_files", || {
+        repo_manager::write(&app_handle, &repo_path, || reset_files(&repo_path, files))
+    })
+}
+
+pub(crate) fn reset_files(repo_path: &str, files: Vec<String>) -> Result<String, String> {
     let repo =
-        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
 
     let mut index = repo
         .index()
@@ -620,3 +1312,189 @@ pub fn git_reset_files(repo_path: String, files: Vec<String>) -> Result<String,
 
     Ok(format!("Removed {} files from staging area", files_count))
 }
+
+/// Creates a commit undoing the changes of `commit_id` on top of HEAD. Nothing
+/// is written when the revert conflicts with later changes.
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_revert_commit(
+    app_handle: AppHandle,
+    repo_path: String,
+    commit_id: String,
+) -> Result<CommitResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_revert_commit",
+        json!({ "commit_id": commit_id }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                revert_commit(repo_path.clone(), commit_id)
+            })
+        },
+    )
+}
+
+fn revert_commit(repo_path: String, commit_id: String) -> Result<CommitResult, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+
+    let commit = repo
+        .revparse_single(&commit_id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find commit {}: {}", commit_id, e))?;
+    let head_commit = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    // Merge commits are reverted relative to their first parent
+    let mainline = if commit.parent_count() > 1 { 1 } else { 0 };
+    let mut revert_index = repo
+        .revert_commit(&commit, &head_commit, mainline, None)
+        .map_err(|e| format!("Failed to revert commit: {}", e))?;
+
+    if revert_index.has_conflicts() {
+        let paths: Vec<String> = conflicts::index_conflicts(&revert_index)?
+            .into_iter()
+            .map(|conflict| conflict.path)
+            .collect();
+        return Err(format!(
+            "Reverting {} conflicts with later changes to: {}",
+            commit_id,
+            paths.join(", ")
+        ));
+    }
+
+    let tree_id = revert_index
+        .write_tree_to(&repo)
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+
+    // Update the workspace first; a safe checkout refuses to overwrite local
+    // modifications, in which case no commit is created
+    let mut checkout = CheckoutBuilder::new();
+    checkout.safe();
+    repo.checkout_tree(tree.as_object(), Some(&mut checkout))
+        .map_err(|e| format!("Failed to update working tree: {}", e))?;
+
+    let signature = identity::signature(&repo)?;
+    let message = format!(
+        "Revert \"{}\"\n\nThis reverts commit {}.",
+        commit.summary().unwrap_or_default(),
+        commit.id()
+    );
+    let revert_id = repo
+        .commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            &message,
+            &tree,
+            &[&head_commit],
+        )
+        .map_err(|e| format!("Failed to create commit: {}", e))?;
+
+    Ok(CommitResult {
+        success: true,
+        message: format!("Reverted commit {}", &commit.id().to_string()[..7]),
+        commit_id: Some(revert_id.to_string()),
+        violations: Vec::new(),
+        size_alerts: Vec::new(),
+        stripped_notebooks: Vec::new(),
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct CherryPickResult {
+    /// New commit ids, in the order the commits were applied
+    pub applied: Vec<String>,
+    /// Commit that could not be applied cleanly; the repository is left in the
+    /// cherry-pick state with its conflicts for the user to resolve
+    pub stopped_at: Option<String>,
+    pub conflicts: Vec<ConflictFile>,
+}
+
+/// Applies commits on top of HEAD one after another, keeping their authors and
+/// messages. Stops at the first commit that conflicts.
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_cherry_pick(
+    app_handle: AppHandle,
+    repo_path: String,
+    commit_ids: Vec<String>,
+) -> Result<CherryPickResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_cherry_pick",
+        json!({ "commit_ids": commit_ids }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                cherry_pick(repo_path.clone(), commit_ids)
+            })
+        },
+    )
+}
+
+fn cherry_pick(repo_path: String, commit_ids: Vec<String>) -> Result<CherryPickResult, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let signature = identity::signature(&repo)?;
+
+    let mut result = CherryPickResult {
+        applied: Vec::new(),
+        stopped_at: None,
+        conflicts: Vec::new(),
+    };
+
+    for commit_id in &commit_ids {
+        let commit = repo
+            .revparse_single(commit_id)
+            .and_then(|object| object.peel_to_commit())
+            .map_err(|e| format!("Failed to find commit {}: {}", commit_id, e))?;
+
+        let mut options = git2::CherrypickOptions::new();
+        if commit.parent_count() > 1 {
+            options.mainline(1);
+        }
+        repo.cherrypick(&commit, Some(&mut options))
+            .map_err(|e| format!("Failed to cherry-pick {}: {}", commit_id, e))?;
+
+        let mut index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.has_conflicts() {
+            result.stopped_at = Some(commit.id().to_string());
+            result.conflicts = conflicts::index_conflicts(&index)?;
+            return Ok(result);
+        }
+
+        let tree = index
+            .write_tree()
+            .and_then(|tree_id| repo.find_tree(tree_id))
+            .map_err(|e| format!("Failed to write tree: {}", e))?;
+        let head_commit = repo
+            .head()
+            .and_then(|head| head.peel_to_commit())
+            .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+        let new_id = repo
+            .commit(
+                Some("HEAD"),
+                &commit.author(),
+                &signature,
+                commit.message().unwrap_or_default(),
+                &tree,
+                &[&head_commit],
+            )
+            .map_err(|e| format!("Failed to create commit: {}", e))?;
+        repo.cleanup_state()
+            .map_err(|e| format!("Failed to clean up cherry-pick state: {}", e))?;
+
+        result.applied.push(new_id.to_string());
+    }
+
+    Ok(result)
+}
@@ -0,0 +1,411 @@
+use git2::{ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::checkout::{self, CheckoutSummary};
+use crate::{dvc_compat, dvcfile, repo_manager};
+
+/// Lock files older than this are assumed to be left behind by a crash
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Serialize)]
+pub struct HealthFinding {
+    /// Kind of check that failed, e.g. "git-missing-object" or "stale-lock"
+    pub check: String,
+    /// "error" or "warning"
+    pub severity: String,
+    pub message: String,
+    pub path: Option<String>,
+    /// Command that fixes the finding when invoked with the same `repo_path`
+    pub repair: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    pub healthy: bool,
+    pub findings: Vec<HealthFinding>,
+}
+
+fn finding(
+    check: &str,
+    severity: &str,
+    message: String,
+    path: Option<String>,
+    repair: Option<&str>,
+) -> HealthFinding {
+    HealthFinding {
+        check: check.to_string(),
+        severity: severity.to_string(),
+        message,
+        path,
+        repair: repair.map(|r| r.to_string()),
+    }
+}
+
+fn open_repo(repo_path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo =
+        Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    Ok((repo, repo_root))
+}
+
+/// Objects reachable from HEAD and blobs staged in the index must all exist
+fn check_git(repo: &Repository, findings: &mut Vec<HealthFinding>) {
+    let odb = match repo.odb() {
+        Ok(odb) => odb,
+        Err(e) => {
+            findings.push(finding(
+                "git-object-database",
+                "error",
+                format!("Cannot open the object database: {}", e),
+                None,
+                None,
+            ));
+            return;
+        }
+    };
+
+    match repo.head().and_then(|head| head.peel_to_tree()) {
+        Ok(tree) => {
+            let mut missing = Vec::new();
+            let walked = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+                if entry.kind() != Some(ObjectType::Commit) && !odb.exists(entry.id()) {
+                    missing.push(format!("{}{}", root, entry.name().unwrap_or_default()));
+                }
+                TreeWalkResult::Ok
+            });
+            if let Err(e) = walked {
+                findings.push(finding(
+                    "git-missing-object",
+                    "error",
+                    format!("The HEAD tree cannot be read: {}", e),
+                    None,
+                    None,
+                ));
+            }
+            for path in missing {
+                findings.push(finding(
+                    "git-missing-object",
+                    "error",
+                    format!("The object for {} at HEAD is missing", path),
+                    Some(path),
+                    None,
+                ));
+            }
+        }
+        // An unborn branch has nothing to check yet
+        Err(e) if e.code() == git2::ErrorCode::UnbornBranch => {}
+        Err(e) => findings.push(finding(
+            "git-head",
+            "error",
+            format!("HEAD cannot be resolved: {}", e),
+            None,
+            None,
+        )),
+    }
+
+    match repo.index() {
+        Ok(index) => {
+            for entry in index.iter() {
+                if !odb.exists(entry.id) {
+                    let path = String::from_utf8_lossy(&entry.path).to_string();
+                    findings.push(finding(
+                        "git-index",
+                        "error",
+                        format!("The staged content of {} is missing", path),
+                        Some(path),
+                        Some("repair_git_index"),
+                    ));
+                }
+            }
+        }
+        Err(e) => findings.push(finding(
+            "git-index",
+            "error",
+            format!("The index cannot be read: {}", e),
+            None,
+            Some("repair_git_index"),
+        )),
+    }
+}
+
+fn check_dvc_config(repo_root: &Path, findings: &mut Vec<HealthFinding>) -> bool {
+    let dvc_dir = repo_root.join(".dvc");
+    if !dvc_dir.is_dir() {
+        findings.push(finding(
+            "dvc-config",
+            "error",
+            "The repository is not initialized for DVC".to_string(),
+            None,
+            Some("repair_dvc_config"),
+        ));
+        return false;
+    }
+    if !dvc_dir.join("config").is_file() {
+        findings.push(finding(
+            "dvc-config",
+            "warning",
+            "The DVC config file is missing".to_string(),
+            Some(".dvc/config".to_string()),
+            Some("repair_dvc_config"),
+        ));
+    }
+    true
+}
+
+/// Pointers whose data is missing from the workspace, and cache objects whose
+/// content no longer matches their hash
+fn check_dvc_data(repo_root: &Path, findings: &mut Vec<HealthFinding>) {
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let format = dvc_compat::repo_format(repo_root);
+    let mut objects = BTreeSet::new();
+
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        let relative = dvcfile::to_git_path(pointer.strip_prefix(repo_root).unwrap_or(&pointer));
+        let dvc_file = match dvcfile::read_dvc_file(&pointer) {
+            Ok(dvc_file) => dvc_file,
+            Err(e) => {
+                findings.push(finding("dvc-pointer", "error", e, Some(relative), None));
+                continue;
+            }
+        };
+
+        let base_dir = pointer.parent().unwrap_or(repo_root);
+        for out in &dvc_file.outs {
+            let Some(md5) = out.md5.as_deref() else {
+                continue;
+            };
+            let data_path = base_dir.join(&out.path);
+            let data_relative =
+                dvcfile::to_git_path(data_path.strip_prefix(repo_root).unwrap_or(&data_path));
+            let in_cache = dvcfile::cache_object_path(&cache_dir, md5).exists();
+
+            if out.is_dir() {
+                if let Ok(manifest) = dvcfile::read_dir_manifest(&cache_dir, md5) {
+                    objects.extend(manifest.into_iter().map(|entry| entry.md5));
+                }
+            } else {
+                objects.insert(md5.to_string());
+            }
+
+            if !data_path.exists() {
+                let (message, repair) = if in_cache {
+                    (
+                        format!(
+                            "{} is missing but can be restored from the cache",
+                            data_relative
+                        ),
+                        "repair_missing_data",
+                    )
+                } else {
+                    (
+                        format!(
+                            "{} is missing from the workspace and the cache",
+                            data_relative
+                        ),
+                        "dvc_pull",
+                    )
+                };
+                findings.push(finding(
+                    "dvc-missing-data",
+                    "warning",
+                    message,
+                    Some(data_relative),
+                    Some(repair),
+                ));
+            }
+        }
+    }
+
+    for md5 in objects {
+        let object = dvcfile::cache_object_path(&cache_dir, &md5);
+        if !object.exists() {
+            continue;
+        }
+        if dvcfile::hash_file_for(&object, format).ok().as_deref() != Some(md5.as_str()) {
+            findings.push(finding(
+                "dvc-cache-corruption",
+                "error",
+                format!("Cache object {} does not match its hash", md5),
+                Some(dvcfile::to_git_path(
+                    object.strip_prefix(repo_root).unwrap_or(&object),
+                )),
+                Some("repair_cache"),
+            ));
+        }
+    }
+}
+
+fn lock_files(repo: &Repository, repo_root: &Path) -> Vec<PathBuf> {
+    let tmp = repo_root.join(".dvc").join("tmp");
+    vec![
+        repo.path().join("index.lock"),
+        repo.path().join("HEAD.lock"),
+        tmp.join("lock"),
+        tmp.join("rwlock.lock"),
+    ]
+}
+
+fn stale_locks(repo: &Repository, repo_root: &Path) -> Vec<PathBuf> {
+    lock_files(repo, repo_root)
+        .into_iter()
+        .filter(|path| {
+            fs::metadata(path)
+                .and_then(|metadata| metadata.modified())
+                .ok()
+                .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+                .is_some_and(|age| age >= STALE_LOCK_AGE)
+        })
+        .collect()
+}
+
+/// Checks git integrity, DVC setup, workspace data, the cache and lock files,
+/// returning findings with the command that repairs each one where possible
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn check_repository_health(repo_path: String) -> Result<HealthReport, String> {
+    let (repo, repo_root) = open_repo(&repo_path)?;
+    let mut findings = Vec::new();
+
+    check_git(&repo, &mut findings);
+    if check_dvc_config(&repo_root, &mut findings) {
+        check_dvc_data(&repo_root, &mut findings);
+    }
+    for lock in stale_locks(&repo, &repo_root) {
+        let relative = dvcfile::to_git_path(lock.strip_prefix(&repo_root).unwrap_or(&lock));
+        findings.push(finding(
+            "stale-lock",
+            "warning",
+            format!("{} looks left over from an interrupted operation", relative),
+            Some(relative),
+            Some("repair_stale_locks"),
+        ));
+    }
+
+    Ok(HealthReport {
+        healthy: !findings.iter().any(|f| f.severity == "error"),
+        findings,
+    })
+}
+
+/// Removes lock files old enough to be left over from a crash
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn repair_stale_locks(repo_path: String) -> Result<Vec<String>, String> {
+    let (repo, repo_root) = open_repo(&repo_path)?;
+    let mut removed = Vec::new();
+    for lock in stale_locks(&repo, &repo_root) {
+        fs::remove_file(&lock)
+            .map_err(|e| format!("Failed to remove {}: {}", lock.display(), e))?;
+        removed.push(dvcfile::to_git_path(
+            lock.strip_prefix(&repo_root).unwrap_or(&lock),
+        ));
+    }
+    Ok(removed)
+}
+
+/// Rebuilds the index from HEAD, keeping the workspace untouched
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn repair_git_index(app_handle: AppHandle, repo_path: String) -> Result<String, String> {
+    let (repo, _) = open_repo(&repo_path)?;
+    repo_manager::write(&app_handle, &repo_path, || {
+        let tree = repo
+            .head()
+            .and_then(|head| head.peel_to_tree())
+            .map_err(|e| format!("Failed to read HEAD: {}", e))?;
+        let mut index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        index
+            .read_tree(&tree)
+            .and_then(|_| index.write())
+            .map_err(|e| format!("Failed to rebuild index: {}", e))
+    })?;
+    Ok("Index rebuilt from HEAD; staged changes need to be staged again".to_string())
+}
+
+/// Creates the `.dvc` directory layout `dvc init` would
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn repair_dvc_config(repo_path: String) -> Result<String, String> {
+    let (_, repo_root) = open_repo(&repo_path)?;
+    let dvc_dir = repo_root.join(".dvc");
+    fs::create_dir_all(&dvc_dir).map_err(|e| format!("Failed to create .dvc: {}", e))?;
+
+    let config = dvc_dir.join("config");
+    if !config.exists() {
+        fs::write(&config, "").map_err(|e| format!("Failed to create DVC config: {}", e))?;
+    }
+    let gitignore = dvc_dir.join(".gitignore");
+    if !gitignore.exists() {
+        fs::write(&gitignore, "/config.local\n/tmp\n/cache\n")
+            .map_err(|e| format!("Failed to create .dvc/.gitignore: {}", e))?;
+    }
+    Ok("DVC configuration restored".to_string())
+}
+
+/// Deletes cache objects whose content doesn't match their hash so they can
+/// be pulled again from the remote
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn repair_cache(repo_path: String) -> Result<Vec<String>, String> {
+    let (_, repo_root) = open_repo(&repo_path)?;
+    let mut findings = Vec::new();
+    check_dvc_data(&repo_root, &mut findings);
+
+    let mut removed = Vec::new();
+    for corrupted in findings
+        .into_iter()
+        .filter(|f| f.check == "dvc-cache-corruption")
+        .filter_map(|f| f.path)
+    {
+        fs::remove_file(repo_root.join(&corrupted))
+            .map_err(|e| format!("Failed to remove {}: {}", corrupted, e))?;
+        removed.push(corrupted);
+    }
+    Ok(removed)
+}
+
+/// Restores workspace data missing for any pointer from the local cache
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn repair_missing_data(repo_path: String) -> Result<CheckoutSummary, String> {
+    let (_, repo_root) = open_repo(&repo_path)?;
+    let pointers = dvcfile::find_pointer_files(&repo_root);
+    checkout::checkout_pointers(&repo_root, &pointers, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn repair_cache_keeps_valid_objects_and_removes_corrupted_ones() {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let cache_dir = dvcfile::cache_dir(fixture.path());
+        let text = fixture.track_dvc2_dataset("data/notes.txt", "a,b\r\n1,2\r\n");
+        let corrupted = fixture.track_dvc2_dataset("data/train.csv", "a,b\n1,2\n");
+        fs::write(
+            dvcfile::cache_object_path(&cache_dir, &corrupted),
+            "garbage",
+        )
+        .unwrap();
+
+        let removed = repair_cache(fixture.path_string()).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert!(removed[0].ends_with(&corrupted[2..]));
+        assert!(dvcfile::cache_object_path(&cache_dir, &text).exists());
+        assert!(!dvcfile::cache_object_path(&cache_dir, &corrupted).exists());
+    }
+}
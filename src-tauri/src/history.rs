@@ -0,0 +1,465 @@
+use git2::{Commit, Oid, Repository, Sort};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::audit;
+use crate::checkout::{self, CheckoutSummary};
+use crate::dvcfile::{self, DvcOut};
+use crate::repo_manager;
+
+#[derive(Debug, Serialize)]
+pub struct FileVersion {
+    pub commit_id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub author: String,
+    /// Commit time in seconds since the epoch
+    pub timestamp: i64,
+    /// "added", "modified" or "deleted"
+    pub change: String,
+    /// Path the history follows: the `.dvc` pointer for DVC-tracked data
+    pub tracked_path: String,
+    pub is_dvc: bool,
+    /// DVC hash of the data at this version (`.dir` suffix for directories)
+    pub md5: Option<String>,
+    /// Size recorded in the pointer, or the blob size for plain git files
+    pub size: Option<u64>,
+    pub nfiles: Option<u64>,
+}
+
+/// Repository relative path history is followed on: the pointer file when the
+/// path is (or was) DVC tracked, otherwise the path itself
+pub(crate) fn history_path(repo: &Repository, path: &str) -> Result<(PathBuf, bool), String> {
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let relative = dvcfile::repo_relative_path(repo_root, Path::new(path))?;
+    if relative.extension().and_then(|e| e.to_str()) == Some("dvc") {
+        return Ok((relative, true));
+    }
+
+    let pointer = dvcfile::pointer_path(&relative);
+    let pointer_in_head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_tree().ok())
+        .is_some_and(|tree| tree.get_path(&pointer).is_ok());
+    if repo_root.join(&pointer).exists() || pointer_in_head {
+        Ok((pointer, true))
+    } else {
+        Ok((relative, false))
+    }
+}
+
+fn blob_at(commit: &Commit, path: &Path) -> Option<Oid> {
+    commit
+        .tree()
+        .ok()
+        .and_then(|tree| tree.get_path(path).ok())
+        .map(|entry| entry.id())
+}
+
+/// How a commit changed a path compared to its first parent, with the blob
+/// the path has after it; `None` when the commit left the path alone
+fn change_at(commit: &Commit, path: &Path) -> Option<(&'static str, Option<Oid>)> {
+    let current = blob_at(commit, path);
+    let previous = commit
+        .parent(0)
+        .ok()
+        .and_then(|parent| blob_at(&parent, path));
+    match (previous, current) {
+        (None, Some(_)) => Some(("added", current)),
+        (Some(_), None) => Some(("deleted", current)),
+        (Some(before), Some(after)) if before != after => Some(("modified", current)),
+        _ => None,
+    }
+}
+
+/// Content of a file at a commit: a DVC cache object, or the bytes of a
+/// blob for files in git
+pub(crate) enum VersionContent {
+    Cache(PathBuf),
+    Blob(Vec<u8>),
+}
+
+/// DVC output that tracks `relative` at a commit, either itself or as part of
+/// a tracked directory, with the path inside that directory
+pub(crate) fn dvc_out_at(
+    repo: &Repository,
+    commit: &Commit,
+    relative: &Path,
+) -> Option<(DvcOut, PathBuf)> {
+    relative
+        .ancestors()
+        .filter(|ancestor| !ancestor.as_os_str().is_empty())
+        .find_map(|ancestor| {
+            let id = blob_at(commit, &dvcfile::pointer_path(ancestor))?;
+            let blob = repo.find_blob(id).ok()?;
+            let dvc_file =
+                dvcfile::parse_dvc_file(&String::from_utf8_lossy(blob.content())).ok()?;
+            let out = dvc_file.outs.into_iter().next()?;
+            let inner = relative.strip_prefix(ancestor).ok()?.to_path_buf();
+            Some((out, inner))
+        })
+}
+
+/// Finds the content of a file at a revision, from the local DVC cache when
+/// the file is DVC tracked
+pub(crate) fn content_at(
+    repo: &Repository,
+    relative: &Path,
+    rev: &str,
+) -> Result<VersionContent, String> {
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find {}: {}", rev, e))?;
+    let name = dvcfile::to_git_path(relative);
+
+    if let Some((out, inner)) = dvc_out_at(repo, &commit, relative) {
+        let cache_dir = dvcfile::cache_dir(repo_root);
+        let md5 = out
+            .md5
+            .ok_or_else(|| format!("{} has no hash at {}", name, rev))?;
+        let md5 = if inner.as_os_str().is_empty() {
+            md5
+        } else {
+            let inner = dvcfile::to_git_path(&inner);
+            dvcfile::read_dir_manifest(&cache_dir, &md5)?
+                .into_iter()
+                .find(|entry| entry.relpath == inner)
+                .map(|entry| entry.md5)
+                .ok_or_else(|| format!("{} does not exist at {}", name, rev))?
+        };
+        let object = dvcfile::cache_object_path(&cache_dir, &md5);
+        if !object.exists() {
+            return Err(format!(
+                "{} at {} is not in the local DVC cache; pull it first",
+                name, rev
+            ));
+        }
+        return Ok(VersionContent::Cache(object));
+    }
+
+    let id =
+        blob_at(&commit, relative).ok_or_else(|| format!("{} does not exist at {}", name, rev))?;
+    let blob = repo
+        .find_blob(id)
+        .map_err(|e| format!("Failed to read blob: {}", e))?;
+    Ok(VersionContent::Blob(blob.content().to_vec()))
+}
+
+/// Walks the commits reachable from HEAD that changed a path (or its `.dvc`
+/// pointer), newest first, with the DVC hash and size recorded at each version
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn file_history(repo_path: String, path: String) -> Result<Vec<FileVersion>, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let (tracked_path, is_dvc) = history_path(&repo, &path)?;
+
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    revwalk
+        .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+        .map_err(|e| format!("Failed to sort history: {}", e))?;
+    revwalk
+        .push_head()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let mut versions = Vec::new();
+    for oid in revwalk {
+        let oid = oid.map_err(|e| format!("Failed to walk history: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+
+        let Some((change, current)) = change_at(&commit, &tracked_path) else {
+            continue;
+        };
+
+        let (mut md5, mut size, mut nfiles) = (None, None, None);
+        if let Some(blob_id) = current {
+            let blob = repo
+                .find_blob(blob_id)
+                .map_err(|e| format!("Failed to read blob: {}", e))?;
+            if is_dvc {
+                let content = String::from_utf8_lossy(blob.content());
+                if let Some(out) = dvcfile::parse_dvc_file(&content)
+                    .ok()
+                    .and_then(|file| file.outs.into_iter().next())
+                {
+                    md5 = out.md5;
+                    size = out.size;
+                    nfiles = out.nfiles;
+                }
+            } else {
+                size = Some(blob.size() as u64);
+            }
+        }
+
+        versions.push(FileVersion {
+            commit_id: commit.id().to_string(),
+            short_id: commit.id().to_string()[..7].to_string(),
+            summary: commit.summary().unwrap_or_default().to_string(),
+            author: commit.author().name().unwrap_or_default().to_string(),
+            timestamp: commit.time().seconds(),
+            change: change.to_string(),
+            tracked_path: dvcfile::to_git_path(&tracked_path),
+            is_dvc,
+            md5,
+            size,
+            nfiles,
+        });
+    }
+
+    Ok(versions)
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathOwner {
+    pub name: String,
+    pub email: String,
+    /// Commits by this author that changed the path
+    pub commits: usize,
+    /// Time of their latest change, in seconds since the epoch
+    pub last_commit_at: i64,
+    /// Lines of the file at HEAD last changed by this author, for text files
+    /// in git
+    pub lines: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathOwners {
+    /// Path the history follows: the `.dvc` pointer for DVC-tracked data
+    pub tracked_path: String,
+    pub is_dvc: bool,
+    pub total_commits: usize,
+    /// Most recent change to the path
+    pub last_touched: Option<FileVersion>,
+    /// Most commits first, then most blamed lines
+    pub owners: Vec<PathOwner>,
+}
+
+/// Who last changed a file or dataset and who changes it most often, from the
+/// commits reachable from HEAD that touched it (its `.dvc` pointer for DVC
+/// data) and, for text files in git, from blame
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn get_path_owners(repo_path: String, path: String) -> Result<PathOwners, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let (tracked_path, is_dvc) = history_path(&repo, &path)?;
+    let tracked_name = dvcfile::to_git_path(&tracked_path);
+
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    revwalk
+        .set_sorting(Sort::TIME | Sort::TOPOLOGICAL)
+        .map_err(|e| format!("Failed to sort history: {}", e))?;
+    revwalk
+        .push_head()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let mut total_commits = 0;
+    let mut last_touched = None;
+    let mut owners: HashMap<String, PathOwner> = HashMap::new();
+    for oid in revwalk {
+        let oid = oid.map_err(|e| format!("Failed to walk history: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        let Some((change, _)) = change_at(&commit, &tracked_path) else {
+            continue;
+        };
+
+        total_commits += 1;
+        let author = commit.author();
+        let name = author.name().unwrap_or_default().to_string();
+        let time = commit.time().seconds();
+        if last_touched.is_none() {
+            last_touched = Some(FileVersion {
+                commit_id: commit.id().to_string(),
+                short_id: commit.id().to_string()[..7].to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: name.clone(),
+                timestamp: time,
+                change: change.to_string(),
+                tracked_path: tracked_name.clone(),
+                is_dvc,
+                md5: None,
+                size: None,
+                nfiles: None,
+            });
+        }
+        let email = author.email().unwrap_or_default().to_lowercase();
+        let owner = owners.entry(email.clone()).or_insert_with(|| PathOwner {
+            name,
+            email,
+            commits: 0,
+            last_commit_at: time,
+            lines: None,
+        });
+        owner.commits += 1;
+        owner.last_commit_at = owner.last_commit_at.max(time);
+    }
+
+    // Blame only means something for text; a pointer's lines are just hashes
+    let is_text = !is_dvc
+        && repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok())
+            .and_then(|head| blob_at(&head, &tracked_path))
+            .and_then(|id| repo.find_blob(id).ok())
+            .is_some_and(|blob| !blob.is_binary());
+    if is_text {
+        let blame = repo
+            .blame_file(&tracked_path, None)
+            .map_err(|e| format!("Failed to blame {}: {}", tracked_name, e))?;
+        for hunk in blame.iter() {
+            let signature = hunk.final_signature();
+            let email = signature.email().unwrap_or_default().to_lowercase();
+            let owner = owners.entry(email.clone()).or_insert_with(|| PathOwner {
+                name: signature.name().unwrap_or_default().to_string(),
+                email,
+                commits: 0,
+                last_commit_at: signature.when().seconds(),
+                lines: None,
+            });
+            *owner.lines.get_or_insert(0) += hunk.lines_in_hunk();
+        }
+    }
+
+    let mut owners: Vec<PathOwner> = owners.into_values().collect();
+    owners.sort_by(|a, b| {
+        b.commits
+            .cmp(&a.commits)
+            .then(b.lines.cmp(&a.lines))
+            .then(a.name.cmp(&b.name))
+    });
+
+    Ok(PathOwners {
+        tracked_path: tracked_name,
+        is_dvc,
+        total_commits,
+        last_touched,
+        owners,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreResult {
+    pub commit_id: String,
+    /// Repository relative path written and staged (the pointer for DVC data)
+    pub restored_path: String,
+    /// Result of checking the data out of the DVC cache, for DVC-tracked paths
+    pub checkout: Option<CheckoutSummary>,
+}
+
+/// Restores a file, or a DVC pointer and its data, as it was at a past commit
+/// without moving HEAD. The restored file is staged so it can be reviewed and
+/// committed like any other change.
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn restore_file_version(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    commit_id: String,
+) -> Result<RestoreResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "restore_file_version",
+        json!({ "path": path, "commit_id": commit_id }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                restore_version(repo_path.clone(), path, commit_id)
+            })
+        },
+    )
+}
+
+fn restore_version(
+    repo_path: String,
+    path: String,
+    commit_id: String,
+) -> Result<RestoreResult, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let (tracked_path, is_dvc) = history_path(&repo, &path)?;
+
+    let commit = repo
+        .revparse_single(&commit_id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find commit {}: {}", commit_id, e))?;
+    let blob_id = blob_at(&commit, &tracked_path).ok_or_else(|| {
+        format!(
+            "{} does not exist at commit {}",
+            dvcfile::to_git_path(&tracked_path),
+            commit_id
+        )
+    })?;
+    let blob = repo
+        .find_blob(blob_id)
+        .map_err(|e| format!("Failed to read blob: {}", e))?;
+
+    let destination = repo_root.join(&tracked_path);
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&destination, blob.content())
+        .map_err(|e| format!("Failed to write {}: {}", destination.display(), e))?;
+
+    let mut staged = vec![tracked_path.clone()];
+    let checkout = if is_dvc {
+        let dvc_file = dvcfile::read_dvc_file(&destination)?;
+        let base_dir = destination.parent().unwrap_or(&repo_root);
+        for out in &dvc_file.outs {
+            let gitignore = dvcfile::add_gitignore_entry(&base_dir.join(&out.path))?;
+            staged.push(dvcfile::repo_relative_path(&repo_root, &gitignore)?);
+        }
+        // The restored pointer is authoritative, so overwrite the workspace data
+        Some(checkout::checkout_pointers(
+            &repo_root,
+            &[destination.clone()],
+            true,
+        )?)
+    } else {
+        None
+    };
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    for path in &staged {
+        index
+            .add_path(path)
+            .map_err(|e| format!("Failed to stage {}: {}", path.display(), e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    Ok(RestoreResult {
+        commit_id: commit.id().to_string(),
+        restored_path: dvcfile::to_git_path(&tracked_path),
+        checkout,
+    })
+}
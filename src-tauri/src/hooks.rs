@@ -0,0 +1,434 @@
+use git2::{Delta, Index, Repository};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::notebook::{self, NotebookOutputSettings};
+use crate::sensitive::{self, SensitiveDataSettings, SensitiveFinding};
+use crate::{dvcfile, settings, transfer};
+
+const LARGE_FILES: &str = "large-files";
+const DVC_SYNTAX: &str = "dvc-syntax";
+const COMMIT_MESSAGE: &str = "commit-message";
+const SENSITIVE_DATA: &str = "sensitive-data";
+const SECRETS: &str = "secrets";
+const NOTEBOOK_OUTPUTS: &str = "notebook-outputs";
+
+const HOOKS: [&str; 6] = [
+    LARGE_FILES,
+    DVC_SYNTAX,
+    COMMIT_MESSAGE,
+    SENSITIVE_DATA,
+    SECRETS,
+    NOTEBOOK_OUTPUTS,
+];
+
+/// Hooks that are off until a project turns them on
+const OPT_IN_HOOKS: &[&str] = &[SENSITIVE_DATA, NOTEBOOK_OUTPUTS];
+
+/// Pre-commit check configuration, stored with the project settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HookSettings {
+    /// Ids of the hooks turned off for the project
+    pub disabled: Vec<String>,
+    /// Ids of the opt-in hooks turned on for the project
+    pub enabled: Vec<String>,
+    /// Staged files above this size must be tracked with DVC instead
+    pub max_file_size_kib: u64,
+    /// Extensions (without the dot) of data files that are never staged directly
+    pub data_extensions: Vec<String>,
+    pub max_summary_length: usize,
+    pub sensitive_data: SensitiveDataSettings,
+    pub notebook_outputs: NotebookOutputSettings,
+}
+
+impl Default for HookSettings {
+    fn default() -> Self {
+        Self {
+            disabled: Vec::new(),
+            enabled: Vec::new(),
+            max_file_size_kib: 10 * 1024,
+            data_extensions: [
+                "csv", "parquet", "h5", "hdf5", "npy", "npz", "pkl", "pt", "ckpt", "tfrecord",
+                "zip", "tar", "gz",
+            ]
+            .into_iter()
+            .map(String::from)
+            .collect(),
+            max_summary_length: 72,
+            sensitive_data: SensitiveDataSettings::default(),
+            notebook_outputs: NotebookOutputSettings::default(),
+        }
+    }
+}
+
+impl HookSettings {
+    fn is_enabled(&self, hook: &str) -> bool {
+        if OPT_IN_HOOKS.contains(&hook) {
+            self.enabled.iter().any(|id| id == hook)
+        } else {
+            !self.disabled.iter().any(|id| id == hook)
+        }
+    }
+
+    fn is_data_extension(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|ext| {
+                self.data_extensions
+                    .iter()
+                    .any(|data| data.eq_ignore_ascii_case(ext))
+            })
+    }
+}
+
+/// File refused by the staging guard, to be tracked with DVC instead
+#[derive(Debug, Serialize)]
+pub struct DvcTrackSuggestion {
+    pub path: String,
+    pub size: u64,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HookInfo {
+    pub id: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct HookViolation {
+    pub hook: String,
+    /// Staged file the violation is about; `None` for commit message rules
+    pub path: Option<String>,
+    /// 1-based line of the file the violation is on, for content scans
+    pub line: Option<usize>,
+    /// Scan rule that matched, e.g. "aws-access-key"
+    pub rule: Option<String>,
+    pub message: String,
+}
+
+fn hook_descriptions(settings: &HookSettings) -> [(&'static str, String); 6] {
+    [
+        (
+            LARGE_FILES,
+            format!(
+                "Keep files over {} KiB and data files out of git in favour of DVC",
+                settings.max_file_size_kib
+            ),
+        ),
+        (
+            DVC_SYNTAX,
+            "Validate the syntax of staged .dvc files".to_string(),
+        ),
+        (
+            COMMIT_MESSAGE,
+            format!(
+                "Require a commit summary of at most {} characters",
+                settings.max_summary_length
+            ),
+        ),
+        (
+            SENSITIVE_DATA,
+            "Block staged text files containing emails, social security numbers or API keys"
+                .to_string(),
+        ),
+        (
+            SECRETS,
+            "Block staged files containing credentials like cloud keys, tokens or private keys"
+                .to_string(),
+        ),
+        (
+            NOTEBOOK_OUTPUTS,
+            match settings.notebook_outputs.max_output_kib {
+                0 => "Strip outputs from staged notebooks".to_string(),
+                max => format!("Strip outputs over {} KiB from staged notebooks", max),
+            },
+        ),
+    ]
+}
+
+fn violation(hook: &str, path: Option<String>, message: String) -> HookViolation {
+    HookViolation {
+        hook: hook.to_string(),
+        path,
+        line: None,
+        rule: None,
+        message,
+    }
+}
+
+fn finding_violation(hook: &str, finding: SensitiveFinding) -> HookViolation {
+    HookViolation {
+        hook: hook.to_string(),
+        message: format!(
+            "{} on line {} of {}: {}",
+            finding.description, finding.line, finding.path, finding.excerpt
+        ),
+        path: Some(finding.path),
+        line: Some(finding.line),
+        rule: Some(finding.rule),
+    }
+}
+
+fn check_commit_message(settings: &HookSettings, message: &str) -> Vec<HookViolation> {
+    let summary = message.lines().next().unwrap_or_default().trim();
+    let mut violations = Vec::new();
+    if summary.is_empty() {
+        violations.push(violation(
+            COMMIT_MESSAGE,
+            None,
+            "Commit summary cannot be empty".to_string(),
+        ));
+    } else if summary.chars().count() > settings.max_summary_length {
+        violations.push(violation(
+            COMMIT_MESSAGE,
+            None,
+            format!(
+                "Commit summary is {} characters, the limit is {}",
+                summary.chars().count(),
+                settings.max_summary_length
+            ),
+        ));
+    }
+    violations
+}
+
+/// Rewrites the staged versions of notebooks in `index` without their
+/// outputs, as the `notebook-outputs` hook is configured. Only the in-memory
+/// index changes; the caller writes it once the commit goes ahead, and the
+/// working tree keeps the outputs. Returns the notebooks that were changed.
+pub fn strip_staged_notebooks(
+    repo: &Repository,
+    index: &mut Index,
+    settings: &HookSettings,
+) -> Result<Vec<String>, String> {
+    if !settings.is_enabled(NOTEBOOK_OUTPUTS) {
+        return Ok(Vec::new());
+    }
+
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&*index), None)
+        .map_err(|e| format!("Failed to diff staged changes: {}", e))?;
+
+    let mut stripped = Vec::new();
+    for delta in diff.deltas() {
+        if !matches!(
+            delta.status(),
+            Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied
+        ) {
+            continue;
+        }
+        let file = delta.new_file();
+        let Some(path) = file.path() else {
+            continue;
+        };
+        if path.extension().and_then(|e| e.to_str()) != Some("ipynb") {
+            continue;
+        }
+        let git_path = dvcfile::to_git_path(path);
+        let blob = repo
+            .find_blob(file.id())
+            .map_err(|e| format!("Failed to read {}: {}", git_path, e))?;
+        let content = notebook::strip_outputs(blob.content(), &settings.notebook_outputs)
+            .map_err(|e| format!("Failed to strip {}: {}", git_path, e))?;
+        let Some(content) = content else {
+            continue;
+        };
+
+        let mut entry = index
+            .get_path(path, 0)
+            .ok_or_else(|| format!("{} is not in the index", git_path))?;
+        entry.id = repo
+            .blob(&content)
+            .map_err(|e| format!("Failed to write {}: {}", git_path, e))?;
+        entry.file_size = content.len() as u32;
+        index
+            .add(&entry)
+            .map_err(|e| format!("Failed to stage {}: {}", git_path, e))?;
+        stripped.push(git_path);
+    }
+    Ok(stripped)
+}
+
+/// Runs the enabled checks against the changes staged in `index` and the
+/// commit message
+pub fn run_pre_commit(
+    repo: &Repository,
+    index: &Index,
+    settings: &HookSettings,
+    message: &str,
+) -> Result<Vec<HookViolation>, String> {
+    let head_tree = repo.head().ok().and_then(|head| head.peel_to_tree().ok());
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(index), None)
+        .map_err(|e| format!("Failed to diff staged changes: {}", e))?;
+
+    let max_size = settings.max_file_size_kib.saturating_mul(1024);
+    let sensitive_rules = if settings.is_enabled(SENSITIVE_DATA) {
+        sensitive::rules(&settings.sensitive_data)?
+    } else {
+        Vec::new()
+    };
+    let mut violations = Vec::new();
+    for delta in diff.deltas() {
+        if !matches!(
+            delta.status(),
+            Delta::Added | Delta::Modified | Delta::Renamed | Delta::Copied
+        ) {
+            continue;
+        }
+        let file = delta.new_file();
+        let Some(path) = file.path() else {
+            continue;
+        };
+        let git_path = dvcfile::to_git_path(path);
+        let blob = repo
+            .find_blob(file.id())
+            .map_err(|e| format!("Failed to read {}: {}", git_path, e))?;
+        let is_pointer = path.extension().and_then(|e| e.to_str()) == Some("dvc");
+
+        if settings.is_enabled(LARGE_FILES) && !is_pointer && blob.size() as u64 > max_size {
+            violations.push(violation(
+                LARGE_FILES,
+                Some(git_path.clone()),
+                format!(
+                    "{} is {} KiB; track it with DVC instead of committing it to git",
+                    git_path,
+                    blob.size() / 1024
+                ),
+            ));
+        }
+
+        if settings.is_enabled(DVC_SYNTAX) && is_pointer {
+            if let Some(message) = pointer_error(blob.content(), path) {
+                violations.push(violation(DVC_SYNTAX, Some(git_path.clone()), message));
+            }
+        }
+
+        if !sensitive_rules.is_empty() && !is_pointer {
+            let max_len = settings.sensitive_data.max_scan_kib.saturating_mul(1024) as usize;
+            let content = &blob.content()[..blob.size().min(max_len)];
+            for finding in sensitive::scan_content(&git_path, content, &sensitive_rules) {
+                violations.push(finding_violation(SENSITIVE_DATA, finding));
+            }
+        }
+
+        // Notebooks and configs leak keys, so every staged file is checked
+        if settings.is_enabled(SECRETS) {
+            for finding in
+                sensitive::scan_content(&git_path, blob.content(), sensitive::secret_rules())
+            {
+                violations.push(finding_violation(SECRETS, finding));
+            }
+        }
+    }
+
+    if settings.is_enabled(COMMIT_MESSAGE) {
+        violations.extend(check_commit_message(settings, message));
+    }
+    Ok(violations)
+}
+
+/// Files among `files` (relative to the repository root) that should be
+/// tracked with DVC rather than staged in git
+pub fn staging_guard(
+    repo_root: &Path,
+    settings: &HookSettings,
+    files: &[String],
+) -> Vec<DvcTrackSuggestion> {
+    if !settings.is_enabled(LARGE_FILES) {
+        return Vec::new();
+    }
+
+    let max_size = settings.max_file_size_kib.saturating_mul(1024);
+    files
+        .iter()
+        .filter_map(|file| {
+            let path = Path::new(file);
+            // Deletions and directories are left to git
+            let metadata = std::fs::metadata(repo_root.join(path)).ok()?;
+            if !metadata.is_file() {
+                return None;
+            }
+            let size = metadata.len();
+            let reason = if size > max_size {
+                format!(
+                    "{} KiB is over the {} KiB limit for files in git",
+                    size / 1024,
+                    settings.max_file_size_kib
+                )
+            } else if settings.is_data_extension(path) {
+                "Looks like a data file".to_string()
+            } else {
+                return None;
+            };
+            Some(DvcTrackSuggestion {
+                path: file.clone(),
+                size,
+                reason,
+            })
+        })
+        .collect()
+}
+
+/// Why a `.dvc` file's content isn't a usable pointer, if it isn't
+fn pointer_error(content: &[u8], path: &Path) -> Option<String> {
+    let name = dvcfile::to_git_path(path);
+    let dvc_file = match dvcfile::parse_dvc_file(&String::from_utf8_lossy(content)) {
+        Ok(dvc_file) => dvc_file,
+        Err(e) => return Some(format!("{} is not a valid .dvc file: {}", name, e)),
+    };
+    if dvc_file.outs.is_empty() {
+        return Some(format!("{} has no outputs", name));
+    }
+    dvc_file
+        .outs
+        .iter()
+        .find(|out| out.md5.is_none())
+        .map(|out| format!("{} output {} has no md5 hash", name, out.path))
+}
+
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_hooks(app_handle: AppHandle, repo_path: String) -> Result<Vec<HookInfo>, String> {
+    let settings = settings::load(&app_handle, &transfer::repo_root(&repo_path)?)?.hooks;
+    Ok(hook_descriptions(&settings)
+        .into_iter()
+        .map(|(id, description)| HookInfo {
+            id: id.to_string(),
+            description,
+            enabled: settings.is_enabled(id),
+        })
+        .collect())
+}
+
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_hook_enabled(
+    app_handle: AppHandle,
+    repo_path: String,
+    hook: String,
+    enabled: bool,
+) -> Result<Vec<HookInfo>, String> {
+    if !HOOKS.contains(&hook.as_str()) {
+        return Err(format!("Unknown hook: {}", hook));
+    }
+
+    settings::update(&app_handle, &transfer::repo_root(&repo_path)?, |settings| {
+        settings.hooks.disabled.retain(|id| id != &hook);
+        settings.hooks.enabled.retain(|id| id != &hook);
+        if OPT_IN_HOOKS.contains(&hook.as_str()) {
+            if enabled {
+                settings.hooks.enabled.push(hook.clone());
+            }
+        } else if !enabled {
+            settings.hooks.disabled.push(hook.clone());
+        }
+    })?;
+    list_hooks(app_handle, repo_path)
+}
@@ -0,0 +1,162 @@
+use git2::{Config, ConfigLevel, Repository, Signature};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+/// Git config a `set_git_identity` call writes to
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IdentityScope {
+    /// This repository only
+    Repository,
+    /// Every repository of the user, like `git config --global`
+    Global,
+}
+
+/// Name and email commits are made with, as git resolves them
+#[derive(Debug, Serialize)]
+pub struct GitIdentity {
+    pub name: Option<String>,
+    pub email: Option<String>,
+    /// Where the name comes from: "repository", "global" or "system"
+    pub scope: Option<String>,
+}
+
+fn scope_name(level: ConfigLevel) -> &'static str {
+    match level {
+        ConfigLevel::Local | ConfigLevel::Worktree => "repository",
+        ConfigLevel::Global | ConfigLevel::XDG => "global",
+        _ => "system",
+    }
+}
+
+fn repo_config(repo_path: Option<&str>) -> Result<Config, String> {
+    match repo_path {
+        Some(repo_path) => Repository::discover(repo_path)
+            .map_err(|e| format!("Failed to open repository: {}", e))?
+            .config(),
+        None => Config::open_default(),
+    }
+    .map_err(|e| format!("Failed to read git config: {}", e))
+}
+
+fn identity(config: &Config) -> GitIdentity {
+    let value = |key: &str| {
+        config
+            .get_string(key)
+            .ok()
+            .filter(|value| !value.trim().is_empty())
+    };
+    GitIdentity {
+        name: value("user.name"),
+        email: value("user.email"),
+        scope: config
+            .get_entry("user.name")
+            .ok()
+            .map(|entry| scope_name(entry.level()).to_string()),
+    }
+}
+
+/// Signature for commits made by the app: the name and email from the
+/// repository's git config, falling back to the user's global config
+pub(crate) fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    repo.signature().map_err(|_| {
+        "No git identity is configured; set your name and email before committing".to_string()
+    })
+}
+
+/// Identity commits in the repository are made with, or the global one
+/// without `repo_path`
+#[command]
+#[instrument(err(Debug))]
+pub fn get_git_identity(repo_path: Option<String>) -> Result<GitIdentity, String> {
+    Ok(identity(&repo_config(repo_path.as_deref())?))
+}
+
+/// Saves the name and email commits are made with, for one repository or as
+/// the user's global git identity
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_git_identity(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+    name: String,
+    email: String,
+    scope: IdentityScope,
+) -> Result<GitIdentity, String> {
+    let (name, email) = (name.trim(), email.trim());
+    if name.is_empty() || email.is_empty() {
+        return Err("Name and email cannot be empty".to_string());
+    }
+    Signature::now(name, email).map_err(|e| format!("Invalid identity: {}", e))?;
+
+    let mut config = match scope {
+        IdentityScope::Repository => {
+            let repo_path = repo_path
+                .as_deref()
+                .ok_or("A repository is needed to set its identity")?;
+            repo_config(Some(repo_path))?
+                .open_level(ConfigLevel::Local)
+                .map_err(|e| format!("Failed to open repository config: {}", e))?
+        }
+        IdentityScope::Global => {
+            let path = match Config::find_global() {
+                Ok(path) => path,
+                Err(_) => app_handle
+                    .path()
+                    .home_dir()
+                    .map_err(|e| format!("Failed to get home directory: {}", e))?
+                    .join(".gitconfig"),
+            };
+            Config::open(&path).map_err(|e| format!("Failed to open global git config: {}", e))?
+        }
+    };
+    config
+        .set_str("user.name", name)
+        .and_then(|_| config.set_str("user.email", email))
+        .map_err(|e| format!("Failed to write git config: {}", e))?;
+
+    Ok(identity(&repo_config(repo_path.as_deref())?))
+}
+
+/// Git settings the app needs before it can commit
+#[derive(Debug, Serialize)]
+pub struct GitConfigurationCheck {
+    pub complete: bool,
+    /// Missing config keys, e.g. "user.name"
+    pub missing: Vec<String>,
+    pub identity: GitIdentity,
+}
+
+/// Which of the settings needed to commit are missing, for the repository or
+/// globally without `repo_path`
+#[command]
+#[instrument(err(Debug))]
+pub fn check_git_configuration(repo_path: Option<String>) -> Result<GitConfigurationCheck, String> {
+    let identity = identity(&repo_config(repo_path.as_deref())?);
+    let mut missing = Vec::new();
+    if identity.name.is_none() {
+        missing.push("user.name".to_string());
+    }
+    if identity.email.is_none() {
+        missing.push("user.email".to_string());
+    }
+    Ok(GitConfigurationCheck {
+        complete: missing.is_empty(),
+        missing,
+        identity,
+    })
+}
+
+/// Writes the name and email to the user's global git config, for users
+/// setting up git for the first time
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn bootstrap_git_config(
+    app_handle: AppHandle,
+    name: String,
+    email: String,
+) -> Result<GitConfigurationCheck, String> {
+    set_git_identity(app_handle, None, name, email, IdentityScope::Global)?;
+    check_git_configuration(None)
+}
@@ -0,0 +1,176 @@
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use git2::Repository;
+use image::{ImageFormat, ImageReader};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::Cursor;
+use std::path::Path;
+use tauri::command;
+use tracing::instrument;
+
+use crate::{dvcfile, history};
+
+/// Extensions of the files compared as images
+const IMAGE_EXTENSIONS: &[&str] = &["png", "jpg", "jpeg", "gif", "bmp", "tif", "tiff", "webp"];
+
+/// Longest side of a thumbnail, in pixels
+const THUMBNAIL_SIZE: u32 = 128;
+
+/// Changed images beyond this many are listed without thumbnails
+const MAX_THUMBNAILS: usize = 50;
+
+#[derive(Debug, Serialize)]
+pub struct ImageChange {
+    /// Path inside the directory
+    pub path: String,
+    pub md5_a: String,
+    pub md5_b: String,
+    /// Base64 PNG thumbnails; `None` when the image isn't in the local cache
+    /// or can't be decoded
+    pub thumbnail_a: Option<String>,
+    pub thumbnail_b: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImageDirectoryDiff {
+    pub path: String,
+    pub rev_a: String,
+    pub rev_b: String,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+    pub changed: Vec<ImageChange>,
+    pub unchanged: usize,
+}
+
+fn is_image(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| {
+            IMAGE_EXTENSIONS
+                .iter()
+                .any(|image| image.eq_ignore_ascii_case(ext))
+        })
+}
+
+/// Images of a DVC-tracked directory at a revision, keyed by their path inside
+/// it, from the directory's manifest
+fn images_at(
+    repo: &Repository,
+    relative: &Path,
+    rev: &str,
+) -> Result<BTreeMap<String, String>, String> {
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find {}: {}", rev, e))?;
+    let name = dvcfile::to_git_path(relative);
+    let (out, inner) = history::dvc_out_at(repo, &commit, relative)
+        .ok_or_else(|| format!("{} is not tracked with DVC at {}", name, rev))?;
+    if !out.is_dir() {
+        return Err(format!("{} is not a directory at {}", name, rev));
+    }
+    let md5 = out.md5.unwrap_or_default();
+    let manifest = dvcfile::read_dir_manifest(&dvcfile::cache_dir(repo_root), &md5)
+        .map_err(|e| format!("{} at {}: {}; pull it first", name, rev, e))?;
+
+    // A subdirectory of the tracked directory only has the entries below it
+    let prefix = match dvcfile::to_git_path(&inner) {
+        inner if inner.is_empty() => inner,
+        inner => format!("{}/", inner),
+    };
+    Ok(manifest
+        .into_iter()
+        .filter_map(|entry| {
+            let path = entry.relpath.strip_prefix(&prefix)?.to_string();
+            is_image(&path).then_some((path, entry.md5))
+        })
+        .collect())
+}
+
+/// Small PNG of a cached image, base64 encoded
+fn thumbnail(cache_dir: &Path, md5: &str) -> Option<String> {
+    let object = dvcfile::cache_object_path(cache_dir, md5);
+    // Cache objects have no extension, so the format comes from the content
+    let image = ImageReader::open(object)
+        .ok()?
+        .with_guessed_format()
+        .ok()?
+        .decode()
+        .ok()?;
+    let mut png = Vec::new();
+    image
+        .thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE)
+        .write_to(&mut Cursor::new(&mut png), ImageFormat::Png)
+        .ok()?;
+    Some(BASE64.encode(png))
+}
+
+/// Images added, removed and changed in a DVC-tracked directory between two
+/// revisions, with thumbnails of both versions of the changed ones
+#[command(async)]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn diff_image_directory(
+    repo_path: String,
+    path: String,
+    rev_a: String,
+    rev_b: String,
+) -> Result<ImageDirectoryDiff, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let relative = dvcfile::repo_relative_path(repo_root, Path::new(&path))?;
+    let images_a = images_at(&repo, &relative, &rev_a)?;
+    let images_b = images_at(&repo, &relative, &rev_b)?;
+
+    let added = images_b
+        .keys()
+        .filter(|path| !images_a.contains_key(*path))
+        .cloned()
+        .collect();
+    let removed = images_a
+        .keys()
+        .filter(|path| !images_b.contains_key(*path))
+        .cloned()
+        .collect();
+
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let mut changed = Vec::new();
+    let mut unchanged = 0;
+    for (path, md5_a) in &images_a {
+        let Some(md5_b) = images_b.get(path) else {
+            continue;
+        };
+        if md5_a == md5_b {
+            unchanged += 1;
+            continue;
+        }
+        let with_thumbnails = changed.len() < MAX_THUMBNAILS;
+        changed.push(ImageChange {
+            path: path.clone(),
+            md5_a: md5_a.clone(),
+            md5_b: md5_b.clone(),
+            thumbnail_a: with_thumbnails
+                .then(|| thumbnail(&cache_dir, md5_a))
+                .flatten(),
+            thumbnail_b: with_thumbnails
+                .then(|| thumbnail(&cache_dir, md5_b))
+                .flatten(),
+        });
+    }
+
+    Ok(ImageDirectoryDiff {
+        path: dvcfile::to_git_path(&relative),
+        rev_a,
+        rev_b,
+        added,
+        removed,
+        changed,
+        unchanged,
+    })
+}
@@ -0,0 +1,450 @@
+use md5::Md5;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+use tauri::{command, AppHandle};
+use tracing::{instrument, warn};
+
+use crate::dvc_undo::{self, AddJournal};
+use crate::transfer::{self, ProgressTracker};
+use crate::{audit, dvc, remote, repo_manager};
+
+const COPY_BUFFER_SIZE: usize = 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    Copy,
+    /// Renames when source and project are on the same filesystem, otherwise
+    /// copies and deletes the source once the data is tracked
+    Move,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DownloadResult {
+    pub operation_id: String,
+    /// Destination relative to the repository root
+    pub path: String,
+    pub bytes: u64,
+    pub md5: String,
+    pub sha256: String,
+    /// Message of the DVC add that tracked the download
+    pub tracked: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportResult {
+    pub operation_id: String,
+    /// Destination relative to the repository root
+    pub path: String,
+    pub files: u64,
+    pub bytes: u64,
+    /// Message of the DVC add that tracked the data
+    pub tracked: String,
+}
+
+/// Files below `src` (or `src` itself) with their sizes
+fn source_files(src: &Path) -> Result<Vec<(PathBuf, u64)>, String> {
+    let mut files = Vec::new();
+    for entry in walkdir::WalkDir::new(src) {
+        let entry = entry.map_err(|e| format!("Failed to read {}: {}", src.display(), e))?;
+        if entry.file_type().is_file() {
+            let size = entry
+                .metadata()
+                .map_err(|e| format!("Failed to read {}: {}", entry.path().display(), e))?
+                .len();
+            files.push((entry.into_path(), size));
+        }
+    }
+    Ok(files)
+}
+
+fn copy_file(source: &Path, target: &Path, tracker: &ProgressTracker) -> Result<(), String> {
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let mut reader = fs::File::open(source)
+        .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let mut writer = fs::File::create(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        writer
+            .write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        tracker.add_bytes(read as u64);
+    }
+    Ok(())
+}
+
+fn copy_tree(
+    src: &Path,
+    dest: &Path,
+    files: &[(PathBuf, u64)],
+    tracker: &ProgressTracker,
+) -> Result<(), String> {
+    for (source, _) in files {
+        let relative = source.strip_prefix(src).unwrap_or(Path::new(""));
+        let target = if relative.as_os_str().is_empty() {
+            dest.to_path_buf()
+        } else {
+            dest.join(relative)
+        };
+        copy_file(source, &target, tracker)?;
+        tracker.complete();
+    }
+    // Keep empty directories of the source
+    if src.is_dir() {
+        fs::create_dir_all(dest)
+            .map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    }
+    Ok(())
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Destination inside the repository; it must be relative, stay inside the
+/// repository and not exist yet
+fn destination(repo_root: &Path, dest_rel_path: &str) -> Result<PathBuf, String> {
+    let relative = Path::new(dest_rel_path);
+    let inside = relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_) | Component::CurDir));
+    if !inside || relative.as_os_str().is_empty() {
+        return Err(format!(
+            "{} must be a path relative to the repository root",
+            dest_rel_path
+        ));
+    }
+    let dest = repo_root.join(relative);
+    if dest.exists() {
+        return Err(format!("{} already exists", dest_rel_path));
+    }
+    Ok(dest)
+}
+
+/// Tracks newly brought in data like `add_dvc_file`, so `undo_last_dvc_add`
+/// reverts it
+fn track(app_handle: &AppHandle, repo_root: &Path, relative: &str) -> Result<String, String> {
+    let repo_root = repo_root.to_string_lossy().to_string();
+    repo_manager::write(app_handle, &repo_root, || {
+        let journal = AddJournal::record(&repo_root, &[relative.to_string()])?;
+        let result = dvc::add_single(app_handle, &repo_root, relative).map_err(String::from);
+        dvc_undo::finish(&journal, &repo_root, result)
+    })
+}
+
+fn import(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    src_path: &str,
+    dest_rel_path: &str,
+    mode: ImportMode,
+    operation_id: String,
+) -> Result<ImportResult, String> {
+    let repo_root = transfer::repo_root(repo_path)?;
+    let src = Path::new(src_path)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", src_path, e))?;
+    let canonical_root = repo_root
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", repo_root.display(), e))?;
+    if src.starts_with(&canonical_root) {
+        return Err(format!(
+            "{} is already inside the repository; track it directly",
+            src_path
+        ));
+    }
+    let dest = destination(&repo_root, dest_rel_path)?;
+
+    let files = source_files(&src)?;
+    let bytes = files.iter().map(|(_, size)| size).sum();
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "import");
+    tracker.add_total(files.len() as u64);
+
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let renamed = matches!(mode, ImportMode::Move) && fs::rename(&src, &dest).is_ok();
+    if renamed {
+        tracker.add_bytes(bytes);
+        for _ in &files {
+            tracker.complete();
+        }
+    } else if let Err(e) = copy_tree(&src, &dest, &files, &tracker) {
+        if let Err(cleanup) = remove_path(&dest) {
+            warn!(
+                "Failed to remove partial copy {}: {}",
+                dest.display(),
+                cleanup
+            );
+        }
+        return Err(e);
+    }
+    tracker.emit(true);
+
+    let relative = dest_rel_path.replace('\\', "/");
+    let tracked = match track(app_handle, &repo_root, &relative) {
+        Ok(tracked) => tracked,
+        Err(e) => {
+            // Leave the source where it was
+            let restored = if renamed {
+                fs::rename(&dest, &src)
+            } else {
+                remove_path(&dest)
+            };
+            if let Err(restore) = restored {
+                warn!(
+                    "Failed to undo the import of {}: {}",
+                    src.display(),
+                    restore
+                );
+            }
+            return Err(e);
+        }
+    };
+
+    if matches!(mode, ImportMode::Move) && !renamed {
+        if let Err(e) = remove_path(&src) {
+            warn!("Imported {} but failed to remove it: {}", src.display(), e);
+        }
+    }
+
+    Ok(ImportResult {
+        operation_id,
+        path: relative,
+        files: files.len() as u64,
+        bytes,
+        tracked,
+    })
+}
+
+/// Checksum a download must match
+enum Checksum {
+    Md5(String),
+    Sha256(String),
+}
+
+/// Accepts `md5:<hex>` and `sha256:<hex>`, or bare hex whose length tells the
+/// algorithm
+fn parse_checksum(value: &str) -> Result<Checksum, String> {
+    let value = value.trim();
+    let (algorithm, hex) = match value.split_once(':') {
+        Some((algorithm, hex)) => (algorithm.to_ascii_lowercase(), hex),
+        None => {
+            let algorithm = match value.len() {
+                32 => "md5",
+                64 => "sha256",
+                _ => return Err(format!("Cannot tell the algorithm of checksum {}", value)),
+            };
+            (algorithm.to_string(), value)
+        }
+    };
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("{} is not a hex checksum", hex));
+    }
+    let hex = hex.to_ascii_lowercase();
+    match algorithm.as_str() {
+        "md5" if hex.len() == 32 => Ok(Checksum::Md5(hex)),
+        "sha256" if hex.len() == 64 => Ok(Checksum::Sha256(hex)),
+        _ => Err(format!("Unsupported checksum {}; use md5 or sha256", value)),
+    }
+}
+
+/// Streams a response into `target`, returning its size, md5 and sha256
+fn write_download(
+    response: ureq::Response,
+    target: &Path,
+    tracker: &ProgressTracker,
+) -> Result<(u64, String, String), String> {
+    let mut reader = response.into_reader();
+    let mut file = fs::File::create(target)
+        .map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+    let mut md5 = Md5::new();
+    let mut sha256 = Sha256::new();
+    let mut size = 0;
+    let mut buffer = vec![0u8; COPY_BUFFER_SIZE];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to download: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        file.write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write {}: {}", target.display(), e))?;
+        md5.update(&buffer[..read]);
+        sha256.update(&buffer[..read]);
+        size += read as u64;
+        tracker.add_bytes(read as u64);
+    }
+    Ok((
+        size,
+        format!("{:x}", md5.finalize()),
+        format!("{:x}", sha256.finalize()),
+    ))
+}
+
+fn download(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    url: &str,
+    dest: &str,
+    expected_checksum: Option<&str>,
+    operation_id: String,
+) -> Result<DownloadResult, String> {
+    if !(url.starts_with("https://") || url.starts_with("http://")) {
+        return Err(format!("{} is not an HTTP(S) URL", url));
+    }
+    let expected = expected_checksum.map(parse_checksum).transpose()?;
+    let repo_root = transfer::repo_root(repo_path)?;
+    let target = destination(&repo_root, dest)?;
+    let file_name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("The destination needs a file name")?;
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "download");
+    tracker.add_total(1);
+    let response = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(30))
+        .timeout_read(Duration::from_secs(300))
+        .build()
+        .get(url)
+        .set("User-Agent", "fenn-app")
+        .call()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+
+    // Downloaded next to the destination so a failed or rejected download
+    // never appears under its final name
+    let partial = target.with_file_name(format!(".{}.part", file_name));
+    let (bytes, md5, sha256) = match write_download(response, &partial, &tracker) {
+        Ok(download) => download,
+        Err(e) => {
+            let _ = fs::remove_file(&partial);
+            tracker.fail();
+            return Err(e);
+        }
+    };
+    let mismatch = match &expected {
+        Some(Checksum::Md5(hex)) if *hex != md5 => Some((hex, &md5)),
+        Some(Checksum::Sha256(hex)) if *hex != sha256 => Some((hex, &sha256)),
+        _ => None,
+    };
+    if let Some((expected, actual)) = mismatch {
+        let _ = fs::remove_file(&partial);
+        tracker.fail();
+        return Err(format!(
+            "Checksum mismatch for {}: expected {}, got {}",
+            url, expected, actual
+        ));
+    }
+    fs::rename(&partial, &target)
+        .map_err(|e| format!("Failed to move download to {}: {}", target.display(), e))?;
+    tracker.complete();
+    tracker.emit(true);
+
+    let relative = dest.replace('\\', "/");
+    let tracked = track(app_handle, &repo_root, &relative).inspect_err(|_| {
+        if let Err(e) = fs::remove_file(&target) {
+            warn!("Failed to remove {}: {}", target.display(), e);
+        }
+    })?;
+
+    Ok(DownloadResult {
+        operation_id,
+        path: relative,
+        bytes,
+        md5,
+        sha256,
+        tracked,
+    })
+}
+
+/// Brings a file or folder from outside the project into it at
+/// `dest_rel_path`, tracks it with DVC and stages the pointer and
+/// `.gitignore`. Copy progress is reported as transfer progress events with
+/// the "import" operation.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn import_external_data(
+    app_handle: AppHandle,
+    repo_path: String,
+    src_path: String,
+    dest_rel_path: String,
+    mode: ImportMode,
+    operation_id: Option<String>,
+) -> Result<ImportResult, String> {
+    let operation_id = remote::operation_id(operation_id);
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "import_external_data",
+        json!({ "src_path": src_path, "dest_rel_path": dest_rel_path, "mode": mode }),
+        || {
+            import(
+                &app_handle,
+                &repo_path,
+                &src_path,
+                &dest_rel_path,
+                mode,
+                operation_id.clone(),
+            )
+        },
+    )
+}
+
+/// Downloads a file over HTTP(S) to `dest` in the project, checks it against
+/// `expected_checksum` (`md5:` or `sha256:` prefixed hex) when given and
+/// tracks it with DVC. Progress is reported as transfer progress events with
+/// the "download" operation.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn download_and_track(
+    app_handle: AppHandle,
+    repo_path: String,
+    url: String,
+    dest: String,
+    expected_checksum: Option<String>,
+    operation_id: Option<String>,
+) -> Result<DownloadResult, String> {
+    let operation_id = remote::operation_id(operation_id);
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "download_and_track",
+        json!({ "url": url, "dest": dest, "expected_checksum": expected_checksum }),
+        || {
+            download(
+                &app_handle,
+                &repo_path,
+                &url,
+                &dest,
+                expected_checksum.as_deref(),
+                operation_id.clone(),
+            )
+        },
+    )
+}
@@ -0,0 +1,463 @@
+use notify::{Event, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, UNIX_EPOCH};
+use tauri::{command, AppHandle, State};
+
+use crate::db::{self, PooledConnection};
+use crate::dvc;
+use crate::dvcfile;
+use crate::file::{self, FileEntry, TreeFilter};
+use crate::metrics;
+use crate::remote_status;
+use crate::transfer;
+
+#[derive(Debug, Serialize)]
+pub struct FileTreeSnapshot {
+    pub entries: Vec<FileEntry>,
+    /// True when the index may not reflect the disk, e.g. it was built in a
+    /// previous session or DVC statuses changed since the last full scan
+    pub stale: bool,
+    pub indexed_at: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct PendingChanges {
+    paths: HashSet<PathBuf>,
+    git_state_changed: bool,
+    dvc_state_changed: bool,
+}
+
+/// Filesystem watchers, one per project, and the changes they have seen for
+/// each indexed tree
+#[derive(Default)]
+pub struct FileIndex {
+    /// Watchers by repository root
+    watchers: HashMap<PathBuf, RecommendedWatcher>,
+    /// Indexed trees by repository root, fed by the project's watcher
+    trees: Arc<Mutex<HashMap<PathBuf, HashSet<String>>>>,
+    pending: Arc<Mutex<HashMap<String, PendingChanges>>>,
+}
+
+impl FileIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn is_watched(&self, repo_path: &str) -> bool {
+        self.trees
+            .lock()
+            .map(|trees| trees.values().any(|paths| paths.contains(repo_path)))
+            .unwrap_or(false)
+    }
+}
+
+pub type FileIndexState = Mutex<FileIndex>;
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    let conn = db::connection(app_handle, db::FILE_INDEX_SCHEMA)?;
+    add_file_type_column(&conn)?;
+    Ok(conn)
+}
+
+/// Indexes created before file types were detected lack their column
+fn add_file_type_column(conn: &Connection) -> Result<(), String> {
+    if !db::column_exists(conn, "file_index", "file_type")? {
+        conn.execute("ALTER TABLE file_index ADD COLUMN file_type TEXT", [])
+            .map_err(|e| format!("Failed to add file type column: {}", e))?;
+    }
+    Ok(())
+}
+
+fn modified_time(path: &Path) -> Option<i64> {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_secs() as i64)
+}
+
+/// md5 recorded in the entry's `.dvc` pointer, if it has one
+fn pointer_hash(repo_root: &Path, entry: &FileEntry) -> Option<String> {
+    if !entry.has_dvc_file {
+        return None;
+    }
+    let pointer = dvcfile::pointer_path(&repo_root.join(&entry.path));
+    dvcfile::read_dvc_file(&pointer)
+        .ok()
+        .and_then(|f| f.outs.into_iter().next())
+        .and_then(|out| out.md5)
+}
+
+fn upsert_entries(
+    conn: &mut Connection,
+    repo_path: &str,
+    repo_root: &Path,
+    entries: &[FileEntry],
+) -> Result<(), String> {
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR REPLACE INTO file_index
+                 (repo_path, path, size, mtime, hash, is_directory, has_dvc_file, git_status, file_type, indexed_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, CURRENT_TIMESTAMP)",
+            )
+            .map_err(|e| format!("Failed to prepare index insert: {}", e))?;
+
+        for entry in entries {
+            stmt.execute(params![
+                repo_path,
+                entry.path,
+                entry.size as i64,
+                modified_time(&repo_root.join(&entry.path)),
+                pointer_hash(repo_root, entry),
+                entry.is_directory,
+                entry.has_dvc_file,
+                entry.git_status,
+                entry.file_type,
+            ])
+            .map_err(|e| format!("Failed to index {}: {}", entry.path, e))?;
+        }
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit index update: {}", e))
+}
+
+fn read_entries(conn: &Connection, repo_path: &str) -> Result<Vec<FileEntry>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, size, is_directory, has_dvc_file, git_status, file_type
+             FROM file_index WHERE repo_path = ?1 ORDER BY path",
+        )
+        .map_err(|e| format!("Failed to prepare index query: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![repo_path], |row| {
+            Ok(FileEntry {
+                path: row.get(0)?,
+                size: row.get::<_, i64>(1)? as u64,
+                is_directory: row.get(2)?,
+                has_dvc_file: row.get(3)?,
+                git_status: row.get(4)?,
+                file_type: row.get(5)?,
+                rollup: None,
+                remote_status: None,
+            })
+        })
+        .map_err(|e| format!("Failed to query index: {}", e))?;
+
+    let mut entries = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read index row: {}", e))?;
+    file::compute_rollups(&mut entries);
+    Ok(entries)
+}
+
+fn last_full_scan(conn: &Connection, repo_path: &str) -> Result<Option<String>, String> {
+    conn.query_row(
+        "SELECT last_full_scan FROM file_index_state WHERE repo_path = ?1",
+        params![repo_path],
+        |row| row.get(0),
+    )
+    .optional()
+    .map_err(|e| format!("Failed to read index state: {}", e))
+}
+
+/// Replaces the index of a repository with a fresh walk of the disk
+fn full_scan(app_handle: &AppHandle, conn: &mut Connection, repo_path: &str) -> Result<(), String> {
+    let started = Instant::now();
+    let entries = file::walk_file_tree(app_handle, repo_path)?;
+    let (repo_root, _) = file::get_repo_git_status(Path::new(repo_path))?;
+    metrics::record(
+        app_handle,
+        &repo_root,
+        "tree_scan",
+        started.elapsed(),
+        Some(entries.len() as u64),
+        None,
+    );
+
+    conn.execute(
+        "DELETE FROM file_index WHERE repo_path = ?1",
+        params![repo_path],
+    )
+    .map_err(|e| format!("Failed to clear index: {}", e))?;
+    upsert_entries(conn, repo_path, &repo_root, &entries)?;
+
+    conn.execute(
+        "INSERT OR REPLACE INTO file_index_state (repo_path, last_full_scan, entry_count)
+         VALUES (?1, CURRENT_TIMESTAMP, ?2)",
+        params![repo_path, entries.len() as i64],
+    )
+    .map_err(|e| format!("Failed to update index state: {}", e))?;
+
+    Ok(())
+}
+
+/// Applies watcher-reported changes to the index without walking the whole tree.
+/// Returns true when DVC statuses may now be outdated.
+fn apply_pending(
+    app_handle: &AppHandle,
+    conn: &mut Connection,
+    repo_path: &str,
+    changes: PendingChanges,
+) -> Result<bool, String> {
+    let (repo_root, git_status_map) = file::get_repo_git_status(Path::new(repo_path))?;
+    // DVC statuses need the (slow) diff script; they are refreshed on the next full scan
+    let dvc_status_map = HashMap::new();
+    let filter = TreeFilter::load(app_handle, &repo_root)?;
+    let mut dvc_touched = changes.dvc_state_changed;
+
+    for absolute in &changes.paths {
+        let relative = dvcfile::to_git_path(absolute.strip_prefix(&repo_root).unwrap_or(absolute));
+        if relative.is_empty() {
+            continue;
+        }
+
+        if absolute.exists() {
+            let entries = file::list_file_entries(
+                absolute,
+                &repo_root,
+                &git_status_map,
+                &dvc_status_map,
+                &filter,
+                absolute.is_dir(),
+            )?;
+            dvc_touched |= entries.iter().any(|e| e.has_dvc_file);
+            upsert_entries(conn, repo_path, &repo_root, &entries)?;
+        } else {
+            conn.execute(
+                "DELETE FROM file_index WHERE repo_path = ?1 AND (path = ?2 OR path LIKE ?3)",
+                params![repo_path, relative, format!("{}/%", relative)],
+            )
+            .map_err(|e| format!("Failed to remove {} from index: {}", relative, e))?;
+        }
+    }
+
+    if changes.git_state_changed {
+        // Staging, commits and branch switches only change statuses, not the file set
+        let entries = read_entries(conn, repo_path)?;
+        // They can change what DVC data differs from the pointers as well
+        let dvc_status_map = if entries.iter().any(|e| e.has_dvc_file) {
+            match dvc::dvc_diff(app_handle, &repo_root) {
+                Ok(statuses) => statuses,
+                Err(e) => {
+                    tracing::warn!("Failed to refresh DVC statuses: {}", e);
+                    dvc_touched = true;
+                    HashMap::new()
+                }
+            }
+        } else {
+            HashMap::new()
+        };
+        let refreshed: Vec<FileEntry> = entries
+            .into_iter()
+            .map(|mut e| {
+                e.git_status = file::get_git_status_for_path(
+                    &repo_root.join(&e.path),
+                    &repo_root,
+                    &git_status_map,
+                    e.has_dvc_file,
+                );
+                if let Some(status) = dvc_status_map.get(&e.path).filter(|_| e.has_dvc_file) {
+                    e.git_status = status.clone();
+                }
+                e
+            })
+            .collect();
+        upsert_entries(conn, repo_path, &repo_root, &refreshed)?;
+    }
+
+    Ok(dvc_touched)
+}
+
+/// Records the paths of a watcher event as changes of the trees they belong to
+fn record_event(
+    trees: &HashSet<String>,
+    pending: &mut HashMap<String, PendingChanges>,
+    paths: Vec<PathBuf>,
+) {
+    for path in paths {
+        let git = path.components().any(|c| c.as_os_str() == ".git");
+        let dvc = path.components().any(|c| c.as_os_str() == ".dvc");
+        for tree in trees {
+            if git {
+                pending.entry(tree.clone()).or_default().git_state_changed = true;
+            } else if dvc {
+                pending.entry(tree.clone()).or_default().dvc_state_changed = true;
+            } else if path.starts_with(tree) {
+                pending
+                    .entry(tree.clone())
+                    .or_default()
+                    .paths
+                    .insert(path.clone());
+            }
+        }
+    }
+}
+
+/// Watches the project of a tree, sharing the project's watcher with its
+/// other indexed trees
+fn start_watcher(index: &mut FileIndex, repo_path: &str) -> Result<(), String> {
+    let root = transfer::repo_root(repo_path)?;
+    if !index.watchers.contains_key(&root) {
+        let trees = Arc::clone(&index.trees);
+        let pending = Arc::clone(&index.pending);
+        let key = root.clone();
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let Ok(event) = res else {
+                return;
+            };
+            let (Ok(trees), Ok(mut pending)) = (trees.lock(), pending.lock()) else {
+                return;
+            };
+            if let Some(trees) = trees.get(&key) {
+                record_event(trees, &mut pending, event.paths);
+            }
+        })
+        .map_err(|e| format!("Failed to create file watcher: {}", e))?;
+
+        watcher
+            .watch(&root, RecursiveMode::Recursive)
+            .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+        index.watchers.insert(root.clone(), watcher);
+    }
+
+    index
+        .trees
+        .lock()
+        .map_err(|e| e.to_string())?
+        .entry(root)
+        .or_default()
+        .insert(repo_path.to_string());
+    Ok(())
+}
+
+/// Stops feeding a tree's index; the project's watcher is dropped with its
+/// last tree
+fn stop_watcher(index: &mut FileIndex, repo_path: &str) -> Result<(), String> {
+    let emptied: Vec<PathBuf> = {
+        let mut trees = index.trees.lock().map_err(|e| e.to_string())?;
+        for paths in trees.values_mut() {
+            paths.remove(repo_path);
+        }
+        let emptied = trees
+            .iter()
+            .filter(|(_, paths)| paths.is_empty())
+            .map(|(root, _)| root.clone())
+            .collect();
+        trees.retain(|_, paths| !paths.is_empty());
+        emptied
+    };
+    for root in emptied {
+        index.watchers.remove(&root);
+    }
+    index
+        .pending
+        .lock()
+        .map_err(|e| e.to_string())?
+        .remove(repo_path);
+    Ok(())
+}
+
+/// Sets the remote status of DVC-tracked entries from what is cached about
+/// the remote; the tree is still served when that fails
+fn with_remote_status(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    mut entries: Vec<FileEntry>,
+) -> Vec<FileEntry> {
+    let annotated = transfer::repo_root(repo_path)
+        .and_then(|repo_root| remote_status::annotate(app_handle, &repo_root, &mut entries));
+    if let Err(e) = annotated {
+        tracing::warn!("Failed to read remote status of {}: {}", repo_path, e);
+    }
+    entries
+}
+
+/// Makes the next load of a repository's tree rebuild its index, e.g. after
+/// the tree settings changed
+pub(crate) fn invalidate(app_handle: &AppHandle, repo_path: &str) -> Result<(), String> {
+    let conn = open_connection(app_handle)?;
+    conn.execute(
+        "DELETE FROM file_index_state WHERE repo_path = ?1",
+        params![repo_path],
+    )
+    .map_err(|e| format!("Failed to reset index state: {}", e))?;
+    Ok(())
+}
+
+/// Returns the indexed tree for a repository, building the index on first use
+pub fn load_tree(
+    app_handle: &AppHandle,
+    index_state: &FileIndexState,
+    repo_path: &str,
+) -> Result<FileTreeSnapshot, String> {
+    let mut conn = open_connection(app_handle)?;
+    let mut index = index_state.lock().map_err(|e| e.to_string())?;
+
+    // An index without a running watcher was built in a previous session
+    let mut stale = !index.is_watched(repo_path);
+
+    if last_full_scan(&conn, repo_path)?.is_none() {
+        full_scan(app_handle, &mut conn, repo_path)?;
+        stale = false;
+    } else {
+        let changes = index
+            .pending
+            .lock()
+            .map_err(|e| e.to_string())?
+            .remove(repo_path);
+        if let Some(changes) = changes {
+            stale |= apply_pending(app_handle, &mut conn, repo_path, changes)?;
+        }
+    }
+
+    start_watcher(&mut index, repo_path)?;
+
+    Ok(FileTreeSnapshot {
+        entries: with_remote_status(app_handle, repo_path, read_entries(&conn, repo_path)?),
+        stale,
+        indexed_at: last_full_scan(&conn, repo_path)?,
+    })
+}
+
+/// Rebuilds the index of a repository from a full walk of the disk
+#[command]
+pub fn refresh_file_index(
+    app_handle: AppHandle,
+    index_state: State<'_, FileIndexState>,
+    path: String,
+) -> Result<FileTreeSnapshot, String> {
+    let mut conn = open_connection(&app_handle)?;
+    let mut index = index_state.lock().map_err(|e| e.to_string())?;
+
+    if let Ok(mut pending) = index.pending.lock() {
+        pending.remove(&path);
+    }
+    full_scan(&app_handle, &mut conn, &path)?;
+    start_watcher(&mut index, &path)?;
+
+    Ok(FileTreeSnapshot {
+        entries: with_remote_status(&app_handle, &path, read_entries(&conn, &path)?),
+        stale: false,
+        indexed_at: last_full_scan(&conn, &path)?,
+    })
+}
+
+/// Stops watching a tree, e.g. when its project is closed. The index is kept
+/// and reported stale when the tree is loaded again.
+#[command]
+pub fn close_file_index(
+    index_state: State<'_, FileIndexState>,
+    path: String,
+) -> Result<(), String> {
+    let mut index = index_state.lock().map_err(|e| e.to_string())?;
+    stop_watcher(&mut index, &path)
+}
@@ -0,0 +1,183 @@
+use git2::Repository;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use walkdir::WalkDir;
+
+use crate::dvc_compat::{self, DvcFormat};
+use crate::dvcfile::{self, DvcOut};
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityResult {
+    pub path: String,
+    /// One of "ok", "modified", "missing", "added" or "unknown"
+    pub status: String,
+    pub expected_md5: Option<String>,
+    pub actual_md5: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IntegrityReport {
+    pub checked: usize,
+    pub ok: usize,
+    pub problems: Vec<IntegrityResult>,
+}
+
+fn verify_file(
+    path: &Path,
+    relative: String,
+    expected: Option<&str>,
+    format: Option<DvcFormat>,
+) -> IntegrityResult {
+    if !path.exists() {
+        return IntegrityResult {
+            path: relative,
+            status: "missing".to_string(),
+            expected_md5: expected.map(|s| s.to_string()),
+            actual_md5: None,
+        };
+    }
+
+    let actual = dvcfile::hash_file_for(path, format).ok();
+    let status = match (expected, actual.as_deref()) {
+        (Some(expected), Some(actual)) if expected == actual => "ok",
+        (Some(_), Some(_)) => "modified",
+        _ => "unknown",
+    };
+
+    IntegrityResult {
+        path: relative,
+        status: status.to_string(),
+        expected_md5: expected.map(|s| s.to_string()),
+        actual_md5: actual,
+    }
+}
+
+fn verify_directory(
+    repo_root: &Path,
+    data_path: &Path,
+    out: &DvcOut,
+    format: Option<DvcFormat>,
+    results: &mut Vec<IntegrityResult>,
+) {
+    let relative_dir = dvcfile::to_git_path(data_path.strip_prefix(repo_root).unwrap_or(data_path));
+    let md5 = out.md5.as_deref().unwrap_or_default();
+
+    let manifest = match dvcfile::read_dir_manifest(&dvcfile::cache_dir(repo_root), md5) {
+        Ok(manifest) => manifest,
+        Err(_) => {
+            // Without the manifest there is nothing to compare individual files against
+            results.push(IntegrityResult {
+                path: relative_dir,
+                status: "unknown".to_string(),
+                expected_md5: out.md5.clone(),
+                actual_md5: None,
+            });
+            return;
+        }
+    };
+
+    let mut expected_paths = HashSet::new();
+    for entry in &manifest {
+        expected_paths.insert(entry.relpath.clone());
+        results.push(verify_file(
+            &data_path.join(&entry.relpath),
+            format!("{}/{}", relative_dir, entry.relpath),
+            Some(&entry.md5),
+            format,
+        ));
+    }
+
+    for entry in WalkDir::new(data_path).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let relpath =
+            dvcfile::to_git_path(entry.path().strip_prefix(data_path).unwrap_or(entry.path()));
+        if !expected_paths.contains(&relpath) {
+            results.push(IntegrityResult {
+                path: format!("{}/{}", relative_dir, relpath),
+                status: "added".to_string(),
+                expected_md5: None,
+                actual_md5: dvcfile::hash_file_for(entry.path(), format).ok(),
+            });
+        }
+    }
+}
+
+fn verify_pointer(
+    repo_root: &Path,
+    pointer: &Path,
+    format: Option<DvcFormat>,
+    results: &mut Vec<IntegrityResult>,
+) -> Result<(), String> {
+    let dvc_file = dvcfile::read_dvc_file(pointer)?;
+    let base_dir = pointer.parent().unwrap_or(repo_root);
+
+    for out in &dvc_file.outs {
+        let data_path = base_dir.join(&out.path);
+        if out.is_dir() {
+            verify_directory(repo_root, &data_path, out, format, results);
+        } else {
+            let relative =
+                dvcfile::to_git_path(data_path.strip_prefix(repo_root).unwrap_or(&data_path));
+            results.push(verify_file(
+                &data_path,
+                relative,
+                out.md5.as_deref(),
+                format,
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Re-hashes DVC-tracked workspace data and compares it with the md5s recorded
+/// in the `.dvc` pointers. An empty `paths` list verifies every tracked output.
+#[command]
+pub fn verify_data_integrity(
+    repo_path: String,
+    paths: Vec<String>,
+) -> Result<IntegrityReport, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+
+    let pointers: Vec<PathBuf> = if paths.is_empty() {
+        dvcfile::find_pointer_files(&repo_root)
+    } else {
+        paths
+            .iter()
+            .map(|p| {
+                let relative = dvcfile::repo_relative_path(&repo_root, Path::new(p))?;
+                let absolute = repo_root.join(relative);
+                if absolute.extension().and_then(|e| e.to_str()) == Some("dvc") {
+                    Ok(absolute)
+                } else {
+                    Ok(dvcfile::pointer_path(&absolute))
+                }
+            })
+            .collect::<Result<_, String>>()?
+    };
+
+    // DVC 2 repositories hash text files with normalized line endings
+    let format = dvc_compat::repo_format(&repo_root);
+    let mut results = Vec::new();
+    for pointer in &pointers {
+        if !pointer.exists() {
+            return Err(format!("{} is not tracked by DVC", pointer.display()));
+        }
+        verify_pointer(&repo_root, pointer, format, &mut results)?;
+    }
+
+    let ok = results.iter().filter(|r| r.status == "ok").count();
+    Ok(IntegrityReport {
+        checked: results.len(),
+        ok,
+        problems: results.into_iter().filter(|r| r.status != "ok").collect(),
+    })
+}
@@ -0,0 +1,300 @@
+use chrono::{TimeZone, Utc};
+use git2::{Repository, Sort};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::command;
+use tracing::{instrument, warn};
+
+use crate::storage::{self, RemoteStorage, TransferConfig};
+use crate::{dvc_config, dvcfile, transfer};
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InventoryFormat {
+    Csv,
+    Json,
+    Markdown,
+}
+
+/// One DVC-tracked file or directory
+#[derive(Debug, Serialize)]
+pub struct DatasetEntry {
+    /// Data path relative to the repository root
+    pub path: String,
+    pub pointer: String,
+    pub is_directory: bool,
+    /// DVC hash (`.dir` suffix for directories)
+    pub md5: Option<String>,
+    pub size: Option<u64>,
+    pub nfiles: Option<u64>,
+    /// Last commit reachable from HEAD that changed the pointer, `None` while
+    /// it is uncommitted
+    pub last_commit: Option<String>,
+    pub last_author: Option<String>,
+    /// RFC 3339 time of the last commit
+    pub last_modified: Option<String>,
+    pub in_local_cache: bool,
+    /// Whether the default remote has the data, `None` when there is no
+    /// remote or it could not be reached
+    pub on_remote: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct Inventory<'a> {
+    repository: String,
+    generated_at: String,
+    remote: Option<String>,
+    datasets: &'a [DatasetEntry],
+}
+
+struct LastChange {
+    commit: String,
+    author: String,
+    time: String,
+}
+
+/// Newest commit from HEAD changing each pointer, found in one history walk
+fn last_changes(repo: &Repository, pointers: &[PathBuf]) -> HashMap<PathBuf, LastChange> {
+    let mut found = HashMap::new();
+    let Ok(mut revwalk) = repo.revwalk() else {
+        return found;
+    };
+    if revwalk.set_sorting(Sort::TIME).is_err() || revwalk.push_head().is_err() {
+        return found;
+    }
+
+    let blob_at = |tree: Option<&git2::Tree>, path: &Path| {
+        tree.and_then(|tree| tree.get_path(path).ok())
+            .map(|entry| entry.id())
+    };
+    for oid in revwalk.flatten() {
+        if found.len() == pointers.len() {
+            break;
+        }
+        let Ok(commit) = repo.find_commit(oid) else {
+            continue;
+        };
+        let tree = commit.tree().ok();
+        let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+        for pointer in pointers {
+            if found.contains_key(pointer) {
+                continue;
+            }
+            let current = blob_at(tree.as_ref(), pointer);
+            if current.is_some() && current != blob_at(parent_tree.as_ref(), pointer) {
+                let time = Utc
+                    .timestamp_opt(commit.time().seconds(), 0)
+                    .single()
+                    .map(|time| time.to_rfc3339())
+                    .unwrap_or_default();
+                found.insert(
+                    pointer.clone(),
+                    LastChange {
+                        commit: commit.id().to_string(),
+                        author: commit.author().name().unwrap_or_default().to_string(),
+                        time,
+                    },
+                );
+            }
+        }
+    }
+    found
+}
+
+fn on_remote(storage: &dyn RemoteStorage, md5: &str) -> Option<bool> {
+    let exists = |key: String| match storage.exists(&key) {
+        Ok(exists) => Some(exists),
+        Err(e) => {
+            warn!("Failed to check {} on the remote: {}", key, e);
+            None
+        }
+    };
+    if exists(storage::object_key(md5))? {
+        return Some(true);
+    }
+    exists(storage::legacy_object_key(md5))
+}
+
+fn collect(repo_root: &Path) -> Result<(Vec<DatasetEntry>, Option<String>), String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let cache_dir = dvcfile::cache_dir(repo_root);
+
+    let remote = dvc_config::remote(repo_root, None).ok();
+    let storage = remote.as_ref().and_then(|remote| {
+        storage::open_remote(remote, &TransferConfig::default())
+            .map_err(|e| warn!("Failed to open remote {}: {}", remote.name, e))
+            .ok()
+    });
+
+    let pointers: Vec<PathBuf> = dvcfile::find_pointer_files(repo_root)
+        .into_iter()
+        .filter_map(|pointer| dvcfile::repo_relative_path(repo_root, &pointer).ok())
+        .collect();
+    let changes = last_changes(&repo, &pointers);
+
+    let mut datasets = Vec::new();
+    for pointer in &pointers {
+        let dvc_file = dvcfile::read_dvc_file(&repo_root.join(pointer))?;
+        let last_change = changes.get(pointer);
+        let dir = pointer.parent().unwrap_or(Path::new(""));
+        for out in dvc_file.outs {
+            let md5 = out.md5.as_deref();
+            datasets.push(DatasetEntry {
+                path: dvcfile::to_git_path(&dir.join(&out.path)),
+                pointer: dvcfile::to_git_path(pointer),
+                is_directory: out.is_dir(),
+                in_local_cache: md5
+                    .is_some_and(|md5| dvcfile::cache_object_path(&cache_dir, md5).exists()),
+                on_remote: match (&storage, md5) {
+                    (Some(storage), Some(md5)) => on_remote(storage.as_ref(), md5),
+                    _ => None,
+                },
+                md5: out.md5,
+                size: out.size,
+                nfiles: out.nfiles,
+                last_commit: last_change.map(|change| change.commit.clone()),
+                last_author: last_change.map(|change| change.author.clone()),
+                last_modified: last_change.map(|change| change.time.clone()),
+            });
+        }
+    }
+    datasets.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok((datasets, remote.map(|remote| remote.name)))
+}
+
+fn optional<T: ToString>(value: &Option<T>) -> String {
+    value.as_ref().map(T::to_string).unwrap_or_default()
+}
+
+fn yes_no(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "yes",
+        Some(false) => "no",
+        None => "unknown",
+    }
+}
+
+/// Cells of a dataset in column order, shared by CSV and Markdown
+fn row(dataset: &DatasetEntry) -> [String; 10] {
+    [
+        dataset.path.clone(),
+        if dataset.is_directory {
+            "directory"
+        } else {
+            "file"
+        }
+        .to_string(),
+        optional(&dataset.size),
+        optional(&dataset.nfiles),
+        optional(&dataset.md5),
+        optional(&dataset.last_commit),
+        optional(&dataset.last_author),
+        optional(&dataset.last_modified),
+        yes_no(Some(dataset.in_local_cache)).to_string(),
+        yes_no(dataset.on_remote).to_string(),
+    ]
+}
+
+const COLUMNS: [&str; 10] = [
+    "path",
+    "type",
+    "size",
+    "files",
+    "md5",
+    "last_commit",
+    "last_author",
+    "last_modified",
+    "in_local_cache",
+    "on_remote",
+];
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn to_csv(datasets: &[DatasetEntry]) -> String {
+    let mut out = COLUMNS.join(",");
+    out.push('\n');
+    for dataset in datasets {
+        let cells: Vec<String> = row(dataset).iter().map(|cell| csv_field(cell)).collect();
+        out.push_str(&cells.join(","));
+        out.push('\n');
+    }
+    out
+}
+
+fn to_markdown(
+    repository: &str,
+    generated_at: &str,
+    remote: Option<&str>,
+    datasets: &[DatasetEntry],
+) -> String {
+    let mut out = format!("# Data inventory: {}\n\n", repository);
+    out.push_str(&format!("Generated {}", generated_at));
+    match remote {
+        Some(remote) => out.push_str(&format!(", remote availability checked on `{}`", remote)),
+        None => out.push_str(", no DVC remote configured"),
+    }
+    out.push_str(&format!(
+        ".\n\n{} datasets, {} bytes in total.\n\n",
+        datasets.len(),
+        datasets.iter().filter_map(|d| d.size).sum::<u64>()
+    ));
+
+    out.push_str(&format!("| {} |\n", COLUMNS.join(" | ")));
+    out.push_str(&format!("|{}\n", "---|".repeat(COLUMNS.len())));
+    for dataset in datasets {
+        let cells: Vec<String> = row(dataset)
+            .iter()
+            .map(|cell| cell.replace('|', "\\|").replace('\n', " "))
+            .collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Report of every DVC-tracked dataset with its size, hash, last commit and
+/// whether the local cache and the default remote have it. The report is
+/// returned and, when `output_path` is given, also written there.
+#[command(async)]
+#[instrument(err(Debug))]
+pub fn export_data_inventory(
+    repo_path: String,
+    format: InventoryFormat,
+    output_path: Option<String>,
+) -> Result<String, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let (datasets, remote) = collect(&repo_root)?;
+    let repository = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| repo_root.to_string_lossy().to_string());
+    let generated_at = Utc::now().to_rfc3339();
+
+    let report = match format {
+        InventoryFormat::Csv => to_csv(&datasets),
+        InventoryFormat::Json => serde_json::to_string_pretty(&Inventory {
+            repository,
+            generated_at,
+            remote,
+            datasets: &datasets,
+        })
+        .map_err(|e| format!("Failed to serialize inventory: {}", e))?,
+        InventoryFormat::Markdown => {
+            to_markdown(&repository, &generated_at, remote.as_deref(), &datasets)
+        }
+    };
+
+    if let Some(output_path) = output_path {
+        fs::write(&output_path, &report)
+            .map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+    }
+    Ok(report)
+}
@@ -0,0 +1,338 @@
+use git2::{AttrCheckFlags, Repository};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::dvc_compat::{self, DvcFormat};
+use crate::dvcfile::{self, DvcFile, DvcOut};
+use crate::repo_manager;
+
+const POINTER_VERSION: &str = "version https://git-lfs.github.com/spec/v1";
+/// LFS pointers are tiny; anything bigger is real content
+const MAX_POINTER_SIZE: u64 = 1024;
+
+/// `oid` and `size` of a Git LFS pointer file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LfsPointer {
+    pub oid: String,
+    pub size: u64,
+}
+
+pub fn parse_pointer(content: &[u8]) -> Option<LfsPointer> {
+    let content = std::str::from_utf8(content).ok()?;
+    let mut lines = content.lines();
+    if lines.next()? != POINTER_VERSION {
+        return None;
+    }
+
+    let (mut oid, mut size) = (None, None);
+    for line in lines {
+        if let Some(value) = line.strip_prefix("oid sha256:") {
+            oid = Some(value.trim().to_string());
+        } else if let Some(value) = line.strip_prefix("size ") {
+            size = value.trim().parse().ok();
+        }
+    }
+    Some(LfsPointer {
+        oid: oid?,
+        size: size?,
+    })
+}
+
+fn read_pointer(path: &Path) -> Option<LfsPointer> {
+    let metadata = fs::metadata(path).ok()?;
+    if metadata.len() > MAX_POINTER_SIZE {
+        return None;
+    }
+    parse_pointer(&fs::read(path).ok()?)
+}
+
+/// Patterns routed through the LFS filter by the root `.gitattributes`
+pub fn lfs_patterns(repo_root: &Path) -> Vec<String> {
+    let Ok(content) = fs::read_to_string(repo_root.join(".gitattributes")) else {
+        return Vec::new();
+    };
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with('#'))
+        .filter_map(|line| {
+            let mut parts = line.split_whitespace();
+            let pattern = parts.next()?;
+            parts
+                .any(|attr| attr == "filter=lfs")
+                .then(|| pattern.to_string())
+        })
+        .collect()
+}
+
+/// Whether git routes a repository relative path through the LFS filter
+pub fn is_lfs_tracked(repo: &Repository, path: &Path) -> bool {
+    matches!(
+        repo.get_attr(path, "filter", AttrCheckFlags::default()),
+        Ok(Some("lfs"))
+    )
+}
+
+fn lfs_object_path(repo: &Repository, oid: &str) -> Option<PathBuf> {
+    if oid.len() < 5 {
+        return None;
+    }
+    Some(
+        repo.path()
+            .join("lfs")
+            .join("objects")
+            .join(&oid[..2])
+            .join(&oid[2..4])
+            .join(oid),
+    )
+}
+
+#[derive(Debug, Serialize)]
+pub struct LfsFile {
+    pub path: String,
+    pub oid: Option<String>,
+    pub size: Option<u64>,
+    /// "pointer" when only the pointer is checked out, "downloaded" when the
+    /// workspace has the content, "missing" when the file isn't in the workspace
+    pub status: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LfsStatus {
+    pub patterns: Vec<String>,
+    pub files: Vec<LfsFile>,
+}
+
+/// LFS patterns of the repository and the tracked files they cover
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn list_lfs_files(repo_path: String) -> Result<LfsStatus, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+
+    let mut files = Vec::new();
+    for entry in index.iter() {
+        let path = PathBuf::from(String::from_utf8_lossy(&entry.path).to_string());
+        if !is_lfs_tracked(&repo, &path) {
+            continue;
+        }
+
+        let workspace = repo_root.join(&path);
+        let (status, pointer) = if !workspace.exists() {
+            ("missing", None)
+        } else if let Some(pointer) = read_pointer(&workspace) {
+            ("pointer", Some(pointer))
+        } else {
+            // Smudged content; the staged blob still holds the pointer
+            let pointer = repo
+                .find_blob(entry.id)
+                .ok()
+                .and_then(|blob| parse_pointer(blob.content()));
+            ("downloaded", pointer)
+        };
+
+        files.push(LfsFile {
+            path: dvcfile::to_git_path(&path),
+            oid: pointer.as_ref().map(|p| p.oid.clone()),
+            size: pointer.map(|p| p.size),
+            status: status.to_string(),
+        });
+    }
+
+    Ok(LfsStatus {
+        patterns: lfs_patterns(&repo_root),
+        files,
+    })
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigratedFile {
+    pub path: String,
+    pub dvc_file: String,
+    pub md5: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MigrationFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LfsMigrationResult {
+    pub migrated: Vec<MigratedFile>,
+    pub failed: Vec<MigrationFailure>,
+}
+
+/// Replaces the LFS pointer in the workspace with its content from the local
+/// LFS object store, if it's still a pointer
+fn materialize(repo: &Repository, workspace: &Path) -> Result<(), String> {
+    let Some(pointer) = read_pointer(workspace) else {
+        return Ok(());
+    };
+    let object = lfs_object_path(repo, &pointer.oid)
+        .filter(|object| object.exists())
+        .ok_or_else(|| {
+            format!(
+                "LFS object {} is not downloaded; run `git lfs pull` first",
+                pointer.oid
+            )
+        })?;
+    let temp = workspace.with_extension("lfs-migrate");
+    fs::copy(&object, &temp)
+        .and_then(|_| fs::rename(&temp, workspace))
+        .map_err(|e| format!("Failed to restore LFS content: {}", e))
+}
+
+/// Copies a file's content into the DVC cache and writes its pointer,
+/// returning the pointer path relative to the repository root
+fn track_with_dvc(repo_root: &Path, relative: &Path) -> Result<(PathBuf, String, u64), String> {
+    let workspace = repo_root.join(relative);
+    let format = dvc_compat::repo_format(repo_root);
+    let md5 = dvcfile::hash_file_for(&workspace, format)?;
+    let size = fs::metadata(&workspace)
+        .map_err(|e| format!("Failed to read {}: {}", workspace.display(), e))?
+        .len();
+
+    let object = dvcfile::cache_object_path_for(&dvcfile::cache_dir(repo_root), &md5, format);
+    if !object.exists() {
+        if let Some(parent) = object.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create cache directory: {}", e))?;
+        }
+        fs::copy(&workspace, &object)
+            .map_err(|e| format!("Failed to add {} to the cache: {}", workspace.display(), e))?;
+    }
+
+    let file_name = relative
+        .file_name()
+        .ok_or("Invalid file name")?
+        .to_string_lossy()
+        .to_string();
+    let pointer = dvcfile::pointer_path(relative);
+    let dvc_file = DvcFile {
+        outs: vec![DvcOut {
+            md5: Some(md5.clone()),
+            size: Some(size),
+            // DVC 2 pointers have no hash field
+            hash: (format != Some(DvcFormat::V2)).then(|| "md5".to_string()),
+            path: file_name,
+            ..Default::default()
+        }],
+        ..Default::default()
+    };
+    dvcfile::write_dvc_file(&repo_root.join(&pointer), &dvc_file)?;
+    Ok((pointer, md5, size))
+}
+
+/// Drops `.gitattributes` lines naming exactly one of the migrated paths.
+/// Wider patterns are left alone; migrated files are git-ignored anyway.
+fn remove_lfs_attributes(repo_root: &Path, paths: &[String]) -> Result<bool, String> {
+    let attributes = repo_root.join(".gitattributes");
+    let Ok(content) = fs::read_to_string(&attributes) else {
+        return Ok(false);
+    };
+    let kept: Vec<&str> = content
+        .lines()
+        .filter(|line| {
+            let pattern = line.split_whitespace().next().unwrap_or_default();
+            let pattern = pattern.trim_start_matches('/');
+            !(line.contains("filter=lfs") && paths.iter().any(|path| path == pattern))
+        })
+        .collect();
+    if kept.len() == content.lines().count() {
+        return Ok(false);
+    }
+
+    let mut updated = kept.join("\n");
+    if !updated.is_empty() {
+        updated.push('\n');
+    }
+    fs::write(&attributes, updated)
+        .map_err(|e| format!("Failed to update .gitattributes: {}", e))?;
+    Ok(true)
+}
+
+/// Converts LFS-tracked files to DVC tracking: the content goes to the DVC
+/// cache, a `.dvc` pointer replaces the LFS pointer in git and the data is
+/// git-ignored. Changes are staged for review.
+#[command]
+#[instrument(skip(app_handle, repo_path, paths), err(Debug))]
+pub fn migrate_lfs_to_dvc(
+    app_handle: AppHandle,
+    repo_path: String,
+    paths: Vec<String>,
+) -> Result<LfsMigrationResult, String> {
+    repo_manager::write(&app_handle, &repo_path, || migrate(&repo_path, paths))
+}
+
+fn migrate(repo_path: &str, paths: Vec<String>) -> Result<LfsMigrationResult, String> {
+    let repo =
+        Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+
+    let mut result = LfsMigrationResult {
+        migrated: Vec::new(),
+        failed: Vec::new(),
+    };
+    for path in paths {
+        let migrated =
+            dvcfile::repo_relative_path(&repo_root, Path::new(&path)).and_then(|relative| {
+                if !is_lfs_tracked(&repo, &relative) {
+                    return Err(format!("{} is not tracked with Git LFS", path));
+                }
+                materialize(&repo, &repo_root.join(&relative))?;
+                let (pointer, md5, size) = track_with_dvc(&repo_root, &relative)?;
+                let gitignore = dvcfile::add_gitignore_entry(&repo_root.join(&relative))?;
+
+                index
+                    .remove_path(&relative)
+                    .and_then(|_| index.add_path(&pointer))
+                    .map_err(|e| format!("Failed to stage {}: {}", pointer.display(), e))?;
+                let gitignore = dvcfile::repo_relative_path(&repo_root, &gitignore)?;
+                index
+                    .add_path(&gitignore)
+                    .map_err(|e| format!("Failed to stage {}: {}", gitignore.display(), e))?;
+
+                Ok(MigratedFile {
+                    path: dvcfile::to_git_path(&relative),
+                    dvc_file: dvcfile::to_git_path(&pointer),
+                    md5,
+                    size,
+                })
+            });
+        match migrated {
+            Ok(file) => result.migrated.push(file),
+            Err(error) => result.failed.push(MigrationFailure { path, error }),
+        }
+    }
+
+    let migrated_paths: Vec<String> = result.migrated.iter().map(|f| f.path.clone()).collect();
+    if remove_lfs_attributes(&repo_root, &migrated_paths)? {
+        index
+            .add_path(Path::new(".gitattributes"))
+            .map_err(|e| format!("Failed to stage .gitattributes: {}", e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    Ok(result)
+}
@@ -1,55 +1,305 @@
-use tauri_plugin_sql::{Migration, MigrationKind};
-
+mod audit;
+mod auto_fetch;
+mod branch_compare;
+mod cache_link;
+mod checkout;
+mod commit_message;
+mod conflicts;
+mod crash;
+mod credentials;
+mod data_commit;
+mod db;
+mod dedup;
+mod diff;
 mod dvc;
+mod dvc_compat;
+mod dvc_config;
+mod dvc_merge;
+mod dvc_undo;
+mod dvcfile;
+mod env;
+mod events;
+mod experiments;
 mod file;
+mod file_type;
+mod forge;
 mod git;
+mod health;
+mod history;
+mod hooks;
+mod identity;
+mod image_diff;
+mod import;
+mod index;
+mod integrity;
+mod inventory;
+mod lfs;
+mod locks;
+mod logging;
+mod metadata;
+mod metrics;
+mod models;
+mod notebook;
+mod notifications;
+mod offline;
+mod onboarding;
+mod ownership;
+mod pins;
+mod project_command;
+mod readonly;
+mod rebase;
+mod remote;
+mod remote_status;
+mod repo_manager;
+mod script_locator;
+mod search;
+mod sensitive;
+mod settings;
+mod shared_cache;
+mod signing;
+mod size_history;
+mod snapshot;
+mod sparse;
+mod ssh;
 mod state;
+mod statistics;
+mod storage;
+mod submodule;
+mod tabular;
+mod templates;
+#[cfg(any(test, feature = "test-support"))]
+pub mod test_support;
+mod throttle;
+mod transfer;
+mod transfer_queue;
+mod trash;
+mod watchdog;
+mod worktree;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
-    let migrations = vec![
-        Migration {
-            version: 1,
-            description: "create_initial_tables",
-            sql: include_str!("migrations/001_initial_schema.sql"),
-            kind: MigrationKind::Up,
-        },
-        Migration {
-            version: 2,
-            description: "create_current_project_table",
-            sql: include_str!("migrations/002_current_project_state.sql"),
-            kind: MigrationKind::Up,
-        },
-    ];
-
-    tauri::Builder::default()
+    let result = tauri::Builder::default()
         .plugin(tauri_plugin_fs::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(
             tauri_plugin_sql::Builder::default()
-                .add_migrations("sqlite:fenn.db", migrations)
+                .add_migrations("sqlite:fenn.db", db::migrations())
                 .build(),
         )
+        .setup(|app| {
+            logging::init(app.handle())?;
+            crash::install_panic_hook(app.handle())?;
+            // Background fetching is optional, so a failure doesn't stop the app
+            if let Err(e) = auto_fetch::start_all(app.handle()) {
+                tracing::warn!("Failed to start auto-fetch: {}", e);
+            }
+            offline::start(app.handle());
+            Ok(())
+        })
         .manage(state::SelectedFilesState::new(state::SelectedFiles::new()))
-        .invoke_handler(tauri::generate_handler![
+        .manage(state::TreeCacheState::new(state::TreeCache::new()))
+        .manage(index::FileIndexState::new(index::FileIndex::new()))
+        .manage(db::DatabaseState::new())
+        .manage(ssh::SshPassphraseState::new(ssh::SshPassphrases::new()))
+        .manage(throttle::TransferThrottleState::new(
+            throttle::TransferThrottles::new(),
+        ))
+        .manage(auto_fetch::AutoFetchState::new(
+            auto_fetch::AutoFetchScheduler::new(),
+        ))
+        .manage(repo_manager::RepoManagerState::new())
+        .manage(readonly::ReadOnlyState::new())
+        .manage(statistics::StatisticsCacheState::new(
+            statistics::StatisticsCache::new(),
+        ))
+        // Refuses commands that would modify a project opened read-only
+        .invoke_handler(readonly::guard(tauri::generate_handler![
             file::get_file_tree_structure,
+            file::set_tree_settings,
+            file::preview_ignore_effect,
             file::get_file_binary,
+            file_type::detect_file_type,
+            tabular::inspect_tabular_schema,
+            tabular::diff_tabular_versions,
+            image_diff::diff_image_directory,
+            notebook::diff_notebook,
             dvc::init_dvc_project,
+            templates::list_project_templates,
+            templates::save_project_template,
+            templates::delete_project_template,
             file::add_selected_file,
             file::remove_selected_file,
             file::get_selected_files,
             file::clear_selected_files,
+            file::get_selection_states,
+            file::get_expanded_selection,
             file::get_files_status,
+            file::get_selected_files_status,
             dvc::add_dvc_file,
+            dvc::add_dvc_files,
+            dvc_undo::undo_last_dvc_add,
+            commit_message::suggest_commit_message,
+            commit_message::list_commit_templates,
+            commit_message::save_commit_template,
+            commit_message::delete_commit_template,
+            dvc::remove_dvc_file,
+            dvc::move_tracked_file,
+            trash::delete_tracked_file,
+            trash::list_trash,
+            trash::restore_from_trash,
+            trash::empty_trash,
+            dvc_compat::get_dvc_version,
             git::git_status,
+            git::git_add_files,
+            git::git_reset_files,
+            git::git_file_diff,
             git::git_commit_and_push,
+            identity::get_git_identity,
+            identity::set_git_identity,
+            identity::check_git_configuration,
+            identity::bootstrap_git_config,
+            data_commit::commit_data_changes,
+            signing::get_commit_signing,
+            signing::set_commit_signing,
+            signing::list_signing_keys,
+            signing::verify_commit_signature,
             git::git_pull,
+            git::set_pull_strategy,
             git::git_checkout,
             git::git_stash,
             git::git_list_branches,
             git::git_current_branch,
             git::git_switch_branch,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            git::git_checkout_commit,
+            git::git_checkout_tag,
+            diff::git_diff_stats,
+            snapshot::create_snapshot,
+            snapshot::list_snapshots,
+            snapshot::restore_snapshot,
+            branch_compare::compare_branches_data,
+            integrity::verify_data_integrity,
+            dedup::find_duplicate_files,
+            search::search_files,
+            index::refresh_file_index,
+            index::close_file_index,
+            remote::git_clone,
+            remote::git_fetch,
+            remote::git_fetch_deepen,
+            remote::git_push,
+            sparse::get_sparse_checkout_patterns,
+            sparse::set_sparse_checkout_patterns,
+            submodule::git_list_submodules,
+            submodule::git_submodule_update,
+            worktree::git_worktree_add,
+            worktree::git_worktree_list,
+            worktree::git_worktree_remove,
+            project_command::run_project_command,
+            env::detect_python_environments,
+            env::create_dvc_environment,
+            env::get_dvc_interpreter,
+            env::set_dvc_interpreter,
+            script_locator::diagnose_dvc_scripts,
+            script_locator::set_dvc_scripts_override,
+            auto_fetch::set_auto_fetch,
+            auto_fetch::check_for_remote_changes,
+            ssh::generate_ssh_key,
+            ssh::list_ssh_keys,
+            ssh::get_public_key,
+            ssh::unlock_ssh_key,
+            forge::create_remote_repository,
+            forge::create_pull_request,
+            forge::list_pull_requests,
+            transfer::dvc_push,
+            transfer::dvc_pull,
+            transfer::dvc_pull_subpath,
+            transfer::prefetch_branch_data,
+            pins::pin_data_version,
+            pins::unpin_data_version,
+            pins::list_data_pins,
+            pins::gc_cache,
+            remote_status::check_remote_status,
+            remote_status::estimate_push,
+            credentials::set_remote_credentials,
+            transfer_queue::list_pending_transfers,
+            transfer_queue::resume_transfers,
+            transfer_queue::clear_transfer_queue,
+            offline::get_pending_sync_operations,
+            offline::cancel_sync_operation,
+            settings::get_project_settings,
+            transfer::set_transfer_limits,
+            cache_link::get_cache_link_settings,
+            cache_link::set_cache_link_type,
+            shared_cache::set_shared_cache,
+            locks::lock_dataset,
+            locks::unlock_dataset,
+            locks::list_locks,
+            history::file_history,
+            history::get_path_owners,
+            size_history::get_size_history,
+            size_history::set_size_alerts,
+            history::restore_file_version,
+            git::git_revert_commit,
+            conflicts::list_conflicts,
+            conflicts::detect_upstream_conflicts,
+            dvc_merge::list_dvc_conflicts,
+            dvc_merge::resolve_dvc_conflict,
+            git::git_cherry_pick,
+            rebase::git_rebase_onto,
+            rebase::get_rebase_plan,
+            rebase::execute_rebase_plan,
+            rebase::git_rebase_continue,
+            rebase::git_rebase_abort,
+            hooks::list_hooks,
+            hooks::set_hook_enabled,
+            sensitive::scan_for_sensitive_data,
+            sensitive::scan_staged_secrets,
+            lfs::list_lfs_files,
+            lfs::migrate_lfs_to_dvc,
+            health::check_repository_health,
+            health::repair_stale_locks,
+            health::repair_git_index,
+            health::repair_dvc_config,
+            health::repair_cache,
+            health::repair_missing_data,
+            repo_manager::get_repository_lock_status,
+            repo_manager::clear_repository_locks,
+            onboarding::analyze_directory,
+            onboarding::adopt_existing_project,
+            audit::get_activity_log,
+            metrics::get_performance_stats,
+            metrics::set_performance_stats_enabled,
+            metrics::clear_performance_stats,
+            inventory::export_data_inventory,
+            metadata::set_dataset_metadata,
+            metadata::query_datasets_by_metadata,
+            models::register_model,
+            models::list_models,
+            models::promote_model,
+            models::get_model_lineage,
+            experiments::export_experiment_report,
+            statistics::get_repo_statistics,
+            notifications::set_notifications,
+            notifications::test_notification,
+            import::import_external_data,
+            import::download_and_track,
+            crash::list_crash_reports,
+            crash::get_crash_report,
+            crash::submit_crash_report,
+            crash::delete_crash_report,
+            logging::get_app_logs,
+            logging::export_logs,
+            readonly::open_project_readonly,
+            readonly::close_readonly_project,
+            readonly::is_project_readonly,
+            ownership::check_repository_access,
+            ownership::trust_repository,
+            watchdog::set_watchdog_settings,
+        ]))
+        .run(tauri::generate_context!());
+
+    if let Err(e) = result {
+        crash::record_fatal_error(format!("error while running tauri application: {}", e));
+        eprintln!("error while running tauri application: {}", e);
+        std::process::exit(1);
+    }
 }
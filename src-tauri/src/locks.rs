@@ -0,0 +1,292 @@
+use git2::{Index, IndexEntry, IndexTime, Repository, Signature};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{audit, dvcfile, identity, repo_manager};
+
+/// Lock records are committed under this directory so they travel with the
+/// repository and teammates see them after a pull
+const LOCKS_DIR: &str = ".fenn/locks";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DatasetLock {
+    /// Locked file or directory, relative to the repository root
+    pub path: String,
+    pub owner_name: String,
+    pub owner_email: String,
+    pub message: String,
+    /// RFC 3339 time the lock was taken
+    pub locked_at: String,
+    /// Whether the lock belongs to the current git user
+    #[serde(skip_deserializing, default)]
+    pub owned_by_me: bool,
+}
+
+fn open_repo(repo_path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo =
+        Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    Ok((repo, repo_root))
+}
+
+/// Path of a dataset relative to the repository root, accepting either an
+/// absolute or an already relative path
+fn relative_path(repo_root: &Path, path: &str) -> String {
+    let path = Path::new(path);
+    dvcfile::to_git_path(path.strip_prefix(repo_root).unwrap_or(path))
+}
+
+/// Git path of the record for a dataset, named by a hash of its path so nested
+/// datasets don't need nested directories
+fn record_path(dataset: &str) -> String {
+    let hash = hex::encode(Sha256::digest(dataset.as_bytes()));
+    format!("{}/{}.json", LOCKS_DIR, &hash[..16])
+}
+
+fn signature(repo: &Repository) -> Result<Signature<'static>, String> {
+    identity::signature(repo)
+}
+
+fn read_locks(repo_root: &Path, me: Option<&Signature>) -> Result<Vec<DatasetLock>, String> {
+    let dir = repo_root.join(LOCKS_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut locks = Vec::new();
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read lock directory: {}", e))? {
+        let path = entry
+            .map_err(|e| format!("Failed to read directory entry: {}", e))?
+            .path();
+        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+            continue;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let mut lock: DatasetLock = serde_json::from_str(&content)
+            .map_err(|e| format!("Failed to parse lock {}: {}", path.display(), e))?;
+        lock.owned_by_me = me.is_some_and(|me| me.email() == Some(lock.owner_email.as_str()));
+        locks.push(lock);
+    }
+
+    locks.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(locks)
+}
+
+/// Commits a single lock record change on top of HEAD without touching other
+/// staged changes, then brings the index entry in line with the commit
+fn commit_record(
+    repo: &Repository,
+    record: &str,
+    content: Option<&[u8]>,
+    message: &str,
+) -> Result<(), String> {
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+    let head_tree = head
+        .tree()
+        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+
+    let mut tree_index = Index::new().map_err(|e| format!("Failed to create index: {}", e))?;
+    tree_index
+        .read_tree(&head_tree)
+        .map_err(|e| format!("Failed to read HEAD tree: {}", e))?;
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    match content {
+        Some(content) => {
+            let entry = IndexEntry {
+                ctime: IndexTime::new(0, 0),
+                mtime: IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: 0o100644,
+                uid: 0,
+                gid: 0,
+                file_size: content.len() as u32,
+                id: git2::Oid::zero(),
+                flags: 0,
+                flags_extended: 0,
+                path: record.as_bytes().to_vec(),
+            };
+            tree_index
+                .add_frombuffer(&entry, content)
+                .map_err(|e| format!("Failed to stage lock record: {}", e))?;
+            index
+                .add_path(Path::new(record))
+                .map_err(|e| format!("Failed to stage lock record: {}", e))?;
+        }
+        None => {
+            tree_index
+                .remove_path(Path::new(record))
+                .map_err(|e| format!("Failed to remove lock record: {}", e))?;
+            // The record may never have been staged in the working index
+            let _ = index.remove_path(Path::new(record));
+        }
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    let tree_id = tree_index
+        .write_tree_to(repo)
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+    let tree = repo
+        .find_tree(tree_id)
+        .map_err(|e| format!("Failed to find tree: {}", e))?;
+    let signature = signature(repo)?;
+    repo.commit(
+        Some("HEAD"),
+        &signature,
+        &signature,
+        message,
+        &tree,
+        &[&head],
+    )
+    .map_err(|e| format!("Failed to commit lock record: {}", e))?;
+    Ok(())
+}
+
+fn lock(repo_path: &str, path: &str, message: &str) -> Result<DatasetLock, String> {
+    let (repo, repo_root) = open_repo(repo_path)?;
+    let dataset = relative_path(&repo_root, path);
+    if !repo_root.join(&dataset).exists() {
+        return Err(format!("{} does not exist", dataset));
+    }
+    let me = signature(&repo)?;
+
+    if let Some(existing) = read_locks(&repo_root, Some(&me))?
+        .into_iter()
+        .find(|lock| lock.path == dataset)
+    {
+        return Err(if existing.owned_by_me {
+            format!("You already hold the lock on {}", dataset)
+        } else {
+            format!(
+                "{} is locked by {} since {}: {}",
+                dataset, existing.owner_name, existing.locked_at, existing.message
+            )
+        });
+    }
+
+    let lock = DatasetLock {
+        path: dataset.clone(),
+        owner_name: me.name().unwrap_or_default().to_string(),
+        owner_email: me.email().unwrap_or_default().to_string(),
+        message: message.to_string(),
+        locked_at: chrono::Utc::now().to_rfc3339(),
+        owned_by_me: true,
+    };
+    // `owned_by_me` depends on who reads the record, so it is not stored
+    let mut record_value =
+        serde_json::to_value(&lock).map_err(|e| format!("Failed to serialize lock: {}", e))?;
+    if let Some(fields) = record_value.as_object_mut() {
+        fields.remove("owned_by_me");
+    }
+    let content = serde_json::to_string_pretty(&record_value)
+        .map_err(|e| format!("Failed to serialize lock: {}", e))?;
+
+    let record = record_path(&dataset);
+    let file = repo_root.join(&record);
+    if let Some(parent) = file.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create lock directory: {}", e))?;
+    }
+    fs::write(&file, &content).map_err(|e| format!("Failed to write lock record: {}", e))?;
+
+    if let Err(e) = commit_record(
+        &repo,
+        &record,
+        Some(content.as_bytes()),
+        &format!("Lock {}", dataset),
+    ) {
+        let _ = fs::remove_file(&file);
+        return Err(e);
+    }
+    Ok(lock)
+}
+
+fn unlock(repo_path: &str, path: &str, force: bool) -> Result<DatasetLock, String> {
+    let (repo, repo_root) = open_repo(repo_path)?;
+    let dataset = relative_path(&repo_root, path);
+    let me = signature(&repo)?;
+
+    let lock = read_locks(&repo_root, Some(&me))?
+        .into_iter()
+        .find(|lock| lock.path == dataset)
+        .ok_or_else(|| format!("{} is not locked", dataset))?;
+    if !lock.owned_by_me && !force {
+        return Err(format!(
+            "{} is locked by {}; force the unlock to release someone else's lock",
+            dataset, lock.owner_name
+        ));
+    }
+
+    let record = record_path(&dataset);
+    commit_record(&repo, &record, None, &format!("Unlock {}", dataset))?;
+    fs::remove_file(repo_root.join(&record))
+        .map_err(|e| format!("Failed to remove lock record: {}", e))?;
+    Ok(lock)
+}
+
+/// Marks a dataset as being modified so teammates don't make conflicting
+/// changes. The lock is a committed record that is shared on the next push.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn lock_dataset(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    message: String,
+) -> Result<DatasetLock, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "lock_dataset",
+        json!({ "path": path, "message": message }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                lock(&repo_path, &path, &message)
+            })
+        },
+    )
+}
+
+/// Releases a dataset lock. Locks held by someone else are only released
+/// when `force` is set.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn unlock_dataset(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    force: bool,
+) -> Result<DatasetLock, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "unlock_dataset",
+        json!({ "path": path, "force": force }),
+        || repo_manager::write(&app_handle, &repo_path, || unlock(&repo_path, &path, force)),
+    )
+}
+
+#[command]
+#[instrument(err(Debug))]
+pub fn list_locks(repo_path: String) -> Result<Vec<DatasetLock>, String> {
+    let (repo, repo_root) = open_repo(&repo_path)?;
+    let me = repo.signature().ok();
+    read_locks(&repo_root, me.as_ref())
+}
@@ -0,0 +1,198 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use tauri::command;
+use tauri::AppHandle;
+use tauri::Manager;
+use tracing::{instrument, Level};
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::LevelFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+use crate::crash;
+
+const LOG_FILE_PREFIX: &str = "fenn";
+const LOG_FILE_SUFFIX: &str = "log";
+/// Number of daily log files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Keeps the background writer alive so buffered lines are flushed on exit
+static WRITER_GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+#[derive(Debug, Serialize)]
+pub struct LogEntry {
+    /// RFC 3339 time the event was recorded
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+    /// Remaining event fields, e.g. paths or operation ids
+    pub fields: Value,
+    /// Names of the spans the event happened in, outermost first
+    pub spans: Vec<String>,
+}
+
+fn log_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("logs");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create log directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Installs the global subscriber: human-readable output on stdout and JSON
+/// lines in a daily rotating file under app data
+pub fn init(app_handle: &AppHandle) -> Result<(), String> {
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix(LOG_FILE_PREFIX)
+        .filename_suffix(LOG_FILE_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(log_dir(app_handle)?)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+    let _ = WRITER_GUARD.set(guard);
+
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::fmt::layer())
+        .with(
+            tracing_subscriber::fmt::layer()
+                .json()
+                .with_current_span(false)
+                .with_span_list(true)
+                .with_writer(writer)
+                .with_filter(LevelFilter::DEBUG),
+        )
+        .with(crash::RecentEventsLayer)
+        .try_init()
+        .map_err(|e| format!("Failed to install log subscriber: {}", e))
+}
+
+/// Log files oldest first. Rotated files are named `fenn.<date>.log`, so
+/// name order is chronological.
+fn log_files(app_handle: &AppHandle) -> Result<Vec<PathBuf>, String> {
+    let dir = log_dir(app_handle)?;
+    let mut files = fs::read_dir(&dir)
+        .map_err(|e| format!("Failed to read log directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(LOG_FILE_PREFIX) && n.ends_with(LOG_FILE_SUFFIX))
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+fn parse_entry(line: &str) -> Option<LogEntry> {
+    let mut value: Value = serde_json::from_str(line).ok()?;
+    let mut fields = value.get_mut("fields").map(Value::take).unwrap_or_default();
+    let message = fields
+        .as_object_mut()
+        .and_then(|f| f.remove("message"))
+        .and_then(|m| m.as_str().map(str::to_string))
+        .unwrap_or_default();
+    let spans = value
+        .get("spans")
+        .and_then(Value::as_array)
+        .map(|spans| {
+            spans
+                .iter()
+                .filter_map(|s| s.get("name").and_then(Value::as_str).map(str::to_string))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(LogEntry {
+        timestamp: value.get("timestamp")?.as_str()?.to_string(),
+        level: value.get("level")?.as_str()?.to_string(),
+        target: value
+            .get("target")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string(),
+        message,
+        fields,
+        spans,
+    })
+}
+
+/// Recent log events, newest first. `level` keeps events at least that
+/// severe ("error", "warn", "info" or "debug"); `since` is an RFC 3339 time.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_app_logs(
+    app_handle: AppHandle,
+    level: Option<String>,
+    since: Option<String>,
+) -> Result<Vec<LogEntry>, String> {
+    let min_level = level
+        .map(|l| {
+            l.parse::<Level>()
+                .map_err(|_| format!("Unknown log level: {}", l))
+        })
+        .transpose()?;
+    let since = since
+        .map(|s| {
+            chrono::DateTime::parse_from_rfc3339(&s)
+                .map_err(|e| format!("Invalid timestamp {}: {}", s, e))
+        })
+        .transpose()?;
+
+    let mut entries = Vec::new();
+    for path in log_files(&app_handle)? {
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        // Skip lines that are not valid entries, e.g. one cut off mid-write
+        entries.extend(content.lines().filter_map(parse_entry).filter(|entry| {
+            let level_ok = min_level.map_or(true, |min| {
+                entry.level.parse::<Level>().is_ok_and(|level| level <= min)
+            });
+            let time_ok = since.map_or(true, |since| {
+                chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+                    .is_ok_and(|time| time >= since)
+            });
+            level_ok && time_ok
+        }));
+    }
+
+    entries.reverse();
+    Ok(entries)
+}
+
+/// Concatenates all retained log files into one file under app data that can
+/// be attached to a bug report, and returns its path
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn export_logs(app_handle: AppHandle) -> Result<String, String> {
+    let export_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("log-exports");
+    fs::create_dir_all(&export_dir)
+        .map_err(|e| format!("Failed to create export directory: {}", e))?;
+
+    let mut content = String::new();
+    for path in log_files(&app_handle)? {
+        content.push_str(
+            &fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        );
+    }
+
+    let export_path = export_dir.join(format!(
+        "fenn-logs-{}.log",
+        chrono::Utc::now().format("%Y%m%d%H%M%S")
+    ));
+    fs::write(&export_path, content).map_err(|e| format!("Failed to write log export: {}", e))?;
+    Ok(export_path.to_string_lossy().to_string())
+}
@@ -0,0 +1,207 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::db::{self, PooledConnection};
+use crate::{audit, dvcfile, settings, transfer};
+
+/// Key of the labels in a `.dvc` pointer, as DVC documents it
+const POINTER_META_KEY: &str = "meta";
+
+/// Labels of one DVC-tracked dataset
+#[derive(Debug, Serialize)]
+pub struct DatasetMetadata {
+    /// Data path relative to the repository root
+    pub path: String,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A label a dataset must have; without `value` any value matches
+#[derive(Debug, Deserialize)]
+pub struct MetadataCondition {
+    pub key: String,
+    /// Compared case-insensitively
+    pub value: Option<String>,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::DATASET_METADATA_SCHEMA)
+}
+
+fn yaml_string(value: &YamlValue) -> Option<String> {
+    match value {
+        YamlValue::String(value) => Some(value.clone()),
+        YamlValue::Bool(value) => Some(value.to_string()),
+        YamlValue::Number(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Labels in the `meta:` section of a pointer
+fn pointer_metadata(pointer: &Path) -> BTreeMap<String, String> {
+    let Ok(dvc_file) = dvcfile::read_dvc_file(pointer) else {
+        return BTreeMap::new();
+    };
+    let Some(YamlValue::Mapping(meta)) = dvc_file.extra.get(POINTER_META_KEY) else {
+        return BTreeMap::new();
+    };
+    meta.iter()
+        .filter_map(|(key, value)| Some((yaml_string(key)?, yaml_string(value)?)))
+        .collect()
+}
+
+/// Sets or, without a value, removes a label in a pointer's `meta:` section,
+/// dropping the section once it is empty
+fn write_pointer_metadata(pointer: &Path, key: &str, value: Option<&str>) -> Result<(), String> {
+    let mut dvc_file = dvcfile::read_dvc_file(pointer)?;
+    let mut meta = match dvc_file.extra.remove(POINTER_META_KEY) {
+        Some(YamlValue::Mapping(meta)) => meta,
+        _ => Mapping::new(),
+    };
+    match value {
+        Some(value) => meta.insert(key.into(), value.into()),
+        None => meta.remove(key),
+    };
+    if !meta.is_empty() {
+        dvc_file
+            .extra
+            .insert(POINTER_META_KEY.into(), YamlValue::Mapping(meta));
+    }
+    dvcfile::write_dvc_file(pointer, &dvc_file)
+}
+
+/// Labels saved in the app for each dataset of a repository
+fn stored_metadata(
+    conn: &Connection,
+    project: &str,
+) -> Result<BTreeMap<String, BTreeMap<String, String>>, String> {
+    let mut stmt = conn
+        .prepare("SELECT path, key, value FROM dataset_metadata WHERE project = ?1")
+        .map_err(|e| format!("Failed to prepare metadata query: {}", e))?;
+    let rows = stmt
+        .query_map(params![project], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })
+        .map_err(|e| format!("Failed to query metadata: {}", e))?;
+
+    let mut metadata: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for row in rows {
+        let (path, key, value): (String, String, String) =
+            row.map_err(|e| format!("Failed to read metadata row: {}", e))?;
+        metadata.entry(path).or_default().insert(key, value);
+    }
+    Ok(metadata)
+}
+
+/// Labels of every dataset: those in pointers, which travel with the
+/// repository, overridden by ones saved in the app
+fn all_metadata(app_handle: &AppHandle, repo_root: &Path) -> Result<Vec<DatasetMetadata>, String> {
+    let conn = open_connection(app_handle)?;
+    let mut stored = stored_metadata(&conn, &settings::project_key(repo_root))?;
+
+    let mut datasets = Vec::new();
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        let data_path = pointer.with_extension("");
+        let path = dvcfile::to_git_path(&dvcfile::repo_relative_path(repo_root, &data_path)?);
+        let mut metadata = pointer_metadata(&pointer);
+        metadata.extend(stored.remove(&path).unwrap_or_default());
+        datasets.push(DatasetMetadata { path, metadata });
+    }
+    datasets.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(datasets)
+}
+
+fn matches(dataset: &DatasetMetadata, filter: &[MetadataCondition]) -> bool {
+    filter.iter().all(|condition| {
+        dataset.metadata.get(&condition.key).is_some_and(|value| {
+            condition
+                .value
+                .as_ref()
+                .is_none_or(|expected| expected.eq_ignore_ascii_case(value))
+        })
+    })
+}
+
+/// Labels a DVC-tracked dataset, e.g. with its owner, license or whether it
+/// holds personal data; removes the label without `value`. With
+/// `write_to_pointer` the change is also made in the `meta:` section of the
+/// `.dvc` file, so it is shared through git.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_dataset_metadata(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    key: String,
+    value: Option<String>,
+    write_to_pointer: Option<bool>,
+) -> Result<DatasetMetadata, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "set_dataset_metadata",
+        json!({ "path": path, "key": key, "value": value }),
+        || {
+            let key = key.trim();
+            if key.is_empty() {
+                return Err("Metadata key cannot be empty".to_string());
+            }
+            let repo_root = transfer::repo_root(&repo_path)?;
+            let relative = dvcfile::repo_relative_path(&repo_root, Path::new(&path))?;
+            let pointer = dvcfile::pointer_path(&repo_root.join(&relative));
+            if !pointer.exists() {
+                return Err(format!("{} is not tracked with DVC", path));
+            }
+            let relative = dvcfile::to_git_path(&relative);
+
+            let conn = open_connection(&app_handle)?;
+            let project = settings::project_key(&repo_root);
+            match &value {
+                Some(value) => conn.execute(
+                    "INSERT OR REPLACE INTO dataset_metadata (project, path, key, value, updated_at)
+                     VALUES (?1, ?2, ?3, ?4, CURRENT_TIMESTAMP)",
+                    params![project, relative, key, value],
+                ),
+                None => conn.execute(
+                    "DELETE FROM dataset_metadata WHERE project = ?1 AND path = ?2 AND key = ?3",
+                    params![project, relative, key],
+                ),
+            }
+            .map_err(|e| format!("Failed to save metadata: {}", e))?;
+            if write_to_pointer.unwrap_or(false) {
+                write_pointer_metadata(&pointer, key, value.as_deref())?;
+            }
+
+            let mut metadata = pointer_metadata(&pointer);
+            metadata.extend(
+                stored_metadata(&conn, &project)?
+                    .remove(&relative)
+                    .unwrap_or_default(),
+            );
+            Ok(DatasetMetadata {
+                path: relative,
+                metadata,
+            })
+        },
+    )
+}
+
+/// Datasets with every label of `filter`; all datasets with an empty filter
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn query_datasets_by_metadata(
+    app_handle: AppHandle,
+    repo_path: String,
+    filter: Vec<MetadataCondition>,
+) -> Result<Vec<DatasetMetadata>, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    Ok(all_metadata(&app_handle, &repo_root)?
+        .into_iter()
+        .filter(|dataset| matches(dataset, &filter))
+        .collect())
+}
@@ -0,0 +1,212 @@
+use rusqlite::{params_from_iter, types::Value as SqlValue};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tauri::{command, AppHandle, Manager};
+use tracing::{instrument, warn};
+
+use crate::db::{self, PooledConnection};
+use crate::{settings, transfer};
+
+/// Whether metrics are recorded; they never leave the machine either way
+const METRICS_SETTINGS_FILE: &str = "metrics-settings.json";
+
+const METRICS_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS operation_metrics (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    project TEXT NOT NULL,
+    operation TEXT NOT NULL,
+    duration_ms INTEGER NOT NULL,
+    items INTEGER,
+    bytes INTEGER,
+    recorded_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_operation_metrics_operation ON operation_metrics(operation, recorded_at);
+";
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct MetricsSettings {
+    enabled: bool,
+}
+
+/// Timings of one kind of operation, e.g. "tree_scan" or "dvc_push"
+#[derive(Debug, Serialize)]
+pub struct OperationStats {
+    pub operation: String,
+    pub count: i64,
+    pub avg_duration_ms: f64,
+    pub min_duration_ms: i64,
+    pub max_duration_ms: i64,
+    pub last_duration_ms: i64,
+    /// Files scanned or objects transferred, summed over all runs
+    pub total_items: i64,
+    pub total_bytes: i64,
+    /// Bytes per second over the runs that moved data
+    pub throughput_bytes_per_sec: Option<f64>,
+    /// RFC 3339 time of the latest run
+    pub last_recorded_at: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PerformanceStats {
+    pub enabled: bool,
+    pub operations: Vec<OperationStats>,
+}
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    Ok(dir.join(METRICS_SETTINGS_FILE))
+}
+
+fn is_enabled(app_handle: &AppHandle) -> bool {
+    settings_path(app_handle)
+        .ok()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|content| serde_json::from_str::<MetricsSettings>(&content).ok())
+        .is_some_and(|settings| settings.enabled)
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, METRICS_SCHEMA)
+}
+
+fn insert(
+    app_handle: &AppHandle,
+    project: &Path,
+    operation: &str,
+    duration: Duration,
+    items: Option<u64>,
+    bytes: Option<u64>,
+) -> Result<(), String> {
+    let conn = open_connection(app_handle)?;
+    conn.execute(
+        "INSERT INTO operation_metrics (project, operation, duration_ms, items, bytes, recorded_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        rusqlite::params![
+            settings::project_key(project),
+            operation,
+            duration.as_millis() as i64,
+            items.map(|items| items as i64),
+            bytes.map(|bytes| bytes as i64),
+            chrono::Utc::now().to_rfc3339()
+        ],
+    )
+    .map_err(|e| format!("Failed to record metrics: {}", e))?;
+    Ok(())
+}
+
+/// Records how long an operation took and how much it processed, when the
+/// user has turned metrics on. Failing to record never fails the operation.
+pub(crate) fn record(
+    app_handle: &AppHandle,
+    project: &Path,
+    operation: &str,
+    duration: Duration,
+    items: Option<u64>,
+    bytes: Option<u64>,
+) {
+    if !is_enabled(app_handle) {
+        return;
+    }
+    if let Err(e) = insert(app_handle, project, operation, duration, items, bytes) {
+        warn!("{}", e);
+    }
+}
+
+/// Durations and throughput of the recorded operations, optionally for one
+/// project and from an RFC 3339 time on
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_performance_stats(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+    since: Option<String>,
+) -> Result<PerformanceStats, String> {
+    let mut filter = String::new();
+    let mut values = Vec::new();
+    if let Some(repo_path) = repo_path {
+        filter.push_str(" AND project = ?");
+        values.push(SqlValue::Text(settings::project_key(&transfer::repo_root(
+            &repo_path,
+        )?)));
+    }
+    if let Some(since) = since {
+        filter.push_str(" AND recorded_at >= ?");
+        values.push(SqlValue::Text(since));
+    }
+    let sql = format!(
+        "SELECT s.operation, s.count, s.avg_ms, s.min_ms, s.max_ms, last.duration_ms,
+                s.items, s.bytes, s.transfer_ms, last.recorded_at
+         FROM (SELECT operation, COUNT(*) AS count, AVG(duration_ms) AS avg_ms,
+                      MIN(duration_ms) AS min_ms, MAX(duration_ms) AS max_ms,
+                      COALESCE(SUM(items), 0) AS items, COALESCE(SUM(bytes), 0) AS bytes,
+                      SUM(CASE WHEN bytes > 0 THEN duration_ms END) AS transfer_ms,
+                      MAX(id) AS last_id
+               FROM operation_metrics WHERE 1 = 1{}
+               GROUP BY operation) s
+         JOIN operation_metrics last ON last.id = s.last_id
+         ORDER BY s.operation",
+        filter
+    );
+
+    let conn = open_connection(&app_handle)?;
+    let mut stmt = conn
+        .prepare(&sql)
+        .map_err(|e| format!("Failed to query metrics: {}", e))?;
+    let operations = stmt
+        .query_map(params_from_iter(values), |row| {
+            let total_bytes: i64 = row.get(7)?;
+            let transfer_ms: Option<i64> = row.get(8)?;
+            Ok(OperationStats {
+                operation: row.get(0)?,
+                count: row.get(1)?,
+                avg_duration_ms: row.get(2)?,
+                min_duration_ms: row.get(3)?,
+                max_duration_ms: row.get(4)?,
+                last_duration_ms: row.get(5)?,
+                total_items: row.get(6)?,
+                total_bytes,
+                throughput_bytes_per_sec: transfer_ms
+                    .filter(|ms| *ms > 0)
+                    .map(|ms| total_bytes as f64 * 1000.0 / ms as f64),
+                last_recorded_at: row.get(9)?,
+            })
+        })
+        .map_err(|e| format!("Failed to query metrics: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read metrics: {}", e))?;
+
+    Ok(PerformanceStats {
+        enabled: is_enabled(&app_handle),
+        operations,
+    })
+}
+
+/// Turns recording of operation timings on or off. Nothing is ever sent
+/// anywhere; turning it off keeps what was recorded until it is cleared.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_performance_stats_enabled(app_handle: AppHandle, enabled: bool) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(&MetricsSettings { enabled })
+        .map_err(|e| format!("Failed to serialize metrics settings: {}", e))?;
+    fs::write(settings_path(&app_handle)?, content)
+        .map_err(|e| format!("Failed to write metrics settings: {}", e))
+}
+
+/// Deletes every recorded metric
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn clear_performance_stats(app_handle: AppHandle) -> Result<(), String> {
+    let conn = open_connection(&app_handle)?;
+    conn.execute("DELETE FROM operation_metrics", [])
+        .map_err(|e| format!("Failed to clear metrics: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS file_index (
+    repo_path TEXT NOT NULL,
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    mtime INTEGER,
+    hash TEXT,
+    is_directory INTEGER NOT NULL,
+    has_dvc_file INTEGER NOT NULL,
+    git_status TEXT NOT NULL,
+    file_type TEXT,
+    indexed_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (repo_path, path)
+);
+
+CREATE TABLE IF NOT EXISTS file_index_state (
+    repo_path TEXT PRIMARY KEY,
+    last_full_scan TIMESTAMP NOT NULL,
+    entry_count INTEGER NOT NULL
+);
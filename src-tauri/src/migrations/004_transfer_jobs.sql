@@ -0,0 +1,18 @@
+CREATE TABLE IF NOT EXISTS transfer_operations (
+    operation_id TEXT PRIMARY KEY,
+    repo_path TEXT NOT NULL,
+    remote TEXT NOT NULL,
+    direction TEXT NOT NULL,
+    targets TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE IF NOT EXISTS transfer_queue (
+    operation_id TEXT NOT NULL,
+    md5 TEXT NOT NULL,
+    status TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (operation_id, md5)
+);
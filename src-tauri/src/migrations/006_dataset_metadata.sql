@@ -0,0 +1,8 @@
+CREATE TABLE IF NOT EXISTS dataset_metadata (
+    project TEXT NOT NULL,
+    path TEXT NOT NULL,
+    key TEXT NOT NULL,
+    value TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (project, path, key)
+);
@@ -0,0 +1,7 @@
+CREATE TABLE IF NOT EXISTS remote_objects (
+    remote TEXT NOT NULL,
+    md5 TEXT NOT NULL,
+    present INTEGER NOT NULL,
+    checked_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (remote, md5)
+);
@@ -0,0 +1,9 @@
+CREATE TABLE IF NOT EXISTS data_pins (
+    project TEXT NOT NULL,
+    path TEXT NOT NULL,
+    rev TEXT NOT NULL,
+    commit_id TEXT NOT NULL,
+    md5 TEXT NOT NULL,
+    pinned_at TIMESTAMP DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (project, path, rev)
+);
@@ -0,0 +1,737 @@
+use git2::{Commit, ErrorCode, ObjectType, Repository, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use serde_json::json;
+use serde_yaml::{Mapping, Value as YamlValue};
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::dvcfile::{self, DvcFile};
+use crate::notifications::{self, Notification};
+use crate::{audit, branch_compare, dvc, git, identity, repo_manager, transfer};
+
+/// Tags of registered models are `model/<name>@v<version>`
+const TAG_PREFIX: &str = "model/";
+/// Key of the registry entry in a pointer's `meta:` section
+const MODEL_META_KEY: &str = "model";
+/// Trailer of the tag message naming the model file
+const PATH_TRAILER: &str = "Path: ";
+/// Trailer of a promotion tag's message naming the promoted version
+const VERSION_TRAILER: &str = "Version: ";
+/// Stages a model version can be promoted to
+const STAGES: &[&str] = &["dev", "staging", "prod"];
+
+/// A version of a model in the in-repo registry
+#[derive(Debug, Serialize)]
+pub struct RegisteredModel {
+    pub name: String,
+    pub version: String,
+    pub tag: String,
+    /// Model file relative to the repository root
+    pub path: String,
+    pub commit_id: String,
+    /// DVC hash of the model file at this version
+    pub md5: Option<String>,
+    pub size: Option<u64>,
+    pub metrics: BTreeMap<String, f64>,
+    /// Tag time in seconds since the epoch
+    pub registered_at: i64,
+    /// Stages this version is the current one of
+    pub stages: Vec<String>,
+}
+
+/// A model version put into a stage. Promotions are annotated tags
+/// `model/<name>#<stage>#<n>` on the version's commit; the highest `n` of a
+/// stage is its current version.
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelPromotion {
+    pub name: String,
+    pub version: String,
+    /// "dev", "staging" or "prod"
+    pub stage: String,
+    pub tag: String,
+    pub promoted_by: String,
+    /// Tag time in seconds since the epoch
+    pub promoted_at: i64,
+}
+
+/// A file that went into a model: a DVC-tracked path or a pipeline dependency
+#[derive(Debug, Serialize)]
+pub struct LineageInput {
+    /// Path relative to the repository root
+    pub path: String,
+    /// DVC hash at the model's commit (`.dir` suffix for directories)
+    pub md5: Option<String>,
+}
+
+/// Pipeline stage whose output a model is, as locked in `dvc.lock`
+#[derive(Debug, Serialize)]
+pub struct PipelineStageRun {
+    pub name: String,
+    /// `dvc.lock` the run is recorded in
+    pub lock_file: String,
+    pub cmd: String,
+    pub deps: Vec<LineageInput>,
+    /// Parameter values by `<params file>:<key>`
+    pub params: BTreeMap<String, serde_json::Value>,
+}
+
+/// Where a model version came from
+#[derive(Debug, Serialize)]
+pub struct ModelLineage {
+    pub model: RegisteredModel,
+    /// Data tracked with DVC at the model's commit, the model itself aside
+    pub datasets: Vec<LineageInput>,
+    /// The pipeline run that produced the model, when it is a stage output
+    pub pipeline: Option<PipelineStageRun>,
+    /// Promotions of this version, newest first
+    pub promotions: Vec<ModelPromotion>,
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid model name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        ))
+    }
+}
+
+/// Version without a leading `v`, so `v2` and `2` are the same version
+fn normalize_version(version: &str) -> Result<String, String> {
+    let version = version.trim();
+    let version = version.strip_prefix('v').unwrap_or(version);
+    if version.is_empty()
+        || !version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == '_')
+    {
+        return Err(format!("Invalid model version '{}'", version));
+    }
+    Ok(version.to_string())
+}
+
+fn model_tag(name: &str, version: &str) -> String {
+    format!("{}{}@v{}", TAG_PREFIX, name, version)
+}
+
+/// Name and version of a model tag
+fn parse_model_tag(tag: &str) -> Option<(String, String)> {
+    let (name, version) = tag.strip_prefix(TAG_PREFIX)?.rsplit_once("@v")?;
+    Some((name.to_string(), version.to_string()))
+}
+
+fn stage_tag(name: &str, stage: &str, number: u32) -> String {
+    format!("{}{}#{}#{}", TAG_PREFIX, name, stage, number)
+}
+
+/// Name, stage and number of a promotion tag
+fn parse_stage_tag(tag: &str) -> Option<(String, String, u32)> {
+    let mut parts = tag.strip_prefix(TAG_PREFIX)?.split('#');
+    let name = parts.next()?.to_string();
+    let stage = parts.next()?.to_string();
+    let number = parts.next()?.parse().ok()?;
+    parts.next().is_none().then_some((name, stage, number))
+}
+
+/// Value of a `<trailer>` line in a tag message
+fn trailer<'a>(message: &'a str, trailer: &str) -> Option<&'a str> {
+    message
+        .lines()
+        .find_map(|line| line.strip_prefix(trailer))
+        .map(str::trim)
+}
+
+/// Adds the registry entry to the `meta:` section of a model's pointer
+fn write_model_meta(
+    dvc_file: &mut DvcFile,
+    name: &str,
+    version: &str,
+    metrics: &BTreeMap<String, f64>,
+) {
+    let mut meta = match dvc_file.extra.remove("meta") {
+        Some(YamlValue::Mapping(meta)) => meta,
+        _ => Mapping::new(),
+    };
+    let mut model = Mapping::new();
+    model.insert("name".into(), name.into());
+    model.insert("version".into(), version.into());
+    if !metrics.is_empty() {
+        let metrics: Mapping = metrics
+            .iter()
+            .map(|(key, value)| (key.as_str().into(), (*value).into()))
+            .collect();
+        model.insert("metrics".into(), YamlValue::Mapping(metrics));
+    }
+    meta.insert(MODEL_META_KEY.into(), YamlValue::Mapping(model));
+    dvc_file
+        .extra
+        .insert("meta".into(), YamlValue::Mapping(meta));
+}
+
+/// Metrics recorded for a model in its pointer
+fn read_model_metrics(dvc_file: &DvcFile) -> BTreeMap<String, f64> {
+    let metrics = dvc_file
+        .extra
+        .get("meta")
+        .and_then(|meta| meta.get(MODEL_META_KEY))
+        .and_then(|model| model.get("metrics"))
+        .and_then(YamlValue::as_mapping);
+    metrics
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?.to_string(), value.as_f64()?)))
+        .collect()
+}
+
+/// Whether the index holds changes that aren't committed, which a
+/// registration commit would sweep up
+fn has_staged_changes(repo: &Repository) -> Result<bool, String> {
+    let head_tree = match repo.head() {
+        Ok(head) => Some(
+            head.peel_to_tree()
+                .map_err(|e| format!("Failed to read HEAD tree: {}", e))?,
+        ),
+        Err(e) if e.code() == ErrorCode::UnbornBranch => None,
+        Err(e) => return Err(format!("Failed to get HEAD: {}", e)),
+    };
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let diff = repo
+        .diff_tree_to_index(head_tree.as_ref(), Some(&index), None)
+        .map_err(|e| format!("Failed to diff index: {}", e))?;
+    Ok(diff.deltas().len() > 0)
+}
+
+/// Reads a registered model version from its tag
+fn read_model(repo: &Repository, tag: &str) -> Result<RegisteredModel, String> {
+    let (name, version) =
+        parse_model_tag(tag).ok_or_else(|| format!("{} is not a model tag", tag))?;
+    let object = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .map_err(|e| format!("Failed to find tag {}: {}", tag, e))?;
+    let annotation = object.as_tag();
+    let path = annotation
+        .and_then(|annotation| annotation.message())
+        .and_then(|message| trailer(message, PATH_TRAILER))
+        .map(str::to_string)
+        .ok_or_else(|| format!("Tag {} doesn't name a model file", tag))?;
+    let commit = object
+        .peel_to_commit()
+        .map_err(|e| format!("Failed to find commit of {}: {}", tag, e))?;
+    let registered_at = annotation
+        .and_then(|annotation| annotation.tagger())
+        .map(|tagger| tagger.when().seconds())
+        .unwrap_or_else(|| commit.time().seconds());
+
+    let pointer = commit
+        .tree()
+        .and_then(|tree| tree.get_path(&dvcfile::pointer_path(Path::new(&path))))
+        .and_then(|entry| entry.to_object(repo))
+        .ok()
+        .and_then(|object| object.into_blob().ok())
+        .and_then(|blob| dvcfile::parse_dvc_file(&String::from_utf8_lossy(blob.content())).ok());
+    let out = pointer
+        .as_ref()
+        .and_then(|pointer| pointer.outs.first().cloned());
+
+    Ok(RegisteredModel {
+        name,
+        version,
+        tag: tag.to_string(),
+        path,
+        commit_id: commit.id().to_string(),
+        md5: out.as_ref().and_then(|out| out.md5.clone()),
+        size: out.as_ref().and_then(|out| out.size),
+        metrics: pointer.as_ref().map(read_model_metrics).unwrap_or_default(),
+        registered_at,
+        stages: Vec::new(),
+    })
+}
+
+/// Promotions of every model or only of `name`, newest first within each
+/// stage
+fn promotions(repo: &Repository, name: Option<&str>) -> Result<Vec<ModelPromotion>, String> {
+    let tags = repo
+        .tag_names(Some(&format!("{}*#*", TAG_PREFIX)))
+        .map_err(|e| format!("Failed to list tags: {}", e))?;
+    let mut promotions = Vec::new();
+    for tag in tags.iter().flatten() {
+        let Some((tag_name, stage, number)) = parse_stage_tag(tag) else {
+            continue;
+        };
+        if name.is_some_and(|name| name != tag_name) {
+            continue;
+        }
+        let annotation = repo
+            .revparse_single(&format!("refs/tags/{}", tag))
+            .ok()
+            .and_then(|object| object.into_tag().ok());
+        let Some(annotation) = annotation else {
+            tracing::warn!("Skipping promotion tag {}: not annotated", tag);
+            continue;
+        };
+        let Some(version) = annotation
+            .message()
+            .and_then(|message| trailer(message, VERSION_TRAILER))
+        else {
+            tracing::warn!("Skipping promotion tag {}: no version", tag);
+            continue;
+        };
+        let tagger = annotation.tagger();
+        promotions.push((
+            number,
+            ModelPromotion {
+                name: tag_name,
+                version: version.to_string(),
+                stage,
+                tag: tag.to_string(),
+                promoted_by: tagger
+                    .as_ref()
+                    .and_then(|tagger| tagger.name().map(str::to_string))
+                    .unwrap_or_default(),
+                promoted_at: tagger.map(|tagger| tagger.when().seconds()).unwrap_or(0),
+            },
+        ));
+    }
+    promotions.sort_by(|(a_number, a), (b_number, b)| {
+        (&a.name, &a.stage, b_number).cmp(&(&b.name, &b.stage, a_number))
+    });
+    Ok(promotions
+        .into_iter()
+        .map(|(_, promotion)| promotion)
+        .collect())
+}
+
+/// Sets the stages each version is current in: the newest promotion of
+/// every model and stage
+fn assign_stages(models: &mut [RegisteredModel], promotions: &[ModelPromotion]) {
+    let mut current: BTreeMap<(&str, &str), &str> = BTreeMap::new();
+    for promotion in promotions {
+        current
+            .entry((promotion.name.as_str(), promotion.stage.as_str()))
+            .or_insert(promotion.version.as_str());
+    }
+    for model in models {
+        model.stages = current
+            .iter()
+            .filter(|((name, _), version)| *name == model.name && **version == model.version)
+            .map(|((_, stage), _)| stage.to_string())
+            .collect();
+    }
+}
+
+/// Every registered version, of every model or only of `name`, newest first
+fn registered_models(
+    repo: &Repository,
+    name: Option<&str>,
+) -> Result<Vec<RegisteredModel>, String> {
+    let tags = repo
+        .tag_names(Some(&format!("{}*", TAG_PREFIX)))
+        .map_err(|e| format!("Failed to list tags: {}", e))?;
+    let mut models = Vec::new();
+    for tag in tags.iter().flatten() {
+        let Some((tag_name, _)) = parse_model_tag(tag) else {
+            continue;
+        };
+        if name.is_some_and(|name| name != tag_name) {
+            continue;
+        }
+        match read_model(repo, tag) {
+            Ok(model) => models.push(model),
+            Err(e) => tracing::warn!("Skipping model tag: {}", e),
+        }
+    }
+    models.sort_by(|a, b| {
+        b.registered_at
+            .cmp(&a.registered_at)
+            .then_with(|| b.version.cmp(&a.version))
+    });
+    assign_stages(&mut models, &promotions(repo, name)?);
+    Ok(models)
+}
+
+fn promote(
+    repo: &Repository,
+    name: &str,
+    version: &str,
+    stage: &str,
+) -> Result<ModelPromotion, String> {
+    if !STAGES.contains(&stage) {
+        return Err(format!(
+            "Unknown stage {}: use {}",
+            stage,
+            STAGES.join(", ")
+        ));
+    }
+    let version = normalize_version(version)?;
+    let commit = repo
+        .revparse_single(&format!("refs/tags/{}", model_tag(name, &version)))
+        .and_then(|object| object.peel(ObjectType::Commit))
+        .map_err(|_| format!("{} v{} is not registered", name, version))?;
+
+    let number = promotions(repo, Some(name))?
+        .iter()
+        .filter(|promotion| promotion.stage == stage)
+        .filter_map(|promotion| parse_stage_tag(&promotion.tag))
+        .map(|(_, _, number)| number)
+        .max()
+        .unwrap_or(0)
+        + 1;
+    let tag = stage_tag(name, stage, number);
+    let signature = identity::signature(repo)?;
+    let message = format!(
+        "Promote {} v{} to {}\n\n{}{}\n",
+        name, version, stage, VERSION_TRAILER, version
+    );
+    repo.tag(&tag, &commit, &signature, &message, false)
+        .map_err(|e| format!("Failed to create tag {}: {}", tag, e))?;
+
+    Ok(ModelPromotion {
+        name: name.to_string(),
+        version,
+        stage: stage.to_string(),
+        tag,
+        promoted_by: signature.name().unwrap_or_default().to_string(),
+        promoted_at: signature.when().seconds(),
+    })
+}
+
+/// `dvc.lock` files in a commit, with their directory relative to the root
+fn lock_files(repo: &Repository, commit: &Commit) -> Vec<(String, YamlValue)> {
+    let Ok(tree) = commit.tree() else {
+        return Vec::new();
+    };
+    let mut locks = Vec::new();
+    let _ = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(ObjectType::Blob) && entry.name() == Some("dvc.lock") {
+            locks.push((root.trim_end_matches('/').to_string(), entry.id()));
+        }
+        TreeWalkResult::Ok
+    });
+    locks
+        .into_iter()
+        .filter_map(|(dir, id)| {
+            let blob = repo.find_blob(id).ok()?;
+            let lock = serde_yaml::from_slice(blob.content()).ok()?;
+            Some((dir, lock))
+        })
+        .collect()
+}
+
+/// Joins a path from a `dvc.lock` to the directory of the lock file
+fn lock_path(dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}/{}", dir, path)
+    }
+}
+
+/// The stage of a `dvc.lock` in the commit that outputs `path`
+fn producing_stage(repo: &Repository, commit: &Commit, path: &str) -> Option<PipelineStageRun> {
+    for (dir, lock) in lock_files(repo, commit) {
+        let Some(stages) = lock.get("stages").and_then(YamlValue::as_mapping) else {
+            continue;
+        };
+        for (stage_name, stage) in stages {
+            let entries = |key: &str| -> Vec<LineageInput> {
+                stage
+                    .get(key)
+                    .and_then(YamlValue::as_sequence)
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|entry| {
+                        Some(LineageInput {
+                            path: lock_path(&dir, entry.get("path")?.as_str()?),
+                            md5: entry
+                                .get("md5")
+                                .and_then(YamlValue::as_str)
+                                .map(str::to_string),
+                        })
+                    })
+                    .collect()
+            };
+            if !entries("outs").iter().any(|out| out.path == path) {
+                continue;
+            }
+
+            let mut params = BTreeMap::new();
+            for (file, values) in stage
+                .get("params")
+                .and_then(YamlValue::as_mapping)
+                .into_iter()
+                .flatten()
+            {
+                let (Some(file), Some(values)) = (file.as_str(), values.as_mapping()) else {
+                    continue;
+                };
+                for (key, value) in values {
+                    if let (Some(key), Ok(value)) = (key.as_str(), serde_json::to_value(value)) {
+                        params.insert(format!("{}:{}", file, key), value);
+                    }
+                }
+            }
+            return Some(PipelineStageRun {
+                name: stage_name.as_str().unwrap_or_default().to_string(),
+                lock_file: lock_path(&dir, "dvc.lock"),
+                cmd: match stage.get("cmd") {
+                    Some(YamlValue::Sequence(cmds)) => cmds
+                        .iter()
+                        .filter_map(YamlValue::as_str)
+                        .collect::<Vec<_>>()
+                        .join(" && "),
+                    Some(cmd) => cmd.as_str().unwrap_or_default().to_string(),
+                    None => String::new(),
+                },
+                deps: entries("deps"),
+                params,
+            });
+        }
+    }
+    None
+}
+
+fn lineage(
+    repo: &Repository,
+    model: RegisteredModel,
+    promotions: &[ModelPromotion],
+) -> Result<ModelLineage, String> {
+    let commit = repo
+        .find_commit(
+            git2::Oid::from_str(&model.commit_id)
+                .map_err(|e| format!("Invalid commit id {}: {}", model.commit_id, e))?,
+        )
+        .map_err(|e| format!("Failed to find commit {}: {}", model.commit_id, e))?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to read tree of {}: {}", model.commit_id, e))?;
+    let datasets = branch_compare::tracked_outputs(repo, &tree)?
+        .into_iter()
+        .filter(|(path, _)| *path != model.path)
+        .map(|(path, out)| LineageInput { path, md5: out.md5 })
+        .collect();
+    let pipeline = producing_stage(repo, &commit, &model.path);
+    let promotions = promotions
+        .iter()
+        .filter(|promotion| promotion.version == model.version)
+        .cloned()
+        .collect();
+    Ok(ModelLineage {
+        model,
+        datasets,
+        pipeline,
+        promotions,
+    })
+}
+
+fn register(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    path: &str,
+    name: &str,
+    version: &str,
+    metrics: &BTreeMap<String, f64>,
+) -> Result<RegisteredModel, String> {
+    validate_name(name)?;
+    let version = normalize_version(version)?;
+    let tag = model_tag(name, &version);
+    let repo_root = transfer::repo_root(repo_path)?;
+    let repo =
+        Repository::open(&repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    if repo.find_reference(&format!("refs/tags/{}", tag)).is_ok() {
+        return Err(format!("{} v{} is already registered", name, version));
+    }
+    if has_staged_changes(&repo)? {
+        return Err("Commit or unstage the staged changes before registering a model".to_string());
+    }
+
+    let relative = dvcfile::repo_relative_path(&repo_root, Path::new(path))?;
+    let data_path = repo_root.join(&relative);
+    if !data_path.is_file() {
+        return Err(format!("{} is not a file", path));
+    }
+    let relative = dvcfile::to_git_path(&relative);
+    let root_str = repo_root.to_string_lossy().to_string();
+    dvc::add_single(app_handle, &root_str, &relative)?;
+
+    let pointer = dvcfile::pointer_path(&data_path);
+    let mut dvc_file = dvcfile::read_dvc_file(&pointer)?;
+    write_model_meta(&mut dvc_file, name, &version, metrics);
+    dvcfile::write_dvc_file(&pointer, &dvc_file)?;
+
+    // `dvc add` ignores the file in the `.gitignore` next to it
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let pointer_relative = dvcfile::pointer_path(Path::new(&relative));
+    index
+        .add_path(&pointer_relative)
+        .map_err(|e| format!("Failed to stage {}: {}", pointer_relative.display(), e))?;
+    let gitignore = Path::new(&relative).with_file_name(".gitignore");
+    if repo_root.join(&gitignore).exists() {
+        index
+            .add_path(&gitignore)
+            .map_err(|e| format!("Failed to stage {}: {}", gitignore.display(), e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    let result = git::commit(
+        app_handle.clone(),
+        repo_path.to_string(),
+        format!("Register model {} v{}", name, version),
+        String::new(),
+    )?;
+    let commit_id = match result.commit_id {
+        Some(commit_id) if result.success => commit_id,
+        _ => return Err(result.message),
+    };
+
+    let commit = repo
+        .revparse_single(&commit_id)
+        .map_err(|e| format!("Failed to find commit {}: {}", commit_id, e))?;
+    let signature = identity::signature(&repo)?;
+    let message = format!(
+        "Model {} v{}\n\n{}{}\n",
+        name, version, PATH_TRAILER, relative
+    );
+    repo.tag(&tag, &commit, &signature, &message, false)
+        .map_err(|e| format!("Failed to create tag {}: {}", tag, e))?;
+    read_model(&repo, &tag)
+}
+
+/// Registers a version of a model: tracks the file with DVC, records the
+/// name, version and metrics in the `meta:` section of its pointer, commits
+/// the pointer and tags the commit `model/<name>@v<version>`
+#[command(async)]
+#[instrument(skip(app_handle, metrics), err(Debug))]
+pub fn register_model(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    name: String,
+    version: String,
+    metrics: Option<BTreeMap<String, f64>>,
+) -> Result<RegisteredModel, String> {
+    let metrics = metrics.unwrap_or_default();
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "register_model",
+        json!({ "path": path, "name": name, "version": version, "metrics": metrics }),
+        || {
+            let model = repo_manager::write(&app_handle, &repo_path, || {
+                register(&app_handle, &repo_path, &path, &name, &version, &metrics)
+            })?;
+            notifications::notify(
+                &app_handle,
+                &transfer::repo_root(&repo_path)?,
+                Notification::new(
+                    notifications::DATA_VERSION_EVENT,
+                    format!("Registered model {} v{}", model.name, model.version),
+                )
+                .field("tag", &model.tag)
+                .field("name", &model.name)
+                .field("version", &model.version),
+            );
+            Ok(model)
+        },
+    )
+}
+
+/// Registered models, every version of each, newest first
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_models(
+    app_handle: AppHandle,
+    repo_path: String,
+    name: Option<String>,
+) -> Result<Vec<RegisteredModel>, String> {
+    repo_manager::read(&app_handle, &repo_path, |repo| {
+        registered_models(repo, name.as_deref())
+    })
+}
+
+/// Makes a registered version the current one of a stage ("dev", "staging"
+/// or "prod") with a `model/<name>#<stage>#<n>` tag on its commit, keeping
+/// earlier promotions as history
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn promote_model(
+    app_handle: AppHandle,
+    repo_path: String,
+    name: String,
+    version: String,
+    stage: String,
+) -> Result<ModelPromotion, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "promote_model",
+        json!({ "name": name, "version": version, "stage": stage }),
+        || {
+            let promotion = repo_manager::write(&app_handle, &repo_path, || {
+                let repo = Repository::discover(&repo_path)
+                    .map_err(|e| format!("Failed to open repository: {}", e))?;
+                promote(&repo, &name, &version, &stage)
+            })?;
+            notifications::notify(
+                &app_handle,
+                &transfer::repo_root(&repo_path)?,
+                Notification::new(
+                    notifications::DATA_VERSION_EVENT,
+                    format!(
+                        "Promoted model {} v{} to {}",
+                        promotion.name, promotion.version, promotion.stage
+                    ),
+                )
+                .field("tag", &promotion.tag)
+                .field("name", &promotion.name)
+                .field("version", &promotion.version)
+                .field("stage", &promotion.stage),
+            );
+            Ok(promotion)
+        },
+    )
+}
+
+/// Where each version of a model (or only `version`) came from: the data
+/// tracked at its commit, the pipeline stage that produced it according to
+/// `dvc.lock`, and its promotions. Newest version first.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_model_lineage(
+    app_handle: AppHandle,
+    repo_path: String,
+    name: String,
+    version: Option<String>,
+) -> Result<Vec<ModelLineage>, String> {
+    let version = version.as_deref().map(normalize_version).transpose()?;
+    repo_manager::read(&app_handle, &repo_path, |repo| {
+        let promotions = promotions(repo, Some(&name))?;
+        let models = registered_models(repo, Some(&name))?;
+        if models.is_empty() {
+            return Err(format!("No model named {} is registered", name));
+        }
+        models
+            .into_iter()
+            .filter(|model| {
+                version
+                    .as_ref()
+                    .is_none_or(|version| *version == model.version)
+            })
+            .map(|model| lineage(repo, model, &promotions))
+            .collect()
+    })
+}
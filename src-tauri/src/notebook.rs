@@ -0,0 +1,392 @@
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use serde_json::ser::{PrettyFormatter, Serializer};
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tauri::command;
+use tracing::instrument;
+
+use crate::dvcfile;
+use crate::history::{self, VersionContent};
+
+/// A cell of an `.ipynb` notebook, as far as diffs care
+#[derive(Debug)]
+struct Cell {
+    id: Option<String>,
+    cell_type: String,
+    source: String,
+    /// Outputs without execution counts, which change on every run
+    outputs: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CellChange {
+    /// "added", "removed" or "modified"
+    pub change: String,
+    pub cell_type: String,
+    /// Position of the cell in each version
+    pub index_a: Option<usize>,
+    pub index_b: Option<usize>,
+    pub source_changed: bool,
+    pub outputs_changed: bool,
+    pub source_a: Option<String>,
+    pub source_b: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct NotebookDiff {
+    pub path: String,
+    pub rev_a: String,
+    pub rev_b: String,
+    pub cells_a: usize,
+    pub cells_b: usize,
+    /// Changed cells in notebook order; unchanged cells are left out
+    pub changes: Vec<CellChange>,
+    /// Whether the notebook metadata, e.g. its kernel, changed
+    pub metadata_changed: bool,
+}
+
+/// Notebook text fields are a string or a list of lines
+fn text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(lines)) => lines.iter().filter_map(Value::as_str).collect(),
+        _ => String::new(),
+    }
+}
+
+fn strip_execution_counts(value: &mut Value) {
+    match value {
+        Value::Object(object) => {
+            object.remove("execution_count");
+            object.values_mut().for_each(strip_execution_counts);
+        }
+        Value::Array(items) => items.iter_mut().for_each(strip_execution_counts),
+        _ => {}
+    }
+}
+
+fn parse(content: &[u8], name: &str) -> Result<(Vec<Cell>, Value), String> {
+    let mut notebook: Value = serde_json::from_slice(content)
+        .map_err(|e| format!("Failed to parse notebook {}: {}", name, e))?;
+    let cells = match notebook.get_mut("cells").map(Value::take) {
+        Some(Value::Array(cells)) => cells,
+        _ => return Err(format!("{} is not a notebook: it has no cells", name)),
+    };
+    let cells = cells
+        .into_iter()
+        .map(|mut cell| {
+            let mut outputs = cell.get_mut("outputs").map(Value::take).unwrap_or_default();
+            strip_execution_counts(&mut outputs);
+            Cell {
+                id: cell.get("id").and_then(Value::as_str).map(str::to_string),
+                cell_type: cell
+                    .get("cell_type")
+                    .and_then(Value::as_str)
+                    .unwrap_or("code")
+                    .to_string(),
+                source: text(cell.get("source")),
+                outputs,
+            }
+        })
+        .collect();
+    let metadata = notebook
+        .get_mut("metadata")
+        .map(Value::take)
+        .unwrap_or_default();
+    Ok((cells, metadata))
+}
+
+/// Pairs of cells that are the same cell in both versions: by cell id when
+/// the notebook has them (nbformat 4.5), otherwise the longest run of cells
+/// with identical source
+fn match_cells(a: &[Cell], b: &[Cell]) -> Vec<(usize, usize)> {
+    let same = |x: &Cell, y: &Cell| match (&x.id, &y.id) {
+        (Some(x), Some(y)) => x == y,
+        _ => x.cell_type == y.cell_type && x.source == y.source,
+    };
+
+    // Longest common subsequence, filled from the end
+    let mut lengths = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lengths[i][j] = if same(&a[i], &b[j]) {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+    let (mut i, mut j) = (0, 0);
+    let mut pairs = Vec::new();
+    while i < a.len() && j < b.len() {
+        if same(&a[i], &b[j]) {
+            pairs.push((i, j));
+            i += 1;
+            j += 1;
+        } else if lengths[i + 1][j] >= lengths[i][j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    pairs
+}
+
+fn cell_change(change: &str, a: Option<(usize, &Cell)>, b: Option<(usize, &Cell)>) -> CellChange {
+    let cell = a.or(b).map(|(_, cell)| cell);
+    let (source_changed, outputs_changed) = match (a, b) {
+        (Some((_, a)), Some((_, b))) => (a.source != b.source, a.outputs != b.outputs),
+        _ => (true, false),
+    };
+    CellChange {
+        change: change.to_string(),
+        cell_type: cell.map(|cell| cell.cell_type.clone()).unwrap_or_default(),
+        index_a: a.map(|(index, _)| index),
+        index_b: b.map(|(index, _)| index),
+        source_changed,
+        outputs_changed,
+        source_a: a.map(|(_, cell)| cell.source.clone()),
+        source_b: b.map(|(_, cell)| cell.source.clone()),
+    }
+}
+
+/// Changes between matched cells. Unmatched cells between two matches are
+/// paired up as edits when their types agree, the rest are added or removed.
+fn cell_changes(a: &[Cell], b: &[Cell]) -> Vec<CellChange> {
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    for (next_a, next_b) in match_cells(a, b).into_iter().chain([(a.len(), b.len())]) {
+        while i < next_a && j < next_b && a[i].cell_type == b[j].cell_type {
+            changes.push(cell_change("modified", Some((i, &a[i])), Some((j, &b[j]))));
+            i += 1;
+            j += 1;
+        }
+        for index in i..next_a {
+            changes.push(cell_change("removed", Some((index, &a[index])), None));
+        }
+        for index in j..next_b {
+            changes.push(cell_change("added", None, Some((index, &b[index]))));
+        }
+        if next_a < a.len() && next_b < b.len() {
+            let (cell_a, cell_b) = (&a[next_a], &b[next_b]);
+            if cell_a.source != cell_b.source || cell_a.outputs != cell_b.outputs {
+                changes.push(cell_change(
+                    "modified",
+                    Some((next_a, cell_a)),
+                    Some((next_b, cell_b)),
+                ));
+            }
+        }
+        (i, j) = (next_a + 1, next_b + 1);
+    }
+    changes
+}
+
+/// Notebook as it was at a revision
+fn read_version(repo: &Repository, relative: &Path, rev: &str) -> Result<Vec<u8>, String> {
+    match history::content_at(repo, relative, rev)? {
+        VersionContent::Cache(path) => {
+            fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+        }
+        VersionContent::Blob(bytes) => Ok(bytes),
+    }
+}
+
+/// Cell by cell changes to a Jupyter notebook between two revisions: cells
+/// added or removed and cells whose source or outputs changed. Execution
+/// counts are ignored.
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn diff_notebook(
+    repo_path: String,
+    path: String,
+    rev_a: String,
+    rev_b: String,
+) -> Result<NotebookDiff, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let relative = dvcfile::repo_relative_path(repo_root, Path::new(&path))?;
+    let name = dvcfile::to_git_path(&relative);
+
+    let (cells_a, metadata_a) = parse(&read_version(&repo, &relative, &rev_a)?, &name)?;
+    let (cells_b, metadata_b) = parse(&read_version(&repo, &relative, &rev_b)?, &name)?;
+
+    Ok(NotebookDiff {
+        path: name,
+        rev_a,
+        rev_b,
+        cells_a: cells_a.len(),
+        cells_b: cells_b.len(),
+        changes: cell_changes(&cells_a, &cells_b),
+        metadata_changed: metadata_a != metadata_b,
+    })
+}
+
+/// Commit-time notebook cleanup configuration, stored with the hook settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotebookOutputSettings {
+    /// Cell outputs above this size are removed; 0 removes every output
+    pub max_output_kib: u64,
+    pub strip_execution_counts: bool,
+}
+
+impl Default for NotebookOutputSettings {
+    fn default() -> Self {
+        Self {
+            max_output_kib: 0,
+            strip_execution_counts: true,
+        }
+    }
+}
+
+/// Notebook content with large outputs and execution counts removed as
+/// configured; `None` when there was nothing to remove
+pub(crate) fn strip_outputs(
+    content: &[u8],
+    settings: &NotebookOutputSettings,
+) -> Result<Option<Vec<u8>>, String> {
+    let mut notebook: Value =
+        serde_json::from_slice(content).map_err(|e| format!("Failed to parse notebook: {}", e))?;
+    let Some(Value::Array(cells)) = notebook.get_mut("cells") else {
+        return Err("Not a notebook: it has no cells".to_string());
+    };
+
+    let max_len = settings.max_output_kib.saturating_mul(1024) as usize;
+    let mut changed = false;
+    for cell in cells {
+        if settings.strip_execution_counts {
+            if let Some(count) = cell.get_mut("execution_count").filter(|c| !c.is_null()) {
+                *count = Value::Null;
+                changed = true;
+            }
+        }
+        let Some(Value::Array(outputs)) = cell.get_mut("outputs") else {
+            continue;
+        };
+        let before = outputs.len();
+        outputs.retain(|output| {
+            max_len > 0 && serde_json::to_vec(output).is_ok_and(|json| json.len() <= max_len)
+        });
+        changed |= outputs.len() != before;
+        if settings.strip_execution_counts {
+            for output in outputs.iter_mut() {
+                if let Some(count) = output.get_mut("execution_count").filter(|c| !c.is_null()) {
+                    *count = Value::Null;
+                    changed = true;
+                }
+            }
+        }
+    }
+    if !changed {
+        return Ok(None);
+    }
+
+    // Written the way Jupyter saves notebooks: one space indents and a
+    // trailing newline, so the next save doesn't show up as a change
+    let mut stripped = Vec::new();
+    let formatter = PrettyFormatter::with_indent(b" ");
+    let mut serializer = Serializer::with_formatter(&mut stripped, formatter);
+    notebook
+        .serialize(&mut serializer)
+        .map_err(|e| format!("Failed to write notebook: {}", e))?;
+    stripped.push(b'\n');
+    Ok(Some(stripped))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn cell(id: Option<&str>, cell_type: &str, source: &str) -> Value {
+        let mut cell = json!({ "cell_type": cell_type, "source": source, "metadata": {} });
+        if let Some(id) = id {
+            cell["id"] = json!(id);
+        }
+        cell
+    }
+
+    fn cells(cells: Vec<Value>) -> Vec<Cell> {
+        let content = json!({ "cells": cells, "metadata": {}, "nbformat": 4 });
+        parse(content.to_string().as_bytes(), "test.ipynb")
+            .unwrap()
+            .0
+    }
+
+    #[test]
+    fn lists_added_removed_and_modified_cells() {
+        let code = |source| cell(None, "code", source);
+        let markdown = |source| cell(None, "markdown", source);
+        let with_id = |id, source| cell(Some(id), "code", source);
+        let cases = [
+            (
+                vec![markdown("# Title"), code("x = 1")],
+                vec![markdown("# Title"), code("x = 1")],
+                vec![],
+            ),
+            (
+                vec![code("a")],
+                vec![code("a"), code("b")],
+                vec![("added", None, Some(1))],
+            ),
+            (
+                vec![code("a"), code("b"), code("c")],
+                vec![code("a"), code("c")],
+                vec![("removed", Some(1), None)],
+            ),
+            (
+                vec![markdown("# Title"), code("x = 1")],
+                vec![markdown("# Title"), code("x = 2")],
+                vec![("modified", Some(1), Some(1))],
+            ),
+            (
+                vec![code("a"), markdown("note")],
+                vec![code("a"), code("note")],
+                vec![("removed", Some(1), None), ("added", None, Some(1))],
+            ),
+            (
+                vec![with_id("x", "print(1)")],
+                vec![with_id("z", "print(1)"), with_id("x", "print(2)")],
+                vec![("added", None, Some(0)), ("modified", Some(0), Some(1))],
+            ),
+        ];
+        for (a, b, expected) in cases {
+            let changes = cell_changes(&cells(a.clone()), &cells(b.clone()));
+            let changes: Vec<_> = changes
+                .iter()
+                .map(|change| (change.change.as_str(), change.index_a, change.index_b))
+                .collect();
+            assert_eq!(changes, expected, "{:?} -> {:?}", a, b);
+        }
+    }
+
+    #[test]
+    fn output_changes_ignore_execution_counts() {
+        let run = |count: u64, result: &str| {
+            let mut executed = cell(None, "code", "1 + 1");
+            executed["execution_count"] = json!(count);
+            executed["outputs"] = json!([{
+                "output_type": "execute_result",
+                "execution_count": count,
+                "data": { "text/plain": result },
+                "metadata": {},
+            }]);
+            executed
+        };
+
+        let rerun = cell_changes(&cells(vec![run(1, "2")]), &cells(vec![run(2, "2")]));
+        assert!(rerun.is_empty());
+
+        let changed = cell_changes(&cells(vec![run(1, "2")]), &cells(vec![run(2, "3")]));
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].change, "modified");
+        assert!(!changed[0].source_changed);
+        assert!(changed[0].outputs_changed);
+    }
+}
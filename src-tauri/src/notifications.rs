@@ -0,0 +1,215 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{settings, transfer};
+
+/// A push (git or DVC) finished
+pub const PUSH_EVENT: &str = "push";
+/// A model or data version was tagged
+pub const DATA_VERSION_EVENT: &str = "data_version";
+const EVENTS: [&str; 2] = [PUSH_EVENT, DATA_VERSION_EVENT];
+
+const DEFAULT_TEMPLATE: &str = "[{{project}}] {{summary}}";
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelKind {
+    Slack,
+    Teams,
+    /// Any endpoint accepting a JSON POST
+    Webhook,
+}
+
+/// Where notifications of a project are sent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    pub kind: ChannelKind,
+    /// Incoming webhook URL
+    pub url: String,
+    /// Events sent to the channel: "push" and/or "data_version"
+    pub events: Vec<String>,
+    /// Message with `{{placeholder}}`s: `project`, `event`, `summary` and the
+    /// fields of the event, such as `remote` or `tag`
+    pub template: Option<String>,
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Notification channels, stored with the project settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotificationSettings {
+    pub channels: Vec<NotificationConfig>,
+}
+
+/// Something that happened in a project that channels may be told about
+#[derive(Debug, Clone)]
+pub struct Notification {
+    event: &'static str,
+    summary: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Notification {
+    pub fn new(event: &'static str, summary: impl Into<String>) -> Self {
+        Self {
+            event,
+            summary: summary.into(),
+            fields: BTreeMap::new(),
+        }
+    }
+
+    pub fn field(mut self, key: &str, value: impl Into<String>) -> Self {
+        self.fields.insert(key.to_string(), value.into());
+        self
+    }
+}
+
+fn validate(config: &NotificationConfig) -> Result<(), String> {
+    if !config.url.starts_with("https://") && !config.url.starts_with("http://") {
+        return Err("The notification URL must start with http:// or https://".to_string());
+    }
+    if config.events.is_empty() {
+        return Err("Choose at least one event to notify about".to_string());
+    }
+    if let Some(event) = config.events.iter().find(|e| !EVENTS.contains(&e.as_str())) {
+        return Err(format!(
+            "Unknown event {}: use {}",
+            event,
+            EVENTS.join(", ")
+        ));
+    }
+    Ok(())
+}
+
+/// Fills the `{{placeholder}}`s of a template, leaving unknown ones as they are
+fn render(template: &str, project: &str, notification: &Notification) -> String {
+    let mut message = template
+        .replace("{{project}}", project)
+        .replace("{{event}}", notification.event)
+        .replace("{{summary}}", &notification.summary);
+    for (key, value) in &notification.fields {
+        message = message.replace(&format!("{{{{{}}}}}", key), value);
+    }
+    message
+}
+
+fn payload(config: &NotificationConfig, project: &str, notification: &Notification) -> Value {
+    let message = render(
+        config.template.as_deref().unwrap_or(DEFAULT_TEMPLATE),
+        project,
+        notification,
+    );
+    match config.kind {
+        ChannelKind::Slack | ChannelKind::Teams => json!({ "text": message }),
+        ChannelKind::Webhook => json!({
+            "event": notification.event,
+            "project": project,
+            "message": message,
+            "summary": notification.summary,
+            "fields": notification.fields,
+        }),
+    }
+}
+
+fn send(
+    config: &NotificationConfig,
+    project: &str,
+    notification: &Notification,
+) -> Result<(), String> {
+    ureq::AgentBuilder::new()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .post(&config.url)
+        .send_json(payload(config, project, notification))
+        // The error of a request names its URL, which must not reach the logs
+        .map_err(|e| match e {
+            ureq::Error::Status(code, _) => {
+                format!("Failed to send notification: HTTP {}", code)
+            }
+            ureq::Error::Transport(transport) => {
+                format!("Failed to send notification: {}", transport.kind())
+            }
+        })?;
+    Ok(())
+}
+
+fn project_name(repo_root: &Path) -> String {
+    repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| settings::project_key(repo_root))
+}
+
+/// Sends a notification to the project's channels that subscribed to its
+/// event, in the background. Failures are only logged: a notification never
+/// fails the operation it reports on.
+pub(crate) fn notify(app_handle: &AppHandle, repo_root: &Path, notification: Notification) {
+    let channels: Vec<NotificationConfig> = match settings::load(app_handle, repo_root) {
+        Ok(settings) => settings
+            .notifications
+            .channels
+            .into_iter()
+            .filter(|channel| {
+                channel.enabled && channel.events.iter().any(|e| e == notification.event)
+            })
+            .collect(),
+        Err(e) => {
+            tracing::warn!("Failed to load notification settings: {}", e);
+            return;
+        }
+    };
+    if channels.is_empty() {
+        return;
+    }
+    let project = project_name(repo_root);
+    thread::spawn(move || {
+        for channel in &channels {
+            if let Err(e) = send(channel, &project, &notification) {
+                tracing::warn!("{}", e);
+            }
+        }
+    });
+}
+
+/// Saves the channels notified about pushes and new data versions
+#[command]
+// Webhook URLs are secrets, so they stay out of the logs
+#[instrument(skip(app_handle, notifications), err(Debug))]
+pub fn set_notifications(
+    app_handle: AppHandle,
+    repo_path: String,
+    notifications: NotificationSettings,
+) -> Result<NotificationSettings, String> {
+    for channel in &notifications.channels {
+        validate(channel)?;
+    }
+    let saved = settings::update(&app_handle, &transfer::repo_root(&repo_path)?, |settings| {
+        settings.notifications = notifications;
+    })?;
+    Ok(saved.notifications)
+}
+
+/// Sends a sample notification through a channel, so its URL and template
+/// can be checked before saving it
+#[command(async)]
+#[instrument(skip(config), err(Debug))]
+pub fn test_notification(config: NotificationConfig) -> Result<String, String> {
+    validate(&config)?;
+    let notification = Notification::new(PUSH_EVENT, "Test notification from the desktop app")
+        .field("remote", "origin")
+        .field("branch", "main");
+    send(&config, "test-project", &notification)?;
+    Ok("Sent a test notification".to_string())
+}
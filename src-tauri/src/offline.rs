@@ -0,0 +1,369 @@
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+use tauri::{command, AppHandle, Emitter};
+use tracing::{info, instrument, warn};
+
+use crate::db::{self, PooledConnection};
+use crate::git::{self, PullStrategy};
+use crate::{readonly, remote, transfer};
+
+pub const SYNC_OPERATIONS_EVENT: &str = "sync://operations-changed";
+
+/// How often queued operations are retried while offline
+const RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Fragments of the errors git, ureq and the storage backends report when
+/// the network can't be reached, lowercased
+const NETWORK_ERRORS: &[&str] = &[
+    "could not resolve",
+    "failed to resolve address",
+    "name or service not known",
+    "temporary failure in name resolution",
+    "nodename nor servname",
+    "no such host is known",
+    "network is unreachable",
+    "no route to host",
+    "failed to connect",
+    "connection refused",
+    "connection timed out",
+    "connection reset",
+    "dns failed",
+    "connection failed",
+];
+
+/// A network operation queued to run again once the connection is back
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "operation", rename_all = "snake_case")]
+pub enum SyncOperation {
+    GitPush {
+        remote: Option<String>,
+        branch: Option<String>,
+    },
+    GitFetch {
+        remote: Option<String>,
+    },
+    GitPull {
+        autostash: Option<bool>,
+        pull_strategy: Option<PullStrategy>,
+    },
+    DvcPush {
+        targets: Option<Vec<String>>,
+        remote: Option<String>,
+    },
+    DvcPull {
+        targets: Option<Vec<String>>,
+        remote: Option<String>,
+    },
+}
+
+impl SyncOperation {
+    fn name(&self) -> &'static str {
+        match self {
+            SyncOperation::GitPush { .. } => "git_push",
+            SyncOperation::GitFetch { .. } => "git_fetch",
+            SyncOperation::GitPull { .. } => "git_pull",
+            SyncOperation::DvcPush { .. } => "dvc_push",
+            SyncOperation::DvcPull { .. } => "dvc_pull",
+        }
+    }
+
+    /// Runs the operation again through its command, so it is recorded in
+    /// the activity log like the original attempt
+    fn run(self, app_handle: &AppHandle, repo_path: String) -> Result<(), String> {
+        let app_handle = app_handle.clone();
+        match self {
+            SyncOperation::GitPush { remote, branch } => {
+                remote::git_push(app_handle, repo_path, remote, branch, None).map(|_| ())
+            }
+            SyncOperation::GitFetch { remote } => {
+                remote::git_fetch(app_handle, repo_path, remote, None).map(|_| ())
+            }
+            SyncOperation::GitPull {
+                autostash,
+                pull_strategy,
+            } => git::git_pull(app_handle, repo_path, None, autostash, pull_strategy).map(|_| ()),
+            SyncOperation::DvcPush { targets, remote } => {
+                transfer::dvc_push(app_handle, repo_path, targets, remote, None).map(|_| ())
+            }
+            SyncOperation::DvcPull { targets, remote } => {
+                transfer::dvc_pull(app_handle, repo_path, targets, remote, None).map(|_| ())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PendingSyncOperation {
+    pub id: i64,
+    pub repo_path: String,
+    pub operation: SyncOperation,
+    /// "pending" while waiting for the connection, "failed" when a retry
+    /// reached the remote but failed for another reason
+    pub status: String,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: String,
+    pub last_attempt_at: Option<String>,
+}
+
+/// Queued operations live apart from the `transfer_operations` jobs: those
+/// track the objects of one DVC transfer so it can resume, while a queued
+/// operation is a whole command, git ones included, to run again later
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::SYNC_OPERATIONS_SCHEMA)
+}
+
+/// Whether an error means the remote could not be reached at all
+pub(crate) fn is_network_error(error: &str) -> bool {
+    let error = error.to_lowercase();
+    NETWORK_ERRORS
+        .iter()
+        .any(|fragment| error.contains(fragment))
+}
+
+fn encode(operation: &SyncOperation) -> Result<String, String> {
+    serde_json::to_string(operation).map_err(|e| format!("Failed to encode operation: {}", e))
+}
+
+/// Queues an operation; one already queued, even one that failed since,
+/// is pending again
+fn enqueue(
+    conn: &Connection,
+    repo_root: &str,
+    operation: &SyncOperation,
+    error: &str,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT INTO sync_operations (repo_path, operation, params, last_error)
+         VALUES (?1, ?2, ?3, ?4)
+         ON CONFLICT (repo_path, params)
+         DO UPDATE SET status = 'pending', last_error = excluded.last_error",
+        params![repo_root, operation.name(), encode(operation)?, error],
+    )
+    .map_err(|e| format!("Failed to queue {}: {}", operation.name(), e))?;
+    Ok(())
+}
+
+fn dequeue(conn: &Connection, repo_root: &str, operation: &SyncOperation) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM sync_operations WHERE repo_path = ?1 AND params = ?2",
+        params![repo_root, encode(operation)?],
+    )
+    .map_err(|e| format!("Failed to update sync queue: {}", e))?;
+    Ok(())
+}
+
+fn emit_changed(app_handle: &AppHandle) {
+    if let Err(e) = app_handle.emit(SYNC_OPERATIONS_EVENT, ()) {
+        warn!("Failed to emit sync queue change: {}", e);
+    }
+}
+
+/// Runs a network operation; when it fails because the network can't be
+/// reached it is queued to run again once the connection is back. A
+/// successful run drops an identical operation from the queue.
+pub(crate) fn queue_when_offline<T>(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    operation: SyncOperation,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    let result = run();
+    let Ok(repo_root) = transfer::repo_root(repo_path) else {
+        return result;
+    };
+    let repo_root = repo_root.to_string_lossy().to_string();
+    let queued = open_connection(app_handle).and_then(|conn| match &result {
+        Ok(_) => dequeue(&conn, &repo_root, &operation).map(|_| false),
+        Err(e) if is_network_error(e) => enqueue(&conn, &repo_root, &operation, e).map(|_| true),
+        Err(_) => Ok(false),
+    });
+    match queued {
+        Ok(true) => {
+            emit_changed(app_handle);
+            result.map_err(|e| {
+                format!(
+                    "{}. The remote can't be reached; {} is queued and runs again once the connection is back.",
+                    e,
+                    operation.name()
+                )
+            })
+        }
+        Ok(false) => result,
+        Err(e) => {
+            warn!("{}", e);
+            result
+        }
+    }
+}
+
+fn pending_operations(
+    conn: &Connection,
+    repo_root: Option<&str>,
+) -> Result<Vec<PendingSyncOperation>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, repo_path, params, status, attempts, last_error, created_at, last_attempt_at
+             FROM sync_operations
+             WHERE ?1 IS NULL OR repo_path = ?1
+             ORDER BY id",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+    let rows = stmt
+        .query_map(params![repo_root], |row| {
+            Ok((
+                row.get::<_, i64>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, String>(3)?,
+                row.get::<_, u32>(4)?,
+                row.get::<_, Option<String>>(5)?,
+                row.get::<_, String>(6)?,
+                row.get::<_, Option<String>>(7)?,
+            ))
+        })
+        .map_err(|e| format!("Failed to read sync queue: {}", e))?;
+
+    let mut operations = Vec::new();
+    for row in rows {
+        let (id, repo_path, params, status, attempts, last_error, created_at, last_attempt_at) =
+            row.map_err(|e| format!("Failed to read sync queue: {}", e))?;
+        match serde_json::from_str(&params) {
+            Ok(operation) => operations.push(PendingSyncOperation {
+                id,
+                repo_path,
+                operation,
+                status,
+                attempts,
+                last_error,
+                created_at,
+                last_attempt_at,
+            }),
+            Err(e) => warn!("Skipping unreadable sync operation {}: {}", id, e),
+        }
+    }
+    Ok(operations)
+}
+
+fn record_attempt(
+    conn: &Connection,
+    id: i64,
+    status: &str,
+    error: Option<&str>,
+) -> Result<(), String> {
+    conn.execute(
+        "UPDATE sync_operations SET status = ?2, attempts = attempts + 1, last_error = ?3,
+         last_attempt_at = CURRENT_TIMESTAMP
+         WHERE id = ?1",
+        params![id, status, error],
+    )
+    .map_err(|e| format!("Failed to update sync queue: {}", e))?;
+    Ok(())
+}
+
+/// Retries the queued operations in order, stopping at the first that still
+/// can't reach the network. Operations of projects open read-only wait until
+/// they are writable again.
+fn retry_pending(app_handle: &AppHandle) -> Result<(), String> {
+    let pending: Vec<PendingSyncOperation> = {
+        let conn = open_connection(app_handle)?;
+        pending_operations(&conn, None)?
+            .into_iter()
+            .filter(|operation| operation.status == "pending")
+            .filter(|operation| {
+                !readonly::is_read_only(app_handle, Path::new(&operation.repo_path))
+            })
+            .collect()
+    };
+    if pending.is_empty() {
+        return Ok(());
+    }
+
+    for operation in pending {
+        let name = operation.operation.name();
+        let result = operation
+            .operation
+            .run(app_handle, operation.repo_path.clone());
+        // A successful run already dropped the operation from the queue
+        let conn = open_connection(app_handle)?;
+        match result {
+            Ok(()) => info!(name, repo = %operation.repo_path, "Ran queued operation"),
+            Err(e) if is_network_error(&e) => {
+                record_attempt(&conn, operation.id, "pending", Some(&e))?;
+                break;
+            }
+            Err(e) => {
+                warn!("Queued {} failed: {}", name, e);
+                record_attempt(&conn, operation.id, "failed", Some(&e))?;
+            }
+        }
+        emit_changed(app_handle);
+    }
+    Ok(())
+}
+
+/// Starts the background thread that retries queued operations
+pub fn start(app_handle: &AppHandle) {
+    let app_handle = app_handle.clone();
+    thread::spawn(move || loop {
+        thread::sleep(RETRY_INTERVAL);
+        if let Err(e) = retry_pending(&app_handle) {
+            warn!("Failed to retry queued operations: {}", e);
+        }
+    });
+}
+
+/// Pushes, pulls and fetches queued while the network was unreachable, for
+/// one repository or all of them
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_pending_sync_operations(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Vec<PendingSyncOperation>, String> {
+    let repo_root = repo_path
+        .map(|path| transfer::repo_root(&path).map(|root| root.to_string_lossy().to_string()))
+        .transpose()?;
+    let conn = open_connection(&app_handle)?;
+    pending_operations(&conn, repo_root.as_deref())
+}
+
+/// Drops a queued operation without running it
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn cancel_sync_operation(app_handle: AppHandle, id: i64) -> Result<(), String> {
+    let conn = open_connection(&app_handle)?;
+    let removed = conn
+        .execute("DELETE FROM sync_operations WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to update sync queue: {}", e))?;
+    if removed == 0 {
+        return Err(format!("No queued operation {}", id));
+    }
+    emit_changed(&app_handle);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failed_sync_operations_are_pending_again_when_queued() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(db::SYNC_OPERATIONS_SCHEMA).unwrap();
+        let operation = SyncOperation::GitFetch { remote: None };
+
+        enqueue(&conn, "/repo", &operation, "could not resolve host").unwrap();
+        let queued = pending_operations(&conn, None).unwrap();
+        record_attempt(&conn, queued[0].id, "failed", Some("denied")).unwrap();
+
+        enqueue(&conn, "/repo", &operation, "connection refused").unwrap();
+        let queued = pending_operations(&conn, None).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].status, "pending");
+        assert_eq!(queued[0].last_error.as_deref(), Some("connection refused"));
+    }
+}
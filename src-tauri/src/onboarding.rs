@@ -0,0 +1,314 @@
+use git2::{Repository, Status};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+use walkdir::WalkDir;
+
+use crate::hooks::HookSettings;
+use crate::{dvc, dvc_config, dvcfile, health};
+
+/// Entries every DVC project wants ignored regardless of the data it tracks
+const DEFAULT_IGNORES: [&str; 3] = [".DS_Store", "__pycache__/", ".ipynb_checkpoints/"];
+
+#[derive(Debug, Serialize)]
+pub struct RemoteInfo {
+    pub name: String,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LargeFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SetupStep {
+    /// "init-git", "initial-commit", "init-dvc", "gitignore", "track-large-files"
+    /// or "add-dvc-remote"
+    pub id: String,
+    pub description: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryAnalysis {
+    pub path: String,
+    pub is_git_repo: bool,
+    /// Root of the enclosing repository, which may be a parent of `path`
+    pub git_root: Option<String>,
+    pub has_commits: bool,
+    pub is_dvc_repo: bool,
+    pub git_remotes: Vec<RemoteInfo>,
+    pub dvc_remotes: Vec<RemoteInfo>,
+    /// Files over the large-file limit that are neither in git nor ignored
+    pub large_untracked_files: Vec<LargeFile>,
+    pub suggested_steps: Vec<SetupStep>,
+}
+
+fn step(id: &str, description: impl Into<String>) -> SetupStep {
+    SetupStep {
+        id: id.to_string(),
+        description: description.into(),
+    }
+}
+
+fn git_remotes(repo: &Repository) -> Vec<RemoteInfo> {
+    let Ok(names) = repo.remotes() else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .flatten()
+        .filter_map(|name| {
+            let remote = repo.find_remote(name).ok()?;
+            Some(RemoteInfo {
+                name: name.to_string(),
+                url: remote.url().unwrap_or_default().to_string(),
+            })
+        })
+        .collect()
+}
+
+fn dvc_remotes(root: &Path) -> Vec<RemoteInfo> {
+    let Ok(config) = dvc_config::load(root) else {
+        return Vec::new();
+    };
+    config
+        .remote_names()
+        .into_iter()
+        .filter_map(|name| {
+            let remote = dvc_config::remote(root, Some(&name)).ok()?;
+            Some(RemoteInfo {
+                name,
+                url: remote.url,
+            })
+        })
+        .collect()
+}
+
+fn large_untracked_files(root: &Path, repo: Option<&Repository>, max_size: u64) -> Vec<LargeFile> {
+    WalkDir::new(root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            !(entry.file_type().is_dir() && (name == ".git" || name == ".dvc"))
+        })
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .filter_map(|entry| {
+            let size = entry.metadata().ok()?.len();
+            if size <= max_size {
+                return None;
+            }
+            let relative = entry.path().strip_prefix(root).ok()?;
+            if let Some(repo) = repo {
+                // Files git already tracks or ignores (e.g. DVC data) are settled
+                let status = repo.status_file(relative).ok()?;
+                if !status.contains(Status::WT_NEW) {
+                    return None;
+                }
+            }
+            Some(LargeFile {
+                path: dvcfile::to_git_path(relative),
+                size,
+            })
+        })
+        .collect()
+}
+
+fn analyze(path: &str) -> Result<DirectoryAnalysis, String> {
+    let dir = Path::new(path);
+    if !dir.is_dir() {
+        return Err(format!("{} is not a directory", path));
+    }
+
+    let repo = Repository::discover(dir).ok();
+    let root = repo
+        .as_ref()
+        .and_then(|repo| repo.workdir())
+        .map(|root| root.to_path_buf())
+        .unwrap_or_else(|| dir.to_path_buf());
+    let has_commits = repo.as_ref().is_some_and(|repo| repo.head().is_ok());
+    let is_dvc_repo = root.join(".dvc").is_dir();
+    let git_remotes = repo.as_ref().map(git_remotes).unwrap_or_default();
+    let dvc_remotes = if is_dvc_repo {
+        dvc_remotes(&root)
+    } else {
+        Vec::new()
+    };
+    let max_size = HookSettings::default().max_file_size_kib * 1024;
+    let large_untracked_files = large_untracked_files(&root, repo.as_ref(), max_size);
+
+    let mut suggested_steps = Vec::new();
+    if repo.is_none() {
+        suggested_steps.push(step("init-git", "Initialize a git repository"));
+    }
+    if !has_commits {
+        suggested_steps.push(step("initial-commit", "Create an initial commit"));
+    }
+    if !is_dvc_repo {
+        suggested_steps.push(step("init-dvc", "Initialize DVC"));
+    }
+    if !root.join(".gitignore").exists() {
+        suggested_steps.push(step("gitignore", "Add a .gitignore with common entries"));
+    }
+    if !large_untracked_files.is_empty() {
+        suggested_steps.push(step(
+            "track-large-files",
+            format!(
+                "Track {} large file(s) with DVC instead of git",
+                large_untracked_files.len()
+            ),
+        ));
+    }
+    if dvc_remotes.is_empty() {
+        suggested_steps.push(step(
+            "add-dvc-remote",
+            "Configure a DVC remote to share data",
+        ));
+    }
+
+    Ok(DirectoryAnalysis {
+        path: path.to_string(),
+        is_git_repo: repo.is_some(),
+        git_root: repo.as_ref().map(|_| root.to_string_lossy().to_string()),
+        has_commits,
+        is_dvc_repo,
+        git_remotes,
+        dvc_remotes,
+        large_untracked_files,
+        suggested_steps,
+    })
+}
+
+/// Describes what a folder already has (git, DVC, remotes, large untracked
+/// files) and the steps needed to manage it with the app
+#[command]
+#[instrument(err(Debug))]
+pub fn analyze_directory(path: String) -> Result<DirectoryAnalysis, String> {
+    analyze(&path)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct AdoptOptions {
+    pub init_dvc: bool,
+    pub update_gitignore: bool,
+    /// Repository relative paths to track with DVC
+    pub track_files: Vec<String>,
+}
+
+impl Default for AdoptOptions {
+    fn default() -> Self {
+        Self {
+            init_dvc: true,
+            update_gitignore: true,
+            track_files: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdoptResult {
+    /// Steps performed, in order
+    pub actions: Vec<String>,
+    /// Files that could not be tracked with DVC
+    pub failed: Vec<TrackFailure>,
+    pub analysis: DirectoryAnalysis,
+}
+
+/// Appends the default entries missing from the root `.gitignore`
+fn update_gitignore(root: &Path) -> Result<usize, String> {
+    let path = root.join(".gitignore");
+    let mut content = fs::read_to_string(&path).unwrap_or_default();
+    let missing: Vec<&str> = DEFAULT_IGNORES
+        .into_iter()
+        .filter(|entry| !content.lines().any(|line| line.trim() == *entry))
+        .collect();
+    if missing.is_empty() {
+        return Ok(0);
+    }
+
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    for entry in &missing {
+        content.push_str(entry);
+        content.push('\n');
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to update .gitignore: {}", e))?;
+    Ok(missing.len())
+}
+
+/// Brings an existing folder or repository up to a working git + DVC setup
+/// without touching anything already in place: existing history, config and
+/// ignore entries are kept as they are
+#[command]
+#[instrument(skip(app_handle, options), err(Debug))]
+pub fn adopt_existing_project(
+    app_handle: AppHandle,
+    path: String,
+    options: AdoptOptions,
+) -> Result<AdoptResult, String> {
+    let mut actions = Vec::new();
+
+    let repo = match Repository::discover(&path) {
+        Ok(repo) => repo,
+        Err(_) => {
+            actions.push("Initialized a git repository".to_string());
+            Repository::init(&path)
+                .map_err(|e| format!("Failed to initialize git repository: {}", e))?
+        }
+    };
+    let root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let root_str = root.to_string_lossy().to_string();
+
+    if options.update_gitignore {
+        let added = update_gitignore(&root)?;
+        if added > 0 {
+            actions.push(format!("Added {} entries to .gitignore", added));
+        }
+    }
+    if dvc::ensure_initial_commit(&repo, &root)? {
+        actions.push("Created an initial commit".to_string());
+    }
+
+    if options.init_dvc {
+        let dvc_dir = root.join(".dvc");
+        if !dvc_dir.is_dir() {
+            dvc::run_dvc_init(&app_handle, &root_str)?;
+            actions.push("Initialized DVC".to_string());
+        } else if !dvc_config::config_path(&root).exists() {
+            health::repair_dvc_config(root_str.clone())?;
+            actions.push("Restored the missing DVC config".to_string());
+        }
+    }
+
+    let mut failed = Vec::new();
+    for file in options.track_files {
+        match dvc::add_dvc_file(app_handle.clone(), &root_str, &file) {
+            Ok(_) => actions.push(format!("Tracked {} with DVC", file)),
+            Err(error) => failed.push(TrackFailure {
+                path: file,
+                error: error.message,
+            }),
+        }
+    }
+
+    Ok(AdoptResult {
+        actions,
+        failed,
+        analysis: analyze(&path)?,
+    })
+}
@@ -0,0 +1,204 @@
+use git2::{Config, ErrorCode, Repository};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+const SAFE_DIRECTORY: &str = "safe.directory";
+
+/// Filesystem types of network mounts, as listed in `/proc/mounts`
+const NETWORK_FILESYSTEMS: &[&str] = &[
+    "nfs",
+    "nfs4",
+    "cifs",
+    "smb3",
+    "smbfs",
+    "fuse.sshfs",
+    "9p",
+    "afs",
+    "ceph",
+    "glusterfs",
+    "fuse.glusterfs",
+    "lustre",
+];
+
+#[derive(Debug, Serialize)]
+pub struct RepositoryAccess {
+    /// Repository root, or the given path when no repository was found
+    pub repo_path: String,
+    /// "ok", "owned_by_other_user" or "not_a_repository"
+    pub status: String,
+    /// Numeric id of the user owning the repository directory (Unix only)
+    pub owner_uid: Option<u32>,
+    pub current_user: Option<String>,
+    /// Type of the filesystem the repository is on, where it can be found
+    pub filesystem: Option<String>,
+    /// Whether the repository is on a network share
+    pub shared_filesystem: bool,
+    /// Whether the repository is listed in git's `safe.directory`
+    pub trusted: bool,
+    pub message: Option<String>,
+}
+
+/// Error for a repository git refused to open, explaining ownership refusals
+/// instead of passing on libgit2's message
+pub(crate) fn open_error(repo_path: &str, e: git2::Error) -> String {
+    if e.code() == ErrorCode::Owner {
+        format!(
+            "{} is owned by another user; trust it to open it",
+            repo_path
+        )
+    } else {
+        format!("Failed to open repository: {}", e)
+    }
+}
+
+/// Nearest directory containing `.git`, found without git so it works for
+/// repositories git refuses to open
+fn find_root(path: &Path) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(".git").exists())
+        .map(Path::to_path_buf)
+}
+
+/// Path as `safe.directory` expects it: forward slashes, no trailing one
+fn safe_directory_value(root: &Path) -> String {
+    root.to_string_lossy()
+        .replace('\\', "/")
+        .trim_end_matches('/')
+        .to_string()
+}
+
+fn safe_directories() -> Vec<String> {
+    let Ok(config) = Config::open_default() else {
+        return Vec::new();
+    };
+    let Ok(entries) = config.multivar(SAFE_DIRECTORY, None) else {
+        return Vec::new();
+    };
+    let mut values = Vec::new();
+    let _ = entries.for_each(|entry| {
+        if let Some(value) = entry.value() {
+            values.push(value.to_string());
+        }
+    });
+    values
+}
+
+fn is_trusted(root: &Path) -> bool {
+    let value = safe_directory_value(root);
+    safe_directories()
+        .iter()
+        .any(|safe| safe == "*" || *safe == value)
+}
+
+#[cfg(unix)]
+fn owner_uid(path: &Path) -> Option<u32> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path).ok().map(|metadata| metadata.uid())
+}
+
+#[cfg(not(unix))]
+fn owner_uid(_path: &Path) -> Option<u32> {
+    None
+}
+
+/// Type of the filesystem mounted at the longest mount point containing `path`
+#[cfg(target_os = "linux")]
+fn filesystem_type(path: &Path) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    let mounts = std::fs::read_to_string("/proc/mounts").ok()?;
+    mounts
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let mount_point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?;
+            path.starts_with(&mount_point)
+                .then(|| (mount_point.len(), fs_type.to_string()))
+        })
+        .max_by_key(|(length, _)| *length)
+        .map(|(_, fs_type)| fs_type)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn filesystem_type(_path: &Path) -> Option<String> {
+    None
+}
+
+fn is_shared_filesystem(path: &Path, filesystem: Option<&str>) -> bool {
+    // UNC paths (\\server\share) are network shares on Windows
+    path.to_string_lossy().starts_with("\\\\")
+        || filesystem.is_some_and(|filesystem| NETWORK_FILESYSTEMS.contains(&filesystem))
+}
+
+fn access(repo_path: &str) -> RepositoryAccess {
+    let path = Path::new(repo_path);
+    let root = find_root(path);
+    let checked = root.as_deref().unwrap_or(path);
+    let filesystem = filesystem_type(checked);
+    let mut access = RepositoryAccess {
+        repo_path: checked.to_string_lossy().to_string(),
+        status: "ok".to_string(),
+        owner_uid: owner_uid(checked),
+        current_user: std::env::var("USER")
+            .or_else(|_| std::env::var("USERNAME"))
+            .ok(),
+        shared_filesystem: is_shared_filesystem(checked, filesystem.as_deref()),
+        filesystem,
+        trusted: root.as_deref().is_some_and(is_trusted),
+        message: None,
+    };
+    match Repository::discover(repo_path) {
+        Ok(_) => {}
+        Err(e) if e.code() == ErrorCode::Owner => {
+            access.status = "owned_by_other_user".to_string();
+            access.message = Some(format!(
+                "{} belongs to another user. Git only opens it once you trust it.",
+                access.repo_path
+            ));
+        }
+        Err(e) => {
+            access.status = "not_a_repository".to_string();
+            access.message = Some(e.message().to_string());
+        }
+    }
+    access
+}
+
+/// Whether git will open a repository, and if not because another user owns
+/// it (common on shared drives), the details to ask the user to trust it
+#[command]
+#[instrument(err(Debug))]
+pub fn check_repository_access(repo_path: String) -> Result<RepositoryAccess, String> {
+    Ok(access(&repo_path))
+}
+
+/// Trusts a repository owned by another user by adding it to git's global
+/// `safe.directory` list, like `git config --global --add safe.directory`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn trust_repository(
+    app_handle: AppHandle,
+    repo_path: String,
+) -> Result<RepositoryAccess, String> {
+    let root = find_root(Path::new(&repo_path))
+        .ok_or_else(|| format!("{} is not inside a git repository", repo_path))?;
+    if !is_trusted(&root) {
+        let path = match Config::find_global() {
+            Ok(path) => path,
+            Err(_) => app_handle
+                .path()
+                .home_dir()
+                .map_err(|e| format!("Failed to get home directory: {}", e))?
+                .join(".gitconfig"),
+        };
+        let mut config =
+            Config::open(&path).map_err(|e| format!("Failed to open global git config: {}", e))?;
+        // A pattern matching no existing value adds a new one
+        config
+            .set_multivar(SAFE_DIRECTORY, "^$", &safe_directory_value(&root))
+            .map_err(|e| format!("Failed to write git config: {}", e))?;
+    }
+    Ok(access(&repo_path))
+}
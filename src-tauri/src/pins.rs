@@ -0,0 +1,384 @@
+use git2::{BranchType, Repository};
+use rusqlite::params;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+use walkdir::WalkDir;
+
+use crate::db::{self, PooledConnection};
+use crate::dvcfile::{self, DvcOut};
+use crate::transfer::{self, TransferReport};
+use crate::{audit, branch_compare, dvc_config, history, remote, settings};
+
+/// A dataset version kept in the local cache whatever branch is checked out
+#[derive(Debug, Serialize)]
+pub struct DataPin {
+    /// Path of the DVC output relative to the repository root
+    pub path: String,
+    /// Revision as the user gave it
+    pub rev: String,
+    /// Commit the revision pointed to when pinned
+    pub commit_id: String,
+    /// DVC hash of the data at that commit (`.dir` suffix for directories)
+    pub md5: String,
+    pub pinned_at: String,
+    /// Whether every object of the version is in the local cache
+    pub cached: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PinResult {
+    pub pin: DataPin,
+    /// Download of the objects that weren't cached yet
+    pub report: Option<TransferReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CacheGcReport {
+    pub dry_run: bool,
+    /// Objects referenced by no pointer of the workspace, a local branch or a pin
+    pub removed: usize,
+    pub removed_bytes: u64,
+    /// Objects kept only because a pin references them
+    pub kept_for_pins: usize,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::DATA_PINS_SCHEMA)
+}
+
+fn pinned_output(path: &str, md5: &str) -> DvcOut {
+    DvcOut {
+        md5: Some(md5.to_string()),
+        path: path.to_string(),
+        ..DvcOut::default()
+    }
+}
+
+fn is_cached(repo_root: &Path, out: &DvcOut) -> bool {
+    transfer::output_objects(repo_root, std::slice::from_ref(out))
+        .iter()
+        .all(|object| object.cache_path.exists())
+}
+
+/// Pins of a repository, oldest first
+fn load_pins(app_handle: &AppHandle, repo_root: &Path) -> Result<Vec<DataPin>, String> {
+    let conn = open_connection(app_handle)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT path, rev, commit_id, md5, pinned_at FROM data_pins
+             WHERE project = ?1 ORDER BY pinned_at, path",
+        )
+        .map_err(|e| format!("Failed to prepare pin query: {}", e))?;
+    let rows = stmt
+        .query_map(params![settings::project_key(repo_root)], |row| {
+            Ok(DataPin {
+                path: row.get(0)?,
+                rev: row.get(1)?,
+                commit_id: row.get(2)?,
+                md5: row.get(3)?,
+                pinned_at: row.get(4)?,
+                cached: false,
+            })
+        })
+        .map_err(|e| format!("Failed to query pins: {}", e))?;
+    let mut pins = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read pin row: {}", e))?;
+    for pin in &mut pins {
+        pin.cached = is_cached(repo_root, &pinned_output(&pin.path, &pin.md5));
+    }
+    Ok(pins)
+}
+
+/// DVC outputs of every pinned version, whose cache objects gc has to keep
+fn pinned_outputs(app_handle: &AppHandle, repo_root: &Path) -> Result<Vec<DvcOut>, String> {
+    Ok(load_pins(app_handle, repo_root)?
+        .iter()
+        .map(|pin| pinned_output(&pin.path, &pin.md5))
+        .collect())
+}
+
+/// Finds the DVC output tracking `path` at `rev`. A path inside a tracked
+/// directory resolves to the whole directory.
+fn resolve_pin(
+    repo_root: &Path,
+    path: &str,
+    rev: &str,
+) -> Result<(String, String, String), String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let commit = repo
+        .revparse_single(rev)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find {}: {}", rev, e))?;
+    let relative = dvcfile::repo_relative_path(repo_root, Path::new(path))?;
+    let relative = if relative.extension().and_then(|e| e.to_str()) == Some("dvc") {
+        relative.with_extension("")
+    } else {
+        relative
+    };
+
+    let (out, inner) = history::dvc_out_at(&repo, &commit, &relative)
+        .ok_or_else(|| format!("{} is not tracked by DVC at {}", path, rev))?;
+    let md5 = out
+        .md5
+        .ok_or_else(|| format!("{} has no hash at {}", path, rev))?;
+    let mut output = relative;
+    for _ in inner.components() {
+        output.pop();
+    }
+    Ok((dvcfile::to_git_path(&output), commit.id().to_string(), md5))
+}
+
+fn pin(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    path: &str,
+    rev: &str,
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<PinResult, String> {
+    let (path, commit_id, md5) = resolve_pin(repo_root, path, rev)?;
+    open_connection(app_handle)?
+        .execute(
+            "INSERT OR REPLACE INTO data_pins (project, path, rev, commit_id, md5, pinned_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, CURRENT_TIMESTAMP)",
+            params![settings::project_key(repo_root), path, rev, commit_id, md5],
+        )
+        .map_err(|e| format!("Failed to save pin: {}", e))?;
+
+    // The pin is kept when the download fails, so a later pull or prefetch
+    // completes it
+    let output = pinned_output(&path, &md5);
+    let report = if is_cached(repo_root, &output) {
+        None
+    } else {
+        Some(transfer::prefetch_outputs(
+            app_handle,
+            repo_root,
+            std::slice::from_ref(&output),
+            &[format!("{}@{}", path, rev)],
+            remote,
+            operation_id,
+        )?)
+    };
+
+    let pin = load_pins(app_handle, repo_root)?
+        .into_iter()
+        .find(|pin| pin.path == path && pin.rev == rev)
+        .ok_or_else(|| format!("Failed to find pin of {} at {}", path, rev))?;
+    Ok(PinResult { pin, report })
+}
+
+/// Objects of the outputs of the workspace pointers, HEAD and every local
+/// branch
+fn referenced_objects(repo_root: &Path) -> Result<HashSet<PathBuf>, String> {
+    let mut outs = Vec::new();
+    // An unreadable pointer could reference anything, so it stops gc
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        outs.extend(dvcfile::read_dvc_file(&pointer)?.outs);
+    }
+
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut revs = vec!["HEAD".to_string()];
+    let branches = repo
+        .branches(Some(BranchType::Local))
+        .map_err(|e| format!("Failed to list branches: {}", e))?;
+    for (branch, _) in branches.filter_map(|branch| branch.ok()) {
+        if let Ok(Some(name)) = branch.name() {
+            revs.push(name.to_string());
+        }
+    }
+    for rev in revs {
+        // An unborn HEAD has no tree yet
+        let Ok(tree) = branch_compare::branch_tree(&repo, &rev) else {
+            continue;
+        };
+        outs.extend(branch_compare::tracked_outputs(&repo, &tree)?.into_values());
+    }
+
+    Ok(transfer::output_objects(repo_root, &outs)
+        .into_iter()
+        .map(|object| object.cache_path)
+        .collect())
+}
+
+/// Files in the cache that look like objects: a file named by the rest of
+/// the hash in a directory named by its first two characters
+fn is_object(path: &Path) -> bool {
+    let is_hex = |name: &str| !name.is_empty() && name.chars().all(|c| c.is_ascii_hexdigit());
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    let prefix = path
+        .parent()
+        .and_then(|parent| parent.file_name())
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    prefix.len() == 2 && is_hex(prefix) && is_hex(name.trim_end_matches(".dir"))
+}
+
+/// Whether the workspace has pipeline stages, whose outputs are recorded in
+/// `dvc.lock` files rather than pointers
+fn has_pipeline_outputs(repo_root: &Path) -> bool {
+    WalkDir::new(repo_root)
+        .into_iter()
+        .filter_entry(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            !(entry.file_type().is_dir() && (name == ".git" || name == ".dvc"))
+        })
+        .filter_map(|e| e.ok())
+        .any(|entry| entry.file_type().is_file() && entry.file_name() == "dvc.lock")
+}
+
+fn gc(app_handle: &AppHandle, repo_root: &Path, dry_run: bool) -> Result<CacheGcReport, String> {
+    let config = dvc_config::load(repo_root)?;
+    if config.get("cache", "dir").is_some() || config.get("cache", "shared").is_some() {
+        return Err(
+            "The cache is configured outside the project and may hold other projects' data"
+                .to_string(),
+        );
+    }
+    if has_pipeline_outputs(repo_root) {
+        return Err(
+            "The project has pipeline outputs; use `dvc gc` to clean its cache".to_string(),
+        );
+    }
+
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let referenced = referenced_objects(repo_root)?;
+    let pinned: HashSet<PathBuf> =
+        transfer::output_objects(repo_root, &pinned_outputs(app_handle, repo_root)?)
+            .into_iter()
+            .map(|object| object.cache_path)
+            .collect();
+
+    let mut report = CacheGcReport {
+        dry_run,
+        removed: 0,
+        removed_bytes: 0,
+        kept_for_pins: pinned.difference(&referenced).count(),
+    };
+    // The run cache lives next to the objects and isn't referenced by pointers
+    for entry in WalkDir::new(&cache_dir)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() != 1 || entry.file_name() != "runs")
+        .filter_map(|e| e.ok())
+    {
+        let path = entry.path();
+        if !entry.file_type().is_file()
+            || !is_object(path)
+            || referenced.contains(path)
+            || pinned.contains(path)
+        {
+            continue;
+        }
+        let size = entry.metadata().map(|metadata| metadata.len()).unwrap_or(0);
+        if !dry_run {
+            fs::remove_file(path)
+                .map_err(|e| format!("Failed to remove {}: {}", path.display(), e))?;
+        }
+        report.removed += 1;
+        report.removed_bytes += size;
+    }
+    Ok(report)
+}
+
+/// Pins the version of a DVC-tracked path at a revision and downloads its
+/// objects into the cache, so checking the version out never waits for a
+/// pull and `gc_cache` keeps it after every branch referencing it is gone
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn pin_data_version(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    rev: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<PinResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "pin_data_version",
+        json!({ "path": path, "rev": rev, "remote": remote }),
+        || {
+            pin(
+                &app_handle,
+                &transfer::repo_root(&repo_path)?,
+                &path,
+                &rev,
+                remote.as_deref(),
+                remote::operation_id(operation_id),
+            )
+        },
+    )
+}
+
+/// Removes a pin; the objects stay in the cache until the next `gc_cache`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn unpin_data_version(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+    rev: String,
+) -> Result<(), String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "unpin_data_version",
+        json!({ "path": path, "rev": rev }),
+        || {
+            let repo_root = transfer::repo_root(&repo_path)?;
+            let relative = dvcfile::repo_relative_path(&repo_root, Path::new(&path))?;
+            let removed = open_connection(&app_handle)?
+                .execute(
+                    "DELETE FROM data_pins WHERE project = ?1 AND path = ?2 AND rev = ?3",
+                    params![
+                        settings::project_key(&repo_root),
+                        dvcfile::to_git_path(&relative),
+                        rev
+                    ],
+                )
+                .map_err(|e| format!("Failed to remove pin: {}", e))?;
+            if removed == 0 {
+                return Err(format!("{} is not pinned at {}", path, rev));
+            }
+            Ok(())
+        },
+    )
+}
+
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_data_pins(app_handle: AppHandle, repo_path: String) -> Result<Vec<DataPin>, String> {
+    load_pins(&app_handle, &transfer::repo_root(&repo_path)?)
+}
+
+/// Removes cache objects no pointer of the workspace, HEAD, a local branch
+/// or a pin references. Only reports what would be removed unless `dry_run`
+/// is false.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn gc_cache(
+    app_handle: AppHandle,
+    repo_path: String,
+    dry_run: Option<bool>,
+) -> Result<CacheGcReport, String> {
+    let dry_run = dry_run.unwrap_or(true);
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "gc_cache",
+        json!({ "dry_run": dry_run }),
+        || gc(&app_handle, &transfer::repo_root(&repo_path)?, dry_run),
+    )
+}
@@ -0,0 +1,148 @@
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashMap;
+use std::process::Command;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter};
+use tracing::{instrument, warn};
+
+use crate::watchdog::{self, CommandError, FailureKind, ProcessError};
+use crate::{audit, remote, transfer};
+
+pub const COMMAND_OUTPUT_EVENT: &str = "command://output";
+
+/// Tools that may be run from the app; anything else is refused
+const ALLOWED_PROGRAMS: &[&str] = &["git", "dvc", "python", "python3"];
+
+const MAX_TIMEOUT_SECS: u64 = 60 * 60;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandOutputLine {
+    pub operation_id: String,
+    /// "stdout" or "stderr"
+    pub stream: String,
+    pub line: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommandRunResult {
+    pub operation_id: String,
+    /// `None` when the process was killed, e.g. on timeout
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+}
+
+fn run(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    cmd: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    timeout: Option<Duration>,
+    operation_id: String,
+) -> Result<CommandRunResult, CommandError> {
+    if !ALLOWED_PROGRAMS.contains(&cmd) {
+        return Err(format!(
+            "{} is not allowed; only {} can be run",
+            cmd,
+            ALLOWED_PROGRAMS.join(", ")
+        )
+        .into());
+    }
+    let repo_root = transfer::repo_root(repo_path)?;
+    let mut limits = watchdog::limits(app_handle, &repo_root, "project_command");
+    if let Some(timeout) = timeout {
+        limits.timeout = timeout;
+    }
+
+    let emitter = app_handle.clone();
+    let output_id = operation_id.clone();
+    let started = Instant::now();
+    let status = watchdog::stream(
+        Command::new(cmd)
+            .args(args)
+            .envs(env)
+            .current_dir(&repo_root),
+        cmd,
+        &limits,
+        move |stream, line| {
+            let output = CommandOutputLine {
+                operation_id: output_id.clone(),
+                stream: stream.to_string(),
+                line,
+            };
+            if let Err(e) = emitter.emit(COMMAND_OUTPUT_EVENT, output) {
+                warn!("Failed to emit command output: {}", e);
+            }
+        },
+    );
+
+    let (exit_code, timed_out) = match status {
+        Ok(status) => (status.code(), false),
+        Err(e @ ProcessError::Timeout { .. }) => {
+            warn!("{}", e);
+            (None, true)
+        }
+        Err(e) => return Err(e.into()),
+    };
+    Ok(CommandRunResult {
+        operation_id,
+        exit_code,
+        timed_out,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+/// Runs git, dvc or python in the project directory, streaming its output
+/// line by line as events. `env` is added to the app's environment and the
+/// process is killed after `timeout_secs`, by default the project's watchdog
+/// timeout. A timeout is reported in the result, other failures by the kind
+/// of the error.
+#[command(async)]
+#[instrument(skip(app_handle, env), err(Debug))]
+pub fn run_project_command(
+    app_handle: AppHandle,
+    repo_path: String,
+    cmd: String,
+    args: Vec<String>,
+    env: Option<HashMap<String, String>>,
+    timeout_secs: Option<u64>,
+    operation_id: Option<String>,
+) -> Result<CommandRunResult, CommandError> {
+    if let Some(timeout) = timeout_secs {
+        if !(1..=MAX_TIMEOUT_SECS).contains(&timeout) {
+            return Err(format!(
+                "The timeout must be between 1 and {} seconds",
+                MAX_TIMEOUT_SECS
+            )
+            .into());
+        }
+    }
+    let operation_id = remote::operation_id(operation_id);
+
+    // Only variable names are recorded since values may hold credentials
+    let env = env.unwrap_or_default();
+    let mut env_names: Vec<&String> = env.keys().collect();
+    env_names.sort();
+    let failure = FailureKind::default();
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "run_project_command",
+        json!({ "cmd": cmd, "args": args, "env": env_names, "operation_id": operation_id }),
+        || {
+            run(
+                &app_handle,
+                &repo_path,
+                &cmd,
+                &args,
+                &env,
+                timeout_secs.map(Duration::from_secs),
+                operation_id.clone(),
+            )
+            .map_err(|e| failure.record(e))
+        },
+    )
+    .map_err(|message| failure.error(message))
+}
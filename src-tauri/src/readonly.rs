@@ -0,0 +1,280 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::ipc::{Invoke, InvokeBody};
+use tauri::{command, AppHandle, Manager, Runtime, State};
+use tracing::instrument;
+
+use crate::{transfer, trash};
+
+/// Arguments naming a repository or a path inside one, as the frontend
+/// sends them (camelCase)
+const PATH_ARGS: &[&str] = &["repoPath", "path", "oldPath", "newPath", "project"];
+
+/// Arguments naming a file the command writes, checked for every command
+/// since even a read-only one may export into a project
+const OUTPUT_ARGS: &[&str] = &["outputPath"];
+
+/// Commands that never modify a repository, its `.git` or `.dvc` directories
+/// or the project's settings. Everything else is refused for a project open
+/// read-only, so a new command is blocked until it is listed here.
+const READ_ONLY_COMMANDS: &[&str] = &[
+    "get_file_tree_structure",
+    "preview_ignore_effect",
+    "get_file_binary",
+    "detect_file_type",
+    "inspect_tabular_schema",
+    "diff_tabular_versions",
+    "diff_image_directory",
+    "diff_notebook",
+    "list_project_templates",
+    "add_selected_file",
+    "remove_selected_file",
+    "get_selected_files",
+    "clear_selected_files",
+    "get_selection_states",
+    "get_expanded_selection",
+    "get_files_status",
+    "get_selected_files_status",
+    "suggest_commit_message",
+    "list_commit_templates",
+    "list_trash",
+    "get_dvc_version",
+    "git_status",
+    "git_file_diff",
+    "get_git_identity",
+    "check_git_configuration",
+    "get_commit_signing",
+    "list_signing_keys",
+    "verify_commit_signature",
+    "git_list_branches",
+    "git_current_branch",
+    "git_diff_stats",
+    "list_snapshots",
+    "compare_branches_data",
+    "verify_data_integrity",
+    "find_duplicate_files",
+    "search_files",
+    "refresh_file_index",
+    "close_file_index",
+    "get_sparse_checkout_patterns",
+    "git_list_submodules",
+    "git_worktree_list",
+    "detect_python_environments",
+    "get_dvc_interpreter",
+    "diagnose_dvc_scripts",
+    "list_ssh_keys",
+    "get_public_key",
+    "list_pull_requests",
+    "list_data_pins",
+    "check_remote_status",
+    "estimate_push",
+    "list_pending_transfers",
+    "get_pending_sync_operations",
+    "get_project_settings",
+    "get_cache_link_settings",
+    "list_locks",
+    "file_history",
+    "get_path_owners",
+    "get_size_history",
+    "list_conflicts",
+    "list_dvc_conflicts",
+    "get_rebase_plan",
+    "list_hooks",
+    "scan_for_sensitive_data",
+    "scan_staged_secrets",
+    "list_lfs_files",
+    "check_repository_health",
+    "get_repository_lock_status",
+    "analyze_directory",
+    "get_activity_log",
+    "get_performance_stats",
+    "export_data_inventory",
+    "query_datasets_by_metadata",
+    "list_models",
+    "get_model_lineage",
+    "export_experiment_report",
+    "get_repo_statistics",
+    "test_notification",
+    "list_crash_reports",
+    "get_crash_report",
+    "get_app_logs",
+    "export_logs",
+    "open_project_readonly",
+    "close_readonly_project",
+    "is_project_readonly",
+    "check_repository_access",
+    // Only writes the global git config, so a shared project can be browsed
+    "trust_repository",
+];
+
+/// Repository roots opened read-only in this session
+#[derive(Debug, Default)]
+pub struct ReadOnlyProjects {
+    roots: Mutex<HashSet<PathBuf>>,
+}
+
+pub type ReadOnlyState = ReadOnlyProjects;
+
+impl ReadOnlyProjects {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The read-only project containing `path`, which may not exist yet
+    /// (e.g. the destination of a move)
+    fn containing(&self, path: &Path) -> Option<PathBuf> {
+        let path = path
+            .ancestors()
+            .find_map(|ancestor| ancestor.canonicalize().ok())?;
+        let roots = self.roots.lock().ok()?;
+        roots.iter().find(|root| path.starts_with(root)).cloned()
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadOnlySession {
+    pub repo_path: String,
+    pub read_only: bool,
+}
+
+fn canonical_root(repo_path: &str) -> Result<PathBuf, String> {
+    let root = transfer::repo_root(repo_path)?;
+    root.canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", root.display(), e))
+}
+
+/// Whether `path` lies in a project open read-only, for writes the app starts
+/// on its own or for repositories a command doesn't name
+pub(crate) fn is_read_only<R: Runtime>(app_handle: &AppHandle<R>, path: &Path) -> bool {
+    app_handle
+        .state::<ReadOnlyState>()
+        .containing(path)
+        .is_some()
+}
+
+/// Repository a command modifies without naming it in its arguments
+fn implied_repo<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    command: &str,
+    args: &Value,
+) -> Option<String> {
+    match command {
+        "restore_from_trash" => {
+            let id = args.get("id").and_then(Value::as_str)?;
+            trash::entry_repo_root(app_handle, id)
+        }
+        _ => None,
+    }
+}
+
+/// Why a command may not run, when a path it would write to is inside a
+/// project open read-only
+fn refusal(
+    projects: &ReadOnlyProjects,
+    command: &str,
+    args: &Value,
+    implied_repo: Option<&str>,
+) -> Option<String> {
+    let arg = |name: &&str| args.get(name).and_then(Value::as_str);
+    let outputs = OUTPUT_ARGS.iter().filter_map(arg);
+    let written: Vec<&str> = if READ_ONLY_COMMANDS.contains(&command) {
+        outputs.collect()
+    } else {
+        PATH_ARGS
+            .iter()
+            .filter_map(arg)
+            .chain(outputs)
+            .chain(implied_repo)
+            .collect()
+    };
+    written
+        .into_iter()
+        .find_map(|path| projects.containing(Path::new(path)))
+        .map(|root| {
+            format!(
+                "{} is open read-only: {} would modify it",
+                root.display(),
+                command
+            )
+        })
+}
+
+/// Wraps the command handler so commands that could modify a project open
+/// read-only are refused before they run, whatever the UI lets through.
+/// Plugin commands (fs, dialog, sql) are not routed through it.
+pub fn guard<R: Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke: Invoke<R>| {
+        let refused = match invoke.message.payload() {
+            InvokeBody::Json(args) => {
+                let webview = invoke.message.webview_ref();
+                let command = invoke.message.command();
+                let implied = implied_repo(webview.app_handle(), command, args);
+                let projects = webview.state::<ReadOnlyState>();
+                refusal(&projects, command, args, implied.as_deref())
+            }
+            InvokeBody::Raw(_) => None,
+        };
+        match refused {
+            Some(error) => {
+                tracing::warn!("{}", error);
+                invoke.resolver.reject(error);
+                true
+            }
+            None => handler(invoke),
+        }
+    }
+}
+
+/// Opens a project for browsing only, e.g. a teammate's checkout or an
+/// archived project on a shared drive: until it is closed, every command that
+/// could write to it is refused
+#[command]
+#[instrument(skip(projects), err(Debug))]
+pub fn open_project_readonly(
+    projects: State<'_, ReadOnlyState>,
+    path: String,
+) -> Result<ReadOnlySession, String> {
+    let root = canonical_root(&path)?;
+    projects
+        .roots
+        .lock()
+        .map_err(|_| "Failed to lock read-only projects".to_string())?
+        .insert(root.clone());
+    Ok(ReadOnlySession {
+        repo_path: root.to_string_lossy().to_string(),
+        read_only: true,
+    })
+}
+
+/// Makes a project opened read-only writable again
+#[command]
+#[instrument(skip(projects), err(Debug))]
+pub fn close_readonly_project(
+    projects: State<'_, ReadOnlyState>,
+    repo_path: String,
+) -> Result<ReadOnlySession, String> {
+    let root = canonical_root(&repo_path)?;
+    projects
+        .roots
+        .lock()
+        .map_err(|_| "Failed to lock read-only projects".to_string())?
+        .remove(&root);
+    Ok(ReadOnlySession {
+        repo_path: root.to_string_lossy().to_string(),
+        read_only: false,
+    })
+}
+
+#[command]
+#[instrument(skip(projects), err(Debug))]
+pub fn is_project_readonly(
+    projects: State<'_, ReadOnlyState>,
+    repo_path: String,
+) -> Result<bool, String> {
+    Ok(projects.containing(Path::new(&repo_path)).is_some())
+}
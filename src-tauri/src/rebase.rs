@@ -0,0 +1,475 @@
+use git2::build::CheckoutBuilder;
+use git2::{CherrypickOptions, Commit, Oid, Repository, Sort};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::conflicts::{self, ConflictFile};
+use crate::{identity, repo_manager};
+
+/// Rebase progress kept in the git directory so a rebase stopped at a
+/// conflict can be continued or aborted later
+const STATE_FILE: &str = "fenn-rebase.json";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RebaseAction {
+    Pick,
+    /// Folds the commit into the previous one, joining both messages
+    Squash,
+    Drop,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RebaseStep {
+    pub commit_id: String,
+    pub action: RebaseAction,
+    /// Message of the resulting commit; defaults to the original message
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebasePlanEntry {
+    pub commit_id: String,
+    pub short_id: String,
+    pub summary: String,
+    pub author: String,
+    pub timestamp: i64,
+    pub step: RebaseStep,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RebaseResult {
+    /// "completed" or "conflicts"
+    pub status: String,
+    /// New tip of the branch once completed
+    pub head: Option<String>,
+    /// Commit whose step stopped with conflicts
+    pub stopped_at: Option<String>,
+    pub conflicts: Vec<ConflictFile>,
+    pub remaining_steps: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct RebaseState {
+    branch_ref: String,
+    orig_head: String,
+    /// Tip of the rewritten history so far
+    tip: String,
+    /// Step stopped at a conflict, committed by `git_rebase_continue`
+    pending: Option<RebaseStep>,
+    remaining: Vec<RebaseStep>,
+}
+
+fn state_path(repo: &Repository) -> PathBuf {
+    repo.path().join(STATE_FILE)
+}
+
+fn load_state(repo: &Repository) -> Result<RebaseState, String> {
+    let content =
+        fs::read_to_string(state_path(repo)).map_err(|_| "No rebase in progress".to_string())?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to read rebase state: {}", e))
+}
+
+fn save_state(repo: &Repository, state: &RebaseState) -> Result<(), String> {
+    let content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize rebase state: {}", e))?;
+    fs::write(state_path(repo), content).map_err(|e| format!("Failed to save rebase state: {}", e))
+}
+
+fn find_commit<'r>(repo: &'r Repository, id: &str) -> Result<Commit<'r>, String> {
+    repo.revparse_single(id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find commit {}: {}", id, e))
+}
+
+fn open_repo(repo_path: &str) -> Result<Repository, String> {
+    Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))
+}
+
+fn has_local_changes(repo: &Repository) -> Result<bool, String> {
+    let mut options = git2::StatusOptions::new();
+    options.include_untracked(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+    Ok(!statuses.is_empty())
+}
+
+/// Commits between `upstream` and HEAD, oldest first, skipping merges
+fn commits_since(repo: &Repository, upstream: &Commit) -> Result<Vec<Oid>, String> {
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(|e| format!("Failed to sort history: {}", e))?;
+    revwalk
+        .push_head()
+        .and_then(|_| revwalk.hide(upstream.id()))
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let oid = oid.map_err(|e| format!("Failed to walk history: {}", e))?;
+        let commit = repo
+            .find_commit(oid)
+            .map_err(|e| format!("Failed to find commit: {}", e))?;
+        if commit.parent_count() <= 1 {
+            commits.push(oid);
+        }
+    }
+    Ok(commits)
+}
+
+/// Commits the current index as the result of a step on top of `tip`
+fn commit_step(repo: &Repository, tip: &Commit, step: &RebaseStep) -> Result<Oid, String> {
+    let original = find_commit(repo, &step.commit_id)?;
+    let signature = identity::signature(repo)?;
+    let tree = repo
+        .index()
+        .and_then(|mut index| index.write_tree())
+        .and_then(|tree_id| repo.find_tree(tree_id))
+        .map_err(|e| format!("Failed to write tree: {}", e))?;
+
+    let original_message = original.message().unwrap_or_default();
+    let id = match step.action {
+        RebaseAction::Squash => {
+            // Replace the previous commit with one combining both changes
+            let message = step.message.clone().unwrap_or_else(|| {
+                format!(
+                    "{}\n\n{}",
+                    tip.message().unwrap_or_default().trim_end(),
+                    original_message
+                )
+            });
+            let parents: Vec<Commit> = tip.parents().collect();
+            let parent_refs: Vec<&Commit> = parents.iter().collect();
+            repo.commit(
+                None,
+                &tip.author(),
+                &signature,
+                &message,
+                &tree,
+                &parent_refs,
+            )
+        }
+        _ => repo.commit(
+            None,
+            &original.author(),
+            &signature,
+            step.message.as_deref().unwrap_or(original_message),
+            &tree,
+            &[tip],
+        ),
+    }
+    .map_err(|e| format!("Failed to create commit: {}", e))?;
+
+    repo.cleanup_state()
+        .map_err(|e| format!("Failed to clean up rebase step: {}", e))?;
+    repo.set_head_detached(id)
+        .map_err(|e| format!("Failed to move HEAD: {}", e))?;
+    Ok(id)
+}
+
+/// Applies the remaining steps, stopping at the first conflict
+fn run_steps(repo: &Repository, mut state: RebaseState) -> Result<RebaseResult, String> {
+    while !state.remaining.is_empty() {
+        let step = state.remaining.remove(0);
+        if step.action == RebaseAction::Drop {
+            continue;
+        }
+
+        let commit = find_commit(repo, &step.commit_id)?;
+        let mut options = CherrypickOptions::new();
+        let mut checkout = CheckoutBuilder::new();
+        checkout
+            .safe()
+            .allow_conflicts(true)
+            .conflict_style_merge(true);
+        options.checkout_builder(checkout);
+        repo.cherrypick(&commit, Some(&mut options))
+            .map_err(|e| format!("Failed to apply {}: {}", step.commit_id, e))?;
+
+        let index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.has_conflicts() {
+            let result = RebaseResult {
+                status: "conflicts".to_string(),
+                head: None,
+                stopped_at: Some(commit.id().to_string()),
+                conflicts: conflicts::index_conflicts(&index)?,
+                remaining_steps: state.remaining.len(),
+            };
+            state.pending = Some(step);
+            save_state(repo, &state)?;
+            return Ok(result);
+        }
+
+        let tip = find_commit(repo, &state.tip)?;
+        state.tip = commit_step(repo, &tip, &step)?.to_string();
+    }
+
+    // Move the branch to the rewritten history and leave the detached HEAD
+    let tip = Oid::from_str(&state.tip).map_err(|e| format!("Invalid rebase tip: {}", e))?;
+    repo.reference(&state.branch_ref, tip, true, "rebase: finished")
+        .map_err(|e| format!("Failed to update branch: {}", e))?;
+    repo.set_head(&state.branch_ref)
+        .map_err(|e| format!("Failed to set HEAD: {}", e))?;
+    let _ = fs::remove_file(state_path(repo));
+
+    Ok(RebaseResult {
+        status: "completed".to_string(),
+        head: Some(state.tip),
+        stopped_at: None,
+        conflicts: Vec::new(),
+        remaining_steps: 0,
+    })
+}
+
+fn start_rebase(
+    repo: &Repository,
+    upstream: &str,
+    steps: Vec<RebaseStep>,
+) -> Result<RebaseResult, String> {
+    if state_path(repo).exists() {
+        return Err("A rebase is already in progress; continue or abort it first".to_string());
+    }
+    if has_local_changes(repo)? {
+        return Err("Commit or stash local changes before rebasing".to_string());
+    }
+    if steps.first().map(|s| s.action) == Some(RebaseAction::Squash) {
+        return Err("The first commit of a rebase cannot be squashed".to_string());
+    }
+
+    let head = repo
+        .head()
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    if !head.is_branch() {
+        return Err("HEAD is not on a branch".to_string());
+    }
+    let branch_ref = head.name().ok_or("Invalid branch name")?.to_string();
+    let orig_head = head.target().ok_or("HEAD has no commit")?.to_string();
+    let onto = find_commit(repo, upstream)?;
+
+    repo.set_head_detached(onto.id())
+        .map_err(|e| format!("Failed to detach HEAD: {}", e))?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))
+        .map_err(|e| format!("Failed to check out {}: {}", upstream, e))?;
+
+    let state = RebaseState {
+        branch_ref,
+        orig_head,
+        tip: onto.id().to_string(),
+        pending: None,
+        remaining: steps,
+    };
+    save_state(repo, &state)?;
+    run_steps(repo, state)
+}
+
+/// Commits of the current branch that a rebase onto `upstream` would replay,
+/// oldest first, each with a default `pick` step to edit
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn get_rebase_plan(
+    repo_path: String,
+    upstream: String,
+) -> Result<Vec<RebasePlanEntry>, String> {
+    let repo = open_repo(&repo_path)?;
+    let upstream_commit = find_commit(&repo, &upstream)?;
+
+    commits_since(&repo, &upstream_commit)?
+        .into_iter()
+        .map(|oid| {
+            let commit = repo
+                .find_commit(oid)
+                .map_err(|e| format!("Failed to find commit: {}", e))?;
+            Ok(RebasePlanEntry {
+                commit_id: oid.to_string(),
+                short_id: oid.to_string()[..7].to_string(),
+                summary: commit.summary().unwrap_or_default().to_string(),
+                author: commit.author().name().unwrap_or_default().to_string(),
+                timestamp: commit.time().seconds(),
+                step: RebaseStep {
+                    commit_id: oid.to_string(),
+                    action: RebaseAction::Pick,
+                    message: None,
+                },
+            })
+        })
+        .collect()
+}
+
+/// Rewrites the current branch on top of `upstream` following the given steps,
+/// in order. Commits left out of the plan are dropped.
+#[command]
+#[instrument(skip(app_handle, repo_path, steps), err(Debug))]
+pub fn execute_rebase_plan(
+    app_handle: AppHandle,
+    repo_path: String,
+    upstream: String,
+    steps: Vec<RebaseStep>,
+) -> Result<RebaseResult, String> {
+    repo_manager::write(&app_handle, &repo_path, || {
+        start_rebase(&open_repo(&repo_path)?, &upstream, steps)
+    })
+}
+
+/// Replays the commits of the current branch on top of `upstream`
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_rebase_onto(
+    app_handle: AppHandle,
+    repo_path: String,
+    upstream: String,
+) -> Result<RebaseResult, String> {
+    repo_manager::write(&app_handle, &repo_path, || {
+        rebase_onto(&open_repo(&repo_path)?, &upstream)
+    })
+}
+
+/// Picks every commit of the current branch onto `upstream`, the plain
+/// `git rebase <upstream>`
+pub(crate) fn rebase_onto(repo: &Repository, upstream: &str) -> Result<RebaseResult, String> {
+    let upstream_commit = find_commit(repo, upstream)?;
+    let steps = commits_since(repo, &upstream_commit)?
+        .into_iter()
+        .map(|oid| RebaseStep {
+            commit_id: oid.to_string(),
+            action: RebaseAction::Pick,
+            message: None,
+        })
+        .collect();
+    start_rebase(repo, upstream, steps)
+}
+
+/// Commits the resolved conflicts of the stopped step and carries on
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_rebase_continue(
+    app_handle: AppHandle,
+    repo_path: String,
+) -> Result<RebaseResult, String> {
+    repo_manager::write(&app_handle, &repo_path, || continue_rebase(&repo_path))
+}
+
+fn continue_rebase(repo_path: &str) -> Result<RebaseResult, String> {
+    let repo = open_repo(repo_path)?;
+    let mut state = load_state(&repo)?;
+
+    if let Some(step) = state.pending.take() {
+        let index = repo
+            .index()
+            .map_err(|e| format!("Failed to get index: {}", e))?;
+        if index.has_conflicts() {
+            return Err("Resolve and stage all conflicted files before continuing".to_string());
+        }
+        let tip = find_commit(&repo, &state.tip)?;
+        state.tip = commit_step(&repo, &tip, &step)?.to_string();
+        save_state(&repo, &state)?;
+    }
+
+    run_steps(&repo, state)
+}
+
+/// Abandons the rebase, restoring the branch and working tree to where they were
+#[command]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_rebase_abort(app_handle: AppHandle, repo_path: String) -> Result<String, String> {
+    repo_manager::write(&app_handle, &repo_path, || abort_rebase(&repo_path))
+}
+
+fn abort_rebase(repo_path: &str) -> Result<String, String> {
+    let repo = open_repo(repo_path)?;
+    let state = load_state(&repo)?;
+
+    repo.cleanup_state()
+        .map_err(|e| format!("Failed to clean up rebase: {}", e))?;
+    repo.set_head(&state.branch_ref)
+        .map_err(|e| format!("Failed to set HEAD: {}", e))?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force()))
+        .map_err(|e| format!("Failed to restore working tree: {}", e))?;
+    fs::remove_file(state_path(&repo))
+        .map_err(|e| format!("Failed to remove rebase state: {}", e))?;
+
+    Ok(format!("Rebase aborted, back on {}", state.orig_head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    /// A fixture with a `base` branch and three commits on `main` after it
+    fn fixture_with_commits() -> FixtureRepo {
+        let fixture = FixtureRepo::new();
+        fixture.create_branch("base");
+        for name in ["one", "two", "three"] {
+            fixture.write_file(&format!("{}.txt", name), name);
+            fixture.commit_all(&format!("Add {}", name));
+        }
+        fixture
+    }
+
+    #[test]
+    fn plans_the_commits_since_the_upstream_oldest_first() {
+        let fixture = fixture_with_commits();
+        let cases: [(&str, &[&str]); 3] = [
+            ("base", &["Add one", "Add two", "Add three"]),
+            ("HEAD~1", &["Add three"]),
+            ("main", &[]),
+        ];
+        for (upstream, expected) in cases {
+            let plan = get_rebase_plan(fixture.path_string(), upstream.to_string()).unwrap();
+            let summaries: Vec<&str> = plan.iter().map(|entry| entry.summary.as_str()).collect();
+            assert_eq!(summaries, expected, "plan onto {}", upstream);
+            assert!(plan
+                .iter()
+                .all(|entry| entry.step.action == RebaseAction::Pick
+                    && entry.step.commit_id == entry.commit_id));
+        }
+    }
+
+    #[test]
+    fn executes_squash_and_drop_steps() {
+        let fixture = fixture_with_commits();
+        let plan = get_rebase_plan(fixture.path_string(), "base".to_string()).unwrap();
+        let actions = [RebaseAction::Pick, RebaseAction::Squash, RebaseAction::Drop];
+        let steps = plan
+            .into_iter()
+            .zip(actions)
+            .map(|(entry, action)| RebaseStep {
+                action,
+                ..entry.step
+            })
+            .collect();
+
+        let result = start_rebase(fixture.repo(), "base", steps).unwrap();
+        assert_eq!(result.status, "completed");
+
+        let head = fixture.repo().head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.message(), Some("Add one\n\nAdd two"));
+        let base = find_commit(fixture.repo(), "base").unwrap();
+        assert_eq!(head.parent_id(0).unwrap(), base.id());
+        assert!(fixture.path().join("two.txt").exists());
+        assert!(!fixture.path().join("three.txt").exists());
+    }
+
+    #[test]
+    fn refuses_to_squash_the_first_commit() {
+        let fixture = fixture_with_commits();
+        let plan = get_rebase_plan(fixture.path_string(), "base".to_string()).unwrap();
+        let steps = plan
+            .into_iter()
+            .map(|entry| RebaseStep {
+                action: RebaseAction::Squash,
+                ..entry.step
+            })
+            .collect();
+        assert!(start_rebase(fixture.repo(), "base", steps).is_err());
+    }
+}
@@ -0,0 +1,355 @@
+use git2::build::CheckoutBuilder;
+use git2::{FetchOptions, PushOptions, RemoteCallbacks, Repository};
+use serde::Serialize;
+use serde_json::json;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use tauri::command;
+use tauri::{AppHandle, Emitter};
+use tracing::instrument;
+
+use crate::notifications::{self, Notification};
+use crate::offline::{self, SyncOperation};
+use crate::{audit, sparse, ssh, transfer};
+
+pub const TRANSFER_PROGRESS_EVENT: &str = "git://transfer-progress";
+
+/// Minimum time between two progress events for the same operation
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TransferProgress {
+    pub operation_id: String,
+    /// "clone", "fetch", "pull" or "push"
+    pub operation: String,
+    /// "receiving", "resolving", "uploading" or "done"
+    pub phase: String,
+    pub total_objects: usize,
+    pub received_objects: usize,
+    pub indexed_objects: usize,
+    pub local_objects: usize,
+    pub bytes: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferSummary {
+    pub operation_id: String,
+    pub message: String,
+}
+
+fn emit_progress(app_handle: &AppHandle, progress: TransferProgress) {
+    if let Err(e) = app_handle.emit(TRANSFER_PROGRESS_EVENT, progress) {
+        tracing::warn!("Failed to emit transfer progress: {}", e);
+    }
+}
+
+/// Generates an operation id unless the frontend supplied one to correlate events
+pub fn operation_id(requested: Option<String>) -> String {
+    requested.unwrap_or_else(|| uuid::Uuid::new_v4().to_string())
+}
+
+/// Remote callbacks that authenticate with the ssh-agent or app-managed keys
+/// and report fetch and push progress as Tauri events
+pub fn progress_callbacks<'a>(
+    app_handle: &AppHandle,
+    operation_id: &str,
+    operation: &str,
+) -> RemoteCallbacks<'a> {
+    let mut callbacks = RemoteCallbacks::new();
+    callbacks.credentials(ssh::credential_callback(app_handle));
+
+    let fetch_handle = app_handle.clone();
+    let fetch_id = operation_id.to_string();
+    let fetch_operation = operation.to_string();
+    let mut last_fetch_event: Option<Instant> = None;
+    callbacks.transfer_progress(move |stats| {
+        let done = stats.received_objects() == stats.total_objects()
+            && stats.indexed_objects() == stats.total_objects();
+        if done || last_fetch_event.map_or(true, |t| t.elapsed() >= PROGRESS_INTERVAL) {
+            last_fetch_event = Some(Instant::now());
+            let phase = if stats.received_objects() < stats.total_objects() {
+                "receiving"
+            } else if !done {
+                "resolving"
+            } else {
+                "done"
+            };
+            emit_progress(
+                &fetch_handle,
+                TransferProgress {
+                    operation_id: fetch_id.clone(),
+                    operation: fetch_operation.clone(),
+                    phase: phase.to_string(),
+                    total_objects: stats.total_objects(),
+                    received_objects: stats.received_objects(),
+                    indexed_objects: stats.indexed_objects(),
+                    local_objects: stats.local_objects(),
+                    bytes: stats.received_bytes(),
+                },
+            );
+        }
+        true
+    });
+
+    let push_handle = app_handle.clone();
+    let push_id = operation_id.to_string();
+    let push_operation = operation.to_string();
+    let mut last_push_event: Option<Instant> = None;
+    callbacks.push_transfer_progress(move |current, total, bytes| {
+        let done = current == total;
+        if done || last_push_event.map_or(true, |t| t.elapsed() >= PROGRESS_INTERVAL) {
+            last_push_event = Some(Instant::now());
+            emit_progress(
+                &push_handle,
+                TransferProgress {
+                    operation_id: push_id.clone(),
+                    operation: push_operation.clone(),
+                    phase: if done { "done" } else { "uploading" }.to_string(),
+                    total_objects: total,
+                    received_objects: current,
+                    indexed_objects: current,
+                    local_objects: 0,
+                    bytes,
+                },
+            );
+        }
+    });
+
+    callbacks
+}
+
+/// Clones a repository, reporting transfer progress. `depth` limits the
+/// history to that many commits and `sparse_paths` limits the working tree to
+/// those directories, for huge repositories where only a dataset is needed.
+#[command(async)]
+#[instrument(skip(app_handle, url, path), err(Debug))]
+pub fn git_clone(
+    app_handle: AppHandle,
+    url: String,
+    path: String,
+    operation_id: Option<String>,
+    depth: Option<u32>,
+    sparse_paths: Option<Vec<String>>,
+) -> Result<TransferSummary, String> {
+    let operation_id = self::operation_id(operation_id);
+    let sparse_paths = sparse_paths.unwrap_or_default();
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(progress_callbacks(&app_handle, &operation_id, "clone"));
+    if let Some(depth) = depth {
+        fetch_options.depth(depth.min(i32::MAX as u32) as i32);
+    }
+
+    let mut builder = git2::build::RepoBuilder::new();
+    builder.fetch_options(fetch_options);
+    if !sparse_paths.is_empty() {
+        // Only the sparse tree is written, once the patterns are in place
+        let mut checkout = CheckoutBuilder::new();
+        checkout.dry_run();
+        builder.with_checkout(checkout);
+    }
+    let repo = builder
+        .clone(&url, Path::new(&path))
+        .map_err(|e| format!("Failed to clone repository: {}", e))?;
+
+    let mut message = format!("Cloned {} into {}", url, path);
+    if !sparse_paths.is_empty() {
+        let dirs = sparse::apply(&repo, &sparse_paths)?;
+        message.push_str(&format!(" with only {} checked out", dirs.join(", ")));
+    }
+
+    Ok(TransferSummary {
+        operation_id,
+        message,
+    })
+}
+
+/// Number of commits of the first-parent history of HEAD available locally
+fn history_depth(repo: &Repository) -> Result<usize, String> {
+    let mut revwalk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    revwalk
+        .push_head()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    revwalk
+        .simplify_first_parent()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    Ok(revwalk.filter(|oid| oid.is_ok()).count())
+}
+
+/// Fetches `commits` more commits of history into a shallow clone, like
+/// `git fetch --deepen`
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_fetch_deepen(
+    app_handle: AppHandle,
+    repo_path: String,
+    commits: u32,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferSummary, String> {
+    if commits == 0 {
+        return Err("Deepen by at least one commit".to_string());
+    }
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    if !repo.is_shallow() {
+        return Err("The repository already has its full history".to_string());
+    }
+    let remote_name = remote.unwrap_or_else(|| "origin".to_string());
+    let operation_id = self::operation_id(operation_id);
+
+    let depth = history_depth(&repo)?.saturating_add(commits as usize);
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(progress_callbacks(&app_handle, &operation_id, "fetch"));
+    fetch_options.depth(depth.min(i32::MAX as usize) as i32);
+
+    repo.find_remote(&remote_name)
+        .map_err(|e| format!("Failed to find remote: {}", e))?
+        .fetch(&[] as &[&str], Some(&mut fetch_options), None)
+        .map_err(|e| format!("Failed to fetch: {}", e))?;
+
+    let message = if repo.is_shallow() {
+        format!("History deepened to {} commits", history_depth(&repo)?)
+    } else {
+        "Fetched the full history".to_string()
+    };
+    Ok(TransferSummary {
+        operation_id,
+        message,
+    })
+}
+
+/// Fetches from a remote (default `origin`), reporting transfer progress
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_fetch(
+    app_handle: AppHandle,
+    repo_path: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferSummary, String> {
+    let operation = SyncOperation::GitFetch {
+        remote: remote.clone(),
+    };
+    offline::queue_when_offline(&app_handle, &repo_path, operation, || {
+        fetch(&app_handle, &repo_path, remote, operation_id)
+    })
+}
+
+fn fetch(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferSummary, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = remote.unwrap_or_else(|| "origin".to_string());
+    let operation_id = self::operation_id(operation_id);
+
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+
+    let mut fetch_options = FetchOptions::new();
+    fetch_options.remote_callbacks(progress_callbacks(app_handle, &operation_id, "fetch"));
+
+    remote
+        .fetch(&[] as &[&str], Some(&mut fetch_options), None)
+        .map_err(|e| format!("Failed to fetch: {}", e))?;
+
+    Ok(TransferSummary {
+        operation_id,
+        message: format!("Fetched from {}", remote_name),
+    })
+}
+
+/// Pushes the current (or given) branch to a remote, reporting transfer progress
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_push(
+    app_handle: AppHandle,
+    repo_path: String,
+    remote: Option<String>,
+    branch: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferSummary, String> {
+    let operation = SyncOperation::GitPush {
+        remote: remote.clone(),
+        branch: branch.clone(),
+    };
+    offline::queue_when_offline(&app_handle, &repo_path, operation, || {
+        audit::track(
+            &app_handle,
+            &repo_path,
+            "git_push",
+            json!({ "remote": remote, "branch": branch }),
+            || {
+                let summary = push(
+                    app_handle.clone(),
+                    repo_path.clone(),
+                    remote,
+                    branch,
+                    operation_id,
+                )?;
+                notifications::notify(
+                    &app_handle,
+                    &transfer::repo_root(&repo_path)?,
+                    Notification::new(notifications::PUSH_EVENT, summary.message.clone()),
+                );
+                Ok(summary)
+            },
+        )
+    })
+}
+
+pub(crate) fn push(
+    app_handle: AppHandle,
+    repo_path: String,
+    remote: Option<String>,
+    branch: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferSummary, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let remote_name = remote.unwrap_or_else(|| "origin".to_string());
+    let operation_id = self::operation_id(operation_id);
+
+    let branch = match branch {
+        Some(branch) => branch,
+        None => repo
+            .head()
+            .map_err(|e| format!("Failed to get HEAD: {}", e))?
+            .shorthand()
+            .ok_or("Failed to get branch name")?
+            .to_string(),
+    };
+
+    let mut remote = repo
+        .find_remote(&remote_name)
+        .map_err(|e| format!("Failed to find remote: {}", e))?;
+
+    let mut callbacks = progress_callbacks(&app_handle, &operation_id, "push");
+    callbacks.push_update_reference(|refname, status| match status {
+        Some(message) => Err(git2::Error::from_str(&format!(
+            "Remote rejected {}: {}",
+            refname, message
+        ))),
+        None => Ok(()),
+    });
+
+    let mut push_options = PushOptions::new();
+    push_options.remote_callbacks(callbacks);
+
+    let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+    remote
+        .push(&[refspec.as_str()], Some(&mut push_options))
+        .map_err(|e| format!("Failed to push: {}", e))?;
+
+    Ok(TransferSummary {
+        operation_id,
+        message: format!("Pushed {} to {}", branch, remote_name),
+    })
+}
@@ -0,0 +1,325 @@
+use rusqlite::params;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::db::{self, PooledConnection};
+use crate::dvc_config::RemoteConfig;
+use crate::file::FileEntry;
+use crate::storage::{self, RemoteStorage, TransferConfig};
+use crate::transfer::CacheObject;
+use crate::{dvc_config, dvcfile, settings, transfer};
+
+pub const PUSHED: &str = "pushed";
+pub const NOT_PUSHED: &str = "not-pushed";
+pub const UNKNOWN: &str = "unknown";
+
+/// Whether the data of a DVC-tracked path is on the remote
+#[derive(Debug, Serialize)]
+pub struct RemoteStatus {
+    /// Data path relative to the repository root
+    pub path: String,
+    /// "pushed", "not-pushed" or "unknown"
+    pub remote_status: String,
+    /// Objects of the path that aren't on the remote
+    pub missing_objects: usize,
+}
+
+/// What a push of DVC-tracked paths would upload
+#[derive(Debug, Serialize)]
+pub struct PushEstimate {
+    pub remote: String,
+    pub upload_objects: usize,
+    pub upload_bytes: u64,
+    /// Objects the remote already has, which a push skips
+    pub present_objects: usize,
+    pub present_bytes: u64,
+    /// Objects referenced by pointers but absent from the local cache, which
+    /// a push can't upload
+    pub missing_locally: usize,
+    /// Objects whose presence couldn't be checked, counted as uploads
+    pub unchecked_objects: usize,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::REMOTE_OBJECTS_SCHEMA)
+}
+
+/// Saves whether objects are on a remote, keyed by the remote's URL so
+/// renaming a remote keeps what is known about it
+pub(crate) fn record(
+    app_handle: &AppHandle,
+    remote_url: &str,
+    md5s: &[String],
+    present: bool,
+) -> Result<(), String> {
+    let mut conn = open_connection(app_handle)?;
+    let tx = conn
+        .transaction()
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    {
+        let mut stmt = tx
+            .prepare(
+                "INSERT OR REPLACE INTO remote_objects (remote, md5, present, checked_at)
+                 VALUES (?1, ?2, ?3, CURRENT_TIMESTAMP)",
+            )
+            .map_err(|e| format!("Failed to prepare remote status insert: {}", e))?;
+        for md5 in md5s {
+            stmt.execute(params![remote_url, md5, present])
+                .map_err(|e| format!("Failed to record remote status of {}: {}", md5, e))?;
+        }
+    }
+    tx.commit()
+        .map_err(|e| format!("Failed to commit remote status: {}", e))
+}
+
+/// What is known about the objects on a remote
+fn known_objects(
+    app_handle: &AppHandle,
+    remote_url: &str,
+) -> Result<HashMap<String, bool>, String> {
+    let conn = open_connection(app_handle)?;
+    let mut stmt = conn
+        .prepare("SELECT md5, present FROM remote_objects WHERE remote = ?1")
+        .map_err(|e| format!("Failed to prepare remote status query: {}", e))?;
+    let rows = stmt
+        .query_map(params![remote_url], |row| Ok((row.get(0)?, row.get(1)?)))
+        .map_err(|e| format!("Failed to query remote status: {}", e))?;
+    let known = rows
+        .collect::<Result<HashMap<_, _>, _>>()
+        .map_err(|e| format!("Failed to read remote status row: {}", e))?;
+    Ok(known)
+}
+
+/// Objects a pointer's outputs consist of; the files of a directory are only
+/// known when its manifest is cached
+fn pointer_objects(repo_root: &Path, pointer: &Path) -> Vec<String> {
+    transfer::collect_objects(repo_root, &[pointer.to_path_buf()])
+        .map(|objects| objects.into_iter().map(|object| object.md5).collect())
+        .unwrap_or_default()
+}
+
+/// Pushed when every object is known to be on the remote, not pushed when
+/// any is known to be missing
+fn push_status(objects: &[String], known: &HashMap<String, bool>) -> (&'static str, usize) {
+    let missing = objects
+        .iter()
+        .filter(|md5| known.get(*md5) == Some(&false))
+        .count();
+    let status = if objects.is_empty() {
+        UNKNOWN
+    } else if missing > 0 {
+        NOT_PUSHED
+    } else if objects.iter().all(|md5| known.get(md5) == Some(&true)) {
+        PUSHED
+    } else {
+        UNKNOWN
+    };
+    (status, missing)
+}
+
+/// A remote of the repository, the default one unless `remote` is given,
+/// with the number of connections its transfer limits allow
+fn open_storage(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    remote: Option<&str>,
+) -> Result<(RemoteConfig, usize, Box<dyn RemoteStorage>), String> {
+    let remote_config = dvc_config::remote(repo_root, remote)?;
+    let limits = settings::load(app_handle, repo_root)?.transfer_limits;
+    let config = TransferConfig {
+        concurrency: limits.max_concurrency.max(1),
+        ..TransferConfig::default()
+    };
+    let storage = storage::open_remote(&remote_config, &config)?;
+    Ok((remote_config, config.concurrency, storage))
+}
+
+/// Asks the remote about objects not yet known to be on it, recording the
+/// answers and adding them to `known`
+fn check_objects(
+    app_handle: &AppHandle,
+    storage: &dyn RemoteStorage,
+    remote_config: &RemoteConfig,
+    concurrency: usize,
+    md5s: &[String],
+    known: &mut HashMap<String, bool>,
+) -> Result<(), String> {
+    let mut unchecked: Vec<String> = md5s
+        .iter()
+        .filter(|md5| known.get(*md5) != Some(&true))
+        .cloned()
+        .collect();
+    unchecked.sort();
+    unchecked.dedup();
+
+    let found = Mutex::new(Vec::new());
+    let errors = transfer::run_parallel(&unchecked, concurrency, |md5| {
+        let present = transfer::remote_key(storage, md5)?.is_some();
+        if let Ok(mut found) = found.lock() {
+            found.push((md5.clone(), present));
+        }
+        Ok(())
+    });
+    if let Some(error) = errors.first() {
+        tracing::warn!(
+            "Failed to check {} objects on {}: {}",
+            errors.len(),
+            remote_config.name,
+            error
+        );
+    }
+    let found = found.into_inner().unwrap_or_default();
+    let (present, missing): (Vec<_>, Vec<_>) = found.into_iter().partition(|(_, present)| *present);
+    let present: Vec<String> = present.into_iter().map(|(md5, _)| md5).collect();
+    let missing: Vec<String> = missing.into_iter().map(|(md5, _)| md5).collect();
+    record(app_handle, &remote_config.url, &present, true)?;
+    record(app_handle, &remote_config.url, &missing, false)?;
+    known.extend(present.into_iter().map(|md5| (md5, true)));
+    known.extend(missing.into_iter().map(|md5| (md5, false)));
+    Ok(())
+}
+
+/// Sets the remote status of DVC-tracked tree entries from what earlier
+/// checks and pushes found out, without contacting the remote
+pub(crate) fn annotate(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    entries: &mut [FileEntry],
+) -> Result<(), String> {
+    let known = match dvc_config::remote(repo_root, None) {
+        Ok(remote) => known_objects(app_handle, &remote.url)?,
+        Err(_) => HashMap::new(),
+    };
+    for entry in entries.iter_mut().filter(|entry| entry.has_dvc_file) {
+        let pointer = dvcfile::pointer_path(&repo_root.join(&entry.path));
+        let (status, _) = push_status(&pointer_objects(repo_root, &pointer), &known);
+        entry.remote_status = Some(status.to_string());
+    }
+    Ok(())
+}
+
+/// Checks which objects of DVC-tracked paths (all of them without `paths`)
+/// are on the remote, the default one unless `remote` is given. Objects
+/// already known to be there aren't checked again; the results are cached
+/// for the file tree.
+#[command(async)]
+#[instrument(skip(app_handle, paths), err(Debug))]
+pub fn check_remote_status(
+    app_handle: AppHandle,
+    repo_path: String,
+    paths: Vec<String>,
+    remote: Option<String>,
+) -> Result<Vec<RemoteStatus>, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let (remote_config, concurrency, storage) =
+        open_storage(&app_handle, &repo_root, remote.as_deref())?;
+
+    let pointers = transfer::resolve_pointers(&repo_root, &paths)?;
+    let objects: Vec<(PathBuf, Vec<String>)> = pointers
+        .into_iter()
+        .map(|pointer| {
+            let objects = pointer_objects(&repo_root, &pointer);
+            (pointer, objects)
+        })
+        .collect();
+
+    let mut known = known_objects(&app_handle, &remote_config.url)?;
+    let md5s: Vec<String> = objects
+        .iter()
+        .flat_map(|(_, objects)| objects)
+        .cloned()
+        .collect();
+    check_objects(
+        &app_handle,
+        storage.as_ref(),
+        &remote_config,
+        concurrency,
+        &md5s,
+        &mut known,
+    )?;
+
+    objects
+        .into_iter()
+        .map(|(pointer, objects)| {
+            let (status, missing_objects) = push_status(&objects, &known);
+            Ok(RemoteStatus {
+                path: dvcfile::to_git_path(&dvcfile::repo_relative_path(
+                    &repo_root,
+                    &pointer.with_extension(""),
+                )?),
+                remote_status: status.to_string(),
+                missing_objects,
+            })
+        })
+        .collect()
+}
+
+/// Counts the objects and bytes a push of DVC-tracked paths (all of them
+/// without `paths`) would upload, leaving out objects the remote already
+/// has. Objects known to be on the remote from earlier checks and pushes
+/// aren't checked again.
+#[command(async)]
+#[instrument(skip(app_handle, paths), err(Debug))]
+pub fn estimate_push(
+    app_handle: AppHandle,
+    repo_path: String,
+    paths: Option<Vec<String>>,
+    remote: Option<String>,
+) -> Result<PushEstimate, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let (remote_config, concurrency, storage) =
+        open_storage(&app_handle, &repo_root, remote.as_deref())?;
+
+    let pointers = transfer::resolve_pointers(&repo_root, &paths.unwrap_or_default())?;
+    let (cached, missing): (Vec<CacheObject>, Vec<CacheObject>) =
+        transfer::collect_objects(&repo_root, &pointers)?
+            .into_iter()
+            .partition(|object| object.cache_path.exists());
+
+    let mut known = known_objects(&app_handle, &remote_config.url)?;
+    let md5s: Vec<String> = cached.iter().map(|object| object.md5.clone()).collect();
+    check_objects(
+        &app_handle,
+        storage.as_ref(),
+        &remote_config,
+        concurrency,
+        &md5s,
+        &mut known,
+    )?;
+
+    let mut estimate = PushEstimate {
+        remote: remote_config.name,
+        upload_objects: 0,
+        upload_bytes: 0,
+        present_objects: 0,
+        present_bytes: 0,
+        missing_locally: missing.len(),
+        unchecked_objects: 0,
+    };
+    for object in cached {
+        let size = fs::metadata(&object.cache_path)
+            .map(|metadata| metadata.len())
+            .unwrap_or(0);
+        match known.get(&object.md5) {
+            Some(true) => {
+                estimate.present_objects += 1;
+                estimate.present_bytes += size;
+            }
+            Some(false) => {
+                estimate.upload_objects += 1;
+                estimate.upload_bytes += size;
+            }
+            None => {
+                estimate.unchecked_objects += 1;
+                estimate.upload_objects += 1;
+                estimate.upload_bytes += size;
+            }
+        }
+    }
+    Ok(estimate)
+}
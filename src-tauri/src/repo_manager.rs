@@ -0,0 +1,232 @@
+use git2::Repository;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File, TryLockError};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tauri::async_runtime::{self, RwLock, TokioHandle};
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+use crate::{dvcfile, ownership};
+
+/// Git lock files older than this are assumed to be left behind by a crash
+const STALE_LOCK_AGE: Duration = Duration::from_secs(10 * 60);
+
+/// A lock another process holds on the repository
+#[derive(Debug, Serialize)]
+pub struct RepoLock {
+    /// Lock file relative to the repository root
+    pub path: String,
+    /// "git" or "dvc"
+    pub kind: String,
+    pub age_secs: Option<u64>,
+    /// Whether the lock looks left over from a crash and can be cleared
+    pub stale: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepoLockStatus {
+    /// Whether another process is using the repository
+    pub busy: bool,
+    pub locks: Vec<RepoLock>,
+}
+
+/// Serializes access to each repository: operations that write the index, refs
+/// or working tree take the write lock and wait for each other, while reads
+/// like status share the read lock and see the repository between writes.
+#[derive(Debug, Default)]
+pub struct RepoManager {
+    /// One lock per git directory, so linked worktrees (each with its own
+    /// index) don't wait for each other
+    locks: Mutex<HashMap<PathBuf, Arc<RwLock<()>>>>,
+}
+
+pub type RepoManagerState = RepoManager;
+
+impl RepoManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn lock_for(&self, repo: &Repository) -> Result<Arc<RwLock<()>>, String> {
+        let git_dir = repo
+            .path()
+            .canonicalize()
+            .unwrap_or_else(|_| repo.path().to_path_buf());
+        let mut locks = self
+            .locks
+            .lock()
+            .map_err(|e| format!("Failed to lock repository registry: {}", e))?;
+        Ok(locks.entry(git_dir).or_default().clone())
+    }
+
+    /// Runs `run` on a repository opened once every pending write is done;
+    /// other reads may run at the same time
+    pub fn read<T>(
+        &self,
+        repo_path: &str,
+        run: impl FnOnce(&Repository) -> Result<T, String>,
+    ) -> Result<T, String> {
+        let lock = self.lock_for(&discover(repo_path)?)?;
+        let _guard = acquire(lock.read_owned());
+        let repo = Repository::open(repo_path).map_err(|e| ownership::open_error(repo_path, e))?;
+        run(&repo)
+    }
+
+    /// Runs `run` with no other read or write of the repository in progress.
+    /// Refuses to start while a git or DVC process outside the app holds one
+    /// of the repository's locks.
+    pub fn write<T>(
+        &self,
+        repo_path: &str,
+        run: impl FnOnce() -> Result<T, String>,
+    ) -> Result<T, String> {
+        let repo = discover(repo_path)?;
+        let lock = self.lock_for(&repo)?;
+        let _guard = acquire(lock.write_owned());
+        let held = held_locks(&repo);
+        if !held.is_empty() {
+            return Err(busy_message(&held));
+        }
+        run()
+    }
+}
+
+fn discover(repo_path: &str) -> Result<Repository, String> {
+    Repository::discover(repo_path).map_err(|e| ownership::open_error(repo_path, e))
+}
+
+fn age(path: &Path) -> Option<Duration> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    SystemTime::now().duration_since(modified).ok()
+}
+
+/// DVC keeps its lock files around and holds an OS lock on them while it
+/// runs, so a file is only in use when it can't be locked
+fn dvc_lock_held(path: &Path) -> bool {
+    let Ok(file) = File::open(path) else {
+        return false;
+    };
+    matches!(file.try_lock(), Err(TryLockError::WouldBlock))
+}
+
+/// Locks other processes hold on the repository: git lock files, which only
+/// exist while git writes, and DVC's repository locks
+fn held_locks(repo: &Repository) -> Vec<RepoLock> {
+    let repo_root = repo.workdir().unwrap_or_else(|| repo.path());
+    let relative = |path: &Path| dvcfile::to_git_path(path.strip_prefix(repo_root).unwrap_or(path));
+
+    let mut locks = Vec::new();
+    for name in ["index.lock", "HEAD.lock", "config.lock"] {
+        let path = repo.path().join(name);
+        if !path.exists() {
+            continue;
+        }
+        let age = age(&path);
+        locks.push(RepoLock {
+            path: relative(&path),
+            kind: "git".to_string(),
+            age_secs: age.map(|age| age.as_secs()),
+            stale: age.is_some_and(|age| age >= STALE_LOCK_AGE),
+        });
+    }
+    let tmp = repo_root.join(".dvc").join("tmp");
+    for path in [tmp.join("lock"), tmp.join("rwlock.lock")] {
+        if dvc_lock_held(&path) {
+            locks.push(RepoLock {
+                path: relative(&path),
+                kind: "dvc".to_string(),
+                age_secs: age(&path).map(|age| age.as_secs()),
+                // The OS releases it when the process exits
+                stale: false,
+            });
+        }
+    }
+    locks
+}
+
+fn busy_message(locks: &[RepoLock]) -> String {
+    let paths: Vec<&str> = locks.iter().map(|lock| lock.path.as_str()).collect();
+    let mut message = format!(
+        "Another process is using this repository ({}); try again once it finishes",
+        paths.join(", ")
+    );
+    if locks.iter().any(|lock| lock.stale) {
+        message.push_str(", or clear the stale lock if no git process is running");
+    }
+    message
+}
+
+/// Waits for a lock from sync code. Commands run on the main thread or on a
+/// runtime worker, where blocking on the runtime would panic, so the wait
+/// happens on a helper thread there.
+fn acquire<G: Send>(lock: impl Future<Output = G> + Send) -> G {
+    if TokioHandle::try_current().is_err() {
+        return async_runtime::block_on(lock);
+    }
+    std::thread::scope(|scope| {
+        scope
+            .spawn(|| async_runtime::block_on(lock))
+            .join()
+            .expect("repository lock thread panicked")
+    })
+}
+
+/// Shorthand for `RepoManager::read` through the managed state
+pub(crate) fn read<T>(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    run: impl FnOnce(&Repository) -> Result<T, String>,
+) -> Result<T, String> {
+    app_handle.state::<RepoManagerState>().read(repo_path, run)
+}
+
+/// Shorthand for `RepoManager::write` through the managed state
+pub(crate) fn write<T>(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    run: impl FnOnce() -> Result<T, String>,
+) -> Result<T, String> {
+    app_handle.state::<RepoManagerState>().write(repo_path, run)
+}
+
+/// Whether a git or DVC process outside the app is using the repository
+#[command]
+#[instrument(err(Debug))]
+pub fn get_repository_lock_status(repo_path: String) -> Result<RepoLockStatus, String> {
+    let locks = held_locks(&discover(&repo_path)?);
+    Ok(RepoLockStatus {
+        busy: !locks.is_empty(),
+        locks,
+    })
+}
+
+/// Removes git lock files left over from a crash. Fresh ones are only removed
+/// with `force`, for when the user knows no git process is running; DVC locks
+/// are released by the OS and never need clearing.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn clear_repository_locks(
+    app_handle: AppHandle,
+    repo_path: String,
+    force: Option<bool>,
+) -> Result<Vec<String>, String> {
+    let repo = discover(&repo_path)?;
+    let lock = app_handle.state::<RepoManagerState>().lock_for(&repo)?;
+    let _guard = acquire(lock.write_owned());
+
+    let repo_root = repo.workdir().unwrap_or_else(|| repo.path());
+    let mut removed = Vec::new();
+    for lock in held_locks(&repo) {
+        if lock.kind != "git" || !(lock.stale || force.unwrap_or(false)) {
+            continue;
+        }
+        let path = repo_root.join(&lock.path);
+        fs::remove_file(&path).map_err(|e| format!("Failed to remove {}: {}", lock.path, e))?;
+        removed.push(lock.path);
+    }
+    Ok(removed)
+}
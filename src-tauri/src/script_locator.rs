@@ -0,0 +1,249 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tauri::{command, AppHandle, Manager};
+use tracing::{debug, instrument, warn};
+
+use crate::env;
+
+/// Versions and checksums of the pre-built scripts this build was made with
+const MANIFEST: &str = include_str!("../dvc-scripts/manifest.json");
+
+/// Scripts the DVC layer runs, without extension
+pub const SCRIPTS: &[&str] = &["dvc_init_script", "dvc_add_script", "dvc_diff_script"];
+
+const SCRIPTS_DIR: &str = "dvc-scripts";
+const SOURCES_DIR: &str = "python-scripts";
+
+/// How many parent directories of the executable are searched for a
+/// development checkout, e.g. `src-tauri/target/debug` up to `src-tauri`
+const DEVELOPMENT_DEPTH: usize = 3;
+
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    /// DVC release the scripts were built against
+    dvc: Option<String>,
+    scripts: HashMap<String, ManifestEntry>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestEntry {
+    version: String,
+    sha256: String,
+}
+
+fn manifest() -> &'static Manifest {
+    static PARSED: OnceLock<Manifest> = OnceLock::new();
+    PARSED.get_or_init(|| {
+        serde_json::from_str(MANIFEST).unwrap_or_else(|e| {
+            warn!("Invalid embedded script manifest: {}", e);
+            Manifest::default()
+        })
+    })
+}
+
+/// DVC release bundled into the pre-built scripts
+pub(crate) fn bundled_dvc_version() -> Option<&'static str> {
+    manifest().dvc.as_deref()
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptCandidate {
+    /// "override", "executable", "bundled" or "development"
+    pub source: String,
+    pub path: String,
+    /// "ok", "unpinned", "missing", "checksum_mismatch" or "not_executable"
+    pub status: String,
+}
+
+/// Where a script was looked for and why each location was rejected
+#[derive(Debug, Clone, Serialize)]
+pub struct ScriptDiagnostic {
+    pub script: String,
+    /// Version the embedded manifest pins the script to
+    pub expected_version: Option<String>,
+    pub resolved: Option<String>,
+    pub candidates: Vec<ScriptCandidate>,
+}
+
+impl fmt::Display for ScriptDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Executable '{}' not found. Searched:", self.script)?;
+        for candidate in &self.candidates {
+            write!(
+                f,
+                "\n  {} ({}): {}",
+                candidate.path, candidate.source, candidate.status
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// File name of a pre-built script on this platform
+fn executable_name(script: &str) -> String {
+    let extension = if cfg!(target_os = "windows") {
+        "exe"
+    } else {
+        "bin"
+    };
+    format!("{}.{}", script, extension)
+}
+
+fn sha256_file(path: &Path) -> Result<String, String> {
+    let content =
+        fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    Ok(hex::encode(Sha256::digest(content)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Finds the DVC scripts in, in order: the user's override directory, next to
+/// the executable, the bundled resources and a development checkout. Pre-built
+/// scripts must match the checksums of the embedded manifest, except in the
+/// override directory where any build is accepted.
+pub struct ScriptLocator {
+    /// Source name, directory of pre-built scripts and directory of Python
+    /// sources of each location
+    locations: Vec<(&'static str, PathBuf, PathBuf)>,
+}
+
+impl ScriptLocator {
+    pub fn new(app_handle: &AppHandle) -> Self {
+        let mut locations = Vec::new();
+
+        if let Some(dir) = env::scripts_override(app_handle) {
+            locations.push(("override", dir.clone(), dir));
+        }
+        let exe_dir = std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf));
+        if let Some(dir) = &exe_dir {
+            locations.push(("executable", dir.join(SCRIPTS_DIR), dir.join(SOURCES_DIR)));
+        }
+        if let Ok(dir) = app_handle.path().resource_dir() {
+            locations.push(("bundled", dir.join(SCRIPTS_DIR), dir.join(SOURCES_DIR)));
+        }
+        if let Some(dir) = &exe_dir {
+            for ancestor in dir.ancestors().skip(1).take(DEVELOPMENT_DEPTH) {
+                locations.push((
+                    "development",
+                    ancestor.join(SCRIPTS_DIR),
+                    ancestor.join(SOURCES_DIR),
+                ));
+            }
+        }
+        if let Ok(dir) = std::env::current_dir() {
+            locations.push(("development", dir.join(SCRIPTS_DIR), dir.join(SOURCES_DIR)));
+        }
+
+        locations.dedup_by(|a, b| a.1 == b.1);
+        Self { locations }
+    }
+
+    fn check(&self, source: &str, path: &Path, pinned: Option<&ManifestEntry>) -> &'static str {
+        if !path.is_file() {
+            return "missing";
+        }
+        if !is_executable(path) {
+            return "not_executable";
+        }
+        let Some(pinned) = pinned else {
+            return "unpinned";
+        };
+        match sha256_file(path) {
+            Ok(hash) if hash == pinned.sha256 => "ok",
+            // A user-provided build is trusted even if it differs
+            _ if source == "override" => "unpinned",
+            _ => "checksum_mismatch",
+        }
+    }
+
+    /// Every location searched for a pre-built script and its verdict
+    pub fn diagnose(&self, script: &str) -> ScriptDiagnostic {
+        let file_name = executable_name(script);
+        let pinned = manifest().scripts.get(&file_name);
+
+        let mut diagnostic = ScriptDiagnostic {
+            script: file_name.clone(),
+            expected_version: pinned.map(|entry| entry.version.clone()),
+            resolved: None,
+            candidates: Vec::new(),
+        };
+        for (source, scripts_dir, _) in &self.locations {
+            let path = scripts_dir.join(&file_name);
+            let status = self.check(source, &path, pinned);
+            if diagnostic.resolved.is_none() && matches!(status, "ok" | "unpinned") {
+                diagnostic.resolved = Some(path.to_string_lossy().to_string());
+            }
+            diagnostic.candidates.push(ScriptCandidate {
+                source: source.to_string(),
+                path: path.to_string_lossy().to_string(),
+                status: status.to_string(),
+            });
+        }
+        diagnostic
+    }
+
+    /// Path of the first valid pre-built script, or where it was looked for
+    pub fn locate(&self, script: &str) -> Result<PathBuf, ScriptDiagnostic> {
+        let diagnostic = self.diagnose(script);
+        match &diagnostic.resolved {
+            Some(path) => {
+                debug!(path = %path, "Found DVC script");
+                Ok(PathBuf::from(path))
+            }
+            None => Err(diagnostic),
+        }
+    }
+
+    /// Python source of a script, for running it with a chosen interpreter
+    pub fn locate_source(&self, script: &str) -> Option<PathBuf> {
+        let file_name = format!("{}.py", script);
+        self.locations
+            .iter()
+            .map(|(_, _, sources_dir)| sources_dir.join(&file_name))
+            .find(|path| path.is_file())
+    }
+}
+
+/// Where each DVC script resolves from, with the reason every other location
+/// was rejected
+#[command]
+#[instrument(skip(app_handle))]
+pub fn diagnose_dvc_scripts(app_handle: AppHandle) -> Vec<ScriptDiagnostic> {
+    let locator = ScriptLocator::new(&app_handle);
+    SCRIPTS
+        .iter()
+        .map(|script| locator.diagnose(script))
+        .collect()
+}
+
+/// Sets the directory searched first for the pre-built scripts, or clears it
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_dvc_scripts_override(
+    app_handle: AppHandle,
+    path: Option<String>,
+) -> Result<Vec<ScriptDiagnostic>, String> {
+    if let Some(dir) = &path {
+        if !Path::new(dir).is_dir() {
+            return Err(format!("{} is not a directory", dir));
+        }
+    }
+    env::set_scripts_override(&app_handle, path)?;
+    Ok(diagnose_dvc_scripts(app_handle))
+}
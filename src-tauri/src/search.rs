@@ -0,0 +1,135 @@
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, State};
+
+use crate::file::{self, FileEntry};
+use crate::state::TreeCacheState;
+
+const DEFAULT_SEARCH_LIMIT: usize = 500;
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct SearchFilters {
+    /// Status names to keep: any git/DVC status, or "dvc-tracked"
+    pub statuses: Vec<String>,
+    pub extensions: Vec<String>,
+    pub min_size: Option<u64>,
+    pub max_size: Option<u64>,
+    pub include_directories: bool,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SearchResult {
+    pub entries: Vec<FileEntry>,
+    pub total_matches: usize,
+    pub truncated: bool,
+}
+
+enum QueryMatcher {
+    All,
+    Glob(glob::Pattern),
+    Substring(String),
+}
+
+impl QueryMatcher {
+    fn new(query: &str) -> Result<Self, String> {
+        let query = query.trim();
+        if query.is_empty() {
+            Ok(QueryMatcher::All)
+        } else if query.contains(['*', '?', '[']) {
+            glob::Pattern::new(query)
+                .map(QueryMatcher::Glob)
+                .map_err(|e| format!("Invalid glob pattern: {}", e))
+        } else {
+            Ok(QueryMatcher::Substring(query.to_lowercase()))
+        }
+    }
+
+    fn matches(&self, path: &str) -> bool {
+        match self {
+            QueryMatcher::All => true,
+            QueryMatcher::Glob(pattern) => {
+                let name = path.rsplit('/').next().unwrap_or(path);
+                pattern.matches(path) || pattern.matches(name)
+            }
+            QueryMatcher::Substring(needle) => path.to_lowercase().contains(needle),
+        }
+    }
+}
+
+fn matches_filters(entry: &FileEntry, filters: &SearchFilters) -> bool {
+    if entry.is_directory && !filters.include_directories {
+        return false;
+    }
+
+    if !filters.statuses.is_empty() {
+        let status_match = filters.statuses.iter().any(|status| {
+            if status == "dvc-tracked" {
+                entry.has_dvc_file
+            } else {
+                &entry.git_status == status
+            }
+        });
+        if !status_match {
+            return false;
+        }
+    }
+
+    if !filters.extensions.is_empty() {
+        let extension = entry
+            .path
+            .rsplit_once('.')
+            .map(|(_, ext)| ext.to_lowercase())
+            .unwrap_or_default();
+        let extension_match = filters
+            .extensions
+            .iter()
+            .any(|e| e.trim_start_matches('.').to_lowercase() == extension);
+        if !extension_match {
+            return false;
+        }
+    }
+
+    if filters.min_size.is_some_and(|min| entry.size < min) {
+        return false;
+    }
+    if filters.max_size.is_some_and(|max| entry.size > max) {
+        return false;
+    }
+
+    true
+}
+
+/// Searches the cached file tree of a repository, walking it first when no
+/// cached tree exists yet
+#[tauri::command]
+pub fn search_files(
+    app_handle: AppHandle,
+    tree_cache: State<'_, TreeCacheState>,
+    repo_path: String,
+    query: String,
+    filters: Option<SearchFilters>,
+) -> Result<SearchResult, String> {
+    let filters = filters.unwrap_or_default();
+    let matcher = QueryMatcher::new(&query)?;
+    let limit = filters.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+
+    let mut cache = tree_cache.lock().map_err(|e| e.to_string())?;
+    if cache.get(&repo_path).is_none() {
+        let entries = file::walk_file_tree(&app_handle, &repo_path)?;
+        cache.insert(repo_path.clone(), entries);
+    }
+    let entries = cache.get(&repo_path).map(|e| e.as_slice()).unwrap_or(&[]);
+
+    let matches: Vec<&FileEntry> = entries
+        .iter()
+        .filter(|entry| matcher.matches(&entry.path) && matches_filters(entry, &filters))
+        .collect();
+
+    let total_matches = matches.len();
+    Ok(SearchResult {
+        entries: matches.into_iter().take(limit).cloned().collect(),
+        total_matches,
+        truncated: total_matches > limit,
+    })
+}
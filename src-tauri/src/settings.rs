@@ -0,0 +1,120 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+use crate::auto_fetch::AutoFetchSettings;
+use crate::file::TreeSettings;
+use crate::git::PullStrategy;
+use crate::hooks::HookSettings;
+use crate::notifications::NotificationSettings;
+use crate::size_history::SizeAlertSettings;
+use crate::transfer;
+use crate::watchdog::WatchdogSettings;
+
+const SETTINGS_FILE: &str = "project-settings.json";
+
+/// Limits for DVC pushes and pulls
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TransferLimits {
+    pub max_concurrency: usize,
+    /// Upload rate limit in KiB/s; `None` is unlimited
+    pub upload_kib_per_sec: Option<u64>,
+    /// Download rate limit in KiB/s; `None` is unlimited
+    pub download_kib_per_sec: Option<u64>,
+}
+
+impl Default for TransferLimits {
+    fn default() -> Self {
+        Self {
+            max_concurrency: 4,
+            upload_kib_per_sec: None,
+            download_kib_per_sec: None,
+        }
+    }
+}
+
+/// Settings stored per repository in the app config directory
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProjectSettings {
+    pub transfer_limits: TransferLimits,
+    pub hooks: HookSettings,
+    pub auto_fetch: AutoFetchSettings,
+    /// Strategy `git_pull` uses when none is given
+    pub pull_strategy: PullStrategy,
+    pub size_alerts: SizeAlertSettings,
+    pub tree: TreeSettings,
+    pub notifications: NotificationSettings,
+    pub watchdog: WatchdogSettings,
+}
+
+fn settings_path(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_config_dir()
+        .map_err(|e| format!("Failed to get app config directory: {}", e))?;
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create app config directory: {}", e))?;
+    Ok(dir.join(SETTINGS_FILE))
+}
+
+fn read_all(app_handle: &AppHandle) -> Result<HashMap<String, ProjectSettings>, String> {
+    let path = settings_path(app_handle)?;
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read settings: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse settings: {}", e))
+}
+
+/// Key a repository is stored under, independent of trailing separators
+pub(crate) fn project_key(repo_root: &Path) -> String {
+    repo_root
+        .to_string_lossy()
+        .trim_end_matches(['/', '\\'])
+        .to_string()
+}
+
+/// Saved settings of every repository, keyed by repository root
+pub(crate) fn load_all(app_handle: &AppHandle) -> Result<HashMap<String, ProjectSettings>, String> {
+    read_all(app_handle)
+}
+
+/// Settings of a repository, falling back to defaults when none were saved
+pub fn load(app_handle: &AppHandle, repo_root: &Path) -> Result<ProjectSettings, String> {
+    Ok(read_all(app_handle)?
+        .remove(&project_key(repo_root))
+        .unwrap_or_default())
+}
+
+/// Applies `update` to the settings of a repository and saves them
+pub fn update(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    update: impl FnOnce(&mut ProjectSettings),
+) -> Result<ProjectSettings, String> {
+    let mut all = read_all(app_handle)?;
+    let settings = all.entry(project_key(repo_root)).or_default();
+    update(settings);
+    let updated = settings.clone();
+
+    let content = serde_json::to_string_pretty(&all)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    fs::write(settings_path(app_handle)?, content)
+        .map_err(|e| format!("Failed to write settings: {}", e))?;
+    Ok(updated)
+}
+
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_project_settings(
+    app_handle: AppHandle,
+    repo_path: String,
+) -> Result<ProjectSettings, String> {
+    load(&app_handle, &transfer::repo_root(&repo_path)?)
+}
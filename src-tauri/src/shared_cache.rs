@@ -0,0 +1,257 @@
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+use walkdir::WalkDir;
+
+use crate::dvc_config::{self, DvcConfig};
+use crate::{audit, checkout, dvcfile, transfer};
+
+#[derive(Debug, Serialize)]
+pub struct SharedCacheResult {
+    pub cache_dir: String,
+    /// Objects moved from the previous cache into the shared one
+    pub migrated: usize,
+    /// Objects the shared cache already had, e.g. from another user
+    pub already_present: usize,
+    /// Workspace files linked again after their cache objects moved
+    pub relinked: usize,
+}
+
+/// The shared cache must be an existing or creatable directory that this user
+/// can write to, since every add and pull stores objects there
+fn check_writable(dir: &Path) -> Result<(), String> {
+    if !dir.is_absolute() {
+        return Err(format!(
+            "Shared cache path must be absolute: {}",
+            dir.display()
+        ));
+    }
+    if dir.exists() && !dir.is_dir() {
+        return Err(format!("{} is not a directory", dir.display()));
+    }
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+
+    let probe = dir.join(format!(
+        ".fenn-write-probe-{}",
+        uuid::Uuid::new_v4().simple()
+    ));
+    fs::write(&probe, b"probe").map_err(|e| {
+        format!(
+            "No write permission for shared cache {}: {}",
+            dir.display(),
+            e
+        )
+    })?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Moves an object, copying when the shared cache is on another filesystem
+fn move_object(source: &Path, dest: &Path) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    if fs::rename(source, dest).is_ok() {
+        return Ok(());
+    }
+    // Copy under a temporary name so other users never see a partial object
+    let temp = dest.with_file_name(format!(
+        ".{}.fenn-tmp",
+        dest.file_name().unwrap_or_default().to_string_lossy()
+    ));
+    fs::copy(source, &temp).map_err(|e| format!("Failed to copy {}: {}", source.display(), e))?;
+    fs::rename(&temp, dest).map_err(|e| format!("Failed to move {}: {}", dest.display(), e))?;
+    fs::remove_file(source).map_err(|e| format!("Failed to remove {}: {}", source.display(), e))
+}
+
+/// Objects moved by a migration, kept so a failure can move them back
+#[derive(Default)]
+struct Migration {
+    moved: Vec<(PathBuf, PathBuf)>,
+    already_present: usize,
+}
+
+impl Migration {
+    /// Moves the migrated objects back into the previous cache
+    fn rollback(&self) {
+        for (source, dest) in self.moved.iter().rev() {
+            if let Err(e) = move_object(dest, source) {
+                tracing::warn!("Failed to move {} back: {}", dest.display(), e);
+            }
+        }
+    }
+}
+
+/// Moves every object of `from` that `to` lacks into `to`, moving them all
+/// back on failure. Objects are content-addressed, so one that already exists
+/// in `to` is identical and its local copy is left for the caller to drop.
+fn migrate_objects(from: &Path, to: &Path) -> Result<Migration, String> {
+    let mut migration = Migration::default();
+    if !from.is_dir() {
+        return Ok(migration);
+    }
+
+    for entry in WalkDir::new(from).into_iter().filter_map(|e| e.ok()) {
+        if !entry.file_type().is_file() {
+            continue;
+        }
+        let Ok(relative) = entry.path().strip_prefix(from) else {
+            continue;
+        };
+        let dest = to.join(relative);
+        if dest.exists() {
+            migration.already_present += 1;
+            continue;
+        }
+        if let Err(e) = move_object(entry.path(), &dest) {
+            migration.rollback();
+            return Err(e);
+        }
+        migration.moved.push((entry.path().to_path_buf(), dest));
+    }
+    Ok(migration)
+}
+
+/// Resolves symlinks in the existing part of a path, so paths that don't
+/// exist yet can still be compared
+fn resolve(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    while !existing.exists() {
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+    let mut resolved = existing
+        .canonicalize()
+        .unwrap_or_else(|_| existing.to_path_buf());
+    resolved.extend(missing.into_iter().rev());
+    resolved
+}
+
+fn write_cache_config(repo_root: &Path, path: &str) -> Result<(), String> {
+    // `cache.shared = group` makes DVC create objects the whole group can read
+    let config_path = dvc_config::config_path(repo_root);
+    let mut config = DvcConfig::read(&config_path)?;
+    config.set("cache", "dir", path);
+    config.set("cache", "shared", "group");
+    config.write(&config_path)
+}
+
+fn set_cache(repo_path: &str, path: &str) -> Result<SharedCacheResult, String> {
+    let repo_root = transfer::repo_root(repo_path)?;
+    let shared = PathBuf::from(path);
+    let current = dvcfile::cache_dir(&repo_root);
+
+    // Moving a cache into its own subdirectory, or into a directory that
+    // contains it, would walk and delete the objects being moved
+    let (shared_resolved, current_resolved) = (resolve(&shared), resolve(&current));
+    let same = shared_resolved == current_resolved;
+    if !same
+        && (shared_resolved.starts_with(&current_resolved)
+            || current_resolved.starts_with(&shared_resolved))
+    {
+        return Err(format!(
+            "Shared cache {} must not be inside or contain the current cache {}",
+            shared.display(),
+            current.display()
+        ));
+    }
+    check_writable(&shared)?;
+
+    let migration = if same {
+        Migration::default()
+    } else {
+        migrate_objects(&current, &shared)?
+    };
+    if let Err(e) = write_cache_config(&repo_root, path) {
+        migration.rollback();
+        return Err(e);
+    }
+
+    // The previous cache only holds objects the shared one has as well now
+    if !same && current.is_dir() {
+        if let Err(e) = fs::remove_dir_all(&current) {
+            tracing::warn!("Failed to remove {}: {}", current.display(), e);
+        }
+    }
+
+    // Symlinks into the previous cache dangle now; checking out again links
+    // the workspace to the shared objects
+    let relinked = if same {
+        0
+    } else {
+        let pointers = dvcfile::find_pointer_files(&repo_root);
+        let summary = checkout::checkout_pointers(&repo_root, &pointers, false)?;
+        summary.added.len() + summary.modified.len()
+    };
+
+    Ok(SharedCacheResult {
+        cache_dir: path.to_string(),
+        migrated: migration.moved.len(),
+        already_present: migration.already_present,
+        relinked,
+    })
+}
+
+/// Points the repository's DVC cache at a directory shared with other users
+/// and moves the objects of the current cache into it
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_shared_cache(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+) -> Result<SharedCacheResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "set_shared_cache",
+        json!({ "path": path }),
+        || set_cache(&repo_path, &path),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn shared_cache_takes_over_the_objects_of_the_current_cache() {
+        let mut fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let md5 = fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+        let shared = fixture.scratch_path("shared-cache");
+
+        let result = set_cache(&fixture.path_string(), &shared.to_string_lossy()).unwrap();
+        assert_eq!(result.migrated, 1);
+        assert_eq!(dvcfile::cache_dir(fixture.path()), shared);
+        assert!(dvcfile::cache_object_path(&shared, &md5).exists());
+        assert!(!fixture.path().join(".dvc/cache").exists());
+        assert_eq!(fixture.read_file("data/train.csv"), "a,b\n1,2\n");
+    }
+
+    #[test]
+    fn shared_cache_refuses_paths_nested_with_the_current_cache() {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let md5 = fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+        let cache = fixture.path().join(".dvc/cache");
+
+        for path in [cache.join("shared"), fixture.path().join(".dvc")] {
+            let result = set_cache(&fixture.path_string(), &path.to_string_lossy());
+            assert!(result.is_err());
+        }
+        assert_eq!(dvcfile::cache_dir(fixture.path()), cache);
+        assert!(dvcfile::cache_object_path(&cache, &md5).exists());
+    }
+}
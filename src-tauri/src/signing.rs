@@ -0,0 +1,463 @@
+use git2::{Commit, Oid, Repository, Signature, Tree};
+use serde::{Deserialize, Serialize};
+use ssh_key::{HashAlg, LineEnding, PrivateKey, PublicKey, SshSig};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::ssh;
+
+/// Namespace git signs commits in with SSH keys
+const SSH_NAMESPACE: &str = "git";
+
+/// Key type of a signing key, stored as git's `gpg.format`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningFormat {
+    Openpgp,
+    Ssh,
+}
+
+/// Whether the app signs commits and with which key, kept in the repository's
+/// git config (`commit.gpgsign`, `gpg.format`, `user.signingkey`) so git on
+/// the command line signs the same way
+#[derive(Debug, Serialize)]
+pub struct CommitSigningConfig {
+    pub enabled: bool,
+    pub format: SigningFormat,
+    /// GPG key id, or path of the SSH private key
+    pub key: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SigningKey {
+    pub format: SigningFormat,
+    /// Value for `set_commit_signing`'s `key`
+    pub key: String,
+    pub label: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CommitSignature {
+    pub commit_id: String,
+    pub signed: bool,
+    pub format: Option<SigningFormat>,
+    /// Whether the signature matches the commit
+    pub valid: bool,
+    /// Who the key belongs to, when GPG or the allowed signers file knows it
+    pub signer: Option<String>,
+    /// GPG key id or SSH key fingerprint
+    pub key: Option<String>,
+    pub message: String,
+}
+
+/// Outcome of checking a signature
+struct Verification {
+    valid: bool,
+    signer: Option<String>,
+    key: Option<String>,
+    message: String,
+}
+
+fn signing_config(repo: &Repository) -> Result<CommitSigningConfig, String> {
+    let config = repo
+        .config()
+        .map_err(|e| format!("Failed to read git config: {}", e))?;
+    let format = match config.get_string("gpg.format").ok().as_deref() {
+        Some("ssh") => SigningFormat::Ssh,
+        _ => SigningFormat::Openpgp,
+    };
+    Ok(CommitSigningConfig {
+        enabled: config.get_bool("commit.gpgsign").unwrap_or(false),
+        format,
+        key: config.get_string("user.signingkey").ok(),
+    })
+}
+
+fn gpg_program(repo: &Repository) -> String {
+    repo.config()
+        .and_then(|config| config.get_string("gpg.program"))
+        .unwrap_or_else(|_| "gpg".to_string())
+}
+
+/// Runs a program with `input` on stdin, returning its output
+fn run_with_input(mut command: Command, input: &[u8]) -> Result<std::process::Output, String> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(input)
+            .map_err(|e| format!("Failed to write to {}: {}", program, e))?;
+    }
+    child
+        .wait_with_output()
+        .map_err(|e| format!("Failed to run {}: {}", program, e))
+}
+
+fn sign_gpg(repo: &Repository, key: Option<&str>, buffer: &str) -> Result<String, String> {
+    let mut command = Command::new(gpg_program(repo));
+    command.args(["--status-fd=2", "-bsa"]);
+    if let Some(key) = key {
+        command.args(["-u", key]);
+    }
+    let output = run_with_input(command, buffer.as_bytes())?;
+    if !output.status.success() {
+        return Err(format!(
+            "Failed to sign commit with GPG: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Loads an SSH private key, decrypting app-managed keys with the passphrase
+/// they were unlocked with this session
+fn ssh_private_key(app_handle: &AppHandle, key_path: &str) -> Result<PrivateKey, String> {
+    let path = Path::new(key_path);
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", key_path, e))?;
+    let private_key = PrivateKey::from_openssh(&content)
+        .map_err(|e| format!("{} is not an SSH private key: {}", key_path, e))?;
+    if !private_key.is_encrypted() {
+        return Ok(private_key);
+    }
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let passphrase = ssh::session_passphrase(app_handle, &name).ok_or_else(|| {
+        format!(
+            "SSH key '{}' is locked; unlock it with its passphrase first",
+            name
+        )
+    })?;
+    private_key
+        .decrypt(&passphrase)
+        .map_err(|_| format!("Failed to decrypt SSH key '{}'", name))
+}
+
+fn sign_ssh(app_handle: &AppHandle, key: Option<&str>, buffer: &str) -> Result<String, String> {
+    let key = key.ok_or("Select an SSH key to sign commits with")?;
+    let private_key = ssh_private_key(app_handle, key)?;
+    private_key
+        .sign(SSH_NAMESPACE, HashAlg::Sha512, buffer.as_bytes())
+        .and_then(|signature| signature.to_pem(LineEnding::LF))
+        .map_err(|e| format!("Failed to sign commit with SSH key: {}", e))
+}
+
+/// Points HEAD, or the branch it is on, at a commit created without a ref
+fn update_head(repo: &Repository, commit_id: Oid, message: &str) -> Result<(), String> {
+    let head = repo
+        .find_reference("HEAD")
+        .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+    let reflog = format!("commit: {}", message.lines().next().unwrap_or_default());
+    match head.symbolic_target() {
+        Some(branch) => repo.reference(branch, commit_id, true, &reflog).map(|_| ()),
+        None => repo.set_head_detached(commit_id),
+    }
+    .map_err(|e| format!("Failed to update HEAD: {}", e))
+}
+
+/// Creates a commit on HEAD like `Repository::commit`, signed with the
+/// configured key when the repository has `commit.gpgsign` set
+pub(crate) fn commit(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    author: &Signature,
+    committer: &Signature,
+    message: &str,
+    tree: &Tree,
+    parents: &[&Commit],
+) -> Result<Oid, String> {
+    let config = signing_config(repo)?;
+    if !config.enabled {
+        return repo
+            .commit(Some("HEAD"), author, committer, message, tree, parents)
+            .map_err(|e| format!("Failed to create commit: {}", e));
+    }
+
+    let buffer = repo
+        .commit_create_buffer(author, committer, message, tree, parents)
+        .map_err(|e| format!("Failed to create commit: {}", e))?;
+    let buffer = buffer
+        .as_str()
+        .ok_or("Commit contents are not valid UTF-8")?;
+    let signature = match config.format {
+        SigningFormat::Openpgp => sign_gpg(repo, config.key.as_deref(), buffer)?,
+        SigningFormat::Ssh => sign_ssh(app_handle, config.key.as_deref(), buffer)?,
+    };
+    let commit_id = repo
+        .commit_signed(buffer, &signature, None)
+        .map_err(|e| format!("Failed to create signed commit: {}", e))?;
+    update_head(repo, commit_id, message)?;
+    Ok(commit_id)
+}
+
+fn verify_gpg(
+    repo: &Repository,
+    signature: &[u8],
+    signed_data: &[u8],
+) -> Result<Verification, String> {
+    let signature_path =
+        std::env::temp_dir().join(format!("fenn-sig-{}.asc", uuid::Uuid::new_v4().simple()));
+    fs::write(&signature_path, signature)
+        .map_err(|e| format!("Failed to write signature: {}", e))?;
+    let mut command = Command::new(gpg_program(repo));
+    command
+        .args(["--status-fd=1", "--verify"])
+        .arg(&signature_path)
+        .arg("-");
+    let output = run_with_input(command, signed_data);
+    let _ = fs::remove_file(&signature_path);
+    let output = output?;
+
+    let status = String::from_utf8_lossy(&output.stdout);
+    let mut valid = false;
+    let mut signer = None;
+    let mut key = None;
+    let mut message = "Signature could not be checked".to_string();
+    for line in status.lines() {
+        let mut fields = line.trim_start_matches("[GNUPG:] ").splitn(3, ' ');
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some("GOODSIG"), Some(id), uid) => {
+                valid = true;
+                key = Some(id.to_string());
+                signer = uid.map(str::to_string);
+                message = "Good signature".to_string();
+            }
+            (Some("BADSIG"), Some(id), uid) => {
+                key = Some(id.to_string());
+                signer = uid.map(str::to_string);
+                message = "Bad signature".to_string();
+            }
+            (Some("NO_PUBKEY"), Some(id), _) => {
+                key = Some(id.to_string());
+                message = format!("Public key {} is not in the keyring", id);
+            }
+            (Some("EXPKEYSIG"), Some(id), uid) => {
+                key = Some(id.to_string());
+                signer = uid.map(str::to_string);
+                message = "Signed with an expired key".to_string();
+            }
+            (Some("REVKEYSIG"), Some(id), uid) => {
+                key = Some(id.to_string());
+                signer = uid.map(str::to_string);
+                message = "Signed with a revoked key".to_string();
+            }
+            _ => {}
+        }
+    }
+    Ok(Verification {
+        valid,
+        signer,
+        key,
+        message,
+    })
+}
+
+/// Principal the allowed signers file (`gpg.ssh.allowedSignersFile`) gives
+/// the key
+fn allowed_signer(repo: &Repository, public_key: &PublicKey) -> Option<String> {
+    let path = repo
+        .config()
+        .ok()?
+        .get_path("gpg.ssh.allowedSignersFile")
+        .ok()?;
+    let content = fs::read_to_string(path).ok()?;
+    content.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        let principals = fields.first().filter(|first| !first.starts_with('#'))?;
+        // Options may come between the principals and the key
+        let key = fields[1..]
+            .windows(2)
+            .find_map(|pair| PublicKey::from_openssh(&pair.join(" ")).ok())?;
+        (key.key_data() == public_key.key_data()).then(|| principals.to_string())
+    })
+}
+
+fn verify_ssh(
+    repo: &Repository,
+    signature: &[u8],
+    signed_data: &[u8],
+) -> Result<Verification, String> {
+    let signature =
+        SshSig::from_pem(signature).map_err(|e| format!("Failed to parse signature: {}", e))?;
+    let public_key = PublicKey::from(signature.public_key().clone());
+    let fingerprint = public_key.fingerprint(HashAlg::Sha256).to_string();
+    if public_key
+        .verify(SSH_NAMESPACE, signed_data, &signature)
+        .is_err()
+    {
+        return Ok(Verification {
+            valid: false,
+            signer: None,
+            key: Some(fingerprint),
+            message: "Bad signature".to_string(),
+        });
+    }
+    let signer = allowed_signer(repo, &public_key);
+    let message = if signer.is_some() {
+        "Good signature"
+    } else {
+        "Good signature from a key not in the allowed signers file"
+    };
+    Ok(Verification {
+        valid: true,
+        signer,
+        key: Some(fingerprint),
+        message: message.to_string(),
+    })
+}
+
+/// Signing settings of the repository
+#[command]
+#[instrument(err(Debug))]
+pub fn get_commit_signing(repo_path: String) -> Result<CommitSigningConfig, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    signing_config(&repo)
+}
+
+/// Turns commit signing on or off for the repository and selects the key,
+/// writing the repository's git config
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_commit_signing(
+    app_handle: AppHandle,
+    repo_path: String,
+    enabled: bool,
+    format: SigningFormat,
+    key: Option<String>,
+) -> Result<CommitSigningConfig, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let key = key.filter(|key| !key.trim().is_empty());
+    if enabled {
+        // Sign something first so a wrong key or missing gpg shows up now
+        match format {
+            SigningFormat::Openpgp => sign_gpg(&repo, key.as_deref(), "test").map(|_| ())?,
+            SigningFormat::Ssh => sign_ssh(&app_handle, key.as_deref(), "test").map(|_| ())?,
+        }
+    }
+
+    let mut config = repo
+        .config()
+        .and_then(|config| config.open_level(git2::ConfigLevel::Local))
+        .map_err(|e| format!("Failed to open repository config: {}", e))?;
+    let format_name = match format {
+        SigningFormat::Openpgp => "openpgp",
+        SigningFormat::Ssh => "ssh",
+    };
+    config
+        .set_bool("commit.gpgsign", enabled)
+        .and_then(|_| config.set_str("gpg.format", format_name))
+        .map_err(|e| format!("Failed to write git config: {}", e))?;
+    match &key {
+        Some(key) => config.set_str("user.signingkey", key),
+        None => config
+            .remove("user.signingkey")
+            .or_else(|e| match e.code() {
+                git2::ErrorCode::NotFound => Ok(()),
+                _ => Err(e),
+            }),
+    }
+    .map_err(|e| format!("Failed to write git config: {}", e))?;
+    signing_config(&repo)
+}
+
+/// Keys commits can be signed with: GPG secret keys when gpg is installed and
+/// the app's SSH keys
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_signing_keys(app_handle: AppHandle) -> Result<Vec<SigningKey>, String> {
+    let mut keys = Vec::new();
+    if let Ok(output) = Command::new("gpg")
+        .args(["--list-secret-keys", "--with-colons"])
+        .output()
+    {
+        let listing = String::from_utf8_lossy(&output.stdout);
+        let mut key_id = None;
+        for line in listing.lines() {
+            let fields: Vec<&str> = line.split(':').collect();
+            match fields.first() {
+                Some(&"sec") => key_id = fields.get(4).map(|id| id.to_string()),
+                Some(&"uid") => {
+                    if let (Some(id), Some(uid)) = (key_id.take(), fields.get(9)) {
+                        keys.push(SigningKey {
+                            format: SigningFormat::Openpgp,
+                            label: format!("{} ({})", uid, id),
+                            key: id,
+                        });
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    for info in ssh::list_ssh_keys(app_handle)? {
+        keys.push(SigningKey {
+            format: SigningFormat::Ssh,
+            label: format!("{} ({})", info.name, info.fingerprint),
+            key: info.private_key_path,
+        });
+    }
+    Ok(keys)
+}
+
+/// Checks the GPG or SSH signature of a commit
+#[command]
+#[instrument(err(Debug))]
+pub fn verify_commit_signature(
+    repo_path: String,
+    commit_id: String,
+) -> Result<CommitSignature, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let oid = repo
+        .revparse_single(&commit_id)
+        .and_then(|object| object.peel_to_commit())
+        .map_err(|e| format!("Failed to find commit {}: {}", commit_id, e))?
+        .id();
+    let (signature, signed_data) = match repo.extract_signature(&oid, None) {
+        Ok(extracted) => extracted,
+        Err(e) if e.code() == git2::ErrorCode::NotFound => {
+            return Ok(CommitSignature {
+                commit_id: oid.to_string(),
+                signed: false,
+                format: None,
+                valid: false,
+                signer: None,
+                key: None,
+                message: "Commit is not signed".to_string(),
+            });
+        }
+        Err(e) => return Err(format!("Failed to read commit signature: {}", e)),
+    };
+
+    let format = if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+        SigningFormat::Ssh
+    } else {
+        SigningFormat::Openpgp
+    };
+    let verification = match format {
+        SigningFormat::Ssh => verify_ssh(&repo, &signature, &signed_data)?,
+        SigningFormat::Openpgp => verify_gpg(&repo, &signature, &signed_data)?,
+    };
+    Ok(CommitSignature {
+        commit_id: oid.to_string(),
+        signed: true,
+        format: Some(format),
+        valid: verification.valid,
+        signer: verification.signer,
+        key: verification.key,
+        message: verification.message,
+    })
+}
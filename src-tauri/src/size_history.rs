@@ -0,0 +1,301 @@
+use git2::{Delta, Oid, Repository};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use tauri::{command, AppHandle, Emitter};
+use tracing::{instrument, warn};
+
+use crate::db::{self, PooledConnection};
+use crate::{dvcfile, history, settings, transfer};
+
+pub const SIZE_ALERT_EVENT: &str = "dvc://size-alert";
+
+const SIZE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS dataset_sizes (
+    project TEXT NOT NULL,
+    path TEXT NOT NULL,
+    commit_id TEXT NOT NULL,
+    committed_at INTEGER NOT NULL,
+    size INTEGER NOT NULL,
+    nfiles INTEGER,
+    PRIMARY KEY (project, path, commit_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dataset_sizes_path ON dataset_sizes(project, path, committed_at);
+";
+
+/// When a commit growing a dataset raises an alert, stored with the project
+/// settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SizeAlertSettings {
+    pub enabled: bool,
+    /// Growth relative to the previous version that raises an alert
+    pub max_growth_percent: f64,
+    /// Growth below this many MiB never alerts, however large in percent
+    pub min_growth_mib: u64,
+}
+
+impl Default for SizeAlertSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_growth_percent: 50.0,
+            min_growth_mib: 100,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct SizeRecord {
+    pub commit_id: String,
+    pub short_id: String,
+    /// Commit time in seconds since the epoch
+    pub committed_at: i64,
+    pub size: u64,
+    pub nfiles: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SizeAlert {
+    /// Data path relative to the repository root
+    pub path: String,
+    pub commit_id: String,
+    pub previous_size: u64,
+    pub size: u64,
+    /// `None` when the previous version was empty
+    pub growth_percent: Option<f64>,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, SIZE_SCHEMA)
+}
+
+fn insert(
+    conn: &Connection,
+    project: &str,
+    path: &str,
+    commit_id: &str,
+    committed_at: i64,
+    size: u64,
+    nfiles: Option<u64>,
+) -> Result<(), String> {
+    conn.execute(
+        "INSERT OR IGNORE INTO dataset_sizes (project, path, commit_id, committed_at, size, nfiles)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![
+            project,
+            path,
+            commit_id,
+            committed_at,
+            size as i64,
+            nfiles.map(|nfiles| nfiles as i64)
+        ],
+    )
+    .map_err(|e| format!("Failed to record dataset size: {}", e))?;
+    Ok(())
+}
+
+/// Data path a pointer tracks: the pointer path without `.dvc`
+fn data_path(pointer: &str) -> &str {
+    pointer.strip_suffix(".dvc").unwrap_or(pointer)
+}
+
+/// Size and file count of the first output of a pointer blob
+fn pointer_size(repo: &Repository, blob_id: Oid) -> Option<(u64, Option<u64>)> {
+    let blob = repo.find_blob(blob_id).ok()?;
+    let content = String::from_utf8_lossy(blob.content());
+    let out = dvcfile::parse_dvc_file(&content)
+        .ok()?
+        .outs
+        .into_iter()
+        .next()?;
+    Some((out.size?, out.nfiles))
+}
+
+fn growth_alert(
+    settings: &SizeAlertSettings,
+    path: &str,
+    commit_id: &str,
+    previous_size: u64,
+    size: u64,
+) -> Option<SizeAlert> {
+    let growth = size.checked_sub(previous_size)?;
+    if growth < settings.min_growth_mib * 1024 * 1024 {
+        return None;
+    }
+    let growth_percent = (previous_size > 0).then(|| growth as f64 * 100.0 / previous_size as f64);
+    let exceeded = growth_percent.is_none_or(|percent| percent > settings.max_growth_percent);
+    exceeded.then(|| SizeAlert {
+        path: path.to_string(),
+        commit_id: commit_id.to_string(),
+        previous_size,
+        size,
+        growth_percent,
+    })
+}
+
+fn record(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    commit_id: Oid,
+) -> Result<Vec<SizeAlert>, String> {
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let alert_settings = settings::load(app_handle, repo_root)?.size_alerts;
+    let project = settings::project_key(repo_root);
+
+    let commit = repo
+        .find_commit(commit_id)
+        .map_err(|e| format!("Failed to find commit: {}", e))?;
+    let tree = commit
+        .tree()
+        .map_err(|e| format!("Failed to read commit tree: {}", e))?;
+    let parent_tree = commit.parent(0).ok().and_then(|parent| parent.tree().ok());
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(|e| format!("Failed to diff commit: {}", e))?;
+
+    let conn = open_connection(app_handle)?;
+    let mut alerts = Vec::new();
+    for delta in diff.deltas() {
+        if !matches!(delta.status(), Delta::Added | Delta::Modified) {
+            continue;
+        }
+        let Some(pointer) = delta.new_file().path().map(dvcfile::to_git_path) else {
+            continue;
+        };
+        if !pointer.ends_with(".dvc") {
+            continue;
+        }
+        let Some((size, nfiles)) = pointer_size(repo, delta.new_file().id()) else {
+            continue;
+        };
+        let path = data_path(&pointer);
+        insert(
+            &conn,
+            &project,
+            path,
+            &commit_id.to_string(),
+            commit.time().seconds(),
+            size,
+            nfiles,
+        )?;
+
+        let previous = match delta.status() {
+            Delta::Modified if alert_settings.enabled => pointer_size(repo, delta.old_file().id()),
+            _ => None,
+        };
+        if let Some((previous_size, _)) = previous {
+            alerts.extend(growth_alert(
+                &alert_settings,
+                path,
+                &commit_id.to_string(),
+                previous_size,
+                size,
+            ));
+        }
+    }
+    Ok(alerts)
+}
+
+/// Records the sizes of the datasets a new commit adds or changes, returning
+/// (and emitting) alerts for those that grew beyond the project's limits.
+/// Failing to record never fails the commit.
+pub(crate) fn record_commit(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    commit_id: Oid,
+) -> Vec<SizeAlert> {
+    match record(app_handle, repo, commit_id) {
+        Ok(alerts) => {
+            if !alerts.is_empty() {
+                if let Err(e) = app_handle.emit(SIZE_ALERT_EVENT, &alerts) {
+                    warn!("Failed to emit size alerts: {}", e);
+                }
+            }
+            alerts
+        }
+        Err(e) => {
+            warn!("Failed to record dataset sizes: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// Size of a dataset at every commit that changed it, oldest first. Commits
+/// made outside the app are filled in from the history of its pointer.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_size_history(
+    app_handle: AppHandle,
+    repo_path: String,
+    path: String,
+) -> Result<Vec<SizeRecord>, String> {
+    let repo_root = transfer::repo_root(&repo_path)?;
+    let project = settings::project_key(&repo_root);
+    let versions = history::file_history(repo_root.to_string_lossy().to_string(), path)?;
+    let Some(latest) = versions.first() else {
+        return Ok(Vec::new());
+    };
+    if !latest.is_dvc {
+        return Err(format!("{} is not tracked by DVC", latest.tracked_path));
+    }
+    let data_path = data_path(&latest.tracked_path);
+
+    let conn = open_connection(&app_handle)?;
+    for version in &versions {
+        if let Some(size) = version.size {
+            insert(
+                &conn,
+                &project,
+                data_path,
+                &version.commit_id,
+                version.timestamp,
+                size,
+                version.nfiles,
+            )?;
+        }
+    }
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT commit_id, committed_at, size, nfiles FROM dataset_sizes
+             WHERE project = ?1 AND path = ?2 ORDER BY committed_at, commit_id",
+        )
+        .map_err(|e| format!("Failed to query size history: {}", e))?;
+    let records = stmt
+        .query_map(params![project, data_path], |row| {
+            let commit_id: String = row.get(0)?;
+            let size: i64 = row.get(2)?;
+            let nfiles: Option<i64> = row.get(3)?;
+            Ok(SizeRecord {
+                short_id: commit_id.chars().take(7).collect(),
+                commit_id,
+                committed_at: row.get(1)?,
+                size: size as u64,
+                nfiles: nfiles.map(|nfiles| nfiles as u64),
+            })
+        })
+        .map_err(|e| format!("Failed to query size history: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read size history: {}", e))?;
+    Ok(records)
+}
+
+/// Saves when growing datasets raise an alert on commit
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_size_alerts(
+    app_handle: AppHandle,
+    repo_path: String,
+    size_alerts: SizeAlertSettings,
+) -> Result<SizeAlertSettings, String> {
+    if !size_alerts.max_growth_percent.is_finite() || size_alerts.max_growth_percent < 0.0 {
+        return Err("The growth limit must be a positive percentage".to_string());
+    }
+    let saved = settings::update(&app_handle, &transfer::repo_root(&repo_path)?, |settings| {
+        settings.size_alerts = size_alerts;
+    })?;
+    Ok(saved.size_alerts)
+}
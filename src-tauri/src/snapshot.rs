@@ -0,0 +1,430 @@
+use git2::build::CheckoutBuilder;
+use git2::{
+    BranchType, Oid, Repository, StashApplyOptions, StashFlags, StatusOptions, TreeWalkMode,
+    TreeWalkResult,
+};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+use walkdir::WalkDir;
+
+use crate::db::{self, PooledConnection};
+use crate::{audit, cache_link, checkout, dvc_compat, dvcfile, identity, repo_manager, settings};
+
+const SNAPSHOT_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS snapshots (
+    id TEXT PRIMARY KEY,
+    project TEXT NOT NULL,
+    label TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    commit_id TEXT NOT NULL,
+    branch TEXT,
+    stash_id TEXT,
+    data_files TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_snapshots_project ON snapshots(project, created_at);
+";
+
+#[derive(Debug, Serialize)]
+pub struct Snapshot {
+    pub id: String,
+    pub label: String,
+    pub created_at: String,
+    pub commit_id: String,
+    /// Branch checked out at the time, `None` for a detached HEAD
+    pub branch: Option<String>,
+    /// Stash commit holding uncommitted changes, if there were any
+    pub stash_id: Option<String>,
+    /// Number of DVC-tracked files whose content was captured
+    pub data_files: usize,
+}
+
+/// Content of a DVC-tracked file at snapshot time; the object is kept in the cache
+#[derive(Debug, Serialize, Deserialize)]
+struct DataFile {
+    path: String,
+    md5: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RestoreSnapshotResult {
+    pub snapshot: Snapshot,
+    /// Snapshot of the state that was replaced, when it had uncommitted changes
+    pub safety_snapshot: Option<Snapshot>,
+    /// Data files rewritten from the cache
+    pub restored_data: usize,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, SNAPSHOT_SCHEMA)
+}
+
+fn open_repo(repo_path: &str) -> Result<(Repository, PathBuf), String> {
+    let repo =
+        Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    Ok((repo, repo_root))
+}
+
+/// Stash commits are kept reachable under this ref prefix so git never prunes them
+fn stash_ref(id: &str) -> String {
+    format!("refs/fenn/snapshots/{}", id)
+}
+
+fn has_changes(repo: &Repository) -> Result<bool, String> {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).include_ignored(false);
+    let statuses = repo
+        .statuses(Some(&mut options))
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+    Ok(!statuses.is_empty())
+}
+
+/// Records uncommitted changes as a stash commit and puts them straight back,
+/// so the workspace is left as it was
+fn capture_changes(repo: &mut Repository, id: &str, label: &str) -> Result<Option<Oid>, String> {
+    if !has_changes(repo)? {
+        return Ok(None);
+    }
+    let signature = identity::signature(repo)?;
+    let stash_id = repo
+        .stash_save(
+            &signature,
+            &format!("Snapshot: {}", label),
+            Some(StashFlags::INCLUDE_UNTRACKED),
+        )
+        .map_err(|e| format!("Failed to stash changes: {}", e))?;
+    repo.reference(&stash_ref(id), stash_id, true, "snapshot")
+        .map_err(|e| format!("Failed to keep snapshot changes: {}", e))?;
+
+    let mut options = StashApplyOptions::new();
+    options.reinstate_index();
+    repo.stash_pop(0, Some(&mut options))
+        .map_err(|e| format!("Failed to restore changes after snapshot: {}", e))?;
+    Ok(Some(stash_id))
+}
+
+/// Hashes every file of the workspace's DVC outputs and makes sure its
+/// content is in the cache, so the snapshot survives later edits
+fn capture_data(repo_root: &Path) -> Result<Vec<DataFile>, String> {
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let format = dvc_compat::repo_format(repo_root);
+    let mut files = Vec::new();
+
+    for pointer in dvcfile::find_pointer_files(repo_root) {
+        let Ok(dvc_file) = dvcfile::read_dvc_file(&pointer) else {
+            continue;
+        };
+        let base_dir = pointer.parent().unwrap_or(repo_root);
+        for out in &dvc_file.outs {
+            let data_path = base_dir.join(&out.path);
+            for entry in WalkDir::new(&data_path).into_iter().filter_map(|e| e.ok()) {
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+                let md5 = dvcfile::hash_file_for(entry.path(), format)?;
+                let object = dvcfile::cache_object_path_for(&cache_dir, &md5, format);
+                if !object.exists() {
+                    store_object(entry.path(), &object)?;
+                }
+                files.push(DataFile {
+                    path: dvcfile::to_git_path(&dvcfile::repo_relative_path(
+                        repo_root,
+                        entry.path(),
+                    )?),
+                    md5,
+                });
+            }
+        }
+    }
+    Ok(files)
+}
+
+fn store_object(source: &Path, object: &Path) -> Result<(), String> {
+    if let Some(parent) = object.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let temp = object.with_extension("fenn-tmp");
+    fs::copy(source, &temp).map_err(|e| format!("Failed to cache {}: {}", source.display(), e))?;
+    fs::rename(&temp, object).map_err(|e| format!("Failed to cache {}: {}", source.display(), e))
+}
+
+fn create(app_handle: &AppHandle, repo_path: &str, label: &str) -> Result<Snapshot, String> {
+    let (mut repo, repo_root) = open_repo(repo_path)?;
+    let (commit_id, branch) = {
+        let head = repo
+            .head()
+            .map_err(|e| format!("Failed to get HEAD: {}", e))?;
+        let commit = head
+            .peel_to_commit()
+            .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+        let branch = head
+            .is_branch()
+            .then(|| head.shorthand().map(str::to_string))
+            .flatten();
+        (commit.id().to_string(), branch)
+    };
+
+    let id = uuid::Uuid::new_v4().to_string();
+    let stash_id = capture_changes(&mut repo, &id, label)?.map(|oid| oid.to_string());
+    let data_files = capture_data(&repo_root)?;
+    let data_json = serde_json::to_string(&data_files)
+        .map_err(|e| format!("Failed to serialize snapshot data: {}", e))?;
+
+    let snapshot = Snapshot {
+        id,
+        label: label.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        commit_id,
+        branch,
+        stash_id,
+        data_files: data_files.len(),
+    };
+    let conn = open_connection(app_handle)?;
+    conn.execute(
+        "INSERT INTO snapshots (id, project, label, created_at, commit_id, branch, stash_id, data_files)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+        rusqlite::params![
+            snapshot.id,
+            settings::project_key(&repo_root),
+            snapshot.label,
+            snapshot.created_at,
+            snapshot.commit_id,
+            snapshot.branch,
+            snapshot.stash_id,
+            data_json
+        ],
+    )
+    .map_err(|e| format!("Failed to save snapshot: {}", e))?;
+    Ok(snapshot)
+}
+
+fn load(
+    conn: &Connection,
+    repo_root: &Path,
+    id: &str,
+) -> Result<(Snapshot, Vec<DataFile>), String> {
+    conn.query_row(
+        "SELECT id, label, created_at, commit_id, branch, stash_id, data_files
+         FROM snapshots WHERE project = ?1 AND id = ?2",
+        rusqlite::params![settings::project_key(repo_root), id],
+        |row| {
+            let data_json: String = row.get(6)?;
+            let data_files: Vec<DataFile> = serde_json::from_str(&data_json).unwrap_or_default();
+            Ok((
+                Snapshot {
+                    id: row.get(0)?,
+                    label: row.get(1)?,
+                    created_at: row.get(2)?,
+                    commit_id: row.get(3)?,
+                    branch: row.get(4)?,
+                    stash_id: row.get(5)?,
+                    data_files: data_files.len(),
+                },
+                data_files,
+            ))
+        },
+    )
+    .map_err(|e| format!("Snapshot {} not found: {}", id, e))
+}
+
+/// Puts the uncommitted changes of a stash commit back on top of the commit
+/// it was taken from: tracked files from its tree, staged changes from its
+/// index parent and untracked files from its third parent
+fn apply_stash(repo: &Repository, repo_root: &Path, stash_id: &str) -> Result<(), String> {
+    let stash = Oid::from_str(stash_id)
+        .and_then(|oid| repo.find_commit(oid))
+        .map_err(|e| format!("Failed to find snapshot changes: {}", e))?;
+    let tree = stash
+        .tree()
+        .map_err(|e| format!("Failed to read snapshot changes: {}", e))?;
+    repo.checkout_tree(tree.as_object(), Some(CheckoutBuilder::new().force()))
+        .map_err(|e| format!("Failed to restore changes: {}", e))?;
+
+    let staged = stash
+        .parent(1)
+        .and_then(|commit| commit.tree())
+        .map_err(|e| format!("Failed to read staged changes: {}", e))?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    index
+        .read_tree(&staged)
+        .map_err(|e| format!("Failed to restore staged changes: {}", e))?;
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    let Ok(untracked) = stash.parent(2).and_then(|commit| commit.tree()) else {
+        return Ok(());
+    };
+    let mut result = Ok(());
+    let walked = untracked.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let Ok(blob) = entry.to_object(repo).and_then(|o| o.peel_to_blob()) else {
+            return TreeWalkResult::Ok;
+        };
+        let path = repo_root.join(root).join(entry.name().unwrap_or_default());
+        let written = path
+            .parent()
+            .map_or(Ok(()), |parent| fs::create_dir_all(parent))
+            .and_then(|_| fs::write(&path, blob.content()));
+        match written {
+            Ok(()) => TreeWalkResult::Ok,
+            Err(e) => {
+                result = Err(format!("Failed to restore {}: {}", path.display(), e));
+                TreeWalkResult::Abort
+            }
+        }
+    });
+    result?;
+    walked.map_err(|e| format!("Failed to restore untracked files: {}", e))
+}
+
+fn restore(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    id: &str,
+) -> Result<RestoreSnapshotResult, String> {
+    let (repo, repo_root) = open_repo(repo_path)?;
+    let conn = open_connection(app_handle)?;
+    let (snapshot, data_files) = load(&conn, &repo_root, id)?;
+
+    // Nothing uncommitted is lost: the current state becomes a snapshot first
+    let safety_snapshot = if has_changes(&repo)? {
+        Some(create(
+            app_handle,
+            repo_path,
+            &format!("Before restoring \"{}\"", snapshot.label),
+        )?)
+    } else {
+        None
+    };
+
+    let commit_oid =
+        Oid::from_str(&snapshot.commit_id).map_err(|e| format!("Invalid commit id: {}", e))?;
+    let branch_at_commit = snapshot.branch.as_deref().filter(|branch| {
+        repo.find_branch(branch, BranchType::Local)
+            .ok()
+            .and_then(|b| b.get().target())
+            == Some(commit_oid)
+    });
+    match branch_at_commit {
+        Some(branch) => repo.set_head(&format!("refs/heads/{}", branch)),
+        None => repo.set_head_detached(commit_oid),
+    }
+    .map_err(|e| format!("Failed to move HEAD: {}", e))?;
+    repo.checkout_head(Some(CheckoutBuilder::new().force().remove_untracked(true)))
+        .map_err(|e| format!("Failed to check out snapshot commit: {}", e))?;
+
+    if let Some(stash_id) = &snapshot.stash_id {
+        apply_stash(&repo, &repo_root, stash_id)?;
+    }
+
+    let cache_dir = dvcfile::cache_dir(&repo_root);
+    let format = dvc_compat::repo_format(&repo_root);
+    let link_types = cache_link::configured(&repo_root);
+    let mut restored_data = 0;
+    for file in &data_files {
+        let dest = repo_root.join(&file.path);
+        if dest.exists() && dvcfile::hash_file_for(&dest, format)? == file.md5 {
+            continue;
+        }
+        let object = dvcfile::cache_object_path(&cache_dir, &file.md5);
+        if !object.exists() {
+            return Err(format!(
+                "The cached content of {} is missing, the snapshot cannot be fully restored",
+                file.path
+            ));
+        }
+        checkout::materialize(&object, &dest, &link_types)?;
+        restored_data += 1;
+    }
+
+    Ok(RestoreSnapshotResult {
+        snapshot,
+        safety_snapshot,
+        restored_data,
+    })
+}
+
+/// Captures the current commit, uncommitted changes and DVC data so the
+/// workspace can be brought back to this point with `restore_snapshot`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn create_snapshot(
+    app_handle: AppHandle,
+    repo_path: String,
+    label: String,
+) -> Result<Snapshot, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "create_snapshot",
+        json!({ "label": label }),
+        || create(&app_handle, &repo_path, &label),
+    )
+}
+
+/// Snapshots of a repository, newest first
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_snapshots(app_handle: AppHandle, repo_path: String) -> Result<Vec<Snapshot>, String> {
+    let (_, repo_root) = open_repo(&repo_path)?;
+    let conn = open_connection(&app_handle)?;
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, label, created_at, commit_id, branch, stash_id, data_files
+             FROM snapshots WHERE project = ?1 ORDER BY created_at DESC",
+        )
+        .map_err(|e| format!("Failed to query snapshots: {}", e))?;
+    let snapshots = stmt
+        .query_map([settings::project_key(&repo_root)], |row| {
+            let data_json: String = row.get(6)?;
+            Ok(Snapshot {
+                id: row.get(0)?,
+                label: row.get(1)?,
+                created_at: row.get(2)?,
+                commit_id: row.get(3)?,
+                branch: row.get(4)?,
+                stash_id: row.get(5)?,
+                data_files: serde_json::from_str::<Vec<DataFile>>(&data_json)
+                    .map(|files| files.len())
+                    .unwrap_or_default(),
+            })
+        })
+        .map_err(|e| format!("Failed to query snapshots: {}", e))?
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read snapshots: {}", e))?;
+    Ok(snapshots)
+}
+
+/// Brings the workspace back to a snapshot: its commit, its uncommitted
+/// changes and its DVC data. Uncommitted work is saved as a new snapshot first.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn restore_snapshot(
+    app_handle: AppHandle,
+    repo_path: String,
+    id: String,
+) -> Result<RestoreSnapshotResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "restore_snapshot",
+        json!({ "id": id }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                restore(&app_handle, &repo_path, &id)
+            })
+        },
+    )
+}
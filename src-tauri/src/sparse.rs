@@ -0,0 +1,268 @@
+use git2::build::CheckoutBuilder;
+use git2::{IndexEntryExtendedFlag, IndexEntryFlag, Repository, StatusOptions};
+use serde_json::json;
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{audit, repo_manager};
+
+/// Patterns file read by git itself, so the CLI honors the same sparse tree
+const SPARSE_CHECKOUT_FILE: &str = "info/sparse-checkout";
+
+/// Normalizes a directory pattern to a repository-relative path without
+/// leading or trailing separators
+fn normalize(pattern: &str) -> String {
+    pattern.replace('\\', "/").trim_matches('/').to_string()
+}
+
+/// Directories above `dir`, outermost first: `a/b/c` gives `a` and `a/b`
+fn ancestors(dir: &str) -> Vec<&str> {
+    dir.match_indices('/').map(|(i, _)| &dir[..i]).collect()
+}
+
+/// Whether a tracked file is part of the sparse tree, following git's cone
+/// mode: files of the listed directories, files directly inside their parent
+/// directories (which is where `.dvc` pointers of directory outputs live) and
+/// everything at the root. `.dvc/` is always kept so DVC keeps working.
+fn is_included(path: &str, dirs: &[String]) -> bool {
+    let parent = match path.rfind('/') {
+        Some(i) => &path[..i],
+        None => return true,
+    };
+    if parent == ".dvc" || parent.starts_with(".dvc/") {
+        return true;
+    }
+    dirs.iter().any(|dir| {
+        path.starts_with(&format!("{}/", dir))
+            || dir.starts_with(&format!("{}/", parent))
+            || dir == parent
+    })
+}
+
+/// Cone mode patterns file for `dirs`, as `git sparse-checkout set --cone`
+/// writes it
+fn patterns_file(dirs: &[String]) -> String {
+    let mut parents = BTreeSet::new();
+    for dir in dirs {
+        parents.extend(ancestors(dir));
+    }
+
+    let mut content = String::from("/*\n!/*/\n");
+    for parent in &parents {
+        content.push_str(&format!("/{}/\n!/{}/*/\n", parent, parent));
+    }
+    for dir in dirs {
+        content.push_str(&format!("/{}/\n", dir));
+    }
+    content
+}
+
+/// Directories of the sparse tree, or an empty list when the repository is
+/// not sparse
+fn read_patterns(repo: &Repository) -> Result<Vec<String>, String> {
+    let file = repo.path().join(SPARSE_CHECKOUT_FILE);
+    let enabled = repo
+        .config()
+        .and_then(|config| config.get_bool("core.sparseCheckout"))
+        .unwrap_or(false);
+    if !enabled || !file.exists() {
+        return Ok(Vec::new());
+    }
+
+    let content = fs::read_to_string(&file)
+        .map_err(|e| format!("Failed to read sparse-checkout patterns: {}", e))?;
+    // Parent directories are listed with a negation of their subdirectories;
+    // the directories without one are the recursive patterns
+    let negated: BTreeSet<&str> = content
+        .lines()
+        .filter_map(|line| line.strip_prefix("!/")?.strip_suffix("/*/"))
+        .collect();
+    Ok(content
+        .lines()
+        .filter(|line| !line.starts_with('!') && line.ends_with('/') && *line != "/*")
+        .map(|line| line.trim_matches('/'))
+        .filter(|dir| !dir.is_empty() && !negated.contains(dir))
+        .map(str::to_string)
+        .collect())
+}
+
+/// Removes the now empty directories above a removed file
+fn remove_empty_parents(repo_root: &Path, file: &Path) {
+    let mut dir = file.parent();
+    while let Some(current) = dir {
+        if current == repo_root || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+/// Limits the working tree to `dirs`, or restores the full tree when `dirs`
+/// is empty. Files left out are marked skip-worktree in the index and removed
+/// from disk; files brought back are checked out from the index.
+pub(crate) fn apply(repo: &Repository, dirs: &[String]) -> Result<Vec<String>, String> {
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let mut dirs: Vec<String> = dirs
+        .iter()
+        .map(|dir| normalize(dir))
+        .filter(|dir| !dir.is_empty())
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    // A clone made without checkout has an empty index to fill from HEAD
+    if index.is_empty() {
+        if let Ok(head) = repo.head().and_then(|head| head.peel_to_tree()) {
+            index
+                .read_tree(&head)
+                .and_then(|_| index.write())
+                .map_err(|e| format!("Failed to read HEAD tree: {}", e))?;
+        }
+    }
+
+    let mut status_opts = StatusOptions::new();
+    status_opts.include_untracked(false);
+    let statuses = repo
+        .statuses(Some(&mut status_opts))
+        .map_err(|e| format!("Failed to get status: {}", e))?;
+    let blocked: Vec<String> = statuses
+        .iter()
+        .filter(|entry| !entry.status().is_wt_deleted())
+        .filter_map(|entry| entry.path().map(str::to_string))
+        .filter(|path| !dirs.is_empty() && !is_included(path, &dirs))
+        .collect();
+    if !blocked.is_empty() {
+        return Err(format!(
+            "Commit or stash changes to {} before leaving them out of the working tree",
+            blocked.join(", ")
+        ));
+    }
+
+    let mut restore = Vec::new();
+    let entries: Vec<_> = index.iter().collect();
+    for mut entry in entries {
+        let path = String::from_utf8_lossy(&entry.path).to_string();
+        let included = dirs.is_empty() || is_included(&path, &dirs);
+        let skipped = entry.flags_extended & IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0;
+        let file = repo_root.join(&path);
+
+        if included {
+            if skipped {
+                entry.flags_extended &= !IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+            }
+            if !file.exists() {
+                restore.push(path);
+            }
+        } else {
+            entry.flags_extended |= IndexEntryExtendedFlag::SKIP_WORKTREE.bits();
+            if file.exists() {
+                fs::remove_file(&file).map_err(|e| format!("Failed to remove {}: {}", path, e))?;
+                remove_empty_parents(&repo_root, &file);
+            }
+        }
+        if entry.flags_extended == 0 {
+            entry.flags &= !IndexEntryFlag::EXTENDED.bits();
+        } else {
+            entry.flags |= IndexEntryFlag::EXTENDED.bits();
+        }
+        index
+            .add(&entry)
+            .map_err(|e| format!("Failed to update {}: {}", path, e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    if !restore.is_empty() {
+        let mut checkout = CheckoutBuilder::new();
+        checkout.force().disable_pathspec_match(true);
+        for path in &restore {
+            checkout.path(path.as_str());
+        }
+        repo.checkout_index(Some(&mut index), Some(&mut checkout))
+            .map_err(|e| format!("Failed to check out files: {}", e))?;
+    }
+
+    let mut config = repo
+        .config()
+        .map_err(|e| format!("Failed to open git config: {}", e))?;
+    let file = repo.path().join(SPARSE_CHECKOUT_FILE);
+    if dirs.is_empty() {
+        config
+            .set_bool("core.sparseCheckout", false)
+            .map_err(|e| format!("Failed to disable sparse checkout: {}", e))?;
+        if file.exists() {
+            fs::remove_file(&file)
+                .map_err(|e| format!("Failed to remove sparse-checkout patterns: {}", e))?;
+        }
+    } else {
+        if let Some(parent) = file.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::write(&file, patterns_file(&dirs))
+            .map_err(|e| format!("Failed to write sparse-checkout patterns: {}", e))?;
+        config
+            .set_bool("core.sparseCheckout", true)
+            .and_then(|_| config.set_bool("core.sparseCheckoutCone", true))
+            .map_err(|e| format!("Failed to enable sparse checkout: {}", e))?;
+    }
+
+    Ok(dirs)
+}
+
+/// Tracked paths left out of the working tree by sparse checkout
+pub(crate) fn skipped_paths(repo: &Repository) -> Result<HashSet<String>, String> {
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    Ok(index
+        .iter()
+        .filter(|entry| entry.flags_extended & IndexEntryExtendedFlag::SKIP_WORKTREE.bits() != 0)
+        .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+        .collect())
+}
+
+fn open_repo(repo_path: &str) -> Result<Repository, String> {
+    Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))
+}
+
+/// Directories the working tree is limited to; empty when the whole tree is
+/// checked out
+#[command]
+#[instrument(err(Debug))]
+pub fn get_sparse_checkout_patterns(repo_path: String) -> Result<Vec<String>, String> {
+    read_patterns(&open_repo(&repo_path)?)
+}
+
+/// Limits the working tree to the given directories, removing the files of
+/// the others and restoring files that come back into scope. An empty list
+/// checks out the whole tree again.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_sparse_checkout_patterns(
+    app_handle: AppHandle,
+    repo_path: String,
+    patterns: Vec<String>,
+) -> Result<Vec<String>, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "set_sparse_checkout_patterns",
+        json!({ "patterns": patterns }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                apply(&open_repo(&repo_path)?, &patterns)
+            })
+        },
+    )
+}
@@ -0,0 +1,260 @@
+use git2::{Cred, CredentialType};
+use serde::Serialize;
+use ssh_key::rand_core::OsRng;
+use ssh_key::{Algorithm, HashAlg, LineEnding, PrivateKey, PublicKey};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Manager, State};
+
+#[derive(Debug, Serialize)]
+pub struct SshKeyInfo {
+    pub name: String,
+    pub public_key: String,
+    pub fingerprint: String,
+    pub private_key_path: String,
+    pub encrypted: bool,
+}
+
+/// Passphrases entered this session, so encrypted keys can be used for git auth
+#[derive(Debug, Default)]
+pub struct SshPassphrases {
+    passphrases: HashMap<String, String>,
+}
+
+impl SshPassphrases {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub type SshPassphraseState = Mutex<SshPassphrases>;
+
+fn keys_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    let dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?
+        .join("ssh");
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create ssh directory: {}", e))?;
+    Ok(dir)
+}
+
+fn validate_key_name(name: &str) -> Result<(), String> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+        && !name.starts_with('.');
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "Invalid key name '{}': use letters, digits, '-', '_' or '.'",
+            name
+        ))
+    }
+}
+
+#[cfg(unix)]
+fn restrict_permissions(path: &std::path::Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+        .map_err(|e| format!("Failed to set key permissions: {}", e))
+}
+
+#[cfg(not(unix))]
+fn restrict_permissions(_path: &std::path::Path) -> Result<(), String> {
+    Ok(())
+}
+
+fn key_info(app_handle: &AppHandle, name: &str) -> Result<SshKeyInfo, String> {
+    let dir = keys_dir(app_handle)?;
+    let private_path = dir.join(name);
+    let public_path = dir.join(format!("{}.pub", name));
+
+    let public_openssh = fs::read_to_string(&public_path)
+        .map_err(|e| format!("Failed to read public key {}: {}", name, e))?;
+    let public_key = PublicKey::from_openssh(public_openssh.trim())
+        .map_err(|e| format!("Failed to parse public key {}: {}", name, e))?;
+
+    let private_openssh = fs::read_to_string(&private_path)
+        .map_err(|e| format!("Failed to read private key {}: {}", name, e))?;
+    let private_key = PrivateKey::from_openssh(&private_openssh)
+        .map_err(|e| format!("Failed to parse private key {}: {}", name, e))?;
+
+    Ok(SshKeyInfo {
+        name: name.to_string(),
+        public_key: public_openssh.trim().to_string(),
+        fingerprint: public_key.fingerprint(HashAlg::Sha256).to_string(),
+        private_key_path: private_path.to_string_lossy().to_string(),
+        encrypted: private_key.is_encrypted(),
+    })
+}
+
+/// Generates an ed25519 key pair under app data, optionally passphrase protected
+#[command]
+pub fn generate_ssh_key(
+    app_handle: AppHandle,
+    passphrases: State<'_, SshPassphraseState>,
+    name: String,
+    passphrase: Option<String>,
+    comment: Option<String>,
+) -> Result<SshKeyInfo, String> {
+    validate_key_name(&name)?;
+    let dir = keys_dir(&app_handle)?;
+    let private_path = dir.join(&name);
+    let public_path = dir.join(format!("{}.pub", name));
+
+    if private_path.exists() || public_path.exists() {
+        return Err(format!("An SSH key named '{}' already exists", name));
+    }
+
+    let mut private_key = PrivateKey::random(&mut OsRng, Algorithm::Ed25519)
+        .map_err(|e| format!("Failed to generate key: {}", e))?;
+    private_key.set_comment(comment.unwrap_or_else(|| format!("fenn-app {}", name)));
+
+    let public_openssh = private_key
+        .public_key()
+        .to_openssh()
+        .map_err(|e| format!("Failed to encode public key: {}", e))?;
+
+    let passphrase = passphrase.filter(|p| !p.is_empty());
+    let stored_key = match &passphrase {
+        Some(passphrase) => private_key
+            .encrypt(&mut OsRng, passphrase)
+            .map_err(|e| format!("Failed to encrypt key: {}", e))?,
+        None => private_key,
+    };
+    let private_openssh = stored_key
+        .to_openssh(LineEnding::LF)
+        .map_err(|e| format!("Failed to encode private key: {}", e))?;
+
+    fs::write(&private_path, private_openssh.as_bytes())
+        .map_err(|e| format!("Failed to write private key: {}", e))?;
+    restrict_permissions(&private_path)?;
+    fs::write(&public_path, format!("{}\n", public_openssh))
+        .map_err(|e| format!("Failed to write public key: {}", e))?;
+
+    if let Some(passphrase) = passphrase {
+        let mut passphrases = passphrases.lock().map_err(|e| e.to_string())?;
+        passphrases.passphrases.insert(name.clone(), passphrase);
+    }
+
+    key_info(&app_handle, &name)
+}
+
+#[command]
+pub fn list_ssh_keys(app_handle: AppHandle) -> Result<Vec<SshKeyInfo>, String> {
+    let dir = keys_dir(&app_handle)?;
+    let mut keys = Vec::new();
+
+    for entry in fs::read_dir(&dir).map_err(|e| format!("Failed to read ssh directory: {}", e))? {
+        let entry = entry.map_err(|e| format!("Failed to read directory entry: {}", e))?;
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some("pub") {
+            continue;
+        }
+        if let Some(name) = path.file_stem().and_then(|s| s.to_str()) {
+            if let Ok(info) = key_info(&app_handle, name) {
+                keys.push(info);
+            }
+        }
+    }
+
+    keys.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(keys)
+}
+
+/// Returns the OpenSSH public key line, ready to paste into GitHub/GitLab
+#[command]
+pub fn get_public_key(app_handle: AppHandle, name: String) -> Result<String, String> {
+    validate_key_name(&name)?;
+    Ok(key_info(&app_handle, &name)?.public_key)
+}
+
+/// Remembers the passphrase of an encrypted key for the rest of the session
+#[command]
+pub fn unlock_ssh_key(
+    app_handle: AppHandle,
+    passphrases: State<'_, SshPassphraseState>,
+    name: String,
+    passphrase: String,
+) -> Result<(), String> {
+    validate_key_name(&name)?;
+    let private_path = keys_dir(&app_handle)?.join(&name);
+    let private_openssh = fs::read_to_string(&private_path)
+        .map_err(|e| format!("Failed to read private key {}: {}", name, e))?;
+    let private_key = PrivateKey::from_openssh(&private_openssh)
+        .map_err(|e| format!("Failed to parse private key {}: {}", name, e))?;
+    private_key
+        .decrypt(&passphrase)
+        .map_err(|_| "Incorrect passphrase".to_string())?;
+
+    let mut passphrases = passphrases.lock().map_err(|e| e.to_string())?;
+    passphrases.passphrases.insert(name, passphrase);
+    Ok(())
+}
+
+/// Passphrase of an app-managed key unlocked this session
+pub(crate) fn session_passphrase(app_handle: &AppHandle, name: &str) -> Option<String> {
+    app_handle
+        .try_state::<SshPassphraseState>()
+        .and_then(|state| {
+            state
+                .lock()
+                .ok()
+                .and_then(|p| p.passphrases.get(name).cloned())
+        })
+}
+
+/// Git credential candidates in the order they are tried: the ssh-agent, then
+/// the app-managed keys
+pub fn credential_callback(
+    app_handle: &AppHandle,
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> {
+    let app_handle = app_handle.clone();
+    let mut attempt = 0usize;
+
+    move |_url, username_from_url, allowed_types| {
+        let username = username_from_url.unwrap_or("git");
+
+        if allowed_types.contains(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        if !allowed_types.contains(CredentialType::SSH_KEY) {
+            return Cred::default();
+        }
+
+        let current = attempt;
+        attempt += 1;
+        if current == 0 {
+            return Cred::ssh_key_from_agent(username);
+        }
+
+        let keys = list_ssh_keys(app_handle.clone()).unwrap_or_default();
+        let Some(key) = keys.get(current - 1) else {
+            return Err(git2::Error::from_str(
+                "No SSH key was accepted by the remote; add one of your keys to your account",
+            ));
+        };
+
+        let passphrase = session_passphrase(&app_handle, &key.name);
+        if key.encrypted && passphrase.is_none() {
+            return Err(git2::Error::from_str(&format!(
+                "SSH key '{}' is locked; unlock it with its passphrase first",
+                key.name
+            )));
+        }
+
+        let private_path = PathBuf::from(&key.private_key_path);
+        let public_path = private_path.with_file_name(format!("{}.pub", key.name));
+        Cred::ssh_key(
+            username,
+            Some(&public_path),
+            &private_path,
+            passphrase.as_deref(),
+        )
+    }
+}
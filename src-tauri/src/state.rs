@@ -1,30 +1,175 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
 use std::sync::Mutex;
 
+use crate::file::FileEntry;
+
+/// How much of a path the selection covers, for checkbox states in the UI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionState {
+    Selected,
+    /// Some but not all of a directory's contents are selected
+    Partial,
+    Unselected,
+}
+
+/// Selected paths, where a selected directory covers everything inside it
+/// except the paths deselected under it
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct SelectedFiles {
     pub paths: HashSet<String>,
+    /// Paths deselected inside a selected directory
+    #[serde(default)]
+    pub excluded: HashSet<String>,
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_end_matches('/').to_string()
+}
+
+/// Whether `path` is strictly inside `dir`
+fn is_within(path: &str, dir: &str) -> bool {
+    path.len() > dir.len() && path.starts_with(dir) && path.as_bytes()[dir.len()] == b'/'
 }
 
 impl SelectedFiles {
     pub fn new() -> Self {
         Self {
             paths: HashSet::new(),
+            excluded: HashSet::new(),
         }
     }
 
     pub fn add_path(&mut self, path: String) {
+        let path = normalize(&path);
+        self.excluded
+            .retain(|excluded| *excluded != path && !is_within(excluded, &path));
+        if self.is_selected(&path) {
+            return;
+        }
+        // Selections inside the directory are now covered by it
+        self.paths.retain(|selected| !is_within(selected, &path));
         self.paths.insert(path);
     }
 
     pub fn remove_path(&mut self, path: &str) {
-        self.paths.remove(path);
+        let path = normalize(path);
+        self.paths
+            .retain(|selected| *selected != path && !is_within(selected, &path));
+        self.excluded.retain(|excluded| !is_within(excluded, &path));
+        // Still covered by a selected directory, so exclude it from that
+        if self.is_selected(&path) {
+            self.excluded.insert(path);
+        }
     }
 
     pub fn clear(&mut self) {
         self.paths.clear();
+        self.excluded.clear();
+    }
+
+    /// Whether the path is selected itself or through a directory, decided by
+    /// the closest selection or exclusion at or above it
+    pub fn is_selected(&self, path: &str) -> bool {
+        let path = normalize(path);
+        let closest = |set: &HashSet<String>| {
+            set.iter()
+                .filter(|entry| **entry == path || is_within(&path, entry))
+                .map(String::len)
+                .max()
+        };
+        match (closest(&self.paths), closest(&self.excluded)) {
+            (Some(selected), Some(excluded)) => selected > excluded,
+            (selected, _) => selected.is_some(),
+        }
+    }
+
+    pub fn selection_state(&self, path: &str) -> SelectionState {
+        let path = normalize(path);
+        if self.is_selected(&path) {
+            if self
+                .excluded
+                .iter()
+                .any(|excluded| is_within(excluded, &path))
+            {
+                SelectionState::Partial
+            } else {
+                SelectionState::Selected
+            }
+        } else if self.paths.iter().any(|selected| is_within(selected, &path)) {
+            SelectionState::Partial
+        } else {
+            SelectionState::Unselected
+        }
+    }
+
+    /// The selection as paths to hand to `dvc add` or `git add`, resolved
+    /// against `root`. Directories stay whole unless something inside them is
+    /// deselected, or `files_only` asks for the files in them.
+    pub fn expand(&self, root: &Path, files_only: bool) -> Vec<String> {
+        let mut selected: Vec<&String> = self.paths.iter().collect();
+        selected.sort();
+        let mut expanded = Vec::new();
+        for path in selected {
+            self.expand_into(root, path, files_only, &mut expanded);
+        }
+        expanded
+    }
+
+    fn expand_into(&self, root: &Path, path: &str, files_only: bool, out: &mut Vec<String>) {
+        let full = root.join(path);
+        let walk = full.is_dir()
+            && (files_only
+                || self
+                    .excluded
+                    .iter()
+                    .any(|excluded| is_within(excluded, path)));
+        if !walk {
+            out.push(path.to_string());
+            return;
+        }
+        let mut children: Vec<String> = fs::read_dir(&full)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name != ".git")
+            .collect();
+        children.sort();
+        for name in children {
+            let child = format!("{}/{}", path, name);
+            if !self.excluded.contains(&child) {
+                self.expand_into(root, &child, files_only, out);
+            }
+        }
     }
 }
 
 pub type SelectedFilesState = Mutex<SelectedFiles>;
+
+/// Last tree walk per repository, reused by search so it doesn't re-walk the disk
+#[derive(Debug, Default)]
+pub struct TreeCache {
+    entries: HashMap<String, Vec<FileEntry>>,
+}
+
+impl TreeCache {
+    pub fn new() -> Self {
+        Self {
+            entries: HashMap::new(),
+        }
+    }
+
+    pub fn get(&self, repo_path: &str) -> Option<&Vec<FileEntry>> {
+        self.entries.get(repo_path)
+    }
+
+    pub fn insert(&mut self, repo_path: String, entries: Vec<FileEntry>) {
+        self.entries.insert(repo_path, entries);
+    }
+}
+
+pub type TreeCacheState = Mutex<TreeCache>;
@@ -0,0 +1,234 @@
+use chrono::{TimeZone, Utc};
+use git2::{ObjectType, Repository, Sort, TreeWalkMode, TreeWalkResult};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Mutex;
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+use crate::{branch_compare, repo_manager, settings, transfer};
+
+/// How many files `largest_files` lists
+const LARGEST_FILES: usize = 20;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PeriodCount {
+    /// "YYYY-MM"
+    pub period: String,
+    pub commits: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub commits: usize,
+    /// Commit times in seconds since the epoch
+    pub first_commit_at: i64,
+    pub last_commit_at: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LargeFile {
+    pub path: String,
+    pub size: u64,
+    pub dvc_tracked: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RepoStatistics {
+    /// Commit the statistics were computed at
+    pub head: String,
+    pub computed_at: String,
+    pub total_commits: usize,
+    /// Commits reachable from HEAD per month, oldest first
+    pub commits_by_month: Vec<PeriodCount>,
+    /// Most commits first
+    pub contributors: Vec<Contributor>,
+    /// DVC-tracked outputs at HEAD, with the size and file count their
+    /// pointers record
+    pub dataset_count: usize,
+    pub dataset_size: u64,
+    pub dataset_files: u64,
+    /// Files stored in git at HEAD, DVC pointers excluded
+    pub code_files: usize,
+    pub code_size: u64,
+    /// Share of the project's bytes that are DVC-tracked data
+    pub data_percent: f64,
+    pub largest_files: Vec<LargeFile>,
+}
+
+/// Statistics per repository root, valid while HEAD stays at the commit they
+/// were computed at
+#[derive(Debug, Default)]
+pub struct StatisticsCache {
+    entries: HashMap<String, RepoStatistics>,
+}
+
+impl StatisticsCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+pub type StatisticsCacheState = Mutex<StatisticsCache>;
+
+fn month(seconds: i64) -> String {
+    Utc.timestamp_opt(seconds, 0)
+        .single()
+        .map(|time| time.format("%Y-%m").to_string())
+        .unwrap_or_default()
+}
+
+fn compute(repo: &Repository) -> Result<RepoStatistics, String> {
+    let head = repo
+        .head()
+        .and_then(|head| head.peel_to_commit())
+        .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+    walk.set_sorting(Sort::TIME)
+        .and_then(|_| walk.push(head.id()))
+        .map_err(|e| format!("Failed to walk history: {}", e))?;
+
+    let mut total_commits = 0;
+    let mut by_month: BTreeMap<String, usize> = BTreeMap::new();
+    let mut contributors: HashMap<String, Contributor> = HashMap::new();
+    for oid in walk {
+        let commit = oid
+            .and_then(|oid| repo.find_commit(oid))
+            .map_err(|e| format!("Failed to read commit: {}", e))?;
+        let time = commit.time().seconds();
+        total_commits += 1;
+        *by_month.entry(month(time)).or_default() += 1;
+
+        let author = commit.author();
+        let email = author.email().unwrap_or_default().to_lowercase();
+        let contributor = contributors
+            .entry(email.clone())
+            .or_insert_with(|| Contributor {
+                name: author.name().unwrap_or_default().to_string(),
+                email,
+                commits: 0,
+                first_commit_at: time,
+                last_commit_at: time,
+            });
+        contributor.commits += 1;
+        contributor.first_commit_at = contributor.first_commit_at.min(time);
+        contributor.last_commit_at = contributor.last_commit_at.max(time);
+    }
+    let mut contributors: Vec<Contributor> = contributors.into_values().collect();
+    contributors.sort_by(|a, b| b.commits.cmp(&a.commits).then(a.name.cmp(&b.name)));
+
+    let tree = head
+        .tree()
+        .map_err(|e| format!("Failed to get HEAD tree: {}", e))?;
+    let odb = repo
+        .odb()
+        .map_err(|e| format!("Failed to open object database: {}", e))?;
+    let mut files = Vec::new();
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() != Some(ObjectType::Blob) {
+            return TreeWalkResult::Ok;
+        }
+        let name = entry.name().unwrap_or_default();
+        if name.ends_with(".dvc") || root.starts_with(".dvc/") {
+            return TreeWalkResult::Ok;
+        }
+        // Only the object header is read, not the content
+        if let Ok((size, _)) = odb.read_header(entry.id()) {
+            files.push(LargeFile {
+                path: format!("{}{}", root, name),
+                size: size as u64,
+                dvc_tracked: false,
+            });
+        }
+        TreeWalkResult::Ok
+    })
+    .map_err(|e| format!("Failed to walk tree: {}", e))?;
+    let code_files = files.len();
+    let code_size: u64 = files.iter().map(|file| file.size).sum();
+
+    let outputs = branch_compare::tracked_outputs(repo, &tree)?;
+    let dataset_count = outputs.len();
+    let dataset_size: u64 = outputs.values().filter_map(|out| out.size).sum();
+    let dataset_files: u64 = outputs
+        .values()
+        .map(|out| out.nfiles.unwrap_or(if out.is_dir() { 0 } else { 1 }))
+        .sum();
+    files.extend(outputs.into_iter().map(|(path, out)| LargeFile {
+        path,
+        size: out.size.unwrap_or(0),
+        dvc_tracked: true,
+    }));
+    files.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.path.cmp(&b.path)));
+    files.truncate(LARGEST_FILES);
+
+    let total_size = code_size + dataset_size;
+    let data_percent = if total_size == 0 {
+        0.0
+    } else {
+        dataset_size as f64 * 100.0 / total_size as f64
+    };
+
+    Ok(RepoStatistics {
+        head: head.id().to_string(),
+        computed_at: Utc::now().to_rfc3339(),
+        total_commits,
+        commits_by_month: by_month
+            .into_iter()
+            .map(|(period, commits)| PeriodCount { period, commits })
+            .collect(),
+        contributors,
+        dataset_count,
+        dataset_size,
+        dataset_files,
+        code_files,
+        code_size,
+        data_percent,
+        largest_files: files,
+    })
+}
+
+/// Overview of a project for its dashboard: commits per month, contributors,
+/// DVC dataset totals, how much of the project is code versus data and its
+/// largest files. Cached until HEAD moves, unless `refresh` is set.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn get_repo_statistics(
+    app_handle: AppHandle,
+    repo_path: String,
+    refresh: Option<bool>,
+) -> Result<RepoStatistics, String> {
+    let cache = app_handle.state::<StatisticsCacheState>();
+    let key = settings::project_key(&transfer::repo_root(&repo_path)?);
+    repo_manager::read(&app_handle, &repo_path, |repo| {
+        let head = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .map(|oid| oid.to_string());
+        if !refresh.unwrap_or(false) {
+            let cached = cache
+                .lock()
+                .map_err(|_| "Failed to lock statistics cache".to_string())?
+                .entries
+                .get(&key)
+                .filter(|statistics| Some(&statistics.head) == head.as_ref())
+                .cloned();
+            if let Some(statistics) = cached {
+                return Ok(statistics);
+            }
+        }
+
+        let statistics = compute(repo)?;
+        cache
+            .lock()
+            .map_err(|_| "Failed to lock statistics cache".to_string())?
+            .entries
+            .insert(key.clone(), statistics.clone());
+        Ok(statistics)
+    })
+}
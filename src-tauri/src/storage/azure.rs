@@ -0,0 +1,322 @@
+//! Azure Blob Storage backend authenticated with Shared Key signing or SAS tokens
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::{self, File};
+use std::path::Path;
+
+use super::{
+    http_agent, http_error, prefixed_key, read_chunk, split_url, uri_encode, with_retry,
+    write_response, ProgressFn, ProgressReader, RemoteStorage, StorageError, TransferConfig,
+};
+use crate::credentials::{self, AzureAuth, AzureCredentials};
+use crate::dvc_config::RemoteConfig;
+
+type HmacSha256 = Hmac<Sha256>;
+
+const API_VERSION: &str = "2021-08-06";
+
+pub struct AzureStorage {
+    container: String,
+    prefix: String,
+    /// Blob service endpoint without a trailing slash
+    endpoint: String,
+    credentials: AzureCredentials,
+    agent: ureq::Agent,
+    config: TransferConfig,
+}
+
+impl AzureStorage {
+    pub fn from_remote(remote: &RemoteConfig, config: &TransferConfig) -> Result<Self, String> {
+        let (container, prefix) = split_url(&remote.url, "azure")?;
+        let credentials = credentials::azure_credentials(remote)?;
+        let endpoint = credentials
+            .endpoint
+            .clone()
+            .unwrap_or_else(|| {
+                format!("https://{}.blob.core.windows.net", credentials.account_name)
+            })
+            .trim_end_matches('/')
+            .to_string();
+
+        Ok(Self {
+            container: container.to_string(),
+            prefix,
+            endpoint,
+            credentials,
+            agent: http_agent(),
+            config: config.clone(),
+        })
+    }
+
+    /// Path of a blob relative to the endpoint host, already URL-encoded
+    fn blob_path(&self, key: &str) -> String {
+        format!(
+            "{}/{}",
+            uri_encode(&self.container, false),
+            uri_encode(&prefixed_key(&self.prefix, key), true)
+        )
+    }
+
+    /// Canonical form of a request signed with Shared Key, see "Authorize with
+    /// Shared Key" in the Azure Storage REST reference
+    fn string_to_sign(
+        &self,
+        method: &str,
+        url_path: &str,
+        query: &[(&str, String)],
+        headers: &[(String, String)],
+        content_length: u64,
+        content_type: &str,
+    ) -> String {
+        let mut canonical_headers: Vec<&(String, String)> = headers
+            .iter()
+            .filter(|(name, _)| name.starts_with("x-ms-"))
+            .collect();
+        canonical_headers.sort();
+
+        let mut canonical_query: Vec<(String, &str)> = query
+            .iter()
+            .map(|(name, value)| (name.to_lowercase(), value.as_str()))
+            .collect();
+        canonical_query.sort();
+
+        let mut string_to_sign = format!(
+            "{}\n\n\n{}\n\n{}\n\n\n\n\n\n\n",
+            method,
+            if content_length > 0 {
+                content_length.to_string()
+            } else {
+                String::new()
+            },
+            content_type
+        );
+        for (name, value) in canonical_headers {
+            string_to_sign.push_str(&format!("{}:{}\n", name, value.trim()));
+        }
+        string_to_sign.push_str(&format!("/{}{}", self.credentials.account_name, url_path));
+        for (name, value) in canonical_query {
+            string_to_sign.push_str(&format!("\n{}:{}", name, value));
+        }
+        string_to_sign
+    }
+
+    fn shared_key_authorization(
+        &self,
+        account_key: &str,
+        string_to_sign: &str,
+    ) -> Result<String, StorageError> {
+        let secret = BASE64
+            .decode(account_key)
+            .map_err(|e| StorageError::fatal(format!("Invalid Azure account key: {}", e)))?;
+        let mut mac = HmacSha256::new_from_slice(&secret).expect("HMAC accepts keys of any length");
+        mac.update(string_to_sign.as_bytes());
+        let signature = BASE64.encode(mac.finalize().into_bytes());
+
+        Ok(format!(
+            "SharedKey {}:{}",
+            self.credentials.account_name, signature
+        ))
+    }
+
+    /// Builds an authenticated request for a blob. `content_length` and
+    /// `content_type` must match the body that is sent, as both are signed.
+    fn request(
+        &self,
+        method: &str,
+        key: &str,
+        query: &[(&str, String)],
+        extra_headers: &[(&str, &str)],
+        content_length: u64,
+        content_type: &str,
+    ) -> Result<ureq::Request, StorageError> {
+        let blob_path = self.blob_path(key);
+        let url_path = {
+            let after_scheme = self
+                .endpoint
+                .split_once("://")
+                .map(|(_, rest)| rest)
+                .unwrap_or(&self.endpoint);
+            let base_path = after_scheme
+                .split_once('/')
+                .map(|(_, path)| format!("/{}", path))
+                .unwrap_or_default();
+            format!("{}/{}", base_path, blob_path)
+        };
+
+        let mut headers: Vec<(String, String)> = vec![
+            (
+                "x-ms-date".to_string(),
+                chrono::Utc::now()
+                    .format("%a, %d %b %Y %H:%M:%S GMT")
+                    .to_string(),
+            ),
+            ("x-ms-version".to_string(), API_VERSION.to_string()),
+        ];
+        headers.extend(
+            extra_headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+
+        let mut query_string = query
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, uri_encode(value, false)))
+            .collect::<Vec<_>>()
+            .join("&");
+        let authorization = match &self.credentials.auth {
+            AzureAuth::SharedKey(account_key) => {
+                let string_to_sign = self.string_to_sign(
+                    method,
+                    &url_path,
+                    query,
+                    &headers,
+                    content_length,
+                    content_type,
+                );
+                Some(self.shared_key_authorization(account_key, &string_to_sign)?)
+            }
+            AzureAuth::Sas(token) => {
+                if !query_string.is_empty() {
+                    query_string.push('&');
+                }
+                query_string.push_str(token);
+                None
+            }
+        };
+
+        let url = if query_string.is_empty() {
+            format!("{}/{}", self.endpoint, blob_path)
+        } else {
+            format!("{}/{}?{}", self.endpoint, blob_path, query_string)
+        };
+
+        let mut request = self.agent.request(method, &url);
+        for (name, value) in &headers {
+            request = request.set(name, value);
+        }
+        if let Some(authorization) = authorization {
+            request = request.set("Authorization", &authorization);
+        }
+        if !content_type.is_empty() {
+            request = request.set("Content-Type", content_type);
+        }
+        Ok(request)
+    }
+
+    fn put_blob(
+        &self,
+        key: &str,
+        source: &Path,
+        size: u64,
+        progress: ProgressFn,
+    ) -> Result<(), StorageError> {
+        let file = File::open(source).map_err(|e| {
+            StorageError::fatal(format!("Failed to open {}: {}", source.display(), e))
+        })?;
+        self.request(
+            "PUT",
+            key,
+            &[],
+            &[("x-ms-blob-type", "BlockBlob")],
+            size,
+            "application/octet-stream",
+        )?
+        .set("Content-Length", &size.to_string())
+        .send(ProgressReader::new(file, progress))
+        .map_err(|e| http_error("upload blob", e))?;
+        Ok(())
+    }
+
+    /// Uploads large files as staged blocks committed with a block list
+    fn block_upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String> {
+        let mut file = File::open(source)
+            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        let mut buffer = vec![0u8; self.config.part_size as usize];
+        let mut block_ids = Vec::new();
+
+        loop {
+            let filled = read_chunk(&mut file, &mut buffer, source)?;
+            if filled == 0 {
+                break;
+            }
+            // Block IDs must all have the same length within a blob
+            let block_id = BASE64.encode(format!("block-{:08}", block_ids.len()));
+            let block = &buffer[..filled];
+
+            with_retry(self.config.max_retries, || {
+                self.request(
+                    "PUT",
+                    key,
+                    &[("comp", "block".to_string()), ("blockid", block_id.clone())],
+                    &[],
+                    filled as u64,
+                    "application/octet-stream",
+                )?
+                .set("Content-Length", &filled.to_string())
+                .send(ProgressReader::new(block, progress))
+                .map_err(|e| http_error("upload block", e))
+            })?;
+
+            block_ids.push(block_id);
+        }
+
+        let mut body = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?><BlockList>");
+        for id in &block_ids {
+            body.push_str(&format!("<Latest>{}</Latest>", id));
+        }
+        body.push_str("</BlockList>");
+
+        with_retry(self.config.max_retries, || {
+            self.request(
+                "PUT",
+                key,
+                &[("comp", "blocklist".to_string())],
+                &[],
+                body.len() as u64,
+                "application/xml",
+            )?
+            .send_bytes(body.as_bytes())
+            .map_err(|e| http_error("commit block list", e))
+        })?;
+        Ok(())
+    }
+}
+
+impl RemoteStorage for AzureStorage {
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        with_retry(self.config.max_retries, || {
+            match self.request("HEAD", key, &[], &[], 0, "")?.call() {
+                Ok(_) => Ok(true),
+                Err(ureq::Error::Status(404, _)) => Ok(false),
+                Err(e) => Err(http_error("check blob", e)),
+            }
+        })
+    }
+
+    fn upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String> {
+        let size = fs::metadata(source)
+            .map_err(|e| format!("Failed to stat {}: {}", source.display(), e))?
+            .len();
+
+        if size > self.config.multipart_threshold {
+            return self.block_upload(key, source, progress);
+        }
+
+        with_retry(self.config.max_retries, || {
+            self.put_blob(key, source, size, progress)
+        })
+    }
+
+    fn download(&self, key: &str, dest: &Path, progress: ProgressFn) -> Result<(), String> {
+        with_retry(self.config.max_retries, || {
+            let response = self
+                .request("GET", key, &[], &[], 0, "")?
+                .call()
+                .map_err(|e| http_error("download blob", e))?;
+
+            write_response(response, dest, progress)
+        })
+    }
+}
@@ -0,0 +1,283 @@
+//! Google Cloud Storage backend using the JSON API with OAuth2 access tokens
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD as BASE64_URL, Engine as _};
+use rsa::pkcs1v15::SigningKey;
+use rsa::pkcs8::DecodePrivateKey;
+use rsa::signature::{SignatureEncoding, Signer};
+use rsa::RsaPrivateKey;
+use serde_json::{json, Value};
+use sha2::Sha256;
+use std::fs::{self, File};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use super::{
+    http_agent, http_error, prefixed_key, read_chunk, split_url, uri_encode, with_retry,
+    write_response, ProgressFn, ProgressReader, RemoteStorage, StorageError, TransferConfig,
+};
+use crate::credentials::{self, GcsCredentials};
+use crate::dvc_config::RemoteConfig;
+
+const API_BASE: &str = "https://storage.googleapis.com";
+const SCOPE: &str = "https://www.googleapis.com/auth/devstorage.read_write";
+const REFRESH_TOKEN_URI: &str = "https://oauth2.googleapis.com/token";
+
+/// Resumable upload chunks must be multiples of 256 KiB
+const CHUNK_ALIGNMENT: u64 = 256 * 1024;
+
+pub struct GcsStorage {
+    bucket: String,
+    prefix: String,
+    credentials: GcsCredentials,
+    /// Cached access token and the instant it stops being usable
+    token: Mutex<Option<(String, Instant)>>,
+    agent: ureq::Agent,
+    config: TransferConfig,
+}
+
+/// Builds the RS256-signed JWT a service account exchanges for an access token
+fn service_account_assertion(
+    client_email: &str,
+    private_key: &str,
+    token_uri: &str,
+) -> Result<String, String> {
+    let now = chrono::Utc::now().timestamp();
+    let header = json!({ "alg": "RS256", "typ": "JWT" });
+    let claims = json!({
+        "iss": client_email,
+        "scope": SCOPE,
+        "aud": token_uri,
+        "iat": now,
+        "exp": now + 3600,
+    });
+    let message = format!(
+        "{}.{}",
+        BASE64_URL.encode(header.to_string()),
+        BASE64_URL.encode(claims.to_string())
+    );
+
+    let key = RsaPrivateKey::from_pkcs8_pem(private_key)
+        .map_err(|e| format!("Failed to parse service account key: {}", e))?;
+    let signature = SigningKey::<Sha256>::new(key).sign(message.as_bytes());
+
+    Ok(format!(
+        "{}.{}",
+        message,
+        BASE64_URL.encode(signature.to_bytes())
+    ))
+}
+
+impl GcsStorage {
+    pub fn from_remote(remote: &RemoteConfig, config: &TransferConfig) -> Result<Self, String> {
+        let (bucket, prefix) = split_url(&remote.url, "gs")?;
+
+        Ok(Self {
+            bucket: bucket.to_string(),
+            prefix,
+            credentials: credentials::gcs_credentials(remote)?,
+            token: Mutex::new(None),
+            agent: http_agent(),
+            config: config.clone(),
+        })
+    }
+
+    fn fetch_token(&self) -> Result<(String, u64), StorageError> {
+        let response = match &self.credentials {
+            GcsCredentials::ServiceAccount {
+                client_email,
+                private_key,
+                token_uri,
+            } => {
+                let assertion = service_account_assertion(client_email, private_key, token_uri)
+                    .map_err(StorageError::fatal)?;
+                self.agent.post(token_uri).send_form(&[
+                    ("grant_type", "urn:ietf:params:oauth:grant-type:jwt-bearer"),
+                    ("assertion", &assertion),
+                ])
+            }
+            GcsCredentials::AuthorizedUser {
+                client_id,
+                client_secret,
+                refresh_token,
+            } => self.agent.post(REFRESH_TOKEN_URI).send_form(&[
+                ("grant_type", "refresh_token"),
+                ("client_id", client_id),
+                ("client_secret", client_secret),
+                ("refresh_token", refresh_token),
+            ]),
+        };
+
+        let body: Value = response
+            .map_err(|e| http_error("get Google access token", e))?
+            .into_json()
+            .map_err(|e| {
+                StorageError::retryable(format!("Failed to parse token response: {}", e))
+            })?;
+        let token = body
+            .get("access_token")
+            .and_then(|v| v.as_str())
+            .ok_or_else(|| StorageError::fatal("Token response has no access_token"))?;
+        let expires_in = body
+            .get("expires_in")
+            .and_then(|v| v.as_u64())
+            .unwrap_or(3600);
+        Ok((token.to_string(), expires_in))
+    }
+
+    /// Returns a valid access token, refreshing it a minute before it expires
+    fn access_token(&self) -> Result<String, StorageError> {
+        let mut cached = self
+            .token
+            .lock()
+            .map_err(|_| StorageError::fatal("Token cache lock poisoned"))?;
+        if let Some((token, expires_at)) = cached.as_ref() {
+            if Instant::now() < *expires_at {
+                return Ok(token.clone());
+            }
+        }
+
+        let (token, expires_in) = self.fetch_token()?;
+        let lifetime = Duration::from_secs(expires_in.saturating_sub(60));
+        *cached = Some((token.clone(), Instant::now() + lifetime));
+        Ok(token)
+    }
+
+    fn request(&self, method: &str, url: &str) -> Result<ureq::Request, StorageError> {
+        let token = self.access_token()?;
+        Ok(self
+            .agent
+            .request(method, url)
+            .set("Authorization", &format!("Bearer {}", token)))
+    }
+
+    fn object_url(&self, key: &str) -> String {
+        format!(
+            "{}/storage/v1/b/{}/o/{}",
+            API_BASE,
+            uri_encode(&self.bucket, false),
+            uri_encode(&prefixed_key(&self.prefix, key), false)
+        )
+    }
+
+    fn upload_url(&self, key: &str, upload_type: &str) -> String {
+        format!(
+            "{}/upload/storage/v1/b/{}/o?uploadType={}&name={}",
+            API_BASE,
+            uri_encode(&self.bucket, false),
+            upload_type,
+            uri_encode(&prefixed_key(&self.prefix, key), false)
+        )
+    }
+
+    fn simple_upload(
+        &self,
+        key: &str,
+        source: &Path,
+        size: u64,
+        progress: ProgressFn,
+    ) -> Result<(), StorageError> {
+        let file = File::open(source).map_err(|e| {
+            StorageError::fatal(format!("Failed to open {}: {}", source.display(), e))
+        })?;
+        self.request("POST", &self.upload_url(key, "media"))?
+            .set("Content-Type", "application/octet-stream")
+            .set("Content-Length", &size.to_string())
+            .send(ProgressReader::new(file, progress))
+            .map_err(|e| http_error("upload object", e))?;
+        Ok(())
+    }
+
+    /// Uploads large files in chunks through a resumable upload session
+    fn resumable_upload(
+        &self,
+        key: &str,
+        source: &Path,
+        size: u64,
+        progress: ProgressFn,
+    ) -> Result<(), String> {
+        let session = with_retry(self.config.max_retries, || {
+            let response = self
+                .request("POST", &self.upload_url(key, "resumable"))?
+                .set("X-Upload-Content-Length", &size.to_string())
+                .send_json(json!({}))
+                .map_err(|e| http_error("start resumable upload", e))?;
+            response
+                .header("Location")
+                .map(|s| s.to_string())
+                .ok_or_else(|| StorageError::fatal("Resumable upload has no session URL"))
+        })?;
+
+        let chunk_size = (self.config.part_size / CHUNK_ALIGNMENT).max(1) * CHUNK_ALIGNMENT;
+        let mut file = File::open(source)
+            .map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+        let mut buffer = vec![0u8; chunk_size as usize];
+        let mut offset = 0u64;
+
+        while offset < size {
+            let filled = read_chunk(&mut file, &mut buffer, source)?;
+            if filled == 0 {
+                return Err(format!("{} changed during upload", source.display()));
+            }
+            let chunk = &buffer[..filled];
+            let range = format!("bytes {}-{}/{}", offset, offset + filled as u64 - 1, size);
+
+            with_retry(self.config.max_retries, || {
+                let response = self
+                    .request("PUT", &session)?
+                    .set("Content-Range", &range)
+                    .set("Content-Length", &filled.to_string())
+                    .send(ProgressReader::new(chunk, progress))
+                    .map_err(|e| http_error("upload chunk", e))?;
+                // 308 means the chunk was stored and more are expected
+                match response.status() {
+                    200 | 201 | 308 => Ok(()),
+                    status => Err(StorageError::retryable(format!(
+                        "Unexpected status {} while uploading chunk",
+                        status
+                    ))),
+                }
+            })?;
+
+            offset += filled as u64;
+        }
+        Ok(())
+    }
+}
+
+impl RemoteStorage for GcsStorage {
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        with_retry(self.config.max_retries, || {
+            match self.request("GET", &self.object_url(key))?.call() {
+                Ok(_) => Ok(true),
+                Err(ureq::Error::Status(404, _)) => Ok(false),
+                Err(e) => Err(http_error("check object", e)),
+            }
+        })
+    }
+
+    fn upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String> {
+        let size = fs::metadata(source)
+            .map_err(|e| format!("Failed to stat {}: {}", source.display(), e))?
+            .len();
+
+        if size > self.config.multipart_threshold {
+            return self.resumable_upload(key, source, size, progress);
+        }
+
+        with_retry(self.config.max_retries, || {
+            self.simple_upload(key, source, size, progress)
+        })
+    }
+
+    fn download(&self, key: &str, dest: &Path, progress: ProgressFn) -> Result<(), String> {
+        with_retry(self.config.max_retries, || {
+            let response = self
+                .request("GET", &format!("{}?alt=media", self.object_url(key)))?
+                .call()
+                .map_err(|e| http_error("download object", e))?;
+
+            write_response(response, dest, progress)
+        })
+    }
+}
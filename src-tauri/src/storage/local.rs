@@ -0,0 +1,92 @@
+//! Backend for DVC remotes on local disks or mounted network drives
+
+use std::fs::{self, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use super::{ProgressFn, RemoteStorage};
+use crate::dvc_config::RemoteConfig;
+
+const BUFFER_SIZE: usize = 1024 * 1024;
+
+pub struct LocalStorage {
+    root: PathBuf,
+}
+
+/// Copies a file, preferring a reflink (copy-on-write clone) and then a hard
+/// link, which are near-instant on filesystems that support them. Falls back to
+/// a buffered copy that reports progress.
+pub fn link_or_copy(source: &Path, dest: &Path, progress: ProgressFn) -> Result<(), String> {
+    let size = fs::metadata(source)
+        .map_err(|e| format!("Failed to stat {}: {}", source.display(), e))?
+        .len();
+
+    if reflink_copy::reflink(source, dest).is_ok() || fs::hard_link(source, dest).is_ok() {
+        progress(size);
+        return Ok(());
+    }
+
+    let mut reader =
+        File::open(source).map_err(|e| format!("Failed to open {}: {}", source.display(), e))?;
+    let mut writer =
+        File::create(dest).map_err(|e| format!("Failed to create {}: {}", dest.display(), e))?;
+    let mut buffer = vec![0u8; BUFFER_SIZE];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        writer
+            .write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to write {}: {}", dest.display(), e))?;
+        progress(read as u64);
+    }
+    writer
+        .sync_all()
+        .map_err(|e| format!("Failed to flush {}: {}", dest.display(), e))
+}
+
+impl LocalStorage {
+    pub fn from_remote(remote: &RemoteConfig) -> Result<Self, String> {
+        let root = PathBuf::from(remote.url.strip_prefix("file://").unwrap_or(&remote.url));
+        if !root.is_dir() {
+            return Err(format!(
+                "DVC remote '{}' points to {}, which is not an accessible directory",
+                remote.name,
+                root.display()
+            ));
+        }
+        Ok(Self { root })
+    }
+}
+
+impl RemoteStorage for LocalStorage {
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        Ok(self.root.join(key).is_file())
+    }
+
+    fn upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String> {
+        let dest = self.root.join(key);
+        let parent = dest.parent().ok_or("Invalid remote object path")?;
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+
+        // Copy next to the destination first so other clients never see a
+        // partially written object
+        let temp = parent.join(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+        let result = link_or_copy(source, &temp, progress).and_then(|_| {
+            fs::rename(&temp, &dest)
+                .map_err(|e| format!("Failed to move {} into place: {}", dest.display(), e))
+        });
+        if result.is_err() {
+            let _ = fs::remove_file(&temp);
+        }
+        result
+    }
+
+    fn download(&self, key: &str, dest: &Path, progress: ProgressFn) -> Result<(), String> {
+        link_or_copy(&self.root.join(key), dest, progress)
+    }
+}
@@ -0,0 +1,294 @@
+//! Native backends for DVC remotes. Objects are addressed by their remote key,
+//! e.g. `files/md5/ab/cdef...` for DVC 3 remotes.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::dvc_compat::DvcFormat;
+use crate::dvc_config::RemoteConfig;
+
+pub mod azure;
+pub mod gcs;
+pub mod local;
+pub mod s3;
+pub mod sftp;
+
+/// Callback receiving the number of bytes transferred since the previous call
+pub type ProgressFn<'a> = &'a (dyn Fn(u64) + Sync);
+
+pub trait RemoteStorage: Send + Sync {
+    /// Returns true when the object is present on the remote
+    fn exists(&self, key: &str) -> Result<bool, String>;
+
+    /// Uploads a local file to the given key
+    fn upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String>;
+
+    /// Downloads the given key into a local file
+    fn download(&self, key: &str, dest: &Path, progress: ProgressFn) -> Result<(), String>;
+}
+
+/// Limits applied by the transfer engine and backends
+#[derive(Debug, Clone)]
+pub struct TransferConfig {
+    pub concurrency: usize,
+    pub max_retries: u32,
+    /// Files above this size use multipart uploads where the backend supports it
+    pub multipart_threshold: u64,
+    pub part_size: u64,
+}
+
+impl Default for TransferConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_retries: 3,
+            multipart_threshold: 64 * 1024 * 1024,
+            part_size: 16 * 1024 * 1024,
+        }
+    }
+}
+
+/// Error from a backend call, flagged with whether retrying could help
+#[derive(Debug)]
+pub struct StorageError {
+    pub message: String,
+    pub retryable: bool,
+}
+
+impl StorageError {
+    pub fn fatal(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: false,
+        }
+    }
+
+    pub fn retryable(message: impl Into<String>) -> Self {
+        Self {
+            message: message.into(),
+            retryable: true,
+        }
+    }
+}
+
+/// Runs an operation, retrying retryable failures with exponential backoff
+pub fn with_retry<T>(
+    max_retries: u32,
+    mut operation: impl FnMut() -> Result<T, StorageError>,
+) -> Result<T, String> {
+    let mut attempt = 0;
+    loop {
+        match operation() {
+            Ok(value) => return Ok(value),
+            Err(error) if error.retryable && attempt < max_retries => {
+                let delay = Duration::from_millis(500 * 2u64.pow(attempt));
+                tracing::warn!(
+                    "Transfer attempt {} failed, retrying in {:?}: {}",
+                    attempt + 1,
+                    delay,
+                    error.message
+                );
+                thread::sleep(delay);
+                attempt += 1;
+            }
+            Err(error) => return Err(error.message),
+        }
+    }
+}
+
+/// Classifies an HTTP client error: throttling, server errors and transport
+/// failures are worth retrying, other client errors are not
+pub fn http_error(action: &str, error: ureq::Error) -> StorageError {
+    match error {
+        ureq::Error::Status(code, response) => {
+            let body = response.into_string().unwrap_or_default();
+            let message = format!("Failed to {}: HTTP {} {}", action, code, body.trim());
+            if code == 429 || code >= 500 {
+                StorageError::retryable(message)
+            } else {
+                StorageError::fatal(message)
+            }
+        }
+        ureq::Error::Transport(transport) => {
+            StorageError::retryable(format!("Failed to {}: {}", action, transport))
+        }
+    }
+}
+
+/// HTTP agent shared by the backends. Redirects are disabled since resumable
+/// uploads answer with `308 Resume Incomplete`, which is not a redirect.
+pub fn http_agent() -> ureq::Agent {
+    ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(30))
+        .timeout_read(Duration::from_secs(300))
+        .redirects(0)
+        .build()
+}
+
+/// RFC 3986 encoding of a URL component; `/` is kept for object paths
+pub fn uri_encode(value: &str, keep_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            b'/' if keep_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// Joins the path prefix of a remote URL with an object key
+pub fn prefixed_key(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}/{}", prefix, key)
+    }
+}
+
+/// Splits `scheme://bucket/prefix` into the bucket (or container) and prefix
+pub fn split_url<'a>(url: &'a str, scheme: &str) -> Result<(&'a str, String), String> {
+    let location = url
+        .strip_prefix(scheme)
+        .and_then(|rest| rest.strip_prefix("://"))
+        .ok_or_else(|| format!("Not a {} URL: {}", scheme, url))?;
+    let (bucket, prefix) = location.split_once('/').unwrap_or((location, ""));
+    if bucket.is_empty() {
+        return Err(format!("Remote URL has no bucket or container: {}", url));
+    }
+    Ok((bucket, prefix.trim_matches('/').to_string()))
+}
+
+/// Streams a response body into a local file, reporting progress per chunk
+pub fn write_response(
+    response: ureq::Response,
+    dest: &Path,
+    progress: ProgressFn,
+) -> Result<(), StorageError> {
+    let mut reader = response.into_reader();
+    let mut file = File::create(dest)
+        .map_err(|e| StorageError::fatal(format!("Failed to create {}: {}", dest.display(), e)))?;
+    let mut buffer = vec![0u8; 256 * 1024];
+    loop {
+        let read = reader
+            .read(&mut buffer)
+            .map_err(|e| StorageError::retryable(format!("Failed to read object: {}", e)))?;
+        if read == 0 {
+            return Ok(());
+        }
+        file.write_all(&buffer[..read]).map_err(|e| {
+            StorageError::fatal(format!("Failed to write {}: {}", dest.display(), e))
+        })?;
+        progress(read as u64);
+    }
+}
+
+/// Request body that reports every chunk to `progress` as it is sent. The
+/// transfer's rate limiter sits behind `progress`, so uploads are throttled
+/// while a request is in flight rather than after it. Retried requests
+/// report their bytes again, as they are sent again.
+pub struct ProgressReader<'a, R> {
+    inner: R,
+    progress: ProgressFn<'a>,
+}
+
+impl<'a, R: Read> ProgressReader<'a, R> {
+    pub fn new(inner: R, progress: ProgressFn<'a>) -> Self {
+        Self { inner, progress }
+    }
+}
+
+impl<R: Read> Read for ProgressReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if read > 0 {
+            (self.progress)(read as u64);
+        }
+        Ok(read)
+    }
+}
+
+/// Reads the next chunk of up to `buffer.len()` bytes, returning how many were read
+pub fn read_chunk(file: &mut File, buffer: &mut [u8], source: &Path) -> Result<usize, String> {
+    let mut filled = 0;
+    while filled < buffer.len() {
+        let read = file
+            .read(&mut buffer[filled..])
+            .map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+        if read == 0 {
+            break;
+        }
+        filled += read;
+    }
+    Ok(filled)
+}
+
+/// Key of an object on a DVC 3 remote
+pub fn object_key(md5: &str) -> String {
+    let (prefix, rest) = md5.split_at(2.min(md5.len()));
+    format!("files/md5/{}/{}", prefix, rest)
+}
+
+/// Key of an object on a DVC 2 remote
+pub fn legacy_object_key(md5: &str) -> String {
+    let (prefix, rest) = md5.split_at(2.min(md5.len()));
+    format!("{}/{}", prefix, rest)
+}
+
+/// Key under which a repository in `format` stores an object, the DVC 3
+/// layout unless the repository is known to be DVC 2
+pub fn object_key_for(md5: &str, format: Option<DvcFormat>) -> String {
+    match format {
+        Some(DvcFormat::V2) => legacy_object_key(md5),
+        _ => object_key(md5),
+    }
+}
+
+/// Opens the backend matching the remote URL scheme
+pub fn open_remote(
+    remote: &RemoteConfig,
+    config: &TransferConfig,
+) -> Result<Box<dyn RemoteStorage>, String> {
+    let scheme = remote
+        .url
+        .split_once("://")
+        .map(|(scheme, _)| scheme)
+        .unwrap_or("");
+
+    match scheme {
+        "" | "file" => Ok(Box::new(local::LocalStorage::from_remote(remote)?)),
+        "s3" => Ok(Box::new(s3::S3Storage::from_remote(remote, config)?)),
+        "gs" => Ok(Box::new(gcs::GcsStorage::from_remote(remote, config)?)),
+        "azure" => Ok(Box::new(azure::AzureStorage::from_remote(remote, config)?)),
+        "ssh" => Ok(Box::new(sftp::SftpStorage::from_remote(remote, config)?)),
+        _ => Err(format!(
+            "DVC remote '{}' uses an unsupported storage type: {}",
+            remote.name, remote.url
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upload_bodies_report_progress_while_they_are_read() {
+        use std::sync::Mutex;
+
+        let reported = Mutex::new(Vec::new());
+        let progress = |bytes: u64| reported.lock().unwrap().push(bytes);
+        let body = [7u8; 10];
+        let mut reader = ProgressReader::new(&body[..], &progress);
+
+        let mut chunk = [0u8; 4];
+        while reader.read(&mut chunk).unwrap() > 0 {}
+        assert_eq!(*reported.lock().unwrap(), vec![4, 4, 2]);
+    }
+}
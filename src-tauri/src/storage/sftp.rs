@@ -0,0 +1,376 @@
+//! SSH/SFTP backend for DVC remotes on lab servers (`ssh://user@host:port/path`)
+
+use ssh2::{CheckResult, KnownHostFileKind, OpenFlags, OpenType, RenameFlags, Session, Sftp};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::net::TcpStream;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use super::{prefixed_key, ProgressFn, RemoteStorage, StorageError, TransferConfig};
+use crate::dvc_config::RemoteConfig;
+
+const BUFFER_SIZE: usize = 256 * 1024;
+
+/// An authenticated session with its SFTP channel
+struct Connection {
+    // Keeps the SSH session alive for as long as the channel is in use
+    _session: Session,
+    sftp: Sftp,
+}
+
+pub struct SftpStorage {
+    host: String,
+    port: u16,
+    user: String,
+    /// Remote directory the objects live under
+    root: String,
+    password: Option<String>,
+    keyfile: Option<PathBuf>,
+    /// Idle connections reused by the transfer workers
+    pool: Mutex<Vec<Connection>>,
+    config: TransferConfig,
+}
+
+fn ssh_error(action: &str, error: ssh2::Error) -> StorageError {
+    StorageError::retryable(format!("Failed to {}: {}", action, error))
+}
+
+fn io_error(action: &str, error: std::io::Error) -> StorageError {
+    StorageError::retryable(format!("Failed to {}: {}", action, error))
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// Temporary name an upload is written to before being moved into place, so a
+/// partially uploaded object is never mistaken for a complete one
+fn partial_path(path: &str) -> String {
+    format!("{}.partial", path)
+}
+
+impl SftpStorage {
+    pub fn from_remote(remote: &RemoteConfig, config: &TransferConfig) -> Result<Self, String> {
+        let location = remote
+            .url
+            .strip_prefix("ssh://")
+            .ok_or_else(|| format!("Not an SSH URL: {}", remote.url))?;
+        let (authority, path) = location.split_once('/').unwrap_or((location, ""));
+        let (url_user, host_port) = match authority.rsplit_once('@') {
+            Some((user, host)) => (Some(user), host),
+            None => (None, authority),
+        };
+        let (host, url_port) = match host_port.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                Some(
+                    port.parse::<u16>()
+                        .map_err(|_| format!("Invalid port in {}", remote.url))?,
+                ),
+            ),
+            None => (host_port, None),
+        };
+        if host.is_empty() {
+            return Err(format!("SSH URL has no host: {}", remote.url));
+        }
+
+        let port = match remote.option("port") {
+            Some(port) => port
+                .parse()
+                .map_err(|_| format!("Invalid port option: {}", port))?,
+            None => url_port.unwrap_or(22),
+        };
+        let user = remote
+            .option("user")
+            .or(url_user)
+            .map(|s| s.to_string())
+            .or_else(|| std::env::var("USER").ok())
+            .or_else(|| std::env::var("USERNAME").ok())
+            .ok_or("No SSH user configured")?;
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            user,
+            // An empty path is relative to the login directory
+            root: match path.trim_end_matches('/') {
+                "" => String::new(),
+                path => format!("/{}", path),
+            },
+            password: remote.option("password").map(|s| s.to_string()),
+            keyfile: remote.option("keyfile").map(PathBuf::from),
+            pool: Mutex::new(Vec::new()),
+            config: config.clone(),
+        })
+    }
+
+    fn verify_host_key(&self, session: &Session) -> Result<(), StorageError> {
+        let Some(known_hosts_file) = home_dir().map(|h| h.join(".ssh").join("known_hosts")) else {
+            return Ok(());
+        };
+        let Some((key, _)) = session.host_key() else {
+            return Err(StorageError::fatal("Server did not send a host key"));
+        };
+        let mut known_hosts = session
+            .known_hosts()
+            .map_err(|e| ssh_error("read known hosts", e))?;
+        if known_hosts
+            .read_file(&known_hosts_file, KnownHostFileKind::OpenSSH)
+            .is_err()
+        {
+            return Ok(());
+        }
+
+        match known_hosts.check_port(&self.host, self.port, key) {
+            CheckResult::Mismatch => Err(StorageError::fatal(format!(
+                "Host key for {} does not match known_hosts",
+                self.host
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    fn authenticate(&self, session: &Session) -> Result<(), StorageError> {
+        if let Some(password) = &self.password {
+            let _ = session.userauth_password(&self.user, password);
+        }
+        if !session.authenticated() {
+            if let Some(keyfile) = &self.keyfile {
+                let _ = session.userauth_pubkey_file(&self.user, None, keyfile, None);
+            }
+        }
+        if !session.authenticated() {
+            let _ = session.userauth_agent(&self.user);
+        }
+        if !session.authenticated() {
+            if let Some(ssh_dir) = home_dir().map(|h| h.join(".ssh")) {
+                for name in ["id_ed25519", "id_ecdsa", "id_rsa"] {
+                    let key = ssh_dir.join(name);
+                    if key.exists()
+                        && session
+                            .userauth_pubkey_file(&self.user, None, &key, None)
+                            .is_ok()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+
+        if session.authenticated() {
+            Ok(())
+        } else {
+            Err(StorageError::fatal(format!(
+                "SSH authentication failed for {}@{}",
+                self.user, self.host
+            )))
+        }
+    }
+
+    fn connect(&self) -> Result<Connection, StorageError> {
+        let tcp = TcpStream::connect((self.host.as_str(), self.port))
+            .map_err(|e| io_error(&format!("connect to {}", self.host), e))?;
+        let _ = tcp.set_read_timeout(Some(Duration::from_secs(300)));
+
+        let mut session = Session::new().map_err(|e| ssh_error("create SSH session", e))?;
+        session.set_tcp_stream(tcp);
+        session
+            .handshake()
+            .map_err(|e| ssh_error("complete SSH handshake", e))?;
+        session.set_keepalive(true, 30);
+
+        self.verify_host_key(&session)?;
+        self.authenticate(&session)?;
+
+        let sftp = session
+            .sftp()
+            .map_err(|e| ssh_error("open SFTP channel", e))?;
+        Ok(Connection {
+            _session: session,
+            sftp,
+        })
+    }
+
+    /// Runs an operation on a pooled connection. The connection only goes back
+    /// to the pool when the operation succeeded, so broken sessions are dropped.
+    fn with_connection<T>(
+        &self,
+        operation: impl FnOnce(&Sftp) -> Result<T, StorageError>,
+    ) -> Result<T, StorageError> {
+        let pooled = self.pool.lock().ok().and_then(|mut pool| pool.pop());
+        let connection = match pooled {
+            Some(connection) => connection,
+            None => self.connect()?,
+        };
+
+        let result = operation(&connection.sftp);
+        if result.is_ok() {
+            if let Ok(mut pool) = self.pool.lock() {
+                if pool.len() < self.config.concurrency {
+                    pool.push(connection);
+                }
+            }
+        }
+        result
+    }
+
+    fn remote_path(&self, key: &str) -> String {
+        prefixed_key(&self.root, key)
+    }
+
+    fn create_parent_dirs(sftp: &Sftp, path: &str) -> Result<(), StorageError> {
+        let Some((parent, _)) = path.rsplit_once('/') else {
+            return Ok(());
+        };
+        let mut current = String::new();
+        for component in parent.split('/').filter(|c| !c.is_empty()) {
+            if !current.is_empty() || path.starts_with('/') {
+                current.push('/');
+            }
+            current.push_str(component);
+            if sftp.stat(Path::new(&current)).is_err() {
+                sftp.mkdir(Path::new(&current), 0o755)
+                    .map_err(|e| ssh_error(&format!("create {}", current), e))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Uploads into a `.partial` file, continuing where a previous attempt stopped
+    fn upload_resumable(
+        &self,
+        sftp: &Sftp,
+        path: &str,
+        source: &Path,
+        progress: ProgressFn,
+    ) -> Result<(), StorageError> {
+        Self::create_parent_dirs(sftp, path)?;
+        let partial = partial_path(path);
+        let offset = sftp
+            .stat(Path::new(&partial))
+            .ok()
+            .and_then(|stat| stat.size)
+            .unwrap_or(0);
+
+        let mut local = File::open(source).map_err(|e| {
+            StorageError::fatal(format!("Failed to open {}: {}", source.display(), e))
+        })?;
+        let size = local
+            .metadata()
+            .map_err(|e| io_error("stat local file", e))?
+            .len();
+        // A partial file larger than the source belongs to a different upload
+        let offset = if offset > size { 0 } else { offset };
+        local
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| io_error("seek local file", e))?;
+
+        let flags = if offset > 0 {
+            OpenFlags::WRITE | OpenFlags::APPEND
+        } else {
+            OpenFlags::WRITE | OpenFlags::CREATE | OpenFlags::TRUNCATE
+        };
+        let mut remote = sftp
+            .open_mode(Path::new(&partial), flags, 0o644, OpenType::File)
+            .map_err(|e| ssh_error("open remote file", e))?;
+        if offset > 0 {
+            progress(offset);
+        }
+
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        loop {
+            let read = local
+                .read(&mut buffer)
+                .map_err(|e| io_error("read local file", e))?;
+            if read == 0 {
+                break;
+            }
+            remote
+                .write_all(&buffer[..read])
+                .map_err(|e| io_error("write remote file", e))?;
+            progress(read as u64);
+        }
+        drop(remote);
+
+        sftp.rename(
+            Path::new(&partial),
+            Path::new(path),
+            Some(RenameFlags::OVERWRITE | RenameFlags::ATOMIC | RenameFlags::NATIVE),
+        )
+        .map_err(|e| ssh_error("move uploaded file into place", e))
+    }
+
+    /// Downloads into `dest`, continuing from its current length on retries
+    fn download_resumable(
+        &self,
+        sftp: &Sftp,
+        path: &str,
+        dest: &Path,
+        progress: ProgressFn,
+    ) -> Result<(), StorageError> {
+        let mut local = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(dest)
+            .map_err(|e| {
+                StorageError::fatal(format!("Failed to open {}: {}", dest.display(), e))
+            })?;
+        let offset = local
+            .metadata()
+            .map_err(|e| io_error("stat local file", e))?
+            .len();
+
+        let mut remote = sftp
+            .open(Path::new(path))
+            .map_err(|e| ssh_error("open remote file", e))?;
+        remote
+            .seek(SeekFrom::Start(offset))
+            .map_err(|e| io_error("seek remote file", e))?;
+
+        let mut buffer = vec![0u8; BUFFER_SIZE];
+        loop {
+            let read = remote
+                .read(&mut buffer)
+                .map_err(|e| io_error("read remote file", e))?;
+            if read == 0 {
+                return Ok(());
+            }
+            local.write_all(&buffer[..read]).map_err(|e| {
+                StorageError::fatal(format!("Failed to write {}: {}", dest.display(), e))
+            })?;
+            progress(read as u64);
+        }
+    }
+}
+
+impl RemoteStorage for SftpStorage {
+    fn exists(&self, key: &str) -> Result<bool, String> {
+        let path = self.remote_path(key);
+        super::with_retry(self.config.max_retries, || {
+            self.with_connection(|sftp| Ok(sftp.stat(Path::new(&path)).is_ok()))
+        })
+    }
+
+    fn upload(&self, key: &str, source: &Path, progress: ProgressFn) -> Result<(), String> {
+        let path = self.remote_path(key);
+        super::with_retry(self.config.max_retries, || {
+            self.with_connection(|sftp| self.upload_resumable(sftp, &path, source, progress))
+        })
+    }
+
+    fn download(&self, key: &str, dest: &Path, progress: ProgressFn) -> Result<(), String> {
+        let path = self.remote_path(key);
+        // Start from scratch; only retries within this call resume
+        if dest.exists() {
+            fs::remove_file(dest)
+                .map_err(|e| format!("Failed to remove {}: {}", dest.display(), e))?;
+        }
+        super::with_retry(self.config.max_retries, || {
+            self.with_connection(|sftp| self.download_resumable(sftp, &path, dest, progress))
+        })
+    }
+}
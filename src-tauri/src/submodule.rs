@@ -0,0 +1,217 @@
+use git2::{FetchOptions, Repository, StatusOptions, SubmoduleIgnore, SubmoduleUpdateOptions};
+use serde::Serialize;
+use serde_json::json;
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::{audit, dvcfile, remote, repo_manager};
+
+#[derive(Debug, Serialize)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    /// Path relative to the superproject root
+    pub path: String,
+    pub url: Option<String>,
+    pub branch: Option<String>,
+    /// Commit the superproject records for the submodule
+    pub recorded_commit: Option<String>,
+    /// Commit checked out in the submodule, `None` when not initialized
+    pub checked_out_commit: Option<String>,
+    /// "uninitialized", "new_commits", "dirty" or "clean"
+    pub status: String,
+    /// Modified and untracked files inside the submodule
+    pub changed_files: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SubmoduleUpdateSummary {
+    pub operation_id: String,
+    /// Paths of the updated submodules, nested ones included
+    pub updated: Vec<String>,
+    /// Uninitialized submodules left alone because `init` was not set
+    pub skipped: Vec<String>,
+}
+
+fn changed_files(repo: &Repository) -> usize {
+    let mut options = StatusOptions::new();
+    options.include_untracked(true).exclude_submodules(true);
+    repo.statuses(Some(&mut options))
+        .map(|statuses| statuses.len())
+        .unwrap_or(0)
+}
+
+/// Submodules of a repository with a summary of their state
+pub(crate) fn list(repo: &Repository) -> Result<Vec<SubmoduleInfo>, String> {
+    let submodules = repo
+        .submodules()
+        .map_err(|e| format!("Failed to list submodules: {}", e))?;
+
+    let mut infos = Vec::new();
+    for submodule in submodules {
+        let name = submodule.name().unwrap_or_default().to_string();
+        let state = repo
+            .submodule_status(&name, SubmoduleIgnore::None)
+            .map_err(|e| format!("Failed to get status of submodule {}: {}", name, e))?;
+        let changed = submodule.open().map(|sub| changed_files(&sub)).unwrap_or(0);
+
+        let status = if state.is_wd_uninitialized() {
+            "uninitialized"
+        } else if state.is_wd_modified() {
+            "new_commits"
+        } else if changed > 0 {
+            "dirty"
+        } else {
+            "clean"
+        };
+
+        infos.push(SubmoduleInfo {
+            path: dvcfile::to_git_path(submodule.path()),
+            url: submodule.url().map(str::to_string),
+            branch: submodule.branch().map(str::to_string),
+            recorded_commit: submodule.head_id().map(|id| id.to_string()),
+            checked_out_commit: submodule.workdir_id().map(|id| id.to_string()),
+            status: status.to_string(),
+            changed_files: changed,
+            name,
+        });
+    }
+    infos.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(infos)
+}
+
+/// Paths of the submodules of a repository, relative to its root
+pub(crate) fn paths(repo: &Repository) -> HashSet<String> {
+    repo.submodules()
+        .map(|submodules| {
+            submodules
+                .iter()
+                .map(|submodule| dvcfile::to_git_path(submodule.path()))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Whether a directory is the root of another repository, e.g. a checked out
+/// submodule, whose files belong to that repository
+pub(crate) fn is_repo_root(dir: &Path) -> bool {
+    dir.join(".git").exists()
+}
+
+fn update_all(
+    app_handle: &AppHandle,
+    repo: &Repository,
+    prefix: &str,
+    operation_id: &str,
+    init: bool,
+    recursive: bool,
+    summary: &mut SubmoduleUpdateSummary,
+) -> Result<(), String> {
+    let mut submodules = repo
+        .submodules()
+        .map_err(|e| format!("Failed to list submodules: {}", e))?;
+
+    for submodule in &mut submodules {
+        let path = format!("{}{}", prefix, dvcfile::to_git_path(submodule.path()));
+        let name = submodule.name().unwrap_or_default().to_string();
+        let initialized = !repo
+            .submodule_status(&name, SubmoduleIgnore::None)
+            .map_err(|e| format!("Failed to get status of submodule {}: {}", path, e))?
+            .is_wd_uninitialized();
+        if !initialized && !init {
+            summary.skipped.push(path);
+            continue;
+        }
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.remote_callbacks(remote::progress_callbacks(
+            app_handle,
+            operation_id,
+            "fetch",
+        ));
+        let mut options = SubmoduleUpdateOptions::new();
+        options.fetch(fetch_options);
+        submodule
+            .update(init, Some(&mut options))
+            .map_err(|e| format!("Failed to update submodule {}: {}", path, e))?;
+        summary.updated.push(path.clone());
+
+        if recursive {
+            let sub_repo = submodule
+                .open()
+                .map_err(|e| format!("Failed to open submodule {}: {}", path, e))?;
+            update_all(
+                app_handle,
+                &sub_repo,
+                &format!("{}/", path),
+                operation_id,
+                init,
+                recursive,
+                summary,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn update(
+    app_handle: &AppHandle,
+    repo_path: &str,
+    init: bool,
+    recursive: bool,
+    operation_id: Option<String>,
+) -> Result<SubmoduleUpdateSummary, String> {
+    let repo =
+        Repository::open(repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut summary = SubmoduleUpdateSummary {
+        operation_id: remote::operation_id(operation_id),
+        updated: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let operation_id = summary.operation_id.clone();
+    update_all(
+        app_handle,
+        &repo,
+        "",
+        &operation_id,
+        init,
+        recursive,
+        &mut summary,
+    )?;
+    Ok(summary)
+}
+
+/// Submodules of the repository with their own status summary
+#[command]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn git_list_submodules(repo_path: String) -> Result<Vec<SubmoduleInfo>, String> {
+    let repo =
+        Repository::open(&repo_path).map_err(|e| format!("Failed to open repository: {}", e))?;
+    list(&repo)
+}
+
+/// Checks out the commits the superproject records for its submodules, like
+/// `git submodule update`. `init` also clones uninitialized submodules and
+/// `recursive` descends into nested ones.
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn git_submodule_update(
+    app_handle: AppHandle,
+    repo_path: String,
+    init: bool,
+    recursive: bool,
+    operation_id: Option<String>,
+) -> Result<SubmoduleUpdateSummary, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_submodule_update",
+        json!({ "init": init, "recursive": recursive }),
+        || {
+            repo_manager::write(&app_handle, &repo_path, || {
+                update(&app_handle, &repo_path, init, recursive, operation_id)
+            })
+        },
+    )
+}
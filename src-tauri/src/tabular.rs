@@ -0,0 +1,647 @@
+use git2::Repository;
+use parquet::file::reader::{FileReader, SerializedFileReader};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::Path;
+use tauri::command;
+use tracing::instrument;
+
+use crate::history::{self, VersionContent};
+use crate::{dvcfile, file_type};
+
+/// Rows of a CSV file read to infer its schema; larger files are sampled
+const SAMPLE_ROWS: u64 = 10_000;
+
+/// Rows of each version compared to find added and removed rows
+const COMPARE_ROWS: usize = 200_000;
+
+/// Added and removed rows returned as examples
+const ROW_SAMPLE: usize = 20;
+
+/// Cell values counted as missing in CSV files
+const NULL_VALUES: &[&str] = &["", "NA", "N/A", "NaN", "nan", "null", "NULL", "None"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum TabularFormat {
+    Csv(u8),
+    Parquet,
+}
+
+impl TabularFormat {
+    /// Format of a data file from its name; the CSV delimiter is guessed
+    /// from the content later unless the extension gives it away
+    pub(crate) fn from_path(path: &Path) -> Option<Self> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "csv" => Some(Self::Csv(0)),
+            "tsv" | "tab" => Some(Self::Csv(b'\t')),
+            "parquet" | "pq" => Some(Self::Parquet),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Csv(b'\t') => "tsv",
+            Self::Csv(_) => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ColumnSchema {
+    pub name: String,
+    /// Inferred from a sample for CSV: "boolean", "integer", "float", "date",
+    /// "datetime", "string" or "empty"; the physical or logical type for
+    /// Parquet
+    pub data_type: String,
+    /// Missing values in the sampled rows (all rows for Parquet); `None` when
+    /// a Parquet file has no statistics for the column
+    pub null_count: Option<u64>,
+    pub null_fraction: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TabularSchema {
+    /// "csv", "tsv" or "parquet"
+    pub format: String,
+    pub columns: Vec<ColumnSchema>,
+    pub row_count: u64,
+    /// False when the row count is extrapolated from a sample
+    pub row_count_exact: bool,
+    /// Rows the column statistics are based on
+    pub sampled_rows: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ColumnTypeChange {
+    pub name: String,
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TabularDiff {
+    pub path: String,
+    pub rev_a: String,
+    pub rev_b: String,
+    pub schema_a: TabularSchema,
+    pub schema_b: TabularSchema,
+    pub row_count_delta: i64,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+    pub type_changes: Vec<ColumnTypeChange>,
+    /// Columns in both versions, which rows are compared on
+    pub compared_columns: Vec<String>,
+    pub added_rows: u64,
+    pub removed_rows: u64,
+    /// First added and removed rows, with the values of `compared_columns`
+    pub added_sample: Vec<Vec<String>>,
+    pub removed_sample: Vec<Vec<String>>,
+    /// False when a version had more rows than were compared, so the row
+    /// changes only cover the start of the file
+    pub complete: bool,
+}
+
+/// Rows read from a tabular file, as text
+struct Rows {
+    columns: Vec<String>,
+    rows: Vec<Vec<String>>,
+    complete: bool,
+}
+
+/// Narrowest type that fits every value seen in a CSV column so far
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ColumnKind {
+    Empty,
+    Boolean,
+    Integer,
+    Float,
+    Date,
+    DateTime,
+    String,
+}
+
+impl ColumnKind {
+    fn of(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            Self::Boolean
+        } else if value.parse::<i64>().is_ok() {
+            Self::Integer
+        } else if value.parse::<f64>().is_ok() {
+            Self::Float
+        } else if chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").is_ok() {
+            Self::Date
+        } else if chrono::DateTime::parse_from_rfc3339(value).is_ok()
+            || chrono::NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S").is_ok()
+        {
+            Self::DateTime
+        } else {
+            Self::String
+        }
+    }
+
+    fn merge(self, other: Self) -> Self {
+        use ColumnKind::*;
+        match (self, other) {
+            (Empty, kind) | (kind, Empty) => kind,
+            (a, b) if a == b => a,
+            (Integer, Float) | (Float, Integer) => Float,
+            (Date, DateTime) | (DateTime, Date) => DateTime,
+            _ => String,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Empty => "empty",
+            Self::Boolean => "boolean",
+            Self::Integer => "integer",
+            Self::Float => "float",
+            Self::Date => "date",
+            Self::DateTime => "datetime",
+            Self::String => "string",
+        }
+    }
+}
+
+/// Delimiter used most in the first line
+fn sniff_delimiter(path: &Path) -> u8 {
+    let head = file_type::read_head(path).unwrap_or_default();
+    let first_line = head.split(|&b| b == b'\n').next().unwrap_or_default();
+    [b',', b';', b'\t', b'|']
+        .into_iter()
+        .max_by_key(|&delimiter| first_line.iter().filter(|&&b| b == delimiter).count())
+        .unwrap_or(b',')
+}
+
+fn fraction(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        0.0
+    } else {
+        count as f64 / total as f64
+    }
+}
+
+fn inspect_csv(path: &Path, delimiter: u8) -> Result<TabularSchema, String> {
+    let delimiter = match delimiter {
+        0 => sniff_delimiter(path),
+        delimiter => delimiter,
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open CSV file: {}", e))?;
+    let headers: Vec<String> = reader
+        .headers()
+        .map_err(|e| format!("Failed to read CSV header: {}", e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    let mut kinds = vec![ColumnKind::Empty; headers.len()];
+    let mut nulls = vec![0u64; headers.len()];
+    let mut rows = 0u64;
+    let mut record = csv::StringRecord::new();
+    let mut complete = true;
+    while reader
+        .read_record(&mut record)
+        .map_err(|e| format!("Failed to read CSV row {}: {}", rows + 1, e))?
+    {
+        rows += 1;
+        for (column, kind) in kinds.iter_mut().enumerate() {
+            let value = record.get(column).unwrap_or("").trim();
+            if NULL_VALUES.contains(&value) {
+                nulls[column] += 1;
+            } else {
+                *kind = kind.merge(ColumnKind::of(value));
+            }
+        }
+        if rows >= SAMPLE_ROWS {
+            complete = !reader.read_record(&mut record).unwrap_or(true);
+            break;
+        }
+    }
+
+    // Extrapolated from the bytes the sample took up
+    let row_count = if complete || rows == 0 {
+        rows
+    } else {
+        let total = fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+        let read = reader.position().byte().max(1);
+        (rows as f64 * total as f64 / read as f64).round() as u64
+    };
+
+    let columns = headers
+        .into_iter()
+        .zip(kinds)
+        .zip(nulls)
+        .map(|((name, kind), nulls)| ColumnSchema {
+            name,
+            data_type: kind.name().to_string(),
+            null_count: Some(nulls),
+            null_fraction: Some(fraction(nulls, rows)),
+        })
+        .collect();
+    Ok(TabularSchema {
+        format: TabularFormat::Csv(delimiter).name().to_string(),
+        columns,
+        row_count,
+        row_count_exact: complete,
+        sampled_rows: rows,
+    })
+}
+
+/// Reads only the footer, which has the schema, row count and per row group
+/// null counts
+fn inspect_parquet(path: &Path) -> Result<TabularSchema, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open Parquet file: {}", e))?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| format!("Failed to read Parquet metadata: {}", e))?;
+    let metadata = reader.metadata();
+    let row_count = metadata.file_metadata().num_rows().max(0) as u64;
+
+    let columns = metadata
+        .file_metadata()
+        .schema_descr()
+        .columns()
+        .iter()
+        .enumerate()
+        .map(|(index, column)| {
+            let null_count = metadata
+                .row_groups()
+                .iter()
+                .map(|group| {
+                    group
+                        .column(index)
+                        .statistics()
+                        .and_then(|stats| stats.null_count_opt())
+                })
+                .sum::<Option<u64>>();
+            // Logical types like `Timestamp { .. }` are named without their
+            // parameters
+            let data_type = match column.logical_type() {
+                Some(logical) => format!("{:?}", logical)
+                    .split([' ', '('])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string(),
+                None => column.physical_type().to_string(),
+            };
+            ColumnSchema {
+                name: column.path().string(),
+                data_type: data_type.to_lowercase(),
+                null_count,
+                null_fraction: null_count.map(|nulls| fraction(nulls, row_count)),
+            }
+        })
+        .collect();
+    Ok(TabularSchema {
+        format: TabularFormat::Parquet.name().to_string(),
+        columns,
+        row_count,
+        row_count_exact: true,
+        sampled_rows: row_count,
+    })
+}
+
+fn csv_rows(path: &Path, delimiter: u8, limit: usize) -> Result<Rows, String> {
+    let delimiter = match delimiter {
+        0 => sniff_delimiter(path),
+        delimiter => delimiter,
+    };
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .flexible(true)
+        .from_path(path)
+        .map_err(|e| format!("Failed to open CSV file: {}", e))?;
+    let columns = reader
+        .headers()
+        .map_err(|e| format!("Failed to read CSV header: {}", e))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    let mut records = reader.records();
+    let rows = records
+        .by_ref()
+        .take(limit)
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(str::to_string).collect())
+                .map_err(|e| format!("Failed to read CSV row: {}", e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Rows {
+        columns,
+        rows,
+        complete: records.next().is_none(),
+    })
+}
+
+fn parquet_rows(path: &Path, limit: usize) -> Result<Rows, String> {
+    let file = File::open(path).map_err(|e| format!("Failed to open Parquet file: {}", e))?;
+    let reader = SerializedFileReader::new(file)
+        .map_err(|e| format!("Failed to read Parquet metadata: {}", e))?;
+    let file_metadata = reader.metadata().file_metadata();
+    let columns = file_metadata
+        .schema()
+        .get_fields()
+        .iter()
+        .map(|field| field.name().to_string())
+        .collect();
+    let complete = file_metadata.num_rows() as usize <= limit;
+    let rows = reader
+        .get_row_iter(None)
+        .map_err(|e| format!("Failed to read Parquet rows: {}", e))?
+        .take(limit)
+        .map(|row| {
+            row.map(|row| {
+                row.get_column_iter()
+                    .map(|(_, field)| field.to_string())
+                    .collect()
+            })
+            .map_err(|e| format!("Failed to read Parquet row: {}", e))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Rows {
+        columns,
+        rows,
+        complete,
+    })
+}
+
+fn read_rows(path: &Path, format: TabularFormat, limit: usize) -> Result<Rows, String> {
+    match format {
+        TabularFormat::Csv(delimiter) => csv_rows(path, delimiter, limit),
+        TabularFormat::Parquet => parquet_rows(path, limit),
+    }
+}
+
+/// Values of `columns` in each row
+fn project(rows: &Rows, columns: &[String]) -> Vec<Vec<String>> {
+    let indexes: Vec<usize> = columns
+        .iter()
+        .filter_map(|column| rows.columns.iter().position(|name| name == column))
+        .collect();
+    rows.rows
+        .iter()
+        .map(|row| {
+            indexes
+                .iter()
+                .map(|&index| row.get(index).cloned().unwrap_or_default())
+                .collect()
+        })
+        .collect()
+}
+
+/// Rows of `rows` without a matching row in `other`, counting duplicates, with
+/// the first of them as a sample
+fn unmatched(rows: &[Vec<String>], other: &[Vec<String>]) -> (u64, Vec<Vec<String>>) {
+    let mut remaining: HashMap<&Vec<String>, usize> = HashMap::new();
+    for row in other {
+        *remaining.entry(row).or_default() += 1;
+    }
+    let mut count = 0;
+    let mut sample = Vec::new();
+    for row in rows {
+        match remaining.get_mut(row) {
+            Some(left) if *left > 0 => *left -= 1,
+            _ => {
+                count += 1;
+                if sample.len() < ROW_SAMPLE {
+                    sample.push(row.clone());
+                }
+            }
+        }
+    }
+    (count, sample)
+}
+
+/// Runs `run` on a file holding the content; blobs from git are written to a
+/// temporary file with the data file's extension, so its format is known
+fn with_version_file<T>(
+    content: VersionContent,
+    extension: &str,
+    run: impl FnOnce(&Path) -> Result<T, String>,
+) -> Result<T, String> {
+    match content {
+        VersionContent::Cache(path) => run(&path),
+        VersionContent::Blob(bytes) => {
+            let path = std::env::temp_dir().join(format!(
+                "fenn-tabular-{}.{}",
+                uuid::Uuid::new_v4().simple(),
+                extension
+            ));
+            fs::write(&path, bytes)
+                .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+            let result = run(&path);
+            let _ = fs::remove_file(&path);
+            result
+        }
+    }
+}
+
+/// Schema of a tabular file; `format` is given for files whose name doesn't
+/// tell, like DVC cache objects
+pub(crate) fn inspect(path: &Path, format: TabularFormat) -> Result<TabularSchema, String> {
+    match format {
+        TabularFormat::Csv(delimiter) => inspect_csv(path, delimiter),
+        TabularFormat::Parquet => inspect_parquet(path),
+    }
+}
+
+/// Column names and types, row count and missing values of a CSV, TSV or
+/// Parquet file. Large CSV files are sampled.
+#[command(async)]
+#[instrument(err(Debug))]
+pub fn inspect_tabular_schema(path: String) -> Result<TabularSchema, String> {
+    let path = Path::new(&path);
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    let format = TabularFormat::from_path(path)
+        .ok_or_else(|| format!("{} is not a CSV or Parquet file", path.display()))?;
+    inspect(path, format)
+}
+
+/// Compares two versions of a CSV or Parquet file: row counts, columns added,
+/// removed or changing type, and the rows added and removed. DVC-tracked
+/// versions are read from the local cache.
+#[command(async)]
+#[instrument(skip(repo_path), err(Debug))]
+pub fn diff_tabular_versions(
+    repo_path: String,
+    path: String,
+    rev_a: String,
+    rev_b: String,
+) -> Result<TabularDiff, String> {
+    let repo = Repository::discover(&repo_path)
+        .map_err(|e| format!("Failed to open repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?;
+    let relative = dvcfile::repo_relative_path(repo_root, Path::new(&path))?;
+    let format = TabularFormat::from_path(&relative)
+        .ok_or_else(|| format!("{} is not a CSV or Parquet file", path))?;
+    let extension = relative
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default();
+
+    let read = |rev: &str| {
+        with_version_file(
+            history::content_at(&repo, &relative, rev)?,
+            extension,
+            |file| {
+                Ok((
+                    inspect(file, format)?,
+                    read_rows(file, format, COMPARE_ROWS)?,
+                ))
+            },
+        )
+    };
+    let (schema_a, rows_a) = read(&rev_a)?;
+    let (schema_b, rows_b) = read(&rev_b)?;
+
+    let type_of = |schema: &TabularSchema, name: &str| {
+        schema
+            .columns
+            .iter()
+            .find(|column| column.name == name)
+            .map(|column| column.data_type.clone())
+    };
+    let compared_columns: Vec<String> = rows_b
+        .columns
+        .iter()
+        .filter(|column| rows_a.columns.contains(column))
+        .cloned()
+        .collect();
+    let added_columns = rows_b
+        .columns
+        .iter()
+        .filter(|column| !rows_a.columns.contains(column))
+        .cloned()
+        .collect();
+    let removed_columns = rows_a
+        .columns
+        .iter()
+        .filter(|column| !rows_b.columns.contains(column))
+        .cloned()
+        .collect();
+    let type_changes = compared_columns
+        .iter()
+        .filter_map(|name| {
+            let from = type_of(&schema_a, name)?;
+            let to = type_of(&schema_b, name)?;
+            (from != to).then(|| ColumnTypeChange {
+                name: name.clone(),
+                from,
+                to,
+            })
+        })
+        .collect();
+
+    let projected_a = project(&rows_a, &compared_columns);
+    let projected_b = project(&rows_b, &compared_columns);
+    let (added_rows, added_sample) = unmatched(&projected_b, &projected_a);
+    let (removed_rows, removed_sample) = unmatched(&projected_a, &projected_b);
+
+    Ok(TabularDiff {
+        path: dvcfile::to_git_path(&relative),
+        rev_a,
+        rev_b,
+        row_count_delta: schema_b.row_count as i64 - schema_a.row_count as i64,
+        schema_a,
+        schema_b,
+        added_columns,
+        removed_columns,
+        type_changes,
+        compared_columns,
+        added_rows,
+        removed_rows,
+        added_sample,
+        removed_sample,
+        complete: rows_a.complete && rows_b.complete,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FixtureRepo;
+
+    #[test]
+    fn infers_the_narrowest_column_kind() {
+        use ColumnKind::*;
+        let cases: &[(&[&str], ColumnKind)] = &[
+            (&["TRUE", "false"], Boolean),
+            (&["1", "-2"], Integer),
+            (&["1", "2.5"], Float),
+            (&["2024-01-02"], Date),
+            (&["2024-01-02", "2024-01-02 03:04:05"], DateTime),
+            (&["2024-01-02T03:04:05Z"], DateTime),
+            (&["true", "1"], String),
+            (&["1", "2024-01-02"], String),
+            (&["abc"], String),
+        ];
+        for (values, expected) in cases {
+            let kind = values
+                .iter()
+                .fold(Empty, |kind, value| kind.merge(ColumnKind::of(value)));
+            assert_eq!(kind, *expected, "{:?}", values);
+        }
+    }
+
+    #[test]
+    fn unmatched_rows_count_duplicates() {
+        let row = |value: &str| vec![value.to_string()];
+        let cases = [
+            (vec![row("a"), row("b")], vec![row("a"), row("b")], 0),
+            (vec![row("a"), row("a")], vec![row("a")], 1),
+            (vec![row("a")], vec![row("a"), row("a")], 0),
+            (vec![row("a"), row("b"), row("c")], vec![row("b")], 2),
+        ];
+        for (rows, other, expected) in cases {
+            let (count, sample) = unmatched(&rows, &other);
+            assert_eq!(count, expected, "{:?} against {:?}", rows, other);
+            assert_eq!(sample.len() as u64, expected);
+        }
+    }
+
+    #[test]
+    fn diffs_columns_types_and_rows_between_revisions() {
+        let fixture = FixtureRepo::new();
+        fixture.write_file("data.csv", "id,score,name\n1,0.5,a\n2,0.7,b\n");
+        fixture.commit_all("Add data");
+        fixture.write_file("data.csv", "id,score,label\n1,0.5,x\n2,high,y\n4,0.9,z\n");
+        fixture.commit_all("Update data");
+
+        let diff = diff_tabular_versions(
+            fixture.path_string(),
+            fixture
+                .path()
+                .join("data.csv")
+                .to_string_lossy()
+                .into_owned(),
+            "HEAD~1".to_string(),
+            "HEAD".to_string(),
+        )
+        .unwrap();
+
+        assert_eq!(diff.row_count_delta, 1);
+        assert_eq!(diff.added_columns, vec!["label"]);
+        assert_eq!(diff.removed_columns, vec!["name"]);
+        assert_eq!(diff.type_changes.len(), 1);
+        assert_eq!(diff.type_changes[0].name, "score");
+        assert_eq!(diff.type_changes[0].from, "float");
+        assert_eq!(diff.type_changes[0].to, "string");
+        assert_eq!(diff.compared_columns, vec!["id", "score"]);
+        assert_eq!(diff.added_rows, 2);
+        assert_eq!(diff.removed_rows, 1);
+        assert_eq!(diff.removed_sample, vec![vec!["2", "0.7"]]);
+        assert!(diff.complete);
+    }
+}
@@ -0,0 +1,465 @@
+use git2::Repository;
+use glob::{MatchOptions, Pattern};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager};
+use tracing::instrument;
+
+use crate::{dvcfile, repo_manager, transfer};
+
+/// Directory in the app data dir holding saved templates, one folder each
+const TEMPLATES_DIR: &str = "project-templates";
+const TEMPLATE_FILE: &str = "template.json";
+const FILES_DIR: &str = "files";
+
+/// Layout a new project starts with
+#[derive(Debug, Clone, Serialize)]
+pub struct ProjectTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    /// Files the template creates, relative to the project root;
+    /// directories end with `/`
+    pub files: Vec<String>,
+    /// Whether the user saved it from a project
+    pub custom: bool,
+}
+
+struct Template {
+    id: &'static str,
+    name: &'static str,
+    description: &'static str,
+    files: &'static [(&'static str, &'static str)],
+}
+
+/// Template saved from a project. The contents of `files` are kept next to
+/// it; `directories` are created empty.
+#[derive(Debug, Serialize, Deserialize)]
+struct CustomTemplate {
+    id: String,
+    name: String,
+    description: String,
+    /// Project the template was saved from
+    source: String,
+    /// RFC 3339 time it was saved
+    created_at: String,
+    files: Vec<String>,
+    directories: Vec<String>,
+}
+
+impl From<CustomTemplate> for ProjectTemplate {
+    fn from(template: CustomTemplate) -> Self {
+        let mut files = template.files;
+        files.extend(template.directories.iter().map(|dir| format!("{}/", dir)));
+        ProjectTemplate {
+            id: template.id,
+            name: template.name,
+            description: template.description,
+            files,
+            custom: true,
+        }
+    }
+}
+
+const DATA_SCIENCE_GITIGNORE: &str = "\
+__pycache__/
+*.py[cod]
+.ipynb_checkpoints/
+.venv/
+venv/
+.env
+.DS_Store
+";
+
+const DATA_SCIENCE_PARAMS: &str = "\
+prepare:
+  split: 0.2
+  seed: 42
+train:
+  seed: 42
+";
+
+const DATA_SCIENCE_PIPELINE: &str = "\
+# Pipeline stages, run with `dvc repro`. For example:
+#
+# stages:
+#   prepare:
+#     cmd: python src/prepare.py
+#     deps:
+#       - src/prepare.py
+#       - data/raw
+#     params:
+#       - prepare
+#     outs:
+#       - data/prepared
+#   train:
+#     cmd: python src/train.py
+#     deps:
+#       - src/train.py
+#       - data/prepared
+#     params:
+#       - train
+#     outs:
+#       - models/model.pkl
+stages: {}
+";
+
+const TEMPLATES: &[Template] = &[
+    Template {
+        id: "empty",
+        name: "Empty",
+        description: "Git and DVC only",
+        files: &[],
+    },
+    Template {
+        id: "data-science",
+        name: "Data science",
+        description: "Folders for raw and processed data and models, with params.yaml and a dvc.yaml to fill in",
+        files: &[
+            ("data/raw/.gitkeep", ""),
+            ("data/processed/.gitkeep", ""),
+            ("models/.gitkeep", ""),
+            ("src/.gitkeep", ""),
+            (".gitignore", DATA_SCIENCE_GITIGNORE),
+            ("params.yaml", DATA_SCIENCE_PARAMS),
+            ("dvc.yaml", DATA_SCIENCE_PIPELINE),
+        ],
+    },
+];
+
+fn templates_dir(app_handle: &AppHandle) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(TEMPLATES_DIR))
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Id of a saved template: its name in lowercase with anything but letters
+/// and digits turned into dashes
+fn template_id(name: &str) -> Result<String, String> {
+    let id = name
+        .to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if id.is_empty() {
+        return Err(format!(
+            "Invalid template name '{}': use letters or digits",
+            name
+        ));
+    }
+    if TEMPLATES.iter().any(|template| template.id == id) {
+        return Err(format!("{} is the name of a built-in template", name));
+    }
+    Ok(id)
+}
+
+fn read_custom(app_handle: &AppHandle, id: &str) -> Result<Option<CustomTemplate>, String> {
+    // Ids never contain separators, so they can't point outside the directory
+    if id.is_empty() || !id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Ok(None);
+    }
+    let path = templates_dir(app_handle)?.join(id).join(TEMPLATE_FILE);
+    if !path.exists() {
+        return Ok(None);
+    }
+    let content =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read template {}: {}", id, e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse template {}: {}", id, e))
+}
+
+fn list_custom(app_handle: &AppHandle) -> Result<Vec<CustomTemplate>, String> {
+    let Ok(entries) = fs::read_dir(templates_dir(app_handle)?) else {
+        return Ok(Vec::new());
+    };
+    let mut templates = Vec::new();
+    for entry in entries.flatten() {
+        let id = entry.file_name().to_string_lossy().to_string();
+        match read_custom(app_handle, &id) {
+            Ok(Some(template)) => templates.push(template),
+            Ok(None) => {}
+            Err(e) => tracing::warn!("Skipping project template: {}", e),
+        }
+    }
+    templates.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(templates)
+}
+
+/// Fails for a template that doesn't exist, before anything is created
+pub(crate) fn validate(app_handle: &AppHandle, id: &str) -> Result<(), String> {
+    if TEMPLATES.iter().any(|template| template.id == id) || read_custom(app_handle, id)?.is_some()
+    {
+        Ok(())
+    } else {
+        Err(format!("Unknown project template: {}", id))
+    }
+}
+
+/// Lines of `addition` missing from `existing`, appended to it
+fn merge_lines(existing: &str, addition: &str) -> String {
+    let mut merged = existing.to_string();
+    for line in addition.lines() {
+        if !existing
+            .lines()
+            .any(|present| present.trim() == line.trim())
+        {
+            if !merged.is_empty() && !merged.ends_with('\n') {
+                merged.push('\n');
+            }
+            merged.push_str(line);
+            merged.push('\n');
+        }
+    }
+    merged
+}
+
+/// Writes a template file unless the project has it already; a `.gitignore`
+/// gets the entries it lacks instead. Returns whether it was written.
+fn write_file(root: &Path, relative: &str, content: &str) -> Result<bool, String> {
+    let path = root.join(relative);
+    let content = match fs::read_to_string(&path) {
+        Ok(existing) if relative.rsplit('/').next() == Some(".gitignore") => {
+            merge_lines(&existing, content)
+        }
+        Ok(_) => return Ok(false),
+        Err(_) => content.to_string(),
+    };
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    fs::write(&path, content).map_err(|e| format!("Failed to write {}: {}", relative, e))?;
+    Ok(true)
+}
+
+/// Creates the files of a built-in or saved template in a project. Existing
+/// files are kept, except `.gitignore`, which gets the template's entries it
+/// lacks. Returns the files written.
+pub(crate) fn apply(app_handle: &AppHandle, root: &Path, id: &str) -> Result<Vec<String>, String> {
+    let mut written = Vec::new();
+    if let Some(template) = TEMPLATES.iter().find(|template| template.id == id) {
+        for (relative, content) in template.files {
+            if write_file(root, relative, content)? {
+                written.push(relative.to_string());
+            }
+        }
+        return Ok(written);
+    }
+
+    let template =
+        read_custom(app_handle, id)?.ok_or_else(|| format!("Unknown project template: {}", id))?;
+    let files_dir = templates_dir(app_handle)?.join(id).join(FILES_DIR);
+    for relative in &template.files {
+        let content = fs::read_to_string(files_dir.join(relative))
+            .map_err(|e| format!("Failed to read {} of template {}: {}", relative, id, e))?;
+        if write_file(root, relative, &content)? {
+            written.push(relative.clone());
+        }
+    }
+    // Git doesn't keep empty directories, so they get a placeholder
+    for relative in &template.directories {
+        let dir = root.join(relative);
+        fs::create_dir_all(&dir).map_err(|e| format!("Failed to create {}: {}", relative, e))?;
+        let empty = fs::read_dir(&dir)
+            .map(|mut entries| entries.next().is_none())
+            .unwrap_or(false);
+        let placeholder = format!("{}/.gitkeep", relative);
+        if empty && write_file(root, &placeholder, "")? {
+            written.push(placeholder);
+        }
+    }
+    Ok(written)
+}
+
+fn compile(patterns: &[String]) -> Result<Vec<Pattern>, String> {
+    patterns
+        .iter()
+        .map(|pattern| {
+            Pattern::new(pattern.trim_end_matches('/'))
+                .map_err(|e| format!("Invalid pattern {}: {}", pattern, e))
+        })
+        .collect()
+}
+
+/// Whether a path matches by its name or its path from the project root,
+/// like the file tree's ignore patterns
+fn matches(patterns: &[Pattern], relative: &str) -> bool {
+    let name = relative.rsplit('/').next().unwrap_or(relative);
+    let path_options = MatchOptions {
+        require_literal_separator: true,
+        ..MatchOptions::new()
+    };
+    patterns
+        .iter()
+        .any(|pattern| pattern.matches(name) || pattern.matches_with(relative, path_options))
+}
+
+/// Directories above a path: `a/b/c` gives `a` and `a/b`
+fn parents(relative: &str) -> impl Iterator<Item = &str> {
+    relative
+        .match_indices('/')
+        .map(move |(i, _)| &relative[..i])
+}
+
+/// What a project contributes to a template: the git-tracked files matching
+/// `include` with their contents, and the directories of every other tracked
+/// file and DVC-tracked directory. Paths matching `exclude`, or inside a
+/// directory that does, are left out, as are pointers and `.dvc/`.
+fn project_layout(
+    repo_root: &Path,
+    include: &[Pattern],
+    exclude: &[Pattern],
+) -> Result<(Vec<String>, Vec<String>), String> {
+    let repo =
+        Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let index = repo
+        .index()
+        .map_err(|e| format!("Failed to get index: {}", e))?;
+    let excluded = |relative: &str| {
+        parents(relative)
+            .chain(std::iter::once(relative))
+            .any(|path| matches(exclude, path))
+    };
+
+    let mut files = Vec::new();
+    let mut directories = BTreeSet::new();
+    for entry in index.iter() {
+        let relative = String::from_utf8_lossy(&entry.path).to_string();
+        if relative.starts_with(".dvc/") || excluded(&relative) {
+            continue;
+        }
+        if let Some(data) = relative.strip_suffix(".dvc") {
+            // A tracked directory is part of the layout, its data isn't
+            let is_dir = dvcfile::read_dvc_file(&repo_root.join(&relative))
+                .is_ok_and(|dvc_file| dvc_file.outs.iter().any(|out| out.is_dir()));
+            if is_dir && !excluded(data) {
+                directories.insert(data.to_string());
+            }
+            directories.extend(parents(data).map(str::to_string));
+            continue;
+        }
+        directories.extend(parents(&relative).map(str::to_string));
+        if matches(include, &relative) {
+            files.push(relative);
+        }
+    }
+
+    // Only directories nothing else creates need to be listed
+    let directories = directories
+        .iter()
+        .filter(|dir| {
+            let prefix = format!("{}/", dir);
+            !files.iter().any(|file| file.starts_with(&prefix))
+                && !directories.iter().any(|other| other.starts_with(&prefix))
+        })
+        .cloned()
+        .collect();
+    Ok((files, directories))
+}
+
+/// Built-in layouts and templates saved with `save_project_template`, which
+/// `init_dvc_project` can start a project with
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_project_templates(app_handle: AppHandle) -> Result<Vec<ProjectTemplate>, String> {
+    let mut templates: Vec<ProjectTemplate> = TEMPLATES
+        .iter()
+        .map(|template| ProjectTemplate {
+            id: template.id.to_string(),
+            name: template.name.to_string(),
+            description: template.description.to_string(),
+            files: template
+                .files
+                .iter()
+                .map(|(path, _)| path.to_string())
+                .collect(),
+            custom: false,
+        })
+        .collect();
+    templates.extend(
+        list_custom(&app_handle)?
+            .into_iter()
+            .map(ProjectTemplate::from),
+    );
+    Ok(templates)
+}
+
+/// Saves the structure of a project as a template: its directories, plus the
+/// contents of the git-tracked files matching `include` (glob patterns like
+/// `params.yaml` or `configs/*.yaml`). Paths matching `exclude` are left out.
+/// Saving under the name of an existing saved template replaces it.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn save_project_template(
+    app_handle: AppHandle,
+    repo_path: String,
+    name: String,
+    description: Option<String>,
+    include: Vec<String>,
+    exclude: Vec<String>,
+) -> Result<ProjectTemplate, String> {
+    let id = template_id(&name)?;
+    let repo_root = transfer::repo_root(&repo_path)?;
+    // Read between writes so the template doesn't catch a half-done checkout
+    let (files, directories) = repo_manager::read(&app_handle, &repo_path, |_| {
+        project_layout(&repo_root, &compile(&include)?, &compile(&exclude)?)
+    })?;
+
+    // Written next to the old template and swapped in, so a failed save
+    // leaves it intact
+    let dir = templates_dir(&app_handle)?;
+    let staging = dir.join(format!(".{}.saving", id));
+    if staging.exists() {
+        fs::remove_dir_all(&staging)
+            .map_err(|e| format!("Failed to remove {}: {}", staging.display(), e))?;
+    }
+    fs::create_dir_all(&staging)
+        .map_err(|e| format!("Failed to create {}: {}", staging.display(), e))?;
+    for relative in &files {
+        let dest = staging.join(FILES_DIR).join(relative);
+        if let Some(parent) = dest.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+        }
+        fs::copy(repo_root.join(relative), &dest)
+            .map_err(|e| format!("Failed to copy {}: {}", relative, e))?;
+    }
+    let template = CustomTemplate {
+        id: id.clone(),
+        name,
+        description: description.unwrap_or_default(),
+        source: repo_root.to_string_lossy().to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+        files,
+        directories,
+    };
+    let content = serde_json::to_string_pretty(&template)
+        .map_err(|e| format!("Failed to serialize template: {}", e))?;
+    fs::write(staging.join(TEMPLATE_FILE), content)
+        .map_err(|e| format!("Failed to write template: {}", e))?;
+
+    let target = dir.join(&id);
+    if target.exists() {
+        fs::remove_dir_all(&target)
+            .map_err(|e| format!("Failed to replace template {}: {}", id, e))?;
+    }
+    fs::rename(&staging, &target).map_err(|e| format!("Failed to save template: {}", e))?;
+    Ok(template.into())
+}
+
+/// Deletes a template saved with `save_project_template`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn delete_project_template(app_handle: AppHandle, id: String) -> Result<(), String> {
+    if read_custom(&app_handle, &id)?.is_none() {
+        return Err(format!("No saved project template {}", id));
+    }
+    fs::remove_dir_all(templates_dir(&app_handle)?.join(&id))
+        .map_err(|e| format!("Failed to delete template {}: {}", id, e))
+}
@@ -0,0 +1,677 @@
+//! Throwaway git + DVC fixture repositories for exercising the command layer.
+//!
+//! Compiled for unit tests and when the `test-support` feature is enabled.
+
+use git2::{IndexAddOption, Oid, Repository, Signature};
+use md5::{Digest, Md5};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::dvc_compat::DvcFormat;
+use crate::dvcfile;
+
+pub const FIXTURE_USER_NAME: &str = "Fixture User";
+pub const FIXTURE_USER_EMAIL: &str = "fixture@fenn.test";
+
+/// A git repository in a temporary directory, removed again on drop
+pub struct FixtureRepo {
+    root: PathBuf,
+    repo: Repository,
+    extra_dirs: Vec<PathBuf>,
+}
+
+fn temp_path(prefix: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("fenn-{}-{}", prefix, uuid::Uuid::new_v4().simple()))
+}
+
+impl FixtureRepo {
+    /// Creates a repository with a configured identity and an initial commit on `main`
+    pub fn new() -> Self {
+        let fixture = Self::empty();
+        fixture.write_file(".gitignore", "");
+        fixture.commit_all("Initial commit");
+        fixture
+    }
+
+    /// Creates a repository without any commits (unborn HEAD)
+    pub fn empty() -> Self {
+        let root = temp_path("fixture");
+        fs::create_dir_all(&root).expect("create fixture directory");
+
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.initial_head("main");
+        let repo = Repository::init_opts(&root, &opts).expect("init fixture repository");
+        {
+            let mut config = repo.config().expect("open fixture config");
+            config
+                .set_str("user.name", FIXTURE_USER_NAME)
+                .expect("set user.name");
+            config
+                .set_str("user.email", FIXTURE_USER_EMAIL)
+                .expect("set user.email");
+        }
+
+        Self {
+            root,
+            repo,
+            extra_dirs: Vec::new(),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.root
+    }
+
+    /// Repository path in the `String` form the commands accept
+    pub fn path_string(&self) -> String {
+        self.root.to_string_lossy().to_string()
+    }
+
+    pub fn repo(&self) -> &Repository {
+        &self.repo
+    }
+
+    pub fn write_file(&self, relative_path: &str, contents: impl AsRef<[u8]>) -> PathBuf {
+        let path = self.root.join(relative_path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).expect("create parent directories");
+        }
+        fs::write(&path, contents).expect("write fixture file");
+        path
+    }
+
+    pub fn read_file(&self, relative_path: &str) -> String {
+        fs::read_to_string(self.root.join(relative_path)).expect("read fixture file")
+    }
+
+    /// Stages every change in the working tree and commits it on the current branch
+    pub fn commit_all(&self, message: &str) -> Oid {
+        let mut index = self.repo.index().expect("open index");
+        index
+            .add_all(["*"].iter(), IndexAddOption::DEFAULT, None)
+            .expect("stage files");
+        index
+            .update_all(["*"].iter(), None)
+            .expect("stage deletions");
+        index.write().expect("write index");
+
+        let tree_id = index.write_tree().expect("write tree");
+        let tree = self.repo.find_tree(tree_id).expect("find tree");
+        let sig = Signature::now(FIXTURE_USER_NAME, FIXTURE_USER_EMAIL).expect("signature");
+
+        let parent = self
+            .repo
+            .head()
+            .ok()
+            .and_then(|head| head.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+
+        self.repo
+            .commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .expect("create commit")
+    }
+
+    /// Creates a branch at HEAD without switching to it
+    pub fn create_branch(&self, name: &str) {
+        let head = self
+            .repo
+            .head()
+            .and_then(|h| h.peel_to_commit())
+            .expect("HEAD commit");
+        self.repo.branch(name, &head, false).expect("create branch");
+    }
+
+    pub fn current_branch(&self) -> String {
+        self.repo
+            .head()
+            .ok()
+            .and_then(|h| h.shorthand().map(|s| s.to_string()))
+            .unwrap_or_else(|| "HEAD".to_string())
+    }
+
+    /// Lays out a minimal `.dvc` directory with a local cache
+    pub fn init_dvc(&self) {
+        self.write_file(".dvc/config", "");
+        self.write_file(".dvc/.gitignore", "/config.local\n/tmp\n/cache\n");
+        fs::create_dir_all(self.root.join(".dvc/cache")).expect("create DVC cache");
+    }
+
+    /// Writes a data file, stores it in the DVC cache, writes its `.dvc`
+    /// pointer and ignores it in git, mirroring what `dvc add` does.
+    /// Returns the md5 of the contents.
+    pub fn track_dataset(&self, relative_path: &str, contents: impl AsRef<[u8]>) -> String {
+        self.track(relative_path, contents.as_ref(), true)
+    }
+
+    /// Like `track_dataset`, but in the DVC 2 layout: the cache object sits
+    /// directly under the cache root, the pointer has no `hash` field and text
+    /// is hashed with LF line endings
+    pub fn track_dvc2_dataset(&self, relative_path: &str, contents: impl AsRef<[u8]>) -> String {
+        self.track(relative_path, contents.as_ref(), false)
+    }
+
+    fn track(&self, relative_path: &str, contents: &[u8], dvc3: bool) -> String {
+        let data_path = self.write_file(relative_path, contents);
+        let md5 = if dvc3 {
+            format!("{:x}", Md5::digest(contents))
+        } else {
+            dvcfile::hash_file_for(&data_path, Some(DvcFormat::V2)).expect("hash data file")
+        };
+
+        let cache_root = if dvc3 {
+            self.root.join(".dvc/cache/files/md5")
+        } else {
+            self.root.join(".dvc/cache")
+        };
+        let cache_path = cache_root.join(&md5[..2]).join(&md5[2..]);
+        fs::create_dir_all(cache_path.parent().unwrap()).expect("create cache directory");
+        fs::write(&cache_path, contents).expect("write cache object");
+
+        let file_name = data_path.file_name().unwrap().to_string_lossy().to_string();
+        let pointer = format!(
+            "outs:\n- md5: {}\n  size: {}\n{}  path: {}\n",
+            md5,
+            contents.len(),
+            if dvc3 { "  hash: md5\n" } else { "" },
+            file_name
+        );
+        self.write_file(&format!("{}.dvc", relative_path), pointer);
+
+        let gitignore = data_path.parent().unwrap().join(".gitignore");
+        let mut ignored = fs::read_to_string(&gitignore).unwrap_or_default();
+        ignored.push_str(&format!("/{}\n", file_name));
+        fs::write(&gitignore, ignored).expect("write .gitignore");
+
+        md5
+    }
+
+    /// A path in the temporary directory that is removed along with the fixture
+    pub fn scratch_path(&mut self, prefix: &str) -> PathBuf {
+        let path = temp_path(prefix);
+        self.extra_dirs.push(path.clone());
+        path
+    }
+
+    /// Adds a bare repository on the local filesystem as a git remote
+    pub fn add_git_remote(&mut self, name: &str) -> PathBuf {
+        let remote_path = temp_path("remote");
+        let mut opts = git2::RepositoryInitOptions::new();
+        opts.bare(true).initial_head("main");
+        Repository::init_opts(&remote_path, &opts).expect("init bare remote");
+        self.repo
+            .remote(name, &remote_path.to_string_lossy())
+            .expect("add remote");
+        self.extra_dirs.push(remote_path.clone());
+        remote_path
+    }
+
+    /// Pushes the current branch to a remote and sets it as upstream
+    pub fn push_current_branch(&self, remote_name: &str) {
+        let branch = self.current_branch();
+        let mut remote = self.repo.find_remote(remote_name).expect("find remote");
+        let refspec = format!("refs/heads/{0}:refs/heads/{0}", branch);
+        remote.push(&[refspec.as_str()], None).expect("push");
+
+        let mut fetch = self.repo.find_remote(remote_name).expect("find remote");
+        fetch
+            .fetch(&[] as &[&str], None, None)
+            .expect("fetch after push");
+
+        let mut local = self
+            .repo
+            .find_branch(&branch, git2::BranchType::Local)
+            .expect("find branch");
+        local
+            .set_upstream(Some(&format!("{}/{}", remote_name, branch)))
+            .expect("set upstream");
+    }
+
+    /// Creates a directory-backed DVC remote and makes it the default remote
+    pub fn add_dvc_remote(&mut self, name: &str) -> PathBuf {
+        let remote_path = temp_path("dvc-remote");
+        fs::create_dir_all(&remote_path).expect("create DVC remote");
+        let config = format!(
+            "[core]\n    remote = {0}\n['remote \"{0}\"']\n    url = {1}\n",
+            name,
+            remote_path.to_string_lossy().replace('\\', "/")
+        );
+        self.write_file(".dvc/config", config);
+        self.extra_dirs.push(remote_path.clone());
+        remote_path
+    }
+
+    /// Clones the fixture into another temporary directory, e.g. to simulate a teammate
+    pub fn clone_from_remote(&mut self, remote_path: &Path) -> FixtureRepo {
+        let root = temp_path("clone");
+        let repo = Repository::clone(&remote_path.to_string_lossy(), &root).expect("clone remote");
+        {
+            let mut config = repo.config().expect("open clone config");
+            config
+                .set_str("user.name", FIXTURE_USER_NAME)
+                .expect("set user.name");
+            config
+                .set_str("user.email", FIXTURE_USER_EMAIL)
+                .expect("set user.email");
+        }
+        FixtureRepo {
+            root,
+            repo,
+            extra_dirs: Vec::new(),
+        }
+    }
+}
+
+impl Default for FixtureRepo {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for FixtureRepo {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.root);
+        for dir in &self.extra_dirs {
+            let _ = fs::remove_dir_all(dir);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hooks::HookSettings;
+    use crate::storage::{self, RemoteStorage, TransferConfig};
+    use crate::throttle::RateLimiter;
+    use crate::transfer::{self, ProgressTracker};
+    use crate::transfer_queue::TransferQueue;
+    use crate::{dvc, dvc_config, file, git, integrity};
+
+    /// Status as `git_status` reports it, read through a fresh repository handle
+    fn status(fixture: &FixtureRepo) -> git::GitStatus {
+        git::status(&Repository::open(fixture.path()).unwrap()).unwrap()
+    }
+
+    /// What a push or pull needs, minus the app's progress events and job database
+    struct TransferHarness {
+        storage: Box<dyn RemoteStorage>,
+        config: TransferConfig,
+        tracker: ProgressTracker,
+        queue: TransferQueue,
+        limiter: RateLimiter,
+    }
+
+    impl TransferHarness {
+        /// Opens the fixture's default DVC remote
+        fn new(fixture: &FixtureRepo, operation: &str) -> Self {
+            let config = TransferConfig::default();
+            let remote = dvc_config::remote(fixture.path(), None).unwrap();
+            Self {
+                storage: storage::open_remote(&remote, &config).unwrap(),
+                config,
+                tracker: ProgressTracker::detached(operation, operation),
+                queue: TransferQueue::in_memory(operation).unwrap(),
+                limiter: RateLimiter::new(None),
+            }
+        }
+
+        fn push(&self, fixture: &FixtureRepo) -> transfer::PushOutcome {
+            let pointers = transfer::resolve_pointers(fixture.path(), &[]).unwrap();
+            transfer::push_pointers(
+                self.storage.as_ref(),
+                fixture.path(),
+                &pointers,
+                &self.config,
+                &self.tracker,
+                &self.queue,
+                &self.limiter,
+            )
+            .unwrap()
+        }
+
+        fn pull(&self, fixture: &FixtureRepo) -> Vec<String> {
+            let pointers = transfer::resolve_pointers(fixture.path(), &[]).unwrap();
+            let mut outputs = Vec::new();
+            for pointer in &pointers {
+                outputs.extend(dvcfile::read_dvc_file(pointer).unwrap().outs);
+            }
+            transfer::pull_outputs(
+                self.storage.as_ref(),
+                fixture.path(),
+                &outputs,
+                &self.config,
+                &self.tracker,
+                &self.queue,
+                &self.limiter,
+            )
+            .unwrap()
+        }
+    }
+
+    #[test]
+    fn status_reports_untracked_and_staged_files() {
+        let fixture = FixtureRepo::new();
+        fixture.write_file("notes.txt", "hello");
+        fixture.write_file("src/main.py", "print('hi')");
+
+        git::stage_files(
+            &fixture.path_string(),
+            vec!["src/main.py".to_string()],
+            None,
+            &HookSettings::default(),
+        )
+        .unwrap();
+
+        let status = status(&fixture);
+        assert_eq!(status.current_branch, "main");
+        assert!(status.has_untracked);
+        assert!(status.has_staged);
+
+        let notes = status.files.iter().find(|f| f.path == "notes.txt").unwrap();
+        assert_eq!(notes.status, "untracked");
+        let script = status
+            .files
+            .iter()
+            .find(|f| f.path == "src/main.py")
+            .unwrap();
+        assert_eq!(script.status, "staged");
+    }
+
+    #[test]
+    fn add_and_reset_files_round_trip() {
+        let fixture = FixtureRepo::new();
+        fixture.write_file("a.txt", "a");
+
+        git::stage_files(
+            &fixture.path_string(),
+            vec!["a.txt".to_string()],
+            None,
+            &HookSettings::default(),
+        )
+        .unwrap();
+        assert!(status(&fixture).has_staged);
+
+        git::reset_files(&fixture.path_string(), vec!["a.txt".to_string()]).unwrap();
+        let status = status(&fixture);
+        assert!(!status.has_staged);
+        assert!(status.has_untracked);
+    }
+
+    #[test]
+    fn staging_refuses_large_files_unless_forced() {
+        let fixture = FixtureRepo::new();
+        fixture.write_file("weights.bin", vec![0u8; 2048]);
+        let hook_settings = HookSettings {
+            max_file_size_kib: 1,
+            ..HookSettings::default()
+        };
+
+        let refused = git::stage_files(
+            &fixture.path_string(),
+            vec!["weights.bin".to_string()],
+            None,
+            &hook_settings,
+        )
+        .unwrap();
+        assert!(refused.staged.is_empty());
+        assert_eq!(refused.suggestions.len(), 1);
+        assert_eq!(refused.suggestions[0].path, "weights.bin");
+
+        let forced = git::stage_files(
+            &fixture.path_string(),
+            vec!["weights.bin".to_string()],
+            Some(true),
+            &hook_settings,
+        )
+        .unwrap();
+        assert_eq!(forced.staged, vec!["weights.bin".to_string()]);
+        assert!(forced.suggestions.is_empty());
+        let index = fixture.repo().index().unwrap();
+        assert!(index.get_path(Path::new("weights.bin"), 0).is_some());
+    }
+
+    #[test]
+    fn commit_records_staged_changes() {
+        let fixture = FixtureRepo::new();
+        fixture.write_file("a.txt", "a");
+        git::stage_files(
+            &fixture.path_string(),
+            vec!["a.txt".to_string()],
+            None,
+            &HookSettings::default(),
+        )
+        .unwrap();
+
+        let result = git::commit_staged(
+            &fixture.path_string(),
+            "Add a",
+            "",
+            &HookSettings::default(),
+            |repo, signature, message, tree, parents| {
+                repo.commit(Some("HEAD"), signature, signature, message, tree, parents)
+                    .map_err(|e| e.to_string())
+            },
+        )
+        .unwrap();
+        assert!(result.success);
+
+        let status = status(&fixture);
+        assert!(status.files.is_empty());
+    }
+
+    #[test]
+    fn blocked_commits_leave_staged_notebooks_untouched() {
+        let fixture = FixtureRepo::new();
+        let notebook = r#"{"cells": [{"cell_type": "code", "execution_count": 1, "metadata": {}, "outputs": [{"output_type": "stream", "name": "stdout", "text": "hi"}], "source": "print('hi')"}], "metadata": {}, "nbformat": 4, "nbformat_minor": 5}"#;
+        fixture.write_file("analysis.ipynb", notebook);
+        let settings = HookSettings {
+            enabled: vec!["notebook-outputs".to_string()],
+            ..HookSettings::default()
+        };
+        git::stage_files(
+            &fixture.path_string(),
+            vec!["analysis.ipynb".to_string()],
+            None,
+            &settings,
+        )
+        .unwrap();
+
+        // The summary is too long for the commit message check
+        let result = git::commit_staged(
+            &fixture.path_string(),
+            &"x".repeat(settings.max_summary_length + 1),
+            "",
+            &settings,
+            |_, _, _, _, _| panic!("a blocked commit must not be created"),
+        )
+        .unwrap();
+        assert!(!result.success);
+        assert!(result.stripped_notebooks.is_empty());
+
+        let repo = Repository::open(fixture.path()).unwrap();
+        let entry = repo
+            .index()
+            .unwrap()
+            .get_path(Path::new("analysis.ipynb"), 0)
+            .unwrap();
+        let staged = repo.find_blob(entry.id).unwrap();
+        assert_eq!(staged.content(), notebook.as_bytes());
+    }
+
+    #[test]
+    fn checkout_creates_and_switches_branches() {
+        let fixture = FixtureRepo::new();
+        fixture.create_branch("experiment");
+
+        git::switch_branch(fixture.path_string(), "experiment".to_string()).unwrap();
+        assert_eq!(
+            git::git_current_branch(fixture.path_string()).unwrap(),
+            "experiment"
+        );
+
+        git::checkout(fixture.path_string(), "new-branch".to_string()).unwrap();
+        assert_eq!(fixture.current_branch(), "new-branch");
+
+        let branches = git::git_list_branches(fixture.path_string()).unwrap();
+        let names: Vec<_> = branches.iter().map(|b| b.name.as_str()).collect();
+        assert!(names.contains(&"main"));
+        assert!(names.contains(&"experiment"));
+        assert!(names.contains(&"new-branch"));
+    }
+
+    #[test]
+    fn tracked_dataset_status_follows_pointer() {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+        fixture.commit_all("Track training data");
+
+        let data_path = fixture.path().join("data/train.csv");
+        let statuses = file::get_files_status(
+            &fixture.path_string(),
+            vec![data_path.to_string_lossy().to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(statuses.len(), 1);
+        assert!(statuses[0].has_dvc_file);
+        assert_eq!(statuses[0].git_status, "pushed");
+    }
+
+    #[test]
+    fn push_to_fixture_remote_updates_tracking_branch() {
+        let mut fixture = FixtureRepo::new();
+        let remote = fixture.add_git_remote("origin");
+        fixture.push_current_branch("origin");
+
+        let repo = fixture.repo();
+        let head = repo.head().unwrap().target().unwrap();
+        let tracking = repo.refname_to_id("refs/remotes/origin/main").unwrap();
+        assert_eq!(tracking, head);
+        let local = repo.find_branch("main", git2::BranchType::Local).unwrap();
+        let upstream = local.upstream().unwrap();
+        assert_eq!(upstream.name().unwrap(), Some("origin/main"));
+
+        let clone = fixture.clone_from_remote(&remote);
+        assert_eq!(clone.current_branch(), "main");
+        assert_eq!(clone.read_file(".gitignore"), "");
+    }
+
+    #[test]
+    fn dvc_add_stages_pointer_and_gitignore() {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        // What the `dvc add` script leaves behind
+        fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+
+        dvc::stage_pointer(&fixture.path_string(), "data/train.csv").unwrap();
+
+        let staged: Vec<String> = status(&fixture)
+            .files
+            .into_iter()
+            .filter(|f| f.is_staged)
+            .map(|f| f.path)
+            .collect();
+        assert!(staged.contains(&"data/train.csv.dvc".to_string()));
+        assert!(staged.contains(&"data/.gitignore".to_string()));
+        assert!(!staged.contains(&"data/train.csv".to_string()));
+    }
+
+    #[test]
+    fn dvc_add_creates_initial_commit_in_empty_repository() {
+        let fixture = FixtureRepo::empty();
+        fixture.init_dvc();
+        fixture.track_dataset("train.csv", "a,b\n1,2\n");
+
+        dvc::stage_pointer(&fixture.path_string(), "train.csv").unwrap();
+
+        let head = fixture.repo().head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.summary(), Some("Initial commit"));
+        assert!(status(&fixture).has_staged);
+    }
+
+    #[test]
+    fn dvc_push_uploads_cache_objects_to_remote() {
+        let mut fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let remote = fixture.add_dvc_remote("storage");
+        let md5 = fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+
+        let harness = TransferHarness::new(&fixture, "push");
+        let pushed = harness.push(&fixture);
+        assert_eq!(pushed.pushed, vec![md5.clone()]);
+        assert!(pushed.failed.is_empty());
+        assert!(pushed.missing_locally.is_empty());
+        assert!(remote.join(storage::object_key(&md5)).exists());
+
+        // Objects the remote already has are skipped
+        let again = TransferHarness::new(&fixture, "push-again");
+        again.push(&fixture);
+        assert_eq!(again.tracker.bytes(), 0);
+    }
+
+    #[test]
+    fn dvc_pull_restores_data_from_remote() {
+        let mut fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        fixture.add_dvc_remote("storage");
+        let md5 = fixture.track_dataset("data/train.csv", "a,b\n1,2\n");
+        TransferHarness::new(&fixture, "push").push(&fixture);
+
+        // Lose both the workspace copy and the cache, as on a fresh clone
+        fs::remove_file(fixture.path().join("data/train.csv")).unwrap();
+        fs::remove_dir_all(fixture.path().join(".dvc/cache")).unwrap();
+
+        let failed = TransferHarness::new(&fixture, "pull").pull(&fixture);
+        assert!(failed.is_empty());
+        let cache_dir = dvcfile::cache_dir(fixture.path());
+        assert!(dvcfile::cache_object_path(&cache_dir, &md5).exists());
+
+        let pointers = transfer::resolve_pointers(fixture.path(), &[]).unwrap();
+        crate::checkout::checkout_pointers(fixture.path(), &pointers, false).unwrap();
+        assert_eq!(fixture.read_file("data/train.csv"), "a,b\n1,2\n");
+    }
+
+    #[test]
+    fn dvc2_repositories_keep_the_legacy_layout() {
+        let mut fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let remote = fixture.add_dvc_remote("storage");
+        // CRLF text is hashed with LF line endings by DVC 2
+        let md5 = fixture.track_dvc2_dataset("data/train.csv", "a,b\r\n1,2\r\n");
+
+        TransferHarness::new(&fixture, "push").push(&fixture);
+        assert!(remote.join(storage::legacy_object_key(&md5)).exists());
+        assert!(!remote.join(storage::object_key(&md5)).exists());
+
+        // A fresh clone has no cache to tell the layout from, only the pointers
+        fs::remove_file(fixture.path().join("data/train.csv")).unwrap();
+        fs::remove_dir_all(fixture.path().join(".dvc/cache")).unwrap();
+        let failed = TransferHarness::new(&fixture, "pull").pull(&fixture);
+        assert!(failed.is_empty());
+        let cache_dir = dvcfile::cache_dir(fixture.path());
+        assert!(cache_dir.join(&md5[..2]).join(&md5[2..]).exists());
+        assert!(!cache_dir.join("files").exists());
+
+        let pointers = transfer::resolve_pointers(fixture.path(), &[]).unwrap();
+        let summary = crate::checkout::checkout_pointers(fixture.path(), &pointers, false).unwrap();
+        assert_eq!(summary.added, vec!["data/train.csv".to_string()]);
+        let again = crate::checkout::checkout_pointers(fixture.path(), &pointers, false).unwrap();
+        assert_eq!(again.unchanged, 1);
+    }
+
+    #[test]
+    fn dvc2_integrity_checks_hash_text_with_unix_line_endings() {
+        let fixture = FixtureRepo::new();
+        fixture.init_dvc();
+        let md5 = fixture.track_dvc2_dataset("data/notes.txt", "a,b\r\n1,2\r\n");
+        assert_eq!(md5, format!("{:x}", Md5::digest("a,b\n1,2\n")));
+
+        let report = integrity::verify_data_integrity(
+            fixture.path().to_string_lossy().to_string(),
+            Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(report.checked, 1);
+        assert!(report.problems.is_empty());
+    }
+}
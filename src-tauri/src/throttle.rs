@@ -0,0 +1,96 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::settings::TransferLimits;
+
+/// Token bucket shared by all workers of a transfer; a rate of 0 is unlimited
+pub struct RateLimiter {
+    bytes_per_sec: AtomicU64,
+    bucket: Mutex<(f64, Instant)>,
+}
+
+impl RateLimiter {
+    pub fn new(kib_per_sec: Option<u64>) -> Self {
+        let limiter = Self {
+            bytes_per_sec: AtomicU64::new(0),
+            bucket: Mutex::new((0.0, Instant::now())),
+        };
+        limiter.set_rate(kib_per_sec);
+        limiter
+    }
+
+    pub fn set_rate(&self, kib_per_sec: Option<u64>) {
+        let rate = kib_per_sec.unwrap_or(0).saturating_mul(1024);
+        self.bytes_per_sec.store(rate, Ordering::Relaxed);
+    }
+
+    /// Accounts for transferred bytes, sleeping long enough to keep the
+    /// average throughput under the limit
+    pub fn consume(&self, bytes: u64) {
+        let rate = self.bytes_per_sec.load(Ordering::Relaxed) as f64;
+        if rate == 0.0 {
+            return;
+        }
+
+        let wait = {
+            let Ok(mut bucket) = self.bucket.lock() else {
+                return;
+            };
+            let (available, updated) = &mut *bucket;
+            let now = Instant::now();
+            // Allow bursts of at most one second worth of data
+            *available = (*available + now.duration_since(*updated).as_secs_f64() * rate).min(rate);
+            *updated = now;
+            *available -= bytes as f64;
+            if *available < 0.0 {
+                Duration::from_secs_f64(-*available / rate)
+            } else {
+                Duration::ZERO
+            }
+        };
+
+        if !wait.is_zero() {
+            thread::sleep(wait);
+        }
+    }
+}
+
+pub struct RepoThrottle {
+    pub upload: RateLimiter,
+    pub download: RateLimiter,
+}
+
+/// Live rate limiters per repository, so limit changes apply to running transfers
+#[derive(Default)]
+pub struct TransferThrottles {
+    repos: HashMap<String, Arc<RepoThrottle>>,
+}
+
+impl TransferThrottles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get_or_insert(&mut self, repo_key: &str, limits: &TransferLimits) -> Arc<RepoThrottle> {
+        self.repos
+            .entry(repo_key.to_string())
+            .or_insert_with(|| {
+                Arc::new(RepoThrottle {
+                    upload: RateLimiter::new(limits.upload_kib_per_sec),
+                    download: RateLimiter::new(limits.download_kib_per_sec),
+                })
+            })
+            .clone()
+    }
+
+    pub fn apply(&mut self, repo_key: &str, limits: &TransferLimits) {
+        let throttle = self.get_or_insert(repo_key, limits);
+        throttle.upload.set_rate(limits.upload_kib_per_sec);
+        throttle.download.set_rate(limits.download_kib_per_sec);
+    }
+}
+
+pub type TransferThrottleState = Mutex<TransferThrottles>;
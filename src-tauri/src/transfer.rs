@@ -0,0 +1,1026 @@
+use git2::Repository;
+use serde::Serialize;
+use serde_json::json;
+use std::collections::{HashSet, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle, Emitter, Manager, State};
+use tracing::instrument;
+
+use crate::audit;
+use crate::branch_compare;
+use crate::checkout::{self, CheckoutSummary};
+use crate::dvc_compat::{self, DvcFormat};
+use crate::dvc_config;
+use crate::dvcfile::{self, DirManifestEntry, DvcOut};
+use crate::metrics;
+use crate::notifications::{self, Notification};
+use crate::offline::{self, SyncOperation};
+use crate::ownership;
+use crate::remote;
+use crate::remote_status;
+use crate::settings::{self, TransferLimits};
+use crate::storage::{self, RemoteStorage, TransferConfig};
+use crate::throttle::{RateLimiter, RepoThrottle, TransferThrottleState};
+use crate::transfer_queue::TransferQueue;
+
+pub const DVC_TRANSFER_PROGRESS_EVENT: &str = "dvc://transfer-progress";
+pub const DVC_PREFETCH_FINISHED_EVENT: &str = "dvc://prefetch-finished";
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+const MAX_CONCURRENCY: usize = 32;
+
+/// A cache object referenced by a pointer or directory manifest
+#[derive(Debug, Clone)]
+pub struct CacheObject {
+    pub md5: String,
+    pub cache_path: PathBuf,
+    /// Format of the repository, which decides how the content is hashed
+    pub format: Option<DvcFormat>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct DvcTransferProgress {
+    pub operation_id: String,
+    /// "push", "pull", "prefetch", "import" or "download"
+    pub operation: String,
+    pub total_objects: u64,
+    pub completed_objects: u64,
+    pub skipped_objects: u64,
+    pub failed_objects: u64,
+    pub transferred_bytes: u64,
+}
+
+/// Outcome of a background `prefetch_branch_data`
+#[derive(Debug, Serialize)]
+pub struct PrefetchFinished {
+    pub operation_id: String,
+    pub branch: String,
+    pub report: Option<TransferReport>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TransferReport {
+    pub operation_id: String,
+    pub remote: String,
+    pub transferred: u64,
+    pub skipped: u64,
+    pub failed: Vec<String>,
+    pub transferred_bytes: u64,
+    /// Objects referenced by pointers but absent from the local cache (push only)
+    pub missing_locally: Vec<String>,
+    pub checkout: Option<CheckoutSummary>,
+}
+
+/// Shared counters for a running transfer, emitting throttled progress events
+pub struct ProgressTracker {
+    /// Where progress events go; `None` only counts
+    app_handle: Option<AppHandle>,
+    operation_id: String,
+    operation: String,
+    total: AtomicU64,
+    completed: AtomicU64,
+    skipped: AtomicU64,
+    failed: AtomicU64,
+    bytes: AtomicU64,
+    last_emit: Mutex<Option<Instant>>,
+}
+
+impl ProgressTracker {
+    pub fn new(app_handle: &AppHandle, operation_id: &str, operation: &str) -> Self {
+        Self::with_events(Some(app_handle.clone()), operation_id, operation)
+    }
+
+    /// Tracker that only counts, for transfers run outside the app
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn detached(operation_id: &str, operation: &str) -> Self {
+        Self::with_events(None, operation_id, operation)
+    }
+
+    fn with_events(app_handle: Option<AppHandle>, operation_id: &str, operation: &str) -> Self {
+        Self {
+            app_handle,
+            operation_id: operation_id.to_string(),
+            operation: operation.to_string(),
+            total: AtomicU64::new(0),
+            completed: AtomicU64::new(0),
+            skipped: AtomicU64::new(0),
+            failed: AtomicU64::new(0),
+            bytes: AtomicU64::new(0),
+            last_emit: Mutex::new(None),
+        }
+    }
+
+    pub fn add_total(&self, count: u64) {
+        self.total.fetch_add(count, Ordering::Relaxed);
+        self.emit(true);
+    }
+
+    pub fn add_bytes(&self, bytes: u64) {
+        self.bytes.fetch_add(bytes, Ordering::Relaxed);
+        self.emit(false);
+    }
+
+    pub fn complete(&self) {
+        self.completed.fetch_add(1, Ordering::Relaxed);
+        self.emit(false);
+    }
+
+    pub fn skip(&self) {
+        self.skipped.fetch_add(1, Ordering::Relaxed);
+        self.emit(false);
+    }
+
+    pub fn fail(&self) {
+        self.failed.fetch_add(1, Ordering::Relaxed);
+        self.emit(true);
+    }
+
+    pub fn bytes(&self) -> u64 {
+        self.bytes.load(Ordering::Relaxed)
+    }
+
+    pub fn emit(&self, force: bool) {
+        let Some(app_handle) = &self.app_handle else {
+            return;
+        };
+        if let Ok(mut last) = self.last_emit.lock() {
+            if !force && last.is_some_and(|t| t.elapsed() < PROGRESS_INTERVAL) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+        let progress = DvcTransferProgress {
+            operation_id: self.operation_id.clone(),
+            operation: self.operation.clone(),
+            total_objects: self.total.load(Ordering::Relaxed),
+            completed_objects: self.completed.load(Ordering::Relaxed),
+            skipped_objects: self.skipped.load(Ordering::Relaxed),
+            failed_objects: self.failed.load(Ordering::Relaxed),
+            transferred_bytes: self.bytes.load(Ordering::Relaxed),
+        };
+        if let Err(e) = app_handle.emit(DVC_TRANSFER_PROGRESS_EVENT, progress) {
+            tracing::warn!("Failed to emit DVC transfer progress: {}", e);
+        }
+    }
+}
+
+/// Runs `work` over all items on a bounded pool of worker threads, returning
+/// the error messages of failed items
+pub fn run_parallel<T: Sync>(
+    items: &[T],
+    concurrency: usize,
+    work: impl Fn(&T) -> Result<(), String> + Sync,
+) -> Vec<String> {
+    let queue = Mutex::new(items.iter().collect::<VecDeque<_>>());
+    let errors = Mutex::new(Vec::new());
+
+    std::thread::scope(|scope| {
+        for _ in 0..concurrency.max(1) {
+            scope.spawn(|| loop {
+                let next = queue.lock().ok().and_then(|mut q| q.pop_front());
+                let Some(item) = next else {
+                    break;
+                };
+                if let Err(e) = work(item) {
+                    if let Ok(mut errors) = errors.lock() {
+                        errors.push(e);
+                    }
+                }
+            });
+        }
+    });
+
+    errors.into_inner().unwrap_or_default()
+}
+
+pub(crate) fn repo_root(repo_path: &str) -> Result<PathBuf, String> {
+    let repo = Repository::discover(repo_path).map_err(|e| ownership::open_error(repo_path, e))?;
+    repo.workdir()
+        .map(|p| p.to_path_buf())
+        .ok_or_else(|| "Repository has no working directory".to_string())
+}
+
+/// Pointer files selected by the targets, or all pointers when none are given
+pub(crate) fn resolve_pointers(
+    repo_root: &Path,
+    targets: &[String],
+) -> Result<Vec<PathBuf>, String> {
+    if targets.is_empty() {
+        return Ok(dvcfile::find_pointer_files(repo_root));
+    }
+    targets
+        .iter()
+        .map(|target| {
+            let relative = dvcfile::repo_relative_path(repo_root, Path::new(target))?;
+            let absolute = repo_root.join(relative);
+            let pointer = if absolute.extension().and_then(|e| e.to_str()) == Some("dvc") {
+                absolute
+            } else {
+                dvcfile::pointer_path(&absolute)
+            };
+            if pointer.exists() {
+                Ok(pointer)
+            } else {
+                Err(format!("{} is not tracked by DVC", target))
+            }
+        })
+        .collect()
+}
+
+/// DVC outputs recorded in pointers
+fn pointer_outputs(pointers: &[PathBuf]) -> Result<Vec<DvcOut>, String> {
+    let mut outs = Vec::new();
+    for pointer in pointers {
+        outs.extend(dvcfile::read_dvc_file(pointer)?.outs);
+    }
+    Ok(outs)
+}
+
+/// Collects the cache objects referenced by pointers
+pub(crate) fn collect_objects(
+    repo_root: &Path,
+    pointers: &[PathBuf],
+) -> Result<Vec<CacheObject>, String> {
+    Ok(output_objects(repo_root, &pointer_outputs(pointers)?))
+}
+
+/// Collects the cache objects of DVC outputs. Directory outputs contribute
+/// their `.dir` manifest plus, when the manifest is cached, every file it
+/// lists.
+pub(crate) fn output_objects(repo_root: &Path, outs: &[DvcOut]) -> Vec<CacheObject> {
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let format = dvc_compat::repo_format(repo_root);
+    let mut seen = HashSet::new();
+    let mut objects = Vec::new();
+
+    let mut push_object = |md5: &str, objects: &mut Vec<CacheObject>| {
+        if seen.insert(md5.to_string()) {
+            objects.push(CacheObject {
+                md5: md5.to_string(),
+                cache_path: dvcfile::cache_object_path_for(&cache_dir, md5, format),
+                format,
+            });
+        }
+    };
+
+    for out in outs {
+        let Some(md5) = out.md5.as_deref() else {
+            continue;
+        };
+        push_object(md5, &mut objects);
+        if out.is_dir() {
+            if let Ok(manifest) = dvcfile::read_dir_manifest(&cache_dir, md5) {
+                for entry in manifest {
+                    push_object(&entry.md5, &mut objects);
+                }
+            }
+        }
+    }
+
+    objects
+}
+
+/// Resolves the key of an object on the remote, falling back to the DVC 2 layout
+pub(crate) fn remote_key(storage: &dyn RemoteStorage, md5: &str) -> Result<Option<String>, String> {
+    let key = storage::object_key(md5);
+    if storage.exists(&key)? {
+        return Ok(Some(key));
+    }
+    let legacy = storage::legacy_object_key(md5);
+    if storage.exists(&legacy)? {
+        return Ok(Some(legacy));
+    }
+    Ok(None)
+}
+
+/// Downloads an object into the cache through a temporary file, verifying its md5
+fn download_object(
+    storage: &dyn RemoteStorage,
+    key: &str,
+    object: &CacheObject,
+    tracker: &ProgressTracker,
+    limiter: &RateLimiter,
+) -> Result<(), String> {
+    let parent = object
+        .cache_path
+        .parent()
+        .ok_or("Invalid cache object path")?;
+    fs::create_dir_all(parent).map_err(|e| format!("Failed to create cache directory: {}", e))?;
+
+    let temp = parent.join(format!(".{}.tmp", uuid::Uuid::new_v4().simple()));
+    let result = storage
+        .download(key, &temp, &|bytes| {
+            tracker.add_bytes(bytes);
+            limiter.consume(bytes);
+        })
+        .and_then(|_| {
+            let actual = dvcfile::hash_file_for(&temp, object.format)?;
+            let expected = object.md5.trim_end_matches(".dir");
+            if actual != expected {
+                return Err(format!(
+                    "Checksum mismatch for {}: expected {}, got {}",
+                    object.md5, expected, actual
+                ));
+            }
+            fs::rename(&temp, &object.cache_path)
+                .map_err(|e| format!("Failed to store {} in cache: {}", object.md5, e))
+        });
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Records the outcome of an object in the queue; queue errors are only logged
+/// since they must not fail a transfer that otherwise succeeded
+fn record_outcome(queue: &TransferQueue, object: &CacheObject, result: &Result<(), String>) {
+    let recorded = match result {
+        Ok(()) => queue.mark_completed(&object.md5),
+        Err(e) => queue.mark_failed(&object.md5, e),
+    };
+    if let Err(e) = recorded {
+        tracing::warn!("Failed to record transfer of {}: {}", object.md5, e);
+    }
+}
+
+/// Uploads objects the remote lacks under the key layout of `format`
+pub(crate) fn push_objects(
+    storage: &dyn RemoteStorage,
+    objects: &[CacheObject],
+    format: Option<DvcFormat>,
+    config: &TransferConfig,
+    tracker: &ProgressTracker,
+    queue: &TransferQueue,
+    limiter: &RateLimiter,
+) -> Result<Vec<String>, String> {
+    queue.enqueue(objects)?;
+    let done = queue.completed()?;
+    tracker.add_total(objects.len() as u64);
+
+    Ok(run_parallel(objects, config.concurrency, |object| {
+        if done.contains(&object.md5) {
+            tracker.skip();
+            return Ok(());
+        }
+        let key = storage::object_key_for(&object.md5, format);
+        let result = storage.exists(&key).and_then(|exists| {
+            if exists {
+                tracker.skip();
+                Ok(())
+            } else {
+                storage
+                    .upload(&key, &object.cache_path, &|bytes| {
+                        tracker.add_bytes(bytes);
+                        limiter.consume(bytes);
+                    })
+                    .map(|_| tracker.complete())
+            }
+        });
+        if result.is_err() {
+            tracker.fail();
+        }
+        record_outcome(queue, object, &result);
+        result.map_err(|e| format!("{}: {}", object.md5, e))
+    }))
+}
+
+pub(crate) fn pull_objects(
+    storage: &dyn RemoteStorage,
+    objects: &[CacheObject],
+    config: &TransferConfig,
+    tracker: &ProgressTracker,
+    queue: &TransferQueue,
+    limiter: &RateLimiter,
+) -> Result<Vec<String>, String> {
+    queue.enqueue(objects)?;
+    tracker.add_total(objects.len() as u64);
+
+    Ok(run_parallel(objects, config.concurrency, |object| {
+        // The cache itself tells which objects an earlier run already fetched
+        if object.cache_path.exists() {
+            tracker.skip();
+            record_outcome(queue, object, &Ok(()));
+            return Ok(());
+        }
+        let result = remote_key(storage, &object.md5).and_then(|key| match key {
+            Some(key) => {
+                download_object(storage, &key, object, tracker, limiter).map(|_| tracker.complete())
+            }
+            None => Err("not found on remote".to_string()),
+        });
+        if result.is_err() {
+            tracker.fail();
+        }
+        record_outcome(queue, object, &result);
+        result.map_err(|e| format!("{}: {}", object.md5, e))
+    }))
+}
+
+/// Transfer settings of a repository and its live rate limiters
+fn transfer_limits(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+) -> Result<(TransferConfig, Arc<RepoThrottle>), String> {
+    let limits = settings::load(app_handle, repo_root)?.transfer_limits;
+    let config = TransferConfig {
+        concurrency: limits.max_concurrency.max(1),
+        ..TransferConfig::default()
+    };
+    let throttle = app_handle
+        .state::<TransferThrottleState>()
+        .lock()
+        .map_err(|_| "Failed to lock transfer throttles".to_string())?
+        .get_or_insert(&settings::project_key(repo_root), &limits);
+    Ok((config, throttle))
+}
+
+fn record_metrics(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    operation: &str,
+    started: Instant,
+    tracker: &ProgressTracker,
+) {
+    metrics::record(
+        app_handle,
+        repo_root,
+        operation,
+        started.elapsed(),
+        Some(tracker.completed.load(Ordering::Relaxed)),
+        Some(tracker.bytes()),
+    );
+}
+
+pub(crate) fn run_push(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    targets: &[String],
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<TransferReport, String> {
+    let started = Instant::now();
+    let remote_config = dvc_config::remote(repo_root, remote)?;
+    let (config, throttle) = transfer_limits(app_handle, repo_root)?;
+    let storage = storage::open_remote(&remote_config, &config)?;
+    let queue = TransferQueue::open(
+        app_handle,
+        &operation_id,
+        repo_root,
+        &remote_config.name,
+        "push",
+        targets,
+    )?;
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "push");
+
+    let pointers = resolve_pointers(repo_root, targets)?;
+    let pushed = push_pointers(
+        storage.as_ref(),
+        repo_root,
+        &pointers,
+        &config,
+        &tracker,
+        &queue,
+        &throttle.upload,
+    )?;
+    tracker.emit(true);
+    queue.finish()?;
+    record_metrics(app_handle, repo_root, "dvc_push", started, &tracker);
+
+    if let Err(e) = remote_status::record(app_handle, &remote_config.url, &pushed.pushed, true) {
+        tracing::warn!("Failed to record pushed objects: {}", e);
+    }
+
+    Ok(TransferReport {
+        operation_id,
+        remote: remote_config.name,
+        transferred: tracker.completed.load(Ordering::Relaxed),
+        skipped: tracker.skipped.load(Ordering::Relaxed),
+        failed: pushed.failed,
+        transferred_bytes: tracker.bytes(),
+        missing_locally: pushed.missing_locally,
+        checkout: None,
+    })
+}
+
+/// Objects of a push by outcome
+#[derive(Debug)]
+pub(crate) struct PushOutcome {
+    /// Objects the remote has now, uploaded or already there
+    pub pushed: Vec<String>,
+    /// "<md5>: <error>" for each object that failed
+    pub failed: Vec<String>,
+    /// Objects referenced by the pointers but absent from the local cache
+    pub missing_locally: Vec<String>,
+}
+
+/// Uploads the cache objects of pointers that the remote lacks
+pub(crate) fn push_pointers(
+    storage: &dyn RemoteStorage,
+    repo_root: &Path,
+    pointers: &[PathBuf],
+    config: &TransferConfig,
+    tracker: &ProgressTracker,
+    queue: &TransferQueue,
+    limiter: &RateLimiter,
+) -> Result<PushOutcome, String> {
+    let (present, missing): (Vec<CacheObject>, Vec<CacheObject>) =
+        collect_objects(repo_root, pointers)?
+            .into_iter()
+            .partition(|object| object.cache_path.exists());
+
+    let format = dvc_compat::repo_format(repo_root);
+    let failed = push_objects(storage, &present, format, config, tracker, queue, limiter)?;
+
+    // Failures are reported as "<md5>: <error>"
+    let pushed = present
+        .into_iter()
+        .map(|object| object.md5)
+        .filter(|md5| !failed.iter().any(|e| e.starts_with(&format!("{}:", md5))))
+        .collect();
+    Ok(PushOutcome {
+        pushed,
+        failed,
+        missing_locally: missing.into_iter().map(|o| o.md5).collect(),
+    })
+}
+
+/// Downloads the cache objects of DVC outputs that aren't cached yet, without
+/// touching the workspace. Returns the objects that failed.
+pub(crate) fn pull_outputs(
+    storage: &dyn RemoteStorage,
+    repo_root: &Path,
+    outputs: &[DvcOut],
+    config: &TransferConfig,
+    tracker: &ProgressTracker,
+    queue: &TransferQueue,
+    limiter: &RateLimiter,
+) -> Result<Vec<String>, String> {
+    // Directory manifests have to be fetched before the files they list are known
+    let manifests: Vec<CacheObject> = output_objects(repo_root, outputs)
+        .into_iter()
+        .filter(|object| object.md5.ends_with(".dir"))
+        .collect();
+    let mut failed = pull_objects(storage, &manifests, config, tracker, queue, limiter)?;
+
+    let objects: Vec<CacheObject> = output_objects(repo_root, outputs)
+        .into_iter()
+        .filter(|object| !object.md5.ends_with(".dir"))
+        .collect();
+    failed.extend(pull_objects(
+        storage, &objects, config, tracker, queue, limiter,
+    )?);
+    Ok(failed)
+}
+
+pub(crate) fn run_pull(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    targets: &[String],
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<TransferReport, String> {
+    let started = Instant::now();
+    let remote_config = dvc_config::remote(repo_root, remote)?;
+    let (config, throttle) = transfer_limits(app_handle, repo_root)?;
+    let storage = storage::open_remote(&remote_config, &config)?;
+    let queue = TransferQueue::open(
+        app_handle,
+        &operation_id,
+        repo_root,
+        &remote_config.name,
+        "pull",
+        targets,
+    )?;
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "pull");
+
+    let pointers = resolve_pointers(repo_root, targets)?;
+    let failed = pull_outputs(
+        storage.as_ref(),
+        repo_root,
+        &pointer_outputs(&pointers)?,
+        &config,
+        &tracker,
+        &queue,
+        &throttle.download,
+    )?;
+    tracker.emit(true);
+    queue.finish()?;
+    record_metrics(app_handle, repo_root, "dvc_pull", started, &tracker);
+
+    let checkout = checkout::checkout_pointers(repo_root, &pointers, false)?;
+
+    Ok(TransferReport {
+        operation_id,
+        remote: remote_config.name,
+        transferred: tracker.completed.load(Ordering::Relaxed),
+        skipped: tracker.skipped.load(Ordering::Relaxed),
+        failed,
+        transferred_bytes: tracker.bytes(),
+        missing_locally: Vec::new(),
+        checkout: Some(checkout),
+    })
+}
+
+/// Downloads only the files of a directory output under `subpath`, found
+/// through its `.dir` manifest, and checks them out into the workspace
+pub(crate) fn run_pull_subpath(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    dataset: &str,
+    subpath: &str,
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<TransferReport, String> {
+    let started = Instant::now();
+    let pointers = resolve_pointers(repo_root, &[dataset.to_string()])?;
+    let pointer = &pointers[0];
+    let out = dvcfile::read_dvc_file(pointer)?
+        .outs
+        .into_iter()
+        .next()
+        .ok_or_else(|| format!("{} has no outputs", dataset))?;
+    let Some(md5) = out.md5.clone().filter(|_| out.is_dir()) else {
+        return Err(format!(
+            "{} is not a tracked directory; pull it as a whole",
+            dataset
+        ));
+    };
+    let subpath = subpath.replace('\\', "/").trim_matches('/').to_string();
+
+    let remote_config = dvc_config::remote(repo_root, remote)?;
+    let (config, throttle) = transfer_limits(app_handle, repo_root)?;
+    let storage = storage::open_remote(&remote_config, &config)?;
+    let queue = TransferQueue::open(
+        app_handle,
+        &operation_id,
+        repo_root,
+        &remote_config.name,
+        "pull-subpath",
+        &[dataset.to_string(), subpath.clone()],
+    )?;
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "pull");
+
+    let cache_dir = dvcfile::cache_dir(repo_root);
+    let format = dvc_compat::repo_format(repo_root);
+    let manifest = CacheObject {
+        cache_path: dvcfile::cache_object_path_for(&cache_dir, &md5, format),
+        md5: md5.clone(),
+        format,
+    };
+    let mut failed = pull_objects(
+        storage.as_ref(),
+        &[manifest],
+        &config,
+        &tracker,
+        &queue,
+        &throttle.download,
+    )?;
+    if !failed.is_empty() {
+        queue.finish()?;
+        return Err(format!(
+            "Failed to download the manifest of {}: {}",
+            dataset,
+            failed.join(", ")
+        ));
+    }
+
+    let entries: Vec<DirManifestEntry> = dvcfile::read_dir_manifest(&cache_dir, &md5)?
+        .into_iter()
+        .filter(|entry| {
+            subpath.is_empty()
+                || entry.relpath == subpath
+                || entry.relpath.starts_with(&format!("{}/", subpath))
+        })
+        .collect();
+    if entries.is_empty() {
+        queue.finish()?;
+        return Err(format!("{} does not exist in {}", subpath, dataset));
+    }
+
+    let mut seen = HashSet::new();
+    let objects: Vec<CacheObject> = entries
+        .iter()
+        .filter(|entry| seen.insert(entry.md5.clone()))
+        .map(|entry| CacheObject {
+            md5: entry.md5.clone(),
+            cache_path: dvcfile::cache_object_path_for(&cache_dir, &entry.md5, format),
+            format,
+        })
+        .collect();
+    failed.extend(pull_objects(
+        storage.as_ref(),
+        &objects,
+        &config,
+        &tracker,
+        &queue,
+        &throttle.download,
+    )?);
+    tracker.emit(true);
+    queue.finish()?;
+    record_metrics(app_handle, repo_root, "dvc_pull", started, &tracker);
+
+    let data_path = pointer.parent().unwrap_or(repo_root).join(&out.path);
+    let checkout = checkout::checkout_dir_entries(repo_root, &data_path, &entries, false)?;
+
+    Ok(TransferReport {
+        operation_id,
+        remote: remote_config.name,
+        transferred: tracker.completed.load(Ordering::Relaxed),
+        skipped: tracker.skipped.load(Ordering::Relaxed),
+        failed,
+        transferred_bytes: tracker.bytes(),
+        missing_locally: Vec::new(),
+        checkout: Some(checkout),
+    })
+}
+
+/// Downloads the cache objects the pointers of a branch reference, without
+/// touching the workspace
+pub(crate) fn run_prefetch(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    branch: &str,
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<TransferReport, String> {
+    let outputs: Vec<DvcOut> = {
+        let repo =
+            Repository::open(repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+        let tree = branch_compare::branch_tree(&repo, branch)?;
+        branch_compare::tracked_outputs(&repo, &tree)?
+            .into_values()
+            .collect()
+    };
+    prefetch_outputs(
+        app_handle,
+        repo_root,
+        &outputs,
+        &[branch.to_string()],
+        remote,
+        operation_id,
+    )
+}
+
+/// Downloads the cache objects of DVC outputs that aren't cached yet, without
+/// touching the workspace. `targets` only label the transfer job.
+pub(crate) fn prefetch_outputs(
+    app_handle: &AppHandle,
+    repo_root: &Path,
+    outputs: &[DvcOut],
+    targets: &[String],
+    remote: Option<&str>,
+    operation_id: String,
+) -> Result<TransferReport, String> {
+    let started = Instant::now();
+    let remote_config = dvc_config::remote(repo_root, remote)?;
+    let (config, throttle) = transfer_limits(app_handle, repo_root)?;
+    let storage = storage::open_remote(&remote_config, &config)?;
+    let queue = TransferQueue::open(
+        app_handle,
+        &operation_id,
+        repo_root,
+        &remote_config.name,
+        "prefetch",
+        targets,
+    )?;
+    let tracker = ProgressTracker::new(app_handle, &operation_id, "prefetch");
+
+    let failed = pull_outputs(
+        storage.as_ref(),
+        repo_root,
+        outputs,
+        &config,
+        &tracker,
+        &queue,
+        &throttle.download,
+    )?;
+    tracker.emit(true);
+    queue.finish()?;
+    record_metrics(app_handle, repo_root, "dvc_prefetch", started, &tracker);
+
+    Ok(TransferReport {
+        operation_id,
+        remote: remote_config.name,
+        transferred: tracker.completed.load(Ordering::Relaxed),
+        skipped: tracker.skipped.load(Ordering::Relaxed),
+        failed,
+        transferred_bytes: tracker.bytes(),
+        missing_locally: Vec::new(),
+        checkout: None,
+    })
+}
+
+/// Saves the transfer limits of a repository. Rate limits apply to running
+/// transfers immediately, the connection count from the next transfer on.
+#[command]
+#[instrument(skip(app_handle, throttles), err(Debug))]
+pub fn set_transfer_limits(
+    app_handle: AppHandle,
+    throttles: State<'_, TransferThrottleState>,
+    repo_path: String,
+    limits: TransferLimits,
+) -> Result<TransferLimits, String> {
+    if !(1..=MAX_CONCURRENCY).contains(&limits.max_concurrency) {
+        return Err(format!(
+            "Concurrent connections must be between 1 and {}",
+            MAX_CONCURRENCY
+        ));
+    }
+
+    let repo_root = repo_root(&repo_path)?;
+    let saved = settings::update(&app_handle, &repo_root, |settings| {
+        settings.transfer_limits = limits;
+    })?;
+    throttles
+        .lock()
+        .map_err(|_| "Failed to lock transfer throttles".to_string())?
+        .apply(&settings::project_key(&repo_root), &saved.transfer_limits);
+    Ok(saved.transfer_limits)
+}
+
+/// Turns a transfer where every object failed because the remote could not
+/// be reached into an error, so it is queued for when the connection is back
+fn unreachable_as_error(report: TransferReport) -> Result<TransferReport, String> {
+    let unreachable = report.transferred == 0
+        && !report.failed.is_empty()
+        && report.failed.iter().all(|e| offline::is_network_error(e));
+    if unreachable {
+        return Err(format!(
+            "Failed to reach {}: {}",
+            report.remote, report.failed[0]
+        ));
+    }
+    Ok(report)
+}
+
+/// Uploads the cache objects of the targeted (or all) DVC outputs that the
+/// remote does not have yet
+#[command(async)]
+#[instrument(skip(app_handle, repo_path, targets), err(Debug))]
+pub fn dvc_push(
+    app_handle: AppHandle,
+    repo_path: String,
+    targets: Option<Vec<String>>,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferReport, String> {
+    let operation = SyncOperation::DvcPush {
+        targets: targets.clone(),
+        remote: remote.clone(),
+    };
+    offline::queue_when_offline(&app_handle, &repo_path, operation, || {
+        audit::track(
+            &app_handle,
+            &repo_path,
+            "dvc_push",
+            json!({ "targets": targets, "remote": remote }),
+            || {
+                let repo_root = repo_root(&repo_path)?;
+                let report = unreachable_as_error(run_push(
+                    &app_handle,
+                    &repo_root,
+                    &targets.unwrap_or_default(),
+                    remote.as_deref(),
+                    remote::operation_id(operation_id),
+                )?)?;
+                if report.failed.is_empty() && report.transferred > 0 {
+                    let summary = format!(
+                        "Pushed {} data files ({} bytes) to {}",
+                        report.transferred, report.transferred_bytes, report.remote
+                    );
+                    notifications::notify(
+                        &app_handle,
+                        &repo_root,
+                        Notification::new(notifications::PUSH_EVENT, summary)
+                            .field("remote", &report.remote),
+                    );
+                }
+                Ok(report)
+            },
+        )
+    })
+}
+
+/// Downloads missing cache objects of the targeted (or all) DVC outputs and
+/// checks them out into the workspace
+#[command(async)]
+#[instrument(skip(app_handle, repo_path, targets), err(Debug))]
+pub fn dvc_pull(
+    app_handle: AppHandle,
+    repo_path: String,
+    targets: Option<Vec<String>>,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferReport, String> {
+    let operation = SyncOperation::DvcPull {
+        targets: targets.clone(),
+        remote: remote.clone(),
+    };
+    offline::queue_when_offline(&app_handle, &repo_path, operation, || {
+        audit::track(
+            &app_handle,
+            &repo_path,
+            "dvc_pull",
+            json!({ "targets": targets, "remote": remote }),
+            || {
+                unreachable_as_error(run_pull(
+                    &app_handle,
+                    &repo_root(&repo_path)?,
+                    &targets.unwrap_or_default(),
+                    remote.as_deref(),
+                    remote::operation_id(operation_id),
+                )?)
+            },
+        )
+    })
+}
+
+/// Downloads and checks out only part of a tracked directory: the file or
+/// subdirectory at `subpath` inside `dataset`, so one folder of a large
+/// dataset can be inspected without pulling all of it
+#[command(async)]
+#[instrument(skip(app_handle, repo_path), err(Debug))]
+pub fn dvc_pull_subpath(
+    app_handle: AppHandle,
+    repo_path: String,
+    dataset: String,
+    subpath: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<TransferReport, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "dvc_pull_subpath",
+        json!({ "dataset": dataset, "subpath": subpath, "remote": remote }),
+        || {
+            run_pull_subpath(
+                &app_handle,
+                &repo_root(&repo_path)?,
+                &dataset,
+                &subpath,
+                remote.as_deref(),
+                remote::operation_id(operation_id),
+            )
+        },
+    )
+}
+
+/// Downloads the data of another branch into the cache in the background, so
+/// switching to it can check its datasets out without waiting for a pull.
+/// Returns the operation id of the transfer progress events; the outcome is
+/// sent as a `dvc://prefetch-finished` event.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn prefetch_branch_data(
+    app_handle: AppHandle,
+    repo_path: String,
+    branch: String,
+    remote: Option<String>,
+    operation_id: Option<String>,
+) -> Result<String, String> {
+    let repo_root = repo_root(&repo_path)?;
+    // An unknown branch is reported right away rather than in the event
+    {
+        let repo = Repository::open(&repo_root)
+            .map_err(|e| format!("Failed to open repository: {}", e))?;
+        branch_compare::branch_tree(&repo, &branch)?;
+    }
+
+    let operation_id = remote::operation_id(operation_id);
+    let id = operation_id.clone();
+    std::thread::spawn(move || {
+        let result = audit::track(
+            &app_handle,
+            &repo_path,
+            "prefetch_branch_data",
+            json!({ "branch": branch, "remote": remote }),
+            || {
+                run_prefetch(
+                    &app_handle,
+                    &repo_root,
+                    &branch,
+                    remote.as_deref(),
+                    id.clone(),
+                )
+            },
+        );
+        let finished = PrefetchFinished {
+            operation_id: id,
+            branch,
+            error: result.as_ref().err().cloned(),
+            report: result.ok(),
+        };
+        if let Err(e) = app_handle.emit(DVC_PREFETCH_FINISHED_EVENT, finished) {
+            tracing::warn!("Failed to emit prefetch result: {}", e);
+        }
+    });
+    Ok(operation_id)
+}
@@ -0,0 +1,307 @@
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::db::{self, PooledConnection};
+use crate::readonly;
+use crate::transfer::{self, CacheObject, TransferReport};
+
+/// A push or pull that was interrupted or had failures
+#[derive(Debug, Serialize)]
+pub struct PendingTransfer {
+    pub operation_id: String,
+    pub repo_path: String,
+    pub remote: String,
+    /// "push", "pull", "pull-subpath" or "prefetch"
+    pub direction: String,
+    pub targets: Vec<String>,
+    pub completed: u64,
+    pub remaining: u64,
+    pub created_at: String,
+}
+
+fn open_connection(app_handle: &AppHandle) -> Result<PooledConnection<'_>, String> {
+    db::connection(app_handle, db::TRANSFER_JOBS_SCHEMA)
+}
+
+/// Persistent record of the objects of one push/pull, so an interrupted
+/// operation can be resumed without re-transferring finished objects
+pub struct TransferQueue {
+    conn: Mutex<Connection>,
+    operation_id: String,
+}
+
+impl TransferQueue {
+    /// Opens the queue of an operation, registering it if it is new
+    pub fn open(
+        app_handle: &AppHandle,
+        operation_id: &str,
+        repo_root: &Path,
+        remote: &str,
+        direction: &str,
+        targets: &[String],
+    ) -> Result<Self, String> {
+        let conn = db::dedicated_connection(app_handle, db::TRANSFER_JOBS_SCHEMA)?;
+        let targets = serde_json::to_string(targets)
+            .map_err(|e| format!("Failed to encode targets: {}", e))?;
+        conn.execute(
+            "INSERT OR IGNORE INTO transfer_operations (operation_id, repo_path, remote, direction, targets)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![
+                operation_id,
+                repo_root.to_string_lossy(),
+                remote,
+                direction,
+                targets
+            ],
+        )
+        .map_err(|e| format!("Failed to record transfer: {}", e))?;
+
+        Ok(Self {
+            conn: Mutex::new(conn),
+            operation_id: operation_id.to_string(),
+        })
+    }
+
+    /// Queue kept in memory only, for transfers run outside the app
+    #[cfg(any(test, feature = "test-support"))]
+    pub fn in_memory(operation_id: &str) -> Result<Self, String> {
+        let conn = Connection::open_in_memory()
+            .map_err(|e| format!("Failed to open transfer queue: {}", e))?;
+        conn.execute_batch(db::TRANSFER_JOBS_SCHEMA)
+            .map_err(|e| format!("Failed to create transfer queue: {}", e))?;
+        Ok(Self {
+            conn: Mutex::new(conn),
+            operation_id: operation_id.to_string(),
+        })
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>, String> {
+        self.conn
+            .lock()
+            .map_err(|_| "Transfer queue lock poisoned".to_string())
+    }
+
+    /// Adds objects as pending, leaving already known objects untouched
+    pub fn enqueue(&self, objects: &[CacheObject]) -> Result<(), String> {
+        let mut conn = self.lock()?;
+        let tx = conn
+            .transaction()
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        {
+            let mut stmt = tx
+                .prepare(
+                    "INSERT OR IGNORE INTO transfer_queue (operation_id, md5, status)
+                     VALUES (?1, ?2, 'pending')",
+                )
+                .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+            for object in objects {
+                stmt.execute(params![self.operation_id, object.md5])
+                    .map_err(|e| format!("Failed to queue object: {}", e))?;
+            }
+        }
+        tx.commit()
+            .map_err(|e| format!("Failed to commit transfer queue: {}", e))
+    }
+
+    /// Objects finished by an earlier run of this operation
+    pub fn completed(&self) -> Result<HashSet<String>, String> {
+        let conn = self.lock()?;
+        let mut stmt = conn
+            .prepare(
+                "SELECT md5 FROM transfer_queue WHERE operation_id = ?1 AND status = 'completed'",
+            )
+            .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+        let rows = stmt
+            .query_map(params![self.operation_id], |row| row.get::<_, String>(0))
+            .map_err(|e| format!("Failed to read transfer queue: {}", e))?;
+        rows.collect::<Result<HashSet<_>, _>>()
+            .map_err(|e| format!("Failed to read transfer queue: {}", e))
+    }
+
+    pub fn mark_completed(&self, md5: &str) -> Result<(), String> {
+        self.lock()?
+            .execute(
+                "UPDATE transfer_queue SET status = 'completed', attempts = attempts + 1,
+                 last_error = NULL, updated_at = CURRENT_TIMESTAMP
+                 WHERE operation_id = ?1 AND md5 = ?2",
+                params![self.operation_id, md5],
+            )
+            .map_err(|e| format!("Failed to update transfer queue: {}", e))?;
+        Ok(())
+    }
+
+    pub fn mark_failed(&self, md5: &str, error: &str) -> Result<(), String> {
+        self.lock()?
+            .execute(
+                "UPDATE transfer_queue SET status = 'failed', attempts = attempts + 1,
+                 last_error = ?3, updated_at = CURRENT_TIMESTAMP
+                 WHERE operation_id = ?1 AND md5 = ?2",
+                params![self.operation_id, md5, error],
+            )
+            .map_err(|e| format!("Failed to update transfer queue: {}", e))?;
+        Ok(())
+    }
+
+    /// Drops the operation from the queue once every object went through;
+    /// operations with failures stay around to be resumed
+    pub fn finish(&self) -> Result<(), String> {
+        let conn = self.lock()?;
+        let unfinished: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM transfer_queue WHERE operation_id = ?1 AND status != 'completed'",
+                params![self.operation_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| format!("Failed to read transfer queue: {}", e))?;
+        if unfinished == 0 {
+            delete_operations(&conn, &[self.operation_id.clone()])?;
+        }
+        Ok(())
+    }
+}
+
+fn delete_operations(conn: &Connection, operation_ids: &[String]) -> Result<(), String> {
+    for operation_id in operation_ids {
+        conn.execute(
+            "DELETE FROM transfer_queue WHERE operation_id = ?1",
+            params![operation_id],
+        )
+        .map_err(|e| format!("Failed to clear transfer queue: {}", e))?;
+        conn.execute(
+            "DELETE FROM transfer_operations WHERE operation_id = ?1",
+            params![operation_id],
+        )
+        .map_err(|e| format!("Failed to clear transfer queue: {}", e))?;
+    }
+    Ok(())
+}
+
+fn pending_transfers(
+    conn: &Connection,
+    repo_root: Option<&str>,
+) -> Result<Vec<PendingTransfer>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT o.operation_id, o.repo_path, o.remote, o.direction, o.targets, o.created_at,
+                    COALESCE(SUM(q.status = 'completed'), 0),
+                    COALESCE(SUM(q.status != 'completed'), 0)
+             FROM transfer_operations o
+             LEFT JOIN transfer_queue q ON q.operation_id = o.operation_id
+             WHERE ?1 IS NULL OR o.repo_path = ?1
+             GROUP BY o.operation_id
+             ORDER BY o.created_at",
+        )
+        .map_err(|e| format!("Failed to prepare statement: {}", e))?;
+
+    let rows = stmt
+        .query_map(params![repo_root], |row| {
+            let targets: String = row.get(4)?;
+            Ok(PendingTransfer {
+                operation_id: row.get(0)?,
+                repo_path: row.get(1)?,
+                remote: row.get(2)?,
+                direction: row.get(3)?,
+                targets: serde_json::from_str(&targets).unwrap_or_default(),
+                created_at: row.get(5)?,
+                completed: row.get(6)?,
+                remaining: row.get(7)?,
+            })
+        })
+        .map_err(|e| format!("Failed to read transfer queue: {}", e))?;
+
+    rows.collect::<Result<Vec<_>, _>>()
+        .map_err(|e| format!("Failed to read transfer queue: {}", e))
+}
+
+fn normalized_root(repo_path: Option<String>) -> Result<Option<String>, String> {
+    repo_path
+        .map(|path| transfer::repo_root(&path).map(|root| root.to_string_lossy().to_string()))
+        .transpose()
+}
+
+/// Lists interrupted or partially failed transfers, for one repository or all of them
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_pending_transfers(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Vec<PendingTransfer>, String> {
+    let repo_root = normalized_root(repo_path)?;
+    let conn = open_connection(&app_handle)?;
+    pending_transfers(&conn, repo_root.as_deref())
+}
+
+/// Re-runs interrupted or partially failed pushes and pulls, skipping the
+/// objects they already transferred. Transfers of projects open read-only
+/// stay queued.
+#[command(async)]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn resume_transfers(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<Vec<TransferReport>, String> {
+    let repo_root = normalized_root(repo_path)?;
+    let pending = {
+        let conn = open_connection(&app_handle)?;
+        pending_transfers(&conn, repo_root.as_deref())?
+    };
+
+    let mut reports = Vec::new();
+    for transfer in pending {
+        let root = Path::new(&transfer.repo_path);
+        if readonly::is_read_only(&app_handle, root) {
+            continue;
+        }
+        let report = match transfer.direction.as_str() {
+            "push" => transfer::run_push(
+                &app_handle,
+                root,
+                &transfer.targets,
+                Some(&transfer.remote),
+                transfer.operation_id,
+            ),
+            "pull-subpath" if transfer.targets.len() == 2 => transfer::run_pull_subpath(
+                &app_handle,
+                root,
+                &transfer.targets[0],
+                &transfer.targets[1],
+                Some(&transfer.remote),
+                transfer.operation_id,
+            ),
+            _ => transfer::run_pull(
+                &app_handle,
+                root,
+                &transfer.targets,
+                Some(&transfer.remote),
+                transfer.operation_id,
+            ),
+        };
+        match report {
+            Ok(report) => reports.push(report),
+            Err(e) => tracing::warn!("Failed to resume transfer: {}", e),
+        }
+    }
+    Ok(reports)
+}
+
+/// Forgets queued transfers, for one repository or all of them
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn clear_transfer_queue(
+    app_handle: AppHandle,
+    repo_path: Option<String>,
+) -> Result<usize, String> {
+    let repo_root = normalized_root(repo_path)?;
+    let conn = open_connection(&app_handle)?;
+    let operation_ids: Vec<String> = pending_transfers(&conn, repo_root.as_deref())?
+        .into_iter()
+        .map(|transfer| transfer.operation_id)
+        .collect();
+    delete_operations(&conn, &operation_ids)?;
+    Ok(operation_ids.len())
+}
@@ -0,0 +1,270 @@
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle, Manager, Runtime};
+use tracing::{instrument, warn};
+
+use crate::{audit, checkout, dvc, dvcfile, repo_manager, transfer};
+
+/// Directory in the app data dir holding deleted data, one folder per entry
+const TRASH_DIR: &str = "trash";
+const ENTRY_FILE: &str = "entry.json";
+const DATA_DIR: &str = "data";
+
+/// Data deleted from a repository, kept so it can be put back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    pub id: String,
+    pub repo_root: String,
+    /// Data path relative to the repository root
+    pub path: String,
+    /// Content of the `.dvc` pointer at deletion
+    pub pointer: String,
+    pub is_directory: bool,
+    /// RFC 3339 time of the deletion
+    pub deleted_at: String,
+}
+
+fn trash_dir<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
+    app_handle
+        .path()
+        .app_data_dir()
+        .map(|dir| dir.join(TRASH_DIR))
+        .map_err(|e| format!("Failed to get app data directory: {}", e))
+}
+
+/// Folder of a trash entry; ids are uuids, anything else is refused
+fn entry_dir<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Result<PathBuf, String> {
+    uuid::Uuid::parse_str(id).map_err(|_| format!("{} is not a trash entry", id))?;
+    Ok(trash_dir(app_handle)?.join(id))
+}
+
+/// Repository a trash entry would be restored into
+pub(crate) fn entry_repo_root<R: Runtime>(app_handle: &AppHandle<R>, id: &str) -> Option<String> {
+    let entry = read_entry(&entry_dir(app_handle, id).ok()?).ok()?;
+    Some(entry.repo_root)
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> Result<(), String> {
+    if from.is_dir() {
+        fs::create_dir_all(to).map_err(|e| format!("Failed to create {}: {}", to.display(), e))?;
+        let entries =
+            fs::read_dir(from).map_err(|e| format!("Failed to read {}: {}", from.display(), e))?;
+        for entry in entries.flatten() {
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+        Ok(())
+    } else {
+        fs::copy(from, to)
+            .map(|_| ())
+            .map_err(|e| format!("Failed to copy {}: {}", from.display(), e))
+    }
+}
+
+/// Renames, or copies and deletes when the trash is on another filesystem
+fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    if fs::rename(from, to).is_ok() {
+        return Ok(());
+    }
+    copy_recursive(from, to)?;
+    let removed = if from.is_dir() {
+        fs::remove_dir_all(from)
+    } else {
+        fs::remove_file(from)
+    };
+    removed.map_err(|e| format!("Failed to remove {}: {}", from.display(), e))
+}
+
+fn read_entry(dir: &Path) -> Result<TrashEntry, String> {
+    let content = fs::read_to_string(dir.join(ENTRY_FILE))
+        .map_err(|e| format!("Failed to read trash entry: {}", e))?;
+    serde_json::from_str(&content).map_err(|e| format!("Failed to parse trash entry: {}", e))
+}
+
+/// Moves the data into the trash, then untracks it like `remove_dvc_file`.
+/// The data is moved back when untracking fails.
+fn trash(app_handle: &AppHandle, path: &str) -> Result<TrashEntry, String> {
+    let data_path = Path::new(path);
+    let repo = Repository::discover(data_path)
+        .map_err(|e| format!("Failed to open git repository: {}", e))?;
+    let repo_root = repo
+        .workdir()
+        .ok_or("Repository has no working directory")?
+        .to_path_buf();
+    let relative = dvcfile::repo_relative_path(&repo_root, data_path)?;
+    let absolute = repo_root.join(&relative);
+    let pointer_path = dvcfile::pointer_path(&absolute);
+    let pointer = fs::read_to_string(&pointer_path)
+        .map_err(|_| format!("{} is not tracked by DVC", relative.display()))?;
+    let file_name = absolute
+        .file_name()
+        .ok_or_else(|| format!("Invalid data path: {}", path))?
+        .to_os_string();
+
+    let entry = TrashEntry {
+        id: uuid::Uuid::new_v4().to_string(),
+        repo_root: repo_root.to_string_lossy().to_string(),
+        path: dvcfile::to_git_path(&relative),
+        pointer,
+        is_directory: absolute.is_dir(),
+        deleted_at: chrono::Utc::now().to_rfc3339(),
+    };
+    let entry_dir = trash_dir(app_handle)?.join(&entry.id);
+    let data_dir = entry_dir.join(DATA_DIR);
+    fs::create_dir_all(&data_dir)
+        .map_err(|e| format!("Failed to create {}: {}", data_dir.display(), e))?;
+    let content = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize trash entry: {}", e))?;
+    fs::write(entry_dir.join(ENTRY_FILE), content)
+        .map_err(|e| format!("Failed to write trash entry: {}", e))?;
+
+    let trashed = data_dir.join(&file_name);
+    if absolute.exists() {
+        move_path(&absolute, &trashed)?;
+    }
+    if let Err(e) = dvc::remove_tracking(
+        &repo_root.to_string_lossy(),
+        &dvcfile::to_git_path(&relative),
+        false,
+    ) {
+        if trashed.exists() {
+            if let Err(restore) = move_path(&trashed, &absolute) {
+                warn!("Failed to put {} back: {}", absolute.display(), restore);
+                return Err(format!("{} (the data is kept in the trash)", e));
+            }
+        }
+        let _ = fs::remove_dir_all(&entry_dir);
+        return Err(e);
+    }
+    Ok(entry)
+}
+
+/// Puts trashed data back, rewrites its pointer and ignore entry and stages
+/// them. Data that was already missing when trashed is restored from the
+/// cache with a checkout.
+fn restore(app_handle: &AppHandle, id: &str) -> Result<TrashEntry, String> {
+    let entry_dir = entry_dir(app_handle, id)?;
+    let entry = read_entry(&entry_dir)?;
+    let repo_root = PathBuf::from(&entry.repo_root);
+    let relative = PathBuf::from(&entry.path);
+    let absolute = repo_root.join(&relative);
+    let pointer_path = dvcfile::pointer_path(&absolute);
+    if absolute.exists() || pointer_path.exists() {
+        return Err(format!(
+            "{} exists again; move it away to restore the deleted version",
+            entry.path
+        ));
+    }
+
+    let file_name = absolute
+        .file_name()
+        .ok_or_else(|| format!("Invalid data path: {}", entry.path))?;
+    let trashed = entry_dir.join(DATA_DIR).join(file_name);
+    if let Some(parent) = absolute.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let in_trash = trashed.exists();
+    if in_trash {
+        move_path(&trashed, &absolute)?;
+    }
+    fs::write(&pointer_path, &entry.pointer)
+        .map_err(|e| format!("Failed to write {}: {}", pointer_path.display(), e))?;
+    let gitignore = dvcfile::add_gitignore_entry(&absolute)?;
+    if !in_trash {
+        checkout::checkout_pointers(&repo_root, std::slice::from_ref(&pointer_path), false)?;
+    }
+
+    let repo =
+        Repository::open(&repo_root).map_err(|e| format!("Failed to open repository: {}", e))?;
+    let mut index = repo
+        .index()
+        .map_err(|e| format!("Failed to get repository index: {}", e))?;
+    for path in [pointer_path, gitignore] {
+        let staged = dvcfile::repo_relative_path(&repo_root, &path)?;
+        index
+            .add_path(&staged)
+            .map_err(|e| format!("Failed to add {} to index: {}", staged.display(), e))?;
+    }
+    index
+        .write()
+        .map_err(|e| format!("Failed to write index: {}", e))?;
+
+    if let Err(e) = fs::remove_dir_all(&entry_dir) {
+        warn!("Failed to remove trash entry {}: {}", id, e);
+    }
+    Ok(entry)
+}
+
+/// Deletes DVC-tracked data: its pointer and ignore entry are removed and
+/// staged, and the data goes to the app's trash when `to_trash` is set (so
+/// `restore_from_trash` can bring it back) or is deleted otherwise
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn delete_tracked_file(
+    app_handle: AppHandle,
+    path: String,
+    to_trash: bool,
+) -> Result<Option<TrashEntry>, String> {
+    audit::track(
+        &app_handle,
+        &path,
+        "delete_tracked_file",
+        json!({ "path": path, "to_trash": to_trash }),
+        || {
+            repo_manager::write(&app_handle, &path, || {
+                if to_trash {
+                    trash(&app_handle, &path).map(Some)
+                } else {
+                    let repo_root = transfer::repo_root(&path)?;
+                    dvc::remove_tracking(&repo_root.to_string_lossy(), &path, false).map(|_| None)
+                }
+            })
+        },
+    )
+}
+
+/// Data in the trash, most recently deleted first
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn list_trash(app_handle: AppHandle) -> Result<Vec<TrashEntry>, String> {
+    let Ok(dirs) = fs::read_dir(trash_dir(&app_handle)?) else {
+        return Ok(Vec::new());
+    };
+    let mut entries: Vec<TrashEntry> = dirs
+        .flatten()
+        .filter_map(|dir| read_entry(&dir.path()).ok())
+        .collect();
+    entries.sort_by(|a, b| b.deleted_at.cmp(&a.deleted_at));
+    Ok(entries)
+}
+
+/// Moves trashed data back into its repository and tracks it again
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn restore_from_trash(app_handle: AppHandle, id: String) -> Result<TrashEntry, String> {
+    let entry = read_entry(&entry_dir(&app_handle, &id)?)?;
+    audit::track(
+        &app_handle,
+        &entry.repo_root,
+        "restore_from_trash",
+        json!({ "id": id, "path": entry.path }),
+        || repo_manager::write(&app_handle, &entry.repo_root, || restore(&app_handle, &id)),
+    )
+}
+
+/// Permanently deletes one trash entry, or all of them when `id` is `None`
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn empty_trash(app_handle: AppHandle, id: Option<String>) -> Result<(), String> {
+    let target = match &id {
+        Some(id) => entry_dir(&app_handle, id)?,
+        None => trash_dir(&app_handle)?,
+    };
+    if !target.exists() {
+        return Ok(());
+    }
+    fs::remove_dir_all(&target).map_err(|e| format!("Failed to empty trash: {}", e))
+}
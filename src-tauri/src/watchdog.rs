@@ -0,0 +1,506 @@
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::fmt;
+use std::io::{BufRead, BufReader, Read};
+use std::path::Path;
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{command, AppHandle};
+use tracing::{instrument, warn};
+
+use crate::{settings, transfer};
+
+/// How often a running process is checked for exit, timeout or output limit
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_TIMEOUT_SECS: u64 = 24 * 60 * 60;
+
+/// Limits for the external processes the app runs (DVC scripts, Python),
+/// stored with the project settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WatchdogSettings {
+    pub default_timeout_secs: u64,
+    /// Timeouts by process name, e.g. "dvc_diff_script"
+    pub timeouts: HashMap<String, u64>,
+    /// Combined stdout and stderr a process may write before it is killed
+    pub max_output_mib: u64,
+    /// Address space limit (Unix only); `None` is unlimited
+    pub max_memory_mib: Option<u64>,
+}
+
+impl Default for WatchdogSettings {
+    fn default() -> Self {
+        Self {
+            default_timeout_secs: 300,
+            timeouts: [
+                ("dvc_diff_script", 120),
+                ("dvc_add_script", 3600),
+                ("python_probe", 30),
+                ("pip_install", 1800),
+            ]
+            .into_iter()
+            .map(|(name, secs)| (name.to_string(), secs))
+            .collect(),
+            max_output_mib: 64,
+            max_memory_mib: None,
+        }
+    }
+}
+
+impl WatchdogSettings {
+    pub fn limits(&self, name: &str) -> Limits {
+        let timeout = self
+            .timeouts
+            .get(name)
+            .copied()
+            .unwrap_or(self.default_timeout_secs);
+        Limits {
+            timeout: Duration::from_secs(timeout),
+            max_output_bytes: self.max_output_mib * 1024 * 1024,
+            max_memory_mib: self.max_memory_mib,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Limits {
+    pub timeout: Duration,
+    pub max_output_bytes: u64,
+    pub max_memory_mib: Option<u64>,
+}
+
+#[derive(Debug)]
+pub enum ProcessError {
+    /// The process could not be started
+    Spawn {
+        name: String,
+        error: String,
+    },
+    /// The process ran longer than its timeout and was killed
+    Timeout {
+        name: String,
+        timeout: Duration,
+    },
+    /// The process wrote more output than allowed and was killed
+    OutputLimit {
+        name: String,
+        limit_bytes: u64,
+    },
+    Wait {
+        name: String,
+        error: String,
+    },
+}
+
+impl fmt::Display for ProcessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProcessError::Spawn { name, error } => write!(f, "Failed to run {}: {}", name, error),
+            ProcessError::Timeout { name, timeout } => write!(
+                f,
+                "{} timed out after {} seconds and was stopped",
+                name,
+                timeout.as_secs()
+            ),
+            ProcessError::OutputLimit { name, limit_bytes } => write!(
+                f,
+                "{} wrote more than {} MiB of output and was stopped",
+                name,
+                limit_bytes / (1024 * 1024)
+            ),
+            ProcessError::Wait { name, error } => {
+                write!(f, "Failed to wait for {}: {}", name, error)
+            }
+        }
+    }
+}
+
+impl ProcessError {
+    pub fn kind(&self) -> ProcessErrorKind {
+        match self {
+            ProcessError::Spawn { .. } => ProcessErrorKind::Spawn,
+            ProcessError::Timeout { .. } => ProcessErrorKind::Timeout,
+            ProcessError::OutputLimit { .. } => ProcessErrorKind::OutputLimit,
+            ProcessError::Wait { .. } => ProcessErrorKind::Wait,
+        }
+    }
+}
+
+impl From<ProcessError> for String {
+    fn from(error: ProcessError) -> Self {
+        error.to_string()
+    }
+}
+
+/// Why a process failed, for the frontend to tell e.g. a timeout from a
+/// script that could not be started
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ProcessErrorKind {
+    Spawn,
+    Timeout,
+    OutputLimit,
+    Wait,
+}
+
+/// Error of a command that runs external processes. `kind` is set when one
+/// of them failed, so the frontend need not match on the message.
+#[derive(Debug, Serialize)]
+pub struct CommandError {
+    pub kind: Option<ProcessErrorKind>,
+    pub message: String,
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for CommandError {
+    fn from(message: String) -> Self {
+        Self {
+            kind: None,
+            message,
+        }
+    }
+}
+
+impl From<ProcessError> for CommandError {
+    fn from(error: ProcessError) -> Self {
+        Self {
+            kind: Some(error.kind()),
+            message: error.to_string(),
+        }
+    }
+}
+
+impl From<CommandError> for String {
+    fn from(error: CommandError) -> Self {
+        error.message
+    }
+}
+
+/// Carries the kind of a process failure past wrappers that pass errors on as
+/// strings, such as `audit::track` and `repo_manager::write`
+#[derive(Debug, Default)]
+pub(crate) struct FailureKind(Cell<Option<ProcessErrorKind>>);
+
+impl FailureKind {
+    /// Flattens an error to its message, remembering its kind
+    pub fn record(&self, error: CommandError) -> String {
+        if error.kind.is_some() {
+            self.0.set(error.kind);
+        }
+        error.message
+    }
+
+    /// The error to return once the message made it through the wrappers
+    pub fn error(&self, message: String) -> CommandError {
+        CommandError {
+            kind: self.0.get(),
+            message,
+        }
+    }
+}
+
+/// Limits of a project's processes named `name`, falling back to the defaults
+/// when its settings can't be read
+pub(crate) fn limits(app_handle: &AppHandle, repo_root: &Path, name: &str) -> Limits {
+    match settings::load(app_handle, repo_root) {
+        Ok(settings) => settings.watchdog.limits(name),
+        Err(e) => {
+            warn!("Failed to load watchdog settings: {}", e);
+            WatchdogSettings::default().limits(name)
+        }
+    }
+}
+
+/// Starts the process in its own process group with the memory limit
+/// applied, so it and everything it started can be killed together
+#[cfg(unix)]
+fn isolate(command: &mut Command, limits: &Limits) {
+    use std::os::unix::process::CommandExt;
+    command.process_group(0);
+    if let Some(mib) = limits.max_memory_mib {
+        let bytes = (mib * 1024 * 1024) as libc::rlim_t;
+        // Only async-signal-safe calls are allowed between fork and exec
+        unsafe {
+            command.pre_exec(move || {
+                let limit = libc::rlimit {
+                    rlim_cur: bytes,
+                    rlim_max: bytes,
+                };
+                if libc::setrlimit(libc::RLIMIT_AS, &limit) != 0 {
+                    return Err(std::io::Error::last_os_error());
+                }
+                Ok(())
+            });
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn isolate(_command: &mut Command, _limits: &Limits) {}
+
+/// Kills the process and, on Unix, the rest of its process group, then reaps
+/// it so no zombie is left behind
+fn kill(child: &mut Child, name: &str) {
+    #[cfg(unix)]
+    {
+        // The group id is the child's pid since it leads its own group
+        if unsafe { libc::killpg(child.id() as libc::pid_t, libc::SIGKILL) } == 0 {
+            let _ = child.wait();
+            return;
+        }
+    }
+    if let Err(e) = child.kill() {
+        warn!("Failed to kill {}: {}", name, e);
+    }
+    let _ = child.wait();
+}
+
+/// Collects a process stream, counting its bytes against the shared limit
+fn collect(
+    mut reader: impl Read + Send + 'static,
+    written: Arc<AtomicU64>,
+    limit: u64,
+    exceeded: Arc<AtomicBool>,
+) -> thread::JoinHandle<Vec<u8>> {
+    thread::spawn(move || {
+        let mut collected = Vec::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) | Err(_) => break,
+                Ok(read) => read,
+            };
+            let total = written.fetch_add(read as u64, Ordering::Relaxed) + read as u64;
+            if total > limit {
+                exceeded.store(true, Ordering::Relaxed);
+                break;
+            }
+            collected.extend_from_slice(&buffer[..read]);
+        }
+        collected
+    })
+}
+
+/// Receives a line of output with the stream it came from, "stdout" or "stderr"
+type LineFn = Arc<dyn Fn(&'static str, String) + Send + Sync>;
+
+/// Hands each line of a process stream to `on_line`, counting its bytes
+/// against the shared limit
+fn forward_lines(
+    reader: impl Read + Send + 'static,
+    stream: &'static str,
+    on_line: LineFn,
+    written: Arc<AtomicU64>,
+    limit: u64,
+    exceeded: Arc<AtomicBool>,
+) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for line in BufReader::new(reader).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            let size = line.len() as u64 + 1;
+            let total = written.fetch_add(size, Ordering::Relaxed) + size;
+            if total > limit {
+                exceeded.store(true, Ordering::Relaxed);
+                break;
+            }
+            on_line(stream, line);
+        }
+    })
+}
+
+fn spawn(command: &mut Command, name: &str, limits: &Limits) -> Result<Child, ProcessError> {
+    isolate(command, limits);
+    command
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| ProcessError::Spawn {
+            name: name.to_string(),
+            error: e.to_string(),
+        })
+}
+
+/// Waits for the process to exit, killing it when it runs past its timeout
+/// or a reader reports that its output went over the limit
+fn wait(
+    child: &mut Child,
+    name: &str,
+    limits: &Limits,
+    exceeded: &AtomicBool,
+) -> Result<ExitStatus, ProcessError> {
+    let started = Instant::now();
+    loop {
+        if exceeded.load(Ordering::Relaxed) {
+            kill(child, name);
+            return Err(ProcessError::OutputLimit {
+                name: name.to_string(),
+                limit_bytes: limits.max_output_bytes,
+            });
+        }
+        match child.try_wait() {
+            Ok(Some(status)) => return Ok(status),
+            Ok(None) if started.elapsed() >= limits.timeout => {
+                kill(child, name);
+                warn!(
+                    name,
+                    timeout_secs = limits.timeout.as_secs(),
+                    "Process timed out"
+                );
+                // The readers finish once every process holding the pipes
+                // is gone; they aren't waited for in case one survived
+                return Err(ProcessError::Timeout {
+                    name: name.to_string(),
+                    timeout: limits.timeout,
+                });
+            }
+            Ok(None) => thread::sleep(WAIT_POLL_INTERVAL),
+            Err(e) => {
+                kill(child, name);
+                return Err(ProcessError::Wait {
+                    name: name.to_string(),
+                    error: e.to_string(),
+                });
+            }
+        }
+    }
+}
+
+/// Runs a command to completion like `Command::output`, but kills it (and
+/// the processes it started) when it exceeds its timeout or output limit
+pub(crate) fn output(
+    command: &mut Command,
+    name: &str,
+    limits: &Limits,
+) -> Result<Output, ProcessError> {
+    let mut child = spawn(command, name, limits)?;
+
+    let written = Arc::new(AtomicU64::new(0));
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let stdout = child.stdout.take().map(|stream| {
+        collect(
+            stream,
+            written.clone(),
+            limits.max_output_bytes,
+            exceeded.clone(),
+        )
+    });
+    let stderr = child.stderr.take().map(|stream| {
+        collect(
+            stream,
+            written.clone(),
+            limits.max_output_bytes,
+            exceeded.clone(),
+        )
+    });
+
+    let status = wait(&mut child, name, limits, &exceeded)?;
+
+    let stdout = stdout
+        .map(|reader| reader.join().unwrap_or_default())
+        .unwrap_or_default();
+    let stderr = stderr
+        .map(|reader| reader.join().unwrap_or_default())
+        .unwrap_or_default();
+    if exceeded.load(Ordering::Relaxed) {
+        return Err(ProcessError::OutputLimit {
+            name: name.to_string(),
+            limit_bytes: limits.max_output_bytes,
+        });
+    }
+    Ok(Output {
+        status,
+        stdout,
+        stderr,
+    })
+}
+
+/// Like `output`, but hands every line of stdout and stderr to `on_line` as
+/// the process writes it instead of collecting them
+pub(crate) fn stream(
+    command: &mut Command,
+    name: &str,
+    limits: &Limits,
+    on_line: impl Fn(&'static str, String) + Send + Sync + 'static,
+) -> Result<ExitStatus, ProcessError> {
+    let mut child = spawn(command, name, limits)?;
+
+    let on_line: LineFn = Arc::new(on_line);
+    let written = Arc::new(AtomicU64::new(0));
+    let exceeded = Arc::new(AtomicBool::new(false));
+    let mut readers = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        readers.push(forward_lines(
+            stdout,
+            "stdout",
+            on_line.clone(),
+            written.clone(),
+            limits.max_output_bytes,
+            exceeded.clone(),
+        ));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        readers.push(forward_lines(
+            stderr,
+            "stderr",
+            on_line,
+            written.clone(),
+            limits.max_output_bytes,
+            exceeded.clone(),
+        ));
+    }
+
+    let status = wait(&mut child, name, limits, &exceeded)?;
+
+    // Let the readers forward what is left in the pipes
+    for reader in readers {
+        let _ = reader.join();
+    }
+    if exceeded.load(Ordering::Relaxed) {
+        return Err(ProcessError::OutputLimit {
+            name: name.to_string(),
+            limit_bytes: limits.max_output_bytes,
+        });
+    }
+    Ok(status)
+}
+
+/// Saves the timeouts and limits of the external processes run for a project
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn set_watchdog_settings(
+    app_handle: AppHandle,
+    repo_path: String,
+    watchdog: WatchdogSettings,
+) -> Result<WatchdogSettings, String> {
+    let timeouts = watchdog
+        .timeouts
+        .values()
+        .chain([&watchdog.default_timeout_secs]);
+    for timeout in timeouts {
+        if !(1..=MAX_TIMEOUT_SECS).contains(timeout) {
+            return Err(format!(
+                "Timeouts must be between 1 and {} seconds",
+                MAX_TIMEOUT_SECS
+            ));
+        }
+    }
+    if watchdog.max_output_mib == 0 || watchdog.max_memory_mib == Some(0) {
+        return Err("Output and memory limits must be above zero".to_string());
+    }
+    let saved = settings::update(&app_handle, &transfer::repo_root(&repo_path)?, |settings| {
+        settings.watchdog = watchdog;
+    })?;
+    Ok(saved.watchdog)
+}
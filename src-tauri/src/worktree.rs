@@ -0,0 +1,281 @@
+use git2::{
+    BranchType, Repository, StatusOptions, WorktreeAddOptions, WorktreeLockStatus,
+    WorktreePruneOptions,
+};
+use serde::Serialize;
+use serde_json::json;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::{command, AppHandle};
+use tracing::instrument;
+
+use crate::checkout::{self, CheckoutSummary};
+use crate::dvc_config::{self, DvcConfig};
+use crate::{audit, dvcfile};
+
+#[derive(Debug, Serialize)]
+pub struct WorktreeInfo {
+    /// Name git stores the worktree under; `None` for the main worktree
+    pub name: Option<String>,
+    pub path: String,
+    /// Checked out branch, `None` when HEAD is detached
+    pub branch: Option<String>,
+    pub head: Option<String>,
+    pub is_main: bool,
+    pub is_locked: bool,
+    /// False when the worktree's directory is gone and it can be pruned
+    pub is_valid: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorktreeAddResult {
+    pub worktree: WorktreeInfo,
+    /// Cache directory the new worktree shares with the main one, if the
+    /// repository uses DVC
+    pub shared_cache_dir: Option<String>,
+    pub checkout: Option<CheckoutSummary>,
+}
+
+fn open_repo(repo_path: &str) -> Result<Repository, String> {
+    Repository::discover(repo_path).map_err(|e| format!("Failed to open repository: {}", e))
+}
+
+/// Working directory of the main worktree, also when opened from a linked one
+fn main_root(repo: &Repository) -> Result<PathBuf, String> {
+    repo.commondir()
+        .parent()
+        .map(Path::to_path_buf)
+        .ok_or_else(|| "Repository has no working directory".to_string())
+}
+
+fn info(repo: &Repository, name: Option<&str>, is_main: bool) -> WorktreeInfo {
+    let head = repo.head().ok();
+    WorktreeInfo {
+        name: name.map(str::to_string),
+        path: repo
+            .workdir()
+            .map(|dir| dir.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        branch: head
+            .as_ref()
+            .filter(|head| head.is_branch())
+            .and_then(|head| head.shorthand())
+            .map(str::to_string),
+        head: head
+            .as_ref()
+            .and_then(|head| head.target())
+            .map(|id| id.to_string()),
+        is_main,
+        is_locked: false,
+        is_valid: true,
+    }
+}
+
+fn list(repo: &Repository) -> Result<Vec<WorktreeInfo>, String> {
+    let main = Repository::open(main_root(repo)?)
+        .map_err(|e| format!("Failed to open main worktree: {}", e))?;
+    let mut worktrees = vec![info(&main, None, true)];
+
+    let names = main
+        .worktrees()
+        .map_err(|e| format!("Failed to list worktrees: {}", e))?;
+    for name in names.iter().flatten() {
+        let worktree = main
+            .find_worktree(name)
+            .map_err(|e| format!("Failed to find worktree {}: {}", name, e))?;
+        let is_locked = matches!(worktree.is_locked(), Ok(WorktreeLockStatus::Locked(_)));
+
+        let mut entry = match worktree.validate() {
+            Ok(()) => Repository::open_from_worktree(&worktree)
+                .map(|repo| info(&repo, Some(name), false))
+                .map_err(|e| format!("Failed to open worktree {}: {}", name, e))?,
+            Err(_) => WorktreeInfo {
+                name: Some(name.to_string()),
+                path: worktree.path().to_string_lossy().to_string(),
+                branch: None,
+                head: None,
+                is_main: false,
+                is_locked: false,
+                is_valid: false,
+            },
+        };
+        entry.is_locked = is_locked;
+        worktrees.push(entry);
+    }
+    Ok(worktrees)
+}
+
+/// Points a new worktree's DVC cache at the main worktree's cache through its
+/// untracked `config.local`, so data is linked rather than downloaded again
+fn share_cache(main_root: &Path, worktree_root: &Path) -> Result<Option<PathBuf>, String> {
+    if !main_root.join(".dvc").is_dir() || !worktree_root.join(".dvc").is_dir() {
+        return Ok(None);
+    }
+
+    let cache_dir = dvcfile::cache_dir(main_root);
+    fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Failed to create {}: {}", cache_dir.display(), e))?;
+    let cache_dir = cache_dir
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", cache_dir.display(), e))?;
+
+    let config_path = dvc_config::local_config_path(worktree_root);
+    let mut config = DvcConfig::read(&config_path)?;
+    config.set("cache", "dir", &cache_dir.to_string_lossy());
+    config.write(&config_path)?;
+    Ok(Some(cache_dir))
+}
+
+fn add(repo_path: &str, branch: &str, path: &str) -> Result<WorktreeAddResult, String> {
+    let repo = open_repo(repo_path)?;
+    let main_root = main_root(&repo)?;
+    let target = PathBuf::from(path);
+    if target.exists() {
+        return Err(format!("{} already exists", path));
+    }
+    let name = target
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or("The worktree path needs a directory name")?;
+
+    // A branch that doesn't exist yet starts from the current commit
+    let branch_ref = match repo.find_branch(branch, BranchType::Local) {
+        Ok(existing) => existing,
+        Err(_) => {
+            let head = repo
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .map_err(|e| format!("Failed to get HEAD commit: {}", e))?;
+            repo.branch(branch, &head, false)
+                .map_err(|e| format!("Failed to create branch {}: {}", branch, e))?
+        }
+    };
+
+    let mut options = WorktreeAddOptions::new();
+    options.reference(Some(branch_ref.get()));
+    let worktree = repo
+        .worktree(&name, &target, Some(&options))
+        .map_err(|e| format!("Failed to add worktree: {}", e))?;
+    let worktree_repo = Repository::open_from_worktree(&worktree)
+        .map_err(|e| format!("Failed to open worktree: {}", e))?;
+
+    let shared_cache_dir = share_cache(&main_root, &target)?;
+    let checkout = match shared_cache_dir {
+        Some(_) => Some(checkout::checkout_pointers(
+            &target,
+            &dvcfile::find_pointer_files(&target),
+            false,
+        )?),
+        None => None,
+    };
+
+    Ok(WorktreeAddResult {
+        worktree: info(&worktree_repo, Some(&name), false),
+        shared_cache_dir: shared_cache_dir.map(|dir| dir.to_string_lossy().to_string()),
+        checkout,
+    })
+}
+
+fn remove(repo_path: &str, worktree: &str, force: bool) -> Result<(), String> {
+    let repo = open_repo(repo_path)?;
+    let main = Repository::open(main_root(&repo)?)
+        .map_err(|e| format!("Failed to open main worktree: {}", e))?;
+
+    // Accept either the worktree's name or its path
+    let names = main
+        .worktrees()
+        .map_err(|e| format!("Failed to list worktrees: {}", e))?;
+    let found = names
+        .iter()
+        .flatten()
+        .filter_map(|name| main.find_worktree(name).ok())
+        .find(|wt| {
+            wt.name() == Some(worktree)
+                || wt.path() == Path::new(worktree)
+                || same_dir(wt.path(), Path::new(worktree))
+        })
+        .ok_or_else(|| format!("{} is not a worktree of this repository", worktree))?;
+
+    if found.validate().is_ok() && !force {
+        let worktree_repo = Repository::open_from_worktree(&found)
+            .map_err(|e| format!("Failed to open worktree: {}", e))?;
+        let mut status_opts = StatusOptions::new();
+        status_opts.include_untracked(true);
+        let dirty = !worktree_repo
+            .statuses(Some(&mut status_opts))
+            .map_err(|e| format!("Failed to get status: {}", e))?
+            .is_empty();
+        if dirty {
+            return Err(format!(
+                "{} has uncommitted changes; force the removal to discard them",
+                worktree
+            ));
+        }
+        if let Ok(WorktreeLockStatus::Locked(reason)) = found.is_locked() {
+            return Err(format!(
+                "{} is locked{}",
+                worktree,
+                reason.map(|r| format!(": {}", r)).unwrap_or_default()
+            ));
+        }
+    }
+
+    let mut options = WorktreePruneOptions::new();
+    options.valid(true).locked(force).working_tree(true);
+    found
+        .prune(Some(&mut options))
+        .map_err(|e| format!("Failed to remove worktree: {}", e))
+}
+
+fn same_dir(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Checks out `branch` into a new working directory at `path`, creating the
+/// branch from HEAD when needed. The new worktree shares the DVC cache of the
+/// main one and its data is checked out from there.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn git_worktree_add(
+    app_handle: AppHandle,
+    repo_path: String,
+    branch: String,
+    path: String,
+) -> Result<WorktreeAddResult, String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_worktree_add",
+        json!({ "branch": branch, "path": path }),
+        || add(&repo_path, &branch, &path),
+    )
+}
+
+/// The main worktree followed by the linked ones
+#[command]
+#[instrument(err(Debug))]
+pub fn git_worktree_list(repo_path: String) -> Result<Vec<WorktreeInfo>, String> {
+    list(&open_repo(&repo_path)?)
+}
+
+/// Deletes a linked worktree, given by name or path. Worktrees with
+/// uncommitted changes or a lock are only removed when `force` is set.
+#[command]
+#[instrument(skip(app_handle), err(Debug))]
+pub fn git_worktree_remove(
+    app_handle: AppHandle,
+    repo_path: String,
+    worktree: String,
+    force: bool,
+) -> Result<(), String> {
+    audit::track(
+        &app_handle,
+        &repo_path,
+        "git_worktree_remove",
+        json!({ "worktree": worktree, "force": force }),
+        || remove(&repo_path, &worktree, force),
+    )
+}
@@ -222,10 +222,14 @@ export const FileTree = forwardRef<FileTreeHandle, FileTreeProps>(
         path: initialPath,
       });
       try {
-        const result = await invoke<FileEntry[]>("get_file_tree_structure", {
+        const result = await invoke<{
+          entries: FileEntry[];
+          stale: boolean;
+          indexed_at: string | null;
+        }>("get_file_tree_structure", {
           path: initialPath,
         });
-        const treeStructure = buildFileTree(result);
+        const treeStructure = buildFileTree(result.entries);
         setTree(treeStructure);
       } catch (error) {
         alert("Error loading file tree: " + error);
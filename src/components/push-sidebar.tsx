@@ -20,6 +20,21 @@ import {
 } from "@/lib/analytics";
 import { FileTreeHandle } from "./file-tree";
 
+interface HookViolation {
+  hook: string;
+  path: string | null;
+  line: number | null;
+  rule: string | null;
+  message: string;
+}
+
+interface CommitResult {
+  success: boolean;
+  message: string;
+  commit_id: string | null;
+  violations: HookViolation[];
+}
+
 export function PushSidebar({
   open,
   onOpenChange,
@@ -36,6 +51,7 @@ export function PushSidebar({
   const [summary, setSummary] = React.useState("");
   const [description, setDescription] = React.useState("");
   const [error, setError] = React.useState<string | null>(null);
+  const [violations, setViolations] = React.useState<HookViolation[]>([]);
   const [loading, setLoading] = React.useState(false);
 
   const canPush = summary.trim().length > 0;
@@ -49,13 +65,25 @@ export function PushSidebar({
     });
 
     setError(null);
+    setViolations([]);
     setLoading(true);
     try {
-      await invoke<string>("git_commit_and_push", {
+      const result = await invoke<CommitResult>("git_commit_and_push", {
         repoPath,
         summary,
         description,
       });
+      // Blocked by the pre-commit checks: keep the sheet open with the reasons
+      if (!result.success) {
+        setError(result.message);
+        setViolations(result.violations);
+        trackError(new Error(result.message), {
+          operation: "git_commit_and_push",
+          repoPath,
+          fileCount: stagedFiles.length,
+        });
+        return;
+      }
       setSummary("");
       setDescription("");
       onOpenChange(false);
@@ -131,6 +159,26 @@ export function PushSidebar({
               rows={3}
             />
           </div>
+          {violations.length > 0 && (
+            <div>
+              <div className="font-medium mb-2 text-destructive">
+                Pre-commit checks failed
+              </div>
+              <ul className="list-disc pl-4 text-xs max-h-40 overflow-auto">
+                {violations.map((violation, i) => (
+                  <li key={i}>
+                    {violation.path && (
+                      <span className="font-mono">
+                        {violation.path}
+                        {violation.line !== null && `:${violation.line}`}{" "}
+                      </span>
+                    )}
+                    {violation.message}
+                  </li>
+                ))}
+              </ul>
+            </div>
+          )}
         </div>
         <SheetFooter>
           <Button
@@ -140,7 +140,7 @@ export function TopBar({
     }
     setBranching(true);
     try {
-      await invoke<string>("git_checkout", {
+      await invoke<{ message: string }>("git_checkout", {
         repoPath,
         branch: branchName.trim(),
       });
@@ -158,7 +158,7 @@ export function TopBar({
     if (targetBranch === branch) return;
     setSwitchingBranch(targetBranch);
     try {
-      await invoke<string>("git_switch_branch", {
+      await invoke<{ message: string }>("git_switch_branch", {
         repoPath,
         branch: targetBranch,
       });
@@ -104,10 +104,15 @@ function Index() {
             );
           }
         } catch (error) {
-          alert(error);
+          // DVC commands reject with { kind, message }, kind naming a
+          // process failure such as "timeout"
+          const message =
+            typeof error === "object" && error !== null && "message" in error
+              ? String(error.message)
+              : String(error);
+          alert(message);
           toast.error("Failed to add files", {
-            description:
-              error instanceof Error ? error.message : "Unknown error occurred",
+            description: message,
           });
         }
         break;